@@ -0,0 +1,21 @@
+// Node.js bindings for nectarflower-rs, built with napi-rs.
+//
+// Build the addon first with `npm run build`, which places
+// nectarflower.node next to this file.
+
+'use strict';
+
+const native = require('./nectarflower.node');
+
+// Block streams are async iterators: `for await (const block of client.streamBlocks(n))`
+native.BlockStream.prototype[Symbol.asyncIterator] = async function* blocks() {
+  for (;;) {
+    const block = await this.nextBlock();
+    if (block === null) {
+      return;
+    }
+    yield block;
+  }
+};
+
+module.exports = native;
@@ -0,0 +1,248 @@
+//! Node.js bindings for nectarflower-rs
+//!
+//! Build with `npm run build` from this directory. Network calls and block
+//! waits run on the libuv thread pool and resolve as promises, so they never
+//! block the event loop.
+
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Error, JsUnknown, Result, Task};
+use napi_derive::napi;
+use nectarflower_rs::block::BlockStream as RustBlockStream;
+use nectarflower_rs::transaction::TransactionSigner;
+use nectarflower_rs::{ChainConfig, Client as RustClient, SecretWif, Transaction};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+fn error(e: impl ToString) -> Error {
+    Error::from_reason(e.to_string())
+}
+
+fn transaction(tx: Value) -> Result<Transaction> {
+    serde_json::from_value(tx).map_err(|e| error(format!("Invalid transaction: {e}")))
+}
+
+/// The main network, or the chain with id `chain_id`
+fn chain(chain_id: Option<String>) -> ChainConfig {
+    match chain_id {
+        Some(chain_id) => ChainConfig::new("custom", &chain_id),
+        None => ChainConfig::mainnet(),
+    }
+}
+
+/// A client reading from `nodes`
+fn client_with(
+    nodes: Vec<String>,
+    failing_nodes: HashMap<nectarflower_rs::Url, nectarflower_rs::NodeFailure>,
+) -> Result<RustClient> {
+    let mut client = RustClient::new();
+    client.set_nodes(nodes, failing_nodes).map_err(error)?;
+    Ok(client)
+}
+
+/// The client calls are made with, replaced whenever the nodes change
+type Shared = Arc<Mutex<Arc<RustClient>>>;
+
+fn current(shared: &Shared) -> Arc<RustClient> {
+    Arc::clone(&shared.lock().unwrap())
+}
+
+/// A read-only Hive client
+///
+/// Changing the nodes replaces the underlying client, so calls in flight and
+/// block streams already started keep using the nodes they started with.
+#[napi]
+pub struct Client {
+    inner: Shared,
+}
+
+#[napi]
+impl Client {
+    #[napi(constructor)]
+    pub fn new(nodes: Option<Vec<String>>) -> Result<Self> {
+        let inner = match nodes {
+            Some(nodes) => client_with(nodes, HashMap::new())?,
+            None => RustClient::new(),
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Arc::new(inner))),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn nodes(&self) -> Vec<String> {
+        current(&self.inner).nodes.clone()
+    }
+
+    #[napi(getter)]
+    pub fn chain_id(&self) -> String {
+        current(&self.inner).chain().chain_id.clone()
+    }
+
+    #[napi]
+    pub fn set_nodes(&self, nodes: Vec<String>) -> Result<()> {
+        *self.inner.lock().unwrap() = Arc::new(client_with(nodes, HashMap::new())?);
+        Ok(())
+    }
+
+    /// Read the node list published in `account`'s metadata
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn update_nodes_from_account(&self, account: Option<String>) -> AsyncTask<UpdateNodes> {
+        AsyncTask::new(UpdateNodes {
+            inner: Arc::clone(&self.inner),
+            account: account.unwrap_or_else(|| "nectarflower".to_string()),
+        })
+    }
+
+    /// Make a JSON-RPC call; broadcast APIs are refused
+    #[napi(ts_return_type = "Promise<any>")]
+    pub fn call(&self, method: String, params: Option<Value>) -> AsyncTask<Call> {
+        AsyncTask::new(Call {
+            client: current(&self.inner),
+            method,
+            params: params.unwrap_or_else(|| Value::Object(Default::default())),
+        })
+    }
+
+    /// Blocks from `start`, waiting for new blocks at the head
+    #[napi]
+    pub fn stream_blocks(&self, start: u32) -> BlockStream {
+        let client = current(&self.inner);
+        // SAFETY: the stream borrows the client behind `client`, which the
+        // state owns and drops after the stream.
+        let stream = unsafe { &*Arc::as_ptr(&client) }.stream_blocks(start);
+        BlockStream {
+            state: Arc::new(Mutex::new(StreamState {
+                stream,
+                _client: client,
+            })),
+        }
+    }
+}
+
+pub struct UpdateNodes {
+    inner: Shared,
+    account: String,
+}
+
+impl Task for UpdateNodes {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<()> {
+        let data = current(&self.inner)
+            .get_nodes_from_account(&self.account)
+            .map_err(error)?;
+        *self.inner.lock().unwrap() = Arc::new(client_with(data.nodes, data.failing_nodes)?);
+        Ok(())
+    }
+
+    fn resolve(&mut self, _env: Env, _output: ()) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct Call {
+    client: Arc<RustClient>,
+    method: String,
+    params: Value,
+}
+
+impl Task for Call {
+    type Output = Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> Result<Value> {
+        self.client
+            .call(&self.method, self.params.take())
+            .map_err(error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Value) -> Result<JsUnknown> {
+        env.to_js_value(&output)
+    }
+}
+
+struct StreamState {
+    // Declared first so it is dropped before the client it borrows
+    stream: RustBlockStream<'static>,
+    _client: Arc<RustClient>,
+}
+
+/// Blocks from [`Client::stream_blocks`]; `index.js` makes it an async iterator
+#[napi]
+pub struct BlockStream {
+    state: Arc<Mutex<StreamState>>,
+}
+
+#[napi]
+impl BlockStream {
+    /// The next block, or `null` once the stream ends
+    #[napi(ts_return_type = "Promise<any>")]
+    pub fn next_block(&self) -> AsyncTask<NextBlock> {
+        AsyncTask::new(NextBlock {
+            state: Arc::clone(&self.state),
+        })
+    }
+}
+
+pub struct NextBlock {
+    state: Arc<Mutex<StreamState>>,
+}
+
+impl Task for NextBlock {
+    type Output = Option<Value>;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> Result<Option<Value>> {
+        let mut state = self.state.lock().unwrap();
+        match state.stream.next() {
+            Some(block) => {
+                let block = block.map_err(error)?;
+                serde_json::to_value(block).map(Some).map_err(error)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn resolve(&mut self, env: Env, output: Option<Value>) -> Result<JsUnknown> {
+        env.to_js_value(&output)
+    }
+}
+
+/// The transaction id of `tx` on the main network, or on `chainId`
+#[napi]
+pub fn transaction_id(tx: Value, chain_id: Option<String>) -> Result<String> {
+    transaction(tx)?.id_for(&chain(chain_id)).map_err(error)
+}
+
+/// The digest to sign for `tx` on the main network, or on `chainId`
+#[napi]
+pub fn transaction_digest(tx: Value, chain_id: Option<String>) -> Result<Buffer> {
+    let digest = transaction(tx)?.digest(&chain(chain_id)).map_err(error)?;
+    Ok(digest.to_vec().into())
+}
+
+/// `tx` signed with each WIF key in `keys` for the main network, or for `chainId`
+///
+/// The digest is computed by the Rust serializer, so a transaction with an
+/// operation it does not cover is an error rather than signed over a node's
+/// bytes.
+#[napi]
+pub fn sign_transaction(tx: Value, keys: Vec<String>, chain_id: Option<String>) -> Result<Value> {
+    let mut tx = transaction(tx)?;
+    let digest = tx.digest(&chain(chain_id)).map_err(error)?;
+    for wif in keys {
+        let key = SecretWif::parse(&wif).map_err(error)?;
+        tx.signatures.push(key.sign_digest(&digest).map_err(error)?);
+    }
+    serde_json::to_value(tx).map_err(error)
+}
+
+/// The public key of a WIF private key, with the address prefix `prefix`, `STM` by default
+#[napi]
+pub fn public_key(wif: String, prefix: Option<String>) -> Result<String> {
+    SecretWif::parse(&wif)
+        .and_then(|key| key.public_key(prefix.as_deref().unwrap_or("STM")))
+        .map_err(error)
+}
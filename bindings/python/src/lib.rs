@@ -0,0 +1,230 @@
+//! Python bindings for nectarflower-rs
+//!
+//! Build with `maturin develop --release` from this directory. Values cross
+//! the boundary as JSON, so calls take and return plain dicts and lists, and
+//! network waits release the GIL.
+
+use nectarflower_rs::block::BlockStream as RustBlockStream;
+use nectarflower_rs::transaction::TransactionSigner;
+use nectarflower_rs::{ChainConfig, Client as RustClient, SecretWif, Transaction};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+create_exception!(nectarflower, NectarflowerError, PyException);
+
+fn error(e: impl ToString) -> PyErr {
+    NectarflowerError::new_err(e.to_string())
+}
+
+/// `obj` encoded with Python's `json` module
+fn to_json(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<String> {
+    py.import("json")?.call_method1("dumps", (obj,))?.extract()
+}
+
+/// `json` decoded with Python's `json` module
+fn from_json(py: Python<'_>, json: &str) -> PyResult<PyObject> {
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn transaction(py: Python<'_>, tx: &Bound<'_, PyAny>) -> PyResult<Transaction> {
+    serde_json::from_str(&to_json(py, tx)?).map_err(|e| error(format!("Invalid transaction: {e}")))
+}
+
+/// The main network, or the chain with id `chain_id`
+fn chain(chain_id: Option<&str>) -> ChainConfig {
+    match chain_id {
+        Some(chain_id) => ChainConfig::new("custom", chain_id),
+        None => ChainConfig::mainnet(),
+    }
+}
+
+/// A client reading from `nodes`
+fn client_with(
+    nodes: Vec<String>,
+    failing_nodes: HashMap<nectarflower_rs::Url, nectarflower_rs::NodeFailure>,
+) -> PyResult<RustClient> {
+    let mut client = RustClient::new();
+    client.set_nodes(nodes, failing_nodes).map_err(error)?;
+    Ok(client)
+}
+
+/// A read-only Hive client
+///
+/// Changing the nodes replaces the underlying client, so block streams
+/// already started keep reading from the nodes they started with.
+#[pyclass(module = "nectarflower")]
+struct Client {
+    inner: Arc<RustClient>,
+}
+
+#[pymethods]
+impl Client {
+    #[new]
+    #[pyo3(signature = (nodes=None))]
+    fn new(nodes: Option<Vec<String>>) -> PyResult<Self> {
+        let inner = match nodes {
+            Some(nodes) => client_with(nodes, HashMap::new())?,
+            None => RustClient::new(),
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    #[getter]
+    fn nodes(&self) -> Vec<String> {
+        self.inner.nodes.clone()
+    }
+
+    #[getter]
+    fn chain_id(&self) -> String {
+        self.inner.chain().chain_id.clone()
+    }
+
+    fn set_nodes(&mut self, nodes: Vec<String>) -> PyResult<()> {
+        self.inner = Arc::new(client_with(nodes, HashMap::new())?);
+        Ok(())
+    }
+
+    /// Read the node list published in `account`'s metadata
+    #[pyo3(signature = (account="nectarflower"))]
+    fn update_nodes_from_account(&mut self, py: Python<'_>, account: &str) -> PyResult<()> {
+        let inner = &self.inner;
+        let data = py
+            .allow_threads(|| inner.get_nodes_from_account(account))
+            .map_err(error)?;
+        self.inner = Arc::new(client_with(data.nodes, data.failing_nodes)?);
+        Ok(())
+    }
+
+    /// Make a JSON-RPC call; broadcast APIs are refused
+    #[pyo3(signature = (method, params=None))]
+    fn call(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let params: Value = match params {
+            Some(params) => serde_json::from_str(&to_json(py, params)?).map_err(error)?,
+            None => Value::Object(Default::default()),
+        };
+        let inner = &self.inner;
+        let result: Value = py
+            .allow_threads(|| inner.call(method, params))
+            .map_err(error)?;
+        from_json(py, &result.to_string())
+    }
+
+    /// Iterate over blocks from `start`, waiting for new blocks at the head
+    fn stream_blocks(&self, start: u32) -> BlockStream {
+        let client = Arc::clone(&self.inner);
+        // SAFETY: the stream borrows the client behind `client`, which the
+        // returned object owns and drops after the stream.
+        let stream = unsafe { &*Arc::as_ptr(&client) }.stream_blocks(start);
+        BlockStream {
+            stream,
+            _client: client,
+        }
+    }
+}
+
+/// Blocks from [`Client::stream_blocks`]
+#[pyclass(module = "nectarflower")]
+struct BlockStream {
+    // Declared first so it is dropped before the client it borrows
+    stream: RustBlockStream<'static>,
+    _client: Arc<RustClient>,
+}
+
+#[pymethods]
+impl BlockStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let stream = &mut self.stream;
+        match py.allow_threads(|| stream.next()) {
+            Some(block) => {
+                let block = block.map_err(error)?;
+                let json = serde_json::to_string(&block).map_err(error)?;
+                from_json(py, &json).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The transaction id of `tx` on the main network, or on `chain_id`
+#[pyfunction]
+#[pyo3(signature = (tx, chain_id=None))]
+fn transaction_id(
+    py: Python<'_>,
+    tx: &Bound<'_, PyAny>,
+    chain_id: Option<&str>,
+) -> PyResult<String> {
+    transaction(py, tx)?.id_for(&chain(chain_id)).map_err(error)
+}
+
+/// The digest to sign for `tx` on the main network, or on `chain_id`
+#[pyfunction]
+#[pyo3(signature = (tx, chain_id=None))]
+fn transaction_digest<'py>(
+    py: Python<'py>,
+    tx: &Bound<'py, PyAny>,
+    chain_id: Option<&str>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let digest = transaction(py, tx)?
+        .digest(&chain(chain_id))
+        .map_err(error)?;
+    Ok(PyBytes::new(py, &digest))
+}
+
+/// `tx` signed with each WIF key in `keys` for the main network, or for `chain_id`
+///
+/// The digest is computed by the Rust serializer, so a transaction with an
+/// operation it does not cover is an error rather than signed over a node's
+/// bytes.
+#[pyfunction]
+#[pyo3(signature = (tx, keys, chain_id=None))]
+fn sign_transaction(
+    py: Python<'_>,
+    tx: &Bound<'_, PyAny>,
+    keys: Vec<String>,
+    chain_id: Option<&str>,
+) -> PyResult<PyObject> {
+    let mut tx = transaction(py, tx)?;
+    let digest = tx.digest(&chain(chain_id)).map_err(error)?;
+    for wif in keys {
+        let key = SecretWif::parse(&wif).map_err(error)?;
+        tx.signatures.push(key.sign_digest(&digest).map_err(error)?);
+    }
+    from_json(py, &serde_json::to_string(&tx).map_err(error)?)
+}
+
+/// The public key of a WIF private key, with the address prefix `prefix`
+#[pyfunction]
+#[pyo3(signature = (wif, prefix="STM"))]
+fn public_key(wif: &str, prefix: &str) -> PyResult<String> {
+    SecretWif::parse(wif)
+        .and_then(|key| key.public_key(prefix))
+        .map_err(error)
+}
+
+#[pymodule]
+fn nectarflower(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("NectarflowerError", m.py().get_type::<NectarflowerError>())?;
+    m.add_class::<Client>()?;
+    m.add_class::<BlockStream>()?;
+    m.add_function(wrap_pyfunction!(transaction_id, m)?)?;
+    m.add_function(wrap_pyfunction!(transaction_digest, m)?)?;
+    m.add_function(wrap_pyfunction!(sign_transaction, m)?)?;
+    m.add_function(wrap_pyfunction!(public_key, m)?)?;
+    Ok(())
+}
@@ -1,5 +1,5 @@
 //! Example usage for nectarflower-rs
-use nectarflower_rs::Client;
+use nectarflower_rs::{params, Client};
 use serde_json::Value;
 
 fn main() {
@@ -26,7 +26,11 @@ fn main() {
 
             // Update client with new nodes
             println!("\nUpdating client with new nodes...");
-            client.set_nodes(node_data.nodes.clone(), node_data.failing_nodes.clone());
+            if let Err(e) =
+                client.set_nodes(node_data.nodes.clone(), node_data.failing_nodes.clone())
+            {
+                eprintln!("Error updating nodes: {e}");
+            }
             println!("Updated client initialized with: {:?}", client.nodes);
         }
         Err(e) => {
@@ -61,8 +65,8 @@ fn main() {
     println!("\nDemonstrating the all-in-one UpdateNodesFromAccount function...");
     let mut new_client = Client::new();
     match new_client.update_nodes_from_account(account_name) {
-        Ok(()) => println!(
-            "One-step update complete. Client initialized with: {:?}",
+        Ok(change) => println!(
+            "One-step update complete ({change}). Client initialized with: {:?}",
             new_client.nodes
         ),
         Err(e) => {
@@ -87,8 +91,8 @@ fn main() {
         println!("Fetching block #{}", target_block_num);
 
         // Create parameters for get_block method
-        let block_params = serde_json::json!({
-            "block_num": target_block_num
+        let block_params = params!(get_block {
+            block_num: target_block_num
         });
 
         // Fetch the block
@@ -132,7 +136,7 @@ fn main() {
                             let tx_ids =
                                 block_data.get("transaction_ids").and_then(|v| v.as_array());
                             let tx_id = tx_ids
-                                .and_then(|ids| ids.get(0))
+                                .and_then(|ids| ids.first())
                                 .and_then(|id| id.as_str())
                                 .unwrap_or("unknown");
 
@@ -140,7 +144,7 @@ fn main() {
                             println!("  Transaction ID: {}", tx_id);
 
                             // Pretty print the first transaction
-                            if let Some(tx) = transactions.get(0) {
+                            if let Some(tx) = transactions.first() {
                                 let tx_json = serde_json::to_string_pretty(tx)
                                     .unwrap_or_else(|_| "Error formatting transaction".to_string());
                                 println!("  Transaction data:\n{}", tx_json);
@@ -0,0 +1,112 @@
+//! Capture golden signing vectors from mainnet blocks
+//!
+//! Scans `<first_block>..=<last_block>` for a transaction carrying each
+//! operation the local serializer supports and prints them in the format of
+//! `fixtures/signing_vectors.json`. A transaction is only kept when its local
+//! id matches the id the chain recorded, the node's `get_transaction_hex`
+//! matches the local bytes, and every signature recovers, with the local
+//! digest, to a key that could sign the transaction now.
+//!
+//! cargo run --example capture_signing_vectors -- 90000000 90001000
+
+use nectarflower_rs::crypto::{encode_public_key, recover_public_key, to_hex};
+use nectarflower_rs::serializer::supported_operations;
+use nectarflower_rs::{Client, Transaction, HIVE_CHAIN_ID};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+fn main() {
+    let args: Vec<u32> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("block numbers"))
+        .collect();
+    let [first, last] = args[..] else {
+        eprintln!("usage: capture_signing_vectors <first_block> <last_block>");
+        std::process::exit(2);
+    };
+    let client = Client::new();
+    let mut wanted: BTreeSet<&str> = supported_operations().collect();
+    let mut vectors = Vec::new();
+    for block_num in first..=last {
+        if wanted.is_empty() {
+            break;
+        }
+        let block = match client.get_block(block_num) {
+            Ok(Some(block)) => block,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        for (trx, trx_id) in block.transactions.iter().zip(&block.transaction_ids) {
+            let Ok(tx) = serde_json::from_value::<Transaction>(trx.clone()) else {
+                continue;
+            };
+            let Some(op) = tx
+                .operations
+                .iter()
+                .map(|op| op.name())
+                .find(|name| wanted.contains(name))
+            else {
+                continue;
+            };
+            match capture(&client, &tx, trx_id, block_num) {
+                Ok(vector) => {
+                    eprintln!("{op}: {trx_id} in block {block_num}");
+                    let op = op.to_string();
+                    wanted.remove(op.as_str());
+                    vectors.push(vector);
+                }
+                Err(e) => eprintln!("{trx_id} in block {block_num} rejected: {e}"),
+            }
+        }
+    }
+    for op in wanted {
+        eprintln!("No {op} transaction found");
+    }
+    println!("{}", serde_json::to_string_pretty(&vectors).unwrap());
+}
+
+/// A vector for `tx`, after checking it against the chain and the node
+fn capture(
+    client: &Client,
+    tx: &Transaction,
+    trx_id: &str,
+    block_num: u32,
+) -> Result<Value, String> {
+    let hex = to_hex(&tx.to_bytes()?);
+    let id = tx.id()?;
+    if id != trx_id {
+        return Err(format!("local id {id}"));
+    }
+    client.verify_transaction_bytes(tx)?;
+    let digest = tx.digest(client.chain())?;
+    let signing_keys = tx
+        .signatures
+        .iter()
+        .map(|signature| {
+            recover_public_key(&digest, signature).map(|key| encode_public_key(&key, "STM"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let potential = client.get_potential_signatures(tx)?;
+    if let Some(key) = signing_keys.iter().find(|key| !potential.contains(key)) {
+        return Err(format!("signature recovers to {key}, which cannot sign it"));
+    }
+    let name = tx
+        .operations
+        .iter()
+        .map(|op| op.name())
+        .collect::<Vec<_>>()
+        .join(" + ");
+    Ok(json!({
+        "name": format!("mainnet {name}"),
+        "transaction": tx,
+        "chain_id": HIVE_CHAIN_ID,
+        "hex": hex,
+        "digest": to_hex(&digest),
+        "id": id,
+        "block_num": block_num,
+        "signing_keys": signing_keys,
+    }))
+}
@@ -0,0 +1,148 @@
+/* C API of nectarflower-rs, generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef NECTARFLOWER_H
+#define NECTARFLOWER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+// Version of this C API, bumped on incompatible changes
+#define NF_API_VERSION 1
+
+// Length of a hex-encoded compact signature, excluding the NUL terminator
+#define NF_SIGNATURE_HEX_LEN 130
+
+// A read-only Hive client; wrap it in a [`WriteClient`] to broadcast
+typedef struct NfClient NfClient;
+
+// A block stream created by [`nf_stream_new`]
+typedef struct NfBlockStream NfBlockStream;
+
+// Signs a 32-byte digest, writing a NUL-terminated hex signature of
+// [`NF_SIGNATURE_HEX_LEN`] characters into `out`; returns 0 on success
+typedef int32_t (*NfSignCallback)(const uint8_t *digest, char *out, size_t out_len, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The version of this C API
+uint32_t nf_api_version(void);
+
+// The last error on this thread, or NULL; valid until the next failing call
+const char *nf_last_error(void);
+
+// Release a string returned by this library
+//
+// # Safety
+// `s` must be NULL or a pointer returned by an `nf_*` function, released only once.
+void nf_string_free(char *s);
+
+// Create a client using the default node
+NfClient *nf_client_new(void);
+
+// Destroy a client
+//
+// # Safety
+// `client` must be NULL or a pointer from [`nf_client_new`], freed only once.
+void nf_client_free(NfClient *client);
+
+// Replace the client's nodes with a JSON array of URLs
+//
+// # Safety
+// `client` must be a live client and `nodes_json` a valid C string.
+int32_t nf_client_set_nodes(NfClient *client, const char *nodes_json);
+
+// Load the node list published in an account's metadata
+//
+// # Safety
+// `client` must be a live client and `account` a valid C string.
+int32_t nf_client_update_nodes_from_account(NfClient *client, const char *account);
+
+// Make a JSON-RPC call, returning the result as a JSON string
+//
+// # Safety
+// `client` must be a live client; `method` and `params_json` valid C strings.
+char *nf_call(const NfClient *client, const char *method, const char *params_json);
+
+// The id of a JSON transaction, computed locally
+//
+// # Safety
+// `tx_json` must be a valid C string.
+char *nf_transaction_id(const char *tx_json);
+
+// The hex digest to sign for a JSON transaction on `chain_id`, computed locally
+//
+// # Safety
+// `tx_json` and `chain_id` must be valid C strings.
+char *nf_transaction_digest(const char *tx_json, const char *chain_id);
+
+// Sign a JSON transaction with a callback, returning the signed transaction as JSON
+//
+// The digest is always computed locally, so a transaction with an operation
+// the local serializer does not cover is an error. `client` may be NULL for
+// offline signing on `chain_id`; otherwise the client's chain is used and
+// `chain_id` may be NULL.
+//
+// # Safety
+// `client` must be NULL or a live client; `tx_json` a valid C string and
+// `chain_id` NULL or a valid C string.
+char *nf_sign_transaction(const NfClient *client,
+                          const char *tx_json,
+                          const char *chain_id,
+                          NfSignCallback callback,
+                          void *user_data);
+
+// Sign a JSON transaction with a WIF private key, returning the signed transaction as JSON
+//
+// As [`nf_sign_transaction`], with the signature made by the library; the
+// key is wiped from the library's memory once the call returns.
+//
+// # Safety
+// `client` must be NULL or a live client; `tx_json` and `wif` valid C strings
+// and `chain_id` NULL or a valid C string.
+char *nf_sign_transaction_with_key(const NfClient *client,
+                                   const char *tx_json,
+                                   const char *chain_id,
+                                   const char *wif);
+
+// The public key of a WIF private key, with the address prefix `prefix` such as `STM`
+//
+// # Safety
+// `wif` and `prefix` must be valid C strings.
+char *nf_public_key(const char *wif, const char *prefix);
+
+// Stream blocks starting at `start`
+//
+// # Safety
+// `client` must be a live client that outlives the returned stream.
+NfBlockStream *nf_stream_new(const NfClient *client, uint32_t start);
+
+// The next block as JSON, waiting for it to be produced if necessary
+//
+// # Safety
+// `stream` must be a live stream from [`nf_stream_new`].
+char *nf_stream_next(NfBlockStream *stream);
+
+// The error of the last failed [`nf_stream_next`] on `stream`, or NULL
+//
+// Unlike [`nf_last_error`] this can be read from a thread other than the one
+// that called [`nf_stream_next`].
+//
+// # Safety
+// `stream` must be a live stream from [`nf_stream_new`].
+const char *nf_stream_error(const NfBlockStream *stream);
+
+// Destroy a block stream
+//
+// # Safety
+// `stream` must be NULL or a pointer from [`nf_stream_new`], freed only once.
+void nf_stream_free(NfBlockStream *stream);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NECTARFLOWER_H */
@@ -0,0 +1,283 @@
+//! Common abuse signals for a post and its author
+//!
+//! [`AbuseAnalyzer`] combines the votes on a post with the author's recent
+//! history: how much of the post's vote value is the author's own, how much
+//! comes from accounts the author votes for in return, which beneficiaries
+//! keep recurring on the author's posts, and which blacklists include them.
+//! The [`AbuseSignals::score`] is a heuristic to rank posts for review, not
+//! a verdict.
+
+use crate::history::HistoryEntry;
+use crate::op_filter::OpTypeSet;
+use crate::operation::Operation;
+use crate::posts::Beneficiary;
+use crate::votes::Vote;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Operations of the author's history read by default
+pub const DEFAULT_HISTORY_DEPTH: usize = 2_000;
+
+/// Posts needed before beneficiary patterns are reported
+const MIN_POSTS_FOR_PATTERNS: usize = 3;
+
+/// A beneficiary on a large share of an author's recent posts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecurringBeneficiary {
+    pub account: String,
+    /// Posts with this beneficiary
+    pub posts: usize,
+    /// Share of the author's posts with beneficiaries set, 0 to 1
+    pub share: f64,
+}
+
+/// Abuse signals for one post, each share from 0 to 1
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AbuseSignals {
+    pub author: String,
+    pub permlink: String,
+    /// Share of the post's upvote rshares cast by the author
+    pub self_vote_share: f64,
+    /// Share of the author's recent upvotes that went to their own content
+    pub account_self_vote_share: f64,
+    /// Share of the post's upvote rshares from other accounts the author recently upvoted
+    pub vote_circle_share: f64,
+    /// Voters the author also upvoted, by upvote rshares on this post
+    pub circle_voters: Vec<String>,
+    pub recurring_beneficiaries: Vec<RecurringBeneficiary>,
+    /// Names of the configured blacklists that include the author
+    pub blacklisted_on: Vec<String>,
+}
+
+impl AbuseSignals {
+    /// Weighted score from 0 (no signal) to 100
+    ///
+    /// Self votes and vote circles weigh 30 each, the account's self-vote
+    /// habit 20, and recurring beneficiaries and blacklisting 10 each.
+    pub fn score(&self) -> f64 {
+        let beneficiaries = self
+            .recurring_beneficiaries
+            .iter()
+            .map(|b| b.share)
+            .fold(0.0, f64::max);
+        let blacklisted = if self.blacklisted_on.is_empty() {
+            0.0
+        } else {
+            1.0
+        };
+        30.0 * self.self_vote_share
+            + 20.0 * self.account_self_vote_share
+            + 30.0 * self.vote_circle_share
+            + 10.0 * beneficiaries
+            + 10.0 * blacklisted
+    }
+}
+
+/// Computes [`AbuseSignals`] with configurable blacklists and thresholds
+#[derive(Debug, Clone)]
+pub struct AbuseAnalyzer {
+    blacklists: BTreeMap<String, HashSet<String>>,
+    history_depth: usize,
+    recurring_share: f64,
+}
+
+impl Default for AbuseAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AbuseAnalyzer {
+    /// Read 2000 history operations and report beneficiaries on half the posts or more
+    pub fn new() -> Self {
+        Self {
+            blacklists: BTreeMap::new(),
+            history_depth: DEFAULT_HISTORY_DEPTH,
+            recurring_share: 0.5,
+        }
+    }
+
+    /// Check authors against a named blacklist
+    pub fn blacklist<I, S>(mut self, name: &str, accounts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.blacklists
+            .entry(name.to_string())
+            .or_default()
+            .extend(accounts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Number of the author's most recent vote and comment option operations to read
+    pub fn history_depth(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self
+    }
+
+    /// Share of posts a beneficiary must be on to be reported, 0 to 1
+    pub fn recurring_share(mut self, share: f64) -> Self {
+        self.recurring_share = share;
+        self
+    }
+
+    /// Compute the signals of a post from its votes and the author's history
+    ///
+    /// `history` should hold the author's `vote` and `comment_options`
+    /// operations; other entries are ignored.
+    pub fn analyze(
+        &self,
+        author: &str,
+        permlink: &str,
+        votes: &[Vote],
+        history: &[HistoryEntry],
+    ) -> AbuseSignals {
+        let mut upvoted: HashSet<&str> = HashSet::new();
+        let (mut own_upvotes, mut all_upvotes) = (0usize, 0usize);
+        let mut beneficiary_posts: HashMap<String, usize> = HashMap::new();
+        let mut posts_with_beneficiaries = 0;
+        for entry in history {
+            let op = &entry.op;
+            match op.name() {
+                "vote" if op.str_field("voter") == Some(author) => {
+                    if op.value.get("weight").and_then(|w| w.as_i64()) <= Some(0) {
+                        continue;
+                    }
+                    all_upvotes += 1;
+                    match op.str_field("author") {
+                        Some(a) if a == author => own_upvotes += 1,
+                        Some(a) => {
+                            upvoted.insert(a);
+                        }
+                        None => {}
+                    }
+                }
+                "comment_options" if op.str_field("author") == Some(author) => {
+                    let beneficiaries = beneficiaries(op);
+                    if beneficiaries.is_empty() {
+                        continue;
+                    }
+                    posts_with_beneficiaries += 1;
+                    for b in beneficiaries {
+                        *beneficiary_posts.entry(b.account).or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let upvotes: Vec<&Vote> = votes.iter().filter(|v| v.rshares > 0).collect();
+        let total: i64 = upvotes.iter().map(|v| v.rshares).sum();
+        let share = |rshares: i64| {
+            if total > 0 {
+                rshares as f64 / total as f64
+            } else {
+                0.0
+            }
+        };
+        let own: i64 = upvotes
+            .iter()
+            .filter(|v| v.voter == author)
+            .map(|v| v.rshares)
+            .sum();
+        let mut circle: Vec<&Vote> = upvotes
+            .iter()
+            .copied()
+            .filter(|v| v.voter != author && upvoted.contains(v.voter.as_str()))
+            .collect();
+        circle.sort_by_key(|v| std::cmp::Reverse(v.rshares));
+
+        let mut recurring: Vec<RecurringBeneficiary> =
+            if posts_with_beneficiaries >= MIN_POSTS_FOR_PATTERNS {
+                beneficiary_posts
+                    .into_iter()
+                    .map(|(account, posts)| RecurringBeneficiary {
+                        share: posts as f64 / posts_with_beneficiaries as f64,
+                        account,
+                        posts,
+                    })
+                    .filter(|b| b.share >= self.recurring_share)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        recurring.sort_by(|a, b| b.posts.cmp(&a.posts).then(a.account.cmp(&b.account)));
+
+        AbuseSignals {
+            author: author.to_string(),
+            permlink: permlink.to_string(),
+            self_vote_share: share(own),
+            account_self_vote_share: if all_upvotes > 0 {
+                own_upvotes as f64 / all_upvotes as f64
+            } else {
+                0.0
+            },
+            vote_circle_share: share(circle.iter().map(|v| v.rshares).sum()),
+            circle_voters: circle.iter().map(|v| v.voter.clone()).collect(),
+            recurring_beneficiaries: recurring,
+            blacklisted_on: self
+                .blacklists
+                .iter()
+                .filter(|(_, accounts)| accounts.contains(author))
+                .map(|(name, _)| name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Beneficiaries set by a `comment_options` operation, in either extension format
+fn beneficiaries(op: &Operation) -> Vec<Beneficiary> {
+    let Some(extensions) = op.value.get("extensions").and_then(|e| e.as_array()) else {
+        return Vec::new();
+    };
+    extensions
+        .iter()
+        .filter_map(|ext| {
+            // `{"type": ..., "value": ...}` or the legacy `[0, {...}]`
+            let value = ext.get("value").or_else(|| ext.get(1))?;
+            serde_json::from_value(value.get("beneficiaries")?.clone()).ok()
+        })
+        .flat_map(|b: Vec<Beneficiary>| b)
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct FollowListEntry {
+    name: String,
+}
+
+impl Client {
+    /// Accounts on `observer`'s hivemind blacklist
+    pub fn get_blacklist(&self, observer: &str) -> Result<HashSet<String>, String> {
+        let entries: Vec<FollowListEntry> = self
+            .call(
+                "bridge.get_follow_list",
+                serde_json::json!({ "observer": observer, "follow_type": "blacklisted" }),
+            )
+            .map_err(|e| format!("Error fetching blacklist of {observer}: {e}"))?;
+        Ok(entries.into_iter().map(|e| e.name).collect())
+    }
+
+    /// Compute the abuse signals of a post
+    ///
+    /// Fetches the post's votes and the author's recent votes and comment
+    /// options, filtered by the node.
+    pub fn abuse_signals(
+        &self,
+        analyzer: &AbuseAnalyzer,
+        author: &str,
+        permlink: &str,
+    ) -> Result<AbuseSignals, String> {
+        let votes = self.get_active_votes(author, permlink)?;
+        // The names are known operations
+        let types = OpTypeSet::from_names(&["vote", "comment_options"]).unwrap();
+        let history = self
+            .account_history(author)
+            .filter(types)
+            .take(analyzer.history_depth)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(analyzer.analyze(author, permlink, &votes, &history))
+    }
+}
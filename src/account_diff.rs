@@ -0,0 +1,194 @@
+//! What changed in an account between two block heights, reconstructed from history
+
+use crate::asset::Asset;
+use crate::balance_history::{apply_operation, Balances};
+use crate::history::HistoryEntry;
+use crate::Client;
+
+/// A delegation set (or removed, when zero) within the range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegationChange {
+    pub delegator: String,
+    pub delegatee: String,
+    /// The delegated amount at the end of the range
+    pub vesting_shares: Asset,
+    pub block: u32,
+}
+
+/// An operation that changed keys, authorities or the recovery account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityChange {
+    pub block: u32,
+    pub timestamp: String,
+    pub trx_id: String,
+    /// Operation name, e.g. `account_update`
+    pub operation: String,
+    /// Which of `owner`, `active`, `posting`, `memo_key` and `recovery_account` it touched
+    pub fields: Vec<&'static str>,
+}
+
+/// Differences in an account between `from_block` (exclusive) and `to_block` (inclusive)
+#[derive(Debug, Clone)]
+pub struct AccountDiff {
+    pub account: String,
+    pub from_block: u32,
+    pub to_block: u32,
+    pub before: Balances,
+    pub after: Balances,
+    /// HIVE Power before and after, valued at the current VESTS price
+    pub hp_before: Asset,
+    pub hp_after: Asset,
+    pub delegations: Vec<DelegationChange>,
+    pub authority_changes: Vec<AuthorityChange>,
+    /// Number of history entries in the range
+    pub operations: usize,
+}
+
+impl AccountDiff {
+    /// Net change of each balance over the range
+    pub fn balance_change(&self) -> Balances {
+        let mut change = self.after;
+        change.sub(self.before.hive);
+        change.sub(self.before.hbd);
+        change.sub(self.before.vests);
+        change
+    }
+}
+
+const AUTHORITY_FIELDS: &[(&str, &str)] = &[
+    ("owner", "owner"),
+    ("active", "active"),
+    ("posting", "posting"),
+    ("memo_key", "memo_key"),
+    ("new_owner_authority", "owner"),
+    ("new_recovery_account", "recovery_account"),
+];
+
+fn authority_change(account: &str, entry: &HistoryEntry) -> Option<AuthorityChange> {
+    let op = &entry.op;
+    let account_field = match op.name() {
+        "account_update" | "account_update2" => "account",
+        "recover_account" | "change_recovery_account" => "account_to_recover",
+        _ => return None,
+    };
+    if op.str_field(account_field) != Some(account) {
+        return None;
+    }
+    let mut fields: Vec<&'static str> = AUTHORITY_FIELDS
+        .iter()
+        .filter(|(key, _)| op.value.get(*key).is_some_and(|v| !v.is_null()))
+        .map(|&(_, field)| field)
+        .collect();
+    // account_update2 always carries memo_key; an empty one means unchanged
+    if op.str_field("memo_key") == Some("") {
+        fields.retain(|&f| f != "memo_key");
+    }
+    if fields.is_empty() {
+        return None;
+    }
+    Some(AuthorityChange {
+        block: entry.block,
+        timestamp: entry.timestamp.clone(),
+        trx_id: entry.trx_id.clone(),
+        operation: op.name().to_string(),
+        fields,
+    })
+}
+
+impl Client {
+    /// Compute balance, HP, delegation and authority changes of `account` between two blocks
+    ///
+    /// Balances at `to_block` are derived by undoing later history from the
+    /// current on-chain balances, so only the history after `from_block` is read.
+    pub fn account_diff(
+        &self,
+        account: &str,
+        from_block: u32,
+        to_block: u32,
+    ) -> Result<AccountDiff, String> {
+        if from_block >= to_block {
+            return Err(format!(
+                "from_block {from_block} must be below to_block {to_block}"
+            ));
+        }
+        let chain_account = self
+            .find_accounts(&[account])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Account '{account}' not found"))?;
+        let props = self.get_dynamic_global_properties()?;
+
+        let mut later = Balances::default();
+        let mut window = Balances::default();
+        let mut delegations: Vec<DelegationChange> = Vec::new();
+        let mut authority_changes = Vec::new();
+        let mut operations = 0;
+        for entry in self.account_history(account) {
+            let entry = entry?;
+            if entry.block <= from_block {
+                break;
+            }
+            if entry.block > props.head_block_number {
+                continue;
+            }
+            if entry.block > to_block {
+                apply_operation(&mut later, account, &entry.op);
+                continue;
+            }
+            operations += 1;
+            apply_operation(&mut window, account, &entry.op);
+            if let Some(change) = authority_change(account, &entry) {
+                authority_changes.push(change);
+            }
+            if entry.op.name() == "delegate_vesting_shares" {
+                let delegator = entry.op.str_field("delegator").unwrap_or_default();
+                let delegatee = entry.op.str_field("delegatee").unwrap_or_default();
+                // Newest first: only the latest setting per pair is the end state
+                let seen = delegations
+                    .iter()
+                    .any(|d| d.delegator == delegator && d.delegatee == delegatee);
+                let amount = entry
+                    .op
+                    .value
+                    .get("vesting_shares")
+                    .and_then(|v| Asset::from_value(v).ok());
+                if let (false, Some(vesting_shares)) = (seen, amount) {
+                    delegations.push(DelegationChange {
+                        delegator: delegator.to_string(),
+                        delegatee: delegatee.to_string(),
+                        vesting_shares,
+                        block: entry.block,
+                    });
+                }
+            }
+        }
+        authority_changes.reverse();
+        delegations.reverse();
+
+        let mut after = Balances {
+            hive: chain_account.balance,
+            hbd: chain_account.hbd_balance,
+            vests: chain_account.vesting_shares,
+        };
+        after.sub(later.hive);
+        after.sub(later.hbd);
+        after.sub(later.vests);
+        let mut before = after;
+        before.sub(window.hive);
+        before.sub(window.hbd);
+        before.sub(window.vests);
+
+        Ok(AccountDiff {
+            account: account.to_string(),
+            from_block,
+            to_block,
+            hp_before: props.vests_to_hp(before.vests),
+            hp_after: props.vests_to_hp(after.vests),
+            before,
+            after,
+            delegations,
+            authority_changes,
+            operations,
+        })
+    }
+}
@@ -0,0 +1,142 @@
+//! Resumable iteration over every account on chain via `database_api.list_accounts`
+
+use crate::cursor::Cursor;
+use crate::{Account, AccountsResponse, Client};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Maximum number of accounts `database_api.list_accounts` returns per call
+pub const LIST_ACCOUNTS_LIMIT: u32 = 1000;
+
+#[derive(Debug, Serialize)]
+struct ListAccountsParams<'a> {
+    start: &'a str,
+    limit: u32,
+    order: &'a str,
+}
+
+impl Client {
+    /// Iterate over all accounts in name order
+    pub fn scan_accounts(&self) -> AccountScan<'_> {
+        AccountScan {
+            client: self,
+            cursor: String::new(),
+            skip_cursor: false,
+            last_yielded: None,
+            page_size: LIST_ACCOUNTS_LIMIT,
+            delay: Duration::ZERO,
+            last_request: None,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+/// Blocking iterator over all accounts, created by [`Client::scan_accounts`]
+#[derive(Debug)]
+pub struct AccountScan<'a> {
+    client: &'a Client,
+    cursor: String,
+    skip_cursor: bool,
+    last_yielded: Option<String>,
+    page_size: u32,
+    delay: Duration,
+    last_request: Option<Instant>,
+    buffer: VecDeque<Account>,
+    done: bool,
+}
+
+impl AccountScan<'_> {
+    /// Continue a previous scan after the account returned by [`AccountScan::checkpoint`]
+    pub fn resume_after(mut self, checkpoint: &str) -> Self {
+        self.cursor = checkpoint.to_string();
+        self.skip_cursor = true;
+        self.last_yielded = Some(checkpoint.to_string());
+        self
+    }
+
+    /// Number of accounts fetched per request, capped at [`LIST_ACCOUNTS_LIMIT`]
+    pub fn page_size(mut self, size: u32) -> Self {
+        self.page_size = size.clamp(2, LIST_ACCOUNTS_LIMIT);
+        self
+    }
+
+    /// Wait at least `delay` between requests to avoid hammering nodes
+    pub fn rate_limit(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Name of the last account yielded, to persist and pass to [`AccountScan::resume_after`]
+    pub fn checkpoint(&self) -> Option<&str> {
+        self.last_yielded.as_deref()
+    }
+
+    /// Position after the accounts yielded so far
+    pub fn cursor(&self) -> Cursor {
+        Cursor::Accounts {
+            after: self.last_yielded.clone(),
+        }
+    }
+
+    /// Continue from a cursor of an earlier account scan
+    pub fn resume(self, cursor: &Cursor) -> Result<Self, String> {
+        match cursor {
+            Cursor::Accounts { after: Some(after) } => Ok(self.resume_after(after)),
+            Cursor::Accounts { after: None } => Ok(self),
+            other => Err(other.mismatch("account scan")),
+        }
+    }
+
+    fn fetch_page(&mut self) -> Result<(), String> {
+        if let Some(last) = self.last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.delay {
+                thread::sleep(self.delay - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+        let params = ListAccountsParams {
+            start: &self.cursor,
+            limit: self.page_size,
+            order: "by_name",
+        };
+        let resp: AccountsResponse = self
+            .client
+            .call("database_api.list_accounts", params)
+            .map_err(|e| format!("Error listing accounts from '{}': {e}", self.cursor))?;
+        let full_page = resp.accounts.len() as u32 >= self.page_size;
+        let mut accounts: VecDeque<Account> = resp.accounts.into();
+        // Pages start at the cursor itself, which was already yielded
+        if self.skip_cursor && accounts.front().is_some_and(|a| a.name == self.cursor) {
+            accounts.pop_front();
+        }
+        match accounts.back() {
+            Some(last) if full_page => {
+                self.cursor = last.name.clone();
+                self.skip_cursor = true;
+            }
+            _ => self.done = true,
+        }
+        self.buffer.extend(accounts);
+        Ok(())
+    }
+}
+
+impl Iterator for AccountScan<'_> {
+    type Item = Result<Account, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() && !self.done {
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        let account = self.buffer.pop_front()?;
+        self.last_yielded = Some(account.name.clone());
+        Some(Ok(account))
+    }
+}
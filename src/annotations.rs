@@ -0,0 +1,227 @@
+//! Local labels and notes attached to transactions and operations
+//!
+//! The chain has no place for bookkeeping such as "payroll" or "refund", so
+//! [`AnnotationStore`] keeps them in a JSON file keyed by transaction id or
+//! operation reference and merges them into history exports.
+
+use crate::history::HistoryEntry;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Labels and an optional note for one transaction or operation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub labels: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.note.is_none()
+    }
+
+    /// Add the labels of `other`, keeping this note if both have one
+    fn merge(&mut self, other: &Annotation) {
+        self.labels.extend(other.labels.iter().cloned());
+        if self.note.is_none() {
+            self.note.clone_from(&other.note);
+        }
+    }
+}
+
+/// Key annotating every operation of a transaction
+pub fn transaction_key(trx_id: &str) -> String {
+    trx_id.to_string()
+}
+
+/// Key annotating a single operation of a history entry
+///
+/// Regular operations are `<trx_id>/<op_in_trx>`. Virtual operations reuse
+/// op positions and often have an all-zero transaction id, so they are keyed
+/// `<block>/<trx_in_block>/<op_in_trx>/virtual` instead.
+pub fn operation_key(entry: &HistoryEntry) -> String {
+    if entry.virtual_op {
+        format!(
+            "{}/{}/{}/virtual",
+            entry.block, entry.trx_in_block, entry.op_in_trx
+        )
+    } else {
+        format!("{}/{}", entry.trx_id, entry.op_in_trx)
+    }
+}
+
+/// A history entry with its annotation, as written by [`Client::dump_annotated_history`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedEntry {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    #[serde(flatten)]
+    pub annotation: Annotation,
+}
+
+/// Annotations keyed by [`transaction_key`] or [`operation_key`], optionally backed by a file
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationStore {
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, Annotation>,
+}
+
+impl AnnotationStore {
+    /// A store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the store at `path`, starting empty if the file does not exist yet
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let entries = match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|e| format!("Error parsing annotations {}: {e}", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(format!("Error reading annotations {}: {e}", path.display())),
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            entries,
+        })
+    }
+
+    /// Write the store back to the file it was opened from
+    ///
+    /// The file is replaced atomically, so a crash never leaves it half written.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let data = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| format!("Error serializing annotations: {e}"))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| format!("Error writing annotations {}: {e}", path.display()))
+    }
+
+    /// Attach `label` to `key`; returns false if it was already there
+    pub fn label(&mut self, key: &str, label: &str) -> bool {
+        self.entries
+            .entry(key.to_string())
+            .or_default()
+            .labels
+            .insert(label.to_string())
+    }
+
+    /// Remove `label` from `key`; returns whether it was there
+    pub fn unlabel(&mut self, key: &str, label: &str) -> bool {
+        let Some(annotation) = self.entries.get_mut(key) else {
+            return false;
+        };
+        let removed = annotation.labels.remove(label);
+        if annotation.is_empty() {
+            self.entries.remove(key);
+        }
+        removed
+    }
+
+    /// Set or clear the note of `key`
+    pub fn set_note(&mut self, key: &str, note: Option<&str>) {
+        let annotation = self.entries.entry(key.to_string()).or_default();
+        annotation.note = note.map(str::to_string);
+        if annotation.is_empty() {
+            self.entries.remove(key);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Annotation> {
+        self.entries.get(key)
+    }
+
+    /// Drop every annotation of `key`
+    pub fn remove(&mut self, key: &str) -> Option<Annotation> {
+        self.entries.remove(key)
+    }
+
+    /// Keys carrying `label`
+    pub fn with_label<'a>(&'a self, label: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(_, a)| a.labels.contains(label))
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// Every label in use, sorted
+    pub fn labels(&self) -> BTreeSet<&str> {
+        self.entries
+            .values()
+            .flat_map(|a| a.labels.iter().map(String::as_str))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Annotation of a history entry: its operation's, plus its transaction's labels
+    pub fn for_entry(&self, entry: &HistoryEntry) -> Annotation {
+        let mut annotation = self
+            .entries
+            .get(&operation_key(entry))
+            .cloned()
+            .unwrap_or_default();
+        if !entry.virtual_op {
+            if let Some(tx) = self.entries.get(&transaction_key(&entry.trx_id)) {
+                annotation.merge(tx);
+            }
+        }
+        annotation
+    }
+
+    /// Attach annotations to a stream of history entries
+    pub fn annotate<'a, I>(
+        &'a self,
+        history: I,
+    ) -> impl Iterator<Item = Result<AnnotatedEntry, String>> + 'a
+    where
+        I: IntoIterator<Item = Result<HistoryEntry, String>>,
+        I::IntoIter: 'a,
+    {
+        history.into_iter().map(move |entry| {
+            entry.map(|entry| AnnotatedEntry {
+                annotation: self.for_entry(&entry),
+                entry,
+            })
+        })
+    }
+}
+
+impl Client {
+    /// Like [`Client::dump_account_history`], with each line carrying its labels and note
+    pub fn dump_annotated_history<W: Write>(
+        &self,
+        account: &str,
+        store: &AnnotationStore,
+        mut writer: W,
+    ) -> Result<u64, String> {
+        let mut written = 0;
+        for entry in store.annotate(self.account_history(account).oldest_first()) {
+            let line = serde_json::to_string(&entry?)
+                .map_err(|e| format!("Error encoding history entry: {e}"))?;
+            writeln!(writer, "{line}").map_err(|e| format!("Error writing history: {e}"))?;
+            written += 1;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("Error writing history: {e}"))?;
+        Ok(written)
+    }
+}
@@ -0,0 +1,240 @@
+//! Asset amounts (HIVE, HBD, VESTS) in both legacy and NAI formats
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AssetSymbol {
+    Hive,
+    Hbd,
+    Vests,
+}
+
+impl AssetSymbol {
+    /// Number of decimal places used by this asset
+    pub fn precision(self) -> u8 {
+        match self {
+            AssetSymbol::Hive | AssetSymbol::Hbd => 3,
+            AssetSymbol::Vests => 6,
+        }
+    }
+
+    /// Numerical asset identifier used by `database_api`
+    pub fn nai(self) -> &'static str {
+        match self {
+            AssetSymbol::Hive => "@@000000021",
+            AssetSymbol::Hbd => "@@000000013",
+            AssetSymbol::Vests => "@@000000037",
+        }
+    }
+
+    /// Symbol name used in the legacy string format
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetSymbol::Hive => "HIVE",
+            AssetSymbol::Hbd => "HBD",
+            AssetSymbol::Vests => "VESTS",
+        }
+    }
+
+    pub fn from_nai(nai: &str) -> Option<Self> {
+        [AssetSymbol::Hive, AssetSymbol::Hbd, AssetSymbol::Vests]
+            .into_iter()
+            .find(|symbol| symbol.nai() == nai)
+    }
+
+    /// The main network's symbol named `name`, under its Hive or Steem name
+    ///
+    /// Other chains' names, e.g. a testnet's `TESTS`, are looked up with
+    /// [`ChainConfig::symbol_from_name`](crate::ChainConfig::symbol_from_name).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "HIVE" | "STEEM" => Some(AssetSymbol::Hive),
+            "HBD" | "SBD" => Some(AssetSymbol::Hbd),
+            "VESTS" => Some(AssetSymbol::Vests),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AssetSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An amount of an asset, stored in its smallest unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Asset {
+    pub amount: i64,
+    pub symbol: AssetSymbol,
+}
+
+impl Asset {
+    pub fn new(amount: i64, symbol: AssetSymbol) -> Self {
+        Self { amount, symbol }
+    }
+
+    pub fn zero(symbol: AssetSymbol) -> Self {
+        Self::new(0, symbol)
+    }
+
+    pub fn zero_hive() -> Self {
+        Self::zero(AssetSymbol::Hive)
+    }
+
+    pub fn zero_hbd() -> Self {
+        Self::zero(AssetSymbol::Hbd)
+    }
+
+    pub fn zero_vests() -> Self {
+        Self::zero(AssetSymbol::Vests)
+    }
+
+    /// The amount as a floating point number, for display and charting
+    pub fn to_f64(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.symbol.precision() as i32)
+    }
+
+    /// The decimal amount without the symbol, e.g. `1.000`
+    pub fn amount_string(&self) -> String {
+        let precision = self.symbol.precision() as u32;
+        let scale = 10u64.pow(precision);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let abs = self.amount.unsigned_abs();
+        format!(
+            "{sign}{}.{:0width$}",
+            abs / scale,
+            abs % scale,
+            width = precision as usize
+        )
+    }
+
+    /// Add two amounts of the same asset, returning `None` on mismatch or overflow
+    pub fn checked_add(self, other: Asset) -> Option<Asset> {
+        if self.symbol != other.symbol {
+            return None;
+        }
+        Some(Asset::new(
+            self.amount.checked_add(other.amount)?,
+            self.symbol,
+        ))
+    }
+
+    /// Subtract two amounts of the same asset, returning `None` on mismatch or overflow
+    pub fn checked_sub(self, other: Asset) -> Option<Asset> {
+        if self.symbol != other.symbol {
+            return None;
+        }
+        Some(Asset::new(
+            self.amount.checked_sub(other.amount)?,
+            self.symbol,
+        ))
+    }
+
+    /// The `database_api` representation of this amount
+    pub fn to_nai(&self) -> Value {
+        serde_json::json!({
+            "amount": self.amount.to_string(),
+            "precision": self.symbol.precision(),
+            "nai": self.symbol.nai(),
+        })
+    }
+
+    /// Parse either the legacy string or the NAI object representation
+    ///
+    /// Legacy strings must use the main network's names; see
+    /// [`ChainConfig::asset_from_value`](crate::ChainConfig::asset_from_value)
+    /// for other chains.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        Self::from_value_with(value, AssetSymbol::from_name)
+    }
+
+    /// [`Asset::from_value`] looking legacy symbol names up with `symbol`
+    pub(crate) fn from_value_with(
+        value: &Value,
+        symbol: impl Fn(&str) -> Option<AssetSymbol>,
+    ) -> Result<Self, String> {
+        match value {
+            Value::String(s) => Self::parse_with(s, symbol),
+            Value::Object(obj) => {
+                let nai = obj
+                    .get("nai")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| "Missing nai in asset".to_string())?;
+                let symbol = AssetSymbol::from_nai(nai)
+                    .ok_or_else(|| format!("Unknown asset nai: {nai}"))?;
+                let amount = match obj.get("amount") {
+                    Some(Value::String(s)) => s
+                        .parse()
+                        .map_err(|e| format!("Invalid asset amount '{s}': {e}"))?,
+                    Some(Value::Number(n)) => n
+                        .as_i64()
+                        .ok_or_else(|| format!("Invalid asset amount: {n}"))?,
+                    _ => return Err("Missing amount in asset".to_string()),
+                };
+                Ok(Asset::new(amount, symbol))
+            }
+            other => Err(format!("Invalid asset: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount_string(), self.symbol)
+    }
+}
+
+impl FromStr for Asset {
+    type Err = String;
+
+    /// Parse the legacy `"1.000 HIVE"` format with the main network's names
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, AssetSymbol::from_name)
+    }
+}
+
+impl Asset {
+    /// Parse the legacy format, looking the symbol name up with `symbol`
+    pub(crate) fn parse_with(
+        s: &str,
+        symbol: impl Fn(&str) -> Option<AssetSymbol>,
+    ) -> Result<Self, String> {
+        let (number, name) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("Invalid asset string: '{s}'"))?;
+        let symbol = symbol(name.trim()).ok_or_else(|| format!("Unknown asset: '{name}'"))?;
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number),
+        };
+        let (whole, frac) = number.split_once('.').unwrap_or((number, ""));
+        let precision = symbol.precision() as usize;
+        if frac.len() > precision {
+            return Err(format!("Too many decimal places in asset: '{s}'"));
+        }
+        let digits = format!("{whole}{frac:0<precision$}");
+        let amount: i64 = digits
+            .parse()
+            .map_err(|e| format!("Invalid asset amount '{s}': {e}"))?;
+        Ok(Asset::new(if negative { -amount } else { amount }, symbol))
+    }
+}
+
+impl Serialize for Asset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Asset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Asset::from_value(&value).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,154 @@
+//! An async counterpart of [`Client`] for use inside async runtimes
+//!
+//! [`AsyncClient`] takes its nodes and settings from a configured [`Client`]
+//! and makes the same JSON-RPC calls without blocking the runtime, so it can
+//! be used from axum or actix handlers directly:
+//!
+//! ```no_run
+//! # async fn handler() -> Result<(), nectarflower_rs::NectarError> {
+//! use nectarflower_rs::{AsyncClient, Client};
+//!
+//! let mut client = AsyncClient::from(Client::new());
+//! client.update_nodes_from_account("nectarflower").await?;
+//! let props: serde_json::Value = client
+//!     .call("condenser_api.get_dynamic_global_properties", ())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requires the `async` feature.
+
+use crate::attempts::{CallAttempts, CallError};
+use crate::error::NectarError;
+use crate::fail_reason::NodeFailure;
+use crate::node_changes::NodeListChange;
+use crate::node_policy::EmptyNodeList;
+use crate::{decode_rpc_body, AccountParams, AccountsResponse, Client, NodeData, RpcRequest, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// A read-only Hive client whose calls are futures
+#[derive(Debug)]
+pub struct AsyncClient {
+    client: Client,
+}
+
+impl AsyncClient {
+    /// Create an async client with a default node
+    pub fn new() -> Self {
+        Self::from(Client::new())
+    }
+
+    /// The blocking client holding the nodes and settings
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The nodes calls are made to
+    pub fn nodes(&self) -> &[String] {
+        &self.client.nodes
+    }
+
+    /// Set the list of nodes, as [`Client::set_nodes`]
+    pub fn set_nodes(
+        &mut self,
+        nodes: Vec<String>,
+        failing_nodes: HashMap<Url, NodeFailure>,
+    ) -> Result<NodeListChange, EmptyNodeList> {
+        self.client.set_nodes(nodes, failing_nodes)
+    }
+
+    /// Make a JSON-RPC call to the Hive API
+    ///
+    /// Broadcast APIs are refused, as with [`Client::call`].
+    pub async fn call<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, NectarError> {
+        Ok(self.call_traced(method, params).await?)
+    }
+
+    /// Like [`AsyncClient::call`], keeping every failed attempt in the error
+    pub async fn call_traced<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, CallError> {
+        crate::write::refuse_broadcast(method, &params)?;
+        let mut attempts = CallAttempts::new(&self.client, method);
+        while let Some((node, delay)) = attempts.next() {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            let started = Instant::now();
+            match self.call_node(node, method, &params).await {
+                Ok(res) => return Ok(res),
+                Err(error) => attempts.failed(node, error, started),
+            }
+        }
+        attempts.finish().0
+    }
+
+    async fn call_node<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        node: &str,
+        method: &str,
+        params: &P,
+    ) -> Result<R, NectarError> {
+        let req = RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: 1,
+        };
+        let payload =
+            serde_json::to_vec(&req).map_err(|e| NectarError::Decode(format!("request: {e}")))?;
+        let body = self
+            .client
+            .transport_ref()
+            .send_async(node, payload)
+            .await?;
+        decode_rpc_body(&body)
+    }
+
+    /// Fetch account JSON metadata and extract node information
+    pub async fn get_nodes_from_account(
+        &self,
+        account_name: &str,
+    ) -> Result<NodeData, NectarError> {
+        let params = AccountParams {
+            accounts: vec![account_name.to_string()],
+        };
+        let resp: AccountsResponse = self.call("database_api.find_accounts", params).await?;
+        let account = resp
+            .accounts
+            .first()
+            .ok_or_else(|| NectarError::AccountNotFound(account_name.to_string()))?;
+        NodeData::from_json_metadata(&account.json_metadata)
+    }
+
+    /// Fetch nodes from an account and update the client, returning what changed
+    pub async fn update_nodes_from_account(
+        &mut self,
+        account_name: &str,
+    ) -> Result<NodeListChange, NectarError> {
+        let node_data = self.get_nodes_from_account(account_name).await?;
+        Ok(self.set_nodes(node_data.nodes, node_data.failing_nodes)?)
+    }
+}
+
+impl From<Client> for AsyncClient {
+    /// Keep the client's nodes, node policy and transport
+    fn from(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Default for AsyncClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
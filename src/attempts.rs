@@ -0,0 +1,182 @@
+//! The per-node history of a call that failed everywhere
+//!
+//! A call is tried on each node in turn. When none answers, [`CallError`]
+//! lists every [`Attempt`] with its node, classified reason and latency, so
+//! one flaky node can be told apart from an outage across all of them.
+
+use crate::error::NectarError;
+use crate::fail_reason::FailReason;
+use crate::retry::RetrySchedule;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// One node's failure to answer a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub node: String,
+    pub reason: FailReason,
+    pub error: NectarError,
+    pub latency: Duration,
+}
+
+impl fmt::Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} [{}, {} ms]",
+            self.node,
+            self.error,
+            self.reason,
+            self.latency.as_millis()
+        )
+    }
+}
+
+/// Why a call returned no result
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CallError {
+    #[error("{0} broadcasts and requires a WriteClient")]
+    RequiresWriteClient(String),
+    #[error("{}", describe_failures(.method, .attempts))]
+    AllNodesFailed {
+        method: String,
+        attempts: Vec<Attempt>,
+    },
+}
+
+pub(crate) fn describe_failures(method: &str, attempts: &[Attempt]) -> String {
+    match attempts {
+        [] => format!("No nodes available for {method}"),
+        [only] => format!("{method} failed on {only}"),
+        _ => {
+            let tried: Vec<String> = attempts.iter().map(Attempt::to_string).collect();
+            format!(
+                "{method} failed on all {} nodes: {}",
+                attempts.len(),
+                tried.join("; ")
+            )
+        }
+    }
+}
+
+impl From<CallError> for String {
+    fn from(e: CallError) -> Self {
+        e.to_string()
+    }
+}
+
+impl CallError {
+    /// The attempts made, oldest first
+    pub fn attempts(&self) -> &[Attempt] {
+        match self {
+            CallError::RequiresWriteClient(_) => &[],
+            CallError::AllNodesFailed { attempts, .. } => attempts,
+        }
+    }
+
+    /// Whether several nodes were tried and all failed for the same reason
+    ///
+    /// Suggests an outage or a bad request rather than individual bad nodes.
+    pub fn is_systemic(&self) -> bool {
+        match self.attempts() {
+            [first, rest @ ..] if !rest.is_empty() => {
+                rest.iter().all(|attempt| attempt.reason == first.reason)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Client {
+    /// Like [`Client::call`], returning every node's failure on error
+    pub fn call_traced<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, CallError> {
+        crate::write::refuse_broadcast(method, &params)?;
+        self.call_attempts(method, params).0
+    }
+
+    /// Try `method` on each node until one answers, also returning the node
+    /// that answered or was tried last
+    pub(crate) fn call_attempts<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> (Result<R, CallError>, Option<&str>) {
+        self.try_nodes(method, |node| self.call_node(node, method, &params))
+    }
+
+    /// Run `request` against each node, as the retry policy allows, until it succeeds
+    pub(crate) fn try_nodes<T>(
+        &self,
+        method: &str,
+        mut request: impl FnMut(&str) -> Result<T, NectarError>,
+    ) -> (Result<T, CallError>, Option<&str>) {
+        let mut attempts = CallAttempts::new(self, method);
+        while let Some((node, delay)) = attempts.next() {
+            thread::sleep(delay);
+            let started = Instant::now();
+            match request(node) {
+                Ok(res) => return (Ok(res), Some(node)),
+                Err(error) => attempts.failed(node, error, started),
+            }
+        }
+        attempts.finish()
+    }
+}
+
+/// The schedule and failures of one call, shared by the blocking and async clients
+///
+/// The caller waits the returned delay, sends the request to the returned
+/// node and reports a failure with [`CallAttempts::failed`], until a request
+/// succeeds or the schedule runs out.
+pub(crate) struct CallAttempts<'a> {
+    method: String,
+    schedule: RetrySchedule<'a>,
+    attempts: Vec<Attempt>,
+    last_node: Option<&'a str>,
+}
+
+impl<'a> CallAttempts<'a> {
+    pub(crate) fn new(client: &'a Client, method: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            schedule: client.retry.schedule(client.nodes_to_try()),
+            attempts: Vec::new(),
+            last_node: None,
+        }
+    }
+
+    /// The next node to try and how long to wait first
+    pub(crate) fn next(&mut self) -> Option<(&'a str, Duration)> {
+        let next = self.schedule.next(self.attempts.last().map(|a| a.reason));
+        if let Some((node, _)) = next {
+            self.last_node = Some(node);
+        }
+        next
+    }
+
+    /// Record that the request sent to `node` at `started` failed
+    pub(crate) fn failed(&mut self, node: &str, error: NectarError, started: Instant) {
+        self.attempts.push(Attempt {
+            node: node.to_string(),
+            reason: FailReason::classify(&error.to_string()),
+            error,
+            latency: started.elapsed(),
+        });
+    }
+
+    /// The error once every node failed, and the node tried last
+    pub(crate) fn finish<T>(self) -> (Result<T, CallError>, Option<&'a str>) {
+        let err = CallError::AllNodesFailed {
+            method: self.method,
+            attempts: self.attempts,
+        };
+        (Err(err), self.last_node)
+    }
+}
@@ -0,0 +1,220 @@
+//! Append-only audit log of broadcasts made through a [`WriteClient`]
+//!
+//! Every broadcast attempt is written as one JSON line with the time, the
+//! transaction id, a summary of its operations, fingerprints of the keys
+//! that signed it, the node it was sent to and the outcome. Memos and other
+//! free text are left out of the summary.
+
+use crate::asset::Asset;
+use crate::chain::ChainConfig;
+use crate::operation::Operation;
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::transaction::{Transaction, TransactionSigner};
+use crate::WriteClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Operation fields naming accounts, included in summaries
+const ACCOUNT_FIELDS: &[&str] = &[
+    "from",
+    "to",
+    "account",
+    "owner",
+    "author",
+    "voter",
+    "delegator",
+    "delegatee",
+    "publisher",
+];
+
+/// Operation fields holding amounts, included in summaries
+const AMOUNT_FIELDS: &[&str] = &[
+    "amount",
+    "amount_to_sell",
+    "min_to_receive",
+    "vesting_shares",
+    "fee",
+];
+
+/// One broadcast attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Local UTC time of the attempt
+    pub timestamp: String,
+    pub trx_id: Option<String>,
+    pub expiration: String,
+    /// One line per operation, e.g. `@alice transferred 1.000 HIVE to @bob`
+    pub operations: Vec<String>,
+    /// Fingerprints of the signing keys; `unknown` for signers that do not provide one
+    pub signers: Vec<String>,
+    /// Node that accepted the transaction, or the last one tried
+    pub node: Option<String>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Summarize an operation by name, accounts and amounts
+///
+/// The fallback of [`Operation::summary`] for operations it has no wording for.
+pub fn summarize_operation(op: &Operation) -> String {
+    let mut summary = op.name().to_string();
+    for field in ACCOUNT_FIELDS {
+        if let Some(value) = op.str_field(field) {
+            summary.push_str(&format!(" {field}={value}"));
+        }
+    }
+    for field in AMOUNT_FIELDS {
+        if let Some(amount) = op.value.get(*field).and_then(|v| Asset::from_value(v).ok()) {
+            summary.push_str(&format!(" {field}={amount}"));
+        }
+    }
+    summary
+}
+
+/// Who produced a signature, noted at signing time
+struct Signed {
+    trx_id: String,
+    fingerprint: Option<String>,
+    /// Expiration of the transaction; the entry is dropped after it
+    expires: i64,
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Where [`AuditRecord`]s are appended
+pub struct AuditLog {
+    writer: Mutex<Box<dyn Write + Send>>,
+    /// Signing details by signature, kept until broadcast or expired
+    signed: Mutex<HashMap<String, Signed>>,
+}
+
+impl AuditLog {
+    /// Append to the file at `path`, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Error opening audit log {}: {e}", path.display()))?;
+        Ok(Self::from_writer(file))
+    }
+
+    /// Append to any writer, e.g. a log shipper's pipe
+    pub fn from_writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+            signed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remember who produced the signatures `tx` gained when signed as `trx_id`
+    ///
+    /// Notes of transactions that expired without a successful broadcast are
+    /// dropped, as they can no longer be broadcast.
+    pub(crate) fn note_signers(
+        &self,
+        tx: &Transaction,
+        trx_id: &str,
+        new_signatures: &[String],
+        signers: &[&dyn TransactionSigner],
+    ) {
+        let now = unix_now();
+        let expires = parse_timestamp(&tx.expiration).unwrap_or(now);
+        let mut signed = self.signed.lock().unwrap();
+        signed.retain(|_, entry| entry.expires >= now);
+        for (signature, signer) in new_signatures.iter().zip(signers) {
+            signed.insert(
+                signature.clone(),
+                Signed {
+                    trx_id: trx_id.to_string(),
+                    fingerprint: signer.key_fingerprint(),
+                    expires,
+                },
+            );
+        }
+    }
+
+    /// Write the record of one broadcast attempt
+    pub(crate) fn record(
+        &self,
+        tx: &Transaction,
+        chain: &ChainConfig,
+        node: Option<&str>,
+        result: &Result<(), String>,
+    ) -> Result<(), String> {
+        let now = unix_now();
+        let mut signed = self.signed.lock().unwrap();
+        let known: Vec<_> = tx.signatures.iter().map(|s| signed.get(s)).collect();
+        let trx_id = known
+            .iter()
+            .flatten()
+            .map(|entry| entry.trx_id.clone())
+            .next()
+            .or_else(|| tx.id_for(chain).ok());
+        let signers = known
+            .iter()
+            .map(|entry| {
+                entry
+                    .and_then(|entry| entry.fingerprint.clone())
+                    .unwrap_or_else(|| "unknown".to_string())
+            })
+            .collect();
+        let record = AuditRecord {
+            timestamp: format_timestamp(now),
+            trx_id,
+            expiration: tx.expiration.clone(),
+            operations: tx
+                .operations
+                .iter()
+                .map(Operation::summary_redacted)
+                .collect(),
+            signers,
+            node: node.map(str::to_string),
+            ok: result.is_ok(),
+            error: result.as_ref().err().cloned(),
+        };
+        if record.ok {
+            for signature in &tx.signatures {
+                signed.remove(signature);
+            }
+        }
+        signed.retain(|_, entry| entry.expires >= now);
+        drop(signed);
+        let line = serde_json::to_string(&record)
+            .map_err(|e| format!("Error encoding audit record: {e}"))?;
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{line}")
+            .and_then(|()| writer.flush())
+            .map_err(|e| format!("Error writing audit log: {e}"))
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog").finish_non_exhaustive()
+    }
+}
+
+impl WriteClient {
+    /// Record every broadcast attempt in `log`
+    ///
+    /// A broadcast the log fails to record returns [`WriteError::Audit`](crate::WriteError::Audit)
+    /// carrying the broadcast's own result; [`WriteClient::broadcast_reliably`]
+    /// stops there rather than send a possibly accepted transaction again.
+    pub fn audit_log(mut self, log: AuditLog) -> Self {
+        self.audit = Some(log);
+        self
+    }
+}
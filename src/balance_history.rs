@@ -0,0 +1,211 @@
+//! Reconstruction of daily liquid balance time series from account history
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::history::HistoryEntry;
+use crate::operation::Operation;
+use crate::timestamp::{format_date, parse_timestamp};
+use crate::Client;
+
+/// Liquid HIVE, HBD and owned VESTS of an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Balances {
+    pub hive: Asset,
+    pub hbd: Asset,
+    pub vests: Asset,
+}
+
+impl Default for Balances {
+    fn default() -> Self {
+        Self {
+            hive: Asset::zero_hive(),
+            hbd: Asset::zero_hbd(),
+            vests: Asset::zero_vests(),
+        }
+    }
+}
+
+impl Balances {
+    /// Add a (possibly negative) amount to the matching balance
+    pub fn add(&mut self, amount: Asset) {
+        let balance = match amount.symbol {
+            AssetSymbol::Hive => &mut self.hive,
+            AssetSymbol::Hbd => &mut self.hbd,
+            AssetSymbol::Vests => &mut self.vests,
+        };
+        balance.amount += amount.amount;
+    }
+
+    pub fn sub(&mut self, amount: Asset) {
+        self.add(Asset::new(-amount.amount, amount.symbol));
+    }
+}
+
+/// Balances at the end of a UTC day
+#[derive(Debug, Clone)]
+pub struct BalanceSnapshot {
+    /// Day in `YYYY-MM-DD` format
+    pub date: String,
+    pub balances: Balances,
+}
+
+/// A reconstructed balance time series along with the chain's current balances
+#[derive(Debug, Clone)]
+pub struct BalanceHistory {
+    pub account: String,
+    pub days: Vec<BalanceSnapshot>,
+    pub reconstructed: Balances,
+    pub current: Balances,
+}
+
+impl BalanceHistory {
+    /// Whether replaying history produced the balances currently on chain
+    pub fn is_consistent(&self) -> bool {
+        self.reconstructed == self.current
+    }
+
+    /// Current balances minus reconstructed balances, showing unaccounted changes
+    pub fn discrepancy(&self) -> Balances {
+        let mut diff = self.current;
+        diff.sub(self.reconstructed.hive);
+        diff.sub(self.reconstructed.hbd);
+        diff.sub(self.reconstructed.vests);
+        diff
+    }
+}
+
+/// Balance effects as (operation, account field, amount field, sign)
+const BALANCE_EFFECTS: &[(&str, &str, &str, i64)] = &[
+    ("transfer", "from", "amount", -1),
+    ("transfer", "to", "amount", 1),
+    ("fill_recurrent_transfer", "from", "amount", -1),
+    ("fill_recurrent_transfer", "to", "amount", 1),
+    ("transfer_to_vesting", "from", "amount", -1),
+    (
+        "transfer_to_vesting_completed",
+        "to_account",
+        "vesting_shares_received",
+        1,
+    ),
+    ("fill_vesting_withdraw", "from_account", "withdrawn", -1),
+    ("fill_vesting_withdraw", "to_account", "deposited", 1),
+    ("transfer_to_savings", "from", "amount", -1),
+    ("fill_transfer_from_savings", "to", "amount", 1),
+    ("claim_reward_balance", "account", "reward_hive", 1),
+    ("claim_reward_balance", "account", "reward_hbd", 1),
+    ("claim_reward_balance", "account", "reward_vests", 1),
+    ("limit_order_create", "owner", "amount_to_sell", -1),
+    ("limit_order_create2", "owner", "amount_to_sell", -1),
+    ("limit_order_cancelled", "seller", "amount_back", 1),
+    ("fill_order", "current_owner", "open_pays", 1),
+    ("fill_order", "open_owner", "current_pays", 1),
+    ("convert", "owner", "amount", -1),
+    ("collateralized_convert", "owner", "amount", -1),
+    ("fill_convert_request", "owner", "amount_out", 1),
+    // HBD is paid out immediately; the fill only returns unused collateral
+    (
+        "collateralized_convert_immediate_conversion",
+        "owner",
+        "hbd_out",
+        1,
+    ),
+    (
+        "fill_collateralized_convert_request",
+        "owner",
+        "excess_collateral",
+        1,
+    ),
+    ("account_create", "creator", "fee", -1),
+    ("account_create_with_delegation", "creator", "fee", -1),
+    ("claim_account", "creator", "fee", -1),
+    ("producer_reward", "producer", "vesting_shares", 1),
+    ("proposal_pay", "receiver", "payment", 1),
+    ("interest", "owner", "interest", 1),
+];
+
+/// Apply the liquid balance changes an operation causes for `account`
+///
+/// Covers transfers, vesting, rewards claims, savings, conversions, market
+/// orders, recurrent transfers, account creation fees, HBD interest and DHF
+/// payments.
+pub fn apply_operation(balances: &mut Balances, account: &str, op: &Operation) {
+    let name = op.name();
+    if name == "interest" && op.value.get("is_saved_into_hbd_balance") == Some(&false.into()) {
+        return;
+    }
+    for &(op_name, account_field, amount_field, sign) in BALANCE_EFFECTS {
+        if op_name != name || op.str_field(account_field) != Some(account) {
+            continue;
+        }
+        if let Some(amount) = op
+            .value
+            .get(amount_field)
+            .and_then(|v| Asset::from_value(v).ok())
+        {
+            balances.add(Asset::new(sign * amount.amount, amount.symbol));
+        }
+    }
+}
+
+/// Replay history entries (oldest first) into end-of-day snapshots up to `until`
+pub fn daily_balances(
+    account: &str,
+    entries: &[HistoryEntry],
+    until: i64,
+) -> Result<(Vec<BalanceSnapshot>, Balances), String> {
+    let mut balances = Balances::default();
+    let mut days = Vec::new();
+    let mut current_day: Option<i64> = None;
+    let last_day = until.div_euclid(86_400);
+    for entry in entries {
+        let day = parse_timestamp(&entry.timestamp)?.div_euclid(86_400);
+        if let Some(mut d) = current_day {
+            while d < day {
+                days.push(BalanceSnapshot {
+                    date: format_date(d * 86_400),
+                    balances,
+                });
+                d += 1;
+            }
+        }
+        current_day = Some(day);
+        apply_operation(&mut balances, account, &entry.op);
+    }
+    if let Some(mut d) = current_day {
+        while d <= last_day {
+            days.push(BalanceSnapshot {
+                date: format_date(d * 86_400),
+                balances,
+            });
+            d += 1;
+        }
+    }
+    Ok((days, balances))
+}
+
+impl Client {
+    /// Replay an account's full history into daily balances and compare with the chain
+    pub fn balance_history(&self, account: &str) -> Result<BalanceHistory, String> {
+        let entries = self
+            .account_history(account)
+            .oldest_first()
+            .collect::<Result<Vec<_>, _>>()?;
+        let props = self.get_dynamic_global_properties()?;
+        let chain_account = self
+            .find_accounts(&[account])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Account '{account}' not found"))?;
+        let (days, reconstructed) =
+            daily_balances(account, &entries, parse_timestamp(&props.time)?)?;
+        Ok(BalanceHistory {
+            account: account.to_string(),
+            days,
+            reconstructed,
+            current: Balances {
+                hive: chain_account.balance,
+                hbd: chain_account.hbd_balance,
+                vests: chain_account.vesting_shares,
+            },
+        })
+    }
+}
@@ -0,0 +1,134 @@
+//! JSON-RPC batch requests
+//!
+//! [`Client::call_batch`] sends several calls to one node in a single HTTP
+//! request and matches the responses to the calls by id. A node that fails
+//! the whole batch is failed over like a single call; an error answering one
+//! call in the batch only fails that call:
+//!
+//! ```no_run
+//! use nectarflower_rs::Client;
+//! use serde_json::json;
+//!
+//! let client = Client::new();
+//! let results = client.call_batch([
+//!     ("database_api.get_dynamic_global_properties", json!({})),
+//!     ("database_api.find_accounts", json!({ "accounts": ["alice"] })),
+//! ])?;
+//! for result in results {
+//!     match result {
+//!         Ok(value) => println!("{value}"),
+//!         Err(e) => eprintln!("{e}"),
+//!     }
+//! }
+//! # Ok::<(), nectarflower_rs::NectarError>(())
+//! ```
+
+use crate::error::NectarError;
+use crate::{Client, RpcError, RpcRequest, RpcResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// JSON-RPC code of a call the node left unanswered in a batch
+pub const MISSING_RESPONSE_CODE: i32 = -32603;
+
+/// One result or error per call of a batch, in the order of the calls
+pub type BatchResults = Vec<Result<Value, RpcError>>;
+
+impl Client {
+    /// Send `calls` as one batch to the first node that accepts it
+    ///
+    /// Returns one result per call, in the order of `calls`.
+    pub fn call_batch<I, M, P>(&self, calls: I) -> Result<BatchResults, NectarError>
+    where
+        I: IntoIterator<Item = (M, P)>,
+        M: AsRef<str>,
+        P: Serialize,
+    {
+        let mut requests = Vec::new();
+        for (id, (method, params)) in calls.into_iter().enumerate() {
+            let method = method.as_ref();
+            crate::write::refuse_broadcast(method, &params)?;
+            requests.push(RpcRequest {
+                jsonrpc: "2.0".to_string(),
+                method: method.to_string(),
+                params: serde_json::to_value(params)
+                    .map_err(|e| NectarError::Decode(format!("params of {method}: {e}")))?,
+                id: id as u32,
+            });
+        }
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+        let label = format!("batch of {} calls", requests.len());
+        let (result, _) = self.try_nodes(&label, |node| self.send_batch(node, &requests));
+        Ok(result?)
+    }
+
+    /// Like [`Client::call_batch`] for calls that all return an `R`
+    pub fn call_batch_as<R, I, M, P>(
+        &self,
+        calls: I,
+    ) -> Result<Vec<Result<R, NectarError>>, NectarError>
+    where
+        R: for<'de> Deserialize<'de>,
+        I: IntoIterator<Item = (M, P)>,
+        M: AsRef<str>,
+        P: Serialize,
+    {
+        let results = self.call_batch(calls)?;
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                serde_json::from_value(result?)
+                    .map_err(|e| NectarError::Decode(format!("result: {e}")))
+            })
+            .collect())
+    }
+
+    fn send_batch(
+        &self,
+        node: &str,
+        requests: &[RpcRequest<Value>],
+    ) -> Result<BatchResults, NectarError> {
+        let body = self.post_json(node, &requests)?;
+        let value: Value =
+            serde_json::from_slice(&body).map_err(|e| NectarError::Decode(e.to_string()))?;
+        // A node that rejects the whole batch answers with a single error
+        let responses: Vec<RpcResponse<Value>> = match value {
+            Value::Array(_) => serde_json::from_value(value)
+                .map_err(|e| NectarError::Decode(format!("batch response: {e}")))?,
+            other => {
+                let single: RpcResponse<Value> = serde_json::from_value(other)
+                    .map_err(|e| NectarError::Decode(format!("batch response: {e}")))?;
+                return Err(match single.error {
+                    Some(error) => error.into(),
+                    None => NectarError::Decode("batch answered with one result".to_string()),
+                });
+            }
+        };
+        let mut results: Vec<Option<Result<Value, RpcError>>> = vec![None; requests.len()];
+        for response in responses {
+            let Some(slot) = results.get_mut(response.id as usize) else {
+                continue;
+            };
+            *slot = Some(match (response.result, response.error) {
+                (_, Some(error)) => Err(error),
+                (Some(result), None) => Ok(result),
+                (None, None) => Ok(Value::Null),
+            });
+        }
+        Ok(results
+            .into_iter()
+            .zip(requests)
+            .map(|(result, request)| {
+                result.unwrap_or_else(|| {
+                    Err(RpcError {
+                        code: MISSING_RESPONSE_CODE,
+                        message: format!("No response to {} in batch", request.method),
+                        data: None,
+                    })
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,432 @@
+//! Block, block header and block streaming helpers
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::cursor::Cursor;
+use crate::custom_json::{CustomOp, CustomOpRegistry};
+use crate::numbers::{de_int, u128_string};
+use crate::operation::Operation;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Hive produces a block every three seconds
+pub const BLOCK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Maximum number of blocks `block_api.get_block_range` returns per call
+pub const MAX_BLOCK_RANGE: u32 = 1000;
+
+/// Default number of blocks a stream may fall behind before fetching in batches
+pub const DEFAULT_CATCH_UP_THRESHOLD: u32 = 20;
+
+/// Default largest batch fetched while catching up
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicGlobalProperties {
+    #[serde(deserialize_with = "de_int")]
+    pub head_block_number: u32,
+    pub head_block_id: String,
+    pub time: String,
+    pub current_witness: String,
+    #[serde(deserialize_with = "de_int")]
+    pub current_aslot: u64,
+    #[serde(deserialize_with = "de_int")]
+    pub last_irreversible_block_num: u32,
+    pub total_vesting_fund_hive: Asset,
+    pub total_vesting_shares: Asset,
+    /// Bitmap of which of the last 128 slots produced a block
+    #[serde(default, with = "u128_string")]
+    pub recent_slots_filled: u128,
+}
+
+impl DynamicGlobalProperties {
+    /// Convert VESTS to their current HIVE Power value
+    pub fn vests_to_hp(&self, vests: Asset) -> Asset {
+        let total_shares = i128::from(self.total_vesting_shares.amount);
+        if total_shares == 0 {
+            return Asset::zero_hive();
+        }
+        let hive = i128::from(vests.amount) * i128::from(self.total_vesting_fund_hive.amount)
+            / total_shares;
+        Asset::new(hive as i64, AssetSymbol::Hive)
+    }
+
+    /// Convert HIVE Power to its current VESTS value
+    pub fn hp_to_vests(&self, hp: Asset) -> Asset {
+        let total_fund = i128::from(self.total_vesting_fund_hive.amount);
+        if total_fund == 0 {
+            return Asset::zero_vests();
+        }
+        let vests =
+            i128::from(hp.amount) * i128::from(self.total_vesting_shares.amount) / total_fund;
+        Asset::new(vests as i64, AssetSymbol::Vests)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub previous: String,
+    pub timestamp: String,
+    pub witness: String,
+    pub transaction_merkle_root: String,
+    #[serde(default)]
+    pub extensions: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    #[serde(flatten)]
+    pub header: BlockHeader,
+    pub witness_signature: String,
+    pub transactions: Vec<Value>,
+    pub block_id: String,
+    pub signing_key: String,
+    pub transaction_ids: Vec<String>,
+}
+
+impl Block {
+    /// Operations in this block paired with the id of their transaction
+    pub fn operations(&self) -> Vec<(&str, Operation)> {
+        let mut ops = Vec::new();
+        for (trx, trx_id) in self.transactions.iter().zip(&self.transaction_ids) {
+            let trx_ops = trx.get("operations").and_then(|o| o.as_array());
+            for op in trx_ops.into_iter().flatten() {
+                if let Ok(op) = serde_json::from_value(op.clone()) {
+                    ops.push((trx_id.as_str(), op));
+                }
+            }
+        }
+        ops
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BlockNumParams {
+    block_num: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockResponse {
+    block: Option<Block>,
+}
+
+#[derive(Debug, Serialize)]
+struct BlockRangeParams {
+    starting_block_num: u32,
+    count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockRangeResponse {
+    blocks: Vec<Block>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockHeaderResponse {
+    header: Option<BlockHeader>,
+}
+
+impl Client {
+    /// Fetch the dynamic global properties of the chain
+    ///
+    /// Also refreshes the client's [`Client::chain_clock`].
+    pub fn get_dynamic_global_properties(&self) -> Result<DynamicGlobalProperties, String> {
+        let sent = SystemTime::now();
+        let start = Instant::now();
+        let props: DynamicGlobalProperties = self
+            .call("database_api.get_dynamic_global_properties", ())
+            .map_err(|e| format!("Error fetching global properties: {e}"))?;
+        self.record_chain_time(&props.time, sent, start.elapsed());
+        Ok(props)
+    }
+
+    /// Fetch a full block, returning `None` if it has not been produced yet
+    pub fn get_block(&self, block_num: u32) -> Result<Option<Block>, String> {
+        let resp: BlockResponse = self
+            .call("block_api.get_block", BlockNumParams { block_num })
+            .map_err(|e| format!("Error fetching block {block_num}: {e}"))?;
+        Ok(resp.block)
+    }
+
+    /// Fetch up to `count` consecutive blocks starting at `start`, fewer near the head
+    pub fn get_block_range(&self, start: u32, count: u32) -> Result<Vec<Block>, String> {
+        let count = count.clamp(1, MAX_BLOCK_RANGE);
+        let resp: BlockRangeResponse = self
+            .call(
+                "block_api.get_block_range",
+                BlockRangeParams {
+                    starting_block_num: start,
+                    count,
+                },
+            )
+            .map_err(|e| {
+                format!(
+                    "Error fetching blocks {start} to {}: {e}",
+                    start + count - 1
+                )
+            })?;
+        Ok(resp.blocks)
+    }
+
+    /// Fetch only the header of a block, returning `None` if it has not been produced yet
+    pub fn get_block_header(&self, block_num: u32) -> Result<Option<BlockHeader>, String> {
+        let resp: BlockHeaderResponse = self
+            .call("block_api.get_block_header", BlockNumParams { block_num })
+            .map_err(|e| format!("Error fetching block header {block_num}: {e}"))?;
+        Ok(resp.header)
+    }
+
+    /// Stream blocks starting at `start`, waiting for new blocks once the head is reached
+    pub fn stream_blocks(&self, start: u32) -> BlockStream<'_> {
+        BlockStream {
+            client: self,
+            next: start,
+            head: 0,
+            mode: StreamMode::Full,
+            poll_interval: BLOCK_INTERVAL,
+            buffer: VecDeque::new(),
+            batch_size: 1,
+            catch_up_threshold: DEFAULT_CATCH_UP_THRESHOLD,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+        }
+    }
+}
+
+/// What a [`BlockStream`] fetches for each block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StreamMode {
+    /// Fetch full blocks including transactions
+    #[default]
+    Full,
+    /// Fetch only block headers (timestamp, witness, previous id)
+    HeaderOnly,
+}
+
+/// A block yielded by a [`BlockStream`]; `block` is `None` in header-only mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedBlock {
+    pub block_num: u32,
+    pub header: BlockHeader,
+    pub block: Option<Block>,
+}
+
+impl StreamedBlock {
+    /// Decode the block's `custom_json` operations; empty in header-only mode
+    pub fn custom_ops<T>(&self, registry: &CustomOpRegistry<T>) -> Vec<(&str, CustomOp<T>)> {
+        self.block
+            .as_ref()
+            .map(|block| registry.decode_block(block))
+            .unwrap_or_default()
+    }
+}
+
+/// How far a [`BlockStream`] is behind the head block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamLag {
+    /// The next block the consumer will receive
+    pub next_block: u32,
+    /// The head block when last checked
+    pub head_block: u32,
+    /// Blocks produced but not yet yielded, including buffered ones
+    pub behind: u32,
+    /// Blocks fetched but not yet yielded
+    pub buffered: usize,
+    /// Size of the last batch; 1 when fetching block by block
+    pub batch_size: u32,
+}
+
+impl StreamLag {
+    /// Whether the stream is fetching in batches to catch up
+    pub fn catching_up(&self) -> bool {
+        self.batch_size > 1
+    }
+
+    /// Approximate time behind the head, assuming one block every [`BLOCK_INTERVAL`]
+    pub fn behind_time(&self) -> Duration {
+        BLOCK_INTERVAL * self.behind
+    }
+}
+
+/// Blocking iterator over consecutive blocks, created by [`Client::stream_blocks`]
+///
+/// At the head block the stream polls every poll interval, or waits on the
+/// client's [`HeadNotifier`](crate::head_notify::HeadNotifier) if one is set.
+///
+/// When the consumer falls more than the catch-up threshold behind the head,
+/// blocks are fetched with `block_api.get_block_range` in batches that double
+/// in size up to the maximum batch size; once caught up the stream returns to
+/// fetching one block at a time.
+#[derive(Debug)]
+pub struct BlockStream<'a> {
+    client: &'a Client,
+    next: u32,
+    head: u32,
+    mode: StreamMode,
+    poll_interval: Duration,
+    buffer: VecDeque<StreamedBlock>,
+    batch_size: u32,
+    catch_up_threshold: u32,
+    max_batch_size: u32,
+}
+
+impl BlockStream<'_> {
+    /// Only fetch block headers instead of full blocks
+    pub fn header_only(mut self) -> Self {
+        self.mode = StreamMode::HeaderOnly;
+        self
+    }
+
+    /// Set the fetch mode
+    pub fn mode(mut self, mode: StreamMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set how long to wait before polling again once the head block is reached
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Start batch fetching once more than `blocks` behind the head
+    pub fn catch_up_threshold(mut self, blocks: u32) -> Self {
+        self.catch_up_threshold = blocks;
+        self
+    }
+
+    /// Largest batch fetched while catching up; 1 disables batch fetching
+    pub fn max_batch_size(mut self, blocks: u32) -> Self {
+        self.max_batch_size = blocks.clamp(1, MAX_BLOCK_RANGE);
+        self
+    }
+
+    /// The number of the next block this stream will yield
+    pub fn next_block_num(&self) -> u32 {
+        self.buffer.front().map_or(self.next, |b| b.block_num)
+    }
+
+    /// Position of the next block to yield
+    pub fn cursor(&self) -> Cursor {
+        Cursor::Blocks {
+            next: self.next_block_num(),
+        }
+    }
+
+    /// Continue from a cursor of an earlier block stream
+    pub fn resume(mut self, cursor: &Cursor) -> Result<Self, String> {
+        let Cursor::Blocks { next } = cursor else {
+            return Err(cursor.mismatch("block stream"));
+        };
+        self.next = *next;
+        self.buffer.clear();
+        Ok(self)
+    }
+
+    /// How far the stream is behind the head block
+    pub fn lag(&self) -> StreamLag {
+        let next_block = self.next_block_num();
+        StreamLag {
+            next_block,
+            head_block: self.head,
+            behind: (self.head + 1).saturating_sub(next_block),
+            buffered: self.buffer.len(),
+            batch_size: self.batch_size,
+        }
+    }
+
+    pub(crate) fn client(&self) -> &Client {
+        self.client
+    }
+
+    fn fetch(&self, block_num: u32) -> Result<Option<StreamedBlock>, String> {
+        match self.mode {
+            StreamMode::Full => Ok(self
+                .client
+                .get_block(block_num)?
+                .map(|block| StreamedBlock {
+                    block_num,
+                    header: block.header.clone(),
+                    block: Some(block),
+                })),
+            StreamMode::HeaderOnly => {
+                Ok(self
+                    .client
+                    .get_block_header(block_num)?
+                    .map(|header| StreamedBlock {
+                        block_num,
+                        header,
+                        block: None,
+                    }))
+            }
+        }
+    }
+
+    /// Wait for the next block, by subscription if the client has one
+    fn wait_for_next(&mut self) {
+        if let Some(head) = self.client.wait_for_block(self.next, self.poll_interval) {
+            self.head = self.head.max(head);
+        }
+    }
+
+    /// Fetch the next batch into the buffer, doubling the batch size
+    fn fetch_batch(&mut self, behind: u32) -> Result<(), String> {
+        self.batch_size = (self.batch_size * 2).min(self.max_batch_size);
+        let blocks = self
+            .client
+            .get_block_range(self.next, self.batch_size.min(behind))?;
+        for block in blocks {
+            let header = block.header.clone();
+            self.buffer.push_back(StreamedBlock {
+                block_num: self.next,
+                header,
+                block: (self.mode == StreamMode::Full).then_some(block),
+            });
+            self.next += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for BlockStream<'_> {
+    type Item = Result<StreamedBlock, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = self.buffer.pop_front() {
+                return Some(Ok(block));
+            }
+            if self.next > self.head || self.batch_size > 1 {
+                match self.client.get_dynamic_global_properties() {
+                    Ok(props) => self.head = props.head_block_number,
+                    Err(e) => return Some(Err(e)),
+                }
+                if self.next > self.head {
+                    self.batch_size = 1;
+                    self.wait_for_next();
+                    continue;
+                }
+            }
+            let behind = self.head - self.next + 1;
+            if behind > self.catch_up_threshold && self.max_batch_size > 1 {
+                match self.fetch_batch(behind) {
+                    Ok(()) if self.buffer.is_empty() => thread::sleep(self.poll_interval),
+                    Ok(()) => {}
+                    Err(e) => return Some(Err(e)),
+                }
+                continue;
+            }
+            self.batch_size = 1;
+            match self.fetch(self.next) {
+                Ok(Some(block)) => {
+                    self.next += 1;
+                    return Some(Ok(block));
+                }
+                Ok(None) => self.wait_for_next(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
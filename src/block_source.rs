@@ -0,0 +1,191 @@
+//! Block sources for indexers: live RPC streams or previously dumped block files
+//!
+//! Indexing logic written against [`BlockSource`] can be re-run offline and
+//! deterministically against captured data.
+
+use crate::block::{Block, BlockStream, StreamedBlock};
+use crate::msgpack::{MsgpackReader, MsgpackWriter, STREAM_MAGIC};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Write};
+use std::path::Path;
+
+/// A sequential supply of blocks
+pub trait BlockSource {
+    /// The next block, or `None` once the source is exhausted
+    fn next_block(&mut self) -> Option<Result<StreamedBlock, String>>;
+
+    /// The last irreversible block number as seen by this source
+    fn last_irreversible_block_num(&mut self) -> Result<u32, String>;
+
+    /// Fetch `block_num` again once it is irreversible, to confirm what was
+    /// read while it was still reversible
+    ///
+    /// `None` means the block is not available yet and should be asked for
+    /// again later.
+    fn irreversible_block(&mut self, _block_num: u32) -> Result<Option<Block>, String> {
+        Ok(None)
+    }
+
+    /// Whether every block from [`BlockSource::next_block`] is already
+    /// irreversible, so it needs no re-check, as with a dump file
+    fn streams_irreversible(&self) -> bool {
+        false
+    }
+}
+
+impl BlockSource for BlockStream<'_> {
+    fn next_block(&mut self) -> Option<Result<StreamedBlock, String>> {
+        self.next()
+    }
+
+    fn last_irreversible_block_num(&mut self) -> Result<u32, String> {
+        Ok(self
+            .client()
+            .get_dynamic_global_properties()?
+            .last_irreversible_block_num)
+    }
+
+    fn irreversible_block(&mut self, block_num: u32) -> Result<Option<Block>, String> {
+        self.client().get_block(block_num)
+    }
+}
+
+/// On-disk layout of a block dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFileFormat {
+    /// One JSON-encoded [`StreamedBlock`] per line
+    JsonLines,
+    /// The MessagePack stream format from [`crate::msgpack`]
+    Msgpack,
+}
+
+enum Records {
+    JsonLines(Lines<BufReader<File>>),
+    Msgpack(MsgpackReader<File, StreamedBlock>),
+}
+
+/// Replays blocks from a dump file
+///
+/// Every block in the file is treated as irreversible once it has been read.
+pub struct FileBlockSource {
+    records: Records,
+    last_block_num: u32,
+}
+
+impl std::fmt::Debug for FileBlockSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileBlockSource")
+            .field("last_block_num", &self.last_block_num)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileBlockSource {
+    /// Open a dump file, detecting its format from the header
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let mut magic = [0u8; 4];
+        let is_msgpack = File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok()
+            && &magic == STREAM_MAGIC;
+        let format = if is_msgpack {
+            BlockFileFormat::Msgpack
+        } else {
+            BlockFileFormat::JsonLines
+        };
+        Self::open_as(path, format)
+    }
+
+    /// Open a dump file in a known format
+    pub fn open_as<P: AsRef<Path>>(path: P, format: BlockFileFormat) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).map_err(|e| format!("Error opening {}: {e}", path.display()))?;
+        let records = match format {
+            BlockFileFormat::JsonLines => Records::JsonLines(BufReader::new(file).lines()),
+            BlockFileFormat::Msgpack => Records::Msgpack(MsgpackReader::new(file)?),
+        };
+        Ok(Self {
+            records,
+            last_block_num: 0,
+        })
+    }
+}
+
+impl BlockSource for FileBlockSource {
+    fn next_block(&mut self) -> Option<Result<StreamedBlock, String>> {
+        let block = match &mut self.records {
+            Records::JsonLines(lines) => loop {
+                match lines.next()? {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => {
+                        break serde_json::from_str(&line)
+                            .map_err(|e| format!("Error decoding block: {e}"))
+                    }
+                    Err(e) => break Err(format!("Read error: {e}")),
+                }
+            },
+            Records::Msgpack(reader) => reader.next()?,
+        };
+        if let Ok(block) = &block {
+            self.last_block_num = block.block_num;
+        }
+        Some(block)
+    }
+
+    fn last_irreversible_block_num(&mut self) -> Result<u32, String> {
+        Ok(self.last_block_num)
+    }
+
+    fn streams_irreversible(&self) -> bool {
+        true
+    }
+}
+
+enum Sink {
+    JsonLines(BufWriter<File>),
+    Msgpack(MsgpackWriter<File>),
+}
+
+/// Writes blocks to a dump file readable by [`FileBlockSource`]
+pub struct BlockFileWriter {
+    sink: Sink,
+}
+
+impl std::fmt::Debug for BlockFileWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockFileWriter").finish_non_exhaustive()
+    }
+}
+
+impl BlockFileWriter {
+    pub fn create<P: AsRef<Path>>(path: P, format: BlockFileFormat) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).map_err(|e| format!("Error creating {}: {e}", path.display()))?;
+        let sink = match format {
+            BlockFileFormat::JsonLines => Sink::JsonLines(BufWriter::new(file)),
+            BlockFileFormat::Msgpack => Sink::Msgpack(MsgpackWriter::new(file)?),
+        };
+        Ok(Self { sink })
+    }
+
+    pub fn write(&mut self, block: &StreamedBlock) -> Result<(), String> {
+        match &mut self.sink {
+            Sink::JsonLines(writer) => {
+                let line =
+                    serde_json::to_string(block).map_err(|e| format!("Encode error: {e}"))?;
+                writeln!(writer, "{line}").map_err(|e| format!("Write error: {e}"))
+            }
+            Sink::Msgpack(writer) => writer.write(block),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        match &mut self.sink {
+            Sink::JsonLines(writer) => writer.flush().map_err(|e| format!("Write error: {e}")),
+            Sink::Msgpack(writer) => writer.flush(),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Configuring a [`Client`] before it is built
+//!
+//! ```
+//! use nectarflower_rs::Client;
+//! use std::time::Duration;
+//!
+//! let client = Client::builder()
+//!     .nodes(["https://api.hive.blog", "https://api.deathwing.me"])
+//!     .timeout(Duration::from_secs(5))
+//!     .connect_timeout(Duration::from_secs(2))
+//!     .user_agent("my-bot/1.0")
+//!     .max_retries(1)
+//!     .build();
+//! assert_eq!(client.nodes.len(), 2);
+//! ```
+
+use crate::dns::HttpSettings;
+use crate::retry::RetryPolicy;
+use crate::transport::Transport;
+use crate::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// The node of a client built without one
+pub const DEFAULT_NODE: &str = "https://api.hive.blog";
+
+/// Settings for a new [`Client`], created by [`Client::builder`]
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    nodes: Vec<String>,
+    http: HttpSettings,
+    max_retries: Option<usize>,
+    retry: RetryPolicy,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// [`DEFAULT_NODE`], [`DEFAULT_TIMEOUT`](crate::dns::DEFAULT_TIMEOUT), all nodes tried
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![DEFAULT_NODE.to_string()],
+            http: HttpSettings::default(),
+            max_retries: None,
+            retry: RetryPolicy::new(),
+            transport: None,
+        }
+    }
+
+    /// The initial node list, replacing [`DEFAULT_NODE`]
+    pub fn nodes<I, S>(mut self, nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.nodes = nodes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Timeout of a whole request, including reading the response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = timeout;
+        self
+    }
+
+    /// Timeout of connecting to a node; by default only the request timeout applies
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// The `User-Agent` header sent to nodes
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.http.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Try at most `retries` further nodes after the first one fails
+    pub fn max_retries(mut self, retries: usize) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+
+    /// Retry transient failures, see [`RetryPolicy`]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Send JSON-RPC requests through `transport`, e.g. a [`MockTransport`](crate::transport::MockTransport) in tests
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn build(self) -> Client {
+        Client {
+            nodes: self.nodes,
+            failing_nodes: HashMap::new(),
+            http_transport: OnceLock::new(),
+            http: self.http,
+            max_retries: self.max_retries,
+            retry: self.retry,
+            verify_chain: false,
+            social: Default::default(),
+            max_response_bytes: None,
+            chain_clock: Default::default(),
+            node_listeners: Default::default(),
+            cold_nodes: Default::default(),
+            chain: Default::default(),
+            empty_node_policy: Default::default(),
+            head_subscription: Default::default(),
+            transport: self.transport,
+        }
+    }
+}
+
+impl Client {
+    /// Configure a client before building it
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// The nodes a call tries, in order, within the retry limit
+    pub(crate) fn nodes_to_try(&self) -> Vec<&str> {
+        let mut nodes = self.nodes_by_warmth();
+        if let Some(retries) = self.max_retries {
+            nodes.truncate(retries.saturating_add(1));
+        }
+        nodes
+    }
+}
@@ -0,0 +1,50 @@
+//! Canonical JSON snapshots of accounts and witnesses
+//!
+//! [`Account::to_canonical_json`] and [`Witness::to_canonical_json`] print
+//! one field per line with keys in sorted order and assets in their
+//! `"1.000 HIVE"` form, whether the node sent them as strings or NAI objects.
+//! The same state always gives the same text, so snapshots taken over time
+//! can be stored and compared with a plain line diff.
+
+use crate::witness::Witness;
+use crate::Account;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+impl Account {
+    /// The account as pretty-printed JSON with sorted keys
+    pub fn to_canonical_json(&self) -> String {
+        to_canonical_json(self)
+    }
+}
+
+impl Witness {
+    /// The witness as pretty-printed JSON with sorted keys
+    pub fn to_canonical_json(&self) -> String {
+        to_canonical_json(self)
+    }
+}
+
+/// `value` as pretty-printed JSON with the keys of every object sorted
+pub fn to_canonical_json<T: Serialize>(value: &T) -> String {
+    let value = serde_json::to_value(value).expect("snapshot serializes to JSON");
+    serde_json::to_string_pretty(&sort_keys(value)).expect("JSON value serializes")
+}
+
+/// Rebuild objects in key order, whatever map type serde_json was built with
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
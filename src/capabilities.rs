@@ -0,0 +1,238 @@
+//! What the current node set can do, for degrading gracefully
+//!
+//! [`Client::capabilities`] asks every node for its methods
+//! (`jsonrpc.get_methods`) and summarizes which features are served by all,
+//! some or none of them, so applications can hide a feature in their UI
+//! instead of failing when it is used:
+//!
+//! ```no_run
+//! use nectarflower_rs::capabilities::Capability;
+//! use nectarflower_rs::Client;
+//!
+//! let capabilities = Client::new().capabilities();
+//! println!("{capabilities}"); // history: yes, broadcast: yes, bridge: partial, ...
+//! let show_history_tab = capabilities.support(Capability::History).is_available();
+//! ```
+
+use crate::methods::bridge;
+use crate::Client;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A feature an application may depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Account history and virtual operations (`account_history_api`)
+    History,
+    /// Broadcasting transactions (`network_broadcast_api`)
+    Broadcast,
+    /// Hivemind's social API (`bridge`)
+    Bridge,
+    /// The internal market (`market_history_api`)
+    Market,
+    /// Resource credits (`rc_api`)
+    ResourceCredits,
+    /// Status of recent transactions (`transaction_status_api`)
+    TransactionStatus,
+    /// Raw account reputation (`reputation_api`)
+    Reputation,
+}
+
+impl Capability {
+    pub const ALL: &'static [Capability] = &[
+        Capability::History,
+        Capability::Broadcast,
+        Capability::Bridge,
+        Capability::Market,
+        Capability::ResourceCredits,
+        Capability::TransactionStatus,
+        Capability::Reputation,
+    ];
+
+    /// The API serving this feature
+    pub fn api(self) -> &'static str {
+        match self {
+            Capability::History => "account_history_api",
+            Capability::Broadcast => "network_broadcast_api",
+            Capability::Bridge => "bridge",
+            Capability::Market => "market_history_api",
+            Capability::ResourceCredits => "rc_api",
+            Capability::TransactionStatus => "transaction_status_api",
+            Capability::Reputation => "reputation_api",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::History => "history",
+            Capability::Broadcast => "broadcast",
+            Capability::Bridge => "bridge",
+            Capability::Market => "market",
+            Capability::ResourceCredits => "rc",
+            Capability::TransactionStatus => "transaction status",
+            Capability::Reputation => "reputation",
+        }
+    }
+
+    /// A cheap call that succeeds when the API is served outside `hived`,
+    /// and so is missing from its method list
+    fn probe(self) -> Option<(&'static str, Value)> {
+        match self {
+            Capability::Bridge => Some((bridge::LIST_COMMUNITIES, json!({ "limit": 1 }))),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How much of the node set serves a [`Capability`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    /// Every reachable node
+    Full,
+    /// Some reachable nodes; calls may need to fail over
+    Partial,
+    /// No reachable node
+    Unavailable,
+}
+
+impl Support {
+    /// Whether at least one node serves the feature
+    pub fn is_available(self) -> bool {
+        self != Support::Unavailable
+    }
+}
+
+impl fmt::Display for Support {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Support::Full => "yes",
+            Support::Partial => "partial",
+            Support::Unavailable => "no",
+        })
+    }
+}
+
+/// The APIs one node serves, or why it could not be asked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeCapabilities {
+    pub node: String,
+    pub apis: Result<BTreeSet<String>, String>,
+}
+
+impl NodeCapabilities {
+    /// The APIs of the methods a node lists
+    pub fn from_methods<S: AsRef<str>>(node: &str, methods: &[S]) -> Self {
+        let apis = methods
+            .iter()
+            .filter_map(|method| Some(method.as_ref().split_once('.')?.0.to_string()))
+            .collect();
+        Self {
+            node: node.to_string(),
+            apis: Ok(apis),
+        }
+    }
+
+    /// Whether the node answered at all
+    pub fn is_reachable(&self) -> bool {
+        self.apis.is_ok()
+    }
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.apis
+            .as_ref()
+            .is_ok_and(|apis| apis.contains(capability.api()))
+    }
+}
+
+/// What the current node set can do
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub nodes: Vec<NodeCapabilities>,
+}
+
+impl Capabilities {
+    /// How many reachable nodes serve `capability`; unreachable nodes are not counted
+    pub fn support(&self, capability: Capability) -> Support {
+        let reachable = self.nodes.iter().filter(|node| node.is_reachable()).count();
+        match self.nodes_with(capability).len() {
+            0 => Support::Unavailable,
+            n if n == reachable => Support::Full,
+            _ => Support::Partial,
+        }
+    }
+
+    /// The nodes serving `capability`
+    pub fn nodes_with(&self, capability: Capability) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|node| node.supports(capability))
+            .map(|node| node.node.as_str())
+            .collect()
+    }
+
+    /// Nodes that could not be asked, with the error
+    pub fn unreachable(&self) -> Vec<(&str, &str)> {
+        self.nodes
+            .iter()
+            .filter_map(|node| Some((node.node.as_str(), node.apis.as_ref().err()?.as_str())))
+            .collect()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<_> = Capability::ALL
+            .iter()
+            .map(|&capability| format!("{capability}: {}", self.support(capability)))
+            .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl Client {
+    /// Detect which features each node serves
+    ///
+    /// Makes one `jsonrpc.get_methods` call per node, plus a probe for APIs
+    /// such as `bridge` that are served next to `hived` rather than by it.
+    pub fn capabilities(&self) -> Capabilities {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| self.node_capabilities(node))
+            .collect();
+        Capabilities { nodes }
+    }
+
+    fn node_capabilities(&self, node: &str) -> NodeCapabilities {
+        let methods: Vec<String> = match self.call_node(node, "jsonrpc.get_methods", &json!({})) {
+            Ok(methods) => methods,
+            Err(error) => {
+                return NodeCapabilities {
+                    node: node.to_string(),
+                    apis: Err(error.to_string()),
+                }
+            }
+        };
+        let mut capabilities = NodeCapabilities::from_methods(node, &methods);
+        for &capability in Capability::ALL {
+            let Some((method, params)) = capability.probe() else {
+                continue;
+            };
+            if !capabilities.supports(capability)
+                && self.call_node::<_, Value>(node, method, &params).is_ok()
+            {
+                if let Ok(apis) = &mut capabilities.apis {
+                    apis.insert(capability.api().to_string());
+                }
+            }
+        }
+        capabilities
+    }
+}
@@ -0,0 +1,287 @@
+//! Chain parameters, so clients for Hive, its testnets and forks can coexist
+//!
+//! A [`ChainConfig`] holds what differs between chains: the chain id signed
+//! over, the public key address prefix and the names of the liquid assets.
+//! Each [`Client`] carries one, [`ChainAsset`] tags amounts with their chain
+//! so HIVE and TESTS are never added together, and a [`ChainRegistry`] looks
+//! configurations up by name or chain id.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::crypto::decode_public_key_with_prefix;
+use crate::node_policy::EmptyNodePolicy;
+use crate::transaction::HIVE_CHAIN_ID;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Chain id of hived built as a testnet
+pub const TESTNET_CHAIN_ID: &str =
+    "18dcf0a285365fc58b71f18b3d3fec954aa0c141c44e4e5cb4cf777b9eab274e";
+
+/// Parameters of one chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Name to look the chain up by, e.g. `hive`
+    pub name: String,
+    pub chain_id: String,
+    /// Prefix of public keys, e.g. `STM`
+    pub address_prefix: String,
+    /// Name of the liquid core asset, e.g. `HIVE`
+    pub hive_symbol: String,
+    /// Name of the dollar asset, e.g. `HBD`
+    pub hbd_symbol: String,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+impl ChainConfig {
+    /// A chain with the main network's prefix and asset names
+    pub fn new(name: &str, chain_id: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            chain_id: chain_id.to_string(),
+            address_prefix: "STM".to_string(),
+            hive_symbol: "HIVE".to_string(),
+            hbd_symbol: "HBD".to_string(),
+        }
+    }
+
+    /// The Hive main network
+    pub fn mainnet() -> Self {
+        Self::new("hive", HIVE_CHAIN_ID)
+    }
+
+    /// A hived testnet, which uses `TST` keys and TESTS/TBD
+    pub fn testnet(chain_id: &str) -> Self {
+        Self::new("testnet", chain_id)
+            .address_prefix("TST")
+            .symbols("TESTS", "TBD")
+    }
+
+    pub fn address_prefix(mut self, prefix: &str) -> Self {
+        self.address_prefix = prefix.to_string();
+        self
+    }
+
+    /// Names of the core and dollar assets
+    pub fn symbols(mut self, hive: &str, hbd: &str) -> Self {
+        self.hive_symbol = hive.to_string();
+        self.hbd_symbol = hbd.to_string();
+        self
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.chain_id == HIVE_CHAIN_ID
+    }
+
+    /// This chain's name for `symbol`
+    pub fn symbol_name(&self, symbol: AssetSymbol) -> &str {
+        match symbol {
+            AssetSymbol::Hive => &self.hive_symbol,
+            AssetSymbol::Hbd => &self.hbd_symbol,
+            AssetSymbol::Vests => "VESTS",
+        }
+    }
+
+    /// The symbol name `symbol` is serialized with in transactions
+    ///
+    /// The main network still serializes HIVE and HBD under their Steem
+    /// names; testnets use their own names.
+    pub fn wire_symbol(&self, symbol: AssetSymbol) -> &str {
+        match self.symbol_name(symbol) {
+            "HIVE" => "STEEM",
+            "HBD" => "SBD",
+            name => name,
+        }
+    }
+
+    /// The symbol this chain names `name`, under its own or its wire name
+    pub fn symbol_from_name(&self, name: &str) -> Option<AssetSymbol> {
+        [AssetSymbol::Hive, AssetSymbol::Hbd, AssetSymbol::Vests]
+            .into_iter()
+            .find(|&symbol| self.symbol_name(symbol) == name || self.wire_symbol(symbol) == name)
+    }
+
+    /// Parse a legacy string or NAI amount, with this chain's symbol names
+    pub fn asset_from_value(&self, value: &Value) -> Result<Asset, String> {
+        Asset::from_value_with(value, |name| self.symbol_from_name(name))
+    }
+
+    /// `asset` in the legacy format with this chain's symbol, e.g. `1.000 TESTS`
+    pub fn format_asset(&self, asset: Asset) -> String {
+        format!(
+            "{} {}",
+            asset.amount_string(),
+            self.symbol_name(asset.symbol)
+        )
+    }
+
+    /// Parse a legacy amount, rejecting symbols of other chains
+    pub fn parse_asset(&self, s: &str) -> Result<ChainAsset, String> {
+        let asset = Asset::parse_with(s, |name| self.symbol_from_name(name))?;
+        let name = s.trim().rsplit(' ').next().unwrap_or_default();
+        if name != self.symbol_name(asset.symbol) {
+            return Err(format!("'{s}' is not an amount on {}", self.name));
+        }
+        Ok(self.asset(asset))
+    }
+
+    /// Tag `asset` as an amount on this chain
+    pub fn asset(&self, asset: Asset) -> ChainAsset {
+        ChainAsset {
+            chain_id: self.chain_id.clone(),
+            symbol: self.symbol_name(asset.symbol).to_string(),
+            asset,
+        }
+    }
+
+    /// Decode a public key, which must carry this chain's address prefix
+    pub fn decode_public_key(&self, key: &str) -> Result<[u8; 33], String> {
+        decode_public_key_with_prefix(key, &self.address_prefix)
+    }
+}
+
+/// An amount tagged with the chain it belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChainAsset {
+    chain_id: String,
+    /// The chain's name for the asset's symbol
+    symbol: String,
+    asset: Asset,
+}
+
+impl ChainAsset {
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    pub fn asset(&self) -> Asset {
+        self.asset
+    }
+
+    /// Add an amount of the same asset on the same chain
+    pub fn checked_add(&self, other: &ChainAsset) -> Result<ChainAsset, String> {
+        self.same_chain(other)?;
+        let asset = self
+            .asset
+            .checked_add(other.asset)
+            .ok_or_else(|| format!("Cannot add {} to {}", other.asset, self.asset))?;
+        Ok(ChainAsset {
+            asset,
+            ..self.clone()
+        })
+    }
+
+    /// Subtract an amount of the same asset on the same chain
+    pub fn checked_sub(&self, other: &ChainAsset) -> Result<ChainAsset, String> {
+        self.same_chain(other)?;
+        let asset = self
+            .asset
+            .checked_sub(other.asset)
+            .ok_or_else(|| format!("Cannot subtract {} from {}", other.asset, self.asset))?;
+        Ok(ChainAsset {
+            asset,
+            ..self.clone()
+        })
+    }
+
+    fn same_chain(&self, other: &ChainAsset) -> Result<(), String> {
+        if self.chain_id != other.chain_id {
+            return Err(format!(
+                "Cannot mix amounts of chains {} and {}",
+                self.chain_id, other.chain_id
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ChainAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.asset.amount_string(), self.symbol)
+    }
+}
+
+/// Known chains by name and chain id
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: Vec<ChainConfig>,
+}
+
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        Self {
+            chains: vec![ChainConfig::mainnet()],
+        }
+    }
+}
+
+impl ChainRegistry {
+    /// A registry knowing only the main network
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chain; names and chain ids must be unique
+    pub fn register(&mut self, config: ChainConfig) -> Result<(), String> {
+        if let Some(existing) = self
+            .chains
+            .iter()
+            .find(|c| c.name == config.name || c.chain_id == config.chain_id)
+        {
+            return Err(format!(
+                "Chain '{}' conflicts with registered chain '{}'",
+                config.name, existing.name
+            ));
+        }
+        self.chains.push(config);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChainConfig> {
+        self.chains.iter().find(|c| c.name == name)
+    }
+
+    pub fn by_chain_id(&self, chain_id: &str) -> Option<&ChainConfig> {
+        self.chains.iter().find(|c| c.chain_id == chain_id)
+    }
+
+    pub fn chains(&self) -> impl Iterator<Item = &ChainConfig> {
+        self.chains.iter()
+    }
+
+    /// A client for the chain named `name`, reading from `nodes`
+    ///
+    /// The nodes are checked as by [`Client::set_nodes`]; with none usable,
+    /// this fails rather than keeping the main network's default node.
+    pub fn client(&self, name: &str, nodes: &[&str]) -> Result<Client, String> {
+        let config = self
+            .get(name)
+            .ok_or_else(|| format!("Unknown chain '{name}'"))?;
+        let mut client = Client::new()
+            .with_chain(config.clone())
+            .on_empty_node_list(EmptyNodePolicy::Error);
+        let nodes = nodes.iter().map(|node| node.to_string()).collect();
+        client.set_nodes(nodes, HashMap::new())?;
+        Ok(client.on_empty_node_list(EmptyNodePolicy::default()))
+    }
+}
+
+impl Client {
+    /// Use `config` for chain-specific values; the main network by default
+    pub fn with_chain(mut self, config: ChainConfig) -> Self {
+        self.chain = config;
+        self
+    }
+
+    /// The chain this client is configured for
+    pub fn chain(&self) -> &ChainConfig {
+        &self.chain
+    }
+}
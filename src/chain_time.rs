@@ -0,0 +1,119 @@
+//! Estimating chain time from node responses
+//!
+//! hived validates expirations against its head block time, not the wall
+//! clock of the machine that built the transaction. [`ChainClock`] measures
+//! the offset between the local clock and chain time so expirations can be
+//! set correctly on machines whose clock has drifted.
+
+use crate::block::BLOCK_INTERVAL;
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::transaction::MAX_EXPIRATION_SECS;
+use crate::{Client, Transaction};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long a measured offset is trusted before [`Client::chain_now`] re-syncs
+pub const CHAIN_CLOCK_RESYNC: Duration = Duration::from_secs(600);
+
+/// Longest expiration set from an estimated chain time, leaving a block of margin
+/// below [`MAX_EXPIRATION_SECS`] for the estimate running ahead of the head block
+const SAFE_MAX_EXPIRATION_SECS: i64 = MAX_EXPIRATION_SECS - BLOCK_INTERVAL.as_secs() as i64;
+
+/// Offset between the local clock and chain time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainClock {
+    offset_ms: i64,
+    rtt: Duration,
+    synced: Instant,
+}
+
+impl ChainClock {
+    /// Build from a head block time observed by a request sent at `sent` that took `rtt`
+    ///
+    /// The head block is on average half a block interval old, and the node
+    /// is assumed to have answered halfway through the round trip.
+    pub fn from_head_time(
+        head_time: &str,
+        sent: SystemTime,
+        rtt: Duration,
+    ) -> Result<Self, String> {
+        let chain_ms = parse_timestamp(head_time)? * 1000 + BLOCK_INTERVAL.as_millis() as i64 / 2;
+        let local_ms = unix_ms(sent) + rtt.as_millis() as i64 / 2;
+        Ok(Self {
+            offset_ms: chain_ms - local_ms,
+            rtt,
+            synced: Instant::now(),
+        })
+    }
+
+    /// Chain time minus local time in milliseconds; positive when the local clock is behind
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms
+    }
+
+    /// Round trip of the measuring request, an upper bound on the offset's error
+    pub fn rtt(&self) -> Duration {
+        self.rtt
+    }
+
+    /// Time since the offset was measured
+    pub fn age(&self) -> Duration {
+        self.synced.elapsed()
+    }
+
+    /// Current chain time in Unix seconds
+    pub fn now(&self) -> i64 {
+        (unix_ms(SystemTime::now()) + self.offset_ms).div_euclid(1000)
+    }
+
+    /// A chain timestamp `secs` from now, usable as a transaction expiration
+    pub fn expiration(&self, secs: i64) -> String {
+        format_timestamp(self.now() + secs.clamp(1, SAFE_MAX_EXPIRATION_SECS))
+    }
+}
+
+fn unix_ms(t: SystemTime) -> i64 {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+impl Client {
+    /// Measure the offset between the local clock and chain time now
+    pub fn sync_chain_time(&self) -> Result<ChainClock, String> {
+        self.get_dynamic_global_properties()?;
+        self.chain_clock()
+            .ok_or_else(|| "Chain time was not recorded".to_string())
+    }
+
+    /// The last measured clock offset, if any
+    ///
+    /// Every call to [`Client::get_dynamic_global_properties`] refreshes it.
+    pub fn chain_clock(&self) -> Option<ChainClock> {
+        *self.chain_clock.lock().unwrap()
+    }
+
+    /// Current chain time in Unix seconds, re-syncing once the offset is older than [`CHAIN_CLOCK_RESYNC`]
+    pub fn chain_now(&self) -> Result<i64, String> {
+        match self.chain_clock() {
+            Some(clock) if clock.age() < CHAIN_CLOCK_RESYNC => Ok(clock.now()),
+            _ => self.sync_chain_time().map(|clock| clock.now()),
+        }
+    }
+
+    /// Move an unsigned transaction's expiration to `secs` from the current chain time
+    ///
+    /// For transactions prepared earlier, e.g. while waiting for a user to
+    /// confirm; signatures made before this call become invalid.
+    pub fn refresh_expiration(&self, tx: &mut Transaction, secs: i64) -> Result<(), String> {
+        let now = self.chain_now()?;
+        tx.expiration = format_timestamp(now + secs.clamp(1, SAFE_MAX_EXPIRATION_SECS));
+        Ok(())
+    }
+
+    pub(crate) fn record_chain_time(&self, head_time: &str, sent: SystemTime, rtt: Duration) {
+        if let Ok(clock) = ChainClock::from_head_time(head_time, sent, rtt) {
+            *self.chain_clock.lock().unwrap() = Some(clock);
+        }
+    }
+}
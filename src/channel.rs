@@ -0,0 +1,326 @@
+//! Bounded, backpressure-aware channels for streaming blocks to another thread
+//!
+//! A fetcher thread pushes into a channel of fixed capacity; what happens when
+//! the consumer is too slow and the channel fills up is chosen with
+//! [`OverflowPolicy`], so a slow consumer never causes unbounded memory growth.
+
+use crate::block::StreamedBlock;
+use crate::block_source::BlockSource;
+use crate::Client;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What a sender does when the channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to make room
+    #[default]
+    Block,
+    /// Discard the oldest queued item to make room
+    DropOldest,
+    /// Fail the send and close the channel
+    Error,
+}
+
+/// Why a send failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+    /// The receiver was dropped
+    Disconnected,
+    /// The channel was full under [`OverflowPolicy::Error`]
+    Overflow,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Disconnected => f.write_str("Receiver disconnected"),
+            SendError::Overflow => f.write_str("Channel full"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    dropped: u64,
+    overflowed: bool,
+    senders: usize,
+    receiver: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+}
+
+/// Create a channel holding at most `capacity` items (at least 1)
+pub fn bounded<T>(capacity: usize, policy: OverflowPolicy) -> (StreamSender<T>, StreamReceiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            dropped: 0,
+            overflowed: false,
+            senders: 1,
+            receiver: true,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity: capacity.max(1),
+        policy,
+    });
+    (
+        StreamSender {
+            shared: Arc::clone(&shared),
+        },
+        StreamReceiver { shared },
+    )
+}
+
+/// Sending half of a [`bounded`] channel
+pub struct StreamSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> StreamSender<T> {
+    /// Queue `item`, applying the channel's [`OverflowPolicy`] when it is full
+    pub fn send(&self, item: T) -> Result<(), SendError> {
+        let shared = &self.shared;
+        let mut state = shared.lock();
+        loop {
+            if !state.receiver {
+                return Err(SendError::Disconnected);
+            }
+            if state.overflowed {
+                return Err(SendError::Overflow);
+            }
+            if state.queue.len() < shared.capacity {
+                break;
+            }
+            match shared.policy {
+                OverflowPolicy::Block => state = shared.not_full.wait(state).unwrap(),
+                OverflowPolicy::DropOldest => {
+                    state.queue.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Error => {
+                    state.overflowed = true;
+                    shared.not_empty.notify_all();
+                    return Err(SendError::Overflow);
+                }
+            }
+        }
+        state.queue.push_back(item);
+        shared.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Queue a final item regardless of capacity, e.g. the error ending a stream
+    fn send_last(&self, item: T) {
+        let mut state = self.shared.lock();
+        if state.receiver {
+            state.queue.push_back(item);
+            self.shared.not_empty.notify_one();
+        }
+    }
+}
+
+impl<T> Clone for StreamSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for StreamSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for StreamSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamSender")
+            .field("capacity", &self.shared.capacity)
+            .field("policy", &self.shared.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Receiving half of a [`bounded`] channel; iterating blocks until an item arrives
+pub struct StreamReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> StreamReceiver<T> {
+    /// Wait for the next item; `None` once every sender is gone and the queue is drained
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(item) = self.take(&mut state) {
+                return Some(item);
+            }
+            if state.senders == 0 {
+                return None;
+            }
+            state = self.shared.not_empty.wait(state).unwrap();
+        }
+    }
+
+    /// Wait at most `timeout` for the next item
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(item) = self.take(&mut state) {
+                return Some(item);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if state.senders == 0 || remaining.is_zero() {
+                return None;
+            }
+            state = self
+                .shared
+                .not_empty
+                .wait_timeout(state, remaining)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// The next item if one is queued
+    pub fn try_recv(&self) -> Option<T> {
+        self.take(&mut self.shared.lock())
+    }
+
+    /// Number of queued items
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Items discarded under [`OverflowPolicy::DropOldest`]
+    pub fn dropped(&self) -> u64 {
+        self.shared.lock().dropped
+    }
+
+    /// Whether a send failed under [`OverflowPolicy::Error`]
+    pub fn overflowed(&self) -> bool {
+        self.shared.lock().overflowed
+    }
+
+    fn take(&self, state: &mut State<T>) -> Option<T> {
+        let item = state.queue.pop_front()?;
+        self.shared.not_full.notify_one();
+        Some(item)
+    }
+}
+
+impl<T> Iterator for StreamReceiver<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
+
+impl<T> Drop for StreamReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+impl<T> fmt::Debug for StreamReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamReceiver")
+            .field("capacity", &self.shared.capacity)
+            .field("policy", &self.shared.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Blocks received from a fetcher thread; errors are passed through as items
+pub type BlockReceiver = StreamReceiver<Result<StreamedBlock, String>>;
+
+/// Drain `source` on a new thread into a bounded channel
+///
+/// The thread stops when the source is exhausted or the receiver is dropped.
+/// Under [`OverflowPolicy::Error`] the stream ends with an error item once
+/// the consumer falls `capacity` blocks behind.
+pub fn spawn_source<S>(source: S, capacity: usize, policy: OverflowPolicy) -> BlockReceiver
+where
+    S: BlockSource + Send + 'static,
+{
+    let (sender, receiver) = bounded(capacity, policy);
+    thread::spawn(move || {
+        let mut source = source;
+        pump(&mut source, &sender, capacity);
+    });
+    receiver
+}
+
+fn pump(
+    source: &mut dyn BlockSource,
+    sender: &StreamSender<Result<StreamedBlock, String>>,
+    capacity: usize,
+) {
+    while let Some(item) = source.next_block() {
+        match sender.send(item) {
+            Ok(()) => {}
+            Err(SendError::Disconnected) => return,
+            Err(SendError::Overflow) => {
+                sender.send_last(Err(format!(
+                    "Consumer fell behind: stream buffer of {capacity} blocks is full"
+                )));
+                return;
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Stream blocks from `start` on a background thread into a bounded channel
+    ///
+    /// See [`spawn_source`] for how the fetcher stops.
+    pub fn spawn_block_stream(
+        self: &Arc<Self>,
+        start: u32,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> BlockReceiver {
+        let client = Arc::clone(self);
+        let (sender, receiver) = bounded(capacity, policy);
+        thread::spawn(move || {
+            let mut stream = client.stream_blocks(start);
+            pump(&mut stream, &sender, capacity);
+        });
+        receiver
+    }
+}
@@ -0,0 +1,284 @@
+//! Writing posts and comments, including patch-based edits
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::links::{post_url, Frontend};
+use crate::operation::Operation;
+use crate::patch::patch_text;
+use crate::permlink::{generate_permlink, generate_reply_permlink, validate_permlink};
+use crate::posts::Beneficiary;
+use crate::sanitize::sanitize_body;
+use crate::tags::validate_tags;
+use crate::template::PostTemplate;
+use crate::transaction::TransactionSigner;
+use crate::{Client, WriteClient};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A post or comment as returned by `condenser_api.get_content`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Content {
+    pub author: String,
+    pub permlink: String,
+    pub parent_author: String,
+    pub parent_permlink: String,
+    pub title: String,
+    pub body: String,
+    pub json_metadata: String,
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub last_update: String,
+}
+
+impl Content {
+    /// Link to this post or comment on a front-end
+    pub fn url(&self, frontend: Frontend) -> String {
+        post_url(frontend, &self.author, &self.permlink)
+    }
+}
+
+/// Build a `comment` operation creating or updating a post or comment
+pub fn comment_operation(
+    parent_author: &str,
+    parent_permlink: &str,
+    author: &str,
+    permlink: &str,
+    title: &str,
+    body: &str,
+    json_metadata: &str,
+) -> Operation {
+    Operation::new(
+        "comment_operation",
+        serde_json::json!({
+            "parent_author": parent_author,
+            "parent_permlink": parent_permlink,
+            "author": author,
+            "permlink": permlink,
+            "title": title,
+            "body": body,
+            "json_metadata": json_metadata,
+        }),
+    )
+}
+
+/// Build a `comment_options` operation routing part of the author rewards to beneficiaries
+pub fn comment_options_operation(
+    author: &str,
+    permlink: &str,
+    beneficiaries: &[Beneficiary],
+) -> Operation {
+    let mut sorted = beneficiaries.to_vec();
+    sorted.sort_by(|a, b| a.account.cmp(&b.account));
+    let extensions = if sorted.is_empty() {
+        serde_json::json!([])
+    } else {
+        serde_json::json!([{
+            "type": "comment_payout_beneficiaries",
+            "value": { "beneficiaries": sorted },
+        }])
+    };
+    Operation::new(
+        "comment_options_operation",
+        serde_json::json!({
+            "author": author,
+            "permlink": permlink,
+            "max_accepted_payout": Asset::new(1_000_000_000, AssetSymbol::Hbd).to_nai(),
+            "percent_hbd": 10_000,
+            "allow_votes": true,
+            "allow_curation_rewards": true,
+            "extensions": extensions,
+        }),
+    )
+}
+
+/// A new top-level post or reply ready to be published
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostDraft {
+    pub author: String,
+    /// Generated from the title (or parent for replies) on publish when empty
+    #[serde(default)]
+    pub permlink: String,
+    /// Empty for top-level posts
+    #[serde(default)]
+    pub parent_author: String,
+    /// The category for top-level posts; set from the first tag when empty
+    #[serde(default)]
+    pub parent_permlink: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub beneficiaries: Vec<Beneficiary>,
+}
+
+impl PostDraft {
+    pub fn new(author: &str, permlink: &str, title: &str, body: &str) -> Self {
+        Self {
+            author: author.to_string(),
+            permlink: permlink.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Make this draft a reply to another post or comment
+    pub fn reply_to(mut self, parent_author: &str, parent_permlink: &str) -> Self {
+        self.parent_author = parent_author.to_string();
+        self.parent_permlink = parent_permlink.to_string();
+        self
+    }
+
+    pub fn json_metadata(&self) -> String {
+        metadata_json(&self.tags)
+    }
+
+    /// Normalized tags, with the category first for top-level posts
+    pub fn checked_tags(&self) -> Result<Vec<String>, String> {
+        let category = Some(self.parent_permlink.as_str())
+            .filter(|c| self.parent_author.is_empty() && !c.is_empty());
+        validate_tags(category, &self.tags)
+    }
+
+    /// Fill in a generated permlink if none was supplied
+    pub fn ensure_permlink(&mut self) {
+        if self.permlink.is_empty() {
+            self.permlink = if self.parent_author.is_empty() {
+                generate_permlink(&self.title)
+            } else {
+                generate_reply_permlink(&self.parent_author, &self.parent_permlink)
+            };
+        }
+    }
+
+    /// Link to the published post; call after [`PostDraft::ensure_permlink`]
+    pub fn url(&self, frontend: Frontend) -> String {
+        post_url(frontend, &self.author, &self.permlink)
+    }
+
+    /// Sanitize the body in place, returning warnings about content that was changed
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let report = sanitize_body(&self.body);
+        self.body = report.body;
+        report.warnings
+    }
+
+    /// The `comment` (and, with beneficiaries, `comment_options`) operations for this draft
+    pub fn operations(&self) -> Result<Vec<Operation>, String> {
+        validate_permlink(&self.permlink)?;
+        let tags = self.checked_tags()?;
+        let parent_permlink = if self.parent_author.is_empty() {
+            tags.first().cloned().ok_or_else(|| {
+                "A top-level post needs a category or at least one tag".to_string()
+            })?
+        } else {
+            self.parent_permlink.clone()
+        };
+        let mut ops = vec![comment_operation(
+            &self.parent_author,
+            &parent_permlink,
+            &self.author,
+            &self.permlink,
+            &self.title,
+            &self.body,
+            &metadata_json(&tags),
+        )];
+        if !self.beneficiaries.is_empty() {
+            ops.push(comment_options_operation(
+                &self.author,
+                &self.permlink,
+                &self.beneficiaries,
+            ));
+        }
+        Ok(ops)
+    }
+}
+
+fn metadata_json(tags: &[String]) -> String {
+    serde_json::json!({
+        "tags": tags,
+        "app": concat!("nectarflower-rs/", env!("CARGO_PKG_VERSION")),
+        "format": "markdown",
+    })
+    .to_string()
+}
+
+/// The body to broadcast for an edit: a diff-match-patch patch when shorter than the new body
+///
+/// The full body is sent when diffing takes longer than
+/// [`DIFF_TIMEOUT`](crate::patch::DIFF_TIMEOUT).
+pub fn edit_body(old: &str, new: &str) -> String {
+    match patch_text(old, new) {
+        Some(patch) if !patch.is_empty() && patch.len() < new.len() => patch,
+        _ => new.to_string(),
+    }
+}
+
+impl Client {
+    /// Fetch a post or comment; the author is empty if it does not exist
+    pub fn get_content(&self, author: &str, permlink: &str) -> Result<Content, String> {
+        self.call("condenser_api.get_content", (author, permlink))
+            .map_err(|e| format!("Error fetching content @{author}/{permlink}: {e}"))
+    }
+}
+
+impl WriteClient {
+    /// Publish a post draft, generating its permlink if needed, returning the transaction id
+    pub fn publish(
+        &self,
+        draft: &mut PostDraft,
+        signer: &dyn TransactionSigner,
+    ) -> Result<String, String> {
+        draft.ensure_permlink();
+        let mut tx = self.prepare_transaction(draft.operations()?)?;
+        let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+
+    /// Render a template and publish it in one call, returning the draft and transaction id
+    pub fn publish_template(
+        &self,
+        template: &PostTemplate,
+        author: &str,
+        permlink: Option<&str>,
+        vars: &HashMap<String, String>,
+        signer: &dyn TransactionSigner,
+    ) -> Result<(PostDraft, String), String> {
+        let mut draft = template.render(author, permlink, vars)?;
+        let trx_id = self.publish(&mut draft, signer)?;
+        Ok((draft, trx_id))
+    }
+
+    /// Edit an existing post or comment, returning the transaction id
+    ///
+    /// The body is sent as a patch when that is smaller than the full text;
+    /// the parent, title (unless replaced) and `json_metadata` are preserved.
+    pub fn edit_post(
+        &self,
+        author: &str,
+        permlink: &str,
+        new_body: &str,
+        new_title: Option<&str>,
+        signer: &dyn TransactionSigner,
+    ) -> Result<String, String> {
+        let content = self.get_content(author, permlink)?;
+        if content.author.is_empty() {
+            return Err(format!("Post @{author}/{permlink} not found"));
+        }
+        let op = comment_operation(
+            &content.parent_author,
+            &content.parent_permlink,
+            author,
+            permlink,
+            new_title.unwrap_or(&content.title),
+            &edit_body(&content.body, new_body),
+            &content.json_metadata,
+        );
+        let mut tx = self.prepare_transaction(vec![op])?;
+        let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+}
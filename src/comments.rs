@@ -0,0 +1,249 @@
+//! Consensus comment objects from `database_api.find_comments` and `list_comments`
+//!
+//! Unlike hivemind's [`Post`](crate::Post), these come straight from hived's
+//! state and include cashout times and rshares, which payout-window scanners
+//! need. Paid-out posts and comments stay in state, with a `cashout_time` of
+//! `1969-12-31T23:59:59`.
+
+use crate::asset::Asset;
+use crate::numbers::de_int;
+use crate::posts::Beneficiary;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Maximum number of comments `find_comments` and `list_comments` return per call
+pub const LIST_COMMENTS_LIMIT: u32 = 1000;
+
+/// A post or comment as stored in hived's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainComment {
+    #[serde(default, deserialize_with = "de_int")]
+    pub id: u64,
+    pub author: String,
+    pub permlink: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub parent_author: String,
+    #[serde(default)]
+    pub parent_permlink: String,
+    #[serde(default)]
+    pub root_author: String,
+    #[serde(default)]
+    pub root_permlink: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub json_metadata: String,
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub last_update: String,
+    #[serde(default)]
+    pub cashout_time: String,
+    #[serde(default, deserialize_with = "de_int")]
+    pub depth: u32,
+    #[serde(default, deserialize_with = "de_int")]
+    pub children: u32,
+    #[serde(default, deserialize_with = "de_int")]
+    pub net_rshares: i64,
+    #[serde(default, deserialize_with = "de_int")]
+    pub abs_rshares: i64,
+    #[serde(default, deserialize_with = "de_int")]
+    pub vote_rshares: i64,
+    #[serde(default, deserialize_with = "de_int")]
+    pub net_votes: i32,
+    #[serde(default = "Asset::zero_hbd")]
+    pub max_accepted_payout: Asset,
+    #[serde(default, deserialize_with = "de_int")]
+    pub percent_hbd: u16,
+    #[serde(default = "yes")]
+    pub allow_votes: bool,
+    #[serde(default = "yes")]
+    pub allow_curation_rewards: bool,
+    #[serde(default)]
+    pub beneficiaries: Vec<Beneficiary>,
+}
+
+fn yes() -> bool {
+    true
+}
+
+impl ChainComment {
+    /// Whether this is a top-level post rather than a reply
+    pub fn is_post(&self) -> bool {
+        self.parent_author.is_empty()
+    }
+
+    /// The start key continuing a listing in `order` from this comment
+    ///
+    /// `list_comments` includes the start key, so the next page begins with
+    /// this comment again.
+    pub fn start_key(&self, order: CommentOrder) -> CommentStart {
+        let (author, permlink) = (self.author.clone(), self.permlink.clone());
+        match order {
+            CommentOrder::ByCashoutTime => CommentStart::CashoutTime {
+                time: self.cashout_time.clone(),
+                author,
+                permlink,
+            },
+            CommentOrder::ByPermlink => CommentStart::Permlink { author, permlink },
+            CommentOrder::ByRoot => CommentStart::Root {
+                root_author: self.root_author.clone(),
+                root_permlink: self.root_permlink.clone(),
+                author,
+                permlink,
+            },
+            CommentOrder::ByParent => CommentStart::Parent {
+                parent_author: self.parent_author.clone(),
+                parent_permlink: self.parent_permlink.clone(),
+                author,
+                permlink,
+            },
+        }
+    }
+}
+
+/// Index walked by `database_api.list_comments`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentOrder {
+    /// By the time a comment pays out, soonest first
+    ByCashoutTime,
+    ByPermlink,
+    /// Replies grouped under their root post
+    ByRoot,
+    /// Direct replies grouped under their parent
+    ByParent,
+}
+
+impl CommentOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommentOrder::ByCashoutTime => "by_cashout_time",
+            CommentOrder::ByPermlink => "by_permlink",
+            CommentOrder::ByRoot => "by_root",
+            CommentOrder::ByParent => "by_parent",
+        }
+    }
+}
+
+/// Where a `list_comments` listing starts; each order has its own key
+///
+/// Empty author and permlink fields start at the first comment of the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentStart {
+    CashoutTime {
+        time: String,
+        author: String,
+        permlink: String,
+    },
+    Permlink {
+        author: String,
+        permlink: String,
+    },
+    Root {
+        root_author: String,
+        root_permlink: String,
+        author: String,
+        permlink: String,
+    },
+    Parent {
+        parent_author: String,
+        parent_permlink: String,
+        author: String,
+        permlink: String,
+    },
+}
+
+impl CommentStart {
+    /// Comments paying out at or after `time`
+    pub fn cashout_from(time: &str) -> Self {
+        Self::CashoutTime {
+            time: time.to_string(),
+            author: String::new(),
+            permlink: String::new(),
+        }
+    }
+
+    /// Direct replies to a post or comment
+    pub fn replies_to(parent_author: &str, parent_permlink: &str) -> Self {
+        Self::Parent {
+            parent_author: parent_author.to_string(),
+            parent_permlink: parent_permlink.to_string(),
+            author: String::new(),
+            permlink: String::new(),
+        }
+    }
+
+    pub fn order(&self) -> CommentOrder {
+        match self {
+            Self::CashoutTime { .. } => CommentOrder::ByCashoutTime,
+            Self::Permlink { .. } => CommentOrder::ByPermlink,
+            Self::Root { .. } => CommentOrder::ByRoot,
+            Self::Parent { .. } => CommentOrder::ByParent,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Self::CashoutTime {
+                time,
+                author,
+                permlink,
+            } => json!([time, author, permlink]),
+            Self::Permlink { author, permlink } => json!([author, permlink]),
+            Self::Root {
+                root_author: a,
+                root_permlink: p,
+                author,
+                permlink,
+            }
+            | Self::Parent {
+                parent_author: a,
+                parent_permlink: p,
+                author,
+                permlink,
+            } => json!([a, p, author, permlink]),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentsResponse {
+    comments: Vec<ChainComment>,
+}
+
+impl Client {
+    /// Fetch comments by author and permlink; ones not found are left out
+    pub fn find_comments(&self, keys: &[(&str, &str)]) -> Result<Vec<ChainComment>, String> {
+        let mut comments = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(LIST_COMMENTS_LIMIT as usize) {
+            let resp: CommentsResponse = self
+                .call("database_api.find_comments", json!({ "comments": chunk }))
+                .map_err(|e| format!("Error finding comments: {e}"))?;
+            comments.extend(resp.comments);
+        }
+        Ok(comments)
+    }
+
+    /// Fetch up to `limit` comments in the order of `start`, beginning at `start`
+    pub fn list_comments(
+        &self,
+        start: &CommentStart,
+        limit: u32,
+    ) -> Result<Vec<ChainComment>, String> {
+        let order = start.order().as_str();
+        let params = json!({
+            "start": start.to_value(),
+            "limit": limit.min(LIST_COMMENTS_LIMIT),
+            "order": order,
+        });
+        let resp: CommentsResponse = self
+            .call("database_api.list_comments", params)
+            .map_err(|e| format!("Error listing comments {order}: {e}"))?;
+        Ok(resp.comments)
+    }
+}
@@ -0,0 +1,165 @@
+//! Names familiar from beem (Python) and hive-js, mapped onto the native API
+//!
+//! Enabled with the `compat` feature to ease porting existing bots. New code
+//! should prefer the native methods these wrap.
+
+use crate::asset::Asset;
+use crate::comment::{comment_operation, Content};
+use crate::custom_json::CustomJsonBuilder;
+use crate::operation::Operation;
+use crate::transaction::TransactionSigner;
+use crate::{Account, Client, WriteClient};
+use serde_json::Value;
+
+/// beem's `Hive` blockchain instance
+pub type Hive = Client;
+
+/// beem's `Amount`
+pub type Amount = Asset;
+
+/// beem's `Comment`
+pub type Comment = Content;
+
+impl Client {
+    /// Fetch a single account, `None` if it does not exist (beem `Account(name)`)
+    pub fn get_account(&self, name: &str) -> Result<Option<Account>, String> {
+        Ok(self.find_accounts(&[name])?.into_iter().next())
+    }
+
+    /// Fetch several accounts (hive-js `api.getAccounts`)
+    pub fn get_accounts(&self, names: &[&str]) -> Result<Vec<Account>, String> {
+        Ok(self.find_accounts(names)?)
+    }
+}
+
+impl WriteClient {
+    /// hive-js style broadcaster signing every operation with `signer`
+    pub fn broadcast<'a>(&'a self, signer: &'a dyn TransactionSigner) -> Broadcast<'a> {
+        Broadcast {
+            client: self,
+            signer,
+        }
+    }
+}
+
+/// Single-operation broadcasts named after hive-js `broadcast.*`, created by [`WriteClient::broadcast`]
+///
+/// Each method returns the transaction id.
+pub struct Broadcast<'a> {
+    client: &'a WriteClient,
+    signer: &'a dyn TransactionSigner,
+}
+
+impl std::fmt::Debug for Broadcast<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Broadcast")
+            .field("chain", &self.client.chain().name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Broadcast<'_> {
+    /// Sign and broadcast arbitrary operations in one transaction
+    pub fn send(&self, operations: Vec<Operation>) -> Result<String, String> {
+        let mut tx = self.client.prepare_transaction(operations)?;
+        let trx_id = self.client.sign_transaction(&mut tx, &[self.signer])?;
+        self.client.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+
+    /// `weight` in basis points, -10000 to 10000
+    pub fn vote(
+        &self,
+        voter: &str,
+        author: &str,
+        permlink: &str,
+        weight: i16,
+    ) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "vote_operation",
+            serde_json::json!({
+                "voter": voter,
+                "author": author,
+                "permlink": permlink,
+                "weight": weight,
+            }),
+        )])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn comment(
+        &self,
+        parent_author: &str,
+        parent_permlink: &str,
+        author: &str,
+        permlink: &str,
+        title: &str,
+        body: &str,
+        json_metadata: &str,
+    ) -> Result<String, String> {
+        self.send(vec![comment_operation(
+            parent_author,
+            parent_permlink,
+            author,
+            permlink,
+            title,
+            body,
+            json_metadata,
+        )])
+    }
+
+    pub fn transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: Asset,
+        memo: &str,
+    ) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "transfer_operation",
+            serde_json::json!({
+                "from": from,
+                "to": to,
+                "amount": amount.to_nai(),
+                "memo": memo,
+            }),
+        )])
+    }
+
+    /// Posting-authority `custom_json` (hive-js `broadcast.customJson`)
+    pub fn custom_json(&self, account: &str, id: &str, json: Value) -> Result<String, String> {
+        self.send(vec![CustomJsonBuilder::new(id, account, json).operation()?])
+    }
+
+    pub fn account_witness_vote(
+        &self,
+        account: &str,
+        witness: &str,
+        approve: bool,
+    ) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "account_witness_vote_operation",
+            serde_json::json!({
+                "account": account,
+                "witness": witness,
+                "approve": approve,
+            }),
+        )])
+    }
+
+    pub fn delegate_vesting_shares(
+        &self,
+        delegator: &str,
+        delegatee: &str,
+        vesting_shares: Asset,
+    ) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "delegate_vesting_shares_operation",
+            serde_json::json!({
+                "delegator": delegator,
+                "delegatee": delegatee,
+                "vesting_shares": vesting_shares.to_nai(),
+            }),
+        )])
+    }
+}
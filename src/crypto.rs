@@ -0,0 +1,137 @@
+//! SHA-256, RIPEMD-160, base58 and hex helpers used for transaction digests, ids and keys
+
+use ripemd::Ripemd160;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Compute the SHA-256 digest of `data`
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Compute the RIPEMD-160 digest of `data`, as used in public key checksums
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(data).into()
+}
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decode base58, wiping intermediate buffers since the input may be a private key
+pub(crate) fn base58_decode(s: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(s.len()));
+    for c in s.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    let mut out = Zeroizing::new(Vec::with_capacity(zeros + bytes.len()));
+    out.resize(zeros, 0);
+    out.extend_from_slice(&bytes);
+    Some(out)
+}
+
+/// Encode bytes as base58
+pub(crate) fn base58_encode(data: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in data {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    std::iter::repeat_n(b'1', zeros)
+        .chain(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+/// Address prefixes of public keys on the main network and testnets
+const PUBLIC_KEY_PREFIXES: &[&str] = &["STM", "TST"];
+
+/// Decode a public key such as `STM6...` into its 33 compressed bytes, checking the checksum
+pub fn decode_public_key(key: &str) -> Result<[u8; 33], String> {
+    let prefix = PUBLIC_KEY_PREFIXES
+        .iter()
+        .find(|prefix| key.starts_with(*prefix))
+        .ok_or_else(|| format!("Public key '{key}' does not start with STM or TST"))?;
+    decode_public_key_with_prefix(key, prefix)
+}
+
+/// Decode a public key that must carry the address prefix `prefix`
+pub fn decode_public_key_with_prefix(key: &str, prefix: &str) -> Result<[u8; 33], String> {
+    let encoded = key
+        .strip_prefix(prefix)
+        .ok_or_else(|| format!("Public key '{key}' does not start with {prefix}"))?;
+    let bytes =
+        base58_decode(encoded).ok_or_else(|| format!("Public key '{key}' is not valid base58"))?;
+    if bytes.len() != 37 {
+        return Err(format!("Public key '{key}' has the wrong length"));
+    }
+    if ripemd160(&bytes[..33])[..4] != bytes[33..] {
+        return Err(format!("Public key '{key}' checksum does not match"));
+    }
+    let mut out = [0u8; 33];
+    out.copy_from_slice(&bytes[..33]);
+    Ok(out)
+}
+
+/// Encode 33 compressed public key bytes with the address prefix `prefix`, e.g. `STM6...`
+pub fn encode_public_key(bytes: &[u8; 33], prefix: &str) -> String {
+    let mut data = bytes.to_vec();
+    data.extend_from_slice(&ripemd160(bytes)[..4]);
+    format!("{prefix}{}", base58_encode(&data))
+}
+
+/// Recover the compressed public key that produced a hex-encoded 65-byte
+/// compact signature over `digest`
+pub fn recover_public_key(digest: &[u8; 32], signature: &str) -> Result<[u8; 33], String> {
+    let bytes = from_hex(signature)?;
+    if bytes.len() != 65 || !(27..=34).contains(&bytes[0]) {
+        return Err(format!(
+            "Signature '{signature}' is not a compact signature"
+        ));
+    }
+    let recovery_id = RecoveryId::from_i32(i32::from((bytes[0] - 27) & 3))
+        .map_err(|e| format!("Signature '{signature}': {e}"))?;
+    let signature = RecoverableSignature::from_compact(&bytes[1..], recovery_id)
+        .map_err(|e| format!("Signature '{signature}': {e}"))?;
+    let public = Secp256k1::verification_only()
+        .recover_ecdsa(&Message::from_digest(*digest), &signature)
+        .map_err(|e| format!("Cannot recover public key: {e}"))?;
+    Ok(public.serialize())
+}
+
+/// Encode bytes as lowercase hex
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decode a hex string into bytes
+pub fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return Err(format!("Invalid hex string: '{s}'"));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| format!("Invalid hex '{s}': {e}"))
+        })
+        .collect()
+}
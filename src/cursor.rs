@@ -0,0 +1,59 @@
+//! Serializable positions of pagination iterators
+//!
+//! Every paginating iterator can report a [`Cursor`] for the items it has
+//! yielded so far and be resumed from one, so a long crawl can persist its
+//! cursor and continue after a restart without fetching from the beginning.
+//! Cursors only record a position: settings such as page sizes and history
+//! filters must be applied again when resuming.
+
+use crate::history::HistoryOrder;
+use serde::{Deserialize, Serialize};
+
+/// Position in one account's history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryCursor {
+    pub account: String,
+    pub order: HistoryOrder,
+    /// Index of the last entry yielded; `None` before the first
+    pub after: Option<u64>,
+}
+
+/// Where a pagination iterator continues from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Cursor {
+    /// An [`AccountHistory`](crate::AccountHistory)
+    AccountHistory(HistoryCursor),
+    /// A merged history, one position per account
+    MergedHistory { accounts: Vec<HistoryCursor> },
+    /// An [`AccountScan`](crate::AccountScan), after the named account
+    Accounts { after: Option<String> },
+    /// A [`BlockStream`](crate::BlockStream), at the next block to yield
+    Blocks { next: u32 },
+}
+
+impl Cursor {
+    /// Encode as a compact string to persist
+    pub fn to_token(&self) -> String {
+        // Cursors hold only strings and numbers, which always encode
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_token(token: &str) -> Result<Self, String> {
+        serde_json::from_str(token).map_err(|e| format!("Invalid cursor token: {e}"))
+    }
+
+    /// Name of the iterator kind, for error messages
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::AccountHistory(_) => "account history",
+            Self::MergedHistory { .. } => "merged history",
+            Self::Accounts { .. } => "account scan",
+            Self::Blocks { .. } => "block stream",
+        }
+    }
+
+    pub(crate) fn mismatch(&self, expected: &str) -> String {
+        format!("Cannot resume a {expected} from a {} cursor", self.kind())
+    }
+}
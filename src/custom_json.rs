@@ -0,0 +1,314 @@
+//! Registry decoding `custom_json` operations into application types by id, and
+//! idempotent `custom_json` broadcasting
+
+use crate::block::Block;
+use crate::crypto::{sha256, to_hex};
+use crate::operation::Operation;
+use crate::transaction::TransactionSigner;
+use crate::WriteClient;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// Payload field carrying the idempotency key of an app-level operation
+pub const IDEMPOTENCY_FIELD: &str = "idempotency_key";
+
+/// The value of a `custom_json` operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomJson {
+    #[serde(default)]
+    pub required_auths: Vec<String>,
+    #[serde(default)]
+    pub required_posting_auths: Vec<String>,
+    pub id: String,
+    /// The payload as a JSON-encoded string
+    pub json: String,
+}
+
+impl CustomJson {
+    /// The account that signed this operation, preferring active over posting authority
+    pub fn signer(&self) -> Option<&str> {
+        self.required_auths
+            .first()
+            .or_else(|| self.required_posting_auths.first())
+            .map(String::as_str)
+    }
+
+    /// Parse the JSON payload
+    pub fn payload(&self) -> Result<Value, String> {
+        serde_json::from_str(&self.json)
+            .map_err(|e| format!("Invalid custom_json payload for '{}': {e}", self.id))
+    }
+
+    /// The idempotency key embedded by [`CustomJsonBuilder::idempotent`], if any
+    pub fn idempotency_key(&self) -> Option<String> {
+        self.payload()
+            .ok()?
+            .get(IDEMPOTENCY_FIELD)?
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+/// Derive an idempotency key from the operation content and a sequence number
+///
+/// The same id, account, payload and `sequence` always give the same key, so
+/// retrying a logical operation cannot produce a second distinct key. Give
+/// operations that are meant to repeat, such as two equal transfers, their
+/// own sequence numbers so they are not mistaken for retries.
+pub fn idempotency_key(id: &str, account: &str, payload: &Value, sequence: u64) -> String {
+    let canonical = serde_json::json!([id, account, payload, sequence]).to_string();
+    to_hex(&sha256(canonical.as_bytes())[..16])
+}
+
+/// Builds a `custom_json` operation, optionally carrying an idempotency key
+#[derive(Debug, Clone)]
+pub struct CustomJsonBuilder {
+    id: String,
+    account: String,
+    active: bool,
+    payload: Value,
+    key: Option<String>,
+}
+
+impl CustomJsonBuilder {
+    /// A `custom_json` signed with `account`'s posting authority
+    pub fn new(id: &str, account: &str, payload: Value) -> Self {
+        Self {
+            id: id.to_string(),
+            account: account.to_string(),
+            active: false,
+            payload,
+            key: None,
+        }
+    }
+
+    /// Require the active authority instead of posting
+    pub fn active(mut self) -> Self {
+        self.active = true;
+        self
+    }
+
+    /// Embed a key derived from the content and `sequence` with [`idempotency_key`]
+    ///
+    /// Retries of one logical operation reuse its sequence number.
+    pub fn idempotent(mut self, sequence: u64) -> Self {
+        self.key = Some(idempotency_key(
+            &self.id,
+            &self.account,
+            &self.payload,
+            sequence,
+        ));
+        self
+    }
+
+    /// Embed an application-chosen key, e.g. an order id
+    pub fn idempotency_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn operation(&self) -> Result<Operation, String> {
+        let mut payload = self.payload.clone();
+        if let Some(key) = &self.key {
+            let Some(obj) = payload.as_object_mut() else {
+                return Err(format!(
+                    "An idempotency key needs an object payload for '{}'",
+                    self.id
+                ));
+            };
+            obj.insert(IDEMPOTENCY_FIELD.to_string(), key.clone().into());
+        }
+        let auths = vec![self.account.clone()];
+        let (required_auths, required_posting_auths) = if self.active {
+            (auths, Vec::new())
+        } else {
+            (Vec::new(), auths)
+        };
+        let custom = CustomJson {
+            required_auths,
+            required_posting_auths,
+            id: self.id.clone(),
+            json: payload.to_string(),
+        };
+        Ok(Operation::new(
+            "custom_json_operation",
+            serde_json::to_value(custom).map_err(|e| e.to_string())?,
+        ))
+    }
+}
+
+/// Drops `custom_json` operations whose idempotency key was already seen
+///
+/// Keys are remembered per id and signer, up to `capacity` keys; operations
+/// without a key always pass.
+#[derive(Debug, Clone)]
+pub struct IdempotencyFilter {
+    seen: HashSet<(String, String, String)>,
+    order: VecDeque<(String, String, String)>,
+    capacity: usize,
+}
+
+impl IdempotencyFilter {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Whether this operation should be processed, remembering its key
+    pub fn first_seen(&mut self, custom: &CustomJson) -> bool {
+        let Some(key) = custom.idempotency_key() else {
+            return true;
+        };
+        let entry = (
+            custom.id.clone(),
+            custom.signer().unwrap_or_default().to_string(),
+            key,
+        );
+        if !self.seen.insert(entry.clone()) {
+            return false;
+        }
+        self.order.push_back(entry);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Decode a block's `custom_json` operations, skipping repeated idempotency keys
+    pub fn decode_block<'a, T>(
+        &mut self,
+        registry: &CustomOpRegistry<T>,
+        block: &'a Block,
+    ) -> Vec<(&'a str, CustomOp<T>)> {
+        block
+            .operations()
+            .into_iter()
+            .filter(|(_, op)| match op.value_as::<CustomJson>() {
+                Ok(custom) if op.name() == "custom_json" => self.first_seen(&custom),
+                _ => true,
+            })
+            .filter_map(|(trx_id, op)| registry.decode(&op).map(|custom| (trx_id, custom)))
+            .collect()
+    }
+}
+
+/// A decoded `custom_json` operation
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomOp<T> {
+    /// The id is registered and the payload decoded
+    Typed(T),
+    /// No decoder is registered for the id
+    Unregistered(CustomJson),
+    /// The id is registered but the payload did not decode
+    Invalid(CustomJson, String),
+}
+
+type Decoder<T> = Box<dyn Fn(&CustomJson, Value) -> Result<T, String> + Send + Sync>;
+
+/// Maps `custom_json` ids to decoders producing the application type `T`
+///
+/// Apps handling several protocols typically use an enum for `T` with a
+/// `From` impl per payload type.
+pub struct CustomOpRegistry<T> {
+    decoders: HashMap<String, Decoder<T>>,
+}
+
+impl<T> Default for CustomOpRegistry<T> {
+    fn default() -> Self {
+        Self {
+            decoders: HashMap::new(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for CustomOpRegistry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ids: Vec<&String> = self.decoders.keys().collect();
+        ids.sort();
+        f.debug_struct("CustomOpRegistry")
+            .field("ids", &ids)
+            .finish()
+    }
+}
+
+impl<T> CustomOpRegistry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode payloads with this id by deserializing them as `U`
+    pub fn register<U>(self, id: &str) -> Self
+    where
+        U: DeserializeOwned + Into<T>,
+    {
+        self.register_with(id, |_, payload| {
+            serde_json::from_value::<U>(payload)
+                .map(Into::into)
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    /// Decode payloads with this id using a custom function, e.g. one that checks the signer
+    pub fn register_with<F>(mut self, id: &str, decoder: F) -> Self
+    where
+        F: Fn(&CustomJson, Value) -> Result<T, String> + Send + Sync + 'static,
+    {
+        self.decoders.insert(id.to_string(), Box::new(decoder));
+        self
+    }
+
+    pub fn is_registered(&self, id: &str) -> bool {
+        self.decoders.contains_key(id)
+    }
+
+    /// Decode a `custom_json` operation; `None` for any other operation type
+    pub fn decode(&self, op: &Operation) -> Option<CustomOp<T>> {
+        if op.name() != "custom_json" {
+            return None;
+        }
+        let custom: CustomJson = op.value_as().ok()?;
+        let Some(decoder) = self.decoders.get(&custom.id) else {
+            return Some(CustomOp::Unregistered(custom));
+        };
+        Some(
+            match custom
+                .payload()
+                .and_then(|payload| decoder(&custom, payload))
+            {
+                Ok(typed) => CustomOp::Typed(typed),
+                Err(e) => CustomOp::Invalid(custom, e),
+            },
+        )
+    }
+
+    /// Decode every `custom_json` operation in a block, paired with its transaction id
+    pub fn decode_block<'a>(&self, block: &'a Block) -> Vec<(&'a str, CustomOp<T>)> {
+        block
+            .operations()
+            .into_iter()
+            .filter_map(|(trx_id, op)| self.decode(&op).map(|custom| (trx_id, custom)))
+            .collect()
+    }
+}
+
+impl WriteClient {
+    /// Broadcast a `custom_json` operation, returning the transaction id
+    pub fn broadcast_custom_json(
+        &self,
+        custom: &CustomJsonBuilder,
+        signer: &dyn TransactionSigner,
+    ) -> Result<String, String> {
+        let mut tx = self.prepare_transaction(vec![custom.operation()?])?;
+        let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+}
@@ -0,0 +1,85 @@
+//! Vesting delegations and the expirations of removed delegations
+
+use crate::asset::Asset;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VestingDelegation {
+    pub delegator: String,
+    pub delegatee: String,
+    pub vesting_shares: Asset,
+    pub min_delegation_time: String,
+}
+
+/// Un-delegated VESTS that return to the delegator at `expiration`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VestingDelegationExpiration {
+    pub delegator: String,
+    pub vesting_shares: Asset,
+    pub expiration: String,
+}
+
+/// HIVE Power returning to an account on a given date
+#[derive(Debug, Clone)]
+pub struct ReturningHp {
+    pub expiration: String,
+    pub vests: Asset,
+    pub hp: Asset,
+}
+
+#[derive(Debug, Deserialize)]
+struct DelegationsResponse {
+    delegations: Vec<VestingDelegation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DelegationExpirationsResponse {
+    delegations: Vec<VestingDelegationExpiration>,
+}
+
+impl Client {
+    /// Fetch the outgoing vesting delegations of an account
+    pub fn find_vesting_delegations(
+        &self,
+        account: &str,
+    ) -> Result<Vec<VestingDelegation>, String> {
+        let resp: DelegationsResponse = self
+            .call(
+                "database_api.find_vesting_delegations",
+                serde_json::json!({ "account": account }),
+            )
+            .map_err(|e| format!("Error fetching delegations for {account}: {e}"))?;
+        Ok(resp.delegations)
+    }
+
+    /// Fetch removed delegations still returning to an account
+    pub fn find_vesting_delegation_expirations(
+        &self,
+        account: &str,
+    ) -> Result<Vec<VestingDelegationExpiration>, String> {
+        let resp: DelegationExpirationsResponse = self
+            .call(
+                "database_api.find_vesting_delegation_expirations",
+                serde_json::json!({ "account": account }),
+            )
+            .map_err(|e| format!("Error fetching delegation expirations for {account}: {e}"))?;
+        Ok(resp.delegations)
+    }
+
+    /// When un-delegated HIVE Power becomes available to an account again, soonest first
+    pub fn returning_hp(&self, account: &str) -> Result<Vec<ReturningHp>, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let mut returning: Vec<ReturningHp> = self
+            .find_vesting_delegation_expirations(account)?
+            .into_iter()
+            .map(|e| ReturningHp {
+                hp: props.vests_to_hp(e.vesting_shares),
+                vests: e.vesting_shares,
+                expiration: e.expiration,
+            })
+            .collect();
+        returning.sort_by(|a, b| a.expiration.cmp(&b.expiration));
+        Ok(returning)
+    }
+}
@@ -0,0 +1,250 @@
+//! Exchange-style deposit watching with memo matching and irreversible confirmations
+
+use crate::asset::Asset;
+use crate::block::Block;
+use crate::block_source::BlockSource;
+use crate::chain::ChainConfig;
+use crate::Client;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An incoming transfer to the watched account
+#[derive(Debug, Clone)]
+pub struct Deposit {
+    pub block_num: u32,
+    pub trx_id: String,
+    pub from: String,
+    pub amount: Asset,
+    pub memo: String,
+    /// The reference registered for the memo, if it was expected
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DepositEvent {
+    /// A deposit was included in a block but is not yet irreversible enough
+    Seen(Deposit),
+    /// A deposit with an expected memo reached the required confirmations
+    Credited(Deposit),
+    /// A deposit with an unknown memo reached the required confirmations
+    Unmatched(Deposit),
+    /// A deposit seen in a reversible block is not in the irreversible one,
+    /// so a fork removed it; should a fork have re-included the transfer in
+    /// another block, it is credited from there
+    Reverted(Deposit),
+}
+
+impl Deposit {
+    fn same_transfer(&self, other: &Deposit) -> bool {
+        self.trx_id == other.trx_id
+            && self.from == other.from
+            && self.amount == other.amount
+            && self.memo == other.memo
+    }
+}
+
+/// Watches transfers to a hot wallet and credits them once irreversible
+#[derive(Debug)]
+pub struct DepositWatcher {
+    account: String,
+    confirmations: u32,
+    expected: HashMap<String, String>,
+    pending: Vec<Deposit>,
+    memo_prefix: String,
+    hasher: RandomState,
+    counter: u64,
+    /// The first block not yet credited from its irreversible version
+    next_irreversible: Option<u32>,
+    /// The chain whose symbol names amounts are parsed with
+    chain: ChainConfig,
+}
+
+impl DepositWatcher {
+    /// Watch `account`, crediting deposits once they have `confirmations` irreversible blocks
+    pub fn new(account: &str, confirmations: u32) -> Self {
+        Self {
+            account: account.to_string(),
+            confirmations: confirmations.max(1),
+            expected: HashMap::new(),
+            pending: Vec::new(),
+            memo_prefix: String::new(),
+            hasher: RandomState::new(),
+            counter: 0,
+            next_irreversible: None,
+            chain: ChainConfig::mainnet(),
+        }
+    }
+
+    /// Parse amounts with `chain`'s symbol names; the main network's by default
+    ///
+    /// [`DepositWatcher::watch`] uses its client's chain.
+    pub fn chain(mut self, chain: &ChainConfig) -> Self {
+        self.chain = chain.clone();
+        self
+    }
+
+    /// Prefix generated memos, e.g. with an exchange name
+    pub fn memo_prefix(mut self, prefix: &str) -> Self {
+        self.memo_prefix = prefix.to_string();
+        self
+    }
+
+    /// Generate a unique memo for `reference` (e.g. a user id) and expect deposits with it
+    pub fn generate_memo(&mut self, reference: &str) -> String {
+        loop {
+            self.counter += 1;
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos());
+            let mut hasher = self.hasher.build_hasher();
+            hasher.write(reference.as_bytes());
+            hasher.write_u64(self.counter);
+            hasher.write_u128(nanos);
+            let memo = format!("{}{:016x}", self.memo_prefix, hasher.finish());
+            if !self.expected.contains_key(&memo) {
+                self.expect(&memo, reference);
+                return memo;
+            }
+        }
+    }
+
+    /// Register an externally generated memo
+    pub fn expect(&mut self, memo: &str, reference: &str) {
+        self.expected
+            .insert(memo.trim().to_string(), reference.to_string());
+    }
+
+    /// Stop expecting deposits with `memo`
+    pub fn forget(&mut self, memo: &str) -> Option<String> {
+        self.expected.remove(memo.trim())
+    }
+
+    /// The reference registered for a memo, if any
+    pub fn match_memo(&self, memo: &str) -> Option<&str> {
+        self.expected.get(memo.trim()).map(String::as_str)
+    }
+
+    /// Deposits seen but not yet credited
+    pub fn pending(&self) -> &[Deposit] {
+        &self.pending
+    }
+
+    /// Record transfers to the watched account in a block, returning the new deposits
+    pub fn process_block(&mut self, block_num: u32, block: &Block) -> Vec<Deposit> {
+        let seen = self.deposits_in(block_num, block);
+        self.pending.extend(seen.iter().cloned());
+        seen
+    }
+
+    fn deposits_in(&self, block_num: u32, block: &Block) -> Vec<Deposit> {
+        let mut seen = Vec::new();
+        for (trx_id, op) in block.operations() {
+            if op.name() != "transfer" || op.str_field("to") != Some(self.account.as_str()) {
+                continue;
+            }
+            let Some(amount) = op
+                .value
+                .get("amount")
+                .and_then(|a| self.chain.asset_from_value(a).ok())
+            else {
+                continue;
+            };
+            let memo = op.str_field("memo").unwrap_or_default().to_string();
+            let deposit = Deposit {
+                block_num,
+                trx_id: trx_id.to_string(),
+                from: op.str_field("from").unwrap_or_default().to_string(),
+                amount,
+                reference: self.match_memo(&memo).map(str::to_string),
+                memo,
+            };
+            seen.push(deposit);
+        }
+        seen
+    }
+
+    /// Remove and return deposits that have enough irreversible confirmations
+    ///
+    /// This trusts the blocks the deposits were seen in; [`Self::watch_source`]
+    /// also checks each one is still in the irreversible block.
+    pub fn take_confirmed(&mut self, last_irreversible: u32) -> Vec<Deposit> {
+        let required = self.confirmations;
+        let (confirmed, pending) = self
+            .pending
+            .drain(..)
+            .partition(|d| last_irreversible >= d.block_num + required - 1);
+        self.pending = pending;
+        confirmed
+    }
+
+    /// Stream blocks from `start` and report deposit events until `on_event` returns `false`
+    pub fn watch<F>(&mut self, client: &Client, start: u32, on_event: F) -> Result<(), String>
+    where
+        F: FnMut(DepositEvent) -> bool,
+    {
+        self.chain = client.chain().clone();
+        self.watch_source(&mut client.stream_blocks(start), on_event)
+    }
+
+    /// Report deposit events from any block source, e.g. a replayed dump file
+    ///
+    /// Blocks from the stream are only used for [`DepositEvent::Seen`].
+    /// Crediting walks every block once it has enough irreversible
+    /// confirmations, fetched again with [`BlockSource::irreversible_block`],
+    /// so a transfer a fork moved into an already streamed block is still
+    /// credited. A block the source cannot produce yet is retried after the
+    /// next streamed block.
+    pub fn watch_source<S, F>(&mut self, source: &mut S, mut on_event: F) -> Result<(), String>
+    where
+        S: BlockSource,
+        F: FnMut(DepositEvent) -> bool,
+    {
+        while let Some(streamed) = source.next_block() {
+            let streamed = streamed?;
+            if let Some(block) = &streamed.block {
+                for deposit in self.process_block(streamed.block_num, block) {
+                    if !on_event(DepositEvent::Seen(deposit)) {
+                        return Ok(());
+                    }
+                }
+            }
+            let next = *self.next_irreversible.get_or_insert(streamed.block_num);
+            let lib = source.last_irreversible_block_num()?;
+            let last = (lib + 1).saturating_sub(self.confirmations);
+            for block_num in next..=last {
+                let deposits = if source.streams_irreversible() {
+                    let seen = self.pending.iter().filter(|d| d.block_num == block_num);
+                    seen.cloned().collect()
+                } else {
+                    match source.irreversible_block(block_num)? {
+                        Some(block) => self.deposits_in(block_num, &block),
+                        None => break,
+                    }
+                };
+                self.next_irreversible = Some(block_num + 1);
+                let mut events = Vec::new();
+                for deposit in deposits {
+                    self.pending.retain(|d| !d.same_transfer(&deposit));
+                    events.push(match deposit.reference {
+                        Some(_) => DepositEvent::Credited(deposit),
+                        None => DepositEvent::Unmatched(deposit),
+                    });
+                }
+                let (reverted, pending) = self
+                    .pending
+                    .drain(..)
+                    .partition(|d| d.block_num == block_num);
+                self.pending = pending;
+                events.extend(reverted.into_iter().map(DepositEvent::Reverted));
+                for event in events {
+                    if !on_event(event) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
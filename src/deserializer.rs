@@ -0,0 +1,459 @@
+//! Decoding hived's binary wire format back into transactions
+//!
+//! The inverse of [`crate::serializer`], covering the same operations, so
+//! transactions serialized by cli_wallet or other tools can be read, checked
+//! and relayed.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::crypto::{encode_public_key, to_hex};
+use crate::operation::Operation;
+use crate::serializer::OPERATION_IDS;
+use crate::timestamp::format_timestamp;
+use crate::transaction::{Transaction, SIGNATURE_SIZE};
+use serde_json::{json, Map, Value};
+
+/// Address prefix of public keys decoded from binary, which carries none
+const PUBLIC_KEY_PREFIX: &str = "STM";
+
+/// Reads values from a buffer in hived's binary encoding
+#[derive(Debug)]
+pub struct Deserializer<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Whether every byte has been read
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.buf.len()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| format!("Unexpected end of data at byte {}", self.pos))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.bytes(N)?);
+        Ok(out)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    pub fn bool(&mut self) -> Result<bool, String> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(format!("Invalid boolean {other} at byte {}", self.pos - 1)),
+        }
+    }
+
+    /// Unsigned LEB128, used for lengths and variant indexes
+    pub fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(format!("Varint too long at byte {}", self.pos))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let len = self.varint()?;
+        usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= self.buf.len() - self.pos)
+            .ok_or_else(|| format!("Length {len} exceeds the data at byte {}", self.pos))
+    }
+
+    pub fn string(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        String::from_utf8(self.bytes(len)?.to_vec())
+            .map_err(|_| format!("Invalid UTF-8 string ending at byte {}", self.pos))
+    }
+
+    /// Seconds since the Unix epoch as `u32`, formatted as a timestamp
+    pub fn time(&mut self) -> Result<String, String> {
+        Ok(format_timestamp(i64::from(self.u32()?)))
+    }
+
+    /// Amount, precision and the legacy 7-byte symbol name
+    pub fn asset(&mut self) -> Result<Asset, String> {
+        let amount = self.i64()?;
+        let precision = self.u8()?;
+        let name = self.bytes(7)?;
+        let symbol = match name.split(|&b| b == 0).next().unwrap_or_default() {
+            b"STEEM" => AssetSymbol::Hive,
+            b"SBD" => AssetSymbol::Hbd,
+            b"VESTS" => AssetSymbol::Vests,
+            other => {
+                return Err(format!(
+                    "Unknown asset symbol '{}'",
+                    String::from_utf8_lossy(other)
+                ))
+            }
+        };
+        if precision != symbol.precision() {
+            return Err(format!("Invalid precision {precision} for {symbol:?}"));
+        }
+        Ok(Asset::new(amount, symbol))
+    }
+
+    /// A public key from its 33 compressed bytes
+    pub fn public_key(&mut self) -> Result<String, String> {
+        Ok(encode_public_key(&self.array()?, PUBLIC_KEY_PREFIX))
+    }
+
+    /// An empty `future_extensions` list
+    fn no_extensions(&mut self, name: &str) -> Result<Value, String> {
+        match self.varint()? {
+            0 => Ok(json!([])),
+            _ => Err(format!("Extensions of {name} operations are not supported")),
+        }
+    }
+
+    fn string_list(&mut self) -> Result<Value, String> {
+        let len = self.len()?;
+        (0..len).map(|_| self.string().map(Value::from)).collect()
+    }
+
+    fn int_list(&mut self) -> Result<Value, String> {
+        let len = self.len()?;
+        (0..len).map(|_| self.i64().map(Value::from)).collect()
+    }
+
+    /// Deserialize an operation from its variant index and fields
+    pub fn operation(&mut self) -> Result<Operation, String> {
+        let id = self.varint()?;
+        let name = u32::try_from(id)
+            .ok()
+            .and_then(operation_name)
+            .ok_or_else(|| format!("Decoding operation {id} is not supported"))?;
+        let mut value = Map::new();
+        let mut put = |field: &str, v: Value| {
+            value.insert(field.to_string(), v);
+        };
+        let asset = |d: &mut Self| d.asset().map(|a| json!(a));
+        match name {
+            "vote" => {
+                put("voter", self.string()?.into());
+                put("author", self.string()?.into());
+                put("permlink", self.string()?.into());
+                put("weight", self.i16()?.into());
+            }
+            "comment" => {
+                for field in [
+                    "parent_author",
+                    "parent_permlink",
+                    "author",
+                    "permlink",
+                    "title",
+                    "body",
+                    "json_metadata",
+                ] {
+                    put(field, self.string()?.into());
+                }
+            }
+            "transfer" | "transfer_to_savings" => {
+                put("from", self.string()?.into());
+                put("to", self.string()?.into());
+                put("amount", asset(self)?);
+                put("memo", self.string()?.into());
+            }
+            "transfer_to_vesting" => {
+                put("from", self.string()?.into());
+                put("to", self.string()?.into());
+                put("amount", asset(self)?);
+            }
+            "withdraw_vesting" => {
+                put("account", self.string()?.into());
+                put("vesting_shares", asset(self)?);
+            }
+            "limit_order_create" => {
+                put("owner", self.string()?.into());
+                put("orderid", self.u32()?.into());
+                put("amount_to_sell", asset(self)?);
+                put("min_to_receive", asset(self)?);
+                put("fill_or_kill", self.bool()?.into());
+                put("expiration", self.time()?.into());
+            }
+            "limit_order_cancel" => {
+                put("owner", self.string()?.into());
+                put("orderid", self.u32()?.into());
+            }
+            "feed_publish" => {
+                put("publisher", self.string()?.into());
+                let base = asset(self)?;
+                let quote = asset(self)?;
+                put("exchange_rate", json!({ "base": base, "quote": quote }));
+            }
+            "convert" | "collateralized_convert" => {
+                put("owner", self.string()?.into());
+                put("requestid", self.u32()?.into());
+                put("amount", asset(self)?);
+            }
+            "account_update" | "account_update2" => {
+                put("account", self.string()?.into());
+                for field in ["owner", "active", "posting"] {
+                    if let Some(authority) = self.optional_authority()? {
+                        put(field, authority);
+                    }
+                }
+                // Optional only in account_update2
+                if name == "account_update" || self.bool()? {
+                    put("memo_key", self.public_key()?.into());
+                }
+                put("json_metadata", self.string()?.into());
+                if name == "account_update2" {
+                    put("posting_json_metadata", self.string()?.into());
+                    put("extensions", self.no_extensions(name)?);
+                }
+            }
+            "account_witness_vote" => {
+                put("account", self.string()?.into());
+                put("witness", self.string()?.into());
+                put("approve", self.bool()?.into());
+            }
+            "account_witness_proxy" => {
+                put("account", self.string()?.into());
+                put("proxy", self.string()?.into());
+            }
+            "delete_comment" => {
+                put("author", self.string()?.into());
+                put("permlink", self.string()?.into());
+            }
+            "custom_json" => {
+                put("required_auths", self.string_list()?);
+                put("required_posting_auths", self.string_list()?);
+                put("id", self.string()?.into());
+                put("json", self.string()?.into());
+            }
+            "comment_options" => {
+                put("author", self.string()?.into());
+                put("permlink", self.string()?.into());
+                put("max_accepted_payout", asset(self)?);
+                put("percent_hbd", self.u16()?.into());
+                put("allow_votes", self.bool()?.into());
+                put("allow_curation_rewards", self.bool()?.into());
+                put("extensions", self.comment_options_extensions()?);
+            }
+            "set_withdraw_vesting_route" => {
+                put("from_account", self.string()?.into());
+                put("to_account", self.string()?.into());
+                put("percent", self.u16()?.into());
+                put("auto_vest", self.bool()?.into());
+            }
+            "claim_account" => {
+                put("creator", self.string()?.into());
+                put("fee", asset(self)?);
+                put("extensions", self.no_extensions(name)?);
+            }
+            "change_recovery_account" => {
+                put("account_to_recover", self.string()?.into());
+                put("new_recovery_account", self.string()?.into());
+                put("extensions", self.no_extensions(name)?);
+            }
+            "transfer_from_savings" => {
+                put("from", self.string()?.into());
+                put("request_id", self.u32()?.into());
+                put("to", self.string()?.into());
+                put("amount", asset(self)?);
+                put("memo", self.string()?.into());
+            }
+            "cancel_transfer_from_savings" => {
+                put("from", self.string()?.into());
+                put("request_id", self.u32()?.into());
+            }
+            "claim_reward_balance" => {
+                put("account", self.string()?.into());
+                put("reward_hive", asset(self)?);
+                put("reward_hbd", asset(self)?);
+                put("reward_vests", asset(self)?);
+            }
+            "witness_update" => {
+                put("owner", self.string()?.into());
+                put("url", self.string()?.into());
+                put("block_signing_key", self.public_key()?.into());
+                let fee = asset(self)?;
+                let block_size = self.u32()?;
+                let interest = self.u16()?;
+                put(
+                    "props",
+                    json!({
+                        "account_creation_fee": fee,
+                        "maximum_block_size": block_size,
+                        "hbd_interest_rate": interest,
+                    }),
+                );
+                put("fee", asset(self)?);
+            }
+            "witness_set_properties" => {
+                put("owner", self.string()?.into());
+                let len = self.len()?;
+                let mut props = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.string()?;
+                    let size = self.len()?;
+                    props.push(json!([key, to_hex(self.bytes(size)?)]));
+                }
+                put("props", props.into());
+                put("extensions", self.no_extensions(name)?);
+            }
+            "delegate_vesting_shares" => {
+                put("delegator", self.string()?.into());
+                put("delegatee", self.string()?.into());
+                put("vesting_shares", asset(self)?);
+            }
+            "update_proposal_votes" => {
+                put("voter", self.string()?.into());
+                put("proposal_ids", self.int_list()?);
+                put("approve", self.bool()?.into());
+                put("extensions", self.no_extensions(name)?);
+            }
+            "remove_proposal" => {
+                put("proposal_owner", self.string()?.into());
+                put("proposal_ids", self.int_list()?);
+                put("extensions", self.no_extensions(name)?);
+            }
+            "recurrent_transfer" => {
+                put("from", self.string()?.into());
+                put("to", self.string()?.into());
+                put("amount", asset(self)?);
+                put("memo", self.string()?.into());
+                put("recurrence", self.u16()?.into());
+                put("executions", self.u16()?.into());
+                put("extensions", self.no_extensions(name)?);
+            }
+            _ => unreachable!("operation id table and deserializer are out of sync"),
+        }
+        Ok(Operation::new(
+            &format!("{name}_operation"),
+            Value::Object(value),
+        ))
+    }
+
+    fn optional_authority(&mut self) -> Result<Option<Value>, String> {
+        if !self.bool()? {
+            return Ok(None);
+        }
+        let weight_threshold = self.u32()?;
+        let len = self.len()?;
+        let account_auths = (0..len)
+            .map(|_| Ok(json!([self.string()?, self.u16()?])))
+            .collect::<Result<Vec<_>, String>>()?;
+        let len = self.len()?;
+        let key_auths = (0..len)
+            .map(|_| Ok(json!([self.public_key()?, self.u16()?])))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Some(json!({
+            "weight_threshold": weight_threshold,
+            "account_auths": account_auths,
+            "key_auths": key_auths,
+        })))
+    }
+
+    fn comment_options_extensions(&mut self) -> Result<Value, String> {
+        let len = self.len()?;
+        let mut extensions = Vec::with_capacity(len);
+        for _ in 0..len {
+            let kind = self.varint()?;
+            if kind != 0 {
+                return Err(format!("Unsupported comment_options extension: {kind}"));
+            }
+            let count = self.len()?;
+            let mut beneficiaries = Vec::with_capacity(count);
+            for _ in 0..count {
+                let account = self.string()?;
+                let weight = self.u16()?;
+                beneficiaries.push(json!({ "account": account, "weight": weight }));
+            }
+            extensions.push(json!({
+                "type": "comment_payout_beneficiaries",
+                "value": { "beneficiaries": beneficiaries },
+            }));
+        }
+        Ok(extensions.into())
+    }
+
+    /// Deserialize a transaction without its signatures
+    pub fn transaction(&mut self) -> Result<Transaction, String> {
+        let ref_block_num = self.u16()?;
+        let ref_block_prefix = self.u32()?;
+        let expiration = self.time()?;
+        let len = self.len()?;
+        let operations = (0..len)
+            .map(|_| self.operation())
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.varint()? != 0 {
+            return Err("Transaction extensions are not supported".to_string());
+        }
+        Ok(Transaction {
+            ref_block_num,
+            ref_block_prefix,
+            expiration,
+            operations,
+            extensions: Vec::new(),
+            signatures: Vec::new(),
+        })
+    }
+
+    /// Deserialize a transaction followed by its signatures
+    pub fn signed_transaction(&mut self) -> Result<Transaction, String> {
+        let mut tx = self.transaction()?;
+        let len = self.len()?;
+        for _ in 0..len {
+            tx.signatures.push(to_hex(self.bytes(SIGNATURE_SIZE)?));
+        }
+        Ok(tx)
+    }
+}
+
+/// The operation name of hived's numeric id, for the supported operations
+fn operation_name(id: u32) -> Option<&'static str> {
+    OPERATION_IDS
+        .iter()
+        .find(|(_, op_id)| *op_id == id)
+        .map(|(name, _)| *name)
+}
+
+/// Decode a transaction serialized without signatures
+pub fn transaction_from_bytes(bytes: &[u8]) -> Result<Transaction, String> {
+    let mut d = Deserializer::new(bytes);
+    let tx = d.transaction()?;
+    if !d.is_empty() {
+        return Err("Trailing bytes after the transaction".to_string());
+    }
+    Ok(tx)
+}
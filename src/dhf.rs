@@ -0,0 +1,192 @@
+//! Decentralized Hive Fund (DHF) proposal funding analytics
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::numbers::de_int;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+
+/// The DHF treasury account
+pub const TREASURY_ACCOUNT: &str = "hive.fund";
+
+/// Maximum number of proposals `database_api.list_proposals` returns per call
+pub const LIST_PROPOSALS_LIMIT: u32 = 1000;
+
+/// The treasury pays out at most this fraction of its HBD per day
+const DAILY_BUDGET_DIVISOR: i64 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub id: u32,
+    pub creator: String,
+    pub receiver: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub daily_pay: Asset,
+    pub subject: String,
+    pub permlink: String,
+    /// Sum of the supporting votes, in VESTS satoshis
+    #[serde(deserialize_with = "de_int")]
+    pub total_votes: i64,
+    #[serde(default)]
+    pub status: String,
+}
+
+impl Proposal {
+    /// Whether this is a "return" proposal paying back to the treasury
+    pub fn is_return_proposal(&self) -> bool {
+        self.receiver == TREASURY_ACCOUNT
+    }
+}
+
+/// A proposal with the HBD it receives per day at current votes
+#[derive(Debug, Clone)]
+pub struct FundedProposal {
+    pub proposal: Proposal,
+    /// Equal to `daily_pay` when fully funded, less when the budget runs out
+    pub funded_pay: Asset,
+}
+
+impl FundedProposal {
+    pub fn is_funded(&self) -> bool {
+        self.funded_pay.amount > 0
+    }
+
+    pub fn is_fully_funded(&self) -> bool {
+        self.funded_pay == self.proposal.daily_pay
+    }
+}
+
+/// Funding summary of active proposals
+#[derive(Debug, Clone)]
+pub struct DhfStatus {
+    pub treasury_balance: Asset,
+    pub daily_budget: Asset,
+    /// Votes of the highest return proposal; proposals must exceed it to be paid
+    pub threshold_votes: i64,
+    /// The threshold expressed as HIVE Power at the current VESTS price
+    pub threshold_hp: Asset,
+    /// Active proposals by votes, highest first, with their funding
+    pub proposals: Vec<FundedProposal>,
+    /// HBD paid out per day to funded proposals
+    pub daily_spend: Asset,
+    /// Days the current balance lasts at the current spend, ignoring inflows
+    pub runway_days: Option<f64>,
+}
+
+impl DhfStatus {
+    pub fn funded(&self) -> impl Iterator<Item = &FundedProposal> {
+        self.proposals.iter().filter(|p| p.is_funded())
+    }
+
+    pub fn unfunded(&self) -> impl Iterator<Item = &FundedProposal> {
+        self.proposals
+            .iter()
+            .filter(|p| !p.is_funded() && !p.proposal.is_return_proposal())
+    }
+}
+
+/// Distribute the daily budget to proposals in vote order, down to the threshold
+pub fn allocate_funding(
+    mut proposals: Vec<Proposal>,
+    daily_budget: Asset,
+) -> (Vec<FundedProposal>, i64) {
+    proposals.sort_by(|a, b| b.total_votes.cmp(&a.total_votes).then(a.id.cmp(&b.id)));
+    let threshold = proposals
+        .iter()
+        .find(|p| p.is_return_proposal())
+        .map_or(0, |p| p.total_votes);
+    let mut remaining = daily_budget.amount;
+    let funded = proposals
+        .into_iter()
+        .map(|proposal| {
+            let eligible = !proposal.is_return_proposal()
+                && proposal.total_votes > threshold
+                && proposal.total_votes > 0;
+            let pay = if eligible {
+                proposal.daily_pay.amount.min(remaining).max(0)
+            } else {
+                0
+            };
+            remaining -= pay;
+            FundedProposal {
+                funded_pay: Asset::new(pay, AssetSymbol::Hbd),
+                proposal,
+            }
+        })
+        .collect();
+    (funded, threshold)
+}
+
+#[derive(Debug, Serialize)]
+struct ListProposalsParams<'a> {
+    start: Vec<i64>,
+    limit: u32,
+    order: &'a str,
+    order_direction: &'a str,
+    status: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProposalsResponse {
+    proposals: Vec<Proposal>,
+}
+
+impl Client {
+    /// List active proposals, highest votes first
+    pub fn list_active_proposals(&self) -> Result<Vec<Proposal>, String> {
+        let params = ListProposalsParams {
+            start: Vec::new(),
+            limit: LIST_PROPOSALS_LIMIT,
+            order: "by_total_votes",
+            order_direction: "descending",
+            status: "active",
+        };
+        let resp: ProposalsResponse = self
+            .call("database_api.list_proposals", params)
+            .map_err(|e| format!("Error listing proposals: {e}"))?;
+        Ok(resp.proposals)
+    }
+
+    /// Look up proposals by id; removed proposals are omitted
+    pub fn find_proposals(&self, ids: &[u32]) -> Result<Vec<Proposal>, String> {
+        let resp: ProposalsResponse = self
+            .call(
+                "database_api.find_proposals",
+                serde_json::json!({ "proposal_ids": ids }),
+            )
+            .map_err(|e| format!("Error finding proposals: {e}"))?;
+        Ok(resp.proposals)
+    }
+
+    /// Compute the DHF funding threshold, funded proposals, budget and runway
+    pub fn dhf_status(&self) -> Result<DhfStatus, String> {
+        let treasury = self
+            .find_accounts(&[TREASURY_ACCOUNT])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Treasury account '{TREASURY_ACCOUNT}' not found"))?;
+        let props = self.get_dynamic_global_properties()?;
+        let treasury_balance = treasury.hbd_balance;
+        let daily_budget = Asset::new(
+            treasury_balance.amount / DAILY_BUDGET_DIVISOR,
+            AssetSymbol::Hbd,
+        );
+        let (proposals, threshold_votes) =
+            allocate_funding(self.list_active_proposals()?, daily_budget);
+        let daily_spend = Asset::new(
+            proposals.iter().map(|p| p.funded_pay.amount).sum(),
+            AssetSymbol::Hbd,
+        );
+        let runway_days = (daily_spend.amount > 0)
+            .then(|| treasury_balance.amount as f64 / daily_spend.amount as f64);
+        Ok(DhfStatus {
+            treasury_balance,
+            daily_budget,
+            threshold_votes,
+            threshold_hp: props.vests_to_hp(Asset::new(threshold_votes, AssetSymbol::Vests)),
+            proposals,
+            daily_spend,
+            runway_days,
+        })
+    }
+}
@@ -0,0 +1,309 @@
+//! DNS overrides, custom resolvers and IPv4/IPv6 preference for node connections
+//!
+//! Useful for operators running internal nodes behind split-horizon DNS, or
+//! on hosts where one address family is broken. When a name resolves to both
+//! families the connection prefers the family of the first address and falls
+//! back to the other (happy eyeballs), so [`IpPreference`] controls which
+//! family is tried first.
+
+use crate::Client;
+use reqwest::blocking::{Client as HttpClient, ClientBuilder};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+/// Default timeout of node requests
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TCP keepalive of the long-lived HTTP/2 connections
+const HTTP2_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Resolves host names to IP addresses for node connections
+pub trait DnsResolver: Send + Sync {
+    fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>>;
+}
+
+/// The operating system's resolver
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl DnsResolver for SystemResolver {
+    fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        Ok((host, 0).to_socket_addrs()?.map(|a| a.ip()).collect())
+    }
+}
+
+/// Which address family to connect over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpPreference {
+    /// Use addresses in the order the resolver returns them
+    #[default]
+    Any,
+    /// Try IPv4 first, falling back to IPv6
+    PreferV4,
+    /// Try IPv6 first, falling back to IPv4
+    PreferV6,
+    /// Never connect over IPv6
+    V4Only,
+    /// Never connect over IPv4
+    V6Only,
+}
+
+impl IpPreference {
+    /// Filter and order resolved addresses, keeping the resolver's order within a family
+    pub fn apply(self, mut addrs: Vec<IpAddr>) -> Vec<IpAddr> {
+        match self {
+            IpPreference::Any => {}
+            IpPreference::PreferV4 => addrs.sort_by_key(IpAddr::is_ipv6),
+            IpPreference::PreferV6 => addrs.sort_by_key(IpAddr::is_ipv4),
+            IpPreference::V4Only => addrs.retain(IpAddr::is_ipv4),
+            IpPreference::V6Only => addrs.retain(IpAddr::is_ipv6),
+        }
+        addrs
+    }
+}
+
+/// How the HTTP client connects to nodes; rebuilt into an [`HttpClient`] on change
+#[derive(Clone)]
+pub(crate) struct HttpSettings {
+    pub(crate) timeout: Duration,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) user_agent: Option<String>,
+    overrides: Vec<(String, Vec<SocketAddr>)>,
+    preference: IpPreference,
+    resolver: Option<Arc<dyn DnsResolver>>,
+    /// Origins of nodes spoken to over HTTP/2 without negotiation
+    pub(crate) http2_origins: Vec<String>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            user_agent: None,
+            overrides: Vec::new(),
+            preference: IpPreference::Any,
+            resolver: None,
+            http2_origins: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for HttpSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpSettings")
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("user_agent", &self.user_agent)
+            .field("overrides", &self.overrides)
+            .field("preference", &self.preference)
+            .field("custom_resolver", &self.resolver.is_some())
+            .field("http2_origins", &self.http2_origins)
+            .finish()
+    }
+}
+
+/// Apply the settings to a blocking or async `ClientBuilder`
+macro_rules! configure {
+    ($settings:expr, $builder:expr) => {{
+        let mut builder = $builder.timeout($settings.timeout);
+        if let Some(timeout) = $settings.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(user_agent) = &$settings.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if $settings.resolver.is_some() || $settings.preference != IpPreference::Any {
+            let resolver = $settings
+                .resolver
+                .clone()
+                .unwrap_or_else(|| Arc::new(SystemResolver));
+            builder = builder.dns_resolver(Arc::new(ResolverAdapter {
+                resolver,
+                preference: $settings.preference,
+            }));
+        }
+        for (host, addrs) in &$settings.overrides {
+            builder = builder.resolve_to_addrs(host, addrs);
+        }
+        builder
+    }};
+}
+
+impl HttpSettings {
+    pub(crate) fn build(&self) -> HttpClient {
+        self.builder().build().unwrap()
+    }
+
+    /// A client for the HTTP/2 origins, if there are any
+    pub(crate) fn build_http2(&self) -> Option<HttpClient> {
+        if self.http2_origins.is_empty() {
+            return None;
+        }
+        let builder = self
+            .builder()
+            .http2_prior_knowledge()
+            .http2_adaptive_window(true)
+            .pool_idle_timeout(None)
+            .tcp_keepalive(HTTP2_KEEPALIVE);
+        Some(builder.build().unwrap())
+    }
+
+    /// The addresses of `host` to connect to, for transports outside reqwest
+    #[cfg(feature = "websocket")]
+    pub(crate) fn socket_addrs(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        if let Some((_, addrs)) = self.overrides.iter().find(|(h, _)| h == host) {
+            return Ok(addrs
+                .iter()
+                .map(|addr| match addr.port() {
+                    0 => SocketAddr::new(addr.ip(), port),
+                    _ => *addr,
+                })
+                .collect());
+        }
+        let ips = match &self.resolver {
+            Some(resolver) => resolver.lookup(host)?,
+            None => SystemResolver.lookup(host)?,
+        };
+        let addrs: Vec<SocketAddr> = self
+            .preference
+            .apply(ips)
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, port))
+            .collect();
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No address for {host} matching {:?}", self.preference),
+            ));
+        }
+        Ok(addrs)
+    }
+
+    fn builder(&self) -> ClientBuilder {
+        configure!(self, HttpClient::builder())
+    }
+
+    /// An async client with the same settings, for [`AsyncClient`](crate::AsyncClient)
+    #[cfg(feature = "async")]
+    pub(crate) fn build_async(&self) -> reqwest::Client {
+        configure!(self, reqwest::Client::builder())
+            .build()
+            .unwrap()
+    }
+
+    /// An async client for the HTTP/2 origins, if there are any
+    #[cfg(feature = "async")]
+    pub(crate) fn build_async_http2(&self) -> Option<reqwest::Client> {
+        if self.http2_origins.is_empty() {
+            return None;
+        }
+        let builder = configure!(self, reqwest::Client::builder())
+            .http2_prior_knowledge()
+            .http2_adaptive_window(true)
+            .pool_idle_timeout(None)
+            .tcp_keepalive(HTTP2_KEEPALIVE);
+        Some(builder.build().unwrap())
+    }
+}
+
+impl Client {
+    /// Rebuild the default transport on next use after changing [`HttpSettings`]
+    pub(crate) fn rebuild_http(&mut self) {
+        self.http_transport = OnceLock::new();
+    }
+
+    /// Connect to `host` at these addresses instead of resolving it
+    ///
+    /// A port of 0 uses the port from the node URL.
+    pub fn dns_override(mut self, host: &str, addrs: &[SocketAddr]) -> Self {
+        self.http.overrides.retain(|(h, _)| h != host);
+        self.http.overrides.push((host.to_string(), addrs.to_vec()));
+        self.rebuild_http();
+        self
+    }
+
+    /// Prefer or restrict the address family used to reach nodes
+    pub fn ip_preference(mut self, preference: IpPreference) -> Self {
+        self.http.preference = preference;
+        self.rebuild_http();
+        self
+    }
+
+    /// Resolve node host names with `resolver` instead of the system resolver
+    pub fn dns_resolver<R: DnsResolver + 'static>(mut self, resolver: R) -> Self {
+        self.http.resolver = Some(Arc::new(resolver));
+        self.rebuild_http();
+        self
+    }
+}
+
+/// Runs a [`DnsResolver`] for reqwest, applying the [`IpPreference`]
+struct ResolverAdapter {
+    resolver: Arc<dyn DnsResolver>,
+    preference: IpPreference,
+}
+
+impl Resolve for ResolverAdapter {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = Arc::clone(&self.resolver);
+        let preference = self.preference;
+        let host = name.as_str().to_string();
+        Box::pin(OnThread::spawn(move || {
+            let addrs = preference.apply(resolver.lookup(&host)?);
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No address for {host} matching {preference:?}"),
+                )
+                .into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        }))
+    }
+}
+
+/// A future completed by a closure running on its own thread, so blocking
+/// lookups do not stall the HTTP client's runtime
+struct OnThread<T> {
+    shared: Arc<Mutex<(Option<T>, Option<Waker>)>>,
+}
+
+impl<T: Send + 'static> OnThread<T> {
+    fn spawn<F: FnOnce() -> T + Send + 'static>(f: F) -> Self {
+        let shared = Arc::new(Mutex::new((None, None::<Waker>)));
+        let result = Arc::clone(&shared);
+        thread::spawn(move || {
+            let value = f();
+            let mut state = result.lock().unwrap();
+            state.0 = Some(value);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+        Self { shared }
+    }
+}
+
+impl<T> Future for OnThread<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.shared.lock().unwrap();
+        match state.0.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
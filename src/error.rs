@@ -0,0 +1,153 @@
+//! Typed errors of node calls
+//!
+//! [`NectarError`] tells a timeout from an RPC error from a response that
+//! did not decode. It converts into `String`, so functions still returning
+//! `Result<_, String>` can use `?` on it, and its messages match the ones
+//! [`FailReason::classify`](crate::fail_reason::FailReason::classify) reads.
+//! [`NectarError::localized`] renders it for end users in their language.
+
+use crate::attempts::{describe_failures, Attempt, CallError};
+use crate::i18n::{Message, Translator};
+use crate::node_policy::EmptyNodeList;
+use crate::RpcError;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+/// Why a call or node-list update failed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NectarError {
+    /// The node did not answer within the timeout
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    /// The request could not be sent or its response not read
+    #[error("Request error: {0}")]
+    Transport(String),
+    /// The node answered with a non-success HTTP status
+    #[error("Unexpected status code: {0}")]
+    Status(StatusCode),
+    /// The response was larger than [`Client::max_response_size`](crate::Client::max_response_size)
+    #[error("{}", describe_too_large(*.length, *.limit))]
+    ResponseTooLarge { length: Option<u64>, limit: u64 },
+    /// The node returned a JSON-RPC error
+    #[error("RPC error: {message} (code: {code})")]
+    Rpc {
+        code: i32,
+        message: String,
+        data: Option<Value>,
+    },
+    /// The response or its result was not what was expected
+    #[error("Decode error: {0}")]
+    Decode(String),
+    /// The method broadcasts; use a [`WriteClient`](crate::WriteClient)
+    #[error("{0} broadcasts and requires a WriteClient")]
+    RequiresWriteClient(String),
+    /// Every node was tried and failed, or there were none
+    #[error("{}", describe_failures(.method, .attempts))]
+    NoNodesAvailable {
+        method: String,
+        attempts: Vec<Attempt>,
+    },
+    /// A node list left no usable node
+    #[error(transparent)]
+    EmptyNodeList(#[from] EmptyNodeList),
+    #[error("Account '{0}' not found")]
+    AccountNotFound(String),
+}
+
+fn describe_too_large(length: Option<u64>, limit: u64) -> String {
+    match length {
+        Some(length) => format!("Response of {length} bytes exceeds the limit of {limit} bytes"),
+        None => format!("Response exceeds the limit of {limit} bytes"),
+    }
+}
+
+impl NectarError {
+    /// Whether the node answered in time but could not serve the request
+    pub fn is_rpc(&self) -> bool {
+        matches!(self, NectarError::Rpc { .. })
+    }
+
+    /// Whether the failure was in reaching the node rather than its answer
+    pub fn is_transport(&self) -> bool {
+        matches!(
+            self,
+            NectarError::Timeout(_) | NectarError::Transport(_) | NectarError::Status(_)
+        )
+    }
+
+    /// The untranslated message, for rendering with a [`Translator`]
+    pub fn message(&self) -> Message {
+        match self {
+            NectarError::Timeout(detail) => Message::new("error.timeout").arg("detail", detail),
+            NectarError::Transport(detail) => Message::new("error.transport").arg("detail", detail),
+            NectarError::Status(status) => Message::new("error.status").arg("status", status),
+            NectarError::ResponseTooLarge { length, limit } => match length {
+                Some(length) => Message::new("error.too_large_of").arg("length", length),
+                None => Message::new("error.too_large"),
+            }
+            .arg("limit", limit),
+            NectarError::Rpc { code, message, .. } => Message::new("error.rpc")
+                .arg("message", message)
+                .arg("code", code),
+            NectarError::Decode(detail) => Message::new("error.decode").arg("detail", detail),
+            NectarError::RequiresWriteClient(method) => {
+                Message::new("error.requires_write_client").arg("method", method)
+            }
+            NectarError::NoNodesAvailable { method, attempts } => match attempts.as_slice() {
+                [] => Message::new("error.no_nodes"),
+                [only] => Message::new("error.node_failed").arg("node", &only.node),
+                _ => Message::new("error.all_nodes_failed").arg("count", attempts.len()),
+            }
+            .arg("method", method),
+            NectarError::EmptyNodeList(e) => Message::new("error.empty_node_list")
+                .arg("offered", e.offered)
+                .arg("invalid", e.invalid)
+                .arg("failing", e.failing),
+            NectarError::AccountNotFound(account) => {
+                Message::new("error.account_not_found").arg("account", account)
+            }
+        }
+    }
+
+    /// A short message for end users rendered by `translator`
+    ///
+    /// Leaves out the per-node details of `Display`.
+    pub fn localized(&self, translator: &dyn Translator) -> String {
+        self.message().render(translator)
+    }
+
+    /// The failures of each node tried, when every node failed
+    pub fn attempts(&self) -> &[Attempt] {
+        match self {
+            NectarError::NoNodesAvailable { attempts, .. } => attempts,
+            _ => &[],
+        }
+    }
+}
+
+impl From<CallError> for NectarError {
+    fn from(e: CallError) -> Self {
+        match e {
+            CallError::RequiresWriteClient(method) => NectarError::RequiresWriteClient(method),
+            CallError::AllNodesFailed { method, attempts } => {
+                NectarError::NoNodesAvailable { method, attempts }
+            }
+        }
+    }
+}
+
+impl From<RpcError> for NectarError {
+    fn from(e: RpcError) -> Self {
+        NectarError::Rpc {
+            code: e.code,
+            message: e.message,
+            data: e.data,
+        }
+    }
+}
+
+impl From<NectarError> for String {
+    fn from(e: NectarError) -> Self {
+        e.to_string()
+    }
+}
@@ -0,0 +1,109 @@
+//! Block explorer-style lookup of blocks, transactions, accounts and posts
+
+use crate::block::Block;
+use crate::comment::Content;
+use crate::links::{parse_account_url, parse_post_url};
+use crate::transaction::IncludedTransaction;
+use crate::{Account, Client};
+
+/// What an explorer query was recognized as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    BlockNum(u32),
+    TransactionId(String),
+    Account(String),
+    Post { author: String, permlink: String },
+}
+
+impl Query {
+    /// Detect the kind of an explorer input
+    ///
+    /// Accepts block numbers (`#` and `,`/`_` separators allowed), 40 character
+    /// hex transaction ids, account names or account links, and post links
+    /// in any of the forms accepted by [`parse_post_url`].
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.len() == 40 && input.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(Query::TransactionId(input.to_ascii_lowercase()));
+        }
+        let digits: String = input
+            .trim_start_matches('#')
+            .chars()
+            .filter(|c| !matches!(c, ',' | '_'))
+            .collect();
+        if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+            return digits
+                .parse()
+                .map(Query::BlockNum)
+                .map_err(|_| format!("Block number out of range: {input}"));
+        }
+        if let Ok((author, permlink)) = parse_post_url(input) {
+            return Ok(Query::Post { author, permlink });
+        }
+        if let Ok(name) = parse_account_url(input) {
+            return Ok(Query::Account(name));
+        }
+        let name = input.to_ascii_lowercase();
+        validate_account_name(&name)
+            .map(|_| Query::Account(name))
+            .map_err(|_| format!("Unrecognized explorer query: '{input}'"))
+    }
+}
+
+/// The entity an explorer query resolved to
+#[derive(Debug, Clone)]
+pub enum Entity {
+    Block { block_num: u32, block: Block },
+    Transaction(IncludedTransaction),
+    Account(Box<Account>),
+    Post(Content),
+}
+
+/// Check an account name against the chain's naming rules
+///
+/// Names are 3 to 16 characters of dot-separated segments, each starting
+/// with a letter, ending with a letter or digit, and containing only
+/// lowercase letters, digits and hyphens.
+pub fn validate_account_name(name: &str) -> Result<(), String> {
+    if !(3..=16).contains(&name.len()) {
+        return Err(format!("Account name must be 3 to 16 characters: '{name}'"));
+    }
+    for segment in name.split('.') {
+        let valid = segment.len() >= 3
+            && segment.starts_with(|c: char| c.is_ascii_lowercase())
+            && segment.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(format!("Invalid account name: '{name}'"));
+        }
+    }
+    Ok(())
+}
+
+impl Client {
+    /// Resolve an explorer query to the block, transaction, account or post it refers to
+    pub fn lookup(&self, input: &str) -> Result<Entity, String> {
+        match Query::parse(input)? {
+            Query::BlockNum(block_num) => self
+                .get_block(block_num)?
+                .map(|block| Entity::Block { block_num, block })
+                .ok_or_else(|| format!("Block {block_num} not found")),
+            Query::TransactionId(trx_id) => self.get_transaction(&trx_id).map(Entity::Transaction),
+            Query::Account(name) => self
+                .find_accounts(&[&name])?
+                .into_iter()
+                .next()
+                .map(|account| Entity::Account(Box::new(account)))
+                .ok_or_else(|| format!("Account '{name}' not found")),
+            Query::Post { author, permlink } => {
+                let content = self.get_content(&author, &permlink)?;
+                if content.author.is_empty() {
+                    return Err(format!("Post @{author}/{permlink} not found"));
+                }
+                Ok(Entity::Post(content))
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! One account type, whichever API served it
+//!
+//! `database_api.find_accounts` and `condenser_api.get_accounts` describe an
+//! account differently: NAI objects against asset strings, and only condenser
+//! includes the reputation and witness votes. [`DatabaseAccount`] and
+//! [`CondenserAccount`] decode each shape, and both convert into
+//! [`ExtendedAccount`], so code above them does not care which API answered.
+//! [`Client::get_extended_accounts`] asks `database_api` and falls back to
+//! `condenser_api` on nodes that do not serve it:
+//!
+//! ```no_run
+//! use nectarflower_rs::Client;
+//!
+//! let client = Client::new();
+//! for account in client.get_extended_accounts(&["alice"])? {
+//!     println!("{} has {} posts", account.account.name, account.stats.post_count);
+//! }
+//! # Ok::<(), nectarflower_rs::NectarError>(())
+//! ```
+
+use crate::asset::Asset;
+use crate::error::NectarError;
+use crate::fail_reason::FailReason;
+use crate::numbers::de_int;
+use crate::{Account, AccountParams, Client, FIND_ACCOUNTS_LIMIT};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Voting or downvoting mana at the time of its last update
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manabar {
+    #[serde(deserialize_with = "de_int")]
+    pub current_mana: i64,
+    #[serde(deserialize_with = "de_int")]
+    pub last_update_time: i64,
+}
+
+/// Counters and reward balances both APIs report besides the [`Account`] fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStats {
+    #[serde(default, deserialize_with = "de_int")]
+    pub id: u64,
+    #[serde(default, deserialize_with = "de_int")]
+    pub post_count: u64,
+    #[serde(default)]
+    pub can_vote: bool,
+    #[serde(default)]
+    pub voting_manabar: Manabar,
+    #[serde(default)]
+    pub downvote_manabar: Manabar,
+    #[serde(default = "Asset::zero_hive")]
+    pub reward_hive_balance: Asset,
+    #[serde(default = "Asset::zero_hbd")]
+    pub reward_hbd_balance: Asset,
+    #[serde(default = "Asset::zero_vests")]
+    pub reward_vesting_balance: Asset,
+    #[serde(default)]
+    pub witnesses_voted_for: u32,
+}
+
+/// An account as returned by `database_api.find_accounts`
+#[derive(Debug, Clone)]
+pub struct DatabaseAccount {
+    pub account: Account,
+    pub stats: AccountStats,
+}
+
+/// An account as returned by `condenser_api.get_accounts`
+#[derive(Debug, Clone)]
+pub struct CondenserAccount {
+    pub account: Account,
+    pub stats: AccountStats,
+    /// Raw reputation score
+    pub reputation: i64,
+    /// Witnesses the account votes for
+    pub witness_votes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CondenserOnly {
+    #[serde(default, deserialize_with = "de_int")]
+    reputation: i64,
+    #[serde(default)]
+    witness_votes: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for DatabaseAccount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Ok(DatabaseAccount {
+            account: Account::deserialize(&value).map_err(serde::de::Error::custom)?,
+            stats: AccountStats::deserialize(&value).map_err(serde::de::Error::custom)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for CondenserAccount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let only = CondenserOnly::deserialize(&value).map_err(serde::de::Error::custom)?;
+        Ok(CondenserAccount {
+            account: Account::deserialize(&value).map_err(serde::de::Error::custom)?,
+            stats: AccountStats::deserialize(&value).map_err(serde::de::Error::custom)?,
+            reputation: only.reputation,
+            witness_votes: only.witness_votes,
+        })
+    }
+}
+
+/// An account from either API; fields only one API serves are `Option`s
+#[derive(Debug, Clone)]
+pub struct ExtendedAccount {
+    pub account: Account,
+    pub stats: AccountStats,
+    /// Raw reputation score, served by `condenser_api` only
+    pub reputation: Option<i64>,
+    /// Witnesses the account votes for, served by `condenser_api` only
+    pub witness_votes: Option<Vec<String>>,
+}
+
+impl From<DatabaseAccount> for ExtendedAccount {
+    fn from(account: DatabaseAccount) -> Self {
+        ExtendedAccount {
+            account: account.account,
+            stats: account.stats,
+            reputation: None,
+            witness_votes: None,
+        }
+    }
+}
+
+impl From<CondenserAccount> for ExtendedAccount {
+    fn from(account: CondenserAccount) -> Self {
+        ExtendedAccount {
+            account: account.account,
+            stats: account.stats,
+            reputation: Some(account.reputation),
+            witness_votes: Some(account.witness_votes),
+        }
+    }
+}
+
+impl From<ExtendedAccount> for Account {
+    fn from(account: ExtendedAccount) -> Self {
+        account.account
+    }
+}
+
+#[derive(Deserialize)]
+struct DatabaseAccounts {
+    accounts: Vec<DatabaseAccount>,
+}
+
+impl Client {
+    /// Fetch accounts by name from `database_api`, or `condenser_api` where it is missing
+    pub fn get_extended_accounts(
+        &self,
+        names: &[&str],
+    ) -> Result<Vec<ExtendedAccount>, NectarError> {
+        let mut accounts = Vec::with_capacity(names.len());
+        for chunk in names.chunks(FIND_ACCOUNTS_LIMIT) {
+            let names: Vec<String> = chunk.iter().map(|name| name.to_string()).collect();
+            let (result, _) = self.try_nodes("database_api.find_accounts", |node| {
+                self.extended_accounts_from(node, &names)
+            });
+            accounts.extend(result?);
+        }
+        Ok(accounts)
+    }
+
+    fn extended_accounts_from(
+        &self,
+        node: &str,
+        names: &[String],
+    ) -> Result<Vec<ExtendedAccount>, NectarError> {
+        let params = AccountParams {
+            accounts: names.to_vec(),
+        };
+        let error = match self.call_node::<_, DatabaseAccounts>(
+            node,
+            "database_api.find_accounts",
+            &params,
+        ) {
+            Ok(resp) => return Ok(resp.accounts.into_iter().map(Into::into).collect()),
+            Err(e) => e,
+        };
+        if FailReason::classify(&error.to_string()) != FailReason::MissingApi {
+            return Err(error);
+        }
+        let accounts: Vec<CondenserAccount> =
+            self.call_node(node, "condenser_api.get_accounts", &[names])?;
+        Ok(accounts.into_iter().map(Into::into).collect())
+    }
+}
@@ -0,0 +1,147 @@
+//! Typed reasons for a node being listed in `failing_nodes`
+//!
+//! The published node list stores a free-form message per failing node.
+//! [`NodeFailure`] keeps that message and adds a [`FailReason`] classified
+//! from it, so callers can react to, say, rate limiting differently from a
+//! node that is behind.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Why a node is failing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailReason {
+    /// Requests did not complete within the timeout
+    Timeout,
+    /// The TLS handshake or certificate was rejected
+    BadTls,
+    /// The node's head block is too old
+    Stale,
+    /// The node does not serve a required API or method
+    MissingApi,
+    /// The node answered 429 or reported a rate limit
+    RateLimited,
+    /// The node answered with this HTTP status
+    HttpError(u16),
+    /// The node serves a different chain than the client is configured for
+    WrongChain,
+    /// Anything not covered above
+    Other,
+}
+
+impl FailReason {
+    /// Classify a failure message such as those in the published node list
+    pub fn classify(message: &str) -> Self {
+        let lower = message.to_ascii_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+        let status = status_code(&lower);
+        if status == Some(429) || has(&["rate limit", "too many requests"]) {
+            FailReason::RateLimited
+        } else if has(&["timed out", "timeout", "deadline"]) {
+            FailReason::Timeout
+        } else if has(&["certificate", "tls", "ssl", "handshake"]) {
+            FailReason::BadTls
+        } else if has(&["wrong chain"]) {
+            FailReason::WrongChain
+        } else if has(&["stale", "head block", "behind"]) {
+            FailReason::Stale
+        } else if has(&["could not find api", "could not find method", "-32601"]) {
+            FailReason::MissingApi
+        } else if let Some(code) = status {
+            FailReason::HttpError(code)
+        } else {
+            FailReason::Other
+        }
+    }
+
+    /// Short identifier for logs and reports
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FailReason::Timeout => "timeout",
+            FailReason::BadTls => "bad_tls",
+            FailReason::Stale => "stale",
+            FailReason::MissingApi => "missing_api",
+            FailReason::RateLimited => "rate_limited",
+            FailReason::HttpError(_) => "http_error",
+            FailReason::WrongChain => "wrong_chain",
+            FailReason::Other => "other",
+        }
+    }
+
+    /// Whether the node is likely to recover without operator action
+    pub fn is_transient(self) -> bool {
+        match self {
+            FailReason::Timeout | FailReason::Stale | FailReason::RateLimited => true,
+            FailReason::HttpError(code) => code >= 500,
+            FailReason::BadTls
+            | FailReason::MissingApi
+            | FailReason::WrongChain
+            | FailReason::Other => false,
+        }
+    }
+}
+
+impl fmt::Display for FailReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailReason::HttpError(code) => write!(f, "http_error({code})"),
+            reason => f.write_str(reason.as_str()),
+        }
+    }
+}
+
+/// The HTTP status in messages like `Unexpected status code: 502 Bad Gateway`
+fn status_code(message: &str) -> Option<u16> {
+    let (_, rest) = message
+        .split_once("status code: ")
+        .or_else(|| message.split_once("http "))?;
+    rest.get(..3)?
+        .parse()
+        .ok()
+        .filter(|code| (100..600).contains(code))
+}
+
+/// A failing node's reason and human-readable message
+///
+/// Serialized as the bare message, the form the published node list uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeFailure {
+    pub reason: FailReason,
+    pub message: String,
+}
+
+impl NodeFailure {
+    pub fn new(reason: FailReason, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+        }
+    }
+
+    /// A failure whose reason is classified from `message`
+    pub fn from_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            reason: FailReason::classify(&message),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for NodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Serialize for NodeFailure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.message)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeFailure {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from_message)
+    }
+}
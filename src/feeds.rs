@@ -0,0 +1,218 @@
+//! Price feed staleness checks for the top witnesses
+
+use crate::witness::WitnessRanking;
+use crate::Client;
+use serde::Serialize;
+use std::thread;
+use std::time::Duration;
+
+/// Number of elected witnesses whose feeds make up the median price
+pub const FEED_WITNESSES: u32 = 20;
+
+/// Default age after which a feed counts as stale
+pub const DEFAULT_MAX_FEED_AGE_SECS: i64 = 24 * 3_600;
+
+/// The price feed of one witness
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedStatus {
+    pub owner: String,
+    pub rank: usize,
+    pub last_update: String,
+    /// Seconds since the feed was published, `i64::MAX` if it never was
+    pub age_secs: i64,
+    /// Published HBD per HIVE
+    pub price: f64,
+    pub stale: bool,
+}
+
+/// Feed freshness of the top witnesses at a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedHealth {
+    pub head_block_number: u32,
+    pub time: String,
+    pub max_age_secs: i64,
+    pub feeds: Vec<FeedStatus>,
+}
+
+impl FeedHealth {
+    /// Flag feeds in a ranking older than `max_age_secs`, skipping disabled witnesses
+    pub fn from_ranking(ranking: &WitnessRanking, max_age_secs: i64) -> Self {
+        let feeds = ranking
+            .witnesses
+            .iter()
+            .filter(|ranked| !ranked.witness.is_disabled())
+            .map(|ranked| FeedStatus {
+                owner: ranked.witness.owner.clone(),
+                rank: ranked.rank,
+                last_update: ranked.witness.last_hbd_exchange_update.clone(),
+                age_secs: ranked.feed_age_secs,
+                price: ranked.witness.hbd_exchange_rate.to_f64(),
+                stale: ranked.feed_age_secs > max_age_secs,
+            })
+            .collect();
+        Self {
+            head_block_number: ranking.head_block_number,
+            time: ranking.time.clone(),
+            max_age_secs,
+            feeds,
+        }
+    }
+
+    pub fn get(&self, owner: &str) -> Option<&FeedStatus> {
+        self.feeds.iter().find(|f| f.owner == owner)
+    }
+
+    pub fn stale(&self) -> impl Iterator<Item = &FeedStatus> {
+        self.feeds.iter().filter(|f| f.stale)
+    }
+
+    pub fn fresh_count(&self) -> usize {
+        self.feeds.iter().filter(|f| !f.stale).count()
+    }
+
+    /// Whether a majority of feeds are fresh, so the median price is fresh too
+    pub fn median_is_healthy(&self) -> bool {
+        self.fresh_count() * 2 > self.feeds.len()
+    }
+
+    /// Events describing how feed health changed since an earlier check
+    pub fn events_since(&self, previous: &FeedHealth) -> Vec<FeedEvent> {
+        let mut events = Vec::new();
+        for feed in &self.feeds {
+            let was_stale = previous.get(&feed.owner).is_some_and(|f| f.stale);
+            if feed.stale && !was_stale {
+                events.push(FeedEvent::Stale {
+                    owner: feed.owner.clone(),
+                    age_secs: feed.age_secs,
+                });
+            } else if !feed.stale && was_stale {
+                events.push(FeedEvent::Refreshed {
+                    owner: feed.owner.clone(),
+                });
+            }
+        }
+        match (previous.median_is_healthy(), self.median_is_healthy()) {
+            (true, false) => events.push(FeedEvent::MedianDegraded {
+                fresh: self.fresh_count(),
+                total: self.feeds.len(),
+            }),
+            (false, true) => events.push(FeedEvent::MedianRecovered {
+                fresh: self.fresh_count(),
+                total: self.feeds.len(),
+            }),
+            _ => {}
+        }
+        events
+    }
+
+    /// Events for a first check, reporting every stale feed
+    pub fn initial_events(&self) -> Vec<FeedEvent> {
+        let mut events: Vec<FeedEvent> = self
+            .stale()
+            .map(|feed| FeedEvent::Stale {
+                owner: feed.owner.clone(),
+                age_secs: feed.age_secs,
+            })
+            .collect();
+        if !self.median_is_healthy() {
+            events.push(FeedEvent::MedianDegraded {
+                fresh: self.fresh_count(),
+                total: self.feeds.len(),
+            });
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedEvent {
+    /// A witness's feed became older than the threshold
+    Stale { owner: String, age_secs: i64 },
+    /// A previously stale feed was republished
+    Refreshed { owner: String },
+    /// Half or more of the feeds are stale
+    MedianDegraded { fresh: usize, total: usize },
+    /// A majority of feeds are fresh again
+    MedianRecovered { fresh: usize, total: usize },
+}
+
+/// Polls witness feeds and reports health changes
+#[derive(Debug, Clone)]
+pub struct FeedMonitor {
+    top: u32,
+    max_age_secs: i64,
+    interval: Duration,
+    last: Option<FeedHealth>,
+}
+
+impl Default for FeedMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeedMonitor {
+    /// Check the top 20 witnesses every ten minutes against a one day threshold
+    pub fn new() -> Self {
+        Self {
+            top: FEED_WITNESSES,
+            max_age_secs: DEFAULT_MAX_FEED_AGE_SECS,
+            interval: Duration::from_secs(600),
+            last: None,
+        }
+    }
+
+    pub fn top(mut self, top: u32) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn max_age_secs(mut self, max_age_secs: i64) -> Self {
+        self.max_age_secs = max_age_secs;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The result of the most recent check
+    pub fn last(&self) -> Option<&FeedHealth> {
+        self.last.as_ref()
+    }
+
+    /// Check feeds once, returning the events since the previous check
+    pub fn check(&mut self, client: &Client) -> Result<Vec<FeedEvent>, String> {
+        let health = client.feed_health(self.top, self.max_age_secs)?;
+        let events = match &self.last {
+            Some(previous) => health.events_since(previous),
+            None => health.initial_events(),
+        };
+        self.last = Some(health);
+        Ok(events)
+    }
+
+    /// Check feeds every interval until `on_event` returns `false`
+    pub fn watch<F>(&mut self, client: &Client, mut on_event: F) -> Result<(), String>
+    where
+        F: FnMut(FeedEvent) -> bool,
+    {
+        loop {
+            for event in self.check(client)? {
+                if !on_event(event) {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+impl Client {
+    /// Flag price feeds of the top `top` witnesses older than `max_age_secs`
+    pub fn feed_health(&self, top: u32, max_age_secs: i64) -> Result<FeedHealth, String> {
+        let ranking = self.witness_ranking(top)?;
+        Ok(FeedHealth::from_ranking(&ranking, max_age_secs))
+    }
+}
@@ -0,0 +1,459 @@
+//! C-compatible API for embedding the crate in non-Rust applications
+//!
+//! Enabled with the `ffi` feature; build a shared library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! Conventions:
+//! - Strings are NUL-terminated UTF-8. Strings returned by `nf_*` functions are
+//!   owned by the caller and must be released with [`nf_string_free`].
+//! - Functions returning a pointer return NULL on failure and functions
+//!   returning `i32` return a negative value; [`nf_last_error`] then describes
+//!   the failure on the calling thread.
+//! - Structured values (params, results, transactions) are passed as JSON.
+//! - A panic inside the library is caught at the boundary and reported as a
+//!   failure instead of unwinding into the caller.
+//!
+//! `include/nectarflower.h` declares these functions for C and C++; regenerate
+//! it with `cbindgen --config cbindgen.toml --output include/nectarflower.h`.
+
+use crate::block::BlockStream;
+use crate::chain::ChainConfig;
+use crate::secret::SecretWif;
+use crate::transaction::{Transaction, TransactionSigner};
+use crate::Client;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Version of this C API, bumped on incompatible changes
+pub const NF_API_VERSION: u32 = 1;
+
+/// Length of a hex-encoded compact signature, excluding the NUL terminator
+pub const NF_SIGNATURE_HEX_LEN: usize = 130;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Read a C string argument, recording an error for NULL or invalid UTF-8
+unsafe fn arg<'a>(ptr: *const c_char, name: &str) -> Option<&'a str> {
+    if ptr.is_null() {
+        set_error(format!("Argument {name} is NULL"));
+        return None;
+    }
+    match CStr::from_ptr(ptr).to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_error(format!("Argument {name} is not valid UTF-8"));
+            None
+        }
+    }
+}
+
+fn into_c_string(result: Result<String, String>) -> *mut c_char {
+    match result.and_then(|s| CString::new(s).map_err(|e| e.to_string())) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            set_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Run the body of an export, turning a panic into a failure returning `failed`
+fn guard<T>(failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        set_error(format!("Internal error: {message}"));
+        failed
+    })
+}
+
+fn status(result: Result<(), String>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_error(e);
+            -1
+        }
+    }
+}
+
+/// The version of this C API
+#[no_mangle]
+pub extern "C" fn nf_api_version() -> u32 {
+    NF_API_VERSION
+}
+
+/// The last error on this thread, or NULL; valid until the next failing call
+#[no_mangle]
+pub extern "C" fn nf_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+/// `s` must be NULL or a pointer returned by an `nf_*` function, released only once.
+#[no_mangle]
+pub unsafe extern "C" fn nf_string_free(s: *mut c_char) {
+    guard((), || {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
+
+/// Create a client using the default node
+#[no_mangle]
+pub extern "C" fn nf_client_new() -> *mut Client {
+    guard(ptr::null_mut(), || Box::into_raw(Box::new(Client::new())))
+}
+
+/// Destroy a client
+///
+/// # Safety
+/// `client` must be NULL or a pointer from [`nf_client_new`], freed only once.
+#[no_mangle]
+pub unsafe extern "C" fn nf_client_free(client: *mut Client) {
+    guard((), || {
+        if !client.is_null() {
+            drop(Box::from_raw(client));
+        }
+    })
+}
+
+/// Replace the client's nodes with a JSON array of URLs
+///
+/// # Safety
+/// `client` must be a live client and `nodes_json` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn nf_client_set_nodes(
+    client: *mut Client,
+    nodes_json: *const c_char,
+) -> i32 {
+    guard(-1, || {
+        let Some(client) = client.as_mut() else {
+            set_error("Argument client is NULL".to_string());
+            return -1;
+        };
+        let Some(nodes) = arg(nodes_json, "nodes_json") else {
+            return -1;
+        };
+        status(
+            serde_json::from_str::<Vec<String>>(nodes)
+                .map_err(|e| format!("Invalid nodes JSON: {e}"))
+                .and_then(|nodes| Ok(client.set_nodes(nodes, HashMap::new()).map(drop)?)),
+        )
+    })
+}
+
+/// Load the node list published in an account's metadata
+///
+/// # Safety
+/// `client` must be a live client and `account` a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn nf_client_update_nodes_from_account(
+    client: *mut Client,
+    account: *const c_char,
+) -> i32 {
+    guard(-1, || {
+        let Some(client) = client.as_mut() else {
+            set_error("Argument client is NULL".to_string());
+            return -1;
+        };
+        let Some(account) = arg(account, "account") else {
+            return -1;
+        };
+        status(
+            client
+                .update_nodes_from_account(account)
+                .map(drop)
+                .map_err(String::from),
+        )
+    })
+}
+
+/// Make a JSON-RPC call, returning the result as a JSON string
+///
+/// # Safety
+/// `client` must be a live client; `method` and `params_json` valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn nf_call(
+    client: *const Client,
+    method: *const c_char,
+    params_json: *const c_char,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let Some(client) = client.as_ref() else {
+            set_error("Argument client is NULL".to_string());
+            return ptr::null_mut();
+        };
+        let (Some(method), Some(params)) = (arg(method, "method"), arg(params_json, "params_json"))
+        else {
+            return ptr::null_mut();
+        };
+        into_c_string((|| {
+            let params: Value =
+                serde_json::from_str(params).map_err(|e| format!("Invalid params JSON: {e}"))?;
+            let result: Value = client.call(method, params)?;
+            Ok(result.to_string())
+        })())
+    })
+}
+
+/// The id of a JSON transaction, computed locally
+///
+/// # Safety
+/// `tx_json` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn nf_transaction_id(tx_json: *const c_char) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let Some(tx) = arg(tx_json, "tx_json") else {
+            return ptr::null_mut();
+        };
+        into_c_string(parse_transaction(tx).and_then(|tx| tx.id()))
+    })
+}
+
+/// The hex digest to sign for a JSON transaction on `chain_id`, computed locally
+///
+/// # Safety
+/// `tx_json` and `chain_id` must be valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn nf_transaction_digest(
+    tx_json: *const c_char,
+    chain_id: *const c_char,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let (Some(tx), Some(chain_id)) = (arg(tx_json, "tx_json"), arg(chain_id, "chain_id"))
+        else {
+            return ptr::null_mut();
+        };
+        into_c_string(
+            parse_transaction(tx)
+                .and_then(|tx| tx.digest(&ChainConfig::new("custom", chain_id)))
+                .map(|digest| crate::crypto::to_hex(&digest)),
+        )
+    })
+}
+
+/// Signs a 32-byte digest, writing a NUL-terminated hex signature of
+/// [`NF_SIGNATURE_HEX_LEN`] characters into `out`; returns 0 on success
+pub type NfSignCallback = extern "C" fn(
+    digest: *const u8,
+    out: *mut c_char,
+    out_len: usize,
+    user_data: *mut c_void,
+) -> i32;
+
+struct CallbackSigner {
+    callback: NfSignCallback,
+    user_data: *mut c_void,
+}
+
+impl TransactionSigner for CallbackSigner {
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<String, String> {
+        let mut out = [0 as c_char; NF_SIGNATURE_HEX_LEN + 1];
+        let code = (self.callback)(digest.as_ptr(), out.as_mut_ptr(), out.len(), self.user_data);
+        if code != 0 {
+            return Err(format!("Signing callback failed with code {code}"));
+        }
+        out[NF_SIGNATURE_HEX_LEN] = 0;
+        // SAFETY: `out` is NUL-terminated above
+        let signature = unsafe { CStr::from_ptr(out.as_ptr()) };
+        signature
+            .to_str()
+            .map(str::to_string)
+            .map_err(|_| "Signing callback returned invalid UTF-8".to_string())
+    }
+}
+
+/// Sign a JSON transaction with a callback, returning the signed transaction as JSON
+///
+/// The digest is always computed locally, so a transaction with an operation
+/// the local serializer does not cover is an error. `client` may be NULL for
+/// offline signing on `chain_id`; otherwise the client's chain is used and
+/// `chain_id` may be NULL.
+///
+/// # Safety
+/// `client` must be NULL or a live client; `tx_json` a valid C string and
+/// `chain_id` NULL or a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn nf_sign_transaction(
+    client: *const Client,
+    tx_json: *const c_char,
+    chain_id: *const c_char,
+    callback: NfSignCallback,
+    user_data: *mut c_void,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let Some(tx) = arg(tx_json, "tx_json") else {
+            return ptr::null_mut();
+        };
+        let signer = CallbackSigner {
+            callback,
+            user_data,
+        };
+        sign_with(client.as_ref(), tx, chain_id, &signer)
+    })
+}
+
+/// Sign a JSON transaction with a WIF private key, returning the signed transaction as JSON
+///
+/// As [`nf_sign_transaction`], with the signature made by the library; the
+/// key is wiped from the library's memory once the call returns.
+///
+/// # Safety
+/// `client` must be NULL or a live client; `tx_json` and `wif` valid C strings
+/// and `chain_id` NULL or a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn nf_sign_transaction_with_key(
+    client: *const Client,
+    tx_json: *const c_char,
+    chain_id: *const c_char,
+    wif: *const c_char,
+) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let (Some(tx), Some(wif)) = (arg(tx_json, "tx_json"), arg(wif, "wif")) else {
+            return ptr::null_mut();
+        };
+        let key = match SecretWif::parse(wif) {
+            Ok(key) => key,
+            Err(e) => return into_c_string(Err(e)),
+        };
+        sign_with(client.as_ref(), tx, chain_id, &key)
+    })
+}
+
+/// The public key of a WIF private key, with the address prefix `prefix` such as `STM`
+///
+/// # Safety
+/// `wif` and `prefix` must be valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn nf_public_key(wif: *const c_char, prefix: *const c_char) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let (Some(wif), Some(prefix)) = (arg(wif, "wif"), arg(prefix, "prefix")) else {
+            return ptr::null_mut();
+        };
+        into_c_string(SecretWif::parse(wif).and_then(|key| key.public_key(prefix)))
+    })
+}
+
+/// Sign `tx` for the client's chain, or for `chain_id` without a client
+///
+/// # Safety
+/// `chain_id` must be NULL or a valid C string.
+unsafe fn sign_with(
+    client: Option<&Client>,
+    tx: &str,
+    chain_id: *const c_char,
+    signer: &dyn TransactionSigner,
+) -> *mut c_char {
+    let chain = match client {
+        Some(client) => client.chain().clone(),
+        None => match arg(chain_id, "chain_id") {
+            Some(chain_id) => ChainConfig::new("custom", chain_id),
+            None => return ptr::null_mut(),
+        },
+    };
+    into_c_string((|| {
+        let mut tx = parse_transaction(tx)?;
+        let digest = tx.digest(&chain)?;
+        tx.signatures.push(signer.sign_digest(&digest)?);
+        serde_json::to_string(&tx).map_err(|e| e.to_string())
+    })())
+}
+
+/// A block stream created by [`nf_stream_new`]
+pub struct NfBlockStream {
+    stream: BlockStream<'static>,
+    error: Option<CString>,
+}
+
+/// Stream blocks starting at `start`
+///
+/// # Safety
+/// `client` must be a live client that outlives the returned stream.
+#[no_mangle]
+pub unsafe extern "C" fn nf_stream_new(client: *const Client, start: u32) -> *mut NfBlockStream {
+    guard(ptr::null_mut(), || {
+        let Some(client) = client.as_ref() else {
+            set_error("Argument client is NULL".to_string());
+            return ptr::null_mut();
+        };
+        Box::into_raw(Box::new(NfBlockStream {
+            stream: client.stream_blocks(start),
+            error: None,
+        }))
+    })
+}
+
+/// The next block as JSON, waiting for it to be produced if necessary
+///
+/// # Safety
+/// `stream` must be a live stream from [`nf_stream_new`].
+#[no_mangle]
+pub unsafe extern "C" fn nf_stream_next(stream: *mut NfBlockStream) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let Some(stream) = stream.as_mut() else {
+            set_error("Argument stream is NULL".to_string());
+            return ptr::null_mut();
+        };
+        let block = match stream.stream.next() {
+            Some(block) => block.and_then(|b| serde_json::to_string(&b).map_err(|e| e.to_string())),
+            None => Err("Block stream ended".to_string()),
+        };
+        stream.error = block
+            .as_ref()
+            .err()
+            .and_then(|e| CString::new(e.replace('\0', " ")).ok());
+        into_c_string(block)
+    })
+}
+
+/// The error of the last failed [`nf_stream_next`] on `stream`, or NULL
+///
+/// Unlike [`nf_last_error`] this can be read from a thread other than the one
+/// that called [`nf_stream_next`].
+///
+/// # Safety
+/// `stream` must be a live stream from [`nf_stream_new`].
+#[no_mangle]
+pub unsafe extern "C" fn nf_stream_error(stream: *const NfBlockStream) -> *const c_char {
+    guard(ptr::null(), || {
+        stream
+            .as_ref()
+            .and_then(|s| s.error.as_ref())
+            .map_or(ptr::null(), |e| e.as_ptr())
+    })
+}
+
+/// Destroy a block stream
+///
+/// # Safety
+/// `stream` must be NULL or a pointer from [`nf_stream_new`], freed only once.
+#[no_mangle]
+pub unsafe extern "C" fn nf_stream_free(stream: *mut NfBlockStream) {
+    guard((), || {
+        if !stream.is_null() {
+            drop(Box::from_raw(stream));
+        }
+    })
+}
+
+fn parse_transaction(json: &str) -> Result<Transaction, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid transaction JSON: {e}"))
+}
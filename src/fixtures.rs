@@ -0,0 +1,162 @@
+//! Real-world JSON samples and schema-variation helpers for property testing
+//!
+//! Enabled with the `fixtures` feature. Nodes disagree on details such as
+//! which optional fields are present and whether large integers are sent as
+//! numbers or strings; [`variants`] produces those variations from a sample so
+//! types can be checked against all of them.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A block as returned by `block_api.get_block`
+pub const BLOCK: &str = include_str!("../fixtures/block.json");
+
+/// An account as returned by `database_api.find_accounts`
+pub const ACCOUNT: &str = include_str!("../fixtures/account.json");
+
+/// `database_api.get_dynamic_global_properties` output
+pub const DYNAMIC_GLOBAL_PROPERTIES: &str =
+    include_str!("../fixtures/dynamic_global_properties.json");
+
+/// A list of operations in `{type, value}` form, mixing legacy and NAI assets
+pub const OPERATIONS: &str = include_str!("../fixtures/operations.json");
+
+/// The node list metadata published by the `nectarflower` account
+pub const NODE_METADATA: &str = include_str!("../fixtures/node_metadata.json");
+
+/// Transactions with their expected serialization, digest and id
+pub const SIGNING_VECTORS: &str = include_str!("../fixtures/signing_vectors.json");
+
+/// Every fixture by name, parsed
+pub fn corpus() -> Vec<(&'static str, Value)> {
+    [
+        ("block", BLOCK),
+        ("account", ACCOUNT),
+        ("dynamic_global_properties", DYNAMIC_GLOBAL_PROPERTIES),
+        ("operations", OPERATIONS),
+        ("node_metadata", NODE_METADATA),
+    ]
+    .into_iter()
+    .map(|(name, json)| {
+        (
+            name,
+            serde_json::from_str(json).expect("fixture is valid JSON"),
+        )
+    })
+    .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Variation {
+    /// The field at this JSON pointer was removed
+    MissingField(String),
+    /// The number at this JSON pointer was sent as a string
+    NumberAsString(String),
+    /// The numeric string at this JSON pointer was sent as a number
+    StringAsNumber(String),
+}
+
+impl Variation {
+    /// JSON pointer of the changed value
+    pub fn path(&self) -> &str {
+        match self {
+            Variation::MissingField(path)
+            | Variation::NumberAsString(path)
+            | Variation::StringAsNumber(path) => path,
+        }
+    }
+}
+
+/// A sample with a single variation applied
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub variation: Variation,
+    pub value: Value,
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn collect_variations(value: &Value, path: &str, out: &mut Vec<Variation>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = format!("{path}/{}", escape(key));
+                out.push(Variation::MissingField(child_path.clone()));
+                collect_variations(child, &child_path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_variations(child, &format!("{path}/{index}"), out);
+            }
+        }
+        Value::Number(_) => out.push(Variation::NumberAsString(path.to_string())),
+        Value::String(s) if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => {
+            out.push(Variation::StringAsNumber(path.to_string()))
+        }
+        _ => {}
+    }
+}
+
+fn apply(value: &Value, variation: &Variation) -> Option<Value> {
+    let mut value = value.clone();
+    match variation {
+        Variation::MissingField(path) => {
+            let (parent, key) = path.rsplit_once('/')?;
+            let key = key.replace("~1", "/").replace("~0", "~");
+            value.pointer_mut(parent)?.as_object_mut()?.remove(&key)?;
+        }
+        Variation::NumberAsString(path) => {
+            let target = value.pointer_mut(path)?;
+            *target = Value::String(target.as_number()?.to_string());
+        }
+        Variation::StringAsNumber(path) => {
+            let target = value.pointer_mut(path)?;
+            let digits = target.as_str()?;
+            let number: serde_json::Number = digits.parse().ok()?;
+            // Integers too large for the number type would be sent as floats
+            if number.to_string() != digits {
+                return None;
+            }
+            *target = Value::Number(number);
+        }
+    }
+    Some(value)
+}
+
+/// Every single-change variation of a sample
+pub fn variants(value: &Value) -> Vec<Variant> {
+    let mut variations = Vec::new();
+    collect_variations(value, "", &mut variations);
+    variations
+        .into_iter()
+        .filter_map(|variation| apply(value, &variation).map(|value| Variant { variation, value }))
+        .collect()
+}
+
+/// Decode every variant of a sample as `T`, returning the ones that fail and why
+///
+/// Variations touching a path in `required` (or below it) are skipped, since
+/// removing a genuinely required field is expected to fail.
+pub fn failing_variants<T: DeserializeOwned>(
+    value: &Value,
+    required: &[&str],
+) -> Vec<(Variation, String)> {
+    variants(value)
+        .into_iter()
+        .filter(|variant| {
+            !matches!(variant.variation, Variation::MissingField(_))
+                || !required.iter().any(|r| {
+                    let path = variant.variation.path();
+                    path == *r || path.starts_with(&format!("{r}/"))
+                })
+        })
+        .filter_map(|variant| {
+            serde_json::from_value::<T>(variant.value)
+                .err()
+                .map(|e| (variant.variation, e.to_string()))
+        })
+        .collect()
+}
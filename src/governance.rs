@@ -0,0 +1,108 @@
+//! Reminders for accounts whose witness and proposal votes are about to expire
+
+use crate::comment::PostDraft;
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::{Account, Client};
+use serde::Serialize;
+
+/// An account whose governance votes expire soon
+#[derive(Debug, Clone, Serialize)]
+pub struct GovernanceExpiry {
+    pub account: String,
+    pub expires: String,
+    pub days_left: i64,
+}
+
+/// Accounts with governance votes expiring within a number of days
+#[derive(Debug, Clone, Serialize)]
+pub struct GovernanceReport {
+    pub generated_at: String,
+    pub within_days: u32,
+    /// Soonest expiry first
+    pub expiring: Vec<GovernanceExpiry>,
+}
+
+impl GovernanceReport {
+    /// Find accounts whose votes expire between `now` and `within_days` later
+    ///
+    /// Accounts without governance votes (or whose votes already expired) are skipped.
+    pub fn from_accounts<I>(accounts: I, now: i64, within_days: u32) -> Self
+    where
+        I: IntoIterator<Item = Account>,
+    {
+        let until = now + i64::from(within_days) * 86_400;
+        let mut expiring: Vec<GovernanceExpiry> = accounts
+            .into_iter()
+            .filter_map(|account| {
+                let expires = parse_timestamp(&account.governance_vote_expiration_ts).ok()?;
+                (now..=until).contains(&expires).then(|| GovernanceExpiry {
+                    days_left: (expires - now) / 86_400,
+                    expires: account.governance_vote_expiration_ts,
+                    account: account.name,
+                })
+            })
+            .collect();
+        expiring.sort_by(|a, b| a.expires.cmp(&b.expires).then(a.account.cmp(&b.account)));
+        Self {
+            generated_at: format_timestamp(now),
+            within_days,
+            expiring,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expiring.is_empty()
+    }
+
+    /// A markdown table mentioning each account
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "The witness and proposal votes of these accounts expire within {} days. \
+             Vote for a witness or proposal to renew them.\n\n\
+             | Account | Expires (UTC) | Days left |\n|---|---|---|\n",
+            self.within_days
+        );
+        for e in &self.expiring {
+            out.push_str(&format!(
+                "| @{} | {} | {} |\n",
+                e.account,
+                e.expires.replace('T', " "),
+                e.days_left
+            ));
+        }
+        out
+    }
+
+    /// A post draft announcing the report, ready for [`WriteClient::publish`](crate::WriteClient::publish)
+    pub fn to_draft(&self, author: &str, title: &str, tags: &[&str]) -> PostDraft {
+        let mut draft = PostDraft::new(author, "", title, &self.to_markdown());
+        draft.tags = tags.iter().map(|t| t.to_string()).collect();
+        draft
+    }
+}
+
+impl Client {
+    /// Report governance votes expiring within `within_days`, for `accounts` or every account
+    pub fn governance_expiry_report(
+        &self,
+        accounts: Option<&[&str]>,
+        within_days: u32,
+    ) -> Result<GovernanceReport, String> {
+        let now = parse_timestamp(&self.get_dynamic_global_properties()?.time)?;
+        match accounts {
+            Some(names) => Ok(GovernanceReport::from_accounts(
+                self.find_accounts(names)?,
+                now,
+                within_days,
+            )),
+            None => {
+                let mut error = None;
+                let accounts = self
+                    .scan_accounts()
+                    .map_while(|account| account.map_err(|e| error = Some(e)).ok());
+                let report = GovernanceReport::from_accounts(accounts, now, within_days);
+                error.map_or(Ok(report), Err)
+            }
+        }
+    }
+}
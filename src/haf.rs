@@ -0,0 +1,222 @@
+//! Reading history from a HAF (Hive Application Framework) PostgreSQL database
+//!
+//! Enabled with the `haf` feature. The crate does not bundle a database
+//! driver: implement [`HafConnection`] on top of the PostgreSQL client of your
+//! choice. [`HafSource`] replays blocks from HAF and, once given a client,
+//! continues with the live RPC stream after HAF's irreversible head.
+
+use crate::block::{Block, BlockStream, StreamedBlock};
+use crate::block_source::BlockSource;
+use crate::history::HistoryEntry;
+use crate::Client;
+use serde_json::Value;
+
+/// Runs SQL against a HAF database
+pub trait HafConnection {
+    /// Run `sql` with positional `$1..` parameters and return the first column of each row
+    ///
+    /// The queries issued by this module select a single `json` column.
+    fn query_json(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Value>, String>;
+}
+
+/// First operation type id of virtual operations in HAF
+const FIRST_VIRTUAL_OP_TYPE: u32 = 50;
+
+const TIMESTAMP_FORMAT: &str = r#"'YYYY-MM-DD"T"HH24:MI:SS'"#;
+
+fn block_query() -> String {
+    format!(
+        "SELECT json_build_object(
+            'previous', encode(b.prev, 'hex'),
+            'timestamp', to_char(b.created_at, {ts}),
+            'witness', (SELECT a.name FROM hive.accounts_view a WHERE a.id = b.producer_account_id),
+            'transaction_merkle_root', encode(b.transaction_merkle_root, 'hex'),
+            'extensions', COALESCE(b.extensions, '[]'::jsonb),
+            'witness_signature', encode(b.witness_signature, 'hex'),
+            'block_id', encode(b.hash, 'hex'),
+            'signing_key', b.signing_key,
+            'transaction_ids', COALESCE((
+                SELECT json_agg(encode(t.trx_hash, 'hex') ORDER BY t.trx_in_block)
+                FROM hive.transactions_view t WHERE t.block_num = b.num), '[]'),
+            'transactions', COALESCE((
+                SELECT json_agg(json_build_object(
+                    'ref_block_num', t.ref_block_num,
+                    'ref_block_prefix', t.ref_block_prefix,
+                    'expiration', to_char(t.expiration, {ts}),
+                    'operations', COALESCE((
+                        SELECT json_agg(o.body::jsonb ORDER BY o.op_pos)
+                        FROM hive.operations_view o
+                        WHERE o.block_num = t.block_num AND o.trx_in_block = t.trx_in_block
+                            AND o.op_type_id < {virt}), '[]'),
+                    'extensions', '[]'::json,
+                    'signatures', (
+                        SELECT json_agg(encode(s, 'hex'))
+                        FROM (SELECT t.signature AS s UNION ALL
+                            SELECT m.signature FROM hive.transactions_multisig_view m
+                            WHERE m.trx_hash = t.trx_hash) sigs)
+                ) ORDER BY t.trx_in_block)
+                FROM hive.transactions_view t WHERE t.block_num = b.num), '[]')
+        ) FROM hive.blocks_view b WHERE b.num = $1",
+        ts = TIMESTAMP_FORMAT,
+        virt = FIRST_VIRTUAL_OP_TYPE,
+    )
+}
+
+fn account_history_query() -> String {
+    format!(
+        "SELECT json_build_object(
+            'index', ao.account_op_seq_no,
+            'trx_id', COALESCE(encode(t.trx_hash, 'hex'), '0000000000000000000000000000000000000000'),
+            'block', o.block_num,
+            'trx_in_block', GREATEST(o.trx_in_block, 0),
+            'op_in_trx', o.op_pos,
+            'virtual_op', o.op_type_id >= {virt},
+            'timestamp', to_char(o.timestamp, {ts}),
+            'op', o.body::jsonb
+        )
+        FROM hive.account_operations_view ao
+        JOIN hive.accounts_view a ON a.id = ao.account_id
+        JOIN hive.operations_view o ON o.id = ao.operation_id
+        LEFT JOIN hive.transactions_view t
+            ON t.block_num = o.block_num AND t.trx_in_block = o.trx_in_block
+        WHERE a.name = $1 AND ao.account_op_seq_no >= $2
+        ORDER BY ao.account_op_seq_no
+        LIMIT $3",
+        ts = TIMESTAMP_FORMAT,
+        virt = FIRST_VIRTUAL_OP_TYPE,
+    )
+}
+
+const IRREVERSIBLE_QUERY: &str =
+    "SELECT to_json(consistent_block) FROM hive.irreversible_data_view LIMIT 1";
+
+/// Blocks read from HAF, optionally continuing with RPC once HAF is exhausted
+pub struct HafSource<'a, C: HafConnection> {
+    conn: C,
+    next: u32,
+    irreversible: u32,
+    client: Option<&'a Client>,
+    live: Option<BlockStream<'a>>,
+}
+
+impl<C: HafConnection> std::fmt::Debug for HafSource<'_, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HafSource")
+            .field("next", &self.next)
+            .field("irreversible", &self.irreversible)
+            .field("live", &self.live.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, C: HafConnection> HafSource<'a, C> {
+    /// Read blocks from `start` until HAF's last irreversible block
+    pub fn new(conn: C, start: u32) -> Self {
+        Self {
+            conn,
+            next: start,
+            irreversible: 0,
+            client: None,
+            live: None,
+        }
+    }
+
+    /// Continue with `client`'s block stream after HAF's irreversible head
+    pub fn with_live(mut self, client: &'a Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// The number of the next block this source will yield
+    pub fn next_block_num(&self) -> u32 {
+        self.live
+            .as_ref()
+            .map_or(self.next, BlockStream::next_block_num)
+    }
+
+    pub fn connection(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    /// HAF's last irreversible (fully synced) block
+    pub fn haf_irreversible_block_num(&mut self) -> Result<u32, String> {
+        let rows = self.conn.query_json(IRREVERSIBLE_QUERY, &[])?;
+        let num = rows
+            .first()
+            .and_then(Value::as_u64)
+            .ok_or_else(|| "HAF returned no irreversible block".to_string())?;
+        self.irreversible = num as u32;
+        Ok(self.irreversible)
+    }
+
+    /// Fetch one block from HAF, `None` if HAF does not have it
+    pub fn get_block(&mut self, block_num: u32) -> Result<Option<Block>, String> {
+        let rows = self.conn.query_json(&block_query(), &[block_num.into()])?;
+        match rows.into_iter().next() {
+            Some(Value::Null) | None => Ok(None),
+            Some(row) => serde_json::from_value(row)
+                .map(Some)
+                .map_err(|e| format!("Error decoding HAF block {block_num}: {e}")),
+        }
+    }
+
+    /// Fetch up to `limit` history entries of `account` starting at sequence number `start`
+    pub fn account_history(
+        &mut self,
+        account: &str,
+        start: u64,
+        limit: u32,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        self.conn
+            .query_json(
+                &account_history_query(),
+                &[account.into(), start.into(), limit.into()],
+            )?
+            .into_iter()
+            .map(|row| {
+                serde_json::from_value(row)
+                    .map_err(|e| format!("Error decoding HAF history of {account}: {e}"))
+            })
+            .collect()
+    }
+
+    fn next_from_haf(&mut self) -> Result<Option<StreamedBlock>, String> {
+        if self.next > self.irreversible && self.next > self.haf_irreversible_block_num()? {
+            return Ok(None);
+        }
+        let block_num = self.next;
+        let Some(block) = self.get_block(block_num)? else {
+            return Ok(None);
+        };
+        self.next += 1;
+        Ok(Some(StreamedBlock {
+            block_num,
+            header: block.header.clone(),
+            block: Some(block),
+        }))
+    }
+}
+
+impl<C: HafConnection> BlockSource for HafSource<'_, C> {
+    fn next_block(&mut self) -> Option<Result<StreamedBlock, String>> {
+        if let Some(live) = &mut self.live {
+            return live.next();
+        }
+        match self.next_from_haf() {
+            Ok(Some(block)) => Some(Ok(block)),
+            Ok(None) => {
+                let client = self.client?;
+                let live = self.live.insert(client.stream_blocks(self.next));
+                live.next()
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn last_irreversible_block_num(&mut self) -> Result<u32, String> {
+        match &mut self.live {
+            Some(live) => live.last_irreversible_block_num(),
+            None => self.haf_irreversible_block_num(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Waiting for new blocks without polling every block interval
+//!
+//! Once a [`BlockStream`](crate::BlockStream) reaches the head block it polls
+//! every [`BLOCK_INTERVAL`](crate::block::BLOCK_INTERVAL). With a [`HeadNotifier`] set through
+//! [`Client::subscribe_head`], it waits on the notifier instead and sees new
+//! blocks as soon as the notifier does. When the notifier fails, streams fall
+//! back to polling for [`SUBSCRIPTION_RETRY`] before trying it again.
+//!
+//! Hive's node APIs offer no head block subscription: hived dropped condenser's
+//! block callbacks, and its WebSocket endpoint only answers calls. Polling them
+//! faster than the block interval only costs requests, so without a notifier
+//! streams poll every block interval. Implement [`HeadNotifier`] for
+//! sources that do push new blocks, such as a HAF database's notifications or
+//! a private block relay.
+
+use crate::Client;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest a stream waits on a notifier before checking the head itself
+pub const SUBSCRIPTION_WAIT: Duration = Duration::from_secs(30);
+
+/// How long streams poll after a notifier fails
+pub const SUBSCRIPTION_RETRY: Duration = Duration::from_secs(60);
+
+/// Announces new head blocks
+pub trait HeadNotifier: Send + Sync {
+    /// Wait until block `block_num` exists or `timeout` passes
+    ///
+    /// `client` is the client the stream reads from, for notifiers that ask
+    /// its nodes. Returns the head block number once it is at least
+    /// `block_num`, or `None` on timeout.
+    fn wait_for_block(
+        &self,
+        client: &Client,
+        block_num: u32,
+        timeout: Duration,
+    ) -> Result<Option<u32>, String>;
+}
+
+/// The notifier set with [`Client::subscribe_head`], and when it last failed
+#[derive(Clone, Default)]
+pub(crate) struct HeadSubscription {
+    notifier: Option<Arc<dyn HeadNotifier>>,
+    failed_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl fmt::Debug for HeadSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeadSubscription")
+            .field("notifier", &self.notifier.is_some())
+            .field("failed_at", &self.failed_at.lock().unwrap())
+            .finish()
+    }
+}
+
+impl Client {
+    /// Wait for new blocks with `notifier` instead of polling
+    pub fn subscribe_head<N: HeadNotifier + 'static>(mut self, notifier: N) -> Self {
+        self.head_subscription.notifier = Some(Arc::new(notifier));
+        self
+    }
+
+    /// Whether streams currently wait on a notifier rather than polling
+    pub fn head_subscribed(&self) -> bool {
+        self.head_subscription.notifier.is_some()
+            && self
+                .head_subscription
+                .failed_at
+                .lock()
+                .unwrap()
+                .is_none_or(|failed| failed.elapsed() >= SUBSCRIPTION_RETRY)
+    }
+
+    /// Wait for block `block_num`, returning the head block if it is known
+    ///
+    /// Uses the notifier while it works, and otherwise sleeps `poll_interval`.
+    pub(crate) fn wait_for_block(&self, block_num: u32, poll_interval: Duration) -> Option<u32> {
+        if let Some(notifier) = self.head_subscription.notifier.as_ref() {
+            if self.head_subscribed() {
+                match notifier.wait_for_block(self, block_num, SUBSCRIPTION_WAIT) {
+                    Ok(head) => {
+                        *self.head_subscription.failed_at.lock().unwrap() = None;
+                        return head;
+                    }
+                    Err(e) => {
+                        eprintln!("Warning: head block subscription failed, polling instead: {e}");
+                        *self.head_subscription.failed_at.lock().unwrap() = Some(Instant::now());
+                    }
+                }
+            }
+        }
+        thread::sleep(poll_interval);
+        None
+    }
+}
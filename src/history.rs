@@ -0,0 +1,474 @@
+//! Paginated iteration over an account's operation history
+
+use crate::cursor::{Cursor, HistoryCursor};
+use crate::op_filter::OpTypeSet;
+use crate::operation::Operation;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+
+/// Maximum number of entries `account_history_api.get_account_history` returns per call
+pub const ACCOUNT_HISTORY_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde(default)]
+    pub index: u64,
+    pub trx_id: String,
+    pub block: u32,
+    pub trx_in_block: u32,
+    pub op_in_trx: u32,
+    pub virtual_op: bool,
+    pub timestamp: String,
+    pub op: Operation,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountHistoryParams<'a> {
+    account: &'a str,
+    start: i64,
+    limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation_filter_low: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation_filter_high: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountHistoryResponse {
+    history: Vec<(u64, HistoryEntry)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpsInBlockResponse {
+    ops: Vec<HistoryEntry>,
+}
+
+/// Order in which an [`AccountHistory`] yields entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+impl Client {
+    /// Iterate over an account's history, newest entries first
+    pub fn account_history(&self, account: &str) -> AccountHistory<'_> {
+        AccountHistory {
+            client: self,
+            account: account.to_string(),
+            order: HistoryOrder::NewestFirst,
+            page_size: ACCOUNT_HISTORY_LIMIT,
+            filter: None,
+            last_index: None,
+            last_yielded: None,
+            next_start: None,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Fetch a single page of account history ending at index `start` (`-1` for the latest)
+    pub fn get_account_history(
+        &self,
+        account: &str,
+        start: i64,
+        limit: u32,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        self.get_account_history_filtered(account, start, limit, None)
+    }
+
+    /// Fetch a page of only the operation types in `filter`, filtered by the node
+    ///
+    /// `limit` counts matching entries; the node scans back from `start` until
+    /// it has found that many or reached the account's first operation. It is
+    /// lowered to the `start + 1` entries there are, as nodes reject a `limit`
+    /// above that.
+    pub fn get_account_history_filtered(
+        &self,
+        account: &str,
+        start: i64,
+        limit: u32,
+        filter: Option<&OpTypeSet>,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        let limit = match u32::try_from(start) {
+            Ok(start) => limit.min(start.saturating_add(1)),
+            Err(_) => limit,
+        };
+        let params = AccountHistoryParams {
+            account,
+            start,
+            limit,
+            operation_filter_low: filter.map(OpTypeSet::low),
+            operation_filter_high: filter.map(OpTypeSet::high),
+        };
+        let resp: AccountHistoryResponse = self
+            .call("account_history_api.get_account_history", params)
+            .map_err(|e| format!("Error fetching history for {account}: {e}"))?;
+        Ok(resp
+            .history
+            .into_iter()
+            .map(|(index, mut entry)| {
+                entry.index = index;
+                entry
+            })
+            .collect())
+    }
+}
+
+impl Client {
+    /// Fetch the operations of a block, or only its virtual operations
+    pub fn get_ops_in_block(
+        &self,
+        block: u32,
+        only_virtual: bool,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        let resp: OpsInBlockResponse = self
+            .call(
+                "account_history_api.get_ops_in_block",
+                serde_json::json!({ "block_num": block, "only_virtual": only_virtual }),
+            )
+            .map_err(|e| format!("Error fetching operations in block {block}: {e}"))?;
+        Ok(resp.ops)
+    }
+
+    /// Write an account's full history to `writer` as JSON lines, oldest first
+    ///
+    /// Only one page is held in memory at a time. Returns the number of entries written.
+    pub fn dump_account_history<W: Write>(
+        &self,
+        account: &str,
+        mut writer: W,
+    ) -> Result<u64, String> {
+        let mut written = 0;
+        for entry in self.account_history(account).oldest_first() {
+            let line = serde_json::to_string(&entry?)
+                .map_err(|e| format!("Error encoding history entry: {e}"))?;
+            writeln!(writer, "{line}").map_err(|e| format!("Error writing history: {e}"))?;
+            written += 1;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("Error writing history: {e}"))?;
+        Ok(written)
+    }
+}
+
+/// Blocking iterator over account history pages, created by [`Client::account_history`]
+#[derive(Debug)]
+pub struct AccountHistory<'a> {
+    client: &'a Client,
+    account: String,
+    order: HistoryOrder,
+    page_size: u32,
+    filter: Option<OpTypeSet>,
+    /// Newest index of the account, fetched when filtering oldest first
+    last_index: Option<u64>,
+    last_yielded: Option<u64>,
+    next_start: Option<i64>,
+    buffer: VecDeque<HistoryEntry>,
+    done: bool,
+}
+
+impl AccountHistory<'_> {
+    /// Yield entries from the account's first operation onwards
+    pub fn oldest_first(mut self) -> Self {
+        self.order = HistoryOrder::OldestFirst;
+        self
+    }
+
+    /// Number of entries fetched per request, capped at [`ACCOUNT_HISTORY_LIMIT`]
+    pub fn page_size(mut self, size: u32) -> Self {
+        self.page_size = size.clamp(1, ACCOUNT_HISTORY_LIMIT);
+        self
+    }
+
+    /// Only yield operations of the types in `types`, filtered by the node
+    pub fn filter(mut self, types: OpTypeSet) -> Self {
+        self.filter = Some(types);
+        self
+    }
+
+    /// Position after the entries yielded so far
+    pub fn cursor(&self) -> Cursor {
+        Cursor::AccountHistory(self.history_cursor())
+    }
+
+    fn history_cursor(&self) -> HistoryCursor {
+        HistoryCursor {
+            account: self.account.clone(),
+            order: self.order,
+            after: self.last_yielded,
+        }
+    }
+
+    /// Continue from a cursor of the same account's history, in the cursor's order
+    pub fn resume(self, cursor: &Cursor) -> Result<Self, String> {
+        match cursor {
+            Cursor::AccountHistory(cursor) => self.resume_history(cursor),
+            other => Err(other.mismatch("account history")),
+        }
+    }
+
+    fn resume_history(mut self, cursor: &HistoryCursor) -> Result<Self, String> {
+        if cursor.account != self.account {
+            return Err(format!(
+                "Cursor is for the history of {}, not {}",
+                cursor.account, self.account
+            ));
+        }
+        self.order = cursor.order;
+        self.last_yielded = cursor.after;
+        self.buffer.clear();
+        self.done = false;
+        self.next_start = match (cursor.order, cursor.after) {
+            (_, None) => None,
+            (HistoryOrder::NewestFirst, Some(0)) => {
+                self.done = true;
+                None
+            }
+            (HistoryOrder::NewestFirst, Some(after)) => Some(after as i64 - 1),
+            // Pages are trimmed to entries after `next_start - page_size`
+            (HistoryOrder::OldestFirst, Some(after)) => {
+                Some(after as i64 + i64::from(self.page_size))
+            }
+        };
+        Ok(self)
+    }
+
+    fn fetch_page(&mut self) -> Result<(), String> {
+        let limit = self.page_size;
+        let start = match (self.order, self.next_start) {
+            (_, Some(start)) => start,
+            (HistoryOrder::NewestFirst, None) => -1,
+            (HistoryOrder::OldestFirst, None) => i64::from(limit) - 1,
+        };
+        if self.order == HistoryOrder::OldestFirst
+            && self.filter.is_some()
+            && self.last_index.is_none()
+        {
+            // Filtered pages are sparse, so the end is found by index instead
+            let newest = self.client.get_account_history(&self.account, -1, 1)?;
+            self.last_index = Some(newest.first().map_or(0, |entry| entry.index));
+        }
+        let mut page = self.client.get_account_history_filtered(
+            &self.account,
+            start,
+            limit,
+            self.filter.as_ref(),
+        )?;
+        match self.order {
+            HistoryOrder::NewestFirst => {
+                page.sort_by_key(|entry| std::cmp::Reverse(entry.index));
+                match page.last() {
+                    Some(oldest) if oldest.index > 0 => {
+                        self.next_start = Some(oldest.index as i64 - 1);
+                    }
+                    _ => self.done = true,
+                }
+            }
+            HistoryOrder::OldestFirst => {
+                let yielded = self.next_start.map(|s| s - i64::from(limit));
+                page.retain(|entry| yielded.is_none_or(|y| entry.index as i64 > y));
+                page.sort_by_key(|entry| entry.index);
+                match (self.last_index, page.last()) {
+                    // Each page covers the `limit` indices ending at `start`
+                    (Some(last), _) if start < last as i64 => {
+                        self.next_start = Some(start + i64::from(limit));
+                    }
+                    (Some(_), _) => self.done = true,
+                    (None, Some(newest)) if page.len() as u32 >= limit => {
+                        self.next_start = Some(newest.index as i64 + i64::from(limit));
+                    }
+                    (None, _) => self.done = true,
+                }
+            }
+        }
+        self.buffer.extend(page);
+        Ok(())
+    }
+}
+
+impl Iterator for AccountHistory<'_> {
+    type Item = Result<HistoryEntry, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Filtered pages may come back empty before the end is reached
+        while self.buffer.is_empty() && !self.done {
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        let entry = self.buffer.pop_front()?;
+        self.last_yielded = Some(entry.index);
+        Some(Ok(entry))
+    }
+}
+
+/// An entry of a [`HistoryMerger`] with every merged account it appeared in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedEntry {
+    pub accounts: Vec<String>,
+    /// The entry as seen by the first of `accounts`; `index` is that account's
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+}
+
+/// Merges several accounts' histories into one chronological ledger
+///
+/// Entries are ordered by block, transaction, operation position and
+/// virtual-op flag. An operation in the history of several merged accounts,
+/// such as a transfer between two of them, is yielded once with all of them in
+/// [`MergedEntry::accounts`]. Every source must be in the merger's order.
+#[derive(Debug)]
+pub struct HistoryMerger<I> {
+    order: HistoryOrder,
+    sources: Vec<MergeSource<I>>,
+}
+
+#[derive(Debug)]
+struct MergeSource<I> {
+    account: String,
+    entries: I,
+    head: Option<HistoryEntry>,
+    done: bool,
+}
+
+impl<I: Iterator<Item = Result<HistoryEntry, String>>> HistoryMerger<I> {
+    pub fn new(order: HistoryOrder) -> Self {
+        Self {
+            order,
+            sources: Vec::new(),
+        }
+    }
+
+    /// Add the history of `account`
+    pub fn add(mut self, account: &str, entries: I) -> Self {
+        self.sources.push(MergeSource {
+            account: account.to_string(),
+            entries,
+            head: None,
+            done: false,
+        });
+        self
+    }
+
+    fn fill_heads(&mut self) -> Result<(), String> {
+        for source in self
+            .sources
+            .iter_mut()
+            .filter(|s| s.head.is_none() && !s.done)
+        {
+            match source.entries.next() {
+                Some(Ok(entry)) => source.head = Some(entry),
+                Some(Err(e)) => return Err(format!("{}: {e}", source.account)),
+                None => source.done = true,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Chronological position of an entry
+fn position(entry: &HistoryEntry) -> (u32, u32, u32, bool) {
+    (
+        entry.block,
+        entry.trx_in_block,
+        entry.op_in_trx,
+        entry.virtual_op,
+    )
+}
+
+/// Whether two entries are the same chain operation seen from different accounts
+fn same_operation(a: &HistoryEntry, b: &HistoryEntry) -> bool {
+    position(a) == position(b) && a.trx_id == b.trx_id && a.op == b.op
+}
+
+impl<I: Iterator<Item = Result<HistoryEntry, String>>> Iterator for HistoryMerger<I> {
+    type Item = Result<MergedEntry, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.fill_heads() {
+            return Some(Err(e));
+        }
+        let heads = self.sources.iter().filter_map(|s| s.head.as_ref());
+        let first = match self.order {
+            HistoryOrder::OldestFirst => heads.min_by_key(|e| position(e)),
+            HistoryOrder::NewestFirst => heads.max_by_key(|e| position(e)),
+        }?
+        .clone();
+        let mut accounts = Vec::new();
+        for source in &mut self.sources {
+            if source
+                .head
+                .as_ref()
+                .is_some_and(|e| same_operation(e, &first))
+            {
+                source.head = None;
+                accounts.push(source.account.clone());
+            }
+        }
+        Some(Ok(MergedEntry {
+            accounts,
+            entry: first,
+        }))
+    }
+}
+
+impl HistoryMerger<AccountHistory<'_>> {
+    /// Position after the entries yielded so far
+    pub fn cursor(&self) -> Cursor {
+        let accounts = self
+            .sources
+            .iter()
+            .map(|source| {
+                let mut cursor = source.entries.history_cursor();
+                // A peeked entry has not been yielded yet, so resume just before it
+                if let Some(head) = &source.head {
+                    cursor.after = match cursor.order {
+                        HistoryOrder::OldestFirst => head.index.checked_sub(1),
+                        HistoryOrder::NewestFirst => Some(head.index + 1),
+                    };
+                }
+                cursor
+            })
+            .collect();
+        Cursor::MergedHistory { accounts }
+    }
+
+    /// Continue from a cursor of the same accounts' merged history
+    pub fn resume(mut self, cursor: &Cursor) -> Result<Self, String> {
+        let Cursor::MergedHistory { accounts } = cursor else {
+            return Err(cursor.mismatch("merged history"));
+        };
+        let mut sources = Vec::with_capacity(self.sources.len());
+        for source in self.sources {
+            let position = accounts
+                .iter()
+                .find(|c| c.account == source.account)
+                .ok_or_else(|| format!("Cursor has no position for {}", source.account))?;
+            sources.push(MergeSource {
+                entries: source.entries.resume_history(position)?,
+                head: None,
+                done: false,
+                ..source
+            });
+        }
+        self.sources = sources;
+        Ok(self)
+    }
+}
+
+impl Client {
+    /// Merge the full histories of `accounts`, oldest first
+    pub fn merged_history(&self, accounts: &[&str]) -> HistoryMerger<AccountHistory<'_>> {
+        accounts.iter().fold(
+            HistoryMerger::new(HistoryOrder::OldestFirst),
+            |merger, account| merger.add(account, self.account_history(account).oldest_first()),
+        )
+    }
+}
@@ -0,0 +1,43 @@
+//! HTTP/2 with prior knowledge for nodes known to support it
+//!
+//! Over HTTP/1.1, concurrent calls to a node each need their own connection
+//! and handshake. Nodes listed with [`Client::http2_prior_knowledge`] are
+//! spoken to over HTTP/2 straight away, so [`Client::join`] and other
+//! concurrent callers multiplex over one long-lived connection per node.
+//! Other nodes keep negotiating as before.
+
+use crate::dns::HttpSettings;
+use crate::{Client, Url};
+
+/// The origin requests to `node` are grouped by, e.g. `https://api.hive.blog`
+fn origin(node: &str) -> Option<String> {
+    Url::parse(node)
+        .ok()
+        .map(|url| url.origin().ascii_serialization())
+}
+
+impl Client {
+    /// Use HTTP/2 without negotiation for `nodes`
+    ///
+    /// Only list nodes that serve HTTP/2; others will fail every call.
+    pub fn http2_prior_knowledge(mut self, nodes: &[&str]) -> Self {
+        for origin in nodes.iter().filter_map(|node| origin(node)) {
+            if !self.http.http2_origins.contains(&origin) {
+                self.http.http2_origins.push(origin);
+            }
+        }
+        self.rebuild_http();
+        self
+    }
+
+    /// Whether calls to `node` use HTTP/2 with prior knowledge
+    pub fn uses_http2(&self, node: &str) -> bool {
+        self.http.uses_http2(node)
+    }
+}
+
+impl HttpSettings {
+    pub(crate) fn uses_http2(&self, node: &str) -> bool {
+        origin(node).is_some_and(|origin| self.http2_origins.contains(&origin))
+    }
+}
@@ -0,0 +1,175 @@
+//! Localization hooks for user-facing text
+//!
+//! Operation summaries and error messages are built as [`Message`]s: a key
+//! such as `op.transfer` with named arguments. A [`Translator`] turns them
+//! into text; keys it does not know fall back to [`English`], so a frontend
+//! can translate as much or as little as it likes:
+//!
+//! ```
+//! use nectarflower_rs::i18n::Templates;
+//! use nectarflower_rs::Operation;
+//! use serde_json::json;
+//!
+//! let german = Templates::new()
+//!     .template("op.transfer", "{from} hat {amount} an {to} überwiesen")
+//!     .template("memo", " — Notiz: {memo}");
+//! let op = Operation::new(
+//!     "transfer_operation",
+//!     json!({"from": "alice", "to": "bob", "amount": "5.000 HIVE", "memo": "Miete"}),
+//! );
+//! assert_eq!(
+//!     op.summary_in(&german),
+//!     "@alice hat 5.000 HIVE an @bob überwiesen — Notiz: Miete"
+//! );
+//! ```
+
+use std::collections::HashMap;
+
+/// A user-facing text before translation: a key and named arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// The value of argument `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Render with `translator`, falling back to [`English`]
+    pub fn render(&self, translator: &dyn Translator) -> String {
+        translator
+            .translate(self)
+            .or_else(|| English.translate(self))
+            .unwrap_or_else(|| self.key.to_string())
+    }
+
+    /// Replace each `{name}` in `template` with the argument of that name
+    pub fn fill(&self, template: &str) -> String {
+        let mut text = template.to_string();
+        for (name, value) in &self.args {
+            text = text.replace(&format!("{{{name}}}"), value);
+        }
+        text
+    }
+}
+
+/// Renders [`Message`]s in a language
+pub trait Translator: Send + Sync {
+    /// The text of `message`, or `None` to fall back to English
+    fn translate(&self, message: &Message) -> Option<String>;
+}
+
+/// Templates keyed by message key, e.g. loaded from a frontend's catalog
+#[derive(Debug, Clone, Default)]
+pub struct Templates {
+    templates: HashMap<String, String>,
+}
+
+impl Templates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render messages with `key` from `template`, with `{name}` placeholders
+    pub fn template(mut self, key: &str, template: &str) -> Self {
+        self.templates.insert(key.to_string(), template.to_string());
+        self
+    }
+}
+
+impl Translator for Templates {
+    fn translate(&self, message: &Message) -> Option<String> {
+        self.templates
+            .get(message.key)
+            .map(|template| message.fill(template))
+    }
+}
+
+/// The built-in English texts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl English {
+    /// The English template of `key`, listing every key this crate emits
+    pub fn template(key: &str) -> Option<&'static str> {
+        Some(match key {
+            "op.transfer" => "{from} transferred {amount} to {to}",
+            "op.recurrent_transfer" => {
+                "{from} set up a recurring transfer of {amount} to {to} every {recurrence} hours, {executions} times"
+            }
+            "op.transfer_to_savings" => "{from} moved {amount} to the savings of {to}",
+            "op.transfer_from_savings" => "{from} started withdrawing {amount} from savings to {to}",
+            "op.power_up" => "{from} powered up {amount}",
+            "op.power_up_for" => "{from} powered up {amount} for {to}",
+            "op.withdraw_vesting" => "{account} started a power down of {amount}",
+            "op.delegate_vesting_shares" => "{delegator} delegated {amount} to {delegatee}",
+            "op.vote.remove" => "{voter} removed their vote on {post}",
+            "op.vote.down" => "{voter} downvoted {post} ({weight})",
+            "op.vote.up" => "{voter} upvoted {post} ({weight})",
+            "op.reply" => "{author} replied to {parent} with {post}",
+            "op.post" => "{author} posted {post}",
+            "op.post_titled" => "{author} posted {post}: \"{title}\"",
+            "op.delete_comment" => "{author} deleted {post}",
+            "op.custom_json" => "{signer} broadcast custom_json \"{id}\"",
+            "op.witness_vote" => "{account} voted for witness {witness}",
+            "op.witness_unvote" => "{account} unvoted witness {witness}",
+            "op.witness_proxy" => "{account} set {proxy} as witness proxy",
+            "op.witness_proxy_cleared" => "{account} cleared their witness proxy",
+            "op.claim_reward_balance" => "{account} claimed {hive}, {hbd} and {vests}",
+            "op.limit_order_create" => "{owner} offered {sell} for {receive}",
+            "op.limit_order_cancel" => "{owner} cancelled order {order}",
+            "op.convert" => "{owner} started converting {amount}",
+            "op.author_reward" => "{author} earned {hbd}, {hive} and {vests} for {post}",
+            "op.curation_reward" => "{curator} earned {reward} curating {post}",
+            "op.producer_reward" => "{producer} earned {reward} for producing a block",
+            "op.fill_vesting_withdraw" => "{from} powered down {withdrawn} into {deposited}",
+            "op.interest" => "{owner} received {interest} savings interest",
+            "op.other" => "{details}",
+            "memo" => " — memo: {memo}",
+            "memo.encrypted" => " — memo: (encrypted)",
+            "tx.header.one" => "Transaction with 1 operation, expiring {expiration}",
+            "tx.header" => "Transaction with {count} operations, expiring {expiration}",
+            "tx.item" => "  {number}. {summary}",
+            "error.timeout" => "Request timed out: {detail}",
+            "error.transport" => "Request error: {detail}",
+            "error.status" => "Unexpected status code: {status}",
+            "error.too_large" => "Response exceeds the limit of {limit} bytes",
+            "error.too_large_of" => "Response of {length} bytes exceeds the limit of {limit} bytes",
+            "error.rpc" => "RPC error: {message} (code: {code})",
+            "error.decode" => "Decode error: {detail}",
+            "error.requires_write_client" => "{method} broadcasts and requires a WriteClient",
+            "error.no_nodes" => "No nodes available for {method}",
+            "error.node_failed" => "{method} failed on {node}",
+            "error.all_nodes_failed" => "{method} failed on all {count} nodes",
+            "error.empty_node_list" => {
+                "No usable nodes: {invalid} invalid and {failing} failing of {offered} offered"
+            }
+            "error.account_not_found" => "Account '{account}' not found",
+            _ => return None,
+        })
+    }
+}
+
+impl Translator for English {
+    fn translate(&self, message: &Message) -> Option<String> {
+        English::template(message.key).map(|template| message.fill(template))
+    }
+}
@@ -0,0 +1,69 @@
+//! Reads that must not reflect reversible state
+//!
+//! A node answers from its head state, which may still be undone by a fork.
+//! [`Client::call_at_irreversible`] only returns a result once the same
+//! result has been seen on both sides of the last irreversible block passing
+//! the head it was first read at.
+
+use crate::block::BLOCK_INTERVAL;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a result to become irreversible
+pub const IRREVERSIBLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+impl Client {
+    /// Make a read-only call, returning its result once it is irreversible
+    ///
+    /// The call is made, then repeated after the last irreversible block
+    /// reaches the head block seen at the first call. If both results match
+    /// they are returned; otherwise the newer result is confirmed the same
+    /// way, until [`IRREVERSIBLE_TIMEOUT`] runs out.
+    pub fn call_at_irreversible<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, String> {
+        let deadline = Instant::now() + IRREVERSIBLE_TIMEOUT;
+        let mut seen: Value = self.call(method, &params)?;
+        let mut seen_at = self.get_dynamic_global_properties()?.head_block_number;
+        loop {
+            self.wait_for_irreversible(seen_at, deadline)?;
+            let again: Value = self.call(method, &params)?;
+            if again == seen {
+                return serde_json::from_value(again)
+                    .map_err(|e| format!("Result decode error: {e}"));
+            }
+            seen = again;
+            seen_at = self.get_dynamic_global_properties()?.head_block_number;
+        }
+    }
+
+    /// Whether `block_num` is at or below the last irreversible block
+    pub fn is_irreversible(&self, block_num: u32) -> Result<bool, String> {
+        Ok(self
+            .get_dynamic_global_properties()?
+            .last_irreversible_block_num
+            >= block_num)
+    }
+
+    fn wait_for_irreversible(&self, block_num: u32, deadline: Instant) -> Result<(), String> {
+        loop {
+            let lib = self
+                .get_dynamic_global_properties()?
+                .last_irreversible_block_num;
+            if lib >= block_num {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Block {block_num} did not become irreversible in time (last irreversible: {lib})"
+                ));
+            }
+            thread::sleep(BLOCK_INTERVAL);
+        }
+    }
+}
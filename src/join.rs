@@ -0,0 +1,92 @@
+//! Running several calls at once and collecting their results as a tuple
+//!
+//! Snapshot-style workflows need a handful of unrelated reads. With
+//! [`Client::join`] they run on scoped threads in parallel, so the whole
+//! snapshot takes about as long as its slowest call:
+//!
+//! ```no_run
+//! # use nectarflower_rs::Client;
+//! let client = Client::new();
+//! let (accounts, props) = client.join((
+//!     |c: &Client| c.find_accounts(&["alice"]),
+//!     |c: &Client| c.get_dynamic_global_properties(),
+//! ));
+//! ```
+//!
+//! Each call fails over between nodes as usual, and each result is
+//! returned as is, so one failing call does not discard the others.
+
+use crate::Client;
+use std::thread;
+
+/// A tuple of calls that [`Client::join`] runs concurrently
+pub trait JoinCalls {
+    type Output;
+
+    fn run(self, client: &Client) -> Self::Output;
+}
+
+macro_rules! join_tuple {
+    ($(($f:ident, $r:ident, $h:ident)),+) => {
+        impl<$($f, $r),+> JoinCalls for ($($f,)+)
+        where
+            $($f: FnOnce(&Client) -> $r + Send, $r: Send,)+
+        {
+            type Output = ($($r,)+);
+
+            #[allow(non_snake_case)]
+            fn run(self, client: &Client) -> Self::Output {
+                let ($($f,)+) = self;
+                thread::scope(|scope| {
+                    $(let $h = scope.spawn(move || $f(client));)+
+                    ($(
+                        $h.join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+                    )+)
+                })
+            }
+        }
+    };
+}
+
+join_tuple!((A, RA, a), (B, RB, b));
+join_tuple!((A, RA, a), (B, RB, b), (C, RC, c));
+join_tuple!((A, RA, a), (B, RB, b), (C, RC, c), (D, RD, d));
+join_tuple!((A, RA, a), (B, RB, b), (C, RC, c), (D, RD, d), (E, RE, e));
+join_tuple!(
+    (A, RA, a),
+    (B, RB, b),
+    (C, RC, c),
+    (D, RD, d),
+    (E, RE, e),
+    (F, RF, f)
+);
+
+impl Client {
+    /// Run a tuple of two to six calls concurrently, returning their results in order
+    pub fn join<J: JoinCalls>(&self, calls: J) -> J::Output {
+        calls.run(self)
+    }
+
+    /// Run calls of the same type concurrently, returning their results in order
+    pub fn join_all<T, F>(&self, calls: Vec<F>) -> Vec<T>
+    where
+        F: FnOnce(&Client) -> T + Send,
+        T: Send,
+    {
+        thread::scope(|scope| {
+            let handles: Vec<_> = calls
+                .into_iter()
+                .map(|call| scope.spawn(move || call(self)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    }
+}
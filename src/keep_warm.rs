@@ -0,0 +1,133 @@
+//! Keep-warm pinging of the preferred node
+//!
+//! [`Client::keep_warm`] pings the first node with a cheap
+//! `get_dynamic_global_properties` call at an interval, keeping its
+//! connection open and noticing failures before a user-facing call does.
+//! While the preferred node fails its pings, calls try the other nodes first.
+
+use crate::Client;
+use serde_json::Value;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Method used to ping, answered from memory by every node
+const PING_METHOD: &str = "database_api.get_dynamic_global_properties";
+
+/// Outcome of one ping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ping {
+    pub node: String,
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+impl Ping {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Handle of a keep-warm thread, which stops when the handle is dropped
+pub struct KeepWarm {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    last: Arc<Mutex<Option<Ping>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KeepWarm {
+    /// The most recent ping, if any has completed
+    pub fn last_ping(&self) -> Option<Ping> {
+        self.last.lock().unwrap().clone()
+    }
+}
+
+impl fmt::Debug for KeepWarm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeepWarm")
+            .field("last", &self.last_ping())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for KeepWarm {
+    fn drop(&mut self) {
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap() = true;
+        wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Client {
+    /// Ping the preferred node every `interval` on a background thread
+    ///
+    /// A node failing its ping is tried last by calls until it answers a
+    /// ping again. Pinging stops when the returned handle is dropped.
+    pub fn keep_warm(self: &Arc<Self>, interval: Duration) -> KeepWarm {
+        let client = Arc::clone(self);
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let last = Arc::new(Mutex::new(None));
+        let (thread_stop, thread_last) = (Arc::clone(&stop), Arc::clone(&last));
+        let thread = thread::spawn(move || loop {
+            if let Some(ping) = client.ping_preferred() {
+                *thread_last.lock().unwrap() = Some(ping);
+            }
+            let (stopped, wake) = &*thread_stop;
+            let stopped = wake
+                .wait_timeout_while(stopped.lock().unwrap(), interval, |stopped| !*stopped)
+                .unwrap()
+                .0;
+            if *stopped {
+                return;
+            }
+        });
+        KeepWarm {
+            stop,
+            last,
+            thread: Some(thread),
+        }
+    }
+
+    /// Ping the first node, recording whether it should be tried last
+    pub(crate) fn ping_preferred(&self) -> Option<Ping> {
+        let node = self.nodes.first()?;
+        let started = Instant::now();
+        let result = self.call_node::<_, Value>(node, PING_METHOD, &());
+        let mut cold = self.cold_nodes.lock().unwrap();
+        if result.is_ok() {
+            cold.remove(node);
+        } else {
+            cold.insert(node.clone());
+        }
+        Some(Ping {
+            node: node.clone(),
+            latency: started.elapsed(),
+            error: result.err().map(String::from),
+        })
+    }
+
+    /// Nodes that failed their last keep-warm ping
+    pub fn cold_nodes(&self) -> Vec<String> {
+        let cold = self.cold_nodes.lock().unwrap();
+        self.nodes
+            .iter()
+            .filter(|node| cold.contains(*node))
+            .cloned()
+            .collect()
+    }
+
+    /// The nodes in the order calls should try them: warm nodes first
+    pub(crate) fn nodes_by_warmth(&self) -> Vec<&str> {
+        let cold = self.cold_nodes.lock().unwrap();
+        let (warm, cold): (Vec<&str>, Vec<&str>) = self
+            .nodes
+            .iter()
+            .map(String::as_str)
+            .partition(|node| !cold.contains(*node));
+        warm.into_iter().chain(cold).collect()
+    }
+}
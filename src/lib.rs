@@ -1,9 +1,202 @@
 //! nectarflower-rs: A Rust client for Hive JSON-RPC
 
-use reqwest::blocking::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+pub use url::Url;
+
+pub mod abuse;
+pub mod account_diff;
+pub mod account_scan;
+pub mod annotations;
+pub mod asset;
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod attempts;
+pub mod audit;
+pub mod balance_history;
+pub mod batch;
+pub mod block;
+pub mod block_source;
+pub mod builder;
+pub mod canonical;
+pub mod capabilities;
+pub mod chain;
+pub mod chain_time;
+pub mod channel;
+pub mod comment;
+pub mod comments;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub mod crypto;
+pub mod cursor;
+pub mod custom_json;
+pub mod delegations;
+pub mod deposits;
+pub mod deserializer;
+pub mod dhf;
+pub mod dns;
+pub mod error;
+pub mod explorer;
+pub mod extended_account;
+pub mod fail_reason;
+pub mod feeds;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod governance;
+#[cfg(feature = "haf")]
+pub mod haf;
+pub mod head_notify;
+pub mod history;
+pub mod http2;
+pub mod i18n;
+pub mod irreversible;
+pub mod join;
+pub mod keep_warm;
+pub mod limits;
+pub mod links;
+pub mod market;
+pub mod metadata;
+pub mod methods;
+pub mod msgpack;
+pub mod node_changes;
+pub mod node_policy;
+pub mod node_scan;
+pub mod node_watch;
+pub mod numbers;
+pub mod op_filter;
+pub mod operation;
+pub mod params;
+pub mod patch;
+pub mod payout_window;
+pub mod payouts;
+pub mod permlink;
+pub mod post_rewards;
+pub mod posts;
+pub mod price;
+pub mod rc;
+pub mod recurring;
+pub mod relations;
+pub mod rest;
+pub mod retry;
+pub mod rich_list;
+pub mod sanitize;
+pub mod secret;
+pub mod security;
+pub mod seeds;
+pub mod serializer;
+pub mod signatures;
+pub mod social;
+pub mod summary;
+pub mod tag_stats;
+pub mod tags;
+pub mod template;
+#[cfg(feature = "testnet-harness")]
+pub mod testnet;
+pub mod timestamp;
+pub mod transaction;
+pub mod transport;
+pub mod treasury;
+pub mod version;
+pub mod votes;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod withdrawals;
+pub mod witness;
+pub mod witness_guard;
+pub mod witness_props;
+pub mod write;
+
+pub use abuse::{AbuseAnalyzer, AbuseSignals, RecurringBeneficiary};
+pub use account_diff::{AccountDiff, AuthorityChange, DelegationChange};
+pub use account_scan::AccountScan;
+pub use annotations::{AnnotatedEntry, Annotation, AnnotationStore};
+pub use asset::{Asset, AssetSymbol};
+#[cfg(feature = "async")]
+pub use async_client::AsyncClient;
+pub use attempts::{Attempt, CallError};
+pub use audit::{AuditLog, AuditRecord};
+pub use balance_history::{BalanceHistory, BalanceSnapshot, Balances};
+pub use block::{
+    Block, BlockHeader, BlockStream, DynamicGlobalProperties, StreamLag, StreamMode, StreamedBlock,
+};
+pub use block_source::{BlockFileFormat, BlockFileWriter, BlockSource, FileBlockSource};
+pub use builder::ClientBuilder;
+pub use capabilities::{Capabilities, Capability};
+pub use chain::{ChainAsset, ChainConfig, ChainRegistry};
+pub use chain_time::ChainClock;
+pub use channel::{BlockReceiver, OverflowPolicy, StreamReceiver, StreamSender};
+pub use comment::{Content, PostDraft};
+pub use comments::{ChainComment, CommentOrder, CommentStart};
+pub use cursor::{Cursor, HistoryCursor};
+pub use custom_json::{
+    CustomJson, CustomJsonBuilder, CustomOp, CustomOpRegistry, IdempotencyFilter,
+};
+pub use delegations::{ReturningHp, VestingDelegation, VestingDelegationExpiration};
+pub use deposits::{Deposit, DepositEvent, DepositWatcher};
+pub use dhf::{DhfStatus, FundedProposal, Proposal};
+pub use dns::{DnsResolver, IpPreference, SystemResolver};
+pub use error::NectarError;
+pub use explorer::{Entity, Query};
+pub use fail_reason::{FailReason, NodeFailure};
+pub use feeds::{FeedEvent, FeedHealth, FeedMonitor, FeedStatus};
+pub use governance::{GovernanceExpiry, GovernanceReport};
+pub use history::{AccountHistory, HistoryEntry, HistoryMerger, HistoryOrder, MergedEntry};
+pub use keep_warm::{KeepWarm, Ping};
+pub use limits::{LimitError, TransferLimits};
+pub use links::{parse_account_url, parse_post_url, post_url, Frontend};
+pub use market::{MarketSimulation, OrderBook, OrderSide};
+pub use metadata::MetadataField;
+pub use methods::Method;
+pub use msgpack::{from_msgpack, to_msgpack, MsgpackReader, MsgpackWriter};
+pub use node_changes::NodeListChange;
+pub use node_policy::{EmptyNodeList, EmptyNodePolicy};
+pub use node_scan::{
+    AutoBenchmark, BenchmarkResult, NodeMetadata, NodeReport, NodeScanner, ScanParameters,
+};
+pub use node_watch::{NodeListWatcher, NodeWatchEvent};
+pub use numbers::ExactNumber;
+pub use op_filter::OpTypeSet;
+pub use operation::Operation;
+pub use payout_window::PayoutWindowScanner;
+pub use payouts::{PendingPayout, PendingPayouts};
+pub use permlink::{generate_permlink, generate_reply_permlink, validate_permlink};
+pub use post_rewards::{PostRewards, RewardSplit};
+pub use posts::{AccountPostSort, Post, PostQuery, PostSort};
+pub use price::{Aggregation, PriceOracle, PriceReport, PriceSource};
+pub use rc::{RcAccount, ResourceParams, ResourceUsage, TransactionBudget};
+pub use recurring::{
+    OrderEvent, OrderStatus, OrderTracker, Recurrence, RecurringAction, RecurringTask, TrackedOrder,
+};
+pub use relations::{Relation, RelationGraph, RelationKind};
+pub use rest::{EcencyPoints, RestApi};
+pub use rich_list::{Holding, RankBy, RichList, RichListReport};
+pub use sanitize::{sanitize_body, SanitizeReport};
+pub use secret::{SecretWif, WifSigner};
+pub use security::{FindingKind, SecurityAudit, SecurityFinding, Severity};
+pub use signatures::SignerSet;
+pub use social::{Profile, SocialCache};
+pub use tag_stats::{TagOrder, TagStats, TagStatsCollector, TrendingTag};
+pub use tags::{normalize_tag, validate_tag, validate_tags};
+pub use template::PostTemplate;
+pub use transaction::{
+    IncludedTransaction, SignedTransaction, Transaction, TransactionBuilder, TransactionError,
+    TransactionLocation, TransactionSigner, TransactionStatus, HIVE_CHAIN_ID,
+};
+pub use treasury::{ProposalPaid, ProposalPayments, TreasuryOp};
+pub use version::Version;
+pub use votes::{AnnotatedVote, Vote};
+pub use withdrawals::{Withdrawal, WithdrawalLimits, WithdrawalQueue, WithdrawalStatus};
+pub use witness::{
+    ChainProperties, RankedWitness, Round, ScheduledSlot, Witness, WitnessChange, WitnessRanking,
+    WitnessSchedule,
+};
+pub use witness_guard::{GuardEvent, WitnessGuard};
+pub use witness_props::WitnessProperties;
+pub use write::{WriteClient, WriteError};
 
 // --- Account/Node types for metadata extraction ---
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,10 +204,82 @@ pub struct AccountParams {
     pub accounts: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Authority {
+    #[serde(default, deserialize_with = "numbers::de_int")]
+    pub weight_threshold: u32,
+    #[serde(default)]
+    pub account_auths: Vec<(String, u16)>,
+    #[serde(default)]
+    pub key_auths: Vec<(String, u16)>,
+}
+
+impl Authority {
+    /// Public keys in this authority
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.key_auths.iter().map(|(key, _)| key.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     pub name: String,
     pub json_metadata: String,
+    #[serde(default)]
+    pub posting_json_metadata: String,
+    #[serde(default)]
+    pub owner: Authority,
+    #[serde(default)]
+    pub active: Authority,
+    #[serde(default)]
+    pub posting: Authority,
+    #[serde(default)]
+    pub memo_key: String,
+    #[serde(default)]
+    pub recovery_account: String,
+    /// Account voting for witnesses on this account's behalf, empty if none
+    #[serde(default)]
+    pub proxy: String,
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub last_owner_update: String,
+    #[serde(default)]
+    pub last_account_update: String,
+    #[serde(default)]
+    pub last_post: String,
+    #[serde(default)]
+    pub last_vote_time: String,
+    /// When the account's witness and proposal votes expire unless renewed
+    #[serde(default)]
+    pub governance_vote_expiration_ts: String,
+    #[serde(default = "Asset::zero_hive")]
+    pub balance: Asset,
+    #[serde(default = "Asset::zero_hbd")]
+    pub hbd_balance: Asset,
+    #[serde(default = "Asset::zero_hive")]
+    pub savings_balance: Asset,
+    #[serde(default = "Asset::zero_hbd")]
+    pub savings_hbd_balance: Asset,
+    #[serde(default = "Asset::zero_vests")]
+    pub vesting_shares: Asset,
+    #[serde(default = "Asset::zero_vests")]
+    pub delegated_vesting_shares: Asset,
+    #[serde(default = "Asset::zero_vests")]
+    pub received_vesting_shares: Asset,
+    #[serde(default = "Asset::zero_vests")]
+    pub vesting_withdraw_rate: Asset,
+}
+
+impl Account {
+    /// Own VESTS minus outgoing plus incoming delegations
+    pub fn effective_vests(&self) -> Asset {
+        Asset::new(
+            self.vesting_shares.amount - self.delegated_vesting_shares.amount
+                + self.received_vesting_shares.amount,
+            AssetSymbol::Vests,
+        )
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,129 +287,254 @@ pub struct AccountsResponse {
     pub accounts: Vec<Account>,
 }
 
+/// Maximum number of accounts `database_api.find_accounts` accepts per call
+pub const FIND_ACCOUNTS_LIMIT: usize = 1000;
+
 #[derive(Debug, Default, Clone)]
 pub struct NodeData {
     pub nodes: Vec<String>,
-    pub failing_nodes: HashMap<String, String>,
+    pub failing_nodes: HashMap<Url, NodeFailure>,
+}
+
+impl NodeData {
+    /// Key published failing nodes by URL, dropping entries that are not valid URLs
+    pub fn failing_from_published<I>(failing_nodes: I) -> HashMap<Url, NodeFailure>
+    where
+        I: IntoIterator<Item = (String, NodeFailure)>,
+    {
+        failing_nodes
+            .into_iter()
+            .filter_map(|(node, failure)| Some((Url::parse(&node).ok()?, failure)))
+            .collect()
+    }
+
+    /// Extract the published node list from an account's `json_metadata`
+    pub fn from_json_metadata(json_metadata: &str) -> Result<Self, NectarError> {
+        let metadata_obj: Value = serde_json::from_str(json_metadata)
+            .map_err(|e| NectarError::Decode(format!("JSON metadata: {e}")))?;
+        let mut node_data = NodeData::default();
+        if let Some(nodes) = metadata_obj.get("nodes") {
+            node_data.nodes = serde_json::from_value(nodes.clone())
+                .map_err(|e| NectarError::Decode(format!("nodes: {e}")))?;
+        } else {
+            return Err(NectarError::Decode(
+                "no nodes found in account metadata".to_string(),
+            ));
+        }
+        if let Some(failing_nodes) = metadata_obj.get("failing_nodes") {
+            let published: HashMap<String, NodeFailure> =
+                serde_json::from_value(failing_nodes.clone()).unwrap_or_else(|e| {
+                    eprintln!("Warning: error parsing failing_nodes: {e}");
+                    HashMap::new()
+                });
+            node_data.failing_nodes = NodeData::failing_from_published(published);
+        }
+        Ok(node_data)
+    }
 }
 
+/// A read-only Hive client; wrap it in a [`WriteClient`] to broadcast
 #[derive(Debug)]
 pub struct Client {
     pub nodes: Vec<String>,
-    pub failing_nodes: HashMap<String, String>,
-    http_client: HttpClient,
+    pub failing_nodes: HashMap<Url, NodeFailure>,
+    /// Built from `http` on first use, unless a custom transport is set
+    pub(crate) http_transport: OnceLock<transport::HttpTransport>,
+    pub(crate) http: dns::HttpSettings,
+    pub(crate) max_retries: Option<usize>,
+    pub(crate) retry: retry::RetryPolicy,
+    pub(crate) verify_chain: bool,
+    pub(crate) social: social::SocialStore,
+    pub(crate) max_response_bytes: Option<u64>,
+    pub(crate) chain_clock: std::sync::Mutex<Option<chain_time::ChainClock>>,
+    pub(crate) node_listeners: node_changes::NodeListeners,
+    pub(crate) cold_nodes: std::sync::Mutex<std::collections::HashSet<String>>,
+    pub(crate) chain: chain::ChainConfig,
+    pub(crate) empty_node_policy: EmptyNodePolicy,
+    pub(crate) head_subscription: head_notify::HeadSubscription,
+    pub(crate) transport: Option<Arc<dyn transport::Transport>>,
 }
 
 impl Client {
     /// Create a new Hive client with a default node
     pub fn new() -> Self {
-        Self {
-            nodes: vec!["https://api.hive.blog".to_string()],
-            failing_nodes: HashMap::new(),
-            http_client: HttpClient::builder()
-                .timeout(std::time::Duration::from_secs(10))
-                .build()
-                .unwrap(),
-        }
+        ClientBuilder::new().build()
+    }
+
+    /// Reject responses larger than `bytes` instead of buffering them
+    pub fn max_response_size(mut self, bytes: u64) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self.rebuild_http();
+        self
     }
 
     /// Set the list of nodes, filtering out invalid or failing nodes
-    pub fn set_nodes(&mut self, nodes: Vec<String>, failing_nodes: HashMap<String, String>) {
-        let valid_nodes = nodes
+    ///
+    /// Returns what changed; non-empty changes are also passed to the
+    /// listeners registered with [`Client::on_node_list_change`]. When no
+    /// usable node remains, the [`EmptyNodePolicy`] set with
+    /// [`Client::on_empty_node_list`] applies. With
+    /// [`Client::verify_chain_on_add`], new nodes of another chain are dropped.
+    pub fn set_nodes(
+        &mut self,
+        nodes: Vec<String>,
+        mut failing_nodes: HashMap<Url, NodeFailure>,
+    ) -> Result<NodeListChange, EmptyNodeList> {
+        let offered = nodes.len();
+        let mut invalid = 0;
+        let mut valid_nodes: Vec<String> = nodes
             .into_iter()
-            .filter(|node| !failing_nodes.contains_key(node) && url::Url::parse(node).is_ok())
+            .filter(|node| match Url::parse(node) {
+                Ok(url) => !failing_nodes.contains_key(&url),
+                Err(_) => {
+                    invalid += 1;
+                    false
+                }
+            })
             .collect();
-        self.nodes = valid_nodes;
-        self.failing_nodes = failing_nodes;
+        if self.verify_chain {
+            self.drop_wrong_chain(&mut valid_nodes, &mut failing_nodes);
+        }
+        if !valid_nodes.is_empty() {
+            return Ok(self.replace_nodes(valid_nodes, failing_nodes));
+        }
+        let empty = EmptyNodeList {
+            offered,
+            invalid,
+            failing: offered - invalid,
+        };
+        match self.empty_node_policy.clone() {
+            EmptyNodePolicy::KeepPrevious => {
+                eprintln!("Warning: {empty}; keeping the previous nodes");
+                let previous = self.nodes.clone();
+                Ok(self.replace_nodes(previous, failing_nodes))
+            }
+            EmptyNodePolicy::Fallback(fallback) => {
+                eprintln!("Warning: {empty}; using the fallback nodes");
+                Ok(self.replace_nodes(fallback, failing_nodes))
+            }
+            EmptyNodePolicy::Error => Err(empty),
+        }
     }
 
     /// Make a JSON-RPC call to the Hive API
+    ///
+    /// Broadcast APIs are refused; use a [`WriteClient`] to change chain state.
     pub fn call<P: Serialize, R: for<'de> Deserialize<'de>>(
         &self,
         method: &str,
         params: P,
-    ) -> Result<R, String> {
-        let mut last_err = None;
-        for node in &self.nodes {
-            match self.call_node::<P, R>(node, method, &params) {
-                Ok(res) => return Ok(res),
-                Err(e) => last_err = Some(e),
-            }
-        }
-        Err(last_err.unwrap_or_else(|| "No nodes available".to_string()))
+    ) -> Result<R, NectarError> {
+        Ok(self.call_traced(method, params)?)
     }
 
-    fn call_node<P: Serialize, R: for<'de> Deserialize<'de>>(
+    /// Like [`Client::call`], without refusing broadcast APIs
+    pub(crate) fn call_any<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, CallError> {
+        self.call_attempts(method, params).0
+    }
+
+    pub(crate) fn call_node<P: Serialize, R: for<'de> Deserialize<'de>>(
         &self,
         node: &str,
         method: &str,
         params: &P,
-    ) -> Result<R, String> {
+    ) -> Result<R, NectarError> {
         let req = RpcRequest {
             jsonrpc: "2.0".to_string(),
             method: method.to_string(),
             params,
             id: 1,
         };
-        let resp = self
-            .http_client
-            .post(node)
-            .header("Content-Type", "application/json")
-            .json(&req)
-            .send()
-            .map_err(|e| format!("Request error: {e}"))?;
-        if !resp.status().is_success() {
-            return Err(format!("Unexpected status code: {}", resp.status()));
-        }
-        let rpc: RpcResponse<Value> = resp.json().map_err(|e| format!("Decode error: {e}"))?;
-        if let Some(err) = rpc.error {
-            return Err(format!("RPC error: {} (code: {})", err.message, err.code));
-        }
-        match rpc.result {
-            Some(val) => {
-                serde_json::from_value(val).map_err(|e| format!("Result decode error: {e}"))
-            }
-            None => Err("No result in RPC response".to_string()),
+        let body = self.post_json(node, &req)?;
+        decode_rpc_body(&body)
+    }
+
+    /// Send a JSON-RPC request or batch to `node` and return the response body
+    pub(crate) fn post_json<B: Serialize>(
+        &self,
+        node: &str,
+        request: &B,
+    ) -> Result<Vec<u8>, NectarError> {
+        let payload = serde_json::to_vec(request)
+            .map_err(|e| NectarError::Decode(format!("request: {e}")))?;
+        self.transport_ref().send(node, &payload)
+    }
+
+    /// Fetch accounts by name, batching large lists into multiple requests
+    pub fn find_accounts(&self, names: &[&str]) -> Result<Vec<Account>, NectarError> {
+        let mut accounts = Vec::with_capacity(names.len());
+        for chunk in names.chunks(FIND_ACCOUNTS_LIMIT) {
+            let params = AccountParams {
+                accounts: chunk.iter().map(|name| name.to_string()).collect(),
+            };
+            let resp: AccountsResponse = self.call("database_api.find_accounts", params)?;
+            accounts.extend(resp.accounts);
         }
+        Ok(accounts)
     }
 
     /// Fetch account JSON metadata and extract node information
-    pub fn get_nodes_from_account(&self, account_name: &str) -> Result<NodeData, String> {
+    pub fn get_nodes_from_account(&self, account_name: &str) -> Result<NodeData, NectarError> {
         let params = AccountParams {
             accounts: vec![account_name.to_string()],
         };
-        let resp: AccountsResponse = self
-            .call("database_api.find_accounts", params)
-            .map_err(|e| format!("Error fetching account: {e}"))?;
+        let resp: AccountsResponse = self.call("database_api.find_accounts", params)?;
         let account = resp
             .accounts
-            .get(0)
-            .ok_or_else(|| format!("Account '{account_name}' not found"))?;
-        let json_metadata = &account.json_metadata;
-        let metadata_obj: Value = serde_json::from_str(json_metadata)
-            .map_err(|e| format!("Error parsing JSON metadata: {e}"))?;
-        let mut node_data = NodeData::default();
-        if let Some(nodes) = metadata_obj.get("nodes") {
-            node_data.nodes = serde_json::from_value(nodes.clone())
-                .map_err(|e| format!("Error parsing nodes: {e}"))?;
-        } else {
-            return Err("No nodes found in account metadata".to_string());
+            .first()
+            .ok_or_else(|| NectarError::AccountNotFound(account_name.to_string()))?;
+        NodeData::from_json_metadata(&account.json_metadata)
+    }
+
+    /// Fetch nodes from an account and update the client, returning what changed
+    pub fn update_nodes_from_account(
+        &mut self,
+        account_name: &str,
+    ) -> Result<NodeListChange, NectarError> {
+        let node_data = self.get_nodes_from_account(account_name)?;
+        Ok(self.set_nodes(node_data.nodes, node_data.failing_nodes)?)
+    }
+}
+
+/// Decode a JSON-RPC response body into its result
+pub(crate) fn decode_rpc_body<R: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<R, NectarError> {
+    let rpc: RpcResponse<Value> =
+        serde_json::from_slice(body).map_err(|e| NectarError::Decode(e.to_string()))?;
+    if let Some(err) = rpc.error {
+        return Err(err.into());
+    }
+    match rpc.result {
+        Some(val) => {
+            serde_json::from_value(val).map_err(|e| NectarError::Decode(format!("result: {e}")))
         }
-        if let Some(failing_nodes) = metadata_obj.get("failing_nodes") {
-            node_data.failing_nodes =
-                serde_json::from_value(failing_nodes.clone()).unwrap_or_else(|e| {
-                    eprintln!("Warning: error parsing failing_nodes: {e}");
-                    HashMap::new()
-                });
+        None => Err(NectarError::Decode("no result in RPC response".to_string())),
+    }
+}
+
+/// Describe a failed request, including the underlying cause (e.g. a TLS error)
+pub(crate) fn request_error(e: reqwest::Error) -> NectarError {
+    if e.is_timeout() {
+        return NectarError::Timeout(e.to_string());
+    }
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(&format!(": {cause_message}"));
         }
-        Ok(node_data)
+        source = cause.source();
     }
+    NectarError::Transport(message)
+}
 
-    /// Fetch nodes from an account and update the client
-    pub fn update_nodes_from_account(&mut self, account_name: &str) -> Result<(), String> {
-        let node_data = self.get_nodes_from_account(account_name)?;
-        self.set_nodes(node_data.nodes, node_data.failing_nodes);
-        Ok(())
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -165,8 +555,16 @@ pub struct RpcResponse<R> {
     pub id: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RpcError {
     pub code: i32,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RPC error: {} (code: {})", self.message, self.code)
+    }
 }
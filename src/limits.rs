@@ -0,0 +1,299 @@
+//! Guardrails on outgoing value transfers made through a [`WriteClient`]
+//!
+//! Limits are checked before anything is signed and again when a transaction
+//! is broadcast, so a bot whose logic goes wrong fails with a [`LimitError`]
+//! instead of draining its wallet, however the transaction was signed.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::chain::ChainConfig;
+use crate::operation::Operation;
+use crate::WriteClient;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window of the hourly limits
+const HOUR: Duration = Duration::from_secs(3_600);
+
+/// Operation fields carrying value moved to the `to` account
+const VALUE_FIELDS: &[&str] = &["amount", "hive_amount", "hbd_amount"];
+
+/// Operations whose destinations [`transfers`] reads
+const TRANSFER_OPERATIONS: &[&str] = &[
+    "transfer",
+    "transfer_to_vesting",
+    "transfer_to_savings",
+    "transfer_from_savings",
+    "recurrent_transfer",
+    "escrow_transfer",
+    "escrow_release",
+    "delegate_vesting_shares",
+    "set_withdraw_vesting_route",
+];
+
+/// Operations that never move value to another account
+const NON_TRANSFER_OPERATIONS: &[&str] = &[
+    "vote",
+    "comment",
+    "comment_options",
+    "delete_comment",
+    "custom_json",
+    "withdraw_vesting",
+    "limit_order_create",
+    "limit_order_create2",
+    "limit_order_cancel",
+    "feed_publish",
+    "convert",
+    "collateralized_convert",
+    "account_update",
+    "account_update2",
+    "witness_update",
+    "witness_set_properties",
+    "account_witness_vote",
+    "account_witness_proxy",
+    "claim_account",
+    "claim_reward_balance",
+    "change_recovery_account",
+    "cancel_transfer_from_savings",
+    "escrow_approve",
+    "escrow_dispute",
+    "update_proposal_votes",
+    "remove_proposal",
+];
+
+/// Why a transaction was refused by [`TransferLimits`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LimitError {
+    #[error("Transfer of {amount} to {to} exceeds the per-transfer limit of {max}")]
+    PerTransfer {
+        to: String,
+        amount: Asset,
+        max: Asset,
+    },
+    #[error("{total} transferred within an hour would exceed the hourly limit of {max}")]
+    PerHour { total: Asset, max: Asset },
+    #[error("Transfers to {0} are not on the allow list")]
+    NotAllowed(String),
+    #[error("Transfers to {0} are denied")]
+    Denied(String),
+    /// An operation that may move value elsewhere while an allow list is set
+    #[error("{0} operations are refused while an allow list is set")]
+    Unrecognized(String),
+}
+
+impl From<LimitError> for String {
+    fn from(e: LimitError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Limits on value leaving an account; amounts are per asset symbol
+#[derive(Debug, Clone, Default)]
+pub struct TransferLimits {
+    pub per_transfer: HashMap<AssetSymbol, i64>,
+    pub per_hour: HashMap<AssetSymbol, i64>,
+    /// When set, the only accounts that may receive transfers; operations
+    /// that may move value without [`transfers`] knowing where are refused
+    pub allow: Option<HashSet<String>>,
+    pub deny: HashSet<String>,
+}
+
+impl TransferLimits {
+    pub fn per_transfer(mut self, max: Asset) -> Self {
+        self.per_transfer.insert(max.symbol, max.amount);
+        self
+    }
+
+    /// Cap the total sent within any rolling hour
+    pub fn per_hour(mut self, max: Asset) -> Self {
+        self.per_hour.insert(max.symbol, max.amount);
+        self
+    }
+
+    /// Only allow transfers to these accounts, in addition to any allowed before
+    pub fn allow(mut self, accounts: &[&str]) -> Self {
+        self.allow
+            .get_or_insert_with(HashSet::new)
+            .extend(accounts.iter().map(|a| a.to_string()));
+        self
+    }
+
+    pub fn deny(mut self, accounts: &[&str]) -> Self {
+        self.deny.extend(accounts.iter().map(|a| a.to_string()));
+        self
+    }
+
+    /// Check each transfer in `operations` on `chain` on its own, ignoring the
+    /// hourly limits
+    pub fn check_operations(
+        &self,
+        operations: &[Operation],
+        chain: &ChainConfig,
+    ) -> Result<(), LimitError> {
+        if let Some(op) = operations.iter().find(|op| {
+            self.allow.is_some()
+                && !TRANSFER_OPERATIONS.contains(&op.name())
+                && !NON_TRANSFER_OPERATIONS.contains(&op.name())
+        }) {
+            return Err(LimitError::Unrecognized(op.name().to_string()));
+        }
+        for (to, amount) in operations.iter().flat_map(|op| transfers(op, chain)) {
+            if self.deny.contains(&to) {
+                return Err(LimitError::Denied(to));
+            }
+            if self
+                .allow
+                .as_ref()
+                .is_some_and(|allow| !allow.contains(&to))
+            {
+                return Err(LimitError::NotAllowed(to));
+            }
+            if let Some(&max) = self.per_transfer.get(&amount.symbol) {
+                if amount.amount > max {
+                    let max = Asset::new(max, amount.symbol);
+                    return Err(LimitError::PerTransfer { to, amount, max });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Destination and amount of every value transfer in an operation
+///
+/// Covers transfers, savings, vesting, recurrent and escrow transfers, escrow
+/// agent fees and releases, vesting delegations and withdraw vesting routes; a
+/// missing or empty `to`, as in `transfer_to_vesting` to oneself, is the
+/// sender. A route's withdrawals are not known yet, so it counts as zero VESTS
+/// sent to the route's account. Amounts are parsed with `chain`'s symbol
+/// names, as they would be when signing.
+pub fn transfers(op: &Operation, chain: &ChainConfig) -> Vec<(String, Asset)> {
+    let paid = |to_field: &str, fields: &[&str]| -> Vec<(String, Asset)> {
+        let to = op.str_field(to_field).unwrap_or_default();
+        fields
+            .iter()
+            .filter_map(|field| chain.asset_from_value(op.value.get(*field)?).ok())
+            .filter(|amount| amount.amount > 0)
+            .map(|amount| (to.to_string(), amount))
+            .collect()
+    };
+    match op.name() {
+        "delegate_vesting_shares" => return paid("delegatee", &["vesting_shares"]),
+        "escrow_transfer" => {
+            let mut moved = paid("to", &["hive_amount", "hbd_amount"]);
+            moved.extend(paid("agent", &["fee"]));
+            return moved;
+        }
+        "escrow_release" => return paid("receiver", &["hive_amount", "hbd_amount"]),
+        "set_withdraw_vesting_route" => {
+            let percent = op.value.get("percent").and_then(|p| p.as_u64());
+            return match op.str_field("to_account") {
+                Some(to) if percent.unwrap_or(1) > 0 => {
+                    vec![(to.to_string(), Asset::zero_vests())]
+                }
+                _ => Vec::new(),
+            };
+        }
+        _ => {}
+    }
+    match op.str_field("to") {
+        Some("") => paid("from", VALUE_FIELDS),
+        Some(_) => paid("to", VALUE_FIELDS),
+        None => Vec::new(),
+    }
+}
+
+/// [`TransferLimits`] plus the transfers broadcast within the last hour
+#[derive(Debug)]
+pub(crate) struct TransferGuard {
+    limits: TransferLimits,
+    /// The chain whose symbol names amounts are parsed with
+    chain: ChainConfig,
+    /// When each transfer was admitted, keyed by its transaction
+    sent: Mutex<VecDeque<(Instant, String, Asset)>>,
+}
+
+impl TransferGuard {
+    /// Check a transaction without counting it towards the hourly limits
+    pub(crate) fn check(&self, operations: &[Operation]) -> Result<(), LimitError> {
+        let probe = TransferGuard {
+            limits: self.limits.clone(),
+            chain: self.chain.clone(),
+            sent: Mutex::new(self.sent.lock().unwrap().clone()),
+        };
+        probe.admit("", operations)
+    }
+
+    /// Check transaction `trx_id` about to be broadcast and count it towards
+    /// the hourly limits
+    ///
+    /// A transaction admitted within the hour passes again uncounted, so
+    /// re-broadcasting it is not mistaken for a new transfer.
+    pub(crate) fn admit(&self, trx_id: &str, operations: &[Operation]) -> Result<(), LimitError> {
+        self.limits.check_operations(operations, &self.chain)?;
+        let amounts: Vec<Asset> = operations
+            .iter()
+            .flat_map(|op| transfers(op, &self.chain))
+            .map(|(_, amount)| amount)
+            .collect();
+        let mut sent = self.sent.lock().unwrap();
+        let now = Instant::now();
+        while sent
+            .front()
+            .is_some_and(|(at, _, _)| now.duration_since(*at) >= HOUR)
+        {
+            sent.pop_front();
+        }
+        if !trx_id.is_empty() && sent.iter().any(|(_, id, _)| id == trx_id) {
+            return Ok(());
+        }
+        for (&symbol, &max) in &self.limits.per_hour {
+            let total: i64 = sent
+                .iter()
+                .map(|(_, _, a)| a)
+                .chain(&amounts)
+                .filter(|a| a.symbol == symbol)
+                .map(|a| a.amount)
+                .sum();
+            if total > max {
+                return Err(LimitError::PerHour {
+                    total: Asset::new(total, symbol),
+                    max: Asset::new(max, symbol),
+                });
+            }
+        }
+        sent.extend(
+            amounts
+                .into_iter()
+                .map(|amount| (now, trx_id.to_string(), amount)),
+        );
+        Ok(())
+    }
+}
+
+impl WriteClient {
+    /// Refuse to sign or broadcast transactions breaking `limits`
+    ///
+    /// Every broadcast is checked, including transactions signed elsewhere.
+    /// Transfers count towards the hourly limits once they pass the check at
+    /// broadcast, whether or not the broadcast succeeds.
+    pub fn transfer_limits(mut self, limits: TransferLimits) -> Self {
+        self.limits = Some(TransferGuard {
+            limits,
+            chain: self.chain().clone(),
+            sent: Mutex::new(VecDeque::new()),
+        });
+        self
+    }
+
+    /// Check `operations` against the configured limits without signing
+    ///
+    /// Hourly limits are checked against transfers broadcast so far, without
+    /// counting these.
+    pub fn check_transfers(&self, operations: &[Operation]) -> Result<(), LimitError> {
+        match &self.limits {
+            Some(guard) => guard.check(operations),
+            None => Ok(()),
+        }
+    }
+}
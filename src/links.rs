@@ -0,0 +1,131 @@
+//! Parsing and formatting of Hive front-end links
+
+use crate::explorer::validate_account_name;
+use crate::permlink::validate_permlink;
+
+/// A Hive front-end that links posts as `https://host/[category/]@author/permlink`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Frontend {
+    #[default]
+    HiveBlog,
+    PeakD,
+    Ecency,
+}
+
+impl Frontend {
+    pub fn host(self) -> &'static str {
+        match self {
+            Frontend::HiveBlog => "hive.blog",
+            Frontend::PeakD => "peakd.com",
+            Frontend::Ecency => "ecency.com",
+        }
+    }
+
+    /// Recognize a front-end by host name, ignoring case and `www.`
+    pub fn from_host(host: &str) -> Option<Self> {
+        let host = host.to_ascii_lowercase();
+        match host.strip_prefix("www.").unwrap_or(&host) {
+            "hive.blog" => Some(Frontend::HiveBlog),
+            "peakd.com" => Some(Frontend::PeakD),
+            "ecency.com" => Some(Frontend::Ecency),
+            _ => None,
+        }
+    }
+}
+
+/// Account page tabs that look like `@author/permlink` but are not posts
+const ACCOUNT_TABS: &[&str] = &[
+    "blog",
+    "posts",
+    "comments",
+    "replies",
+    "recent-replies",
+    "feed",
+    "wallet",
+    "transfers",
+    "followers",
+    "following",
+    "communities",
+    "notifications",
+    "activities",
+    "permissions",
+    "settings",
+];
+
+/// The path of `input`, which may be a full URL or a bare path
+///
+/// Only a leading `scheme://` starts a URL, so links in query strings are not
+/// mistaken for one.
+fn url_path(input: &str) -> &str {
+    match input.split_once("://") {
+        Some((scheme, rest))
+            if scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) =>
+        {
+            rest.split_once('/').map_or("", |(_, path)| path)
+        }
+        _ => input,
+    }
+}
+
+/// Parse a post link into `(author, permlink)`
+///
+/// Accepts full URLs on any host as well as bare paths: `@author/permlink`,
+/// `/@author/permlink` and `/category/@author/permlink`. Query strings and
+/// fragments are ignored, as are account page tabs like `@author/wallet`.
+pub fn parse_post_url(input: &str) -> Result<(String, String), String> {
+    let invalid = || format!("Not a Hive post link: '{input}'");
+    let path = url_path(input.trim());
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let (author, permlink) = match segments.as_slice() {
+        [_, permlink] if ACCOUNT_TABS.contains(permlink) => return Err(invalid()),
+        [author, permlink] | [_, author, permlink] => (*author, *permlink),
+        _ => return Err(invalid()),
+    };
+    let author = author
+        .strip_prefix('@')
+        .ok_or_else(invalid)?
+        .to_ascii_lowercase();
+    validate_account_name(&author).map_err(|_| invalid())?;
+    validate_permlink(permlink).map_err(|_| invalid())?;
+    Ok((author, permlink.to_string()))
+}
+
+/// Parse an account link (`https://peakd.com/@alice`, `@alice/wallet`) into the account name
+pub fn parse_account_url(input: &str) -> Result<String, String> {
+    let invalid = || format!("Not a Hive account link: '{input}'");
+    let path = url_path(input.trim());
+    let name = path
+        .trim_start_matches('/')
+        .strip_prefix('@')
+        .and_then(|rest| rest.split(['/', '?', '#']).next())
+        .ok_or_else(invalid)?
+        .to_ascii_lowercase();
+    validate_account_name(&name).map_err(|_| invalid())?;
+    Ok(name)
+}
+
+/// Canonical post path, with the category when given
+pub fn post_path(category: Option<&str>, author: &str, permlink: &str) -> String {
+    match category.filter(|c| !c.is_empty()) {
+        Some(category) => format!("/{category}/@{author}/{permlink}"),
+        None => format!("/@{author}/{permlink}"),
+    }
+}
+
+/// Full post URL on a front-end
+pub fn post_url(frontend: Frontend, author: &str, permlink: &str) -> String {
+    format!(
+        "https://{}{}",
+        frontend.host(),
+        post_path(None, author, permlink)
+    )
+}
+
+/// Full account URL on a front-end
+pub fn account_url(frontend: Frontend, account: &str) -> String {
+    format!("https://{}/@{account}", frontend.host())
+}
@@ -0,0 +1,203 @@
+//! Internal HIVE/HBD market order book and market order simulation
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::operation::Operation;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of orders per side `market_history_api.get_order_book` returns
+pub const ORDER_BOOK_LIMIT: u32 = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookOrder {
+    /// Price in HBD per HIVE
+    #[serde(deserialize_with = "de_price")]
+    pub real_price: f64,
+    /// HIVE amount in its smallest unit
+    pub hive: i64,
+    /// HBD amount in its smallest unit
+    pub hbd: i64,
+    #[serde(default)]
+    pub created: String,
+}
+
+fn de_price<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    /// Orders buying HIVE, best (highest) price first
+    pub bids: Vec<BookOrder>,
+    /// Orders selling HIVE, best (lowest) price first
+    pub asks: Vec<BookOrder>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    /// Spend HBD to buy HIVE, walking the asks
+    Buy,
+    /// Sell HIVE for HBD, walking the bids
+    Sell,
+}
+
+/// A partial fill against one resting order
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub price: f64,
+    pub hive: Asset,
+    pub hbd: Asset,
+}
+
+/// Expected outcome of a market order against an order book snapshot
+#[derive(Debug, Clone)]
+pub struct MarketSimulation {
+    pub side: OrderSide,
+    pub fills: Vec<Fill>,
+    /// Amount of the input asset consumed
+    pub spent: Asset,
+    /// Amount of the output asset received
+    pub received: Asset,
+    /// Input that the book was too thin to fill
+    pub unfilled: Asset,
+    /// Best price on the book before the order
+    pub best_price: f64,
+    /// Volume-weighted price in HBD per HIVE
+    pub average_price: f64,
+    /// Relative difference between the average and best price
+    pub slippage: f64,
+}
+
+/// Build a `limit_order_create` operation offering `amount_to_sell` for at least `min_to_receive`
+pub fn limit_order_create_operation(
+    owner: &str,
+    orderid: u32,
+    amount_to_sell: Asset,
+    min_to_receive: Asset,
+    fill_or_kill: bool,
+    expiration: &str,
+) -> Operation {
+    Operation::new(
+        "limit_order_create_operation",
+        serde_json::json!({
+            "owner": owner,
+            "orderid": orderid,
+            "amount_to_sell": amount_to_sell.to_nai(),
+            "min_to_receive": min_to_receive.to_nai(),
+            "fill_or_kill": fill_or_kill,
+            "expiration": expiration,
+        }),
+    )
+}
+
+/// Build a `limit_order_cancel` operation
+pub fn limit_order_cancel_operation(owner: &str, orderid: u32) -> Operation {
+    Operation::new(
+        "limit_order_cancel_operation",
+        serde_json::json!({ "owner": owner, "orderid": orderid }),
+    )
+}
+
+/// Build a `convert` operation turning HBD into HIVE at the median price after 3.5 days
+pub fn convert_operation(owner: &str, requestid: u32, amount: Asset) -> Operation {
+    Operation::new(
+        "convert_operation",
+        serde_json::json!({
+            "owner": owner,
+            "requestid": requestid,
+            "amount": amount.to_nai(),
+        }),
+    )
+}
+
+impl OrderBook {
+    /// Walk the book to estimate fills, average price and slippage of a market order
+    ///
+    /// `amount` is what is given: HBD for [`OrderSide::Buy`], HIVE for [`OrderSide::Sell`].
+    pub fn simulate_market_order(
+        &self,
+        side: OrderSide,
+        amount: Asset,
+    ) -> Result<MarketSimulation, String> {
+        let (orders, input, output) = match side {
+            OrderSide::Buy => (&self.asks, AssetSymbol::Hbd, AssetSymbol::Hive),
+            OrderSide::Sell => (&self.bids, AssetSymbol::Hive, AssetSymbol::Hbd),
+        };
+        if amount.symbol != input {
+            return Err(format!(
+                "{side:?} orders must be given in {input}, got {amount}"
+            ));
+        }
+        let best_price = orders
+            .first()
+            .map(|o| o.real_price)
+            .ok_or_else(|| "Order book side is empty".to_string())?;
+        let mut remaining = amount.amount;
+        let mut received = 0i64;
+        let mut fills = Vec::new();
+        for order in orders {
+            if remaining <= 0 {
+                break;
+            }
+            let (available, counter) = match side {
+                OrderSide::Buy => (order.hbd, order.hive),
+                OrderSide::Sell => (order.hive, order.hbd),
+            };
+            if available <= 0 {
+                continue;
+            }
+            let taken = remaining.min(available);
+            let got = (i128::from(taken) * i128::from(counter) / i128::from(available)) as i64;
+            remaining -= taken;
+            received += got;
+            let (hive, hbd) = match side {
+                OrderSide::Buy => (got, taken),
+                OrderSide::Sell => (taken, got),
+            };
+            fills.push(Fill {
+                price: order.real_price,
+                hive: Asset::new(hive, AssetSymbol::Hive),
+                hbd: Asset::new(hbd, AssetSymbol::Hbd),
+            });
+        }
+        let total_hive: i64 = fills.iter().map(|f| f.hive.amount).sum();
+        let total_hbd: i64 = fills.iter().map(|f| f.hbd.amount).sum();
+        let average_price = if total_hive > 0 {
+            total_hbd as f64 / total_hive as f64
+        } else {
+            best_price
+        };
+        Ok(MarketSimulation {
+            side,
+            fills,
+            spent: Asset::new(amount.amount - remaining, input),
+            received: Asset::new(received, output),
+            unfilled: Asset::new(remaining, input),
+            best_price,
+            average_price,
+            slippage: (average_price - best_price).abs() / best_price,
+        })
+    }
+}
+
+impl Client {
+    /// Fetch the internal market order book
+    pub fn get_order_book(&self, limit: u32) -> Result<OrderBook, String> {
+        self.call(
+            "market_history_api.get_order_book",
+            serde_json::json!({ "limit": limit.min(ORDER_BOOK_LIMIT) }),
+        )
+        .map_err(|e| format!("Error fetching order book: {e}"))
+    }
+
+    /// Simulate a market order against the current order book
+    pub fn simulate_market_order(
+        &self,
+        side: OrderSide,
+        amount: Asset,
+    ) -> Result<MarketSimulation, String> {
+        self.get_order_book(ORDER_BOOK_LIMIT)?
+            .simulate_market_order(side, amount)
+    }
+}
@@ -0,0 +1,136 @@
+//! Read-modify-write updates of account metadata that keep unrelated keys
+//!
+//! Replacing `json_metadata` wholesale drops keys set by other apps.
+//! [`WriteClient::update_json_metadata`] instead fetches the current object,
+//! lets a closure edit it, and broadcasts only if it changed. If the account's
+//! metadata changes between reading and broadcasting, the update is retried
+//! on the new value.
+
+use crate::operation::Operation;
+use crate::transaction::TransactionSigner;
+use crate::WriteClient;
+use serde_json::{Map, Value};
+
+/// Times an update is retried after the metadata changed underneath it
+pub const METADATA_UPDATE_ATTEMPTS: u32 = 3;
+
+/// Broadcasts of the metadata transaction before giving up on inclusion
+const BROADCAST_ATTEMPTS: u32 = 5;
+
+/// Which of an account's metadata fields to update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    /// `json_metadata`, which needs the active key
+    Json,
+    /// `posting_json_metadata`, used for profiles, which needs the posting key
+    Posting,
+}
+
+impl MetadataField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetadataField::Json => "json_metadata",
+            MetadataField::Posting => "posting_json_metadata",
+        }
+    }
+}
+
+/// Apply `update` to the metadata object in `raw`
+///
+/// Returns the new JSON, or `None` if `update` changed nothing. Empty
+/// metadata is treated as an empty object; metadata that is not a JSON
+/// object is an error rather than being overwritten.
+pub fn merge_metadata<F>(raw: &str, update: F) -> Result<Option<String>, String>
+where
+    F: FnOnce(&mut Map<String, Value>),
+{
+    let mut object = if raw.trim().is_empty() {
+        Map::new()
+    } else {
+        match serde_json::from_str(raw) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => return Err("Account metadata is not a JSON object".to_string()),
+            Err(e) => return Err(format!("Error parsing account metadata: {e}")),
+        }
+    };
+    let before = object.clone();
+    update(&mut object);
+    if object == before {
+        return Ok(None);
+    }
+    serde_json::to_string(&object)
+        .map(Some)
+        .map_err(|e| format!("Error encoding account metadata: {e}"))
+}
+
+/// An `account_update2` operation setting one metadata field of `account`
+///
+/// The other field is left empty, which leaves it unchanged.
+pub fn metadata_operation(account: &str, field: MetadataField, json: &str) -> Operation {
+    let (json_metadata, posting_json_metadata) = match field {
+        MetadataField::Json => (json, ""),
+        MetadataField::Posting => ("", json),
+    };
+    Operation::new(
+        "account_update2_operation",
+        serde_json::json!({
+            "account": account,
+            "json_metadata": json_metadata,
+            "posting_json_metadata": posting_json_metadata,
+            "extensions": [],
+        }),
+    )
+}
+
+impl WriteClient {
+    /// Edit `account`'s metadata in place, keeping keys `update` does not touch
+    ///
+    /// Returns the transaction id, or `None` if nothing changed. `update` may
+    /// run more than once if the metadata changes concurrently.
+    pub fn update_json_metadata<F>(
+        &self,
+        account: &str,
+        field: MetadataField,
+        signer: &dyn TransactionSigner,
+        mut update: F,
+    ) -> Result<Option<String>, String>
+    where
+        F: FnMut(&mut Map<String, Value>),
+    {
+        for _ in 0..METADATA_UPDATE_ATTEMPTS {
+            let original = self.current_metadata(account, field)?;
+            let Some(json) = merge_metadata(&original, &mut update)? else {
+                return Ok(None);
+            };
+            let mut tx =
+                self.prepare_transaction(vec![metadata_operation(account, field, &json)])?;
+            if self.current_metadata(account, field)? != original {
+                continue;
+            }
+            let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+            let status = self.broadcast_reliably(&tx, &trx_id, BROADCAST_ATTEMPTS)?;
+            if !status.is_included() {
+                return Err(format!(
+                    "Metadata update {trx_id} was not included: {status:?}"
+                ));
+            }
+            return Ok(Some(trx_id));
+        }
+        Err(format!(
+            "{} of {account} kept changing; gave up after {METADATA_UPDATE_ATTEMPTS} attempts",
+            field.as_str()
+        ))
+    }
+
+    fn current_metadata(&self, account: &str, field: MetadataField) -> Result<String, String> {
+        let found = self
+            .find_accounts(&[account])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Account '{account}' not found"))?;
+        Ok(match field {
+            MetadataField::Json => found.json_metadata,
+            MetadataField::Posting => found.posting_json_metadata,
+        })
+    }
+}
@@ -0,0 +1,275 @@
+//! Names of known API methods, so typos fail to compile
+//!
+//! Each API has an enum of its methods and a module of string constants:
+//!
+//! ```
+//! use nectarflower_rs::methods::{database_api, DatabaseApi, Method};
+//!
+//! let method = Method::DatabaseApi(DatabaseApi::GetDynamicGlobalProperties);
+//! assert_eq!(method.as_str(), "database_api.get_dynamic_global_properties");
+//! assert_eq!(database_api::GET_DYNAMIC_GLOBAL_PROPERTIES, method.as_str());
+//! ```
+//!
+//! [`Client::call`](crate::Client::call) still takes any string, and
+//! [`Method::Other`] carries methods not listed here.
+
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! apis {
+    ($(
+        $(#[$doc:meta])*
+        $api:ident, $module:ident, $prefix:literal {
+            $($variant:ident, $constant:ident, $method:literal;)*
+        }
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum $api {
+                $($variant,)*
+            }
+
+            impl $api {
+                pub const ALL: &'static [$api] = &[$($api::$variant,)*];
+
+                /// The full method name, e.g. `database_api.get_config`
+                pub fn as_str(self) -> &'static str {
+                    match self {
+                        $($api::$variant => $module::$constant,)*
+                    }
+                }
+            }
+
+            $(#[$doc])*
+            pub mod $module {
+                $(pub const $constant: &str = concat!($prefix, ".", $method);)*
+            }
+        )*
+
+        /// A known API method, or any other method name
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Method {
+            $($api($api),)*
+            Other(String),
+        }
+
+        impl Method {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Method::$api(method) => method.as_str(),)*
+                    Method::Other(method) => method,
+                }
+            }
+
+            /// Every known method
+            pub fn known() -> impl Iterator<Item = Method> {
+                std::iter::empty()
+                    $(.chain($api::ALL.iter().map(|method| Method::$api(*method))))*
+            }
+        }
+
+        $(
+            impl From<$api> for Method {
+                fn from(method: $api) -> Self {
+                    Method::$api(method)
+                }
+            }
+        )*
+    };
+}
+
+apis! {
+    /// `database_api`, the chain state
+    DatabaseApi, database_api, "database_api" {
+        GetActiveWitnesses, GET_ACTIVE_WITNESSES, "get_active_witnesses";
+        GetConfig, GET_CONFIG, "get_config";
+        GetCurrentPriceFeed, GET_CURRENT_PRICE_FEED, "get_current_price_feed";
+        GetDynamicGlobalProperties, GET_DYNAMIC_GLOBAL_PROPERTIES, "get_dynamic_global_properties";
+        GetFeedHistory, GET_FEED_HISTORY, "get_feed_history";
+        GetHardforkProperties, GET_HARDFORK_PROPERTIES, "get_hardfork_properties";
+        GetOrderBook, GET_ORDER_BOOK, "get_order_book";
+        GetPotentialSignatures, GET_POTENTIAL_SIGNATURES, "get_potential_signatures";
+        GetRequiredSignatures, GET_REQUIRED_SIGNATURES, "get_required_signatures";
+        GetRewardFunds, GET_REWARD_FUNDS, "get_reward_funds";
+        GetTransactionHex, GET_TRANSACTION_HEX, "get_transaction_hex";
+        GetVersion, GET_VERSION, "get_version";
+        GetWitnessSchedule, GET_WITNESS_SCHEDULE, "get_witness_schedule";
+        FindAccountRecoveryRequests, FIND_ACCOUNT_RECOVERY_REQUESTS, "find_account_recovery_requests";
+        FindAccounts, FIND_ACCOUNTS, "find_accounts";
+        FindChangeRecoveryAccountRequests, FIND_CHANGE_RECOVERY_ACCOUNT_REQUESTS, "find_change_recovery_account_requests";
+        FindCollateralizedConversionRequests, FIND_COLLATERALIZED_CONVERSION_REQUESTS, "find_collateralized_conversion_requests";
+        FindComments, FIND_COMMENTS, "find_comments";
+        FindEscrows, FIND_ESCROWS, "find_escrows";
+        FindHbdConversionRequests, FIND_HBD_CONVERSION_REQUESTS, "find_hbd_conversion_requests";
+        FindLimitOrders, FIND_LIMIT_ORDERS, "find_limit_orders";
+        FindOwnerHistories, FIND_OWNER_HISTORIES, "find_owner_histories";
+        FindProposals, FIND_PROPOSALS, "find_proposals";
+        FindRecurrentTransfers, FIND_RECURRENT_TRANSFERS, "find_recurrent_transfers";
+        FindSavingsWithdrawals, FIND_SAVINGS_WITHDRAWALS, "find_savings_withdrawals";
+        FindVestingDelegationExpirations, FIND_VESTING_DELEGATION_EXPIRATIONS, "find_vesting_delegation_expirations";
+        FindVestingDelegations, FIND_VESTING_DELEGATIONS, "find_vesting_delegations";
+        FindVotes, FIND_VOTES, "find_votes";
+        FindWithdrawVestingRoutes, FIND_WITHDRAW_VESTING_ROUTES, "find_withdraw_vesting_routes";
+        FindWitnesses, FIND_WITNESSES, "find_witnesses";
+        ListAccounts, LIST_ACCOUNTS, "list_accounts";
+        ListComments, LIST_COMMENTS, "list_comments";
+        ListLimitOrders, LIST_LIMIT_ORDERS, "list_limit_orders";
+        ListProposalVotes, LIST_PROPOSAL_VOTES, "list_proposal_votes";
+        ListProposals, LIST_PROPOSALS, "list_proposals";
+        ListSavingsWithdrawals, LIST_SAVINGS_WITHDRAWALS, "list_savings_withdrawals";
+        ListVestingDelegationExpirations, LIST_VESTING_DELEGATION_EXPIRATIONS, "list_vesting_delegation_expirations";
+        ListVestingDelegations, LIST_VESTING_DELEGATIONS, "list_vesting_delegations";
+        ListVotes, LIST_VOTES, "list_votes";
+        ListWitnessVotes, LIST_WITNESS_VOTES, "list_witness_votes";
+        ListWitnesses, LIST_WITNESSES, "list_witnesses";
+        VerifyAccountAuthority, VERIFY_ACCOUNT_AUTHORITY, "verify_account_authority";
+        VerifyAuthority, VERIFY_AUTHORITY, "verify_authority";
+        VerifySignatures, VERIFY_SIGNATURES, "verify_signatures";
+    }
+
+    /// `block_api`, raw blocks
+    BlockApi, block_api, "block_api" {
+        GetBlock, GET_BLOCK, "get_block";
+        GetBlockHeader, GET_BLOCK_HEADER, "get_block_header";
+        GetBlockRange, GET_BLOCK_RANGE, "get_block_range";
+    }
+
+    /// `account_history_api`, operations by account and block
+    AccountHistoryApi, account_history_api, "account_history_api" {
+        EnumVirtualOps, ENUM_VIRTUAL_OPS, "enum_virtual_ops";
+        GetAccountHistory, GET_ACCOUNT_HISTORY, "get_account_history";
+        GetOpsInBlock, GET_OPS_IN_BLOCK, "get_ops_in_block";
+        GetTransaction, GET_TRANSACTION, "get_transaction";
+    }
+
+    /// `account_by_key_api`, accounts by public key
+    AccountByKeyApi, account_by_key_api, "account_by_key_api" {
+        GetKeyReferences, GET_KEY_REFERENCES, "get_key_references";
+    }
+
+    /// `network_broadcast_api`, which needs a [`WriteClient`](crate::WriteClient)
+    NetworkBroadcastApi, network_broadcast_api, "network_broadcast_api" {
+        BroadcastTransaction, BROADCAST_TRANSACTION, "broadcast_transaction";
+    }
+
+    /// `transaction_status_api`, recent transaction status
+    TransactionStatusApi, transaction_status_api, "transaction_status_api" {
+        FindTransaction, FIND_TRANSACTION, "find_transaction";
+    }
+
+    /// `rc_api`, resource credits
+    RcApi, rc_api, "rc_api" {
+        FindRcAccounts, FIND_RC_ACCOUNTS, "find_rc_accounts";
+        GetResourceParams, GET_RESOURCE_PARAMS, "get_resource_params";
+        GetResourcePool, GET_RESOURCE_POOL, "get_resource_pool";
+        ListRcAccounts, LIST_RC_ACCOUNTS, "list_rc_accounts";
+        ListRcDirectDelegations, LIST_RC_DIRECT_DELEGATIONS, "list_rc_direct_delegations";
+    }
+
+    /// `market_history_api`, the internal market
+    MarketHistoryApi, market_history_api, "market_history_api" {
+        GetMarketHistory, GET_MARKET_HISTORY, "get_market_history";
+        GetMarketHistoryBuckets, GET_MARKET_HISTORY_BUCKETS, "get_market_history_buckets";
+        GetOrderBook, GET_ORDER_BOOK, "get_order_book";
+        GetRecentTrades, GET_RECENT_TRADES, "get_recent_trades";
+        GetTicker, GET_TICKER, "get_ticker";
+        GetTradeHistory, GET_TRADE_HISTORY, "get_trade_history";
+        GetVolume, GET_VOLUME, "get_volume";
+    }
+
+    /// `reputation_api`, raw account reputation
+    ReputationApi, reputation_api, "reputation_api" {
+        GetAccountReputations, GET_ACCOUNT_REPUTATIONS, "get_account_reputations";
+    }
+
+    /// `bridge`, Hivemind's social API
+    Bridge, bridge, "bridge" {
+        AccountNotifications, ACCOUNT_NOTIFICATIONS, "account_notifications";
+        GetAccountPosts, GET_ACCOUNT_POSTS, "get_account_posts";
+        GetCommunity, GET_COMMUNITY, "get_community";
+        GetDiscussion, GET_DISCUSSION, "get_discussion";
+        GetFollowList, GET_FOLLOW_LIST, "get_follow_list";
+        GetPayoutStats, GET_PAYOUT_STATS, "get_payout_stats";
+        GetPost, GET_POST, "get_post";
+        GetPostHeader, GET_POST_HEADER, "get_post_header";
+        GetProfile, GET_PROFILE, "get_profile";
+        GetRankedPosts, GET_RANKED_POSTS, "get_ranked_posts";
+        GetRelationshipBetweenAccounts, GET_RELATIONSHIP_BETWEEN_ACCOUNTS, "get_relationship_between_accounts";
+        ListAllSubscriptions, LIST_ALL_SUBSCRIPTIONS, "list_all_subscriptions";
+        ListCommunities, LIST_COMMUNITIES, "list_communities";
+        ListCommunityRoles, LIST_COMMUNITY_ROLES, "list_community_roles";
+        ListSubscribers, LIST_SUBSCRIBERS, "list_subscribers";
+        NormalizePost, NORMALIZE_POST, "normalize_post";
+        UnreadNotifications, UNREAD_NOTIFICATIONS, "unread_notifications";
+    }
+
+    /// `condenser_api`, the legacy API with positional parameters
+    CondenserApi, condenser_api, "condenser_api" {
+        BroadcastTransaction, BROADCAST_TRANSACTION, "broadcast_transaction";
+        BroadcastTransactionSynchronous, BROADCAST_TRANSACTION_SYNCHRONOUS, "broadcast_transaction_synchronous";
+        GetAccountHistory, GET_ACCOUNT_HISTORY, "get_account_history";
+        GetAccounts, GET_ACCOUNTS, "get_accounts";
+        GetActiveVotes, GET_ACTIVE_VOTES, "get_active_votes";
+        GetBlock, GET_BLOCK, "get_block";
+        GetChainProperties, GET_CHAIN_PROPERTIES, "get_chain_properties";
+        GetContent, GET_CONTENT, "get_content";
+        GetContentReplies, GET_CONTENT_REPLIES, "get_content_replies";
+        GetCurrentMedianHistoryPrice, GET_CURRENT_MEDIAN_HISTORY_PRICE, "get_current_median_history_price";
+        GetDiscussionsByBlog, GET_DISCUSSIONS_BY_BLOG, "get_discussions_by_blog";
+        GetDiscussionsByCreated, GET_DISCUSSIONS_BY_CREATED, "get_discussions_by_created";
+        GetDiscussionsByFeed, GET_DISCUSSIONS_BY_FEED, "get_discussions_by_feed";
+        GetDiscussionsByTrending, GET_DISCUSSIONS_BY_TRENDING, "get_discussions_by_trending";
+        GetDynamicGlobalProperties, GET_DYNAMIC_GLOBAL_PROPERTIES, "get_dynamic_global_properties";
+        GetFollowCount, GET_FOLLOW_COUNT, "get_follow_count";
+        GetFollowers, GET_FOLLOWERS, "get_followers";
+        GetFollowing, GET_FOLLOWING, "get_following";
+        GetOpsInBlock, GET_OPS_IN_BLOCK, "get_ops_in_block";
+        GetRewardFund, GET_REWARD_FUND, "get_reward_fund";
+        GetVersion, GET_VERSION, "get_version";
+        GetVestingDelegations, GET_VESTING_DELEGATIONS, "get_vesting_delegations";
+        GetWitnessByAccount, GET_WITNESS_BY_ACCOUNT, "get_witness_by_account";
+        LookupAccounts, LOOKUP_ACCOUNTS, "lookup_accounts";
+    }
+}
+
+impl Method {
+    /// The API part of the name, e.g. `database_api`
+    pub fn api(&self) -> &str {
+        let name = self.as_str();
+        name.split_once('.').map_or(name, |(api, _)| api)
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Method::Other(_))
+    }
+}
+
+impl FromStr for Method {
+    type Err = std::convert::Infallible;
+
+    /// The known method with this name, or [`Method::Other`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Method::known()
+            .find(|method| method.as_str() == s)
+            .unwrap_or_else(|| Method::Other(s.to_string())))
+    }
+}
+
+impl From<&str> for Method {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_else(|never| match never {})
+    }
+}
+
+impl AsRef<str> for Method {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,329 @@
+//! Compact MessagePack encoding for persisting and replaying block streams
+//!
+//! Values are encoded through their JSON representation, so anything that
+//! round-trips through `serde_json` (blocks, operations, [`StreamedBlock`]s)
+//! round-trips here with the same field names. Object keys are written in
+//! sorted order, making the output deterministic.
+//!
+//! [`StreamedBlock`]: crate::block::StreamedBlock
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+
+/// Written at the start of every stream file so readers can reject foreign data
+pub const STREAM_MAGIC: &[u8; 4] = b"NFMP";
+
+/// Bumped whenever the encoding of stream records changes incompatibly
+pub const STREAM_VERSION: u8 = 1;
+
+/// Deepest nesting of arrays and maps accepted when decoding
+pub const MAX_DEPTH: usize = 128;
+
+/// Encode a value as MessagePack
+pub fn to_msgpack<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let value = serde_json::to_value(value).map_err(|e| format!("Encode error: {e}"))?;
+    let mut out = Vec::new();
+    encode_value(&value, &mut out);
+    Ok(out)
+}
+
+/// Decode a value from MessagePack
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    let mut reader = bytes;
+    let value = decode_value(&mut reader).map_err(|e| format!("Decode error: {e}"))?;
+    if !reader.is_empty() {
+        return Err(format!("Decode error: {} trailing bytes", reader.len()));
+    }
+    serde_json::from_value(value).map_err(|e| format!("Decode error: {e}"))
+}
+
+fn encode_len(len: usize, fix: (u8, usize), tags: [u8; 3], out: &mut Vec<u8>) {
+    if len < fix.1 {
+        out.push(fix.0 | len as u8);
+    } else if tags[0] != 0 && len <= u8::MAX as usize {
+        out.extend([tags[0], len as u8]);
+    } else if len <= u16::MAX as usize {
+        out.push(tags[1]);
+        out.extend((len as u16).to_be_bytes());
+    } else {
+        out.push(tags[2]);
+        out.extend((len as u32).to_be_bytes());
+    }
+}
+
+fn encode_str(s: &str, out: &mut Vec<u8>) {
+    encode_len(s.len(), (0xa0, 32), [0xd9, 0xda, 0xdb], out);
+    out.extend(s.as_bytes());
+}
+
+fn encode_number(n: &Number, out: &mut Vec<u8>) {
+    if let Some(u) = n.as_u64() {
+        match u {
+            0..=0x7f => out.push(u as u8),
+            0x80..=0xff => out.extend([0xcc, u as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend((u as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend((u as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xcf);
+                out.extend(u.to_be_bytes());
+            }
+        }
+    } else if let Some(i) = n.as_i64() {
+        if i >= -32 {
+            out.push(i as i8 as u8);
+        } else if i >= i64::from(i8::MIN) {
+            out.extend([0xd0, i as i8 as u8]);
+        } else if i >= i64::from(i16::MIN) {
+            out.push(0xd1);
+            out.extend((i as i16).to_be_bytes());
+        } else if i >= i64::from(i32::MIN) {
+            out.push(0xd2);
+            out.extend((i as i32).to_be_bytes());
+        } else {
+            out.push(0xd3);
+            out.extend(i.to_be_bytes());
+        }
+    } else if n.is_f64() {
+        out.push(0xcb);
+        out.extend(n.as_f64().unwrap_or_default().to_be_bytes());
+    } else {
+        // Integers beyond 64 bits (with `arbitrary_precision`) keep their exact digits
+        encode_str(&n.to_string(), out);
+    }
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(false) => out.push(0xc2),
+        Value::Bool(true) => out.push(0xc3),
+        Value::Number(n) => encode_number(n, out),
+        Value::String(s) => encode_str(s, out),
+        Value::Array(items) => {
+            encode_len(items.len(), (0x90, 16), [0, 0xdc, 0xdd], out);
+            for item in items {
+                encode_value(item, out);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            encode_len(entries.len(), (0x80, 16), [0, 0xde, 0xdf], out);
+            for (key, item) in entries {
+                encode_str(key, out);
+                encode_value(item, out);
+            }
+        }
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn decode_str(reader: &mut impl Read, len: usize) -> io::Result<String> {
+    // Read through `take` so a corrupt length cannot allocate more than the input holds
+    let mut buf = Vec::with_capacity(len.min(4096));
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(buf).map_err(|e| invalid(format!("Invalid UTF-8 string: {e}")))
+}
+
+fn decode_array(reader: &mut impl Read, len: usize, depth: usize) -> io::Result<Value> {
+    let mut items = Vec::with_capacity(len.min(4096));
+    for _ in 0..len {
+        items.push(decode_nested(reader, depth)?);
+    }
+    Ok(Value::Array(items))
+}
+
+fn decode_map(reader: &mut impl Read, len: usize, depth: usize) -> io::Result<Value> {
+    let mut map = Map::new();
+    for _ in 0..len {
+        let Value::String(key) = decode_nested(reader, depth)? else {
+            return Err(invalid("Map key is not a string".to_string()));
+        };
+        map.insert(key, decode_nested(reader, depth)?);
+    }
+    Ok(Value::Object(map))
+}
+
+fn decode_float(f: f64) -> io::Result<Value> {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .ok_or_else(|| invalid(format!("Non-finite float {f}")))
+}
+
+fn decode_value(reader: &mut impl Read) -> io::Result<Value> {
+    decode_nested(reader, 0)
+}
+
+fn decode_nested(reader: &mut impl Read, depth: usize) -> io::Result<Value> {
+    let [tag] = read_array(reader)?;
+    let depth = match tag {
+        0x80..=0x9f | 0xdc..=0xdf if depth == MAX_DEPTH => {
+            return Err(invalid(format!("Nesting deeper than {MAX_DEPTH} levels")))
+        }
+        0x80..=0x9f | 0xdc..=0xdf => depth + 1,
+        _ => depth,
+    };
+    let value = match tag {
+        0x00..=0x7f => Value::from(tag),
+        0x80..=0x8f => decode_map(reader, usize::from(tag & 0x0f), depth)?,
+        0x90..=0x9f => decode_array(reader, usize::from(tag & 0x0f), depth)?,
+        0xa0..=0xbf => Value::String(decode_str(reader, usize::from(tag & 0x1f))?),
+        0xc0 => Value::Null,
+        0xc2 => Value::Bool(false),
+        0xc3 => Value::Bool(true),
+        0xca => decode_float(f64::from(f32::from_be_bytes(read_array(reader)?)))?,
+        0xcb => decode_float(f64::from_be_bytes(read_array(reader)?))?,
+        0xcc => Value::from(u8::from_be_bytes(read_array(reader)?)),
+        0xcd => Value::from(u16::from_be_bytes(read_array(reader)?)),
+        0xce => Value::from(u32::from_be_bytes(read_array(reader)?)),
+        0xcf => Value::from(u64::from_be_bytes(read_array(reader)?)),
+        0xd0 => Value::from(i8::from_be_bytes(read_array(reader)?)),
+        0xd1 => Value::from(i16::from_be_bytes(read_array(reader)?)),
+        0xd2 => Value::from(i32::from_be_bytes(read_array(reader)?)),
+        0xd3 => Value::from(i64::from_be_bytes(read_array(reader)?)),
+        0xd9 => {
+            let len = u8::from_be_bytes(read_array(reader)?);
+            Value::String(decode_str(reader, usize::from(len))?)
+        }
+        0xda => {
+            let len = u16::from_be_bytes(read_array(reader)?);
+            Value::String(decode_str(reader, usize::from(len))?)
+        }
+        0xdb => {
+            let len = u32::from_be_bytes(read_array(reader)?);
+            Value::String(decode_str(reader, len as usize)?)
+        }
+        0xdc => {
+            let len = u16::from_be_bytes(read_array(reader)?);
+            decode_array(reader, usize::from(len), depth)?
+        }
+        0xdd => {
+            let len = u32::from_be_bytes(read_array(reader)?);
+            decode_array(reader, len as usize, depth)?
+        }
+        0xde => {
+            let len = u16::from_be_bytes(read_array(reader)?);
+            decode_map(reader, usize::from(len), depth)?
+        }
+        0xdf => {
+            let len = u32::from_be_bytes(read_array(reader)?);
+            decode_map(reader, len as usize, depth)?
+        }
+        0xe0..=0xff => Value::from(tag as i8),
+        other => {
+            return Err(invalid(format!(
+                "Unsupported MessagePack type 0x{other:02x}"
+            )))
+        }
+    };
+    Ok(value)
+}
+
+/// Appends records to a MessagePack stream file
+#[derive(Debug)]
+pub struct MsgpackWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl<W: Write> MsgpackWriter<W> {
+    /// Start a new stream, writing the format header
+    pub fn new(writer: W) -> Result<Self, String> {
+        let mut writer = BufWriter::new(writer);
+        writer
+            .write_all(STREAM_MAGIC)
+            .and_then(|_| writer.write_all(&[STREAM_VERSION]))
+            .map_err(|e| format!("Write error: {e}"))?;
+        Ok(Self { writer })
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), String> {
+        let bytes = to_msgpack(record)?;
+        self.writer
+            .write_all(&bytes)
+            .map_err(|e| format!("Write error: {e}"))
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|e| format!("Write error: {e}"))
+    }
+}
+
+/// Iterates over the records of a MessagePack stream file
+///
+/// Iteration ends after a read error, since the stream cannot be resynchronized.
+#[derive(Debug)]
+pub struct MsgpackReader<R: Read, T> {
+    reader: BufReader<R>,
+    failed: bool,
+    _record: PhantomData<T>,
+}
+
+impl<R: Read, T: DeserializeOwned> MsgpackReader<R, T> {
+    /// Open a stream, checking its format header
+    pub fn new(reader: R) -> Result<Self, String> {
+        let mut reader = BufReader::new(reader);
+        let header: [u8; 5] = read_array(&mut reader).map_err(|e| format!("Read error: {e}"))?;
+        if &header[..4] != STREAM_MAGIC {
+            return Err("Not a nectarflower MessagePack stream".to_string());
+        }
+        if header[4] != STREAM_VERSION {
+            return Err(format!(
+                "Unsupported stream version {} (expected {STREAM_VERSION})",
+                header[4]
+            ));
+        }
+        Ok(Self {
+            reader,
+            failed: false,
+            _record: PhantomData,
+        })
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for MsgpackReader<R, T> {
+    type Item = Result<T, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.reader.fill_buf() {
+            Ok([]) => return None,
+            Ok(_) => {}
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(format!("Read error: {e}")));
+            }
+        }
+        let value = match decode_value(&mut self.reader) {
+            Ok(value) => value,
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(format!("Read error: {e}")));
+            }
+        };
+        // A record of the wrong shape is still a whole record, so reading can go on
+        Some(serde_json::from_value(value).map_err(|e| format!("Decode error: {e}")))
+    }
+}
@@ -0,0 +1,146 @@
+//! Structured diffs of a client's node list
+//!
+//! Whenever the node list is replaced, e.g. by
+//! [`Client::update_nodes_from_account`] or an
+//! [`AutoBenchmark`](crate::node_scan::AutoBenchmark), the change is returned
+//! as a [`NodeListChange`] and passed to the listeners registered with
+//! [`Client::on_node_list_change`], so node churn can be logged or alerted on.
+
+use crate::fail_reason::NodeFailure;
+use crate::{Client, Url};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// What changed between two node lists
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeListChange {
+    /// Nodes now in use that were not before
+    pub added: Vec<String>,
+    /// Nodes no longer in use
+    pub removed: Vec<String>,
+    /// Nodes that were not failing before, with the reason they are now
+    pub newly_failing: Vec<(Url, NodeFailure)>,
+    /// Nodes that are no longer listed as failing
+    pub recovered: Vec<Url>,
+    /// Whether nodes in use both before and after changed order
+    pub reordered: bool,
+}
+
+impl NodeListChange {
+    /// Compare the nodes and failing nodes of two lists, in their order
+    pub fn between(
+        old_nodes: &[String],
+        old_failing: &HashMap<Url, NodeFailure>,
+        new_nodes: &[String],
+        new_failing: &HashMap<Url, NodeFailure>,
+    ) -> Self {
+        let added = new_nodes
+            .iter()
+            .filter(|node| !old_nodes.contains(node))
+            .cloned()
+            .collect();
+        let removed = old_nodes
+            .iter()
+            .filter(|node| !new_nodes.contains(node))
+            .cloned()
+            .collect();
+        let mut newly_failing: Vec<_> = new_failing
+            .iter()
+            .filter(|(url, _)| !old_failing.contains_key(url))
+            .map(|(url, failure)| (url.clone(), failure.clone()))
+            .collect();
+        newly_failing.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut recovered: Vec<_> = old_failing
+            .keys()
+            .filter(|url| !new_failing.contains_key(url))
+            .cloned()
+            .collect();
+        recovered.sort();
+        let kept_old = old_nodes.iter().filter(|node| new_nodes.contains(node));
+        let kept_new = new_nodes.iter().filter(|node| old_nodes.contains(node));
+        Self {
+            added,
+            removed,
+            newly_failing,
+            recovered,
+            reordered: !kept_old.eq(kept_new),
+        }
+    }
+
+    /// Whether the lists were identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.newly_failing.is_empty()
+            && self.recovered.is_empty()
+            && !self.reordered
+    }
+}
+
+impl fmt::Display for NodeListChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("node list unchanged");
+        }
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        for (url, failure) in &self.newly_failing {
+            parts.push(format!("{url} failing ({}: {failure})", failure.reason));
+        }
+        if !self.recovered.is_empty() {
+            let urls: Vec<_> = self.recovered.iter().map(Url::as_str).collect();
+            parts.push(format!("recovered {}", urls.join(", ")));
+        }
+        if self.reordered {
+            parts.push("reordered".to_string());
+        }
+        f.write_str(&parts.join("; "))
+    }
+}
+
+type Listener = Arc<dyn Fn(&NodeListChange) + Send + Sync>;
+
+/// Callbacks registered with [`Client::on_node_list_change`]
+#[derive(Clone, Default)]
+pub(crate) struct NodeListeners(Vec<Listener>);
+
+impl fmt::Debug for NodeListeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeListeners({})", self.0.len())
+    }
+}
+
+impl Client {
+    /// Call `listener` with every non-empty change to the node list
+    pub fn on_node_list_change<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&NodeListChange) + Send + Sync + 'static,
+    {
+        self.node_listeners.0.push(Arc::new(listener));
+        self
+    }
+
+    /// Replace the node list as given, notifying listeners of the change
+    pub(crate) fn replace_nodes(
+        &mut self,
+        nodes: Vec<String>,
+        failing_nodes: HashMap<Url, NodeFailure>,
+    ) -> NodeListChange {
+        let change =
+            NodeListChange::between(&self.nodes, &self.failing_nodes, &nodes, &failing_nodes);
+        self.nodes = nodes;
+        self.failing_nodes = failing_nodes;
+        if !change.is_empty() {
+            for listener in &self.node_listeners.0 {
+                listener(&change);
+            }
+        }
+        change
+    }
+}
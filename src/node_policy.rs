@@ -0,0 +1,44 @@
+//! What to do when a new node list leaves no usable node
+//!
+//! [`Client::set_nodes`] drops invalid and failing nodes. If that removes
+//! every node, the [`EmptyNodePolicy`] set with
+//! [`Client::on_empty_node_list`] decides whether the client keeps its
+//! previous nodes, switches to fallback nodes, or refuses the update with
+//! [`EmptyNodeList`].
+
+use crate::Client;
+
+/// How [`Client::set_nodes`] handles a list with no usable node
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EmptyNodePolicy {
+    /// Keep the current nodes, recording the new failing nodes, and warn
+    #[default]
+    KeepPrevious,
+    /// Use these nodes instead, and warn
+    Fallback(Vec<String>),
+    /// Leave the client unchanged and return [`EmptyNodeList`]
+    Error,
+}
+
+/// A node list in which every node was invalid or failing
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("No usable nodes: {invalid} invalid and {failing} failing of {offered} offered")]
+pub struct EmptyNodeList {
+    pub offered: usize,
+    pub invalid: usize,
+    pub failing: usize,
+}
+
+impl From<EmptyNodeList> for String {
+    fn from(e: EmptyNodeList) -> Self {
+        e.to_string()
+    }
+}
+
+impl Client {
+    /// Decide what [`Client::set_nodes`] does when no usable node remains
+    pub fn on_empty_node_list(mut self, policy: EmptyNodePolicy) -> Self {
+        self.empty_node_policy = policy;
+        self
+    }
+}
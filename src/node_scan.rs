@@ -0,0 +1,579 @@
+//! Benchmarking Hive nodes and publishing the results in the nectarflower format
+//!
+//! [`NodeScanner`] runs a fixed set of checks against each node and produces
+//! [`NodeMetadata`], the JSON stored in the `nectarflower` account's
+//! `json_metadata`. The same type reads the published list back, so a scan can
+//! be published as-is and compared with what clients currently consume.
+
+use crate::fail_reason::{FailReason, NodeFailure};
+use crate::operation::Operation;
+use crate::timestamp::parse_timestamp;
+use crate::transaction::HIVE_CHAIN_ID;
+use crate::version::Version;
+use crate::{AccountParams, AccountsResponse, Client, NodeData, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Account whose metadata publishes the node list
+pub const NODE_LIST_ACCOUNT: &str = "nectarflower";
+
+/// Default per-request timeout while scanning
+pub const DEFAULT_SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time between re-benchmarks
+pub const DEFAULT_BENCHMARK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Default fraction by which a node must be faster to move ahead of another
+pub const DEFAULT_HYSTERESIS: f64 = 0.2;
+
+/// Default largest head block age before a node counts as stale
+pub const DEFAULT_MAX_HEAD_DELAY: Duration = Duration::from_secs(60);
+
+/// The node list as published in the account's `json_metadata`
+///
+/// Fields this crate does not know are kept in `extra` so that reading and
+/// re-publishing the metadata does not drop them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    /// Working nodes, best first
+    pub nodes: Vec<String>,
+    /// Failing nodes and why they failed
+    #[serde(default)]
+    pub failing_nodes: HashMap<String, NodeFailure>,
+    #[serde(default)]
+    pub report: Vec<NodeReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<ScanParameters>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Benchmark results of a single node
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeReport {
+    pub node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the node reports the Hive chain id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<BenchmarkResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_diff: Option<BenchmarkResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<BenchmarkResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<BenchmarkResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apicall: Option<BenchmarkResult>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl NodeReport {
+    /// Every test that was run, by name
+    pub fn tests(&self) -> impl Iterator<Item = (&'static str, &BenchmarkResult)> {
+        [
+            ("config", &self.config),
+            ("block_diff", &self.block_diff),
+            ("block", &self.block),
+            ("history", &self.history),
+            ("apicall", &self.apicall),
+        ]
+        .into_iter()
+        .filter_map(|(name, test)| test.as_ref().map(|t| (name, t)))
+    }
+
+    /// Whether every test that was run passed
+    pub fn ok(&self) -> bool {
+        self.tests().all(|(_, t)| t.ok)
+    }
+
+    /// Total time of all tests in seconds
+    pub fn total_time(&self) -> f64 {
+        self.tests().map(|(_, t)| t.time).sum()
+    }
+}
+
+/// Outcome of one benchmark test
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub ok: bool,
+    /// Duration in seconds
+    pub time: f64,
+    /// 1-based rank among nodes that passed this test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<i32>,
+    /// Number of requests made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    /// Seconds between the head block time and the scan (`block_diff` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_delay: Option<f64>,
+    /// Blocks between head and last irreversible block (`block_diff` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_head_irreversible: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Settings the scan ran with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanParameters {
+    /// Per-request timeout in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+    /// Unix time the scan finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark_time: Option<f64>,
+    /// Highest head block seen during the scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_block: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl NodeMetadata {
+    /// Parse an account's `json_metadata`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Error parsing node metadata: {e}"))
+    }
+
+    /// Serialize for an account's `json_metadata`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("node metadata serializes")
+    }
+
+    /// The node list in the form [`Client::set_nodes`] takes
+    pub fn node_data(&self) -> NodeData {
+        NodeData {
+            nodes: self.nodes.clone(),
+            failing_nodes: NodeData::failing_from_published(self.failing_nodes.clone()),
+        }
+    }
+
+    /// The report of `node`, if it was scanned
+    pub fn report_for(&self, node: &str) -> Option<&NodeReport> {
+        self.report.iter().find(|r| r.node == node)
+    }
+
+    /// An `account_update2` operation publishing this metadata on `account`
+    ///
+    /// Needs the account's active authority. The empty `posting_json_metadata`
+    /// leaves the posting metadata unchanged.
+    pub fn publish_operation(&self, account: &str) -> Operation {
+        Operation::new(
+            "account_update2_operation",
+            serde_json::json!({
+                "account": account,
+                "json_metadata": self.to_json(),
+                "posting_json_metadata": "",
+                "extensions": [],
+            }),
+        )
+    }
+}
+
+/// Benchmarks nodes and builds [`NodeMetadata`]
+#[derive(Debug, Clone)]
+pub struct NodeScanner {
+    nodes: Vec<String>,
+    account: String,
+    timeout: Duration,
+    max_head_delay: Duration,
+}
+
+impl NodeScanner {
+    pub fn new<I, S>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            nodes: nodes.into_iter().map(Into::into).collect(),
+            account: NODE_LIST_ACCOUNT.to_string(),
+            timeout: DEFAULT_SCAN_TIMEOUT,
+            max_head_delay: DEFAULT_MAX_HEAD_DELAY,
+        }
+    }
+
+    /// Account used for the history and API call tests
+    pub fn account(mut self, account: &str) -> Self {
+        self.account = account.to_string();
+        self
+    }
+
+    /// Per-request timeout; a node exceeding it fails the test
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Mark nodes whose head block is older than this as failing
+    pub fn max_head_delay(mut self, delay: Duration) -> Self {
+        self.max_head_delay = delay;
+        self
+    }
+
+    /// Scan every node, one at a time
+    pub fn scan(&self) -> NodeMetadata {
+        let client = self.client();
+        let scans: Vec<NodeScan> = self
+            .nodes
+            .iter()
+            .map(|node| self.scan_node_with(&client, node))
+            .collect();
+        let last_block = scans.iter().filter_map(|s| s.head).max();
+
+        let mut failing_nodes = HashMap::new();
+        let mut reports = Vec::with_capacity(scans.len());
+        for scan in scans {
+            if let Some(reason) = self.failure(&scan) {
+                failing_nodes.insert(scan.report.node.clone(), reason);
+            }
+            reports.push(scan.report);
+        }
+        rank(&mut reports);
+        let mut working: Vec<&NodeReport> = reports
+            .iter()
+            .filter(|r| !failing_nodes.contains_key(&r.node))
+            .collect();
+        working.sort_by(|a, b| a.total_time().total_cmp(&b.total_time()));
+
+        NodeMetadata {
+            nodes: working.iter().map(|r| r.node.clone()).collect(),
+            failing_nodes,
+            parameter: Some(ScanParameters {
+                timeout: Some(self.timeout.as_secs_f64()),
+                benchmark_time: Some(unix_now()),
+                last_block,
+                extra: Map::new(),
+            }),
+            report: reports,
+            extra: Map::new(),
+        }
+    }
+
+    /// Run every test against one node
+    pub fn scan_node(&self, node: &str) -> NodeReport {
+        self.scan_node_with(&self.client(), node).report
+    }
+
+    fn client(&self) -> Client {
+        Client::builder().timeout(self.timeout).build()
+    }
+
+    fn scan_node_with(&self, client: &Client, node: &str) -> NodeScan {
+        let mut scan = NodeScan {
+            report: NodeReport {
+                node: node.to_string(),
+                ..NodeReport::default()
+            },
+            head: None,
+            error: None,
+        };
+
+        let (config, version) =
+            timed(|| client.call_node::<_, Version>(node, "condenser_api.get_version", &()));
+        if let Ok(version) = &version {
+            scan.report.version = Some(version.blockchain_version.clone());
+            scan.report.hive = Some(version.chain_id == HIVE_CHAIN_ID);
+        }
+        scan.report.config = Some(scan.record("config", config, version.as_ref().err()));
+
+        let (mut block_diff, props) = timed(|| {
+            client.call_node::<_, crate::DynamicGlobalProperties>(
+                node,
+                "database_api.get_dynamic_global_properties",
+                &(),
+            )
+        });
+        if let Ok(props) = &props {
+            scan.head = Some(props.head_block_number);
+            block_diff.diff_head_irreversible = Some(
+                props
+                    .head_block_number
+                    .saturating_sub(props.last_irreversible_block_num),
+            );
+            block_diff.head_delay = parse_timestamp(&props.time)
+                .ok()
+                .map(|time| (unix_now() - time as f64).max(0.0).round());
+        }
+        scan.report.block_diff = Some(scan.record("block_diff", block_diff, props.as_ref().err()));
+
+        let (block, result) = timed(|| {
+            let block_num = scan.head.ok_or("No head block")?;
+            client
+                .call_node::<_, Value>(
+                    node,
+                    "block_api.get_block",
+                    &serde_json::json!({ "block_num": block_num }),
+                )
+                .map_err(String::from)
+                .and_then(|r| match r.get("block") {
+                    Some(Value::Object(_)) => Ok(()),
+                    _ => Err(format!("Block {block_num} missing")),
+                })
+        });
+        scan.report.block = Some(scan.record("block", block, result.as_ref().err()));
+
+        let (history, result) = timed(|| {
+            client.call_node::<_, Value>(
+                node,
+                "account_history_api.get_account_history",
+                &serde_json::json!({ "account": self.account, "start": -1, "limit": 1 }),
+            )
+        });
+        scan.report.history = Some(scan.record("history", history, result.as_ref().err()));
+
+        let (apicall, result) = timed(|| {
+            client
+                .call_node::<_, AccountsResponse>(
+                    node,
+                    "database_api.find_accounts",
+                    &AccountParams {
+                        accounts: vec![self.account.clone()],
+                    },
+                )
+                .map_err(String::from)
+                .and_then(|r| match r.accounts.is_empty() {
+                    true => Err(format!("Account '{}' not found", self.account)),
+                    false => Ok(()),
+                })
+        });
+        scan.report.apicall = Some(scan.record("apicall", apicall, result.as_ref().err()));
+
+        scan
+    }
+
+    /// Why a node should be listed as failing, `None` if it is usable
+    fn failure(&self, scan: &NodeScan) -> Option<NodeFailure> {
+        if let Some(error) = &scan.error {
+            return Some(NodeFailure::from_message(error.as_str()));
+        }
+        if scan.report.hive == Some(false) {
+            return Some(NodeFailure::new(
+                FailReason::Other,
+                "config: not a Hive node",
+            ));
+        }
+        let delay = scan.report.block_diff.as_ref()?.head_delay?;
+        (delay > self.max_head_delay.as_secs_f64()).then(|| {
+            NodeFailure::new(
+                FailReason::Stale,
+                format!("block_diff: head block is {delay}s old"),
+            )
+        })
+    }
+}
+
+/// A node's report plus what the scan needs but does not publish
+struct NodeScan {
+    report: NodeReport,
+    head: Option<u32>,
+    /// The first failed test and its error
+    error: Option<String>,
+}
+
+impl NodeScan {
+    fn record<E: ToString>(
+        &mut self,
+        name: &str,
+        test: BenchmarkResult,
+        error: Option<E>,
+    ) -> BenchmarkResult {
+        if let (Some(e), None) = (error, &self.error) {
+            self.error = Some(format!("{name}: {}", e.to_string()));
+        }
+        test
+    }
+}
+
+fn timed<T, E>(f: impl FnOnce() -> Result<T, E>) -> (BenchmarkResult, Result<T, E>) {
+    let start = Instant::now();
+    let result = f();
+    let test = BenchmarkResult {
+        ok: result.is_ok(),
+        time: (start.elapsed().as_secs_f64() * 1000.0).round() / 1000.0,
+        count: Some(1),
+        ..BenchmarkResult::default()
+    };
+    (test, result)
+}
+
+/// Rank nodes by time within each test; failed tests get rank -1
+fn rank(reports: &mut [NodeReport]) {
+    let tests: [fn(&mut NodeReport) -> &mut Option<BenchmarkResult>; 5] = [
+        |r| &mut r.config,
+        |r| &mut r.block_diff,
+        |r| &mut r.block,
+        |r| &mut r.history,
+        |r| &mut r.apicall,
+    ];
+    for test in tests {
+        let mut times: Vec<(usize, f64)> = reports
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, r)| test(r).as_ref().filter(|t| t.ok).map(|t| (i, t.time)))
+            .collect();
+        times.sort_by(|a, b| a.1.total_cmp(&b.1));
+        for report in reports.iter_mut() {
+            if let Some(t) = test(report) {
+                t.rank = Some(-1);
+            }
+        }
+        for (position, (i, _)) in times.into_iter().enumerate() {
+            if let Some(t) = test(&mut reports[i]) {
+                t.rank = Some(position as i32 + 1);
+            }
+        }
+    }
+}
+
+/// Periodically re-benchmarks a client's nodes and re-ranks them with hysteresis
+///
+/// A node only moves ahead of another when it is faster by more than the
+/// hysteresis fraction, so small timing jitter between scans does not reorder
+/// the list and make long-lived streams switch nodes. Call [`AutoBenchmark::tick`]
+/// regularly, e.g. between stream batches; it scans only once the interval
+/// has passed.
+#[derive(Debug, Clone)]
+pub struct AutoBenchmark {
+    scanner: NodeScanner,
+    interval: Duration,
+    hysteresis: f64,
+    last_run: Option<Instant>,
+}
+
+impl AutoBenchmark {
+    pub fn new(scanner: NodeScanner) -> Self {
+        Self {
+            scanner,
+            interval: DEFAULT_BENCHMARK_INTERVAL,
+            hysteresis: DEFAULT_HYSTERESIS,
+            last_run: None,
+        }
+    }
+
+    /// Benchmark every node `client` knows, working or failing
+    pub fn for_client(client: &Client) -> Self {
+        let mut nodes = client.nodes.clone();
+        let known: Vec<Url> = nodes.iter().filter_map(|n| Url::parse(n).ok()).collect();
+        let mut failing: Vec<&Url> = client
+            .failing_nodes
+            .keys()
+            .filter(|url| !known.contains(url))
+            .collect();
+        failing.sort();
+        nodes.extend(
+            failing
+                .into_iter()
+                .map(|url| url.as_str().trim_end_matches('/').to_string()),
+        );
+        Self::new(NodeScanner::new(nodes))
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Fraction (0.0 to 1.0) by which a node must be faster to overtake another
+    pub fn hysteresis(mut self, fraction: f64) -> Self {
+        self.hysteresis = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether the interval has passed since the last scan
+    pub fn is_due(&self) -> bool {
+        self.last_run.is_none_or(|t| t.elapsed() >= self.interval)
+    }
+
+    /// Re-benchmark and re-rank `client`'s nodes if due, returning the scan
+    pub fn tick(&mut self, client: &mut Client) -> Option<NodeMetadata> {
+        self.is_due().then(|| self.run(client))
+    }
+
+    /// Re-benchmark and re-rank `client`'s nodes now
+    pub fn run(&mut self, client: &mut Client) -> NodeMetadata {
+        let metadata = self.scanner.scan();
+        self.last_run = Some(Instant::now());
+        self.apply(client, &metadata);
+        metadata
+    }
+
+    /// Re-rank `client`'s nodes from a scan, returning whether the order changed
+    ///
+    /// When every node failed the current list is kept, since an empty list
+    /// would leave the client unable to make any call.
+    pub fn apply(&self, client: &mut Client, metadata: &NodeMetadata) -> bool {
+        if metadata.nodes.is_empty() {
+            return false;
+        }
+        let scores: HashMap<&str, f64> = metadata
+            .nodes
+            .iter()
+            .map(|node| {
+                let time = metadata
+                    .report_for(node)
+                    .map_or(f64::MAX, NodeReport::total_time);
+                (node.as_str(), time)
+            })
+            .collect();
+        let nodes = rerank(&client.nodes, &metadata.nodes, &scores, self.hysteresis);
+        let changed = nodes != client.nodes;
+        client.replace_nodes(nodes, metadata.node_data().failing_nodes);
+        changed
+    }
+}
+
+/// Keep the current order of still-working nodes unless a node is faster by
+/// more than `hysteresis`; newly working nodes join at the end, fastest first
+fn rerank(
+    current: &[String],
+    working: &[String],
+    scores: &HashMap<&str, f64>,
+    hysteresis: f64,
+) -> Vec<String> {
+    let mut order: Vec<String> = current
+        .iter()
+        .filter(|node| scores.contains_key(node.as_str()))
+        .cloned()
+        .collect();
+    order.extend(
+        working
+            .iter()
+            .filter(|node| !current.contains(node))
+            .cloned(),
+    );
+    let beats = |a: &str, b: &str| scores[a] < scores[b] * (1.0 - hysteresis);
+    for i in 1..order.len() {
+        let mut j = i;
+        while j > 0 && beats(&order[j], &order[j - 1]) {
+            order.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+    order
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs() as f64)
+}
+
+impl Client {
+    /// Fetch the full node metadata, including the benchmark report, from `account`
+    pub fn get_node_metadata(&self, account: &str) -> Result<NodeMetadata, String> {
+        let account_data = self
+            .find_accounts(&[account])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Account '{account}' not found"))?;
+        NodeMetadata::from_json(&account_data.json_metadata)
+    }
+}
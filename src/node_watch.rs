@@ -0,0 +1,138 @@
+//! Hot-swapping the node list as soon as the curator publishes a new one
+//!
+//! The node list lives in the `json_metadata` of the node list account,
+//! `nectarflower` by default. Instead of polling it, a [`NodeListWatcher`]
+//! follows new blocks for `account_update` and `account_update2` operations
+//! on that account and applies the published list to the client right away.
+
+use crate::block::{Block, BLOCK_INTERVAL};
+use crate::node_changes::NodeListChange;
+use crate::node_scan::{NodeMetadata, NODE_LIST_ACCOUNT};
+use crate::{Client, NodeData};
+use std::thread;
+
+/// Something a [`NodeListWatcher`] noticed
+#[derive(Debug, Clone)]
+pub enum NodeWatchEvent {
+    /// A published node list was applied
+    Updated {
+        block_num: u32,
+        trx_id: String,
+        change: NodeListChange,
+    },
+    /// A metadata update could not be applied, e.g. it held no usable node
+    Rejected {
+        block_num: u32,
+        trx_id: String,
+        error: String,
+    },
+    /// Fetching a block failed; the watcher retries it
+    Error(String),
+}
+
+/// Applies node lists published in the node list account's metadata
+#[derive(Debug, Clone)]
+pub struct NodeListWatcher {
+    account: String,
+}
+
+impl Default for NodeListWatcher {
+    fn default() -> Self {
+        Self {
+            account: NODE_LIST_ACCOUNT.to_string(),
+        }
+    }
+}
+
+impl NodeListWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow the node list published by `account` instead of `nectarflower`
+    pub fn account(mut self, account: &str) -> Self {
+        self.account = account.to_string();
+        self
+    }
+
+    /// Node lists published by the account in `block`, by transaction id
+    pub fn updates_in_block<'a>(
+        &self,
+        block: &'a Block,
+    ) -> Vec<(&'a str, Result<NodeData, String>)> {
+        block
+            .operations()
+            .into_iter()
+            .filter(|(_, op)| matches!(op.name(), "account_update" | "account_update2"))
+            .filter(|(_, op)| op.str_field("account") == Some(self.account.as_str()))
+            .filter_map(|(trx_id, op)| {
+                // An empty json_metadata leaves the metadata unchanged
+                let json = op
+                    .str_field("json_metadata")
+                    .filter(|json| !json.is_empty())?;
+                Some((trx_id, NodeMetadata::from_json(json).map(|m| m.node_data())))
+            })
+            .collect()
+    }
+
+    /// Apply the node lists published in `block` to `client`
+    pub fn apply_block(
+        &self,
+        client: &mut Client,
+        block_num: u32,
+        block: &Block,
+    ) -> Vec<NodeWatchEvent> {
+        self.updates_in_block(block)
+            .into_iter()
+            .map(|(trx_id, node_data)| {
+                let applied = node_data.and_then(|data| {
+                    client
+                        .set_nodes(data.nodes, data.failing_nodes)
+                        .map_err(String::from)
+                });
+                let trx_id = trx_id.to_string();
+                match applied {
+                    Ok(change) => NodeWatchEvent::Updated {
+                        block_num,
+                        trx_id,
+                        change,
+                    },
+                    Err(error) => NodeWatchEvent::Rejected {
+                        block_num,
+                        trx_id,
+                        error,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Follow new blocks and update `client` until `on_event` returns `false`
+    pub fn watch<F>(&self, client: &mut Client, mut on_event: F) -> Result<(), String>
+    where
+        F: FnMut(NodeWatchEvent) -> bool,
+    {
+        let mut next = client.get_dynamic_global_properties()?.head_block_number + 1;
+        loop {
+            let events = match client.get_block(next) {
+                Ok(Some(block)) => {
+                    next += 1;
+                    self.apply_block(client, next - 1, &block)
+                }
+                Ok(None) => {
+                    thread::sleep(BLOCK_INTERVAL);
+                    Vec::new()
+                }
+                Err(e) => {
+                    thread::sleep(BLOCK_INTERVAL);
+                    vec![NodeWatchEvent::Error(e)]
+                }
+            };
+            for event in events {
+                if !on_event(event) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Lossless decoding of large integers the API sends as numbers or strings
+//!
+//! Responses are decoded through [`serde_json::Value`], which stores integers
+//! above `u64::MAX` as `f64` unless the `arbitrary_precision` feature is
+//! enabled. The helpers here refuse such lossy values instead of silently
+//! rounding them, and keep exact digits when the feature is on.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Number, Value};
+use std::fmt;
+use std::str::FromStr;
+
+fn exact_digits(n: &Number) -> Result<String, String> {
+    if n.is_f64() {
+        if n.as_f64().is_some_and(|f| f.fract() != 0.0) {
+            return Err(format!("Invalid integer: {n}"));
+        }
+        return Err(format!(
+            "Integer {n} was decoded as a float and may have lost precision; \
+             enable the `arbitrary_precision` feature"
+        ));
+    }
+    Ok(n.to_string())
+}
+
+/// Deserialize an integer that the API may return as either a number or a string
+pub(crate) fn de_int<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    <T as FromStr>::Err: fmt::Display,
+{
+    let digits = match Value::deserialize(deserializer)? {
+        Value::Number(n) => exact_digits(&n).map_err(serde::de::Error::custom)?,
+        Value::String(s) => s,
+        other => {
+            return Err(serde::de::Error::custom(format!(
+                "Invalid integer: {other}"
+            )))
+        }
+    };
+    digits
+        .parse()
+        .map_err(|e| serde::de::Error::custom(format!("Invalid integer '{digits}': {e}")))
+}
+
+/// `#[serde(with = "...")]` helpers for `u128` fields, serialized as strings
+pub mod u128_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        de_int(deserializer)
+    }
+}
+
+/// `#[serde(with = "...")]` helpers for `i128` fields, serialized as strings
+pub mod i128_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &i128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+        de_int(deserializer)
+    }
+}
+
+/// An integer of any size kept as its exact decimal digits
+///
+/// Digits are stored without leading zeros, so equal values compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExactNumber(String);
+
+impl Default for ExactNumber {
+    fn default() -> Self {
+        Self("0".to_string())
+    }
+}
+
+impl ExactNumber {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.parse().ok()
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        self.0.parse().ok()
+    }
+}
+
+impl FromStr for ExactNumber {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid integer: '{s}'"));
+        }
+        let digits = digits.trim_start_matches('0');
+        Ok(Self(match (digits.is_empty(), negative) {
+            (true, _) => "0".to_string(),
+            (false, true) => format!("-{digits}"),
+            (false, false) => digits.to_string(),
+        }))
+    }
+}
+
+impl fmt::Display for ExactNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<u128> for ExactNumber {
+    fn from(value: u128) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<i128> for ExactNumber {
+    fn from(value: i128) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl Serialize for ExactNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExactNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        de_int(deserializer)
+    }
+}
@@ -0,0 +1,211 @@
+//! Server-side operation filters for account history
+//!
+//! `account_history_api.get_account_history` takes two 64-bit masks,
+//! `operation_filter_low` and `operation_filter_high`, with one bit per
+//! operation id. [`OpTypeSet`] builds them from operation names.
+
+use crate::operation::Operation;
+
+/// Every operation name, indexed by hived's operation id; virtual operations start at 50
+pub const OPERATION_TYPES: &[&str] = &[
+    "vote",
+    "comment",
+    "transfer",
+    "transfer_to_vesting",
+    "withdraw_vesting",
+    "limit_order_create",
+    "limit_order_cancel",
+    "feed_publish",
+    "convert",
+    "account_create",
+    "account_update",
+    "witness_update",
+    "account_witness_vote",
+    "account_witness_proxy",
+    "pow",
+    "custom",
+    "report_over_production",
+    "delete_comment",
+    "custom_json",
+    "comment_options",
+    "set_withdraw_vesting_route",
+    "limit_order_create2",
+    "claim_account",
+    "create_claimed_account",
+    "request_account_recovery",
+    "recover_account",
+    "change_recovery_account",
+    "escrow_transfer",
+    "escrow_dispute",
+    "escrow_release",
+    "pow2",
+    "escrow_approve",
+    "transfer_to_savings",
+    "transfer_from_savings",
+    "cancel_transfer_from_savings",
+    "custom_binary",
+    "decline_voting_rights",
+    "reset_account",
+    "set_reset_account",
+    "claim_reward_balance",
+    "delegate_vesting_shares",
+    "account_create_with_delegation",
+    "witness_set_properties",
+    "account_update2",
+    "create_proposal",
+    "update_proposal_votes",
+    "remove_proposal",
+    "update_proposal",
+    "collateralized_convert",
+    "recurrent_transfer",
+    "fill_convert_request",
+    "author_reward",
+    "curation_reward",
+    "comment_reward",
+    "liquidity_reward",
+    "interest",
+    "fill_vesting_withdraw",
+    "fill_order",
+    "shutdown_witness",
+    "fill_transfer_from_savings",
+    "hardfork",
+    "comment_payout_update",
+    "return_vesting_delegation",
+    "comment_benefactor_reward",
+    "producer_reward",
+    "clear_null_account_balance",
+    "proposal_pay",
+    "dhf_funding",
+    "hardfork_hive",
+    "hardfork_hive_restore",
+    "delayed_voting",
+    "consolidate_treasury_balance",
+    "effective_comment_vote",
+    "ineffective_delete_comment",
+    "dhf_conversion",
+    "expired_account_notification",
+    "changed_recovery_account",
+    "transfer_to_vesting_completed",
+    "pow_reward",
+    "vesting_shares_split",
+    "account_created",
+    "fill_collateralized_convert_request",
+    "system_warning",
+    "fill_recurrent_transfer",
+    "failed_recurrent_transfer",
+    "limit_order_cancelled",
+    "producer_missed",
+    "proposal_fee",
+    "collateralized_convert_immediate_conversion",
+    "escrow_approved",
+    "escrow_rejected",
+    "proxy_cleared",
+    "declined_voting_rights",
+];
+
+/// Operations moving liquid or savings funds between accounts
+const TRANSFERS: &[&str] = &[
+    "transfer",
+    "transfer_to_vesting",
+    "transfer_to_savings",
+    "transfer_from_savings",
+    "fill_transfer_from_savings",
+    "recurrent_transfer",
+    "fill_recurrent_transfer",
+    "escrow_transfer",
+    "escrow_release",
+];
+
+/// Rewards paid to an account and their claims
+const REWARDS: &[&str] = &[
+    "claim_reward_balance",
+    "author_reward",
+    "curation_reward",
+    "comment_benefactor_reward",
+    "producer_reward",
+    "interest",
+    "proposal_pay",
+];
+
+/// hived's operation id for a name, with or without the `_operation` suffix
+pub fn op_type_id(name: &str) -> Option<u32> {
+    let name = name.strip_suffix("_operation").unwrap_or(name);
+    OPERATION_TYPES
+        .iter()
+        .position(|op| *op == name)
+        .map(|id| id as u32)
+}
+
+/// A set of operation types, compiled to account history filter masks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct OpTypeSet {
+    bits: u128,
+}
+
+impl OpTypeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set of the named operations, failing on the first unknown name
+    pub fn from_names(names: &[&str]) -> Result<Self, String> {
+        names
+            .iter()
+            .try_fold(Self::new(), |set, name| set.with(name))
+    }
+
+    /// Transfers in and out of liquid, savings, vesting, recurrent and escrow balances
+    pub fn transfers() -> Self {
+        // The names are known operations
+        Self::from_names(TRANSFERS).unwrap()
+    }
+
+    /// Author, curation, benefactor, producer, interest and proposal rewards, and claims
+    pub fn rewards() -> Self {
+        Self::from_names(REWARDS).unwrap()
+    }
+
+    pub fn with(mut self, name: &str) -> Result<Self, String> {
+        let id = op_type_id(name).ok_or_else(|| format!("Unknown operation type '{name}'"))?;
+        self.bits |= 1 << id;
+        Ok(self)
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        op_type_id(name).is_some_and(|id| self.bits & (1 << id) != 0)
+    }
+
+    /// Whether `op` is of a type in the set, for filtering locally
+    pub fn matches(&self, op: &Operation) -> bool {
+        self.contains(op.name())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Names of the operation types in the set, in id order
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        OPERATION_TYPES
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| self.bits & (1 << id) != 0)
+            .map(|(_, name)| *name)
+    }
+
+    /// `operation_filter_low`, covering operation ids 0 to 63
+    pub fn low(&self) -> u64 {
+        self.bits as u64
+    }
+
+    /// `operation_filter_high`, covering operation ids 64 to 127
+    pub fn high(&self) -> u64 {
+        (self.bits >> 64) as u64
+    }
+}
@@ -0,0 +1,65 @@
+//! Operations in the `{"type": ..., "value": ...}` format used by appbase APIs
+//!
+//! The legacy `["transfer", {...}]` format of `condenser_api` is accepted when
+//! deserializing and converted to the appbase format.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Operation {
+    #[serde(rename = "type")]
+    pub op_type: String,
+    pub value: Value,
+}
+
+/// Either operation format, as found in transactions and blocks
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OperationRepr {
+    Appbase {
+        #[serde(rename = "type")]
+        op_type: String,
+        value: Value,
+    },
+    Legacy(String, Value),
+}
+
+impl<'de> Deserialize<'de> for Operation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match OperationRepr::deserialize(deserializer)? {
+            OperationRepr::Appbase { op_type, value } => Operation { op_type, value },
+            OperationRepr::Legacy(name, value) => Operation {
+                op_type: format!("{name}_operation"),
+                value,
+            },
+        })
+    }
+}
+
+impl Operation {
+    pub fn new(op_type: &str, value: Value) -> Self {
+        Self {
+            op_type: op_type.to_string(),
+            value,
+        }
+    }
+
+    /// The operation name without the `_operation` suffix, e.g. `transfer`
+    pub fn name(&self) -> &str {
+        self.op_type
+            .strip_suffix("_operation")
+            .unwrap_or(&self.op_type)
+    }
+
+    /// A string field of the operation value
+    pub fn str_field(&self, field: &str) -> Option<&str> {
+        self.value.get(field).and_then(|v| v.as_str())
+    }
+
+    /// Deserialize the operation value into a typed struct
+    pub fn value_as<T: for<'de> Deserialize<'de>>(&self) -> Result<T, String> {
+        serde_json::from_value(self.value.clone())
+            .map_err(|e| format!("Error decoding {} operation: {e}", self.name()))
+    }
+}
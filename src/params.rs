@@ -0,0 +1,109 @@
+//! The [`params!`](crate::params!) macro for ad-hoc call parameters
+//!
+//! Three forms replace hand-written `serde_json::json!` blocks:
+//!
+//! ```
+//! use nectarflower_rs::params;
+//!
+//! // Named parameters of a known method, with field names checked at compile time
+//! let block = params!(get_block { block_num: 5 });
+//! // Named parameters of any method
+//! let custom = params! { account: "alice", limit: 10 };
+//! // Positional parameters for condenser_api
+//! let positional = params!(["alice", 10]);
+//! # assert_eq!(block["block_num"], 5);
+//! # assert_eq!(custom["limit"], 10);
+//! # assert_eq!(positional[0], "alice");
+//! ```
+//!
+//! Fields of known methods that are left out are omitted from the object,
+//! so optional parameters can simply be skipped. A misspelled field does not
+//! compile:
+//!
+//! ```compile_fail
+//! let block = nectarflower_rs::params!(get_block { block_number: 5 });
+//! ```
+
+use serde::Serialize;
+
+/// Build JSON-RPC parameters; see the [module documentation](mod@crate::params)
+#[macro_export]
+macro_rules! params {
+    ([$($value:expr),* $(,)?]) => {
+        $crate::params::Value::Array(vec![$($crate::params::to_value(&$value)),*])
+    };
+    ($method:ident { $($field:ident : $value:expr),* $(,)? }) => {{
+        #[allow(clippy::needless_update)]
+        let params = $crate::params::known::$method {
+            $($field: Some($crate::params::to_value(&$value)),)*
+            ..Default::default()
+        };
+        $crate::params::to_value(&params)
+    }};
+    ($($key:ident : $value:expr),* $(,)?) => {{
+        let mut object = $crate::params::Map::new();
+        $(object.insert(stringify!($key).to_string(), $crate::params::to_value(&$value));)*
+        $crate::params::Value::Object(object)
+    }};
+}
+
+#[doc(hidden)]
+pub use serde_json::{Map, Value};
+
+/// Convert a parameter value to JSON
+///
+/// Panics, like `serde_json::json!`, if the value cannot be represented in
+/// JSON, such as a map with non-string keys.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).expect("parameter is not representable as JSON")
+}
+
+macro_rules! known_methods {
+    ($($method:ident { $($field:ident),* })*) => {
+        /// Parameters of known methods, named after the method without its API
+        ///
+        /// Used by [`params!`](crate::params!); every field is optional.
+        #[allow(non_camel_case_types)]
+        pub mod known {
+            use serde::Serialize;
+            use serde_json::Value;
+
+            $(
+                #[derive(Debug, Default, Serialize)]
+                pub struct $method {
+                    $(
+                        #[serde(skip_serializing_if = "Option::is_none")]
+                        pub $field: Option<Value>,
+                    )*
+                }
+            )*
+        }
+    };
+}
+
+known_methods! {
+    get_version {}
+    get_dynamic_global_properties {}
+    get_block { block_num }
+    get_block_header { block_num }
+    get_block_range { starting_block_num, count }
+    find_accounts { accounts, delegated_vesting_shares }
+    list_accounts { start, limit, order }
+    get_account_history {
+        account, start, limit, operation_filter_low, operation_filter_high, include_reversible
+    }
+    get_ops_in_block { block_num, only_virtual, include_reversible }
+    get_transaction { id, include_reversible }
+    find_transaction { transaction_id, expiration }
+    find_comments { comments }
+    list_comments { start, limit, order }
+    find_proposals { proposal_ids }
+    list_proposals { start, limit, order, order_direction, status }
+    find_witnesses { owners }
+    list_witnesses { start, limit, order }
+    find_rc_accounts { accounts }
+    get_ranked_posts { sort, tag, observer, limit, start_author, start_permlink }
+    get_account_posts { sort, account, observer, limit, start_author, start_permlink }
+    get_discussion { author, permlink, observer }
+    get_follow_list { observer, follow_type }
+}
@@ -0,0 +1,932 @@
+//! Character diffs and diff-match-patch compatible patch text for post edits
+//!
+//! A port of diff-match-patch's `patch_make` as condenser calls it: a
+//! line-level pass for long texts, the linear-space middle-snake bisection,
+//! semantic and efficiency cleanup, and rolling context. Positions count
+//! Unicode scalar values. Like upstream's `Diff_Timeout`, diffing stops after
+//! [`DIFF_TIMEOUT`]; [`patch_text`] then gives up so the full text is sent.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Characters of context kept around each patch
+const PATCH_MARGIN: usize = 4;
+
+/// Longest pattern diff-match-patch can locate when applying a patch
+const MATCH_MAX_BITS: usize = 32;
+
+/// Cost of an empty edit operation, in characters, for the efficiency cleanup
+const DIFF_EDIT_COST: usize = 4;
+
+/// Lines of the old and new text diff-match-patch encodes before bailing out
+const MAX_LINES: (usize, usize) = (40_000, 65_535);
+
+/// How long a diff may run, diff-match-patch's default `Diff_Timeout`
+pub const DIFF_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+    pub op: DiffOp,
+    pub text: Vec<char>,
+}
+
+/// A diff over characters, or over line numbers in line mode
+type Edit<T> = (DiffOp, Vec<T>);
+
+/// Compute a character diff between two texts
+///
+/// Returns `None` when the diff does not finish within [`DIFF_TIMEOUT`].
+pub fn diff(old: &str, new: &str) -> Option<Vec<Diff>> {
+    let a: Vec<char> = old.chars().collect();
+    let b: Vec<char> = new.chars().collect();
+    let mut differ = Differ::new(DIFF_TIMEOUT);
+    let diffs = differ.main(&a, &b, true);
+    (!differ.timed_out).then(|| to_diffs(diffs))
+}
+
+fn to_diffs(diffs: Vec<Edit<char>>) -> Vec<Diff> {
+    diffs
+        .into_iter()
+        .map(|(op, text)| Diff { op, text })
+        .collect()
+}
+
+/// Items diff-match-patch diffs: characters, or lines in line mode
+trait Token: Copy + Eq {
+    /// Diff two long texts line by line first, if this is a character
+    fn line_mode(_differ: &mut Differ, _a: &[Self], _b: &[Self]) -> Option<Vec<Edit<Self>>> {
+        None
+    }
+}
+
+impl Token for char {
+    fn line_mode(differ: &mut Differ, a: &[char], b: &[char]) -> Option<Vec<Edit<char>>> {
+        Some(differ.line_mode(a, b))
+    }
+}
+
+impl Token for u32 {}
+
+/// The deadline of one diff, shared by its recursion
+struct Differ {
+    deadline: Instant,
+    timed_out: bool,
+}
+
+impl Differ {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + timeout,
+            timed_out: false,
+        }
+    }
+
+    /// diff-match-patch's `diff_main`
+    fn main<T: Token>(&mut self, a: &[T], b: &[T], checklines: bool) -> Vec<Edit<T>> {
+        if a == b {
+            return match a.is_empty() {
+                true => Vec::new(),
+                false => vec![(DiffOp::Equal, a.to_vec())],
+            };
+        }
+        let prefix = common_prefix(a, b);
+        let suffix = common_suffix(&a[prefix..], &b[prefix..]);
+        let mut diffs = Vec::new();
+        if prefix > 0 {
+            diffs.push((DiffOp::Equal, a[..prefix].to_vec()));
+        }
+        diffs.extend(self.compute(
+            &a[prefix..a.len() - suffix],
+            &b[prefix..b.len() - suffix],
+            checklines,
+        ));
+        if suffix > 0 {
+            diffs.push((DiffOp::Equal, a[a.len() - suffix..].to_vec()));
+        }
+        cleanup_merge(&mut diffs);
+        diffs
+    }
+
+    /// Diff two texts sharing no common prefix or suffix
+    fn compute<T: Token>(&mut self, a: &[T], b: &[T], checklines: bool) -> Vec<Edit<T>> {
+        if a.is_empty() {
+            return vec![(DiffOp::Insert, b.to_vec())];
+        }
+        if b.is_empty() {
+            return vec![(DiffOp::Delete, a.to_vec())];
+        }
+        let (long, short) = if a.len() > b.len() { (a, b) } else { (b, a) };
+        if let Some(i) = find(long, short, 0) {
+            let op = if a.len() > b.len() {
+                DiffOp::Delete
+            } else {
+                DiffOp::Insert
+            };
+            return vec![
+                (op, long[..i].to_vec()),
+                (DiffOp::Equal, short.to_vec()),
+                (op, long[i + short.len()..].to_vec()),
+            ];
+        }
+        if short.len() == 1 {
+            return vec![(DiffOp::Delete, a.to_vec()), (DiffOp::Insert, b.to_vec())];
+        }
+        if let Some(hm) = half_match(a, b) {
+            let mut diffs = self.main(&hm.a_prefix, &hm.b_prefix, checklines);
+            diffs.push((DiffOp::Equal, hm.common));
+            diffs.extend(self.main(&hm.a_suffix, &hm.b_suffix, checklines));
+            return diffs;
+        }
+        if checklines && a.len() > 100 && b.len() > 100 {
+            if let Some(diffs) = T::line_mode(self, a, b) {
+                return diffs;
+            }
+        }
+        self.bisect(a, b)
+    }
+
+    /// Diff line by line, then rediff each replaced block character by character
+    fn line_mode(&mut self, a: &[char], b: &[char]) -> Vec<Edit<char>> {
+        let mut lines: Vec<&[char]> = vec![&[]];
+        let mut hash: HashMap<&[char], u32> = HashMap::new();
+        let a_lines = lines_to_ids(a, &mut lines, &mut hash, MAX_LINES.0);
+        let b_lines = lines_to_ids(b, &mut lines, &mut hash, MAX_LINES.1);
+        let mut diffs: Vec<Edit<char>> = self
+            .main(&a_lines, &b_lines, false)
+            .into_iter()
+            .map(|(op, ids)| {
+                let text = ids.iter().flat_map(|&id| lines[id as usize]).copied();
+                (op, text.collect())
+            })
+            .collect();
+        cleanup_semantic(&mut diffs);
+
+        diffs.push((DiffOp::Equal, Vec::new()));
+        let mut pointer = 0;
+        let (mut count_delete, mut count_insert) = (0, 0);
+        let (mut text_delete, mut text_insert) = (Vec::new(), Vec::new());
+        while pointer < diffs.len() {
+            match diffs[pointer].0 {
+                DiffOp::Insert => {
+                    count_insert += 1;
+                    text_insert.extend_from_slice(&diffs[pointer].1);
+                }
+                DiffOp::Delete => {
+                    count_delete += 1;
+                    text_delete.extend_from_slice(&diffs[pointer].1);
+                }
+                DiffOp::Equal => {
+                    if count_delete >= 1 && count_insert >= 1 {
+                        let start = pointer - count_delete - count_insert;
+                        let sub = self.main(&text_delete, &text_insert, false);
+                        let count = sub.len();
+                        diffs.splice(start..pointer, sub);
+                        pointer = start + count;
+                    }
+                    count_delete = 0;
+                    count_insert = 0;
+                    text_delete.clear();
+                    text_insert.clear();
+                }
+            }
+            pointer += 1;
+        }
+        diffs.pop();
+        diffs
+    }
+
+    /// Find the middle snake in linear space and diff both halves
+    fn bisect<T: Token>(&mut self, a: &[T], b: &[T]) -> Vec<Edit<T>> {
+        let (n, m) = (a.len() as isize, b.len() as isize);
+        let max_d = (n + m + 1) / 2;
+        let v_offset = max_d;
+        let v_length = 2 * max_d;
+        let mut v1 = vec![-1isize; v_length as usize];
+        let mut v2 = vec![-1isize; v_length as usize];
+        v1[(v_offset + 1) as usize] = 0;
+        v2[(v_offset + 1) as usize] = 0;
+        let delta = n - m;
+        // With an odd delta the forward path collides with the reverse path
+        let front = delta % 2 != 0;
+        let (mut k1start, mut k1end, mut k2start, mut k2end) = (0, 0, 0, 0);
+        let at = |v: &[isize], k: isize| v[k as usize];
+        for d in 0..max_d {
+            if Instant::now() > self.deadline {
+                self.timed_out = true;
+                break;
+            }
+            let mut k1 = -d + k1start;
+            while k1 <= d - k1end {
+                let k1_offset = v_offset + k1;
+                let mut x1 =
+                    if k1 == -d || (k1 != d && at(&v1, k1_offset - 1) < at(&v1, k1_offset + 1)) {
+                        at(&v1, k1_offset + 1)
+                    } else {
+                        at(&v1, k1_offset - 1) + 1
+                    };
+                let mut y1 = x1 - k1;
+                while x1 >= 0 && y1 >= 0 && x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                    x1 += 1;
+                    y1 += 1;
+                }
+                v1[k1_offset as usize] = x1;
+                if x1 > n {
+                    k1end += 2;
+                } else if y1 > m {
+                    k1start += 2;
+                } else if front {
+                    let k2_offset = v_offset + delta - k1;
+                    if (0..v_length).contains(&k2_offset) && at(&v2, k2_offset) != -1 {
+                        let x2 = n - at(&v2, k2_offset);
+                        if x1 >= x2 {
+                            return self.bisect_split(a, b, x1 as usize, y1 as usize);
+                        }
+                    }
+                }
+                k1 += 2;
+            }
+            let mut k2 = -d + k2start;
+            while k2 <= d - k2end {
+                let k2_offset = v_offset + k2;
+                let mut x2 =
+                    if k2 == -d || (k2 != d && at(&v2, k2_offset - 1) < at(&v2, k2_offset + 1)) {
+                        at(&v2, k2_offset + 1)
+                    } else {
+                        at(&v2, k2_offset - 1) + 1
+                    };
+                let mut y2 = x2 - k2;
+                while x2 >= 0
+                    && y2 >= 0
+                    && x2 < n
+                    && y2 < m
+                    && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize]
+                {
+                    x2 += 1;
+                    y2 += 1;
+                }
+                v2[k2_offset as usize] = x2;
+                if x2 > n {
+                    k2end += 2;
+                } else if y2 > m {
+                    k2start += 2;
+                } else if !front {
+                    let k1_offset = v_offset + delta - k2;
+                    if (0..v_length).contains(&k1_offset) && at(&v1, k1_offset) != -1 {
+                        let x1 = at(&v1, k1_offset);
+                        let y1 = v_offset + x1 - k1_offset;
+                        if x1 >= n - x2 {
+                            return self.bisect_split(a, b, x1 as usize, y1 as usize);
+                        }
+                    }
+                }
+                k2 += 2;
+            }
+        }
+        // Out of time, or no commonality at all
+        vec![(DiffOp::Delete, a.to_vec()), (DiffOp::Insert, b.to_vec())]
+    }
+
+    fn bisect_split<T: Token>(&mut self, a: &[T], b: &[T], x: usize, y: usize) -> Vec<Edit<T>> {
+        let mut diffs = self.main(&a[..x], &b[..y], false);
+        diffs.extend(self.main(&a[x..], &b[y..], false));
+        diffs
+    }
+}
+
+/// Encode each line of `text`, trailing newline included, as an index into `lines`
+fn lines_to_ids<'a>(
+    text: &'a [char],
+    lines: &mut Vec<&'a [char]>,
+    hash: &mut HashMap<&'a [char], u32>,
+    max_lines: usize,
+) -> Vec<u32> {
+    let mut ids = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = text[start..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(text.len(), |i| start + i + 1);
+        let mut line = &text[start..end];
+        let id = match hash.get(line) {
+            Some(&id) => id,
+            None => {
+                // Past the limit, the rest of the text becomes one line
+                if lines.len() == max_lines {
+                    line = &text[start..];
+                    end = text.len();
+                }
+                let id = lines.len() as u32;
+                hash.insert(line, id);
+                lines.push(line);
+                id
+            }
+        };
+        ids.push(id);
+        start = end;
+    }
+    ids
+}
+
+fn common_prefix<T: Eq>(a: &[T], b: &[T]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+fn common_suffix<T: Eq>(a: &[T], b: &[T]) -> usize {
+    a.iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// Length of the longest suffix of `a` that is a prefix of `b`
+fn common_overlap(a: &[char], b: &[char]) -> usize {
+    if a.is_empty() || b.is_empty() {
+        return 0;
+    }
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[a.len() - len..], &b[..len]);
+    if a == b {
+        return len;
+    }
+    let mut best = 0;
+    let mut length = 1;
+    while length <= len {
+        let Some(found) = find(b, &a[len - length..], 0) else {
+            return best;
+        };
+        length += found;
+        if found == 0 || a[len - length..] == b[..length] {
+            best = length;
+            length += 1;
+        }
+    }
+    best
+}
+
+/// Index of the first occurrence of `needle` in `haystack` at or after `from`
+fn find<T: Eq>(haystack: &[T], needle: &[T], from: usize) -> Option<usize> {
+    if from > haystack.len() {
+        return None;
+    }
+    if needle.is_empty() {
+        return Some(from);
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| from + i)
+}
+
+/// Two texts split around a common middle at least half as long as the longer
+struct HalfMatch<T> {
+    a_prefix: Vec<T>,
+    a_suffix: Vec<T>,
+    b_prefix: Vec<T>,
+    b_suffix: Vec<T>,
+    common: Vec<T>,
+}
+
+fn half_match<T: Token>(a: &[T], b: &[T]) -> Option<HalfMatch<T>> {
+    let (long, short) = if a.len() > b.len() { (a, b) } else { (b, a) };
+    if long.len() < 4 || short.len() * 2 < long.len() {
+        return None;
+    }
+    let hm1 = half_match_at(long, short, long.len().div_ceil(4));
+    let hm2 = half_match_at(long, short, long.len().div_ceil(2));
+    let hm = match (hm1, hm2) {
+        (None, None) => return None,
+        (Some(hm), None) | (None, Some(hm)) => hm,
+        (Some(hm1), Some(hm2)) if hm1.common.len() > hm2.common.len() => hm1,
+        (Some(_), Some(hm2)) => hm2,
+    };
+    if a.len() > b.len() {
+        return Some(hm);
+    }
+    Some(HalfMatch {
+        a_prefix: hm.b_prefix,
+        a_suffix: hm.b_suffix,
+        b_prefix: hm.a_prefix,
+        b_suffix: hm.a_suffix,
+        common: hm.common,
+    })
+}
+
+/// A half match seeded by the quarter of `long` starting at `i`, prefixes of `long` first
+fn half_match_at<T: Token>(long: &[T], short: &[T], i: usize) -> Option<HalfMatch<T>> {
+    let seed = &long[i..i + long.len() / 4];
+    let mut best: Option<HalfMatch<T>> = None;
+    let mut best_len = 0;
+    let mut j = find(short, seed, 0);
+    while let Some(at) = j {
+        let prefix = common_prefix(&long[i..], &short[at..]);
+        let suffix = common_suffix(&long[..i], &short[..at]);
+        if best_len < suffix + prefix {
+            best_len = suffix + prefix;
+            best = Some(HalfMatch {
+                a_prefix: long[..i - suffix].to_vec(),
+                a_suffix: long[i + prefix..].to_vec(),
+                b_prefix: short[..at - suffix].to_vec(),
+                b_suffix: short[at + prefix..].to_vec(),
+                common: short[at - suffix..at + prefix].to_vec(),
+            });
+        }
+        j = find(short, seed, at + 1);
+    }
+    best.filter(|_| best_len * 2 >= long.len())
+}
+
+/// Merge adjacent edits of the same kind and factor out common affixes
+fn cleanup_merge<T: Token>(diffs: &mut Vec<Edit<T>>) {
+    diffs.push((DiffOp::Equal, Vec::new()));
+    let mut pointer = 0;
+    let (mut count_delete, mut count_insert) = (0, 0);
+    let (mut text_delete, mut text_insert): (Vec<T>, Vec<T>) = (Vec::new(), Vec::new());
+    while pointer < diffs.len() {
+        match diffs[pointer].0 {
+            DiffOp::Insert => {
+                count_insert += 1;
+                text_insert.extend_from_slice(&diffs[pointer].1);
+                pointer += 1;
+            }
+            DiffOp::Delete => {
+                count_delete += 1;
+                text_delete.extend_from_slice(&diffs[pointer].1);
+                pointer += 1;
+            }
+            DiffOp::Equal => {
+                if count_delete + count_insert > 1 {
+                    if count_delete != 0 && count_insert != 0 {
+                        let common = common_prefix(&text_insert, &text_delete);
+                        if common != 0 {
+                            let start = pointer - count_delete - count_insert;
+                            if start > 0 && diffs[start - 1].0 == DiffOp::Equal {
+                                diffs[start - 1].1.extend_from_slice(&text_insert[..common]);
+                            } else {
+                                diffs.insert(0, (DiffOp::Equal, text_insert[..common].to_vec()));
+                                pointer += 1;
+                            }
+                            text_insert.drain(..common);
+                            text_delete.drain(..common);
+                        }
+                        let common = common_suffix(&text_insert, &text_delete);
+                        if common != 0 {
+                            let tail = text_insert.split_off(text_insert.len() - common);
+                            diffs[pointer].1.splice(0..0, tail);
+                            text_delete.truncate(text_delete.len() - common);
+                        }
+                    }
+                    pointer -= count_delete + count_insert;
+                    diffs.drain(pointer..pointer + count_delete + count_insert);
+                    if !text_delete.is_empty() {
+                        diffs.insert(pointer, (DiffOp::Delete, std::mem::take(&mut text_delete)));
+                        pointer += 1;
+                    }
+                    if !text_insert.is_empty() {
+                        diffs.insert(pointer, (DiffOp::Insert, std::mem::take(&mut text_insert)));
+                        pointer += 1;
+                    }
+                    pointer += 1;
+                } else if pointer != 0 && diffs[pointer - 1].0 == DiffOp::Equal {
+                    let text = diffs.remove(pointer).1;
+                    diffs[pointer - 1].1.extend(text);
+                } else {
+                    pointer += 1;
+                }
+                count_delete = 0;
+                count_insert = 0;
+                text_delete.clear();
+                text_insert.clear();
+            }
+        }
+    }
+    if diffs.last().is_some_and(|(_, text)| text.is_empty()) {
+        diffs.pop();
+    }
+
+    // Shift single edits over a neighbouring equality to remove it,
+    // e.g. A<ins>BA</ins>C becomes <ins>AB</ins>AC
+    let mut changes = false;
+    let mut pointer = 1;
+    while pointer + 1 < diffs.len() {
+        if diffs[pointer - 1].0 == DiffOp::Equal && diffs[pointer + 1].0 == DiffOp::Equal {
+            let previous = diffs[pointer - 1].1.clone();
+            let next = diffs[pointer + 1].1.clone();
+            if diffs[pointer].1.ends_with(&previous) {
+                let edit = &mut diffs[pointer].1;
+                edit.truncate(edit.len() - previous.len());
+                edit.splice(0..0, previous.iter().copied());
+                diffs[pointer + 1].1.splice(0..0, previous);
+                diffs.remove(pointer - 1);
+                changes = true;
+            } else if diffs[pointer].1.starts_with(&next) {
+                diffs[pointer - 1].1.extend_from_slice(&next);
+                let edit = &mut diffs[pointer].1;
+                edit.drain(..next.len());
+                edit.extend(next);
+                diffs.remove(pointer + 1);
+                changes = true;
+            }
+        }
+        pointer += 1;
+    }
+    if changes {
+        cleanup_merge(diffs);
+    }
+}
+
+/// Replace equalities no longer than the edits around them with edits
+fn cleanup_semantic(diffs: &mut Vec<Edit<char>>) {
+    let mut changes = false;
+    let mut equalities: Vec<usize> = Vec::new();
+    let mut last_equality: Option<Vec<char>> = None;
+    let mut pointer: isize = 0;
+    let (mut insertions1, mut deletions1, mut insertions2, mut deletions2) = (0, 0, 0, 0);
+    while (pointer as usize) < diffs.len() {
+        let p = pointer as usize;
+        if diffs[p].0 == DiffOp::Equal {
+            equalities.push(p);
+            insertions1 = insertions2;
+            deletions1 = deletions2;
+            insertions2 = 0;
+            deletions2 = 0;
+            last_equality = Some(diffs[p].1.clone());
+        } else {
+            match diffs[p].0 {
+                DiffOp::Insert => insertions2 += diffs[p].1.len(),
+                _ => deletions2 += diffs[p].1.len(),
+            }
+            if let Some(equality) = last_equality.clone().filter(|e| {
+                !e.is_empty()
+                    && e.len() <= insertions1.max(deletions1)
+                    && e.len() <= insertions2.max(deletions2)
+            }) {
+                let at = *equalities.last().expect("an equality was seen");
+                diffs.insert(at, (DiffOp::Delete, equality));
+                diffs[at + 1].0 = DiffOp::Insert;
+                // Drop this equality and re-evaluate the one before it
+                equalities.pop();
+                equalities.pop();
+                pointer = equalities.last().map_or(-1, |&e| e as isize);
+                (insertions1, deletions1, insertions2, deletions2) = (0, 0, 0, 0);
+                last_equality = None;
+                changes = true;
+            }
+        }
+        pointer += 1;
+    }
+    if changes {
+        cleanup_merge(diffs);
+    }
+    cleanup_semantic_lossless(diffs);
+
+    // Turn overlaps between a deletion and the following insertion into equalities
+    let mut pointer = 1;
+    while pointer < diffs.len() {
+        if diffs[pointer - 1].0 == DiffOp::Delete && diffs[pointer].0 == DiffOp::Insert {
+            let deletion = diffs[pointer - 1].1.clone();
+            let insertion = diffs[pointer].1.clone();
+            let overlap1 = common_overlap(&deletion, &insertion);
+            let overlap2 = common_overlap(&insertion, &deletion);
+            if overlap1 >= overlap2 {
+                if overlap1 * 2 >= deletion.len() || overlap1 * 2 >= insertion.len() {
+                    diffs.insert(pointer, (DiffOp::Equal, insertion[..overlap1].to_vec()));
+                    diffs[pointer - 1].1 = deletion[..deletion.len() - overlap1].to_vec();
+                    diffs[pointer + 1].1 = insertion[overlap1..].to_vec();
+                    pointer += 1;
+                }
+            } else if overlap2 * 2 >= deletion.len() || overlap2 * 2 >= insertion.len() {
+                diffs.insert(pointer, (DiffOp::Equal, deletion[..overlap2].to_vec()));
+                diffs[pointer - 1] = (
+                    DiffOp::Insert,
+                    insertion[..insertion.len() - overlap2].to_vec(),
+                );
+                diffs[pointer + 1] = (DiffOp::Delete, deletion[overlap2..].to_vec());
+                pointer += 1;
+            }
+            pointer += 1;
+        }
+        pointer += 1;
+    }
+}
+
+/// How well a boundary between `one` and `two` falls on a logical break, 6 best
+fn semantic_score(one: &[char], two: &[char]) -> u8 {
+    let (Some(&c1), Some(&c2)) = (one.last(), two.first()) else {
+        return 6;
+    };
+    let non_alphanumeric1 = !c1.is_ascii_alphanumeric();
+    let non_alphanumeric2 = !c2.is_ascii_alphanumeric();
+    let whitespace1 = non_alphanumeric1 && c1.is_whitespace();
+    let whitespace2 = non_alphanumeric2 && c2.is_whitespace();
+    let line_break1 = whitespace1 && matches!(c1, '\r' | '\n');
+    let line_break2 = whitespace2 && matches!(c2, '\r' | '\n');
+    let blank_line1 =
+        line_break1 && (one.ends_with(&['\n', '\n']) || one.ends_with(&['\n', '\r', '\n']));
+    let blank_line2 = line_break2 && {
+        let two = two.strip_prefix(&['\r']).unwrap_or(two);
+        two.strip_prefix(&['\n'])
+            .is_some_and(|rest| rest.starts_with(&['\n']) || rest.starts_with(&['\r', '\n']))
+    };
+    if blank_line1 || blank_line2 {
+        5
+    } else if line_break1 || line_break2 {
+        4
+    } else if non_alphanumeric1 && !whitespace1 && whitespace2 {
+        3
+    } else if whitespace1 || whitespace2 {
+        2
+    } else if non_alphanumeric1 || non_alphanumeric2 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Slide single edits between equalities to fall on word or line boundaries
+fn cleanup_semantic_lossless(diffs: &mut Vec<Edit<char>>) {
+    let mut pointer = 1;
+    while pointer + 1 < diffs.len() {
+        if diffs[pointer - 1].0 == DiffOp::Equal && diffs[pointer + 1].0 == DiffOp::Equal {
+            // Lay out equality, edit and equality as one text, with the edit
+            // shifted as far left as possible
+            let shift = common_suffix(&diffs[pointer - 1].1, &diffs[pointer].1);
+            let mut start = diffs[pointer - 1].1.len() - shift;
+            let mut end = start + diffs[pointer].1.len();
+            let text: Vec<char> = diffs[pointer - 1..=pointer + 1]
+                .iter()
+                .flat_map(|(_, text)| text)
+                .copied()
+                .collect();
+            let score = |start: usize, end: usize| {
+                semantic_score(&text[..start], &text[start..end])
+                    + semantic_score(&text[start..end], &text[end..])
+            };
+            let mut best = (start, end);
+            let mut best_score = score(start, end);
+            // Step right one character at a time, looking for the best fit
+            while end < text.len() && text[start] == text[end] {
+                start += 1;
+                end += 1;
+                let score = score(start, end);
+                // >= prefers trailing rather than leading whitespace on edits
+                if score >= best_score {
+                    best_score = score;
+                    best = (start, end);
+                }
+            }
+            if diffs[pointer - 1].1[..] != text[..best.0] {
+                let (start, end) = best;
+                diffs[pointer].1 = text[start..end].to_vec();
+                if end < text.len() {
+                    diffs[pointer + 1].1 = text[end..].to_vec();
+                } else {
+                    diffs.remove(pointer + 1);
+                    pointer = pointer.saturating_sub(1);
+                }
+                if start > 0 {
+                    diffs[pointer - 1].1 = text[..start].to_vec();
+                } else {
+                    diffs.remove(pointer - 1);
+                    pointer -= 1;
+                }
+            }
+        }
+        pointer += 1;
+    }
+}
+
+/// Replace short equalities between edits with edits, when that is cheaper
+fn cleanup_efficiency(diffs: &mut Vec<Edit<char>>) {
+    let mut changes = false;
+    let mut equalities: Vec<usize> = Vec::new();
+    let mut last_equality: Option<Vec<char>> = None;
+    let mut pointer: isize = 0;
+    let (mut pre_ins, mut pre_del, mut post_ins, mut post_del) = (false, false, false, false);
+    while (pointer as usize) < diffs.len() {
+        let p = pointer as usize;
+        if diffs[p].0 == DiffOp::Equal {
+            if diffs[p].1.len() < DIFF_EDIT_COST && (post_ins || post_del) {
+                equalities.push(p);
+                pre_ins = post_ins;
+                pre_del = post_del;
+                last_equality = Some(diffs[p].1.clone());
+            } else {
+                equalities.clear();
+                last_equality = None;
+            }
+            post_ins = false;
+            post_del = false;
+        } else {
+            match diffs[p].0 {
+                DiffOp::Delete => post_del = true,
+                _ => post_ins = true,
+            }
+            let surrounded = [pre_ins, pre_del, post_ins, post_del]
+                .iter()
+                .filter(|&&edit| edit)
+                .count();
+            if let Some(equality) = last_equality.clone().filter(|e| {
+                !e.is_empty()
+                    && (surrounded == 4 || (e.len() * 2 < DIFF_EDIT_COST && surrounded == 3))
+            }) {
+                let at = *equalities.last().expect("an equality was seen");
+                diffs.insert(at, (DiffOp::Delete, equality));
+                diffs[at + 1].0 = DiffOp::Insert;
+                equalities.pop();
+                last_equality = None;
+                if pre_ins && pre_del {
+                    // No changes made which could affect previous entries
+                    post_ins = true;
+                    post_del = true;
+                    equalities.clear();
+                } else {
+                    equalities.pop();
+                    pointer = equalities.last().map_or(-1, |&e| e as isize);
+                    post_ins = false;
+                    post_del = false;
+                }
+                changes = true;
+            }
+        }
+        pointer += 1;
+    }
+    if changes {
+        cleanup_merge(diffs);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Patch {
+    diffs: Vec<Diff>,
+    start1: usize,
+    start2: usize,
+    length1: usize,
+    length2: usize,
+}
+
+fn find_all(text: &[char], pattern: &[char]) -> usize {
+    if pattern.is_empty() {
+        return text.len() + 1;
+    }
+    text.windows(pattern.len())
+        .filter(|w| *w == pattern)
+        .count()
+}
+
+fn add_context(patch: &mut Patch, text: &[char]) {
+    if text.is_empty() {
+        return;
+    }
+    let end = |padding: usize| (patch.start2 + patch.length1 + padding).min(text.len());
+    let begin = |padding: usize| patch.start2.saturating_sub(padding);
+    let mut padding = 0;
+    let mut pattern = &text[patch.start2.min(text.len())..end(0)];
+    while find_all(text, pattern) > 1 && pattern.len() < MATCH_MAX_BITS - 2 * PATCH_MARGIN {
+        padding += PATCH_MARGIN;
+        pattern = &text[begin(padding)..end(padding)];
+    }
+    padding += PATCH_MARGIN;
+    let prefix = text[begin(padding)..patch.start2.min(text.len())].to_vec();
+    let suffix = text[end(0)..end(padding)].to_vec();
+    if !prefix.is_empty() {
+        patch.diffs.insert(
+            0,
+            Diff {
+                op: DiffOp::Equal,
+                text: prefix.clone(),
+            },
+        );
+    }
+    if !suffix.is_empty() {
+        patch.diffs.push(Diff {
+            op: DiffOp::Equal,
+            text: suffix.clone(),
+        });
+    }
+    patch.start1 -= prefix.len();
+    patch.start2 -= prefix.len();
+    patch.length1 += prefix.len() + suffix.len();
+    patch.length2 += prefix.len() + suffix.len();
+}
+
+fn make_patches(old: &[char], diffs: &[Diff]) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    let mut patch = Patch::default();
+    let mut prepatch: Vec<char> = old.to_vec();
+    let mut postpatch = prepatch.clone();
+    let (mut count1, mut count2) = (0, 0);
+    for (i, d) in diffs.iter().enumerate() {
+        let len = d.text.len();
+        if patch.diffs.is_empty() && d.op != DiffOp::Equal {
+            patch.start1 = count1;
+            patch.start2 = count2;
+        }
+        match d.op {
+            DiffOp::Insert => {
+                patch.diffs.push(d.clone());
+                patch.length2 += len;
+                postpatch.splice(count2..count2, d.text.iter().copied());
+            }
+            DiffOp::Delete => {
+                patch.length1 += len;
+                patch.diffs.push(d.clone());
+                postpatch.drain(count2..count2 + len);
+            }
+            DiffOp::Equal => {
+                // Small equality inside a patch
+                if len <= 2 * PATCH_MARGIN && !patch.diffs.is_empty() && i + 1 != diffs.len() {
+                    patch.diffs.push(d.clone());
+                    patch.length1 += len;
+                    patch.length2 += len;
+                }
+                // Time for a new patch
+                if len >= 2 * PATCH_MARGIN && !patch.diffs.is_empty() {
+                    add_context(&mut patch, &prepatch);
+                    patches.push(std::mem::take(&mut patch));
+                    prepatch = postpatch.clone();
+                    count1 = count2;
+                }
+            }
+        }
+        if d.op != DiffOp::Insert {
+            count1 += len;
+        }
+        if d.op != DiffOp::Delete {
+            count2 += len;
+        }
+    }
+    if !patch.diffs.is_empty() {
+        add_context(&mut patch, &prepatch);
+        patches.push(patch);
+    }
+    patches
+}
+
+/// Percent-encode like JavaScript's `encodeURI`, keeping spaces literal
+fn encode_uri(text: &[char]) -> String {
+    const UNRESERVED: &str = ";,/?:@&=+$-_.!~*'()#";
+    let mut out = String::new();
+    for &c in text {
+        if c.is_ascii_alphanumeric() || c == ' ' || UNRESERVED.contains(c) {
+            out.push(c);
+        } else {
+            let mut buf = [0u8; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    out
+}
+
+fn coords(start: usize, length: usize) -> String {
+    match length {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{length}", start + 1),
+    }
+}
+
+/// Build diff-match-patch patch text turning `old` into `new`
+///
+/// Returns `None` when diffing takes longer than [`DIFF_TIMEOUT`].
+pub fn patch_text(old: &str, new: &str) -> Option<String> {
+    let a: Vec<char> = old.chars().collect();
+    let b: Vec<char> = new.chars().collect();
+    let mut differ = Differ::new(DIFF_TIMEOUT);
+    let mut diffs = differ.main(&a, &b, true);
+    if differ.timed_out {
+        return None;
+    }
+    if diffs.len() > 2 {
+        cleanup_semantic(&mut diffs);
+        cleanup_efficiency(&mut diffs);
+    }
+    let mut out = String::new();
+    for patch in make_patches(&a, &to_diffs(diffs)) {
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            coords(patch.start1, patch.length1),
+            coords(patch.start2, patch.length2)
+        ));
+        for d in &patch.diffs {
+            out.push(match d.op {
+                DiffOp::Equal => ' ',
+                DiffOp::Insert => '+',
+                DiffOp::Delete => '-',
+            });
+            out.push_str(&encode_uri(&d.text));
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
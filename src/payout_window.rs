@@ -0,0 +1,120 @@
+//! Posts entering the final hours before payout
+//!
+//! [`PayoutWindowScanner`] walks `list_comments` by cashout time and reports
+//! each post once as it comes within the configured window of its payout,
+//! for last-minute curation and anti-abuse tools.
+
+use crate::comments::{ChainComment, CommentOrder, CommentStart, LIST_COMMENTS_LIMIT};
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::Client;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+/// Reports posts once they are within a window of their payout
+#[derive(Debug, Clone)]
+pub struct PayoutWindowScanner {
+    window_secs: i64,
+    include_comments: bool,
+    interval: Duration,
+    /// Reported comments and their cashout times, dropped once paid out
+    seen: HashMap<(String, String), i64>,
+}
+
+impl PayoutWindowScanner {
+    /// Report top-level posts paying out within `window_secs`, checking every minute
+    pub fn new(window_secs: i64) -> Self {
+        Self {
+            window_secs,
+            include_comments: false,
+            interval: Duration::from_secs(60),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Report replies as well as top-level posts
+    pub fn include_comments(mut self) -> Self {
+        self.include_comments = true;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Keep the comments newly within the window at chain time `now`
+    ///
+    /// Comments already reported are skipped; ones paid out by `now` are forgotten.
+    pub fn process<I>(&mut self, now: i64, comments: I) -> Vec<ChainComment>
+    where
+        I: IntoIterator<Item = ChainComment>,
+    {
+        self.seen.retain(|_, cashout| *cashout > now);
+        let mut entered = Vec::new();
+        for comment in comments {
+            if !self.include_comments && !comment.is_post() {
+                continue;
+            }
+            let Ok(cashout) = parse_timestamp(&comment.cashout_time) else {
+                continue;
+            };
+            if cashout <= now || cashout > now + self.window_secs {
+                continue;
+            }
+            let key = (comment.author.clone(), comment.permlink.clone());
+            if self.seen.insert(key, cashout).is_none() {
+                entered.push(comment);
+            }
+        }
+        entered
+    }
+
+    /// Scan once, returning the posts that entered the window since the last check
+    pub fn check(&mut self, client: &Client) -> Result<Vec<ChainComment>, String> {
+        let now = parse_timestamp(&client.get_dynamic_global_properties()?.time)?;
+        let end = now + self.window_secs;
+        let mut start = CommentStart::cashout_from(&format_timestamp(now + 1));
+        let mut in_window: Vec<ChainComment> = Vec::new();
+        loop {
+            let mut page = client.list_comments(&start, LIST_COMMENTS_LIMIT)?;
+            let full_page = page.len() as u32 >= LIST_COMMENTS_LIMIT;
+            // Pages after the first start with the last comment of the previous one
+            if let (Some(last), Some(first)) = (in_window.last(), page.first()) {
+                if (&last.author, &last.permlink) == (&first.author, &first.permlink) {
+                    page.remove(0);
+                }
+            }
+            let mut past_end = false;
+            for comment in page {
+                if parse_timestamp(&comment.cashout_time).is_ok_and(|t| t > end) {
+                    past_end = true;
+                    break;
+                }
+                in_window.push(comment);
+            }
+            match in_window.last() {
+                Some(last) if full_page && !past_end => {
+                    start = last.start_key(CommentOrder::ByCashoutTime);
+                }
+                _ => break,
+            }
+        }
+        Ok(self.process(now, in_window))
+    }
+
+    /// Check every interval until `on_entered` returns `false`
+    pub fn watch<F>(&mut self, client: &Client, mut on_entered: F) -> Result<(), String>
+    where
+        F: FnMut(ChainComment) -> bool,
+    {
+        loop {
+            for comment in self.check(client)? {
+                if !on_entered(comment) {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Pending payout aggregation for an account's posts and comments
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::posts::{AccountPostSort, Post, PostQuery, BRIDGE_POSTS_LIMIT};
+use crate::timestamp::parse_timestamp;
+use crate::Client;
+
+/// Length of the Hive payout window in seconds
+pub const PAYOUT_WINDOW_SECS: i64 = 7 * 86_400;
+
+/// Share of a post's rewards that goes to curators, in basis points
+pub const CURATION_REWARD_PERCENT: u64 = 5_000;
+
+/// Pending rewards for a single post or comment
+#[derive(Debug, Clone)]
+pub struct PendingPayout {
+    pub author: String,
+    pub permlink: String,
+    pub title: String,
+    pub depth: u32,
+    pub created: String,
+    pub payout_at: String,
+    pub pending_payout_value: Asset,
+    pub curator_share: Asset,
+    pub beneficiary_share: Asset,
+    pub author_share: Asset,
+}
+
+impl PendingPayout {
+    /// Split a post's pending payout between curators, beneficiaries and the author
+    pub fn from_post(post: &Post) -> Self {
+        let pending = post.pending_payout_value;
+        let curator = pending.amount * CURATION_REWARD_PERCENT as i64 / 10_000;
+        let remaining = pending.amount - curator;
+        let beneficiary_weight: i64 = post.beneficiaries.iter().map(|b| i64::from(b.weight)).sum();
+        let beneficiary = remaining * beneficiary_weight.min(10_000) / 10_000;
+        Self {
+            author: post.author.clone(),
+            permlink: post.permlink.clone(),
+            title: post.title.clone(),
+            depth: post.depth,
+            created: post.created.clone(),
+            payout_at: post.payout_at.clone(),
+            pending_payout_value: pending,
+            curator_share: Asset::new(curator, pending.symbol),
+            beneficiary_share: Asset::new(beneficiary, pending.symbol),
+            author_share: Asset::new(remaining - beneficiary, pending.symbol),
+        }
+    }
+}
+
+/// Pending rewards for every active post and comment of an account
+#[derive(Debug, Clone)]
+pub struct PendingPayouts {
+    pub account: String,
+    pub items: Vec<PendingPayout>,
+    pub total_pending: Asset,
+    pub total_author: Asset,
+}
+
+impl Client {
+    /// Sum the pending author rewards of an account's posts and comments still in the payout window
+    pub fn pending_payouts(&self, account: &str) -> Result<PendingPayouts, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let cutoff = parse_timestamp(&props.time)? - PAYOUT_WINDOW_SECS;
+        let mut items = Vec::new();
+        for sort in [AccountPostSort::Posts, AccountPostSort::Comments] {
+            for post in self.active_account_posts(account, sort, cutoff)? {
+                items.push(PendingPayout::from_post(&post));
+            }
+        }
+        let total = |f: fn(&PendingPayout) -> Asset| {
+            Asset::new(
+                items.iter().map(|item| f(item).amount).sum(),
+                AssetSymbol::Hbd,
+            )
+        };
+        Ok(PendingPayouts {
+            account: account.to_string(),
+            total_pending: total(|item| item.pending_payout_value),
+            total_author: total(|item| item.author_share),
+            items,
+        })
+    }
+
+    fn active_account_posts(
+        &self,
+        account: &str,
+        sort: AccountPostSort,
+        cutoff: i64,
+    ) -> Result<Vec<Post>, String> {
+        let mut active = Vec::new();
+        let mut query = PostQuery::new().author(account).account_sort(sort);
+        loop {
+            let page = self.query_posts(&query)?;
+            let page_len = page.len();
+            let mut reached_cutoff = false;
+            let mut last = None;
+            for post in page {
+                if last.is_none() && query_start_matches(&query, &post) {
+                    continue;
+                }
+                last = Some((post.author.clone(), post.permlink.clone()));
+                if parse_timestamp(&post.created)? < cutoff {
+                    reached_cutoff = true;
+                } else if !post.is_paidout {
+                    active.push(post);
+                }
+            }
+            match last {
+                Some((author, permlink))
+                    if !reached_cutoff && page_len >= BRIDGE_POSTS_LIMIT as usize =>
+                {
+                    query = query.start(&author, &permlink);
+                }
+                _ => return Ok(active),
+            }
+        }
+    }
+}
+
+fn query_start_matches(query: &PostQuery, post: &Post) -> bool {
+    query
+        .start_post()
+        .is_some_and(|(author, permlink)| author == post.author && permlink == post.permlink)
+}
@@ -0,0 +1,138 @@
+//! Permlink generation and validation following condenser's slug rules
+
+use crate::timestamp::format_timestamp;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum permlink length accepted by the chain, which requires fewer than 256
+pub const MAX_PERMLINK_LENGTH: usize = 255;
+
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ł' | 'ľ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' | 'э' => "e",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'й' => "j",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "h",
+        'ц' => "c",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "sch",
+        'ы' => "y",
+        'ю' => "yu",
+        'я' => "ya",
+        'ъ' | 'ь' => "",
+        '&' => "and",
+        _ => return None,
+    })
+}
+
+/// Lowercase, transliterate and hyphenate a title into a slug
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if let Some(latin) = transliterate(c) {
+            slug.push_str(latin);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Timestamp suffix in condenser's format, e.g. `20240101t120000123z`
+pub fn permlink_suffix() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let stamp = format_timestamp(now.as_secs() as i64);
+    format!(
+        "{}{:03}z",
+        stamp.replace(['-', ':'], "").to_lowercase(),
+        now.subsec_millis()
+    )
+}
+
+fn with_suffix(base: &str, suffix: &str) -> String {
+    let max_base = MAX_PERMLINK_LENGTH - suffix.len() - 1;
+    let base: String = base.chars().take(max_base).collect();
+    let base = base.trim_end_matches('-');
+    if base.is_empty() {
+        suffix.to_string()
+    } else {
+        format!("{base}-{suffix}")
+    }
+}
+
+/// Generate a permlink for a top-level post from its title
+///
+/// A timestamp suffix keeps permlinks unique when titles repeat.
+pub fn generate_permlink(title: &str) -> String {
+    with_suffix(&slugify(title), &permlink_suffix())
+}
+
+/// Generate a permlink for a reply, using condenser's `re-` prefix
+pub fn generate_reply_permlink(parent_author: &str, parent_permlink: &str) -> String {
+    let base = format!("re-{}-{}", slugify(parent_author), slugify(parent_permlink));
+    with_suffix(&base, &permlink_suffix())
+}
+
+/// Check that a permlink only uses characters and a length the chain accepts
+pub fn validate_permlink(permlink: &str) -> Result<(), String> {
+    if permlink.is_empty() {
+        return Err("Permlink is empty".to_string());
+    }
+    if permlink.len() > MAX_PERMLINK_LENGTH {
+        return Err(format!(
+            "Permlink is {} characters, above the limit of {MAX_PERMLINK_LENGTH}",
+            permlink.len()
+        ));
+    }
+    if let Some(c) = permlink
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-'))
+    {
+        return Err(format!(
+            "Permlink '{permlink}' contains invalid character '{c}'"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+//! The final reward split of a paid-out post
+//!
+//! Once a post cashes out its pending payout is gone from the API, and only
+//! the `author_reward`, `comment_benefactor_reward`, `curation_reward` and
+//! `comment_reward` virtual operations of the payout block remain.
+//! [`PostRewards`] adds them back up.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::history::HistoryEntry;
+use crate::op_filter::OpTypeSet;
+use crate::operation::Operation;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+
+/// `cashout_time` of comments that have been paid out
+const PAID_OUT: &str = "1969-12-31T23:59:59";
+
+/// HBD, HIVE and VESTS paid to one account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardSplit {
+    pub hbd: Asset,
+    pub hive: Asset,
+    pub vests: Asset,
+}
+
+impl Default for RewardSplit {
+    fn default() -> Self {
+        Self {
+            hbd: Asset::zero_hbd(),
+            hive: Asset::zero_hive(),
+            vests: Asset::zero_vests(),
+        }
+    }
+}
+
+impl RewardSplit {
+    fn add(&mut self, op: &Operation) -> Result<(), String> {
+        let field = |name: &str| Asset::from_value(&op.value[name]);
+        self.hbd = self
+            .hbd
+            .checked_add(field("hbd_payout")?)
+            .unwrap_or(self.hbd);
+        self.hive = self
+            .hive
+            .checked_add(field("hive_payout")?)
+            .unwrap_or(self.hive);
+        self.vests = self
+            .vests
+            .checked_add(field("vesting_payout")?)
+            .unwrap_or(self.vests);
+        Ok(())
+    }
+}
+
+/// How a post's payout was divided, reconstructed from virtual operations
+///
+/// VESTS amounts are as paid; convert them with
+/// [`DynamicGlobalProperties::vests_to_hp`](crate::block::DynamicGlobalProperties::vests_to_hp).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostRewards {
+    pub author: String,
+    pub permlink: String,
+    /// Block the payout happened in
+    pub block: u32,
+    pub timestamp: String,
+    pub author_reward: RewardSplit,
+    /// Each beneficiary's share, in the order they were paid
+    pub beneficiaries: Vec<(String, RewardSplit)>,
+    /// VESTS paid to all curators together
+    pub curators_vests: Asset,
+    pub curators: u32,
+    /// HBD value of the whole payout, from `comment_reward`
+    pub total_payout_value: Option<Asset>,
+}
+
+impl PostRewards {
+    /// Add up the reward operations for `author`/`permlink` among `entries`
+    ///
+    /// Returns `None` if none of them pay out this post.
+    pub fn from_entries(
+        author: &str,
+        permlink: &str,
+        entries: &[HistoryEntry],
+    ) -> Result<Option<Self>, String> {
+        let mut rewards = Self {
+            author: author.to_string(),
+            permlink: permlink.to_string(),
+            block: 0,
+            timestamp: String::new(),
+            author_reward: RewardSplit::default(),
+            beneficiaries: Vec::new(),
+            curators_vests: Asset::zero_vests(),
+            curators: 0,
+            total_payout_value: None,
+        };
+        let mut found = false;
+        for entry in entries {
+            let op = &entry.op;
+            let is_post = |author_field: &str, permlink_field: &str| {
+                op.str_field(author_field) == Some(author)
+                    && op.str_field(permlink_field) == Some(permlink)
+            };
+            match op.name() {
+                "author_reward" if is_post("author", "permlink") => {
+                    rewards.author_reward.add(op)?;
+                }
+                "comment_benefactor_reward" if is_post("author", "permlink") => {
+                    let benefactor = op.str_field("benefactor").unwrap_or_default();
+                    let index = match rewards
+                        .beneficiaries
+                        .iter()
+                        .position(|(account, _)| account == benefactor)
+                    {
+                        Some(index) => index,
+                        None => {
+                            rewards
+                                .beneficiaries
+                                .push((benefactor.to_string(), RewardSplit::default()));
+                            rewards.beneficiaries.len() - 1
+                        }
+                    };
+                    rewards.beneficiaries[index].1.add(op)?;
+                }
+                "curation_reward" if is_post("comment_author", "comment_permlink") => {
+                    let reward = Asset::from_value(&op.value["reward"])?;
+                    rewards.curators_vests = rewards
+                        .curators_vests
+                        .checked_add(reward)
+                        .unwrap_or(rewards.curators_vests);
+                    rewards.curators += 1;
+                }
+                "comment_reward" if is_post("author", "permlink") => {
+                    rewards.total_payout_value =
+                        Some(Asset::from_value(&op.value["total_payout_value"])?);
+                }
+                _ => continue,
+            }
+            found = true;
+            rewards.block = entry.block;
+            rewards.timestamp.clone_from(&entry.timestamp);
+        }
+        Ok(found.then_some(rewards))
+    }
+
+    /// HBD paid to the author and beneficiaries, excluding vested rewards
+    pub fn liquid_hbd(&self) -> Asset {
+        let amount = self.author_reward.hbd.amount
+            + self
+                .beneficiaries
+                .iter()
+                .map(|(_, split)| split.hbd.amount)
+                .sum::<i64>();
+        Asset::new(amount, AssetSymbol::Hbd)
+    }
+}
+
+impl Client {
+    /// The final reward split of a post that has been paid out
+    ///
+    /// Finds the payout in the author's history, then reads every reward
+    /// operation of that block.
+    pub fn post_rewards(&self, author: &str, permlink: &str) -> Result<PostRewards, String> {
+        let comment = self
+            .find_comments(&[(author, permlink)])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Comment @{author}/{permlink} not found"))?;
+        if comment.cashout_time != PAID_OUT {
+            return Err(format!(
+                "@{author}/{permlink} pays out at {}",
+                comment.cashout_time
+            ));
+        }
+        let filter = OpTypeSet::from_names(&["author_reward", "comment_reward"])?;
+        let mut payout_block = None;
+        for entry in self.account_history(author).filter(filter) {
+            let entry = entry?;
+            if entry.timestamp < comment.created {
+                break;
+            }
+            if entry.op.str_field("permlink") == Some(permlink) {
+                payout_block = Some(entry.block);
+                break;
+            }
+        }
+        let block =
+            payout_block.ok_or_else(|| format!("No payout found for @{author}/{permlink}"))?;
+        let ops = self.get_ops_in_block(block, true)?;
+        PostRewards::from_entries(author, permlink, &ops)?
+            .ok_or_else(|| format!("Block {block} has no rewards for @{author}/{permlink}"))
+    }
+}
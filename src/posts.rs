@@ -0,0 +1,242 @@
+//! Post discovery through hivemind's `bridge` API
+
+use crate::asset::Asset;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Maximum number of posts the `bridge` API returns per call
+pub const BRIDGE_POSTS_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Beneficiary {
+    pub account: String,
+    pub weight: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Post {
+    pub post_id: u64,
+    pub author: String,
+    pub permlink: String,
+    pub category: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub json_metadata: Value,
+    pub created: String,
+    #[serde(default)]
+    pub updated: String,
+    pub depth: u32,
+    pub children: u32,
+    #[serde(default)]
+    pub net_rshares: i64,
+    pub is_paidout: bool,
+    pub payout_at: String,
+    pub payout: f64,
+    pub pending_payout_value: Asset,
+    pub author_payout_value: Asset,
+    pub curator_payout_value: Asset,
+    #[serde(default)]
+    pub promoted: Option<Asset>,
+    #[serde(default)]
+    pub beneficiaries: Vec<Beneficiary>,
+    #[serde(default)]
+    pub author_reputation: f64,
+    #[serde(default)]
+    pub url: String,
+}
+
+impl Post {
+    /// Tags from the post metadata, falling back to the category
+    pub fn tags(&self) -> Vec<String> {
+        let tags: Vec<String> = self
+            .json_metadata
+            .get("tags")
+            .and_then(|t| t.as_array())
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if tags.is_empty() {
+            vec![self.category.clone()]
+        } else {
+            tags
+        }
+    }
+}
+
+/// Ranking used by `bridge.get_ranked_posts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PostSort {
+    #[default]
+    Trending,
+    Hot,
+    Created,
+    Promoted,
+    Payout,
+    PayoutComments,
+    Muted,
+}
+
+impl PostSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PostSort::Trending => "trending",
+            PostSort::Hot => "hot",
+            PostSort::Created => "created",
+            PostSort::Promoted => "promoted",
+            PostSort::Payout => "payout",
+            PostSort::PayoutComments => "payout_comments",
+            PostSort::Muted => "muted",
+        }
+    }
+}
+
+/// Listing used by `bridge.get_account_posts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccountPostSort {
+    Blog,
+    Feed,
+    #[default]
+    Posts,
+    Comments,
+    Replies,
+    Payout,
+}
+
+impl AccountPostSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountPostSort::Blog => "blog",
+            AccountPostSort::Feed => "feed",
+            AccountPostSort::Posts => "posts",
+            AccountPostSort::Comments => "comments",
+            AccountPostSort::Replies => "replies",
+            AccountPostSort::Payout => "payout",
+        }
+    }
+}
+
+/// Builder for post discovery queries
+///
+/// Queries with an author are served by `bridge.get_account_posts` and filtered
+/// by tag locally; all others use `bridge.get_ranked_posts`.
+#[derive(Debug, Clone, Default)]
+pub struct PostQuery {
+    sort: PostSort,
+    account_sort: AccountPostSort,
+    tag: Option<String>,
+    author: Option<String>,
+    observer: Option<String>,
+    limit: Option<u32>,
+    start: Option<(String, String)>,
+}
+
+impl PostQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sort(mut self, sort: PostSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Listing to use when an author filter is set
+    pub fn account_sort(mut self, sort: AccountPostSort) -> Self {
+        self.account_sort = sort;
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    /// Account whose mutes and blacklists should be applied to the results
+    pub fn observer(mut self, observer: &str) -> Self {
+        self.observer = Some(observer.to_string());
+        self
+    }
+
+    /// Number of posts per page, capped at [`BRIDGE_POSTS_LIMIT`]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit.min(BRIDGE_POSTS_LIMIT));
+        self
+    }
+
+    /// Continue a previous query after the given post
+    pub fn start(mut self, author: &str, permlink: &str) -> Self {
+        self.start = Some((author.to_string(), permlink.to_string()));
+        self
+    }
+
+    /// The post this query continues after, if any
+    pub fn start_post(&self) -> Option<(&str, &str)> {
+        self.start
+            .as_ref()
+            .map(|(author, permlink)| (author.as_str(), permlink.as_str()))
+    }
+
+    fn params(&self) -> (&'static str, Value) {
+        let mut params = serde_json::Map::new();
+        let method = match &self.author {
+            Some(author) => {
+                params.insert("sort".into(), self.account_sort.as_str().into());
+                params.insert("account".into(), author.as_str().into());
+                "bridge.get_account_posts"
+            }
+            None => {
+                params.insert("sort".into(), self.sort.as_str().into());
+                params.insert("tag".into(), self.tag.as_deref().unwrap_or_default().into());
+                "bridge.get_ranked_posts"
+            }
+        };
+        params.insert(
+            "limit".into(),
+            self.limit.unwrap_or(BRIDGE_POSTS_LIMIT).into(),
+        );
+        if let Some(observer) = &self.observer {
+            params.insert("observer".into(), observer.as_str().into());
+        }
+        if let Some((author, permlink)) = &self.start {
+            params.insert("start_author".into(), author.as_str().into());
+            params.insert("start_permlink".into(), permlink.as_str().into());
+        }
+        (method, Value::Object(params))
+    }
+}
+
+impl Client {
+    /// Run a post discovery query, returning a single page of results
+    pub fn query_posts(&self, query: &PostQuery) -> Result<Vec<Post>, String> {
+        let (method, params) = query.params();
+        let posts: Vec<Post> = self
+            .call(method, params)
+            .map_err(|e| format!("Error querying posts: {e}"))?;
+        Ok(match (&query.author, &query.tag) {
+            (Some(_), Some(tag)) => posts
+                .into_iter()
+                .filter(|post| post.tags().iter().any(|t| t == tag))
+                .collect(),
+            _ => posts,
+        })
+    }
+
+    /// Fetch a single post or comment from hivemind
+    pub fn get_post(&self, author: &str, permlink: &str) -> Result<Option<Post>, String> {
+        self.call(
+            "bridge.get_post",
+            serde_json::json!({ "author": author, "permlink": permlink }),
+        )
+        .map_err(|e| format!("Error fetching post @{author}/{permlink}: {e}"))
+    }
+}
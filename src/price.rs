@@ -0,0 +1,222 @@
+//! HIVE price oracle aggregating the internal market and external sources
+
+use crate::Client;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A source of the HIVE price in HBD (or USD)
+pub trait PriceSource {
+    /// Short name used in reports
+    fn name(&self) -> &str;
+
+    /// Fetch the current price of one HIVE
+    fn fetch_price(&self, client: &Client) -> Result<f64, String>;
+}
+
+/// Latest trade price on the internal HIVE/HBD market
+#[derive(Debug, Clone, Default)]
+pub struct InternalMarket;
+
+#[derive(Debug, Deserialize)]
+struct TickerResponse {
+    latest: String,
+}
+
+impl PriceSource for InternalMarket {
+    fn name(&self) -> &str {
+        "internal_market"
+    }
+
+    fn fetch_price(&self, client: &Client) -> Result<f64, String> {
+        let ticker: TickerResponse = client
+            .call("market_history_api.get_ticker", ())
+            .map_err(|e| format!("Error fetching ticker: {e}"))?;
+        ticker
+            .latest
+            .parse()
+            .map_err(|e| format!("Invalid ticker price '{}': {e}", ticker.latest))
+    }
+}
+
+/// An external HTTP endpoint returning JSON, with the price at a JSON pointer
+///
+/// For example CoinGecko: `https://api.coingecko.com/api/v3/simple/price?ids=hive&vs_currencies=usd`
+/// with pointer `/hive/usd`.
+#[derive(Debug, Clone)]
+pub struct JsonUrlSource {
+    pub name: String,
+    pub url: String,
+    pub pointer: String,
+    /// Multiplier applied to the extracted value, e.g. to invert a quote
+    pub scale: f64,
+}
+
+impl JsonUrlSource {
+    pub fn new(name: &str, url: &str, pointer: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            pointer: pointer.to_string(),
+            scale: 1.0,
+        }
+    }
+
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+impl PriceSource for JsonUrlSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch_price(&self, client: &Client) -> Result<f64, String> {
+        let body = client
+            .transport_ref()
+            .get(&self.url)
+            .map_err(|e| e.to_string())?;
+        let body: Value =
+            serde_json::from_slice(&body).map_err(|e| format!("Decode error: {e}"))?;
+        let value = body
+            .pointer(&self.pointer)
+            .ok_or_else(|| format!("No value at '{}' in {} response", self.pointer, self.name))?;
+        let price = match value {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Invalid price from {}: {value}", self.name))?;
+        Ok(price * self.scale)
+    }
+}
+
+/// How individual source prices are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Median,
+    /// Mean after dropping this fraction of prices from each end
+    TrimmedMean(f64),
+}
+
+/// Result of querying every configured source
+#[derive(Debug, Clone)]
+pub struct PriceReport {
+    pub price: f64,
+    pub quotes: Vec<(String, f64)>,
+    pub errors: Vec<(String, String)>,
+}
+
+/// Aggregates prices from several sources
+pub struct PriceOracle {
+    sources: Vec<Box<dyn PriceSource>>,
+    aggregation: Aggregation,
+    min_sources: usize,
+}
+
+impl std::fmt::Debug for PriceOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriceOracle")
+            .field(
+                "sources",
+                &self.sources.iter().map(|s| s.name()).collect::<Vec<_>>(),
+            )
+            .field("aggregation", &self.aggregation)
+            .field("min_sources", &self.min_sources)
+            .finish()
+    }
+}
+
+impl Default for PriceOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceOracle {
+    /// An oracle using only the internal market, aggregated by median
+    pub fn new() -> Self {
+        Self {
+            sources: vec![Box::new(InternalMarket)],
+            aggregation: Aggregation::Median,
+            min_sources: 1,
+        }
+    }
+
+    pub fn source<S: PriceSource + 'static>(mut self, source: S) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    pub fn aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Fail unless at least this many sources return a price
+    pub fn min_sources(mut self, min: usize) -> Self {
+        self.min_sources = min.max(1);
+        self
+    }
+
+    /// Query every source and aggregate the successful quotes
+    pub fn fetch(&self, client: &Client) -> Result<PriceReport, String> {
+        let mut quotes = Vec::new();
+        let mut errors = Vec::new();
+        for source in &self.sources {
+            match source.fetch_price(client) {
+                Ok(price) if price.is_finite() && price > 0.0 => {
+                    quotes.push((source.name().to_string(), price))
+                }
+                Ok(price) => errors.push((source.name().to_string(), format!("Bad price {price}"))),
+                Err(e) => errors.push((source.name().to_string(), e)),
+            }
+        }
+        if quotes.len() < self.min_sources {
+            return Err(format!(
+                "Only {} of {} required price sources responded",
+                quotes.len(),
+                self.min_sources
+            ));
+        }
+        let prices: Vec<f64> = quotes.iter().map(|(_, p)| *p).collect();
+        let price = match self.aggregation {
+            Aggregation::Median => median(&prices),
+            Aggregation::TrimmedMean(fraction) => trimmed_mean(&prices, fraction),
+        }
+        .ok_or_else(|| "No prices to aggregate".to_string())?;
+        Ok(PriceReport {
+            price,
+            quotes,
+            errors,
+        })
+    }
+}
+
+/// Median of a list of prices
+pub fn median(prices: &[f64]) -> Option<f64> {
+    if prices.is_empty() {
+        return None;
+    }
+    let mut sorted = prices.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+/// Mean after removing `fraction` (0.0..0.5) of the prices from each end
+pub fn trimmed_mean(prices: &[f64], fraction: f64) -> Option<f64> {
+    if prices.is_empty() {
+        return None;
+    }
+    let mut sorted = prices.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let trim = ((sorted.len() as f64) * fraction.clamp(0.0, 0.49)).floor() as usize;
+    let kept = &sorted[trim..sorted.len() - trim];
+    Some(kept.iter().sum::<f64>() / kept.len() as f64)
+}
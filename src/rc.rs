@@ -0,0 +1,195 @@
+//! Resource credit (RC) account lookups and transaction resource estimates
+
+use crate::numbers::de_int;
+use crate::operation::Operation;
+use crate::transaction::Transaction;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Seconds for an empty RC manabar to regenerate fully
+pub const RC_REGENERATION_SECS: i64 = 5 * 86_400;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RcManabar {
+    #[serde(deserialize_with = "de_int")]
+    pub current_mana: i64,
+    pub last_update_time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RcAccount {
+    pub account: String,
+    pub rc_manabar: RcManabar,
+    #[serde(deserialize_with = "de_int")]
+    pub max_rc: i64,
+}
+
+impl RcAccount {
+    /// Mana available at `now` (Unix seconds), including regeneration since the last update
+    pub fn current_mana(&self, now: i64) -> i64 {
+        let elapsed = (now - self.rc_manabar.last_update_time).max(0);
+        let regenerated =
+            i128::from(self.max_rc) * i128::from(elapsed) / i128::from(RC_REGENERATION_SECS);
+        (i128::from(self.rc_manabar.current_mana) + regenerated).min(i128::from(self.max_rc)) as i64
+    }
+
+    /// Available mana as a percentage of the maximum
+    pub fn percent(&self, now: i64) -> f64 {
+        if self.max_rc == 0 {
+            return 0.0;
+        }
+        self.current_mana(now) as f64 * 100.0 / self.max_rc as f64
+    }
+}
+
+/// Per-resource cost constants from `rc_api.get_resource_params`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceParams {
+    #[serde(default)]
+    pub resource_names: Vec<String>,
+    /// Cost constants per resource, e.g. `size_info["resource_state_bytes"]["transaction_object_byte_size"]`
+    #[serde(default)]
+    pub size_info: HashMap<String, HashMap<String, Value>>,
+}
+
+impl ResourceParams {
+    fn constant(&self, resource: &str, name: &str) -> Option<i64> {
+        match self.size_info.get(resource)?.get(name)? {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Estimate the resources a transaction of `size` bytes with `operations` consumes
+    ///
+    /// Operation-specific state (new accounts, open orders) is not included, so
+    /// this is a lower bound for operations that create chain objects.
+    pub fn estimate(&self, size: usize, operations: &[Operation]) -> ResourceUsage {
+        let size = size as i64;
+        let state = |name| self.constant("resource_state_bytes", name).unwrap_or(0);
+        let exec = |name: &str| self.constant("resource_execution_time", name);
+        let execution_time = exec("transaction_time").unwrap_or(0)
+            + operations
+                .iter()
+                .map(|op| {
+                    exec(&format!("{}_exec_time", op.op_type))
+                        .or_else(|| exec(&format!("{}_time", op.name())))
+                        .unwrap_or(0)
+                })
+                .sum::<i64>();
+        ResourceUsage {
+            size,
+            history_bytes: size,
+            state_bytes: state("transaction_object_base_size")
+                + state("transaction_object_byte_size") * size,
+            execution_time,
+        }
+    }
+}
+
+/// Resource consumption of a transaction by RC category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct ResourceUsage {
+    /// Serialized transaction size in bytes
+    pub size: i64,
+    pub history_bytes: i64,
+    /// State bytes, already weighted by how long the chain keeps them
+    pub state_bytes: i64,
+    /// Execution time in the node's abstract time units
+    pub execution_time: i64,
+}
+
+/// Limits checked by [`TransactionBudget::check`] before broadcasting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransactionBudget {
+    pub max_size: Option<i64>,
+    pub max_state_bytes: Option<i64>,
+    pub max_execution_time: Option<i64>,
+}
+
+impl TransactionBudget {
+    pub fn max_size(mut self, bytes: i64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn max_state_bytes(mut self, bytes: i64) -> Self {
+        self.max_state_bytes = Some(bytes);
+        self
+    }
+
+    pub fn max_execution_time(mut self, time: i64) -> Self {
+        self.max_execution_time = Some(time);
+        self
+    }
+
+    /// Warnings for every limit the usage exceeds
+    pub fn check(&self, usage: &ResourceUsage) -> Vec<String> {
+        [
+            ("Transaction size", usage.size, self.max_size),
+            ("State bytes", usage.state_bytes, self.max_state_bytes),
+            (
+                "Execution time",
+                usage.execution_time,
+                self.max_execution_time,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(what, used, max)| {
+            max.filter(|&max| used > max)
+                .map(|max| format!("{what} {used} exceeds the budget of {max}"))
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FindRcAccountsResponse {
+    rc_accounts: Vec<RcAccount>,
+}
+
+impl Client {
+    /// Fetch the resource credit state of accounts
+    pub fn find_rc_accounts(&self, names: &[&str]) -> Result<Vec<RcAccount>, String> {
+        let resp: FindRcAccountsResponse = self
+            .call(
+                "rc_api.find_rc_accounts",
+                serde_json::json!({ "accounts": names }),
+            )
+            .map_err(|e| format!("Error fetching RC accounts: {e}"))?;
+        Ok(resp.rc_accounts)
+    }
+
+    /// Fetch the RC cost constants of the chain
+    pub fn get_resource_params(&self) -> Result<ResourceParams, String> {
+        self.call("rc_api.get_resource_params", serde_json::json!({}))
+            .map_err(|e| format!("Error fetching resource params: {e}"))
+    }
+
+    /// Estimate a transaction's resource usage once signed by `signatures` keys
+    pub fn estimate_resources(
+        &self,
+        tx: &Transaction,
+        signatures: usize,
+    ) -> Result<ResourceUsage, String> {
+        let size = self.serialized_size(tx, signatures)?;
+        Ok(self.get_resource_params()?.estimate(size, &tx.operations))
+    }
+
+    /// Estimate a transaction's resource usage and check it against `budget`
+    ///
+    /// Returns the usage along with a warning for each exceeded limit.
+    pub fn check_budget(
+        &self,
+        tx: &Transaction,
+        signatures: usize,
+        budget: &TransactionBudget,
+    ) -> Result<(ResourceUsage, Vec<String>), String> {
+        let usage = self.estimate_resources(tx, signatures)?;
+        let warnings = budget.check(&usage);
+        Ok((usage, warnings))
+    }
+}
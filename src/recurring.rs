@@ -0,0 +1,421 @@
+//! Building blocks for bots that act on a schedule, such as dollar-cost
+//! averaging on the internal market or periodic HBD conversions
+//!
+//! A [`RecurringTask`] pairs a [`Recurrence`] with a [`RecurringAction`];
+//! calling [`RecurringTask::tick`] from a loop broadcasts the action whenever
+//! it is due, and an [`OrderTracker`] follows the placed orders as they fill.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::history::HistoryEntry;
+use crate::market::{
+    convert_operation, limit_order_create_operation, OrderBook, OrderSide, ORDER_BOOK_LIMIT,
+};
+use crate::operation::Operation;
+use crate::timestamp::format_timestamp;
+use crate::transaction::TransactionSigner;
+use crate::{Client, WriteClient};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Longest lifetime hived accepts for a limit order, in seconds
+pub const MAX_ORDER_EXPIRATION_SECS: i64 = 28 * 86_400;
+
+/// Default lifetime of orders placed by a [`RecurringTask`]
+pub const DEFAULT_ORDER_EXPIRATION_SECS: i64 = 86_400;
+
+/// Number of recent history entries examined by the first [`OrderTracker::poll`]
+const FIRST_POLL_ENTRIES: u32 = 100;
+
+/// A fixed-interval schedule in chain time (Unix seconds)
+///
+/// Runs that were missed, e.g. while the bot was offline, are skipped rather
+/// than replayed in a burst.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    interval: i64,
+    next: Option<i64>,
+    remaining: Option<u32>,
+    runs: u32,
+}
+
+impl Recurrence {
+    /// Run every `interval`, starting with the first check
+    pub fn every(interval: Duration) -> Self {
+        Self {
+            interval: (interval.as_secs() as i64).max(1),
+            next: None,
+            remaining: None,
+            runs: 0,
+        }
+    }
+
+    /// Hold the first run until `unix_secs`
+    pub fn starting_at(mut self, unix_secs: i64) -> Self {
+        self.next = Some(unix_secs);
+        self
+    }
+
+    /// Stop after `count` runs
+    pub fn times(mut self, count: u32) -> Self {
+        self.remaining = Some(count);
+        self
+    }
+
+    /// Chain time of the next run; `None` before the first check or once finished
+    pub fn next_run(&self) -> Option<i64> {
+        self.next.filter(|_| !self.is_finished())
+    }
+
+    /// Number of runs recorded so far
+    pub fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        !self.is_finished() && self.next.is_none_or(|next| now >= next)
+    }
+
+    /// Record a run at `now` and schedule the next one after it
+    pub fn record_run(&mut self, now: i64) {
+        let base = self.next.unwrap_or(now).min(now);
+        let missed = (now - base) / self.interval;
+        self.next = Some(base + (missed + 1) * self.interval);
+        self.runs += 1;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+}
+
+/// What a [`RecurringTask`] does each time it is due
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecurringAction {
+    /// Trade on the internal market at no worse than `max_slippage` from the best price
+    ///
+    /// `amount` is what is given: HBD for [`OrderSide::Buy`], HIVE for
+    /// [`OrderSide::Sell`]. Whatever does not fill at once rests on the book
+    /// until the order expires.
+    MarketOrder {
+        side: OrderSide,
+        amount: Asset,
+        max_slippage: f64,
+    },
+    /// Convert HBD to HIVE
+    Convert { amount: Asset },
+}
+
+impl RecurringAction {
+    /// The operation for one run, with `id` as its order or request id
+    ///
+    /// Market orders are priced from `book`, which is required for them.
+    pub fn operation(
+        &self,
+        owner: &str,
+        id: u32,
+        book: Option<&OrderBook>,
+        expiration: &str,
+    ) -> Result<Operation, String> {
+        match *self {
+            RecurringAction::MarketOrder {
+                side,
+                amount,
+                max_slippage,
+            } => {
+                let book = book.ok_or_else(|| "Market orders need an order book".to_string())?;
+                let min_to_receive = min_to_receive(book, side, amount, max_slippage)?;
+                Ok(limit_order_create_operation(
+                    owner,
+                    id,
+                    amount,
+                    min_to_receive,
+                    false,
+                    expiration,
+                ))
+            }
+            RecurringAction::Convert { amount } => {
+                if amount.symbol != AssetSymbol::Hbd || amount.amount <= 0 {
+                    return Err(format!(
+                        "Only a positive HBD amount can be converted, got {amount}"
+                    ));
+                }
+                Ok(convert_operation(owner, id, amount))
+            }
+        }
+    }
+}
+
+/// Least output accepted for `amount` at `max_slippage` from the best price on `book`
+fn min_to_receive(
+    book: &OrderBook,
+    side: OrderSide,
+    amount: Asset,
+    max_slippage: f64,
+) -> Result<Asset, String> {
+    if !(0.0..1.0).contains(&max_slippage) {
+        return Err(format!("Invalid slippage: {max_slippage}"));
+    }
+    if amount.amount <= 0 {
+        return Err(format!("Order amount must be positive, got {amount}"));
+    }
+    let simulation = book.simulate_market_order(side, amount)?;
+    // HIVE and HBD share a precision, so prices apply to raw amounts as well
+    let best = simulation.best_price;
+    let (min, symbol) = match side {
+        OrderSide::Buy => (
+            amount.amount as f64 / (best * (1.0 + max_slippage)),
+            AssetSymbol::Hive,
+        ),
+        OrderSide::Sell => (
+            amount.amount as f64 * best * (1.0 - max_slippage),
+            AssetSymbol::Hbd,
+        ),
+    };
+    let min = min.floor() as i64;
+    if min <= 0 {
+        return Err(format!("{amount} is too small to trade at {best}"));
+    }
+    Ok(Asset::new(min, symbol))
+}
+
+/// A [`RecurringAction`] broadcast by `owner` on a [`Recurrence`]
+#[derive(Debug, Clone)]
+pub struct RecurringTask {
+    pub owner: String,
+    pub action: RecurringAction,
+    pub recurrence: Recurrence,
+    order_expiration: i64,
+}
+
+impl RecurringTask {
+    pub fn new(owner: &str, action: RecurringAction, recurrence: Recurrence) -> Self {
+        Self {
+            owner: owner.to_string(),
+            action,
+            recurrence,
+            order_expiration: DEFAULT_ORDER_EXPIRATION_SECS,
+        }
+    }
+
+    /// How long placed orders rest on the book, capped at [`MAX_ORDER_EXPIRATION_SECS`]
+    pub fn order_expiration(mut self, secs: i64) -> Self {
+        self.order_expiration = secs.clamp(1, MAX_ORDER_EXPIRATION_SECS);
+        self
+    }
+
+    /// Broadcast the action if it is due, returning the transaction id
+    ///
+    /// Order and request ids are the chain time of the run, so they are unique
+    /// as long as the task runs at most once per second. Placed orders are
+    /// added to `tracker`.
+    pub fn tick(
+        &mut self,
+        client: &WriteClient,
+        signer: &dyn TransactionSigner,
+        tracker: &mut OrderTracker,
+    ) -> Result<Option<String>, String> {
+        let now = client.chain_now()?;
+        if !self.recurrence.is_due(now) {
+            return Ok(None);
+        }
+        let id = now as u32;
+        let book = match self.action {
+            RecurringAction::MarketOrder { .. } => Some(client.get_order_book(ORDER_BOOK_LIMIT)?),
+            RecurringAction::Convert { .. } => None,
+        };
+        let expiration = format_timestamp(now + self.order_expiration);
+        let op = self
+            .action
+            .operation(&self.owner, id, book.as_ref(), &expiration)?;
+        let mut tx = client.prepare_transaction(vec![op.clone()])?;
+        let trx_id = client.sign_transaction(&mut tx, &[signer])?;
+        client.broadcast_transaction(&tx)?;
+        self.recurrence.record_run(now);
+        if let RecurringAction::MarketOrder { amount, .. } = self.action {
+            let min = Asset::from_value(&op.value["min_to_receive"])?;
+            tracker.place(id, amount, min);
+        }
+        Ok(Some(trx_id))
+    }
+}
+
+/// Lifecycle of a tracked limit order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    Open,
+    Filled,
+    /// Cancelled or expired, possibly after partial fills
+    Cancelled,
+}
+
+/// A limit order placed by the tracked account and what it has traded so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub orderid: u32,
+    pub amount_to_sell: Asset,
+    pub min_to_receive: Asset,
+    pub sold: Asset,
+    pub received: Asset,
+    pub status: OrderStatus,
+}
+
+impl TrackedOrder {
+    /// Received per unit sold, in the same direction as the order
+    pub fn average_price(&self) -> Option<f64> {
+        (self.sold.amount > 0).then(|| self.received.amount as f64 / self.sold.amount as f64)
+    }
+}
+
+/// A change to a tracked order; `sold` and `received` are zero for cancellations
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderEvent {
+    pub orderid: u32,
+    pub sold: Asset,
+    pub received: Asset,
+    pub status: OrderStatus,
+}
+
+/// Follows an account's limit orders through `fill_order` and
+/// `limit_order_cancelled` virtual operations in its history
+#[derive(Debug, Clone)]
+pub struct OrderTracker {
+    account: String,
+    orders: BTreeMap<u32, TrackedOrder>,
+    last_index: Option<u64>,
+}
+
+impl OrderTracker {
+    pub fn new(account: &str) -> Self {
+        Self {
+            account: account.to_string(),
+            orders: BTreeMap::new(),
+            last_index: None,
+        }
+    }
+
+    /// Start tracking an order placed outside a [`RecurringTask`]
+    pub fn place(&mut self, orderid: u32, amount_to_sell: Asset, min_to_receive: Asset) {
+        self.orders.insert(
+            orderid,
+            TrackedOrder {
+                orderid,
+                amount_to_sell,
+                min_to_receive,
+                sold: Asset::zero(amount_to_sell.symbol),
+                received: Asset::zero(min_to_receive.symbol),
+                status: OrderStatus::Open,
+            },
+        );
+    }
+
+    pub fn get(&self, orderid: u32) -> Option<&TrackedOrder> {
+        self.orders.get(&orderid)
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders
+            .values()
+            .filter(|o| o.status == OrderStatus::Open)
+    }
+
+    /// Stop tracking filled and cancelled orders
+    pub fn prune(&mut self) {
+        self.orders.retain(|_, o| o.status == OrderStatus::Open);
+    }
+
+    /// Update tracked orders from one history entry of the account
+    pub fn apply(&mut self, entry: &HistoryEntry) -> Result<Option<OrderEvent>, String> {
+        let op = &entry.op;
+        let (orderid, paid, got) = match op.name() {
+            "fill_order" => {
+                let int = |field: &str| op.value[field].as_u64().map(|id| id as u32);
+                let side = if op.str_field("current_owner") == Some(&self.account) {
+                    ("current_orderid", "current_pays", "open_pays")
+                } else if op.str_field("open_owner") == Some(&self.account) {
+                    ("open_orderid", "open_pays", "current_pays")
+                } else {
+                    return Ok(None);
+                };
+                let Some(orderid) = int(side.0) else {
+                    return Ok(None);
+                };
+                let paid = Asset::from_value(&op.value[side.1])?;
+                let got = Asset::from_value(&op.value[side.2])?;
+                (orderid, Some(paid), Some(got))
+            }
+            "limit_order_cancelled" if op.str_field("seller") == Some(&self.account) => {
+                match op.value["orderid"].as_u64() {
+                    Some(orderid) => (orderid as u32, None, None),
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        let Some(order) = self.orders.get_mut(&orderid) else {
+            return Ok(None);
+        };
+        let (sold, received) = match (paid, got) {
+            (Some(paid), Some(got)) => {
+                order.sold = order.sold.checked_add(paid).unwrap_or(order.sold);
+                order.received = order.received.checked_add(got).unwrap_or(order.received);
+                if order.sold.amount >= order.amount_to_sell.amount {
+                    order.status = OrderStatus::Filled;
+                }
+                (paid, got)
+            }
+            _ => {
+                if order.status == OrderStatus::Open {
+                    order.status = OrderStatus::Cancelled;
+                }
+                (
+                    Asset::zero(order.sold.symbol),
+                    Asset::zero(order.received.symbol),
+                )
+            }
+        };
+        Ok(Some(OrderEvent {
+            orderid,
+            sold,
+            received,
+            status: order.status,
+        }))
+    }
+
+    /// Apply the account's history entries added since the last poll, oldest first
+    ///
+    /// The first poll only examines the most recent entries, so create the
+    /// tracker before placing the orders it should follow.
+    pub fn poll(&mut self, client: &Client) -> Result<Vec<OrderEvent>, String> {
+        let mut entries = match self.last_index {
+            None => client.get_account_history(&self.account, -1, FIRST_POLL_ENTRIES)?,
+            Some(last) => {
+                let mut newer = Vec::new();
+                for entry in client
+                    .account_history(&self.account)
+                    .page_size(FIRST_POLL_ENTRIES)
+                {
+                    let entry = entry?;
+                    if entry.index <= last {
+                        break;
+                    }
+                    newer.push(entry);
+                }
+                newer
+            }
+        };
+        entries.sort_by_key(|entry| entry.index);
+        let mut events = Vec::new();
+        for entry in &entries {
+            events.extend(self.apply(entry)?);
+            self.last_index = Some(entry.index);
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,173 @@
+//! Graphs of relations between accounts for due diligence and security analysis
+//!
+//! [`RelationGraph`] links accounts through outgoing vesting delegations,
+//! accounts named in their authorities, recovery accounts and witness vote
+//! proxies, and exports them as Graphviz DOT or JSON.
+
+use crate::asset::Asset;
+use crate::delegations::VestingDelegation;
+use crate::{Account, Client};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// How one account relates to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RelationKind {
+    /// `from` delegates VESTS to `to`
+    Delegation { vesting_shares: Asset },
+    /// `to` appears with `weight` in the `role` authority of `from`
+    AccountAuth { role: String, weight: u16 },
+    /// `to` is the recovery account of `from`
+    Recovery,
+    /// `to` votes for witnesses on behalf of `from`
+    Proxy,
+}
+
+impl RelationKind {
+    /// Short label, e.g. `posting auth` or `delegation`
+    pub fn label(&self) -> String {
+        match self {
+            Self::Delegation { .. } => "delegation".to_string(),
+            Self::AccountAuth { role, .. } => format!("{role} auth"),
+            Self::Recovery => "recovery".to_string(),
+            Self::Proxy => "proxy".to_string(),
+        }
+    }
+}
+
+/// A directed edge between two accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Relation {
+    pub from: String,
+    pub to: String,
+    #[serde(flatten)]
+    pub kind: RelationKind,
+}
+
+/// Relations of a set of accounts, including edges to accounts outside the set
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelationGraph {
+    /// The accounts whose relations were collected
+    pub accounts: BTreeSet<String>,
+    pub relations: Vec<Relation>,
+}
+
+impl RelationGraph {
+    /// Add the authority, recovery and proxy relations of `account`
+    pub fn add_account(&mut self, account: &Account) {
+        let from = &account.name;
+        self.accounts.insert(from.clone());
+        for (role, authority) in [
+            ("owner", &account.owner),
+            ("active", &account.active),
+            ("posting", &account.posting),
+        ] {
+            for (to, weight) in &authority.account_auths {
+                self.push(
+                    from,
+                    to,
+                    RelationKind::AccountAuth {
+                        role: role.to_string(),
+                        weight: *weight,
+                    },
+                );
+            }
+        }
+        if !account.recovery_account.is_empty() {
+            self.push(from, &account.recovery_account, RelationKind::Recovery);
+        }
+        if !account.proxy.is_empty() {
+            self.push(from, &account.proxy, RelationKind::Proxy);
+        }
+    }
+
+    pub fn add_delegation(&mut self, delegation: &VestingDelegation) {
+        self.push(
+            &delegation.delegator,
+            &delegation.delegatee,
+            RelationKind::Delegation {
+                vesting_shares: delegation.vesting_shares,
+            },
+        );
+    }
+
+    fn push(&mut self, from: &str, to: &str, kind: RelationKind) {
+        self.relations.push(Relation {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+        });
+    }
+
+    /// Every account in the graph, including those only related to
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.accounts
+            .iter()
+            .map(String::as_str)
+            .chain(self.relations.iter().map(|r| r.to.as_str()))
+            .collect()
+    }
+
+    pub fn relations_from<'a>(&'a self, account: &'a str) -> impl Iterator<Item = &'a Relation> {
+        self.relations.iter().filter(move |r| r.from == account)
+    }
+
+    pub fn relations_to<'a>(&'a self, account: &'a str) -> impl Iterator<Item = &'a Relation> {
+        self.relations.iter().filter(move |r| r.to == account)
+    }
+
+    /// Graphviz DOT, with the queried accounts drawn as boxes
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph relations {\n");
+        for node in self.nodes() {
+            let shape = if self.accounts.contains(node) {
+                "box"
+            } else {
+                "ellipse"
+            };
+            out.push_str(&format!("  {} [shape={shape}];\n", dot_id(node)));
+        }
+        for r in &self.relations {
+            let label = match &r.kind {
+                RelationKind::Delegation { vesting_shares } => {
+                    format!("delegation {vesting_shares}")
+                }
+                kind => kind.label(),
+            };
+            out.push_str(&format!(
+                "  {} -> {} [label={}];\n",
+                dot_id(&r.from),
+                dot_id(&r.to),
+                dot_id(&label)
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Encode error: {e}"))
+    }
+}
+
+/// Quote a DOT identifier
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Client {
+    /// Build the relation graph of `names`
+    ///
+    /// Accounts are fetched in batches; delegations take one call per account.
+    pub fn relation_graph(&self, names: &[&str]) -> Result<RelationGraph, String> {
+        let mut graph = RelationGraph::default();
+        for account in self.find_accounts(names)? {
+            graph.add_account(&account);
+            for delegation in self.find_vesting_delegations(&account.name)? {
+                graph.add_delegation(&delegation);
+            }
+        }
+        Ok(graph)
+    }
+}
@@ -0,0 +1,132 @@
+//! Third-party REST APIs run by front-ends such as Ecency and PeakD
+//!
+//! Some data (points, notifications, search) is only served by front-end
+//! operators rather than by Hive nodes. These endpoints are not part of the
+//! chain API and may change or disappear without notice.
+
+use crate::error::NectarError;
+use crate::{Client, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A third-party REST API served from one or more base URLs
+#[derive(Debug)]
+pub struct RestApi {
+    name: String,
+    base_urls: Vec<String>,
+    min_interval: Duration,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl RestApi {
+    /// An API tried at each base URL in order until one succeeds
+    pub fn new(name: &str, base_urls: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            base_urls: base_urls
+                .iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect(),
+            min_interval: Duration::ZERO,
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Ecency's private API
+    pub fn ecency() -> Self {
+        Self::new("ecency", &["https://ecency.com/private-api"])
+            .min_interval(Duration::from_millis(250))
+    }
+
+    /// Wait at least this long between requests to stay under the operator's rate limit
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// `GET path?query` decoded as `R`
+    pub fn get<R: DeserializeOwned>(
+        &self,
+        client: &Client,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<R, String> {
+        self.send(path, |url| {
+            // An empty query would still leave a trailing `?`
+            let parsed = match query {
+                [] => Url::parse(url),
+                _ => Url::parse_with_params(url, query),
+            };
+            let url = parsed.map_err(|e| NectarError::Transport(format!("{url}: {e}")))?;
+            client.transport_ref().get(url.as_str())
+        })
+    }
+
+    /// `POST path` with a JSON body, decoded as `R`
+    pub fn post<B: Serialize, R: DeserializeOwned>(
+        &self,
+        client: &Client,
+        path: &str,
+        body: &B,
+    ) -> Result<R, String> {
+        let body = serde_json::to_vec(body).map_err(|e| format!("Encode error: {e}"))?;
+        self.send(path, |url| client.transport_ref().send(url, &body))
+    }
+
+    fn throttle(&self) {
+        let mut last = self.last_request.lock().unwrap();
+        if let Some(wait) = last.and_then(|t| self.min_interval.checked_sub(t.elapsed())) {
+            thread::sleep(wait);
+        }
+        *last = Some(Instant::now());
+    }
+
+    fn send<R, F>(&self, path: &str, request: F) -> Result<R, String>
+    where
+        R: DeserializeOwned,
+        F: Fn(&str) -> Result<Vec<u8>, NectarError>,
+    {
+        let mut last_err = None;
+        for base in &self.base_urls {
+            self.throttle();
+            let url = format!("{base}/{}", path.trim_start_matches('/'));
+            match request(&url) {
+                Ok(body) => {
+                    return serde_json::from_slice(&body).map_err(|e| format!("Decode error: {e}"))
+                }
+                // A response over the size limit would be as large from any mirror
+                Err(e @ NectarError::ResponseTooLarge { .. }) => return Err(e.to_string()),
+                Err(e) => last_err = Some(e.to_string()),
+            }
+        }
+        Err(format!(
+            "Error calling {} {path}: {}",
+            self.name,
+            last_err.unwrap_or_else(|| "No base URLs configured".to_string())
+        ))
+    }
+}
+
+/// An account's Ecency points balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcencyPoints {
+    #[serde(default)]
+    pub username: String,
+    pub points: String,
+    #[serde(default)]
+    pub unclaimed_points: String,
+}
+
+impl Client {
+    /// Fetch an account's Ecency points through `api`, usually [`RestApi::ecency`]
+    pub fn ecency_points(&self, api: &RestApi, username: &str) -> Result<EcencyPoints, String> {
+        api.post(self, "points", &serde_json::json!({ "username": username }))
+    }
+}
@@ -0,0 +1,179 @@
+//! Retrying transient failures with exponential backoff
+//!
+//! By default a call tries each node once. A [`RetryPolicy`] set with
+//! [`Client::retry_policy`] retries timeouts, 5xx answers and other
+//! [transient](crate::fail_reason::FailReason::is_transient) failures on the
+//! same node, and can make several passes over the node list, waiting longer
+//! after each retry so a brief outage does not burn through every node:
+//!
+//! ```
+//! use nectarflower_rs::retry::RetryPolicy;
+//! use nectarflower_rs::Client;
+//! use std::time::Duration;
+//!
+//! let client = Client::new().retry_policy(
+//!     RetryPolicy::new()
+//!         .attempts_per_node(3)
+//!         .rounds(2)
+//!         .base_delay(Duration::from_millis(200)),
+//! );
+//! ```
+
+use crate::fail_reason::FailReason;
+use crate::Client;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Default delay before the first retry
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Default longest delay between retries
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Default fraction of each delay that is randomized
+pub const DEFAULT_JITTER: f64 = 0.5;
+
+/// How often and how patiently calls are retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub(crate) attempts_per_node: u32,
+    pub(crate) rounds: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// One attempt per node and one pass over the nodes, as without a policy
+    pub fn new() -> Self {
+        Self {
+            attempts_per_node: 1,
+            rounds: 1,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+
+    /// Attempts on a node before moving on; only transient failures are retried
+    pub fn attempts_per_node(mut self, attempts: u32) -> Self {
+        self.attempts_per_node = attempts.max(1);
+        self
+    }
+
+    /// Passes over the node list before giving up
+    pub fn rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds.max(1);
+        self
+    }
+
+    /// Delay before the first retry, doubled for each retry after it
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Cap on the delay between retries
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Fraction of each delay, 0.0 to 1.0, replaced by a random amount
+    ///
+    /// Spreads out retries of clients that failed at the same moment.
+    pub fn jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The delay before retry number `retry`, counting from 0
+    pub fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        backoff.mul_f64(1.0 - self.jitter * random_fraction())
+    }
+
+    /// The order of tries of a call over `nodes`
+    pub(crate) fn schedule<'a>(&self, nodes: Vec<&'a str>) -> RetrySchedule<'a> {
+        RetrySchedule {
+            policy: *self,
+            nodes,
+            started: false,
+            round: 0,
+            index: 0,
+            attempt: 0,
+            retries: 0,
+        }
+    }
+}
+
+/// Which node a call tries next, and how long to wait first
+#[derive(Debug)]
+pub(crate) struct RetrySchedule<'a> {
+    policy: RetryPolicy,
+    nodes: Vec<&'a str>,
+    started: bool,
+    round: u32,
+    index: usize,
+    attempt: u32,
+    retries: u32,
+}
+
+impl<'a> RetrySchedule<'a> {
+    /// The next node to try after a failure for `reason`, or `None` when done
+    pub(crate) fn next(&mut self, reason: Option<FailReason>) -> Option<(&'a str, Duration)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some((self.nodes[0], Duration::ZERO));
+        }
+        let transient = reason.is_some_and(FailReason::is_transient);
+        if transient && self.attempt + 1 < self.policy.attempts_per_node {
+            self.attempt += 1;
+            return Some((self.nodes[self.index], self.backoff()));
+        }
+        self.attempt = 0;
+        self.index += 1;
+        if self.index < self.nodes.len() {
+            return Some((self.nodes[self.index], Duration::ZERO));
+        }
+        self.round += 1;
+        if self.round >= self.policy.rounds {
+            return None;
+        }
+        self.index = 0;
+        Some((self.nodes[0], self.backoff()))
+    }
+
+    fn backoff(&mut self) -> Duration {
+        let delay = self.policy.delay(self.retries);
+        self.retries += 1;
+        delay
+    }
+}
+
+/// A random number in `[0, 1)`, good enough to spread out retries
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+impl Client {
+    /// Retry transient failures according to `policy`
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+}
@@ -0,0 +1,189 @@
+//! Rich list reports ranking accounts by their HIVE, HBD or HIVE Power holdings
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::block::DynamicGlobalProperties;
+use crate::{Account, Client};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Holding used to rank accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    /// Liquid plus savings HIVE
+    Hive,
+    /// Liquid plus savings HBD
+    Hbd,
+    /// Owned HIVE Power, excluding delegations
+    Hp,
+}
+
+/// One row of a rich list
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub rank: usize,
+    pub account: String,
+    pub hive: Asset,
+    pub hbd: Asset,
+    pub hp: Asset,
+}
+
+impl Holding {
+    fn from_account(account: &Account, props: &DynamicGlobalProperties) -> Self {
+        Self {
+            rank: 0,
+            account: account.name.clone(),
+            hive: Asset::new(
+                account.balance.amount + account.savings_balance.amount,
+                AssetSymbol::Hive,
+            ),
+            hbd: Asset::new(
+                account.hbd_balance.amount + account.savings_hbd_balance.amount,
+                AssetSymbol::Hbd,
+            ),
+            hp: props.vests_to_hp(account.vesting_shares),
+        }
+    }
+
+    /// The holding selected by `rank_by`
+    pub fn value(&self, rank_by: RankBy) -> Asset {
+        match rank_by {
+            RankBy::Hive => self.hive,
+            RankBy::Hbd => self.hbd,
+            RankBy::Hp => self.hp,
+        }
+    }
+}
+
+/// Settings for a rich list report
+#[derive(Debug, Clone)]
+pub struct RichList {
+    rank_by: RankBy,
+    excluded: HashSet<String>,
+    min_balance: i64,
+    top: Option<usize>,
+}
+
+impl RichList {
+    pub fn new(rank_by: RankBy) -> Self {
+        Self {
+            rank_by,
+            excluded: HashSet::new(),
+            min_balance: 0,
+            top: None,
+        }
+    }
+
+    /// Leave out accounts such as exchanges or burn accounts
+    pub fn exclude(mut self, accounts: &[&str]) -> Self {
+        self.excluded
+            .extend(accounts.iter().map(|name| name.to_string()));
+        self
+    }
+
+    /// Leave out accounts holding less than `min`; HP is compared in HIVE
+    ///
+    /// Accounts holding nothing of the ranked asset are always left out.
+    pub fn min_balance(mut self, min: Asset) -> Self {
+        self.min_balance = min.amount;
+        self
+    }
+
+    /// Only keep the `n` largest holders
+    pub fn top(mut self, n: usize) -> Self {
+        self.top = Some(n);
+        self
+    }
+
+    /// Rank the given accounts
+    pub fn build<I>(&self, accounts: I, props: &DynamicGlobalProperties) -> RichListReport
+    where
+        I: IntoIterator<Item = Account>,
+    {
+        let mut holdings = Vec::new();
+        let mut scanned = 0;
+        for account in accounts {
+            scanned += 1;
+            if self.excluded.contains(&account.name) {
+                continue;
+            }
+            let holding = Holding::from_account(&account, props);
+            if holding.value(self.rank_by).amount < self.min_balance.max(1) {
+                continue;
+            }
+            holdings.push(holding);
+            if let Some(top) = self.top {
+                // Keep memory bounded when scanning every account on chain
+                if holdings.len() > top.saturating_mul(2).max(1024) {
+                    self.rank(&mut holdings);
+                }
+            }
+        }
+        self.rank(&mut holdings);
+        RichListReport {
+            rank_by: self.rank_by,
+            scanned,
+            holdings,
+        }
+    }
+
+    fn rank(&self, holdings: &mut Vec<Holding>) {
+        let rank_by = self.rank_by;
+        holdings.sort_by(|a, b| {
+            b.value(rank_by)
+                .amount
+                .cmp(&a.value(rank_by).amount)
+                .then_with(|| a.account.cmp(&b.account))
+        });
+        if let Some(top) = self.top {
+            holdings.truncate(top);
+        }
+        for (index, holding) in holdings.iter_mut().enumerate() {
+            holding.rank = index + 1;
+        }
+    }
+}
+
+/// A ranked list of holders
+#[derive(Debug, Clone, Serialize)]
+pub struct RichListReport {
+    pub rank_by: RankBy,
+    /// Number of accounts examined, including filtered ones
+    pub scanned: usize,
+    pub holdings: Vec<Holding>,
+}
+
+impl RichListReport {
+    /// CSV with a header row and plain decimal amounts
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("rank,account,hive,hbd,hp\n");
+        for h in &self.holdings {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                h.rank,
+                h.account,
+                h.hive.amount_string(),
+                h.hbd.amount_string(),
+                h.hp.amount_string()
+            ));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Encode error: {e}"))
+    }
+}
+
+impl Client {
+    /// Scan every account on chain and rank them
+    pub fn rich_list(&self, settings: &RichList) -> Result<RichListReport, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let mut error = None;
+        let accounts = self
+            .scan_accounts()
+            .map_while(|account| account.map_err(|e| error = Some(e)).ok());
+        let report = settings.build(accounts, &props);
+        error.map_or(Ok(report), Err)
+    }
+}
@@ -0,0 +1,458 @@
+//! Opt-in sanitizer for post bodies following Hive frontends' rendering rules
+
+/// HTML tags rendered by Hive frontends; anything else is stripped
+pub const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "center",
+    "code",
+    "del",
+    "details",
+    "div",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Attributes kept on allowed tags
+pub const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "alt", "class", "colspan", "height", "href", "rowspan", "src", "title", "width",
+];
+
+/// Tags removed together with their content
+const DROPPED_WITH_CONTENT: &[&str] = &["script", "style", "iframe", "object", "embed", "form"];
+
+/// URL schemes removed from `href` and `src`, as they run script or inline a document
+const BLOCKED_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+/// Bodies larger than this are rejected by the chain
+pub const MAX_BODY_BYTES: usize = 65_535;
+
+/// A sanitized body and the problems found while cleaning it
+#[derive(Debug, Clone, Default)]
+pub struct SanitizeReport {
+    pub body: String,
+    pub warnings: Vec<String>,
+}
+
+/// Strip disallowed HTML and normalize markdown, reporting what will render unexpectedly
+///
+/// Blocked URL schemes are removed from HTML attributes and from markdown
+/// links, images and reference definitions alike. Text inside fenced code
+/// blocks is left untouched.
+pub fn sanitize_body(body: &str) -> SanitizeReport {
+    let mut report = SanitizeReport::default();
+    let normalized = body.replace("\r\n", "\n");
+    let mut out: Vec<String> = Vec::new();
+    let mut text: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for (index, line) in normalized.split('\n').enumerate() {
+        let is_fence = line.trim_start().starts_with("```");
+        if in_fence || is_fence {
+            if !text.is_empty() {
+                out.push(sanitize_text(&text.join("\n"), &mut report.warnings));
+                text.clear();
+            }
+            if is_fence {
+                in_fence = !in_fence;
+            }
+            out.push(line.to_string());
+        } else {
+            text.push(fix_heading(line, index + 1, &mut report.warnings));
+        }
+    }
+    if !text.is_empty() {
+        out.push(sanitize_text(&text.join("\n"), &mut report.warnings));
+    }
+    if in_fence {
+        report
+            .warnings
+            .push("Unclosed code fence; a closing ``` was added".to_string());
+        out.push("```".to_string());
+    }
+    report.body = out.join("\n");
+    if report.body.len() > MAX_BODY_BYTES {
+        report.warnings.push(format!(
+            "Body is {} bytes, above the {MAX_BODY_BYTES} byte limit",
+            report.body.len()
+        ));
+    }
+    report
+}
+
+/// Text outside code fences with its HTML and markdown links sanitized
+fn sanitize_text(text: &str, warnings: &mut Vec<String>) -> String {
+    let html = sanitize_html(text, warnings);
+    let inline = sanitize_inline_links(&html, warnings);
+    sanitize_link_references(&inline, warnings)
+}
+
+fn fix_heading(line: &str, number: usize, warnings: &mut Vec<String>) -> String {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) {
+        if let Some(c) = line[hashes..].chars().next() {
+            if !c.is_whitespace() && c != '#' {
+                warnings.push(format!(
+                    "Line {number}: heading needs a space after '#'; one was added"
+                ));
+                return format!("{} {}", &line[..hashes], &line[hashes..]);
+            }
+        }
+    }
+    line.to_string()
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    attributes: Vec<(String, Option<String>)>,
+    self_closing: bool,
+}
+
+fn parse_tag(raw: &str) -> Option<Tag> {
+    let inner = raw.strip_prefix('<')?.strip_suffix('>')?;
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, inner),
+    };
+    let (inner, self_closing) = match inner.trim_end().strip_suffix('/') {
+        Some(rest) => (rest, true),
+        None => (inner, false),
+    };
+    let name_end = inner
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = None;
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (v, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after[1..].find(q).map_or(after.len(), |i| i + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = Some(v.to_string());
+            rest = remaining.trim_start();
+        }
+        if !key.is_empty() {
+            attributes.push((key, value));
+        }
+    }
+    Some(Tag {
+        name,
+        closing,
+        attributes,
+        self_closing,
+    })
+}
+
+fn render_tag(tag: &Tag, warnings: &mut Vec<String>) -> String {
+    if tag.closing {
+        return format!("</{}>", tag.name);
+    }
+    let mut out = format!("<{}", tag.name);
+    for (key, value) in &tag.attributes {
+        if !ALLOWED_ATTRIBUTES.contains(&key.as_str()) {
+            warnings.push(format!("Removed attribute '{key}' from <{}>", tag.name));
+            continue;
+        }
+        let value = value.as_deref().unwrap_or("");
+        if matches!(key.as_str(), "href" | "src") {
+            if let Some(scheme) = blocked_scheme(value) {
+                warnings.push(format!("Removed {scheme} URL from <{}>", tag.name));
+                continue;
+            }
+        }
+        out.push_str(&format!(" {key}=\"{}\"", value.replace('"', "&quot;")));
+    }
+    if tag.self_closing {
+        out.push_str(" /");
+    }
+    out.push('>');
+    out
+}
+
+/// The blocked scheme of `url` as a browser reads it, if any
+///
+/// Browsers decode character references and skip tabs, newlines and control
+/// characters in URLs, so `java&#x09;script:` still runs script.
+fn blocked_scheme(url: &str) -> Option<&'static str> {
+    let url: String = decode_references(url)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    BLOCKED_SCHEMES
+        .iter()
+        .copied()
+        .find(|scheme| url.starts_with(scheme))
+}
+
+/// The blocked scheme of a markdown link destination, after its backslash escapes
+fn markdown_blocked_scheme(destination: &str) -> Option<&'static str> {
+    let mut unescaped = String::with_capacity(destination.len());
+    let mut chars = destination.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && next.is_ascii_punctuation() => {}
+            _ => unescaped.push(c),
+        }
+    }
+    blocked_scheme(&unescaped)
+}
+
+/// The destination a markdown link's `(` is followed by, and the length it
+/// takes up, including the whitespace before it
+///
+/// Destinations are either `<...>` or run to whitespace or the unbalanced `)`.
+fn link_destination(s: &str) -> (&str, usize) {
+    let trimmed = s.trim_start();
+    let lead = s.len() - trimmed.len();
+    if let Some(inner) = trimmed.strip_prefix('<') {
+        if let Some(end) = inner
+            .find(['>', '\n'])
+            .filter(|&i| inner[i..].starts_with('>'))
+        {
+            return (&inner[..end], lead + end + 2);
+        }
+    }
+    let mut depth = 0usize;
+    let mut escaped = false;
+    for (i, c) in trimmed.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' if depth == 0 => return (&trimmed[..i], lead + i),
+            ')' => depth -= 1,
+            c if c.is_whitespace() => return (&trimmed[..i], lead + i),
+            _ => {}
+        }
+    }
+    (trimmed, s.len())
+}
+
+/// Inline links and images, `[text](url)` and `![alt](url)`, without blocked URLs
+fn sanitize_inline_links(text: &str, warnings: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find("](") {
+        out.push_str(&rest[..i + 2]);
+        let after = &rest[i + 2..];
+        let (destination, len) = link_destination(after);
+        rest = &after[len..];
+        match markdown_blocked_scheme(destination) {
+            Some(scheme) => {
+                let opening = out[..out.len() - 2].rfind('[');
+                let kind = match opening {
+                    Some(j) if out[..j].ends_with('!') => "image",
+                    _ => "link",
+                };
+                warnings.push(format!("Removed {scheme} URL from a markdown {kind}"));
+            }
+            None => out.push_str(&after[..len]),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Reference definitions, `[label]: url`, with a blocked URL are dropped
+fn sanitize_link_references(text: &str, warnings: &mut Vec<String>) -> String {
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| {
+            let definition = line
+                .trim_start_matches(' ')
+                .strip_prefix('[')
+                .and_then(|label| label.split_once("]:"))
+                .filter(|(label, _)| !label.contains(['[', ']']));
+            let Some((_, destination)) = definition else {
+                return line;
+            };
+            match markdown_blocked_scheme(link_destination(destination).0) {
+                Some(scheme) => {
+                    warnings.push(format!("Removed {scheme} URL from a markdown reference"));
+                    ""
+                }
+                None => line,
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// `text` with numeric and URL-relevant named character references decoded
+fn decode_references(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        match character_reference(rest) {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character a reference following `&` stands for, and the reference's length
+///
+/// Numeric references need no closing `;`, as in browsers.
+fn character_reference(s: &str) -> Option<(char, usize)> {
+    if let Some(number) = s.strip_prefix('#') {
+        let (radix, skip) = match number.strip_prefix(['x', 'X']) {
+            Some(_) => (16, 2),
+            None => (10, 1),
+        };
+        let digits = s[skip..].chars().take_while(|c| c.is_digit(radix)).count();
+        if digits == 0 {
+            return None;
+        }
+        let c = u32::from_str_radix(&s[skip..skip + digits], radix)
+            .ok()
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        let len = skip + digits;
+        return Some((c, len + usize::from(s[len..].starts_with(';'))));
+    }
+    [("colon;", ':'), ("tab;", '\t'), ("newline;", '\n')]
+        .into_iter()
+        .find(|(name, _)| {
+            s.get(..name.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name))
+        })
+        .map(|(name, c)| (c, name.len()))
+}
+
+/// The index of the '>' ending the tag `candidate` starts with
+///
+/// A '>' inside a quoted attribute value does not end the tag.
+fn tag_end(candidate: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut after_equals = false;
+    for (i, c) in candidate.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '>' => return Some(i),
+            None if after_equals && (c == '"' || c == '\'') => quote = Some(c),
+            None => {}
+        }
+        if !c.is_whitespace() {
+            after_equals = quote.is_none() && c == '=';
+        }
+    }
+    None
+}
+
+fn sanitize_html(text: &str, warnings: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        if let Some(comment) = candidate.strip_prefix("<!--") {
+            // Browsers also end comments at "--!>", and "<!-->" is an empty comment
+            let end = ["-->", "--!>"]
+                .iter()
+                .filter_map(|close| candidate[2..].find(close).map(|i| i + 2 + close.len()))
+                .min();
+            match end {
+                Some(end) => {
+                    out.push_str(&candidate[..end]);
+                    rest = &candidate[end..];
+                }
+                // Unclosed, so what follows is sanitized like any other text
+                None => {
+                    out.push_str("<!--");
+                    rest = comment;
+                }
+            }
+            continue;
+        }
+        let Some(end) = tag_end(candidate) else {
+            if candidate[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+                warnings.push("Removed unterminated tag at the end of the body".to_string());
+            } else {
+                out.push_str(candidate);
+            }
+            return out;
+        };
+        let raw = &candidate[..=end];
+        let Some(tag) = parse_tag(raw) else {
+            // A literal '<', as in "1 < 2"; tags after it are still checked
+            out.push('<');
+            rest = &candidate[1..];
+            continue;
+        };
+        rest = &candidate[end + 1..];
+        if ALLOWED_TAGS.contains(&tag.name.as_str()) {
+            out.push_str(&render_tag(&tag, warnings));
+        } else if DROPPED_WITH_CONTENT.contains(&tag.name.as_str()) {
+            if !tag.closing {
+                warnings.push(format!("Removed <{}> and its content", tag.name));
+                let close = format!("</{}", tag.name);
+                let lower = rest.to_ascii_lowercase();
+                rest = match lower.find(&close) {
+                    Some(i) => rest[i..].find('>').map_or("", |j| &rest[i + j + 1..]),
+                    None => "",
+                };
+            }
+        } else if !tag.closing {
+            warnings.push(format!("Removed unsupported tag <{}>", tag.name));
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,191 @@
+//! Private key handling that keeps key material out of logs and memory
+//!
+//! [`SecretWif`] holds a WIF-encoded private key, wipes it on drop, prints as
+//! `<redacted>` and never includes the key in error messages. It signs
+//! transactions itself; [`WifSigner`] instead lends the decoded key to your
+//! own signing function only for the duration of one signature.
+
+use crate::crypto::{base58_decode, encode_public_key, sha256, to_hex};
+use crate::transaction::TransactionSigner;
+use secp256k1::{ecdsa::RecoverableSignature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Version byte of a WIF private key
+const WIF_VERSION: u8 = 0x80;
+
+/// A WIF private key, zeroized on drop and never printed
+#[derive(Clone)]
+pub struct SecretWif {
+    wif: Zeroizing<String>,
+}
+
+impl SecretWif {
+    /// Validate the encoding and checksum of `wif`
+    ///
+    /// Errors describe what is wrong without repeating the key.
+    pub fn parse(wif: &str) -> Result<Self, String> {
+        decode_wif(wif.trim())?;
+        Ok(Self {
+            wif: Zeroizing::new(wif.trim().to_string()),
+        })
+    }
+
+    /// Take ownership of `wif`, wiping the original string even if it is invalid
+    pub fn from_string(mut wif: String) -> Result<Self, String> {
+        let parsed = Self::parse(&wif);
+        wif.zeroize();
+        parsed
+    }
+
+    /// The WIF string, e.g. to hand to a signing library
+    pub fn expose_secret(&self) -> &str {
+        &self.wif
+    }
+
+    /// First 8 bytes of the double SHA-256 of the key, in hex, for identifying it in logs
+    pub fn fingerprint(&self) -> String {
+        to_hex(&sha256(&sha256(self.key_bytes().as_slice()))[..8])
+    }
+
+    /// The 32-byte private key, wiped when the returned value is dropped
+    pub fn key_bytes(&self) -> Zeroizing<[u8; 32]> {
+        // Validated on construction
+        decode_wif(&self.wif).unwrap()
+    }
+
+    /// The matching public key with the address prefix `prefix`, e.g. `STM`
+    pub fn public_key(&self, prefix: &str) -> Result<String, String> {
+        let mut secret = self.secret_key()?;
+        let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &secret);
+        secret.non_secure_erase();
+        Ok(encode_public_key(&public.serialize(), prefix))
+    }
+
+    /// Sign `digest` with the canonical signature hived accepts, as hex
+    ///
+    /// Non-canonical signatures are retried with the same nonce data as dhive
+    /// and beem, so signing the same digest twice gives the same signature.
+    pub fn sign(&self, digest: &[u8; 32]) -> Result<String, String> {
+        let mut secret = self.secret_key()?;
+        let secp = Secp256k1::signing_only();
+        let message = Message::from_digest(*digest);
+        let mut signature = None;
+        for attempt in 1..=u8::MAX {
+            let mut nonce_data = digest.to_vec();
+            nonce_data.push(attempt);
+            let candidate =
+                secp.sign_ecdsa_recoverable_with_noncedata(&message, &secret, &sha256(&nonce_data));
+            if is_canonical(&candidate) {
+                signature = Some(candidate);
+                break;
+            }
+        }
+        secret.non_secure_erase();
+        let (recovery_id, compact) = signature
+            .ok_or_else(|| "Could not produce a canonical signature".to_string())?
+            .serialize_compact();
+        let mut bytes = vec![recovery_id.to_i32() as u8 + 31];
+        bytes.extend_from_slice(&compact);
+        Ok(to_hex(&bytes))
+    }
+
+    fn secret_key(&self) -> Result<SecretKey, String> {
+        SecretKey::from_slice(self.key_bytes().as_slice())
+            .map_err(|_| "Private key is out of range".to_string())
+    }
+}
+
+/// Whether hived accepts `signature`: both halves must be minimally encoded
+fn is_canonical(signature: &RecoverableSignature) -> bool {
+    let (_, compact) = signature.serialize_compact();
+    let half_ok = |h: &[u8]| h[0] & 0x80 == 0 && !(h[0] == 0 && h[1] & 0x80 == 0);
+    half_ok(&compact[..32]) && half_ok(&compact[32..])
+}
+
+impl TransactionSigner for SecretWif {
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<String, String> {
+        self.sign(digest)
+    }
+
+    fn key_fingerprint(&self) -> Option<String> {
+        Some(self.fingerprint())
+    }
+}
+
+impl fmt::Debug for SecretWif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretWif(<redacted>)")
+    }
+}
+
+impl PartialEq for SecretWif {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare without short-circuiting on the first differing byte
+        let (a, b) = (self.wif.as_bytes(), other.wif.as_bytes());
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+impl Eq for SecretWif {}
+
+impl<'de> Deserialize<'de> for SecretWif {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wif = String::deserialize(deserializer)?;
+        Self::from_string(wif).map_err(serde::de::Error::custom)
+    }
+}
+
+fn decode_wif(wif: &str) -> Result<Zeroizing<[u8; 32]>, String> {
+    let bytes = base58_decode(wif).ok_or_else(|| "Private key is not valid base58".to_string())?;
+    if bytes.len() != 37 || bytes[0] != WIF_VERSION {
+        return Err("Private key is not in WIF format".to_string());
+    }
+    let checksum = sha256(&sha256(&bytes[..33]));
+    if checksum[..4] != bytes[33..] {
+        return Err("Private key checksum does not match".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&bytes[1..33]);
+    Ok(key)
+}
+
+/// A [`TransactionSigner`] backed by a [`SecretWif`] and a signing function
+///
+/// `sign` receives the private key and the digest and returns the hex
+/// compact signature; the key is wiped as soon as it returns.
+pub struct WifSigner<F> {
+    key: SecretWif,
+    sign: F,
+}
+
+impl<F> WifSigner<F>
+where
+    F: Fn(&[u8; 32], &[u8; 32]) -> Result<String, String>,
+{
+    pub fn new(key: SecretWif, sign: F) -> Self {
+        Self { key, sign }
+    }
+}
+
+impl<F> TransactionSigner for WifSigner<F>
+where
+    F: Fn(&[u8; 32], &[u8; 32]) -> Result<String, String>,
+{
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<String, String> {
+        (self.sign)(&self.key.key_bytes(), digest)
+    }
+
+    fn key_fingerprint(&self) -> Option<String> {
+        Some(self.key.fingerprint())
+    }
+}
+
+impl<F> fmt::Debug for WifSigner<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WifSigner")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
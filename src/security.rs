@@ -0,0 +1,268 @@
+//! Account security audit: key reuse, recovery setup and recent authority changes
+
+use crate::timestamp::parse_timestamp;
+use crate::{Account, Client};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Authority changes newer than this are reported
+pub const RECENT_CHANGE_SECS: i64 = 30 * 86_400;
+
+/// Recovery accounts without activity for this long are considered dead
+pub const INACTIVE_RECOVERY_SECS: i64 = 365 * 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindingKind {
+    /// The owner key also appears in the active or posting authority
+    OwnerKeyShared {
+        role: String,
+        key: String,
+    },
+    /// A key is used by several roles (including the memo key)
+    KeyReused {
+        key: String,
+        roles: Vec<String>,
+    },
+    RecoveryAccountUnset,
+    /// The recovery account is the account itself
+    RecoveryAccountSelf,
+    RecoveryAccountMissing {
+        recovery_account: String,
+    },
+    RecoveryAccountInactive {
+        recovery_account: String,
+        last_activity: String,
+    },
+    PendingRecoveryRequest {
+        expires: String,
+    },
+    PendingRecoveryAccountChange {
+        recovery_account: String,
+        effective_on: String,
+    },
+    OwnerRecentlyChanged {
+        at: String,
+    },
+    AccountRecentlyUpdated {
+        at: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityFinding {
+    pub severity: Severity,
+    pub kind: FindingKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityAudit {
+    pub account: String,
+    pub findings: Vec<SecurityFinding>,
+}
+
+impl SecurityAudit {
+    /// Highest severity among the findings, if any
+    pub fn worst(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RecoveryRequest {
+    expires: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecoveryRequestsResponse {
+    requests: Vec<RecoveryRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangeRecoveryRequest {
+    recovery_account: String,
+    effective_on: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChangeRecoveryRequestsResponse {
+    requests: Vec<ChangeRecoveryRequest>,
+}
+
+/// Report keys shared between roles of an account
+pub fn audit_keys(account: &Account) -> Vec<SecurityFinding> {
+    let mut findings = Vec::new();
+    let mut roles: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (role, authority) in [
+        ("owner", &account.owner),
+        ("active", &account.active),
+        ("posting", &account.posting),
+    ] {
+        for key in authority.keys() {
+            roles.entry(key).or_default().push(role.to_string());
+        }
+    }
+    if !account.memo_key.is_empty() {
+        roles
+            .entry(account.memo_key.as_str())
+            .or_default()
+            .push("memo".to_string());
+    }
+    for (key, key_roles) in roles {
+        if key_roles.len() < 2 {
+            continue;
+        }
+        if key_roles[0] == "owner" {
+            for role in &key_roles[1..] {
+                findings.push(SecurityFinding {
+                    severity: Severity::Critical,
+                    kind: FindingKind::OwnerKeyShared {
+                        role: role.clone(),
+                        key: key.to_string(),
+                    },
+                    message: format!("Owner key {key} is also used as the {role} key"),
+                });
+            }
+        }
+        findings.push(SecurityFinding {
+            severity: Severity::Warning,
+            message: format!("Key {key} is reused for {}", key_roles.join(", ")),
+            kind: FindingKind::KeyReused {
+                key: key.to_string(),
+                roles: key_roles,
+            },
+        });
+    }
+    findings
+}
+
+fn last_activity(account: &Account) -> Option<(i64, &str)> {
+    [
+        &account.last_post,
+        &account.last_vote_time,
+        &account.last_account_update,
+        &account.created,
+    ]
+    .into_iter()
+    .filter_map(|t| parse_timestamp(t).ok().map(|secs| (secs, t.as_str())))
+    .max_by_key(|(secs, _)| *secs)
+}
+
+impl Client {
+    /// Audit an account for weak key and recovery setups
+    pub fn audit_account_security(&self, name: &str) -> Result<SecurityAudit, String> {
+        let account = self
+            .find_accounts(&[name])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Account '{name}' not found"))?;
+        let now = parse_timestamp(&self.get_dynamic_global_properties()?.time)?;
+        let mut findings = audit_keys(&account);
+
+        let recovery = account.recovery_account.as_str();
+        if recovery.is_empty() {
+            findings.push(SecurityFinding {
+                severity: Severity::Critical,
+                kind: FindingKind::RecoveryAccountUnset,
+                message: "No recovery account is set".to_string(),
+            });
+        } else if recovery == name {
+            findings.push(SecurityFinding {
+                severity: Severity::Warning,
+                kind: FindingKind::RecoveryAccountSelf,
+                message: "The account is its own recovery account".to_string(),
+            });
+        } else {
+            match self.find_accounts(&[recovery])?.into_iter().next() {
+                None => findings.push(SecurityFinding {
+                    severity: Severity::Critical,
+                    kind: FindingKind::RecoveryAccountMissing {
+                        recovery_account: recovery.to_string(),
+                    },
+                    message: format!("Recovery account @{recovery} does not exist"),
+                }),
+                Some(recovery_account) => match last_activity(&recovery_account) {
+                    Some((secs, at)) if now - secs > INACTIVE_RECOVERY_SECS => {
+                        findings.push(SecurityFinding {
+                            severity: Severity::Warning,
+                            kind: FindingKind::RecoveryAccountInactive {
+                                recovery_account: recovery.to_string(),
+                                last_activity: at.to_string(),
+                            },
+                            message: format!(
+                                "Recovery account @{recovery} has been inactive since {at}"
+                            ),
+                        })
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        let params = serde_json::json!({ "accounts": [name] });
+        let requests: RecoveryRequestsResponse = self
+            .call("database_api.find_account_recovery_requests", &params)
+            .map_err(|e| format!("Error fetching recovery requests: {e}"))?;
+        for request in requests.requests {
+            findings.push(SecurityFinding {
+                severity: Severity::Critical,
+                message: format!("Account recovery request pending until {}", request.expires),
+                kind: FindingKind::PendingRecoveryRequest {
+                    expires: request.expires,
+                },
+            });
+        }
+        let changes: ChangeRecoveryRequestsResponse = self
+            .call(
+                "database_api.find_change_recovery_account_requests",
+                &params,
+            )
+            .map_err(|e| format!("Error fetching recovery account changes: {e}"))?;
+        for change in changes.requests {
+            findings.push(SecurityFinding {
+                severity: Severity::Warning,
+                message: format!(
+                    "Recovery account changes to @{} on {}",
+                    change.recovery_account, change.effective_on
+                ),
+                kind: FindingKind::PendingRecoveryAccountChange {
+                    recovery_account: change.recovery_account,
+                    effective_on: change.effective_on,
+                },
+            });
+        }
+
+        let recent = |t: &str| parse_timestamp(t).is_ok_and(|secs| now - secs < RECENT_CHANGE_SECS);
+        if recent(&account.last_owner_update) {
+            findings.push(SecurityFinding {
+                severity: Severity::Warning,
+                message: format!("Owner authority changed on {}", account.last_owner_update),
+                kind: FindingKind::OwnerRecentlyChanged {
+                    at: account.last_owner_update.clone(),
+                },
+            });
+        }
+        if recent(&account.last_account_update) {
+            findings.push(SecurityFinding {
+                severity: Severity::Info,
+                message: format!("Account updated on {}", account.last_account_update),
+                kind: FindingKind::AccountRecentlyUpdated {
+                    at: account.last_account_update.clone(),
+                },
+            });
+        }
+
+        findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+        Ok(SecurityAudit {
+            account: name.to_string(),
+            findings,
+        })
+    }
+}
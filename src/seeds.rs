@@ -0,0 +1,53 @@
+//! A built-in list of public nodes to start from
+//!
+//! [`Client::with_default_seeds`] starts with these nodes, so a client can
+//! fetch the published node list without knowing a node up front, and falls
+//! back to them when every published node is failing.
+//!
+//! Set the `NECTARFLOWER_SEED_NODES` environment variable to a
+//! comma-separated list of URLs while building to replace the defaults.
+
+use crate::node_policy::EmptyNodePolicy;
+use crate::Client;
+
+/// The curated default seed nodes
+pub const DEFAULT_SEED_NODES: &[&str] = &[
+    "https://api.hive.blog",
+    "https://api.deathwing.me",
+    "https://anyx.io",
+    "https://api.openhive.network",
+    "https://techcoderx.com",
+    "https://hive-api.arcange.eu",
+    "https://rpc.mahdiyari.info",
+    "https://api.syncad.com",
+];
+
+/// The seed nodes compiled in: `NECTARFLOWER_SEED_NODES` if set at build time,
+/// otherwise [`DEFAULT_SEED_NODES`]
+pub fn seed_nodes() -> Vec<String> {
+    let custom: Vec<String> = option_env!("NECTARFLOWER_SEED_NODES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|node| !node.is_empty())
+        .map(str::to_string)
+        .collect();
+    if custom.is_empty() {
+        DEFAULT_SEED_NODES
+            .iter()
+            .map(|node| node.to_string())
+            .collect()
+    } else {
+        custom
+    }
+}
+
+impl Client {
+    /// Use the [seed nodes](seed_nodes), and fall back to them when no
+    /// published node is usable
+    pub fn with_default_seeds(mut self) -> Self {
+        let seeds = seed_nodes();
+        self.nodes.clone_from(&seeds);
+        self.on_empty_node_list(EmptyNodePolicy::Fallback(seeds))
+    }
+}
@@ -0,0 +1,637 @@
+//! Local binary serialization of transactions in hived's wire format
+//!
+//! Covers the commonly broadcast operations. Signing always uses these bytes;
+//! [`Client::verify_transaction_bytes`](crate::Client::verify_transaction_bytes)
+//! compares them with a node's serialization.
+
+use crate::asset::Asset;
+use crate::chain::ChainConfig;
+use crate::crypto::from_hex;
+use crate::operation::Operation;
+use crate::timestamp::parse_timestamp;
+use crate::transaction::Transaction;
+use serde_json::Value;
+
+/// Index of each supported operation in hived's operation variant
+pub(crate) const OPERATION_IDS: &[(&str, u32)] = &[
+    ("vote", 0),
+    ("comment", 1),
+    ("transfer", 2),
+    ("transfer_to_vesting", 3),
+    ("withdraw_vesting", 4),
+    ("limit_order_create", 5),
+    ("limit_order_cancel", 6),
+    ("feed_publish", 7),
+    ("convert", 8),
+    ("account_update", 10),
+    ("witness_update", 11),
+    ("account_witness_vote", 12),
+    ("account_witness_proxy", 13),
+    ("delete_comment", 17),
+    ("custom_json", 18),
+    ("comment_options", 19),
+    ("set_withdraw_vesting_route", 20),
+    ("claim_account", 22),
+    ("change_recovery_account", 26),
+    ("transfer_to_savings", 32),
+    ("transfer_from_savings", 33),
+    ("cancel_transfer_from_savings", 34),
+    ("claim_reward_balance", 39),
+    ("delegate_vesting_shares", 40),
+    ("witness_set_properties", 42),
+    ("account_update2", 43),
+    ("update_proposal_votes", 45),
+    ("remove_proposal", 46),
+    ("collateralized_convert", 48),
+    ("recurrent_transfer", 49),
+];
+
+/// hived's numeric id for an operation name (without the `_operation` suffix)
+pub fn operation_id(name: &str) -> Option<u32> {
+    OPERATION_IDS
+        .iter()
+        .find(|(op, _)| *op == name)
+        .map(|(_, id)| *id)
+}
+
+/// Names of the operations this module serializes, without the `_operation` suffix
+pub fn supported_operations() -> impl Iterator<Item = &'static str> {
+    OPERATION_IDS.iter().map(|(op, _)| *op)
+}
+
+/// Appends values to a buffer in hived's binary encoding
+#[derive(Debug, Default)]
+pub struct Serializer {
+    buf: Vec<u8>,
+    chain: ChainConfig,
+}
+
+impl Serializer {
+    /// A serializer for the main network's asset names and key prefix
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A serializer for `chain`'s asset names and key prefix
+    pub fn for_chain(chain: &ChainConfig) -> Self {
+        Self {
+            buf: Vec::new(),
+            chain: chain.clone(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn i16(&mut self, v: i16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn i64(&mut self, v: i64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn bool(&mut self, v: bool) {
+        self.buf.push(u8::from(v));
+    }
+
+    /// Unsigned LEB128, used for lengths and variant indexes
+    pub fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8 & 0x7f) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) {
+        self.buf.extend_from_slice(v);
+    }
+
+    pub fn string(&mut self, v: &str) {
+        self.varint(v.len() as u64);
+        self.bytes(v.as_bytes());
+    }
+
+    /// Seconds since the Unix epoch as `u32`
+    pub fn time(&mut self, v: &str) -> Result<(), String> {
+        let secs = parse_timestamp(v)?;
+        let secs = u32::try_from(secs).map_err(|_| format!("Timestamp out of range: {v}"))?;
+        self.u32(secs);
+        Ok(())
+    }
+
+    /// Amount, precision and the chain's legacy 7-byte symbol name, e.g.
+    /// `STEEM`, `SBD` and `VESTS` on the main network
+    pub fn asset(&mut self, v: &Asset) {
+        self.i64(v.amount);
+        self.u8(v.symbol.precision());
+        let name = self.chain.wire_symbol(v.symbol).as_bytes();
+        let mut padded = [0u8; 7];
+        let len = name.len().min(padded.len());
+        padded[..len].copy_from_slice(&name[..len]);
+        self.bytes(&padded);
+    }
+
+    /// A public key with the chain's prefix, as its 33 compressed bytes
+    pub fn public_key(&mut self, key: &str) -> Result<(), String> {
+        let bytes = self.chain.decode_public_key(key)?;
+        self.bytes(&bytes);
+        Ok(())
+    }
+
+    /// An empty `future_extensions` list
+    pub fn no_extensions(&mut self) {
+        self.varint(0);
+    }
+
+    /// Serialize an operation with its variant index
+    pub fn operation(&mut self, op: &Operation) -> Result<(), String> {
+        let name = op.name();
+        let id = operation_id(name)
+            .ok_or_else(|| format!("Local serialization of {name} operations is not supported"))?;
+        self.varint(u64::from(id));
+        let chain = self.chain.clone();
+        let f = Fields {
+            op,
+            name,
+            chain: &chain,
+        };
+        match name {
+            "vote" => {
+                self.string(f.str("voter")?);
+                self.string(f.str("author")?);
+                self.string(f.str("permlink")?);
+                self.i16(f.int("weight")?);
+            }
+            "comment" => {
+                for field in [
+                    "parent_author",
+                    "parent_permlink",
+                    "author",
+                    "permlink",
+                    "title",
+                    "body",
+                    "json_metadata",
+                ] {
+                    self.string(f.str(field)?);
+                }
+            }
+            "transfer" | "transfer_to_savings" => {
+                self.string(f.str("from")?);
+                self.string(f.str("to")?);
+                self.asset(&f.asset("amount")?);
+                self.string(f.str("memo")?);
+            }
+            "transfer_to_vesting" => {
+                self.string(f.str("from")?);
+                self.string(f.str("to")?);
+                self.asset(&f.asset("amount")?);
+            }
+            "withdraw_vesting" => {
+                self.string(f.str("account")?);
+                self.asset(&f.asset("vesting_shares")?);
+            }
+            "limit_order_create" => {
+                self.string(f.str("owner")?);
+                self.u32(f.int("orderid")?);
+                self.asset(&f.asset("amount_to_sell")?);
+                self.asset(&f.asset("min_to_receive")?);
+                self.bool(f.bool("fill_or_kill")?);
+                self.time(f.str("expiration")?)?;
+            }
+            "limit_order_cancel" => {
+                self.string(f.str("owner")?);
+                self.u32(f.int("orderid")?);
+            }
+            "feed_publish" => {
+                self.string(f.str("publisher")?);
+                let rate = f.get("exchange_rate")?;
+                for side in ["base", "quote"] {
+                    let value = rate
+                        .get(side)
+                        .ok_or_else(|| format!("Missing exchange_rate.{side} in {name}"))?;
+                    self.asset(&chain.asset_from_value(value)?);
+                }
+            }
+            "convert" | "collateralized_convert" => {
+                self.string(f.str("owner")?);
+                self.u32(f.int("requestid")?);
+                self.asset(&f.asset("amount")?);
+            }
+            "account_update" => {
+                self.string(f.str("account")?);
+                for field in ["owner", "active", "posting"] {
+                    self.optional_authority(&f, field)?;
+                }
+                self.public_key(f.str("memo_key")?)?;
+                self.string(f.str("json_metadata")?);
+            }
+            "account_update2" => {
+                self.string(f.str("account")?);
+                for field in ["owner", "active", "posting"] {
+                    self.optional_authority(&f, field)?;
+                }
+                match f.opt_str("memo_key")? {
+                    Some(key) => {
+                        self.bool(true);
+                        self.public_key(key)?;
+                    }
+                    None => self.bool(false),
+                }
+                self.string(f.str("json_metadata")?);
+                self.string(f.str("posting_json_metadata")?);
+                f.no_extensions()?;
+                self.no_extensions();
+            }
+            "account_witness_vote" => {
+                self.string(f.str("account")?);
+                self.string(f.str("witness")?);
+                self.bool(f.bool("approve")?);
+            }
+            "account_witness_proxy" => {
+                self.string(f.str("account")?);
+                self.string(f.str("proxy")?);
+            }
+            "delete_comment" => {
+                self.string(f.str("author")?);
+                self.string(f.str("permlink")?);
+            }
+            "custom_json" => {
+                self.string_set(f.str_list("required_auths")?);
+                self.string_set(f.str_list("required_posting_auths")?);
+                self.string(f.str("id")?);
+                self.string(f.str("json")?);
+            }
+            "comment_options" => {
+                self.string(f.str("author")?);
+                self.string(f.str("permlink")?);
+                self.asset(&f.asset("max_accepted_payout")?);
+                self.u16(f.int("percent_hbd")?);
+                self.bool(f.bool("allow_votes")?);
+                self.bool(f.bool("allow_curation_rewards")?);
+                self.comment_options_extensions(f.get("extensions")?)?;
+            }
+            "set_withdraw_vesting_route" => {
+                self.string(f.str("from_account")?);
+                self.string(f.str("to_account")?);
+                self.u16(f.int("percent")?);
+                self.bool(f.bool("auto_vest")?);
+            }
+            "claim_account" => {
+                self.string(f.str("creator")?);
+                self.asset(&f.asset("fee")?);
+                self.no_extensions();
+            }
+            "change_recovery_account" => {
+                self.string(f.str("account_to_recover")?);
+                self.string(f.str("new_recovery_account")?);
+                self.no_extensions();
+            }
+            "transfer_from_savings" => {
+                self.string(f.str("from")?);
+                self.u32(f.int("request_id")?);
+                self.string(f.str("to")?);
+                self.asset(&f.asset("amount")?);
+                self.string(f.str("memo")?);
+            }
+            "cancel_transfer_from_savings" => {
+                self.string(f.str("from")?);
+                self.u32(f.int("request_id")?);
+            }
+            "claim_reward_balance" => {
+                self.string(f.str("account")?);
+                self.asset(&f.asset("reward_hive")?);
+                self.asset(&f.asset("reward_hbd")?);
+                self.asset(&f.asset("reward_vests")?);
+            }
+            "witness_update" => {
+                self.string(f.str("owner")?);
+                self.string(f.str("url")?);
+                self.public_key(f.str("block_signing_key")?)?;
+                let props = f.get("props")?;
+                let prop = |field: &str| {
+                    props
+                        .get(field)
+                        .ok_or_else(|| format!("Missing props.{field} in {name}"))
+                };
+                self.asset(&chain.asset_from_value(prop("account_creation_fee")?)?);
+                let int = |field: &str| {
+                    prop(field)?
+                        .as_u64()
+                        .ok_or_else(|| format!("Field props.{field} of {name} must be an integer"))
+                };
+                let block_size = int("maximum_block_size")?;
+                self.u32(u32::try_from(block_size).map_err(|_| "Block size out of range")?);
+                let interest = int("hbd_interest_rate")?;
+                self.u16(u16::try_from(interest).map_err(|_| "Interest rate out of range")?);
+                self.asset(&f.asset("fee")?);
+            }
+            "witness_set_properties" => {
+                self.string(f.str("owner")?);
+                let props = f
+                    .get("props")?
+                    .as_array()
+                    .ok_or_else(|| format!("Field props of {name} must be a list"))?;
+                self.varint(props.len() as u64);
+                for prop in props {
+                    let (Some(key), Some(value)) = (
+                        prop.get(0).and_then(Value::as_str),
+                        prop.get(1).and_then(Value::as_str),
+                    ) else {
+                        return Err(format!("Invalid {name} property: {prop}"));
+                    };
+                    let value = from_hex(value)?;
+                    self.string(key);
+                    self.varint(value.len() as u64);
+                    self.bytes(&value);
+                }
+                self.no_extensions();
+            }
+            "delegate_vesting_shares" => {
+                self.string(f.str("delegator")?);
+                self.string(f.str("delegatee")?);
+                self.asset(&f.asset("vesting_shares")?);
+            }
+            "update_proposal_votes" => {
+                self.string(f.str("voter")?);
+                self.int_set(f.int_list("proposal_ids")?);
+                self.bool(f.bool("approve")?);
+                self.no_extensions();
+            }
+            "remove_proposal" => {
+                self.string(f.str("proposal_owner")?);
+                self.int_set(f.int_list("proposal_ids")?);
+                self.no_extensions();
+            }
+            "recurrent_transfer" => {
+                self.string(f.str("from")?);
+                self.string(f.str("to")?);
+                self.asset(&f.asset("amount")?);
+                self.string(f.str("memo")?);
+                self.u16(f.int("recurrence")?);
+                self.u16(f.int("executions")?);
+                self.no_extensions();
+            }
+            _ => unreachable!("operation id table and serializer are out of sync"),
+        }
+        Ok(())
+    }
+
+    /// An `optional<authority>`, absent when the field is missing or null
+    fn optional_authority(&mut self, f: &Fields, field: &str) -> Result<(), String> {
+        let Some(authority) = f.opt(field) else {
+            self.bool(false);
+            return Ok(());
+        };
+        self.bool(true);
+        let name = f.name;
+        let part = |key: &str| {
+            authority
+                .get(key)
+                .ok_or_else(|| format!("Missing {field}.{key} in {name}"))
+        };
+        let threshold = part("weight_threshold")?
+            .as_u64()
+            .and_then(|t| u32::try_from(t).ok())
+            .ok_or_else(|| format!("Invalid {field}.weight_threshold in {name}"))?;
+        self.u32(threshold);
+        // Both are flat_maps in hived, serialized in key order
+        let mut accounts = weighted(part("account_auths")?, field, name)?;
+        accounts.sort_by(|a, b| a.0.cmp(b.0));
+        let mut keys = weighted(part("key_auths")?, field, name)?
+            .into_iter()
+            .map(|(key, weight)| Ok((self.chain.decode_public_key(key)?, key, weight)))
+            .collect::<Result<Vec<_>, String>>()?;
+        keys.sort_by_key(|k| k.0);
+        if let Some(pair) = accounts.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!(
+                "Duplicate account {} in {field} of {name}",
+                pair[0].0
+            ));
+        }
+        if let Some(pair) = keys.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!("Duplicate key {} in {field} of {name}", pair[0].1));
+        }
+        self.varint(accounts.len() as u64);
+        for (account, weight) in accounts {
+            self.string(account);
+            self.u16(weight);
+        }
+        self.varint(keys.len() as u64);
+        for (key, _, weight) in keys {
+            self.bytes(&key);
+            self.u16(weight);
+        }
+        Ok(())
+    }
+
+    /// A `flat_set`, which hived keeps sorted and free of duplicates
+    fn string_set(&mut self, mut v: Vec<&str>) {
+        v.sort_unstable();
+        v.dedup();
+        self.varint(v.len() as u64);
+        for s in v {
+            self.string(s);
+        }
+    }
+
+    /// A `flat_set` of integers, sorted and free of duplicates like hived's
+    fn int_set(&mut self, mut v: Vec<i64>) {
+        v.sort_unstable();
+        v.dedup();
+        self.varint(v.len() as u64);
+        for i in v {
+            self.i64(i);
+        }
+    }
+
+    fn comment_options_extensions(&mut self, extensions: &Value) -> Result<(), String> {
+        let extensions = extensions.as_array().cloned().unwrap_or_default();
+        self.varint(extensions.len() as u64);
+        for extension in &extensions {
+            let (kind, value) = match extension {
+                Value::Array(pair) if pair.len() == 2 => (pair[0].clone(), pair[1].clone()),
+                Value::Object(obj) => (
+                    obj.get("type").cloned().unwrap_or_default(),
+                    obj.get("value").cloned().unwrap_or_default(),
+                ),
+                other => return Err(format!("Invalid comment_options extension: {other}")),
+            };
+            let known =
+                kind.as_str() == Some("comment_payout_beneficiaries") || kind.as_u64() == Some(0);
+            if !known {
+                return Err(format!("Unsupported comment_options extension: {kind}"));
+            }
+            let beneficiaries = value
+                .get("beneficiaries")
+                .and_then(Value::as_array)
+                .ok_or_else(|| "Missing beneficiaries in comment_options extension".to_string())?;
+            self.varint(0);
+            self.varint(beneficiaries.len() as u64);
+            for beneficiary in beneficiaries {
+                let account = beneficiary
+                    .get("account")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| "Missing beneficiary account".to_string())?;
+                let weight = beneficiary
+                    .get("weight")
+                    .and_then(Value::as_u64)
+                    .and_then(|w| u16::try_from(w).ok())
+                    .ok_or_else(|| format!("Invalid beneficiary weight for {account}"))?;
+                self.string(account);
+                self.u16(weight);
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize a transaction without its signatures
+    pub fn transaction(&mut self, tx: &Transaction) -> Result<(), String> {
+        self.u16(tx.ref_block_num);
+        self.u32(tx.ref_block_prefix);
+        self.time(&tx.expiration)?;
+        self.varint(tx.operations.len() as u64);
+        for op in &tx.operations {
+            self.operation(op)?;
+        }
+        if !tx.extensions.is_empty() {
+            return Err("Transaction extensions are not supported".to_string());
+        }
+        self.no_extensions();
+        Ok(())
+    }
+}
+
+/// Typed access to the fields of an operation value, with descriptive errors
+struct Fields<'a> {
+    op: &'a Operation,
+    name: &'a str,
+    chain: &'a ChainConfig,
+}
+
+impl<'a> Fields<'a> {
+    /// A field that may be left out, or null
+    fn opt(&self, field: &str) -> Option<&'a Value> {
+        self.op.value.get(field).filter(|v| !v.is_null())
+    }
+
+    fn opt_str(&self, field: &str) -> Result<Option<&'a str>, String> {
+        self.opt(field)
+            .map(|v| {
+                v.as_str()
+                    .ok_or_else(|| format!("Field {field} of {} must be a string", self.name))
+            })
+            .transpose()
+    }
+
+    /// Check that `extensions`, if given, is empty
+    fn no_extensions(&self) -> Result<(), String> {
+        match self.opt("extensions") {
+            None => Ok(()),
+            Some(Value::Array(items)) if items.is_empty() => Ok(()),
+            Some(_) => Err(format!(
+                "Extensions of {} operations are not supported",
+                self.name
+            )),
+        }
+    }
+
+    fn get(&self, field: &str) -> Result<&'a Value, String> {
+        self.op
+            .value
+            .get(field)
+            .ok_or_else(|| format!("Missing {field} in {} operation", self.name))
+    }
+
+    fn str(&self, field: &str) -> Result<&'a str, String> {
+        self.get(field)?
+            .as_str()
+            .ok_or_else(|| format!("Field {field} of {} must be a string", self.name))
+    }
+
+    fn bool(&self, field: &str) -> Result<bool, String> {
+        self.get(field)?
+            .as_bool()
+            .ok_or_else(|| format!("Field {field} of {} must be a boolean", self.name))
+    }
+
+    fn int<T: TryFrom<i64>>(&self, field: &str) -> Result<T, String> {
+        let invalid = || format!("Field {field} of {} is not a valid integer", self.name);
+        let value = match self.get(field)? {
+            Value::Number(n) => n.as_i64().ok_or_else(invalid)?,
+            Value::String(s) => s.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        };
+        T::try_from(value).map_err(|_| invalid())
+    }
+
+    /// An amount, whose legacy string must use the chain's symbol names
+    fn asset(&self, field: &str) -> Result<Asset, String> {
+        self.chain.asset_from_value(self.get(field)?)
+    }
+
+    fn str_list(&self, field: &str) -> Result<Vec<&'a str>, String> {
+        let invalid = || format!("Field {field} of {} must be a list of strings", self.name);
+        self.get(field)?
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|v| v.as_str().ok_or_else(invalid))
+            .collect()
+    }
+
+    fn int_list(&self, field: &str) -> Result<Vec<i64>, String> {
+        let invalid = || format!("Field {field} of {} must be a list of integers", self.name);
+        self.get(field)?
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|v| match v {
+                Value::Number(n) => n.as_i64().ok_or_else(invalid),
+                Value::String(s) => s.parse().map_err(|_| invalid()),
+                _ => Err(invalid()),
+            })
+            .collect()
+    }
+}
+
+/// The `[name, weight]` pairs of an authority's `account_auths` or `key_auths`
+fn weighted<'a>(value: &'a Value, field: &str, name: &str) -> Result<Vec<(&'a str, u16)>, String> {
+    let invalid = || format!("Invalid {field} authority in {name}: {value}");
+    value
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|pair| match pair.as_array().map(Vec::as_slice) {
+            Some([key, weight]) => Ok((
+                key.as_str().ok_or_else(invalid)?,
+                weight
+                    .as_u64()
+                    .and_then(|w| u16::try_from(w).ok())
+                    .ok_or_else(invalid)?,
+            )),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+/// Serialize a transaction without its signatures for `chain`
+pub fn transaction_bytes(tx: &Transaction, chain: &ChainConfig) -> Result<Vec<u8>, String> {
+    let mut s = Serializer::for_chain(chain);
+    s.transaction(tx)?;
+    Ok(s.into_bytes())
+}
@@ -0,0 +1,112 @@
+//! Asking the node which keys a transaction needs
+//!
+//! `database_api.get_potential_signatures` lists every key that could sign a
+//! transaction and `get_required_signatures` picks the ones needed from the
+//! keys on hand. A [`SignerSet`] holds signers by public key, so
+//! [`WriteClient::sign_required`] signs with exactly those.
+
+use crate::transaction::{Transaction, TransactionSigner};
+use crate::{Client, WriteClient, WriteError};
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Debug, Deserialize)]
+struct KeysResponse {
+    keys: Vec<String>,
+}
+
+impl Client {
+    /// The public keys among `available_keys` needed to authorize `tx`
+    pub fn get_required_signatures(
+        &self,
+        tx: &Transaction,
+        available_keys: &[&str],
+    ) -> Result<Vec<String>, String> {
+        let resp: KeysResponse = self
+            .call(
+                "database_api.get_required_signatures",
+                serde_json::json!({ "trx": tx, "available_keys": available_keys }),
+            )
+            .map_err(|e| format!("Error fetching required signatures: {e}"))?;
+        Ok(resp.keys)
+    }
+
+    /// Every public key that could take part in authorizing `tx`
+    pub fn get_potential_signatures(&self, tx: &Transaction) -> Result<Vec<String>, String> {
+        let resp: KeysResponse = self
+            .call(
+                "database_api.get_potential_signatures",
+                serde_json::json!({ "trx": tx }),
+            )
+            .map_err(|e| format!("Error fetching potential signatures: {e}"))?;
+        Ok(resp.keys)
+    }
+}
+
+/// Signers by the public key they sign for
+#[derive(Default)]
+pub struct SignerSet<'a> {
+    signers: Vec<(String, &'a dyn TransactionSigner)>,
+}
+
+impl fmt::Debug for SignerSet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.public_keys()).finish()
+    }
+}
+
+impl<'a> SignerSet<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the signer for `public_key`, replacing any earlier one
+    pub fn with(mut self, public_key: &str, signer: &'a dyn TransactionSigner) -> Self {
+        self.signers.retain(|(key, _)| key != public_key);
+        self.signers.push((public_key.to_string(), signer));
+        self
+    }
+
+    pub fn public_keys(&self) -> impl Iterator<Item = &str> {
+        self.signers.iter().map(|(key, _)| key.as_str())
+    }
+
+    pub fn get(&self, public_key: &str) -> Option<&'a dyn TransactionSigner> {
+        self.signers
+            .iter()
+            .find(|(key, _)| key == public_key)
+            .map(|(_, signer)| *signer)
+    }
+
+    /// The signers for `keys`, failing on the first key without one
+    pub fn select(&self, keys: &[String]) -> Result<Vec<&'a dyn TransactionSigner>, String> {
+        keys.iter()
+            .map(|key| {
+                self.get(key)
+                    .ok_or_else(|| format!("No signer for required key {key}"))
+            })
+            .collect()
+    }
+}
+
+impl WriteClient {
+    /// Sign `tx` with only the signers the node says are required
+    ///
+    /// Returns the transaction id. Fails if the keys in `signers` cannot
+    /// authorize the transaction.
+    pub fn sign_required(
+        &self,
+        tx: &mut Transaction,
+        signers: &SignerSet<'_>,
+    ) -> Result<String, WriteError> {
+        let available: Vec<&str> = signers.public_keys().collect();
+        let required = self.get_required_signatures(tx, &available)?;
+        if required.is_empty() {
+            return Err(WriteError::Failed(
+                "None of the available keys can sign this transaction".to_string(),
+            ));
+        }
+        let selected = signers.select(&required)?;
+        self.sign_transaction(tx, &selected)
+    }
+}
@@ -0,0 +1,219 @@
+//! Cached author profiles: reputation, follower counts and profile metadata
+
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default time a cached profile stays fresh
+pub const DEFAULT_SOCIAL_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileStats {
+    #[serde(default)]
+    pub followers: u32,
+    #[serde(default)]
+    pub following: u32,
+    #[serde(default)]
+    pub rank: u32,
+}
+
+/// User-facing profile fields from `posting_json_metadata`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileMetadata {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub about: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub website: String,
+    #[serde(default)]
+    pub profile_image: String,
+    #[serde(default)]
+    pub cover_image: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProfileMetadataWrapper {
+    #[serde(default)]
+    profile: ProfileMetadata,
+}
+
+/// An account profile as returned by `bridge.get_profile`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub created: String,
+    #[serde(default)]
+    pub post_count: u32,
+    /// Display reputation, e.g. `65.3`
+    #[serde(default)]
+    pub reputation: f64,
+    #[serde(default)]
+    pub stats: ProfileStats,
+    #[serde(default, rename = "metadata", with = "profile_metadata")]
+    pub profile: ProfileMetadata,
+}
+
+mod profile_metadata {
+    use super::{ProfileMetadata, ProfileMetadataWrapper};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &ProfileMetadata,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ProfileMetadataWrapper {
+            profile: value.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ProfileMetadata, D::Error> {
+        Ok(ProfileMetadataWrapper::deserialize(deserializer)?.profile)
+    }
+}
+
+#[derive(Debug)]
+struct CachedProfile {
+    profile: Profile,
+    fetched: Instant,
+}
+
+/// Profiles cached by a [`Client`], shared by every [`SocialCache`] handle
+#[derive(Debug)]
+pub(crate) struct SocialStore {
+    ttl: Mutex<Duration>,
+    profiles: Mutex<HashMap<String, CachedProfile>>,
+}
+
+impl Default for SocialStore {
+    fn default() -> Self {
+        Self {
+            ttl: Mutex::new(DEFAULT_SOCIAL_TTL),
+            profiles: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Handle to a client's profile cache, created by [`Client::social_cache`]
+#[derive(Debug, Clone, Copy)]
+pub struct SocialCache<'a> {
+    client: &'a Client,
+    store: &'a SocialStore,
+}
+
+impl SocialCache<'_> {
+    pub fn ttl(&self) -> Duration {
+        *self.store.ttl.lock().unwrap()
+    }
+
+    /// Change how long profiles stay fresh; applies to already cached entries too
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.store.ttl.lock().unwrap() = ttl;
+    }
+
+    /// A cached profile if it is still fresh
+    pub fn cached(&self, account: &str) -> Option<Profile> {
+        let ttl = self.ttl();
+        let profiles = self.store.profiles.lock().unwrap();
+        profiles
+            .get(account)
+            .filter(|cached| cached.fetched.elapsed() < ttl)
+            .map(|cached| cached.profile.clone())
+    }
+
+    /// The profile of `account`, fetched only if not cached or expired
+    pub fn profile(&self, account: &str) -> Result<Profile, String> {
+        if let Some(profile) = self.cached(account) {
+            return Ok(profile);
+        }
+        let profile = self.client.get_profile(account)?;
+        self.insert(profile.clone());
+        Ok(profile)
+    }
+
+    /// Display reputation of `account`
+    pub fn reputation(&self, account: &str) -> Result<f64, String> {
+        Ok(self.profile(account)?.reputation)
+    }
+
+    /// `(followers, following)` counts of `account`
+    pub fn follow_counts(&self, account: &str) -> Result<(u32, u32), String> {
+        let stats = self.profile(account)?.stats;
+        Ok((stats.followers, stats.following))
+    }
+
+    /// Fetch every account not already fresh in the cache, returning those that failed
+    pub fn warm(&self, accounts: &[&str]) -> Vec<(String, String)> {
+        let mut errors = Vec::new();
+        for account in accounts {
+            if let Err(e) = self.profile(account) {
+                errors.push((account.to_string(), e));
+            }
+        }
+        errors
+    }
+
+    /// Drop one account so the next lookup refetches it
+    pub fn invalidate(&self, account: &str) {
+        self.store.profiles.lock().unwrap().remove(account);
+    }
+
+    /// Drop expired entries
+    pub fn prune(&self) {
+        let ttl = self.ttl();
+        self.store
+            .profiles
+            .lock()
+            .unwrap()
+            .retain(|_, cached| cached.fetched.elapsed() < ttl);
+    }
+
+    pub fn clear(&self) {
+        self.store.profiles.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.profiles.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&self, profile: Profile) {
+        self.store.profiles.lock().unwrap().insert(
+            profile.name.clone(),
+            CachedProfile {
+                profile,
+                fetched: Instant::now(),
+            },
+        );
+    }
+}
+
+impl Client {
+    /// Fetch an account's hivemind profile
+    pub fn get_profile(&self, account: &str) -> Result<Profile, String> {
+        self.call(
+            "bridge.get_profile",
+            serde_json::json!({ "account": account }),
+        )
+        .map_err(|e| format!("Error fetching profile of {account}: {e}"))
+    }
+
+    /// The in-process profile cache shared by all users of this client
+    pub fn social_cache(&self) -> SocialCache<'_> {
+        SocialCache {
+            client: self,
+            store: &self.social,
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Human-readable summaries of operations and transactions
+//!
+//! One place for display logic shared by the audit log, alerting bots and
+//! command-line tools:
+//!
+//! ```
+//! use nectarflower_rs::Operation;
+//! use serde_json::json;
+//!
+//! let op = Operation::new(
+//!     "transfer_operation",
+//!     json!({"from": "alice", "to": "bob", "amount": "5.000 HIVE", "memo": "rent"}),
+//! );
+//! assert_eq!(op.summary(), "@alice transferred 5.000 HIVE to @bob — memo: rent");
+//! assert_eq!(op.summary_redacted(), "@alice transferred 5.000 HIVE to @bob");
+//! ```
+//!
+//! Summaries are built as [`Message`]s, so [`Operation::summary_in`] can
+//! render them in another language; see [`crate::i18n`].
+//!
+//! Operations without a dedicated summary fall back to their name, accounts
+//! and amounts, as in `account_update account=alice`.
+
+use crate::asset::Asset;
+use crate::audit::summarize_operation;
+use crate::i18n::{English, Message, Translator};
+use crate::operation::Operation;
+use crate::transaction::Transaction;
+use serde_json::Value;
+
+/// Longest free text (memo, title) shown before it is cut off
+const MAX_TEXT_CHARS: usize = 80;
+
+impl Operation {
+    /// A one-line description, e.g. `@alice transferred 5.000 HIVE to @bob — memo: rent`
+    pub fn summary(&self) -> String {
+        self.summary_in(&English)
+    }
+
+    /// Like [`Operation::summary`], leaving out memos, titles and other free text
+    pub fn summary_redacted(&self) -> String {
+        self.summary_redacted_in(&English)
+    }
+
+    /// [`Operation::summary`] rendered by `translator`
+    pub fn summary_in(&self, translator: &dyn Translator) -> String {
+        render(&self.summary_messages(true), translator)
+    }
+
+    /// [`Operation::summary_redacted`] rendered by `translator`
+    pub fn summary_redacted_in(&self, translator: &dyn Translator) -> String {
+        render(&self.summary_messages(false), translator)
+    }
+
+    /// The untranslated summary: the description and, with `free_text`, the memo
+    pub fn summary_messages(&self, free_text: bool) -> Vec<Message> {
+        let mut messages = vec![describe(self, free_text)];
+        let has_memo = matches!(
+            self.name(),
+            "transfer" | "recurrent_transfer" | "transfer_to_savings" | "transfer_from_savings"
+        );
+        if free_text && has_memo {
+            messages.extend(self.str_field("memo").and_then(memo));
+        }
+        messages
+    }
+}
+
+impl Transaction {
+    /// A numbered list of the operations' summaries under a header line
+    pub fn summary(&self) -> String {
+        self.summary_in(&English)
+    }
+
+    /// [`Transaction::summary`] rendered by `translator`
+    pub fn summary_in(&self, translator: &dyn Translator) -> String {
+        let count = self.operations.len();
+        let header = Message::new(if count == 1 {
+            "tx.header.one"
+        } else {
+            "tx.header"
+        })
+        .arg("count", count)
+        .arg("expiration", &self.expiration);
+        let mut lines = vec![header.render(translator)];
+        for (i, op) in self.operations.iter().enumerate() {
+            let item = Message::new("tx.item")
+                .arg("number", i + 1)
+                .arg("summary", op.summary_in(translator));
+            lines.push(item.render(translator));
+        }
+        lines.join("\n")
+    }
+}
+
+fn render(messages: &[Message], translator: &dyn Translator) -> String {
+    messages.iter().map(|m| m.render(translator)).collect()
+}
+
+fn describe(op: &Operation, free_text: bool) -> Message {
+    let account = |field: &str| format!("@{}", op.str_field(field).unwrap_or("?"));
+    let amount = |field: &str| {
+        op.value
+            .get(field)
+            .and_then(|v| Asset::from_value(v).ok())
+            .map_or_else(|| "?".to_string(), |asset| asset.to_string())
+    };
+    let post = |author: &str, permlink: &str| {
+        format!(
+            "@{}/{}",
+            op.str_field(author).unwrap_or("?"),
+            op.str_field(permlink).unwrap_or("?")
+        )
+    };
+    match op.name() {
+        "transfer" | "transfer_to_savings" | "transfer_from_savings" => {
+            let key = match op.name() {
+                "transfer" => "op.transfer",
+                "transfer_to_savings" => "op.transfer_to_savings",
+                _ => "op.transfer_from_savings",
+            };
+            Message::new(key)
+                .arg("from", account("from"))
+                .arg("amount", amount("amount"))
+                .arg("to", account("to"))
+        }
+        "recurrent_transfer" => Message::new("op.recurrent_transfer")
+            .arg("from", account("from"))
+            .arg("amount", amount("amount"))
+            .arg("to", account("to"))
+            .arg("recurrence", number(op, "recurrence"))
+            .arg("executions", number(op, "executions")),
+        "transfer_to_vesting" => {
+            let to = op.str_field("to").filter(|to| !to.is_empty());
+            let message = match to {
+                Some(to) if Some(to) != op.str_field("from") => {
+                    Message::new("op.power_up_for").arg("to", format!("@{to}"))
+                }
+                _ => Message::new("op.power_up"),
+            };
+            message
+                .arg("from", account("from"))
+                .arg("amount", amount("amount"))
+        }
+        "withdraw_vesting" => Message::new("op.withdraw_vesting")
+            .arg("account", account("account"))
+            .arg("amount", amount("vesting_shares")),
+        "delegate_vesting_shares" => Message::new("op.delegate_vesting_shares")
+            .arg("delegator", account("delegator"))
+            .arg("amount", amount("vesting_shares"))
+            .arg("delegatee", account("delegatee")),
+        "vote" => {
+            let weight = op.value.get("weight").and_then(Value::as_i64).unwrap_or(0);
+            let message = match weight {
+                0 => Message::new("op.vote.remove"),
+                w if w < 0 => Message::new("op.vote.down").arg("weight", percent(-w)),
+                w => Message::new("op.vote.up").arg("weight", percent(w)),
+            };
+            message
+                .arg("voter", account("voter"))
+                .arg("post", post("author", "permlink"))
+        }
+        "comment" => {
+            let message = match op.str_field("parent_author").filter(|a| !a.is_empty()) {
+                Some(_) => {
+                    Message::new("op.reply").arg("parent", post("parent_author", "parent_permlink"))
+                }
+                None => match op.str_field("title").filter(|t| free_text && !t.is_empty()) {
+                    Some(title) => Message::new("op.post_titled").arg("title", truncate(title)),
+                    None => Message::new("op.post"),
+                },
+            };
+            message
+                .arg("author", account("author"))
+                .arg("post", post("author", "permlink"))
+        }
+        "delete_comment" => Message::new("op.delete_comment")
+            .arg("author", account("author"))
+            .arg("post", post("author", "permlink")),
+        "custom_json" => {
+            let signer = ["required_posting_auths", "required_auths"]
+                .iter()
+                .find_map(|field| op.value.get(*field)?.get(0)?.as_str())
+                .unwrap_or("?");
+            Message::new("op.custom_json")
+                .arg("signer", format!("@{signer}"))
+                .arg("id", op.str_field("id").unwrap_or("?"))
+        }
+        "account_witness_vote" => {
+            let approve = op.value.get("approve").and_then(Value::as_bool) == Some(true);
+            Message::new(if approve {
+                "op.witness_vote"
+            } else {
+                "op.witness_unvote"
+            })
+            .arg("account", account("account"))
+            .arg("witness", account("witness"))
+        }
+        "account_witness_proxy" => match op.str_field("proxy").filter(|p| !p.is_empty()) {
+            Some(proxy) => Message::new("op.witness_proxy").arg("proxy", format!("@{proxy}")),
+            None => Message::new("op.witness_proxy_cleared"),
+        }
+        .arg("account", account("account")),
+        "claim_reward_balance" => Message::new("op.claim_reward_balance")
+            .arg("account", account("account"))
+            .arg("hive", amount("reward_hive"))
+            .arg("hbd", amount("reward_hbd"))
+            .arg("vests", amount("reward_vests")),
+        "limit_order_create" => Message::new("op.limit_order_create")
+            .arg("owner", account("owner"))
+            .arg("sell", amount("amount_to_sell"))
+            .arg("receive", amount("min_to_receive")),
+        "limit_order_cancel" => Message::new("op.limit_order_cancel")
+            .arg("owner", account("owner"))
+            .arg("order", number(op, "orderid")),
+        "convert" => Message::new("op.convert")
+            .arg("owner", account("owner"))
+            .arg("amount", amount("amount")),
+        "author_reward" => Message::new("op.author_reward")
+            .arg("author", account("author"))
+            .arg("hbd", amount("hbd_payout"))
+            .arg("hive", amount("hive_payout"))
+            .arg("vests", amount("vesting_payout"))
+            .arg("post", post("author", "permlink")),
+        "curation_reward" => Message::new("op.curation_reward")
+            .arg("curator", account("curator"))
+            .arg("reward", amount("reward"))
+            .arg("post", post("author", "permlink")),
+        "producer_reward" => Message::new("op.producer_reward")
+            .arg("producer", account("producer"))
+            .arg("reward", amount("vesting_shares")),
+        "fill_vesting_withdraw" => Message::new("op.fill_vesting_withdraw")
+            .arg("from", account("from_account"))
+            .arg("withdrawn", amount("withdrawn"))
+            .arg("deposited", amount("deposited")),
+        "interest" => Message::new("op.interest")
+            .arg("owner", account("owner"))
+            .arg("interest", amount("interest")),
+        _ => Message::new("op.other")
+            .arg("name", op.name())
+            .arg("details", summarize_operation(op)),
+    }
+}
+
+fn number(op: &Operation, field: &str) -> String {
+    op.value.get(field).map_or_else(
+        || "?".to_string(),
+        |v| v.to_string().trim_matches('"').to_string(),
+    )
+}
+
+/// A vote weight in basis points as a percentage, e.g. `50.00%`
+fn percent(weight: i64) -> String {
+    format!("{}.{:02}%", weight / 100, weight % 100)
+}
+
+fn memo(memo: &str) -> Option<Message> {
+    if memo.is_empty() {
+        None
+    } else if memo.starts_with('#') {
+        Some(Message::new("memo.encrypted"))
+    } else {
+        Some(Message::new("memo").arg("memo", truncate(memo)))
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
@@ -0,0 +1,201 @@
+//! Trending tags and per-tag activity statistics
+
+use crate::asset::Asset;
+use crate::block::Block;
+use crate::block_source::BlockSource;
+use crate::posts::Post;
+use crate::tags::{normalize_tag, MAX_TAGS};
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of tags `condenser_api.get_trending_tags` returns per call
+pub const TRENDING_TAGS_LIMIT: u32 = 100;
+
+/// A tag as returned by `condenser_api.get_trending_tags`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingTag {
+    pub name: String,
+    pub total_payouts: Asset,
+    #[serde(default)]
+    pub net_votes: i64,
+    #[serde(default)]
+    pub top_posts: u32,
+    #[serde(default)]
+    pub comments: u32,
+    #[serde(default)]
+    pub trending: String,
+}
+
+/// Activity in one tag over a window
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TagStats {
+    pub tag: String,
+    pub posts: u32,
+    pub comments: u32,
+    /// Payouts of the posts recorded with [`TagStatsCollector::record_post`], in HBD
+    pub payout: f64,
+}
+
+/// Ordering for [`TagStatsCollector::top`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagOrder {
+    #[default]
+    Posts,
+    Comments,
+    Payout,
+}
+
+/// Aggregates post counts and payouts per tag from blocks and post listings
+///
+/// Each post or comment is counted once even if it is edited within the window.
+#[derive(Debug, Clone, Default)]
+pub struct TagStatsCollector {
+    stats: HashMap<String, TagStats>,
+    counted: HashSet<(String, String)>,
+    paid: HashSet<(String, String)>,
+}
+
+impl TagStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&mut self, tag: &str) -> &mut TagStats {
+        self.stats
+            .entry(tag.to_string())
+            .or_insert_with(|| TagStats {
+                tag: tag.to_string(),
+                ..TagStats::default()
+            })
+    }
+
+    /// Count the `comment` operations in a block under their tags
+    pub fn record_block(&mut self, block: &Block) {
+        for (_, op) in block.operations() {
+            if op.name() != "comment" {
+                continue;
+            }
+            let author = op.str_field("author").unwrap_or_default().to_string();
+            let permlink = op.str_field("permlink").unwrap_or_default().to_string();
+            if !self.counted.insert((author, permlink)) {
+                continue;
+            }
+            let is_post = op.str_field("parent_author").unwrap_or_default().is_empty();
+            let category = is_post.then(|| op.str_field("parent_permlink")).flatten();
+            let metadata = op
+                .str_field("json_metadata")
+                .and_then(|m| serde_json::from_str(m).ok())
+                .unwrap_or(Value::Null);
+            for tag in metadata_tags(category, &metadata) {
+                let stats = self.entry(&tag);
+                if is_post {
+                    stats.posts += 1;
+                } else {
+                    stats.comments += 1;
+                }
+            }
+        }
+    }
+
+    /// Add a post's payout (pending or paid) to each of its tags
+    pub fn record_post(&mut self, post: &Post) {
+        if !self
+            .paid
+            .insert((post.author.clone(), post.permlink.clone()))
+        {
+            return;
+        }
+        let category = Some(post.category.as_str()).filter(|_| post.depth == 0);
+        for tag in metadata_tags(category, &post.json_metadata) {
+            self.entry(&tag).payout += post.payout;
+        }
+    }
+
+    /// Record blocks from `source` up to and including `last_block`
+    pub fn record_source<S: BlockSource>(
+        &mut self,
+        source: &mut S,
+        last_block: u32,
+    ) -> Result<(), String> {
+        while let Some(streamed) = source.next_block() {
+            let streamed = streamed?;
+            if let Some(block) = &streamed.block {
+                self.record_block(block);
+            }
+            if streamed.block_num >= last_block {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, tag: &str) -> Option<&TagStats> {
+        self.stats.get(tag)
+    }
+
+    /// The `n` most active tags by `order`, ties broken by name
+    pub fn top(&self, n: usize, order: TagOrder) -> Vec<TagStats> {
+        let mut stats: Vec<TagStats> = self.stats.values().cloned().collect();
+        stats.sort_by(|a, b| {
+            let by = match order {
+                TagOrder::Posts => b.posts.cmp(&a.posts),
+                TagOrder::Comments => b.comments.cmp(&a.comments),
+                TagOrder::Payout => b.payout.total_cmp(&a.payout),
+            };
+            by.then_with(|| a.tag.cmp(&b.tag))
+        });
+        stats.truncate(n);
+        stats
+    }
+
+    pub fn into_stats(self) -> Vec<TagStats> {
+        self.stats.into_values().collect()
+    }
+}
+
+/// Normalized tags from post metadata, with the category first
+fn metadata_tags(category: Option<&str>, metadata: &Value) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let listed = metadata
+        .get("tags")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str());
+    for tag in category.into_iter().chain(listed) {
+        let tag = normalize_tag(tag);
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+        if tags.len() == MAX_TAGS {
+            break;
+        }
+    }
+    tags
+}
+
+impl Client {
+    /// Fetch trending tags starting after `start_tag` (empty for the top)
+    pub fn get_trending_tags(
+        &self,
+        start_tag: &str,
+        limit: u32,
+    ) -> Result<Vec<TrendingTag>, String> {
+        self.call(
+            "condenser_api.get_trending_tags",
+            (start_tag, limit.clamp(1, TRENDING_TAGS_LIMIT)),
+        )
+        .map_err(|e| format!("Error fetching trending tags: {e}"))
+    }
+
+    /// Count posts and comments per tag in blocks `start..=end`
+    pub fn tag_stats(&self, start: u32, end: u32) -> Result<TagStatsCollector, String> {
+        let mut collector = TagStatsCollector::new();
+        if end >= start {
+            collector.record_source(&mut self.stream_blocks(start), end)?;
+        }
+        Ok(collector)
+    }
+}
@@ -0,0 +1,69 @@
+//! Tag and category rules enforced by condenser and hivemind
+
+/// Tags beyond this count are dropped by hivemind
+pub const MAX_TAGS: usize = 10;
+
+/// Longest tag condenser accepts
+pub const MAX_TAG_LENGTH: usize = 24;
+
+/// Lowercase a tag and strip surrounding whitespace and a leading `#`, as in `# Hive`
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').trim_start().to_lowercase()
+}
+
+/// Check a single, already normalized tag
+pub fn validate_tag(tag: &str) -> Result<(), String> {
+    if tag.is_empty() {
+        return Err("Tag is empty".to_string());
+    }
+    let length = tag.chars().count();
+    if length > MAX_TAG_LENGTH {
+        return Err(format!(
+            "Tag '{tag}' is {length} characters, above the limit of {MAX_TAG_LENGTH}"
+        ));
+    }
+    if let Some(c) = tag
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-'))
+    {
+        return Err(format!("Tag '{tag}' contains invalid character '{c}'"));
+    }
+    if !tag.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Err(format!("Tag '{tag}' must start with a letter"));
+    }
+    if tag.ends_with('-') {
+        return Err(format!("Tag '{tag}' must end with a letter or digit"));
+    }
+    if tag.contains("--") {
+        return Err(format!("Tag '{tag}' contains consecutive dashes"));
+    }
+    Ok(())
+}
+
+/// Normalize and validate a post's tags, putting `category` first when given
+///
+/// Duplicates are removed; every invalid tag is reported in a single error.
+pub fn validate_tags(category: Option<&str>, tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len() + 1);
+    for tag in category.into_iter().chain(tags.iter().map(String::as_str)) {
+        let tag = normalize_tag(tag);
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    let mut errors: Vec<String> = normalized
+        .iter()
+        .filter_map(|tag| validate_tag(tag).err())
+        .collect();
+    if normalized.len() > MAX_TAGS {
+        errors.push(format!(
+            "{} tags given, above the limit of {MAX_TAGS}",
+            normalized.len()
+        ));
+    }
+    if errors.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(format!("Invalid tags: {}", errors.join("; ")))
+    }
+}
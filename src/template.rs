@@ -0,0 +1,116 @@
+//! Post templates with variable substitution, saved to and loaded from disk
+
+use crate::comment::PostDraft;
+use crate::posts::Beneficiary;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A reusable post layout, e.g. for bots publishing daily or weekly reports
+///
+/// Title and body may contain `{{name}}` placeholders that are replaced at render time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostTemplate {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub beneficiaries: Vec<Beneficiary>,
+    /// Values used for placeholders not supplied at render time
+    #[serde(default)]
+    pub defaults: HashMap<String, String>,
+}
+
+impl PostTemplate {
+    pub fn new(title: &str, body: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            body: body.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Add a beneficiary receiving `weight` basis points of the author rewards
+    pub fn beneficiary(mut self, account: &str, weight: u16) -> Self {
+        self.beneficiaries.push(Beneficiary {
+            account: account.to_string(),
+            weight,
+        });
+        self
+    }
+
+    pub fn default_var(mut self, name: &str, value: &str) -> Self {
+        self.defaults.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Load a template from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("Error reading template {}: {e}", path.display()))?;
+        serde_json::from_str(&data)
+            .map_err(|e| format!("Error parsing template {}: {e}", path.display()))
+    }
+
+    /// Save the template as a JSON file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Error serializing template: {e}"))?;
+        fs::write(path, data).map_err(|e| format!("Error writing template {}: {e}", path.display()))
+    }
+
+    /// Substitute placeholders in `text`, failing on unknown variables
+    pub fn render_text(
+        &self,
+        text: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<String, String> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| "Unterminated '{{' in template".to_string())?;
+            let name = after[..end].trim();
+            let value = vars
+                .get(name)
+                .or_else(|| self.defaults.get(name))
+                .ok_or_else(|| format!("Missing template variable '{name}'"))?;
+            out.push_str(value);
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Render the template into a post draft for `author`
+    ///
+    /// Without a permlink one is generated from the rendered title on publish.
+    pub fn render(
+        &self,
+        author: &str,
+        permlink: Option<&str>,
+        vars: &HashMap<String, String>,
+    ) -> Result<PostDraft, String> {
+        let mut draft = PostDraft::new(
+            author,
+            permlink.unwrap_or_default(),
+            &self.render_text(&self.title, vars)?,
+            &self.render_text(&self.body, vars)?,
+        );
+        draft.tags = self.tags.clone();
+        draft.beneficiaries = self.beneficiaries.clone();
+        Ok(draft)
+    }
+}
@@ -0,0 +1,251 @@
+//! Harness for end-to-end tests against a local hived testnet
+//!
+//! Enabled with the `testnet-harness` feature. [`Testnet`] connects to a
+//! running testnet node or starts one in Docker, and [`Faucet`] funds and
+//! creates test accounts from a pre-funded account such as `initminer`,
+//! signing with a [`TransactionSigner`] such as a
+//! [`SecretWif`](crate::SecretWif) for that account.
+
+use crate::asset::Asset;
+use crate::block::BLOCK_INTERVAL;
+use crate::chain::ChainConfig;
+use crate::operation::Operation;
+use crate::transaction::{TransactionSigner, HIVE_CHAIN_ID};
+use crate::{Client, WriteClient};
+use serde_json::Value;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the URL of a running testnet node
+pub const TESTNET_URL_VAR: &str = "NECTARFLOWER_TESTNET_URL";
+
+/// Pre-funded account of a fresh testnet
+pub const INITMINER: &str = "initminer";
+
+/// RPC port of hived inside the testnet container
+pub const CONTAINER_RPC_PORT: u16 = 8090;
+
+/// How long a freshly started testnet may take to answer
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long an already running testnet may take to answer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A hived testnet reachable over RPC, stopped on drop if this harness started it
+#[derive(Debug)]
+pub struct Testnet {
+    client: WriteClient,
+    url: String,
+    chain_id: String,
+    container: Option<String>,
+}
+
+impl Testnet {
+    /// Connect to the testnet node at `url`
+    pub fn connect(url: &str) -> Result<Self, String> {
+        Self::wait_ready(url, CONNECT_TIMEOUT, None)
+    }
+
+    /// Connect to the node named by [`TESTNET_URL_VAR`], or `None` if it is unset
+    ///
+    /// Lets end-to-end tests skip themselves where no testnet is available.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match std::env::var(TESTNET_URL_VAR) {
+            Ok(url) if !url.is_empty() => Self::connect(&url).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Start `image` with Docker, publishing its RPC port on `port`, and wait until it answers
+    ///
+    /// `args` are passed to the image after the image name, e.g. hived options.
+    pub fn start_docker(image: &str, port: u16, args: &[&str]) -> Result<Self, String> {
+        let output = Command::new("docker")
+            .args(["run", "-d", "--rm", "-p"])
+            .arg(format!("127.0.0.1:{port}:{CONTAINER_RPC_PORT}"))
+            .arg(image)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Error running docker: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "docker run failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let container = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let url = format!("http://127.0.0.1:{port}");
+        Self::wait_ready(&url, STARTUP_TIMEOUT, Some(container))
+    }
+
+    fn wait_ready(url: &str, timeout: Duration, container: Option<String>) -> Result<Self, String> {
+        let mut client = Client::new();
+        client.nodes = vec![url.to_string()];
+        let deadline = Instant::now() + timeout;
+        let version = loop {
+            match client.call::<_, Value>("database_api.get_version", ()) {
+                Ok(version) => break version,
+                Err(e) if Instant::now() >= deadline => {
+                    if let Some(id) = &container {
+                        stop_container(id);
+                    }
+                    return Err(format!("Testnet at {url} did not answer: {e}"));
+                }
+                Err(_) => thread::sleep(BLOCK_INTERVAL),
+            }
+        };
+        let chain_id = version["chain_id"]
+            .as_str()
+            .ok_or_else(|| format!("Node at {url} did not report a chain id"))?
+            .to_string();
+        let testnet = Self {
+            client: WriteClient::new(client.with_chain(ChainConfig::testnet(&chain_id))),
+            url: url.to_string(),
+            chain_id,
+            container,
+        };
+        if testnet.chain_id == HIVE_CHAIN_ID {
+            return Err(format!("{url} is a main network node, not a testnet"));
+        }
+        Ok(testnet)
+    }
+
+    pub fn client(&self) -> &WriteClient {
+        &self.client
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Chain id to sign testnet transactions for
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Wait until the head block has advanced by `count` blocks
+    pub fn wait_blocks(&self, count: u32) -> Result<u32, String> {
+        let start = self
+            .client
+            .get_dynamic_global_properties()?
+            .head_block_number;
+        let deadline = Instant::now() + BLOCK_INTERVAL * (count + 5);
+        loop {
+            let head = self
+                .client
+                .get_dynamic_global_properties()?
+                .head_block_number;
+            if head >= start + count {
+                return Ok(head);
+            }
+            if Instant::now() >= deadline {
+                return Err(format!("Testnet stalled at block {head}"));
+            }
+            thread::sleep(BLOCK_INTERVAL);
+        }
+    }
+
+    /// A faucet paying out of `account`, which `signer` holds the active key of
+    pub fn faucet<'a>(&'a self, account: &str, signer: &'a dyn TransactionSigner) -> Faucet<'a> {
+        Faucet {
+            testnet: self,
+            account: account.to_string(),
+            signer,
+        }
+    }
+}
+
+impl Drop for Testnet {
+    fn drop(&mut self) {
+        if let Some(id) = &self.container {
+            stop_container(id);
+        }
+    }
+}
+
+fn stop_container(id: &str) {
+    let _ = Command::new("docker").args(["stop", id]).output();
+}
+
+/// Funds and creates test accounts from a pre-funded testnet account
+pub struct Faucet<'a> {
+    testnet: &'a Testnet,
+    account: String,
+    signer: &'a dyn TransactionSigner,
+}
+
+impl std::fmt::Debug for Faucet<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Faucet")
+            .field("account", &self.account)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Faucet<'_> {
+    /// Broadcast `operations` from the faucet account and wait for inclusion
+    pub fn send(&self, operations: Vec<Operation>) -> Result<String, String> {
+        let client = &self.testnet.client;
+        let mut tx = client.prepare_transaction(operations)?;
+        let trx_id = client.sign_transaction(&mut tx, &[self.signer])?;
+        let status = client.broadcast_reliably(&tx, &trx_id, 10)?;
+        if !status.is_included() {
+            return Err(format!("Transaction {trx_id} was not included: {status:?}"));
+        }
+        Ok(trx_id)
+    }
+
+    /// Transfer HIVE or HBD to `to`
+    pub fn fund(&self, to: &str, amount: Asset) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "transfer_operation",
+            serde_json::json!({
+                "from": self.account,
+                "to": to,
+                "amount": amount.to_nai(),
+                "memo": "",
+            }),
+        )])
+    }
+
+    /// Power up HIVE to `to`, giving it resource credits and voting power
+    pub fn vest(&self, to: &str, amount: Asset) -> Result<String, String> {
+        self.send(vec![Operation::new(
+            "transfer_to_vesting_operation",
+            serde_json::json!({
+                "from": self.account,
+                "to": to,
+                "amount": amount.to_nai(),
+            }),
+        )])
+    }
+
+    /// Create `name` with `public_key` as its owner, active, posting and memo key
+    pub fn create_account(&self, name: &str, public_key: &str) -> Result<String, String> {
+        let schedule: Value = self
+            .testnet
+            .client
+            .call("database_api.get_witness_schedule", ())
+            .map_err(|e| format!("Error fetching account creation fee: {e}"))?;
+        let fee = Asset::from_value(&schedule["median_props"]["account_creation_fee"])?;
+        let authority = serde_json::json!({
+            "weight_threshold": 1,
+            "account_auths": [],
+            "key_auths": [[public_key, 1]],
+        });
+        self.send(vec![Operation::new(
+            "account_create_operation",
+            serde_json::json!({
+                "fee": fee.to_nai(),
+                "creator": self.account,
+                "new_account_name": name,
+                "owner": authority,
+                "active": authority,
+                "posting": authority,
+                "memo_key": public_key,
+                "json_metadata": "",
+            }),
+        )])
+    }
+}
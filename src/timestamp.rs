@@ -0,0 +1,70 @@
+//! Conversion between chain timestamps (`2024-01-01T00:00:00`) and Unix seconds
+
+/// Parse a chain timestamp into seconds since the Unix epoch (UTC)
+pub fn parse_timestamp(s: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid timestamp: '{s}'");
+    let s = s.trim_end_matches('Z');
+    let (date, time) = s.split_once('T').ok_or_else(invalid)?;
+    let mut date_parts = date.splitn(3, '-');
+    let mut time_parts = time.splitn(3, ':');
+    let next = |parts: &mut std::str::SplitN<'_, char>| -> Result<i64, String> {
+        parts
+            .next()
+            .and_then(|p| p.split('.').next())
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let year = next(&mut date_parts)?;
+    let month = next(&mut date_parts)?;
+    let day = next(&mut date_parts)?;
+    let hour = next(&mut time_parts)?;
+    let minute = next(&mut time_parts)?;
+    let second = next(&mut time_parts)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Format seconds since the Unix epoch as a chain timestamp
+pub fn format_timestamp(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Format seconds since the Unix epoch as a `YYYY-MM-DD` date
+pub fn format_date(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days algorithms
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
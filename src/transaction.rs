@@ -0,0 +1,564 @@
+//! Transaction preparation, signing, broadcasting and status tracking
+
+use crate::chain::ChainConfig;
+use crate::crypto::{from_hex, sha256, to_hex};
+use crate::deserializer::Deserializer;
+use crate::operation::Operation;
+use crate::serializer::{transaction_bytes, Serializer};
+use crate::timestamp::{format_timestamp, parse_timestamp};
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Chain id of the Hive main network
+pub const HIVE_CHAIN_ID: &str = "beeab0de00000000000000000000000000000000000000000000000000000000";
+
+/// Default transaction expiration in seconds
+pub const DEFAULT_EXPIRATION_SECS: i64 = 60;
+
+/// Longest expiration hived accepts, in seconds from the head block time
+pub const MAX_EXPIRATION_SECS: i64 = 3_600;
+
+/// Largest serialized transaction hived accepts
+pub const MAX_TRANSACTION_SIZE: usize = 64 * 1024;
+
+/// Default cap on operations per transaction used by [`TransactionBuilder`]
+pub const DEFAULT_MAX_OPERATIONS: usize = 1_000;
+
+/// Serialized size of one compact signature
+pub const SIGNATURE_SIZE: usize = 65;
+
+/// Produces signatures over transaction digests
+///
+/// [`crate::SecretWif`] signs with a private key directly; implement this on
+/// top of your key management of choice, or use [`crate::WifSigner`]. The
+/// digest is `sha256(chain_id || serialized transaction)`.
+pub trait TransactionSigner {
+    /// Sign a digest, returning the hex-encoded 65-byte compact signature
+    fn sign_digest(&self, digest: &[u8; 32]) -> Result<String, String>;
+
+    /// A short identifier of the signing key for audit logs, never the key itself
+    fn key_fingerprint(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A transaction with its signatures, as cli_wallet calls it
+pub type SignedTransaction = Transaction;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub ref_block_num: u16,
+    pub ref_block_prefix: u32,
+    pub expiration: String,
+    pub operations: Vec<Operation>,
+    #[serde(default)]
+    pub extensions: Vec<Value>,
+    #[serde(default)]
+    pub signatures: Vec<String>,
+}
+
+impl Transaction {
+    /// Serialize without signatures for the main network, locally and without a node
+    ///
+    /// Fails for operations [`crate::serializer`] does not cover, which
+    /// therefore cannot be signed.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        self.to_bytes_for(&ChainConfig::mainnet())
+    }
+
+    /// Serialize without signatures with `chain`'s asset names and key prefix
+    pub fn to_bytes_for(&self, chain: &ChainConfig) -> Result<Vec<u8>, String> {
+        transaction_bytes(self, chain)
+    }
+
+    /// Serialize with signatures, as cli_wallet's `serialize_transaction` does
+    pub fn to_signed_bytes(&self) -> Result<Vec<u8>, String> {
+        self.to_signed_bytes_for(&ChainConfig::mainnet())
+    }
+
+    /// Serialize with signatures for `chain`
+    pub fn to_signed_bytes_for(&self, chain: &ChainConfig) -> Result<Vec<u8>, String> {
+        let mut s = Serializer::for_chain(chain);
+        s.transaction(self)?;
+        s.varint(self.signatures.len() as u64);
+        for signature in &self.signatures {
+            let bytes = from_hex(signature)?;
+            if bytes.len() != SIGNATURE_SIZE {
+                return Err(format!(
+                    "Signature '{signature}' is not {SIGNATURE_SIZE} bytes"
+                ));
+            }
+            s.bytes(&bytes);
+        }
+        Ok(s.into_bytes())
+    }
+
+    /// [`Transaction::to_signed_bytes`] as hex, for cli_wallet and other tools
+    pub fn to_hex(&self) -> Result<String, String> {
+        Ok(to_hex(&self.to_signed_bytes()?))
+    }
+
+    /// Decode a signed transaction serialized by [`Transaction::to_hex`] or cli_wallet
+    ///
+    /// Public keys are decoded with the `STM` prefix.
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let bytes = from_hex(hex.trim())?;
+        let mut d = Deserializer::new(&bytes);
+        let tx = d.signed_transaction()?;
+        if !d.is_empty() {
+            return Err("Trailing bytes after the signed transaction".to_string());
+        }
+        Ok(tx)
+    }
+
+    /// Serialized size in bytes on the main network once signed by `signatures` keys
+    ///
+    /// Existing signatures are counted if there are more of them. Fails for
+    /// operations [`crate::serializer`] does not cover.
+    pub fn serialized_size(&self, signatures: usize) -> Result<usize, String> {
+        self.serialized_size_for(&ChainConfig::mainnet(), signatures)
+    }
+
+    /// Serialized size in bytes on `chain` once signed by `signatures` keys
+    pub fn serialized_size_for(
+        &self,
+        chain: &ChainConfig,
+        signatures: usize,
+    ) -> Result<usize, String> {
+        let unsigned = self.to_bytes_for(chain)?.len();
+        Ok(signed_size(unsigned, signatures.max(self.signatures.len())))
+    }
+
+    /// The id the main network will record for this transaction
+    pub fn id(&self) -> Result<String, String> {
+        self.id_for(&ChainConfig::mainnet())
+    }
+
+    /// The id `chain` will record for this transaction
+    pub fn id_for(&self, chain: &ChainConfig) -> Result<String, String> {
+        Ok(to_hex(&sha256(&self.to_bytes_for(chain)?)[..20]))
+    }
+
+    /// The digest signers sign on `chain`
+    pub fn digest(&self, chain: &ChainConfig) -> Result<[u8; 32], String> {
+        let mut message = from_hex(&chain.chain_id)?;
+        message.extend_from_slice(&self.to_bytes_for(chain)?);
+        Ok(sha256(&message))
+    }
+}
+
+/// Status reported by `transaction_status_api.find_transaction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    Unknown,
+    WithinMempool,
+    WithinReversibleBlock,
+    WithinIrreversibleBlock,
+    ExpiredReversible,
+    ExpiredIrreversible,
+    TooOld,
+}
+
+impl TransactionStatus {
+    /// Whether the transaction made it into a block
+    pub fn is_included(self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::WithinReversibleBlock | TransactionStatus::WithinIrreversibleBlock
+        )
+    }
+
+    /// Whether the transaction can no longer be included
+    pub fn is_expired(self) -> bool {
+        matches!(
+            self,
+            TransactionStatus::ExpiredReversible
+                | TransactionStatus::ExpiredIrreversible
+                | TransactionStatus::TooOld
+        )
+    }
+}
+
+/// A transaction found on chain by `account_history_api.get_transaction`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncludedTransaction {
+    #[serde(flatten)]
+    pub transaction: Transaction,
+    pub transaction_id: String,
+    pub block_num: u32,
+    pub transaction_num: u32,
+}
+
+/// Block and position of an included transaction, for linking to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionLocation {
+    pub trx_id: String,
+    pub block_num: u32,
+    /// Index of the transaction within its block
+    pub trx_in_block: u32,
+}
+
+/// Why a [`TransactionBuilder`] refused to build a transaction
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransactionError {
+    #[error("Transaction has no operations")]
+    Empty,
+    #[error("{count} operations exceed the limit of {max} per transaction")]
+    TooManyOperations { count: usize, max: usize },
+    #[error("Expiration of {secs} seconds is outside 1..={max}")]
+    InvalidExpiration { secs: i64, max: i64 },
+    #[error("Transaction of {size} bytes exceeds the limit of {max} bytes")]
+    TooLarge { size: usize, max: usize },
+    #[error("{0}")]
+    Rpc(String),
+}
+
+impl From<TransactionError> for String {
+    fn from(e: TransactionError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Queues operations and builds transactions within hived's limits
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    operations: Vec<Operation>,
+    expiration_secs: i64,
+    max_operations: usize,
+    max_size: usize,
+    signatures: usize,
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+            expiration_secs: DEFAULT_EXPIRATION_SECS,
+            max_operations: DEFAULT_MAX_OPERATIONS,
+            max_size: MAX_TRANSACTION_SIZE,
+            signatures: 1,
+        }
+    }
+
+    pub fn operation(mut self, op: Operation) -> Self {
+        self.operations.push(op);
+        self
+    }
+
+    pub fn operations<I: IntoIterator<Item = Operation>>(mut self, ops: I) -> Self {
+        self.operations.extend(ops);
+        self
+    }
+
+    /// Seconds until expiration, at most [`MAX_EXPIRATION_SECS`]
+    pub fn expiration_secs(mut self, secs: i64) -> Self {
+        self.expiration_secs = secs;
+        self
+    }
+
+    /// Lower the operation cap below [`DEFAULT_MAX_OPERATIONS`]
+    pub fn max_operations(mut self, max: usize) -> Self {
+        self.max_operations = max.max(1);
+        self
+    }
+
+    /// Lower the size cap below [`MAX_TRANSACTION_SIZE`]
+    pub fn max_size(mut self, bytes: usize) -> Self {
+        self.max_size = bytes.min(MAX_TRANSACTION_SIZE);
+        self
+    }
+
+    /// Number of keys expected to sign, used to account for signature bytes
+    pub fn signatures(mut self, count: usize) -> Self {
+        self.signatures = count.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Check the limits that do not need the serialized transaction
+    pub fn validate(&self) -> Result<(), TransactionError> {
+        if self.operations.is_empty() {
+            return Err(TransactionError::Empty);
+        }
+        if self.operations.len() > self.max_operations {
+            return Err(TransactionError::TooManyOperations {
+                count: self.operations.len(),
+                max: self.max_operations,
+            });
+        }
+        if !(1..=MAX_EXPIRATION_SECS).contains(&self.expiration_secs) {
+            return Err(TransactionError::InvalidExpiration {
+                secs: self.expiration_secs,
+                max: MAX_EXPIRATION_SECS,
+            });
+        }
+        Ok(())
+    }
+
+    /// Build a single unsigned transaction, enforcing every limit
+    pub fn build(&self, client: &Client) -> Result<Transaction, TransactionError> {
+        self.validate()?;
+        let tx = client
+            .prepare_transaction_expiring(self.operations.clone(), self.expiration_secs)
+            .map_err(TransactionError::Rpc)?;
+        let size = client
+            .serialized_size(&tx, self.signatures)
+            .map_err(TransactionError::Rpc)?;
+        if size > self.max_size {
+            return Err(TransactionError::TooLarge {
+                size,
+                max: self.max_size,
+            });
+        }
+        Ok(tx)
+    }
+
+    /// Split the queued operations into builders that each respect the operation cap
+    pub fn split_into_transactions(&self) -> Vec<TransactionBuilder> {
+        self.operations
+            .chunks(self.max_operations)
+            .map(|chunk| TransactionBuilder {
+                operations: chunk.to_vec(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// Build as many transactions as needed, halving batches that exceed the size cap
+    ///
+    /// Operations keep their order across the returned transactions.
+    pub fn build_all(&self, client: &Client) -> Result<Vec<Transaction>, TransactionError> {
+        if self.operations.is_empty() {
+            return Err(TransactionError::Empty);
+        }
+        let mut pending: Vec<TransactionBuilder> = self.split_into_transactions();
+        pending.reverse();
+        let mut built = Vec::new();
+        while let Some(builder) = pending.pop() {
+            match builder.build(client) {
+                Ok(tx) => built.push(tx),
+                Err(TransactionError::TooLarge { .. }) if builder.len() > 1 => {
+                    let (first, second) = builder.operations.split_at(builder.len() / 2);
+                    pending.push(TransactionBuilder {
+                        operations: second.to_vec(),
+                        ..builder.clone()
+                    });
+                    pending.push(TransactionBuilder {
+                        operations: first.to_vec(),
+                        ..builder.clone()
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(built)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionHexResponse {
+    hex: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FindTransactionResponse {
+    status: TransactionStatus,
+    #[serde(default)]
+    block_num: Option<u32>,
+}
+
+impl Client {
+    /// Build an unsigned transaction referencing the current head block
+    pub fn prepare_transaction(&self, operations: Vec<Operation>) -> Result<Transaction, String> {
+        self.prepare_transaction_expiring(operations, DEFAULT_EXPIRATION_SECS)
+    }
+
+    fn prepare_transaction_expiring(
+        &self,
+        operations: Vec<Operation>,
+        expiration_secs: i64,
+    ) -> Result<Transaction, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let block_id = from_hex(&props.head_block_id)?;
+        if block_id.len() < 8 {
+            return Err(format!("Invalid head block id: {}", props.head_block_id));
+        }
+        let prefix = u32::from_le_bytes([block_id[4], block_id[5], block_id[6], block_id[7]]);
+        Ok(Transaction {
+            ref_block_num: (props.head_block_number & 0xffff) as u16,
+            ref_block_prefix: prefix,
+            expiration: format_timestamp(parse_timestamp(&props.time)? + expiration_secs),
+            operations,
+            extensions: Vec::new(),
+            signatures: Vec::new(),
+        })
+    }
+
+    /// Serialize a transaction (without signatures) for the client's chain
+    ///
+    /// Serialized locally, so a node never chooses the bytes that get signed.
+    pub fn get_transaction_bytes(&self, tx: &Transaction) -> Result<Vec<u8>, String> {
+        tx.to_bytes_for(self.chain())
+    }
+
+    /// Check that the node serializes `tx` exactly as [`Client::get_transaction_bytes`]
+    ///
+    /// A cross-check of the local serializer against the node's
+    /// `database_api.get_transaction_hex`; any difference is an error.
+    pub fn verify_transaction_bytes(&self, tx: &Transaction) -> Result<(), String> {
+        let unsigned = Transaction {
+            signatures: Vec::new(),
+            ..tx.clone()
+        };
+        let local = unsigned.to_signed_bytes_for(self.chain())?;
+        let remote = self.node_transaction_bytes(&unsigned)?;
+        if remote != local {
+            return Err(format!(
+                "Node serialized the transaction as {} instead of {}",
+                to_hex(&remote),
+                to_hex(&local)
+            ));
+        }
+        Ok(())
+    }
+
+    /// The node's serialization of `tx`, signature list included
+    fn node_transaction_bytes(&self, tx: &Transaction) -> Result<Vec<u8>, String> {
+        let resp: TransactionHexResponse = self
+            .call(
+                "database_api.get_transaction_hex",
+                serde_json::json!({ "trx": tx }),
+            )
+            .map_err(|e| format!("Error serializing transaction: {e}"))?;
+        from_hex(&resp.hex)
+    }
+
+    /// Serialized size in bytes of a transaction carrying `signatures` signatures
+    ///
+    /// Pass the number of keys that will sign an unsigned transaction; existing
+    /// signatures are counted if there are more of them. Unlike
+    /// [`Transaction::serialized_size`], operations the local serializer does
+    /// not cover are measured with the node's serializer.
+    pub fn serialized_size(&self, tx: &Transaction, signatures: usize) -> Result<usize, String> {
+        if let Ok(size) = tx.serialized_size_for(self.chain(), signatures) {
+            return Ok(size);
+        }
+        let unsigned = Transaction {
+            signatures: Vec::new(),
+            ..tx.clone()
+        };
+        // The node's bytes end with the empty signature list's length
+        let unsigned_len = self
+            .node_transaction_bytes(&unsigned)?
+            .len()
+            .saturating_sub(varint_len(0));
+        Ok(signed_size(
+            unsigned_len,
+            signatures.max(tx.signatures.len()),
+        ))
+    }
+
+    /// Compute the id of a transaction on the client's chain
+    pub fn transaction_id(&self, tx: &Transaction) -> Result<String, String> {
+        tx.id_for(self.chain())
+    }
+
+    /// Look up the status of a transaction by id
+    pub fn find_transaction(
+        &self,
+        trx_id: &str,
+        expiration: Option<&str>,
+    ) -> Result<TransactionStatus, String> {
+        let mut params = serde_json::json!({ "transaction_id": trx_id });
+        if let Some(expiration) = expiration {
+            params["expiration"] = expiration.into();
+        }
+        let resp: FindTransactionResponse = self
+            .call("transaction_status_api.find_transaction", params)
+            .map_err(|e| format!("Error fetching transaction status: {e}"))?;
+        Ok(resp.status)
+    }
+
+    /// Find the block and position of an included transaction
+    ///
+    /// Recent transactions are located with `transaction_status_api` and the
+    /// block's transaction ids. Older ones, or when that API is unavailable,
+    /// fall back to `account_history_api.get_transaction`.
+    pub fn find_transaction_block(&self, trx_id: &str) -> Result<TransactionLocation, String> {
+        let status: Result<FindTransactionResponse, _> = self.call(
+            "transaction_status_api.find_transaction",
+            serde_json::json!({ "transaction_id": trx_id }),
+        );
+        match status {
+            Ok(FindTransactionResponse {
+                status,
+                block_num: Some(block_num),
+            }) if status.is_included() => {
+                let block = self
+                    .get_block(block_num)?
+                    .ok_or_else(|| format!("Block {block_num} not found"))?;
+                if let Some(index) = block.transaction_ids.iter().position(|id| id == trx_id) {
+                    return Ok(TransactionLocation {
+                        trx_id: trx_id.to_string(),
+                        block_num,
+                        trx_in_block: index as u32,
+                    });
+                }
+            }
+            Ok(resp) if resp.status == TransactionStatus::WithinMempool => {
+                return Err(format!("Transaction {trx_id} is not in a block yet"));
+            }
+            Ok(resp) if resp.status.is_expired() && resp.status != TransactionStatus::TooOld => {
+                return Err(format!(
+                    "Transaction {trx_id} expired without being included"
+                ));
+            }
+            _ => {}
+        }
+        let included = self.get_transaction(trx_id)?;
+        Ok(TransactionLocation {
+            trx_id: included.transaction_id,
+            block_num: included.block_num,
+            trx_in_block: included.transaction_num,
+        })
+    }
+
+    /// Fetch an included transaction by id, including reversible blocks
+    pub fn get_transaction(&self, trx_id: &str) -> Result<IncludedTransaction, String> {
+        self.call(
+            "account_history_api.get_transaction",
+            serde_json::json!({ "id": trx_id, "include_reversible": true }),
+        )
+        .map_err(|e| format!("Error fetching transaction {trx_id}: {e}"))
+    }
+}
+
+/// Size of a transaction of `unsigned` bytes once `signatures` signatures are appended
+fn signed_size(unsigned: usize, signatures: usize) -> usize {
+    unsigned + varint_len(signatures) + signatures * SIGNATURE_SIZE
+}
+
+/// Length of an unsigned LEB128 varint as used for array lengths
+pub(crate) fn varint_len(mut value: usize) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
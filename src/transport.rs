@@ -0,0 +1,436 @@
+//! Pluggable transports for every request a [`Client`] makes
+//!
+//! A [`Client`] sends JSON-RPC requests and batches, the REST calls of
+//! [`RestApi`](crate::rest::RestApi) and the price feeds through its
+//! [`Transport`], as does an [`AsyncClient`](crate::AsyncClient) built from it.
+//! By default that is an [`HttpTransport`] built from the client's timeouts,
+//! user agent, DNS, HTTP/2, WebSocket and response size settings. A transport
+//! set with [`ClientBuilder::transport`](crate::builder::ClientBuilder::transport)
+//! replaces it, while failover, retries and decoding stay in the client.
+//! [`MockTransport`] answers from canned results, so code built on a `Client`
+//! can be tested without a live node:
+//!
+//! ```
+//! use nectarflower_rs::transport::MockTransport;
+//! use nectarflower_rs::Client;
+//! use serde_json::{json, Value};
+//!
+//! let mock = MockTransport::new()
+//!     .fail_node("https://down.example")
+//!     .respond("condenser_api.get_config", json!({ "HIVE_CHAIN_ID": "beeab0de" }));
+//! let client = Client::builder()
+//!     .nodes(["https://down.example", "https://up.example"])
+//!     .transport(mock.clone())
+//!     .build();
+//! let config: Value = client.call("condenser_api.get_config", json!([]))?;
+//! assert_eq!(config["HIVE_CHAIN_ID"], "beeab0de");
+//! assert_eq!(mock.requests().len(), 2);
+//! # Ok::<(), nectarflower_rs::NectarError>(())
+//! ```
+
+use crate::dns::HttpSettings;
+use crate::error::NectarError;
+#[cfg(feature = "websocket")]
+use crate::websocket::{self, WebSockets};
+use crate::{request_error, Client};
+use reqwest::blocking::{Client as HttpClient, RequestBuilder, Response};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::io::Read;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// JSON-RPC code a [`MockTransport`] answers methods it has no result for
+pub const METHOD_NOT_FOUND_CODE: i32 = -32601;
+
+/// The response body a [`Transport::send_async`] resolves to
+#[cfg(feature = "async")]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<u8>, NectarError>> + Send + 'a>>;
+
+/// Carries request bodies to nodes and other HTTP APIs and returns the response bodies
+pub trait Transport: Send + Sync {
+    /// POST `body`, a JSON-RPC request or batch or another JSON document, to `url`
+    fn send(&self, url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError>;
+
+    /// GET `url`, for REST APIs and price feeds
+    fn get(&self, url: &str) -> Result<Vec<u8>, NectarError> {
+        Err(NectarError::Transport(format!(
+            "GET {url} is not supported by this transport"
+        )))
+    }
+
+    /// [`Transport::send`] for [`AsyncClient`](crate::AsyncClient)
+    ///
+    /// The default runs `send` in place, blocking the runtime while it does;
+    /// transports doing network I/O should override it.
+    #[cfg(feature = "async")]
+    fn send_async<'a>(&'a self, url: &'a str, body: Vec<u8>) -> TransportFuture<'a> {
+        Box::pin(std::future::ready(self.send(url, &body)))
+    }
+}
+
+impl fmt::Debug for dyn Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+/// Sends requests over HTTP, and over WebSockets to `ws://` and `wss://` nodes
+///
+/// Each HTTP client, blocking or async, is built on first use, so an async
+/// client never creates a blocking one.
+#[derive(Debug)]
+pub struct HttpTransport {
+    settings: HttpSettings,
+    max_response_bytes: Option<u64>,
+    http_client: OnceLock<HttpClient>,
+    http2_client: OnceLock<Option<HttpClient>>,
+    #[cfg(feature = "async")]
+    async_client: OnceLock<reqwest::Client>,
+    #[cfg(feature = "async")]
+    async_http2_client: OnceLock<Option<reqwest::Client>>,
+    #[cfg(feature = "websocket")]
+    websockets: WebSockets,
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpTransport {
+    /// A transport with the default timeout and no other settings
+    pub fn new() -> Self {
+        Self::with_settings(HttpSettings::default(), None)
+    }
+
+    pub(crate) fn with_settings(settings: HttpSettings, max_response_bytes: Option<u64>) -> Self {
+        Self {
+            settings,
+            max_response_bytes,
+            http_client: OnceLock::new(),
+            http2_client: OnceLock::new(),
+            #[cfg(feature = "async")]
+            async_client: OnceLock::new(),
+            #[cfg(feature = "async")]
+            async_http2_client: OnceLock::new(),
+            #[cfg(feature = "websocket")]
+            websockets: WebSockets::default(),
+        }
+    }
+
+    /// Make blocking HTTP requests over an already configured client
+    pub fn with_client(client: HttpClient) -> Self {
+        let transport = Self::new();
+        let _ = transport.http_client.set(client);
+        transport
+    }
+
+    /// Reject responses larger than `bytes` instead of buffering them
+    pub fn max_response_size(mut self, bytes: u64) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Close the sockets to all nodes; the next request opens new ones
+    #[cfg(feature = "websocket")]
+    pub fn close_websockets(&self) {
+        self.websockets.close_all();
+    }
+
+    /// The blocking HTTP client to reach `url` with
+    fn http_for(&self, url: &str) -> &HttpClient {
+        if self.settings.uses_http2(url) {
+            if let Some(http2) = self
+                .http2_client
+                .get_or_init(|| self.settings.build_http2())
+            {
+                return http2;
+            }
+        }
+        self.http_client.get_or_init(|| self.settings.build())
+    }
+
+    /// The async HTTP client to reach `url` with
+    #[cfg(feature = "async")]
+    fn async_http_for(&self, url: &str) -> &reqwest::Client {
+        if self.settings.uses_http2(url) {
+            let http2 = self
+                .async_http2_client
+                .get_or_init(|| self.settings.build_async_http2());
+            if let Some(http2) = http2 {
+                return http2;
+            }
+        }
+        self.async_client
+            .get_or_init(|| self.settings.build_async())
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        #[cfg(feature = "websocket")]
+        if websocket::is_websocket(url) {
+            let slot = self.websockets.slot(url);
+            return websocket::request_on(
+                &slot,
+                url,
+                &self.settings,
+                body,
+                self.max_response_bytes,
+            );
+        }
+        let request = self
+            .http_for(url)
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(body.to_vec());
+        execute(request, self.max_response_bytes)
+    }
+
+    fn get(&self, url: &str) -> Result<Vec<u8>, NectarError> {
+        execute(self.http_for(url).get(url), self.max_response_bytes)
+    }
+
+    #[cfg(feature = "async")]
+    fn send_async<'a>(&'a self, url: &'a str, body: Vec<u8>) -> TransportFuture<'a> {
+        Box::pin(async move {
+            #[cfg(feature = "websocket")]
+            if websocket::is_websocket(url) {
+                // The socket is blocking, so it is used from a blocking task
+                let slot = self.websockets.slot(url);
+                let (url, settings, limit) = (
+                    url.to_string(),
+                    self.settings.clone(),
+                    self.max_response_bytes,
+                );
+                return tokio::task::spawn_blocking(move || {
+                    websocket::request_on(&slot, &url, &settings, &body, limit)
+                })
+                .await
+                .map_err(|e| NectarError::Transport(format!("WebSocket task failed: {e}")))?;
+            }
+            let resp = self
+                .async_http_for(url)
+                .post(url)
+                .header("Content-Type", "application/json")
+                .body(body)
+                .send()
+                .await
+                .map_err(request_error)?;
+            if !resp.status().is_success() {
+                return Err(NectarError::Status(resp.status()));
+            }
+            read_limited_async(resp, self.max_response_bytes).await
+        })
+    }
+}
+
+/// Send a blocking request and read the response within `limit` bytes
+fn execute(request: RequestBuilder, limit: Option<u64>) -> Result<Vec<u8>, NectarError> {
+    let resp = request.send().map_err(request_error)?;
+    if !resp.status().is_success() {
+        return Err(NectarError::Status(resp.status()));
+    }
+    read_limited(resp, limit)
+}
+
+/// A response body read chunk by chunk, refusing more than a limit
+///
+/// Shared by the blocking and async readers, so both refuse the same bodies.
+struct LimitedBody {
+    body: Vec<u8>,
+    limit: Option<u64>,
+}
+
+impl LimitedBody {
+    /// Start reading a body, refusing it outright if its declared length is over `limit`
+    fn new(content_length: Option<u64>, limit: Option<u64>) -> Result<Self, NectarError> {
+        if let Some(limit) = limit {
+            if let Some(length) = content_length.filter(|&length| length > limit) {
+                return Err(NectarError::ResponseTooLarge {
+                    length: Some(length),
+                    limit,
+                });
+            }
+        }
+        Ok(Self {
+            body: Vec::new(),
+            limit,
+        })
+    }
+
+    fn extend(&mut self, chunk: &[u8]) -> Result<(), NectarError> {
+        self.body.extend_from_slice(chunk);
+        match self.limit {
+            Some(limit) if self.body.len() as u64 > limit => Err(NectarError::ResponseTooLarge {
+                length: None,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Read a response body, refusing more than `limit` bytes
+fn read_limited(mut resp: Response, limit: Option<u64>) -> Result<Vec<u8>, NectarError> {
+    let mut body = LimitedBody::new(resp.content_length(), limit)?;
+    let mut chunk = [0; 16 * 1024];
+    loop {
+        match resp.read(&mut chunk) {
+            Ok(0) => return Ok(body.body),
+            Ok(read) => body.extend(&chunk[..read])?,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(NectarError::Transport(e.to_string())),
+        }
+    }
+}
+
+/// [`read_limited`] for an async response
+#[cfg(feature = "async")]
+async fn read_limited_async(
+    mut resp: reqwest::Response,
+    limit: Option<u64>,
+) -> Result<Vec<u8>, NectarError> {
+    let mut body = LimitedBody::new(resp.content_length(), limit)?;
+    while let Some(chunk) = resp.chunk().await.map_err(request_error)? {
+        body.extend(&chunk)?;
+    }
+    Ok(body.body)
+}
+
+/// An in-memory transport answering from canned results, for tests
+///
+/// Clones share their results and the requests they recorded.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    answers: Arc<Mutex<HashMap<String, Value>>>,
+    url_answers: Arc<Mutex<HashMap<String, Value>>>,
+    down: Arc<Mutex<HashSet<String>>>,
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl MockTransport {
+    /// A transport answering every method with a method-not-found error
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer calls of `method` with `result`
+    pub fn respond(self, method: &str, result: Value) -> Self {
+        self.answers
+            .lock()
+            .unwrap()
+            .insert(method.to_string(), json!({ "result": result }));
+        self
+    }
+
+    /// Answer calls of `method` with a JSON-RPC error
+    pub fn respond_error(self, method: &str, code: i32, message: &str) -> Self {
+        self.answers.lock().unwrap().insert(
+            method.to_string(),
+            json!({ "error": { "code": code, "message": message } }),
+        );
+        self
+    }
+
+    /// Answer GET and POST requests to `url` with `body`, e.g. for a REST API
+    pub fn respond_url(self, url: &str, body: Value) -> Self {
+        self.url_answers
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), body);
+        self
+    }
+
+    /// Fail every request to `url` as if the node were unreachable
+    pub fn fail_node(self, url: &str) -> Self {
+        self.down.lock().unwrap().insert(url.to_string());
+        self
+    }
+
+    /// The requests received so far, with the node each was sent to
+    ///
+    /// GET requests are recorded with a null body.
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn answer(&self, request: &Value) -> Value {
+        let method = request["method"].as_str().unwrap_or_default();
+        let mut response = match self.answers.lock().unwrap().get(method) {
+            Some(answer) => answer.clone(),
+            None => json!({ "error": {
+                "code": METHOD_NOT_FOUND_CODE,
+                "message": format!("Could not find method {method}"),
+            } }),
+        };
+        response["jsonrpc"] = json!("2.0");
+        response["id"] = request["id"].clone();
+        response
+    }
+
+    /// Record a request and fail it if `url` is down, else return any canned body for `url`
+    fn receive(&self, url: &str, request: &Value) -> Result<Option<Vec<u8>>, NectarError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((url.to_string(), request.clone()));
+        if self.down.lock().unwrap().contains(url) {
+            return Err(NectarError::Transport(format!(
+                "error sending request for url ({url}): connection refused"
+            )));
+        }
+        let answer = self.url_answers.lock().unwrap().get(url).cloned();
+        Ok(answer.map(|body| body.to_string().into_bytes()))
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body)
+            .map_err(|e| NectarError::Decode(format!("mock request: {e}")))?;
+        if let Some(body) = self.receive(url, &request)? {
+            return Ok(body);
+        }
+        let response = match &request {
+            Value::Array(requests) => {
+                Value::Array(requests.iter().map(|r| self.answer(r)).collect())
+            }
+            single => self.answer(single),
+        };
+        Ok(response.to_string().into_bytes())
+    }
+
+    fn get(&self, url: &str) -> Result<Vec<u8>, NectarError> {
+        self.receive(url, &Value::Null)?
+            .ok_or(NectarError::Status(reqwest::StatusCode::NOT_FOUND))
+    }
+}
+
+impl Client {
+    /// Send every request through `transport` instead of the built-in [`HttpTransport`]
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// The transport requests are sent through
+    pub(crate) fn transport_ref(&self) -> &dyn Transport {
+        match &self.transport {
+            Some(transport) => transport.as_ref(),
+            None => self.http_transport.get_or_init(|| self.http_transport()),
+        }
+    }
+
+    /// A new [`HttpTransport`] with this client's settings, e.g. to wrap in another transport
+    pub fn http_transport(&self) -> HttpTransport {
+        HttpTransport::with_settings(self.http.clone(), self.max_response_bytes)
+    }
+}
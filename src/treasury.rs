@@ -0,0 +1,202 @@
+//! Typed DHF treasury virtual operations and proposal payment totals
+//!
+//! [`TreasuryOp`] decodes the virtual operations that move funds into and out
+//! of the treasury, including their pre-HF24 `sps_*` names.
+//! [`ProposalPayments`] adds up `proposal_pay` operations to answer how much
+//! each proposal has been paid so far.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::dhf::TREASURY_ACCOUNT;
+use crate::history::HistoryEntry;
+use crate::op_filter::OpTypeSet;
+use crate::operation::Operation;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A proposal's daily payment from the treasury, virtual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalPay {
+    pub proposal_id: u32,
+    pub receiver: String,
+    #[serde(default = "treasury_account")]
+    pub payer: String,
+    pub payment: Asset,
+}
+
+/// Inflation paid into the treasury, virtual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DhfFunding {
+    #[serde(alias = "fund_account")]
+    pub treasury: String,
+    pub additional_funds: Asset,
+}
+
+/// The treasury's daily conversion of HIVE to HBD, virtual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DhfConversion {
+    #[serde(alias = "fund_account")]
+    pub treasury: String,
+    pub hive_amount_in: Asset,
+    pub hbd_amount_out: Asset,
+}
+
+/// The fee paid to the treasury to create a proposal, virtual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalFee {
+    pub creator: String,
+    pub treasury: String,
+    pub proposal_id: u32,
+    pub fee: Asset,
+}
+
+/// Balances of former treasury accounts moved to the treasury, virtual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsolidateTreasuryBalance {
+    pub total_moved: Vec<Asset>,
+}
+
+fn treasury_account() -> String {
+    TREASURY_ACCOUNT.to_string()
+}
+
+/// A virtual operation that moves funds into or out of the treasury
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum TreasuryOp {
+    ProposalPay(ProposalPay),
+    DhfFunding(DhfFunding),
+    DhfConversion(DhfConversion),
+    ProposalFee(ProposalFee),
+    ConsolidateTreasuryBalance(ConsolidateTreasuryBalance),
+}
+
+impl TreasuryOp {
+    /// Decode `op`, or `None` if it is not a treasury operation
+    pub fn from_operation(op: &Operation) -> Result<Option<Self>, String> {
+        Ok(Some(match op.name() {
+            "proposal_pay" => Self::ProposalPay(op.value_as()?),
+            "dhf_funding" | "sps_fund" => Self::DhfFunding(op.value_as()?),
+            "dhf_conversion" | "sps_convert" => Self::DhfConversion(op.value_as()?),
+            "proposal_fee" => Self::ProposalFee(op.value_as()?),
+            "consolidate_treasury_balance" => Self::ConsolidateTreasuryBalance(op.value_as()?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// The amounts the treasury gained (positive) or paid out (negative)
+    pub fn treasury_delta(&self) -> Vec<Asset> {
+        let negate = |asset: Asset| Asset::new(-asset.amount, asset.symbol);
+        match self {
+            Self::ProposalPay(pay) if pay.receiver == TREASURY_ACCOUNT => Vec::new(),
+            Self::ProposalPay(pay) => vec![negate(pay.payment)],
+            Self::DhfFunding(funding) => vec![funding.additional_funds],
+            Self::DhfConversion(conversion) => {
+                vec![negate(conversion.hive_amount_in), conversion.hbd_amount_out]
+            }
+            Self::ProposalFee(fee) => vec![fee.fee],
+            Self::ConsolidateTreasuryBalance(moved) => moved.total_moved.clone(),
+        }
+    }
+}
+
+/// Payments received by one proposal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalPaid {
+    pub proposal_id: u32,
+    pub receiver: String,
+    pub total: Asset,
+    pub payments: u32,
+    pub first_paid: String,
+    pub last_paid: String,
+}
+
+/// Running totals of `proposal_pay` operations by proposal
+#[derive(Debug, Clone, Default)]
+pub struct ProposalPayments {
+    paid: BTreeMap<u32, ProposalPaid>,
+}
+
+impl ProposalPayments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a history entry, ignoring anything but `proposal_pay`
+    pub fn apply(&mut self, entry: &HistoryEntry) -> Result<(), String> {
+        if let Some(TreasuryOp::ProposalPay(pay)) = TreasuryOp::from_operation(&entry.op)? {
+            self.record(&pay, &entry.timestamp);
+        }
+        Ok(())
+    }
+
+    /// Add one payment made at `timestamp`
+    pub fn record(&mut self, pay: &ProposalPay, timestamp: &str) {
+        let paid = self
+            .paid
+            .entry(pay.proposal_id)
+            .or_insert_with(|| ProposalPaid {
+                proposal_id: pay.proposal_id,
+                receiver: pay.receiver.clone(),
+                total: Asset::zero(pay.payment.symbol),
+                payments: 0,
+                first_paid: timestamp.to_string(),
+                last_paid: timestamp.to_string(),
+            });
+        paid.total = paid.total.checked_add(pay.payment).unwrap_or(paid.total);
+        paid.payments += 1;
+        if timestamp < paid.first_paid.as_str() {
+            paid.first_paid = timestamp.to_string();
+        }
+        if timestamp > paid.last_paid.as_str() {
+            paid.last_paid = timestamp.to_string();
+        }
+    }
+
+    pub fn get(&self, proposal_id: u32) -> Option<&ProposalPaid> {
+        self.paid.get(&proposal_id)
+    }
+
+    /// Total paid to `proposal_id`, zero HBD if nothing was recorded
+    pub fn total(&self, proposal_id: u32) -> Asset {
+        self.get(proposal_id)
+            .map_or(Asset::zero(AssetSymbol::Hbd), |paid| paid.total)
+    }
+
+    /// Proposals by id
+    pub fn proposals(&self) -> impl Iterator<Item = &ProposalPaid> {
+        self.paid.values()
+    }
+
+    /// Sum of all recorded payments
+    pub fn grand_total(&self) -> Asset {
+        Asset::new(
+            self.paid.values().map(|paid| paid.total.amount).sum(),
+            AssetSymbol::Hbd,
+        )
+    }
+}
+
+impl Client {
+    /// Add up the `proposal_pay` operations in `receiver`'s history
+    pub fn proposal_payments(&self, receiver: &str) -> Result<ProposalPayments, String> {
+        let mut payments = ProposalPayments::new();
+        let filter = OpTypeSet::new().with("proposal_pay")?;
+        for entry in self.account_history(receiver).filter(filter) {
+            payments.apply(&entry?)?;
+        }
+        Ok(payments)
+    }
+
+    /// How much the treasury has paid proposal `proposal_id` so far
+    pub fn proposal_paid(&self, proposal_id: u32) -> Result<Asset, String> {
+        let proposal = self
+            .find_proposals(&[proposal_id])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Proposal {proposal_id} not found"))?;
+        Ok(self
+            .proposal_payments(&proposal.receiver)?
+            .total(proposal_id))
+    }
+}
@@ -0,0 +1,76 @@
+//! Node versions, and keeping nodes of other chains out of the node list
+//!
+//! A testnet endpoint in a mainnet node list answers every read, just with
+//! the wrong chain's data. With [`Client::verify_chain_on_add`], nodes added
+//! by [`Client::set_nodes`] are asked for their chain id first, and those
+//! reporting a different one than the client's
+//! [`ChainConfig`](crate::ChainConfig) are listed as failing with
+//! [`FailReason::WrongChain`] instead of being used.
+
+use crate::error::NectarError;
+use crate::fail_reason::{FailReason, NodeFailure};
+use crate::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What `database_api.get_version` reports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Version {
+    pub blockchain_version: String,
+    #[serde(default)]
+    pub hive_revision: String,
+    #[serde(default)]
+    pub fc_revision: String,
+    pub chain_id: String,
+    /// `mainnet`, `testnet` or `mirrornet`, on nodes recent enough to report it
+    #[serde(default)]
+    pub node_type: Option<String>,
+}
+
+impl Client {
+    /// The version of the first node that answers
+    pub fn get_version(&self) -> Result<Version, NectarError> {
+        self.call("database_api.get_version", ())
+    }
+
+    /// The version of `node`, without trying other nodes
+    pub fn node_version(&self, node: &str) -> Result<Version, NectarError> {
+        self.call_node(node, "database_api.get_version", &())
+    }
+
+    /// Check the chain id of nodes before [`Client::set_nodes`] adds them
+    ///
+    /// Nodes that do not answer are kept; only a reported mismatch drops one.
+    pub fn verify_chain_on_add(mut self) -> Self {
+        self.verify_chain = true;
+        self
+    }
+
+    /// Move nodes not already in use that report another chain to `failing_nodes`
+    pub(crate) fn drop_wrong_chain(
+        &self,
+        nodes: &mut Vec<String>,
+        failing_nodes: &mut HashMap<Url, NodeFailure>,
+    ) {
+        nodes.retain(|node| {
+            if self.nodes.contains(node) {
+                return true;
+            }
+            let Ok(version) = self.node_version(node) else {
+                return true;
+            };
+            if version.chain_id == self.chain.chain_id {
+                return true;
+            }
+            let message = format!(
+                "wrong chain: reports chain id {}, expected {} ({})",
+                version.chain_id, self.chain.chain_id, self.chain.name
+            );
+            eprintln!("Warning: dropping {node}: {message}");
+            if let Ok(url) = Url::parse(node) {
+                failing_nodes.insert(url, NodeFailure::new(FailReason::WrongChain, message));
+            }
+            false
+        });
+    }
+}
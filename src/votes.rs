@@ -0,0 +1,118 @@
+//! Active votes on a post, annotated with voter HIVE Power and vote age
+
+use crate::asset::Asset;
+use crate::numbers::de_int;
+use crate::timestamp::parse_timestamp;
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum number of votes `database_api.list_votes` returns per call
+pub const LIST_VOTES_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vote {
+    pub voter: String,
+    pub author: String,
+    pub permlink: String,
+    #[serde(deserialize_with = "de_int")]
+    pub weight: u64,
+    #[serde(deserialize_with = "de_int")]
+    pub rshares: i64,
+    pub vote_percent: i16,
+    pub last_update: String,
+    #[serde(default)]
+    pub num_changes: i32,
+}
+
+/// A vote enriched with the voter's effective HIVE Power and the age of the vote
+#[derive(Debug, Clone)]
+pub struct AnnotatedVote {
+    pub vote: Vote,
+    pub effective_vests: Asset,
+    pub effective_hp: Asset,
+    /// Seconds between the vote and the current head block time
+    pub age_secs: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ListVotesParams<'a> {
+    start: (&'a str, &'a str, &'a str),
+    limit: u32,
+    order: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListVotesResponse {
+    votes: Vec<Vote>,
+}
+
+impl Client {
+    /// Fetch every vote on a post via `database_api.list_votes`
+    pub fn get_active_votes(&self, author: &str, permlink: &str) -> Result<Vec<Vote>, String> {
+        let mut votes: Vec<Vote> = Vec::new();
+        loop {
+            let start_voter = votes.last().map_or("", |v| v.voter.as_str());
+            let params = ListVotesParams {
+                start: (author, permlink, start_voter),
+                limit: LIST_VOTES_LIMIT,
+                order: "by_comment_voter",
+            };
+            let resp: ListVotesResponse = self
+                .call("database_api.list_votes", params)
+                .map_err(|e| format!("Error fetching votes for @{author}/{permlink}: {e}"))?;
+            let page_len = resp.votes.len();
+            let skip = usize::from(!votes.is_empty());
+            let before = votes.len();
+            votes.extend(
+                resp.votes
+                    .into_iter()
+                    .skip(skip)
+                    .take_while(|v| v.author == author && v.permlink == permlink),
+            );
+            // A page reaching the next post's votes holds the last of this post's
+            let reached_next = votes.len() - before < page_len.saturating_sub(skip);
+            if page_len < LIST_VOTES_LIMIT as usize || reached_next {
+                return Ok(votes);
+            }
+        }
+    }
+
+    /// Fetch the votes on a post along with each voter's effective HP and vote age
+    ///
+    /// Voter accounts are looked up in batches rather than one request per voter.
+    pub fn get_annotated_votes(
+        &self,
+        author: &str,
+        permlink: &str,
+    ) -> Result<Vec<AnnotatedVote>, String> {
+        let votes = self.get_active_votes(author, permlink)?;
+        let props = self.get_dynamic_global_properties()?;
+        let now = parse_timestamp(&props.time)?;
+        let names: Vec<&str> = votes.iter().map(|v| v.voter.as_str()).collect();
+        let vests: HashMap<String, Asset> = self
+            .find_accounts(&names)?
+            .into_iter()
+            .map(|account| {
+                let vests = account.effective_vests();
+                (account.name, vests)
+            })
+            .collect();
+        votes
+            .into_iter()
+            .map(|vote| {
+                let effective_vests = vests
+                    .get(&vote.voter)
+                    .copied()
+                    .unwrap_or_else(Asset::zero_vests);
+                let age_secs = now - parse_timestamp(&vote.last_update)?;
+                Ok(AnnotatedVote {
+                    effective_hp: props.vests_to_hp(effective_vests),
+                    effective_vests,
+                    age_secs,
+                    vote,
+                })
+            })
+            .collect()
+    }
+}
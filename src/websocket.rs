@@ -0,0 +1,255 @@
+//! JSON-RPC over persistent WebSocket connections
+//!
+//! Enabled with the `websocket` feature. Nodes listed as `ws://` or `wss://`
+//! URLs are called over one long-lived socket per node instead of an HTTP
+//! request per call, which saves a round trip on every call of a bot or
+//! [`BlockStream`](crate::block::BlockStream) polling the head. They take part
+//! in failover and retries like HTTP nodes, and can be mixed with them:
+//!
+//! ```no_run
+//! use nectarflower_rs::Client;
+//!
+//! let client = Client::builder()
+//!     .nodes(["wss://api.hive.blog", "https://api.deathwing.me"])
+//!     .build();
+//! let props: serde_json::Value =
+//!     client.call("database_api.get_dynamic_global_properties", serde_json::json!({}))?;
+//! # Ok::<(), nectarflower_rs::NectarError>(())
+//! ```
+//!
+//! A socket the node closed is reopened on the next call, which is sent again
+//! once over the new socket. Timeouts, the user agent and DNS settings of the
+//! client apply to the connection. The sockets belong to the client's default
+//! [`HttpTransport`](crate::transport::HttpTransport), and
+//! [`AsyncClient`](crate::AsyncClient) uses them from a blocking task. The protocol itself is handled
+//! by `tungstenite`.
+
+use crate::dns::HttpSettings;
+use crate::error::NectarError;
+use crate::{Client, Url};
+use std::collections::HashMap;
+use std::io;
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::error::CapacityError;
+use tungstenite::handshake::HandshakeError;
+use tungstenite::http::header::USER_AGENT;
+use tungstenite::http::HeaderValue;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/// Whether `node` is a `ws://` or `wss://` URL
+pub fn is_websocket(node: &str) -> bool {
+    Url::parse(node).is_ok_and(|url| matches!(url.scheme(), "ws" | "wss"))
+}
+
+pub(crate) type Slot = Arc<Mutex<Option<Connection>>>;
+
+/// Open sockets by node, each behind its own lock
+#[derive(Debug, Default)]
+pub(crate) struct WebSockets {
+    connections: Mutex<HashMap<String, Slot>>,
+}
+
+impl WebSockets {
+    pub(crate) fn slot(&self, node: &str) -> Slot {
+        let mut connections = self.connections.lock().unwrap();
+        Arc::clone(connections.entry(node.to_string()).or_default())
+    }
+
+    pub(crate) fn close_all(&self) {
+        self.connections.lock().unwrap().clear();
+    }
+}
+
+impl Client {
+    /// Close the sockets to all nodes; the next call opens new ones
+    ///
+    /// Only the default transport keeps sockets; a custom one is untouched.
+    pub fn close_websockets(&self) {
+        if let Some(transport) = self.http_transport.get() {
+            transport.close_websockets();
+        }
+    }
+}
+
+/// Send `payload` over the socket in `slot`, reconnecting once if the node closed it
+pub(crate) fn request_on(
+    slot: &Slot,
+    node: &str,
+    settings: &HttpSettings,
+    payload: &[u8],
+    limit: Option<u64>,
+) -> Result<Vec<u8>, NectarError> {
+    let mut connection = slot.lock().unwrap();
+    if let Some(open) = connection.as_mut() {
+        match open.request(payload) {
+            Ok(body) => return Ok(body),
+            // The node closed the idle socket; reconnect below
+            Err(e) if e.is_closed() => *connection = None,
+            Err(e) => {
+                *connection = None;
+                return Err(e.into());
+            }
+        }
+    }
+    let mut fresh = Connection::open(node, settings, limit)?;
+    let body = fresh.request(payload)?;
+    *connection = Some(fresh);
+    Ok(body)
+}
+
+#[derive(Debug)]
+enum WsError {
+    Io(io::Error),
+    Closed(String),
+    Upgrade(String),
+    TooLarge { length: u64, limit: u64 },
+}
+
+impl WsError {
+    /// Whether the socket was closed or reset, rather than slow or refused
+    fn is_closed(&self) -> bool {
+        match self {
+            WsError::Io(e) => !is_timeout(e),
+            WsError::Closed(_) => true,
+            WsError::Upgrade(_) | WsError::TooLarge { .. } => false,
+        }
+    }
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
+}
+
+impl From<io::Error> for WsError {
+    fn from(e: io::Error) -> Self {
+        WsError::Io(e)
+    }
+}
+
+impl From<tungstenite::Error> for WsError {
+    fn from(e: tungstenite::Error) -> Self {
+        match e {
+            tungstenite::Error::Io(e) => WsError::Io(e),
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+                WsError::Closed("closed by the node".to_string())
+            }
+            tungstenite::Error::Protocol(e) => WsError::Closed(e.to_string()),
+            tungstenite::Error::Capacity(CapacityError::MessageTooLong { size, max_size }) => {
+                WsError::TooLarge {
+                    length: size as u64,
+                    limit: max_size as u64,
+                }
+            }
+            tungstenite::Error::Http(resp) => WsError::Upgrade(format!("HTTP {}", resp.status())),
+            e => WsError::Upgrade(e.to_string()),
+        }
+    }
+}
+
+impl From<WsError> for NectarError {
+    fn from(e: WsError) -> Self {
+        match e {
+            WsError::Io(e) if is_timeout(&e) => {
+                NectarError::Timeout(format!("WebSocket read timed out: {e}"))
+            }
+            WsError::Io(e) => NectarError::Transport(format!("WebSocket error: {e}")),
+            WsError::Closed(message) => {
+                NectarError::Transport(format!("WebSocket closed: {message}"))
+            }
+            WsError::Upgrade(message) => {
+                NectarError::Transport(format!("WebSocket upgrade refused: {message}"))
+            }
+            WsError::TooLarge { length, limit } => NectarError::ResponseTooLarge {
+                length: Some(length),
+                limit,
+            },
+        }
+    }
+}
+
+/// One upgraded connection to a node
+pub(crate) struct Connection {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl std::fmt::Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Connection").finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    /// Connect with the client's DNS settings and timeouts, then upgrade
+    ///
+    /// Messages over `limit` bytes are refused while they are read.
+    fn open(node: &str, settings: &HttpSettings, limit: Option<u64>) -> Result<Self, WsError> {
+        let url = Url::parse(node).map_err(|e| WsError::Upgrade(format!("{node}: {e}")))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| WsError::Upgrade(format!("{node} has no host")))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| WsError::Upgrade(format!("{node} has no port")))?;
+        let tcp = connect(host, port, settings)?;
+        tcp.set_read_timeout(Some(settings.timeout))?;
+        tcp.set_write_timeout(Some(settings.timeout))?;
+        tcp.set_nodelay(true)?;
+
+        let mut request = node.into_client_request()?;
+        if let Some(user_agent) = &settings.user_agent {
+            let value = HeaderValue::from_str(user_agent)
+                .map_err(|e| WsError::Upgrade(format!("user agent: {e}")))?;
+            request.headers_mut().insert(USER_AGENT, value);
+        }
+        let limit = limit.map(|limit| usize::try_from(limit).unwrap_or(usize::MAX));
+        let config = WebSocketConfig::default()
+            .max_message_size(limit)
+            .max_frame_size(limit);
+        let (socket, _) = tungstenite::client_tls_with_config(request, tcp, Some(config), None)
+            .map_err(|e| match e {
+                HandshakeError::Failure(e) => WsError::from(e),
+                HandshakeError::Interrupted(_) => {
+                    WsError::Io(io::Error::from(io::ErrorKind::WouldBlock))
+                }
+            })?;
+        Ok(Connection { socket })
+    }
+
+    /// Send one text message and wait for the next data message
+    fn request(&mut self, payload: &[u8]) -> Result<Vec<u8>, WsError> {
+        let text = String::from_utf8(payload.to_vec())
+            .map_err(|e| WsError::Upgrade(format!("request is not UTF-8: {e}")))?;
+        self.socket.send(Message::text(text))?;
+        loop {
+            // Pings are answered by tungstenite on the next read or write
+            match self.socket.read()? {
+                Message::Text(text) => return Ok(text.as_bytes().to_vec()),
+                Message::Binary(bytes) => return Ok(bytes.to_vec()),
+                Message::Close(_) => return Err(WsError::Closed("closed by the node".to_string())),
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+            }
+        }
+    }
+}
+
+/// Connect to the first reachable address of `host`
+///
+/// Without a connect timeout, the client's request timeout bounds the connect.
+fn connect(host: &str, port: u16, settings: &HttpSettings) -> io::Result<TcpStream> {
+    let timeout = settings.connect_timeout.unwrap_or(settings.timeout);
+    let mut last_error = None;
+    for addr in settings.socket_addrs(host, port)? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotFound)))
+}
@@ -0,0 +1,326 @@
+//! Batched withdrawal queue for exchange hot wallets
+//!
+//! The queue serializes with serde. Persist it from the callback of
+//! [`WithdrawalQueue::process_with`], which runs after a batch is signed and
+//! marked pending but before it is broadcast: a queue restored after a crash
+//! then holds the batch as pending under its transaction id, for
+//! [`WithdrawalQueue::refresh`] to resolve, instead of queued to be paid twice.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::operation::Operation;
+use crate::timestamp::parse_timestamp;
+use crate::transaction::{TransactionSigner, TransactionStatus};
+use crate::{Client, WriteClient, WriteError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Conservative RC cost estimate for a single transfer operation
+pub const DEFAULT_RC_PER_TRANSFER: i64 = 2_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WithdrawalStatus {
+    /// Waiting to be included in a batch
+    Queued,
+    /// Refused by a limit check and will not be sent
+    Rejected(String),
+    /// Broadcast but not yet seen in a block; must not be re-sent until it expires
+    Pending { trx_id: String, expiration: String },
+    /// Broadcast and included in a block
+    Sent {
+        trx_id: String,
+        status: TransactionStatus,
+    },
+    /// Signing failed, the transaction was refused before it could have been
+    /// accepted, or it expired without being included and its expiration is
+    /// irreversible; safe to re-queue
+    ///
+    /// A transaction that expired while still reversible could yet be
+    /// included by a fork, so it stays [`Self::Pending`] until then.
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub id: u64,
+    pub to: String,
+    pub amount: Asset,
+    pub memo: String,
+    pub status: WithdrawalStatus,
+}
+
+/// Limits enforced by a [`WithdrawalQueue`]; amounts are per asset symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WithdrawalLimits {
+    pub per_withdrawal: HashMap<AssetSymbol, i64>,
+    pub per_day: HashMap<AssetSymbol, i64>,
+    /// Maximum transfers per transaction
+    pub max_per_tx: Option<usize>,
+}
+
+impl WithdrawalLimits {
+    pub fn per_withdrawal(mut self, max: Asset) -> Self {
+        self.per_withdrawal.insert(max.symbol, max.amount);
+        self
+    }
+
+    pub fn per_day(mut self, max: Asset) -> Self {
+        self.per_day.insert(max.symbol, max.amount);
+        self
+    }
+
+    pub fn max_per_tx(mut self, max: usize) -> Self {
+        self.max_per_tx = Some(max.max(1));
+        self
+    }
+}
+
+/// Queue of outgoing transfers from a hot wallet, broadcast in batches
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithdrawalQueue {
+    account: String,
+    limits: WithdrawalLimits,
+    rc_per_transfer: i64,
+    withdrawals: Vec<Withdrawal>,
+    next_id: u64,
+    day: i64,
+    sent_today: HashMap<AssetSymbol, i64>,
+}
+
+impl WithdrawalQueue {
+    pub fn new(account: &str, limits: WithdrawalLimits) -> Self {
+        Self {
+            account: account.to_string(),
+            limits,
+            rc_per_transfer: DEFAULT_RC_PER_TRANSFER,
+            withdrawals: Vec::new(),
+            next_id: 1,
+            day: 0,
+            sent_today: HashMap::new(),
+        }
+    }
+
+    /// Override the RC cost assumed for each transfer
+    pub fn rc_per_transfer(mut self, rc: i64) -> Self {
+        self.rc_per_transfer = rc.max(1);
+        self
+    }
+
+    /// Queue a withdrawal, returning its id; limit violations are recorded as rejected
+    pub fn enqueue(&mut self, to: &str, amount: Asset, memo: &str) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let status = match self.limits.per_withdrawal.get(&amount.symbol) {
+            _ if amount.amount <= 0 => WithdrawalStatus::Rejected("Amount must be positive".into()),
+            _ if amount.symbol == AssetSymbol::Vests => {
+                WithdrawalStatus::Rejected("VESTS cannot be transferred".into())
+            }
+            Some(&max) if amount.amount > max => WithdrawalStatus::Rejected(format!(
+                "{amount} exceeds the per-withdrawal limit of {}",
+                Asset::new(max, amount.symbol)
+            )),
+            _ => WithdrawalStatus::Queued,
+        };
+        self.withdrawals.push(Withdrawal {
+            id,
+            to: to.to_string(),
+            amount,
+            memo: memo.to_string(),
+            status,
+        });
+        id
+    }
+
+    /// Current status of a withdrawal
+    pub fn status(&self, id: u64) -> Option<&WithdrawalStatus> {
+        self.withdrawals
+            .iter()
+            .find(|w| w.id == id)
+            .map(|w| &w.status)
+    }
+
+    pub fn withdrawals(&self) -> &[Withdrawal] {
+        &self.withdrawals
+    }
+
+    /// Put a failed withdrawal back into the queue
+    pub fn requeue(&mut self, id: u64) -> bool {
+        match self.withdrawals.iter_mut().find(|w| w.id == id) {
+            Some(w) if matches!(w.status, WithdrawalStatus::Failed(_)) => {
+                w.status = WithdrawalStatus::Queued;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Resolve pending withdrawals whose transactions were included or irreversibly expired
+    pub fn refresh(&mut self, client: &Client) -> Result<(), String> {
+        for w in &mut self.withdrawals {
+            let WithdrawalStatus::Pending { trx_id, expiration } = &w.status else {
+                continue;
+            };
+            let status = client.find_transaction(trx_id, Some(expiration))?;
+            if status.is_included() {
+                w.status = WithdrawalStatus::Sent {
+                    trx_id: trx_id.clone(),
+                    status,
+                };
+            } else if status == TransactionStatus::ExpiredIrreversible {
+                w.status = WithdrawalStatus::Failed(format!("Transaction {trx_id} expired"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop withdrawals that reached a final state
+    pub fn prune(&mut self) {
+        self.withdrawals.retain(|w| {
+            matches!(
+                w.status,
+                WithdrawalStatus::Queued | WithdrawalStatus::Failed(_)
+            )
+        });
+    }
+
+    /// Broadcast the next batch of queued withdrawals, returning how many were sent
+    ///
+    /// The batch respects the daily limits (by chain time), the per-transaction
+    /// operation cap and the hot wallet's available resource credits.
+    pub fn process(
+        &mut self,
+        client: &WriteClient,
+        signer: &dyn TransactionSigner,
+    ) -> Result<usize, String> {
+        self.process_with(client, signer, |_| Ok(()))
+    }
+
+    /// Like [`Self::process`], calling `persist` with the queue once the batch
+    /// is signed and marked pending, just before it is broadcast
+    ///
+    /// If `persist` fails, nothing is broadcast and the batch stays queued.
+    pub fn process_with<F>(
+        &mut self,
+        client: &WriteClient,
+        signer: &dyn TransactionSigner,
+        mut persist: F,
+    ) -> Result<usize, String>
+    where
+        F: FnMut(&Self) -> Result<(), String>,
+    {
+        let props = client.get_dynamic_global_properties()?;
+        let now = parse_timestamp(&props.time)?;
+        if now.div_euclid(86_400) != self.day {
+            self.day = now.div_euclid(86_400);
+            self.sent_today.clear();
+        }
+        let rc = client
+            .find_rc_accounts(&[&self.account])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("RC account '{}' not found", self.account))?;
+        let rc_capacity = (rc.current_mana(now) / self.rc_per_transfer).max(0) as usize;
+        let max_ops = self
+            .limits
+            .max_per_tx
+            .unwrap_or(usize::MAX)
+            .min(rc_capacity);
+
+        let mut batch = Vec::new();
+        let mut batch_totals: HashMap<AssetSymbol, i64> = HashMap::new();
+        for (index, w) in self.withdrawals.iter().enumerate() {
+            if batch.len() >= max_ops {
+                break;
+            }
+            if w.status != WithdrawalStatus::Queued {
+                continue;
+            }
+            let symbol = w.amount.symbol;
+            let total = self.sent_today.get(&symbol).copied().unwrap_or(0)
+                + batch_totals.get(&symbol).copied().unwrap_or(0)
+                + w.amount.amount;
+            if self
+                .limits
+                .per_day
+                .get(&symbol)
+                .is_some_and(|&max| total > max)
+            {
+                continue;
+            }
+            *batch_totals.entry(symbol).or_default() += w.amount.amount;
+            batch.push(index);
+        }
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let ops = batch
+            .iter()
+            .map(|&i| {
+                let w = &self.withdrawals[i];
+                Operation::new(
+                    "transfer_operation",
+                    serde_json::json!({
+                        "from": self.account,
+                        "to": w.to,
+                        "amount": w.amount.to_nai(),
+                        "memo": w.memo,
+                    }),
+                )
+            })
+            .collect();
+        let mut tx = client.prepare_transaction(ops)?;
+        let trx_id = match client.sign_transaction(&mut tx, &[signer]) {
+            Ok(trx_id) => trx_id,
+            Err(e) => {
+                self.set_status(&batch, WithdrawalStatus::Failed(e.to_string()));
+                return Ok(0);
+            }
+        };
+        self.set_status(
+            &batch,
+            WithdrawalStatus::Pending {
+                trx_id: trx_id.clone(),
+                expiration: tx.expiration.clone(),
+            },
+        );
+        self.add_sent(&batch_totals, 1);
+        if let Err(e) = persist(self) {
+            self.set_status(&batch, WithdrawalStatus::Queued);
+            self.add_sent(&batch_totals, -1);
+            return Err(e);
+        }
+
+        let status = match client.broadcast_reliably(&tx, &trx_id, 5) {
+            Ok(status) if status.is_included() => WithdrawalStatus::Sent {
+                trx_id: trx_id.clone(),
+                status,
+            },
+            Ok(TransactionStatus::ExpiredIrreversible) => {
+                WithdrawalStatus::Failed(format!("Transaction {trx_id} expired"))
+            }
+            Err(e @ (WriteError::Limit(_) | WriteError::Decode(_) | WriteError::Rejected(_))) => {
+                WithdrawalStatus::Failed(e.to_string())
+            }
+            _ => return Ok(batch.len()),
+        };
+        let sent = !matches!(status, WithdrawalStatus::Failed(_));
+        if !sent {
+            self.add_sent(&batch_totals, -1);
+        }
+        self.set_status(&batch, status);
+        Ok(if sent { batch.len() } else { 0 })
+    }
+
+    fn set_status(&mut self, batch: &[usize], status: WithdrawalStatus) {
+        for &i in batch {
+            self.withdrawals[i].status = status.clone();
+        }
+    }
+
+    /// Count a batch's totals towards today's limits, or with `sign` -1 uncount them
+    fn add_sent(&mut self, totals: &HashMap<AssetSymbol, i64>, sign: i64) {
+        for (symbol, amount) in totals {
+            *self.sent_today.entry(*symbol).or_default() += sign * amount;
+        }
+    }
+}
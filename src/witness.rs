@@ -0,0 +1,385 @@
+//! Witness schedule and production round tracking
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::block::BLOCK_INTERVAL;
+use crate::numbers::de_int;
+use crate::operation::Operation;
+use crate::timestamp::parse_timestamp;
+use crate::transaction::TransactionSigner;
+use crate::{Client, WriteClient};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessSchedule {
+    pub id: u64,
+    pub current_virtual_time: String,
+    pub next_shuffle_block_num: u32,
+    pub current_shuffled_witnesses: Vec<String>,
+    pub num_scheduled_witnesses: u32,
+    #[serde(default)]
+    pub elected_weight: u32,
+    #[serde(default)]
+    pub timeshare_weight: u32,
+    #[serde(default)]
+    pub miner_weight: u32,
+    #[serde(default)]
+    pub witness_pay_normalization_factor: u32,
+}
+
+/// An upcoming block production slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledSlot {
+    /// Offset from the current head block, starting at 1
+    pub slot: u32,
+    /// Absolute slot number on the chain
+    pub aslot: u64,
+    pub witness: String,
+}
+
+impl ScheduledSlot {
+    /// Expected time until this slot, assuming no missed blocks
+    pub fn eta(&self) -> Duration {
+        BLOCK_INTERVAL * self.slot
+    }
+}
+
+/// The remaining slots of the current witness round
+#[derive(Debug, Clone)]
+pub struct Round {
+    pub head_block_number: u32,
+    pub next_shuffle_block_num: u32,
+    pub slots: Vec<ScheduledSlot>,
+}
+
+impl Round {
+    /// The next slot in this round assigned to `witness`, if any
+    pub fn next_slot_for(&self, witness: &str) -> Option<&ScheduledSlot> {
+        self.slots.iter().find(|slot| slot.witness == witness)
+    }
+}
+
+/// Signing key of a witness that disabled block production
+pub const DISABLED_SIGNING_KEY: &str = "STM1111111111111111111111111111111114T1Anm";
+
+/// Maximum number of witnesses `database_api.list_witnesses` returns per call
+pub const LIST_WITNESSES_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Price {
+    pub base: Asset,
+    pub quote: Asset,
+}
+
+impl Price {
+    /// HBD per HIVE as quoted by this price
+    pub fn to_f64(&self) -> f64 {
+        let quote = self.quote.to_f64();
+        if quote == 0.0 {
+            0.0
+        } else {
+            self.base.to_f64() / quote
+        }
+    }
+}
+
+/// A witness as returned by `database_api.list_witnesses`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Witness {
+    pub owner: String,
+    #[serde(default)]
+    pub url: String,
+    /// Approval votes in VESTS satoshis
+    #[serde(deserialize_with = "de_int")]
+    pub votes: i64,
+    pub total_missed: u32,
+    #[serde(default)]
+    pub last_confirmed_block_num: u32,
+    pub signing_key: String,
+    pub running_version: String,
+    pub hbd_exchange_rate: Price,
+    pub last_hbd_exchange_update: String,
+    #[serde(default)]
+    pub props: Option<ChainProperties>,
+}
+
+/// Chain parameters a witness votes on, published with `witness_update`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainProperties {
+    pub account_creation_fee: Asset,
+    #[serde(deserialize_with = "de_int")]
+    pub maximum_block_size: u32,
+    /// Interest on HBD savings in basis points
+    #[serde(deserialize_with = "de_int")]
+    pub hbd_interest_rate: u16,
+}
+
+impl Witness {
+    pub fn is_disabled(&self) -> bool {
+        self.signing_key == DISABLED_SIGNING_KEY
+    }
+}
+
+/// A witness's position in a [`WitnessRanking`]
+#[derive(Debug, Clone)]
+pub struct RankedWitness {
+    pub rank: usize,
+    pub witness: Witness,
+    /// Votes expressed as HIVE Power at the snapshot's VESTS price
+    pub votes_hp: Asset,
+    /// Seconds since the witness last published a price feed
+    pub feed_age_secs: i64,
+}
+
+/// Top witnesses by votes at a point in time
+#[derive(Debug, Clone)]
+pub struct WitnessRanking {
+    pub head_block_number: u32,
+    pub time: String,
+    pub witnesses: Vec<RankedWitness>,
+}
+
+impl WitnessRanking {
+    pub fn get(&self, owner: &str) -> Option<&RankedWitness> {
+        self.witnesses.iter().find(|w| w.witness.owner == owner)
+    }
+
+    /// Changes from an earlier snapshot, for alerting
+    pub fn changes_since(&self, previous: &WitnessRanking) -> Vec<WitnessChange> {
+        let mut changes = Vec::new();
+        for current in &self.witnesses {
+            let owner = current.witness.owner.clone();
+            let Some(before) = previous.get(&owner) else {
+                changes.push(WitnessChange::Entered {
+                    owner,
+                    rank: current.rank,
+                });
+                continue;
+            };
+            if before.rank != current.rank {
+                changes.push(WitnessChange::Moved {
+                    owner: owner.clone(),
+                    from: before.rank,
+                    to: current.rank,
+                });
+            }
+            if current.witness.total_missed > before.witness.total_missed {
+                changes.push(WitnessChange::MissedBlocks {
+                    owner: owner.clone(),
+                    missed: current.witness.total_missed - before.witness.total_missed,
+                });
+            }
+            if current.witness.running_version != before.witness.running_version {
+                changes.push(WitnessChange::VersionChanged {
+                    owner: owner.clone(),
+                    from: before.witness.running_version.clone(),
+                    to: current.witness.running_version.clone(),
+                });
+            }
+            if current.witness.is_disabled() != before.witness.is_disabled() {
+                changes.push(WitnessChange::SigningKeyChanged {
+                    owner,
+                    disabled: current.witness.is_disabled(),
+                });
+            }
+        }
+        for before in &previous.witnesses {
+            if self.get(&before.witness.owner).is_none() {
+                changes.push(WitnessChange::Left {
+                    owner: before.witness.owner.clone(),
+                    rank: before.rank,
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// A difference between two [`WitnessRanking`] snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessChange {
+    /// The witness entered the ranking
+    Entered {
+        owner: String,
+        rank: usize,
+    },
+    /// The witness dropped out of the ranking
+    Left {
+        owner: String,
+        rank: usize,
+    },
+    Moved {
+        owner: String,
+        from: usize,
+        to: usize,
+    },
+    MissedBlocks {
+        owner: String,
+        missed: u32,
+    },
+    VersionChanged {
+        owner: String,
+        from: String,
+        to: String,
+    },
+    /// The witness disabled or re-enabled block production
+    SigningKeyChanged {
+        owner: String,
+        disabled: bool,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct ListWitnessesParams<'a> {
+    start: (i64, &'a str),
+    limit: u32,
+    order: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct WitnessesResponse {
+    witnesses: Vec<Witness>,
+}
+
+/// Build a `feed_publish` operation quoting one HIVE at `price` HBD
+pub fn feed_publish_operation(witness: &str, price: f64) -> Result<Operation, String> {
+    if !price.is_finite() || price <= 0.0 {
+        return Err(format!("Invalid feed price: {price}"));
+    }
+    let base = Asset::new((price * 1_000.0).round() as i64, AssetSymbol::Hbd);
+    Ok(Operation::new(
+        "feed_publish_operation",
+        serde_json::json!({
+            "publisher": witness,
+            "exchange_rate": {
+                "base": base.to_nai(),
+                "quote": Asset::new(1_000, AssetSymbol::Hive).to_nai(),
+            },
+        }),
+    ))
+}
+
+/// Build a `witness_update` operation switching `witness` to `signing_key`
+///
+/// The URL and chain properties are kept as they are, so the witness
+/// must have been fetched with its properties.
+pub fn witness_update_operation(witness: &Witness, signing_key: &str) -> Result<Operation, String> {
+    let props = witness
+        .props
+        .as_ref()
+        .ok_or_else(|| format!("Chain properties of witness {} are unknown", witness.owner))?;
+    Ok(Operation::new(
+        "witness_update_operation",
+        serde_json::json!({
+            "owner": witness.owner,
+            "url": witness.url,
+            "block_signing_key": signing_key,
+            "props": {
+                "account_creation_fee": props.account_creation_fee.to_nai(),
+                "maximum_block_size": props.maximum_block_size,
+                "hbd_interest_rate": props.hbd_interest_rate,
+            },
+            "fee": Asset::zero_hive().to_nai(),
+        }),
+    ))
+}
+
+impl Client {
+    /// Fetch a witness by owner, or `None` if the account is not a witness
+    pub fn find_witness(&self, owner: &str) -> Result<Option<Witness>, String> {
+        let resp: WitnessesResponse = self
+            .call(
+                "database_api.find_witnesses",
+                serde_json::json!({ "owners": [owner] }),
+            )
+            .map_err(|e| format!("Error fetching witness {owner}: {e}"))?;
+        Ok(resp.witnesses.into_iter().next())
+    }
+
+    /// Fetch the current witness schedule
+    pub fn get_witness_schedule(&self) -> Result<WitnessSchedule, String> {
+        self.call("database_api.get_witness_schedule", ())
+            .map_err(|e| format!("Error fetching witness schedule: {e}"))
+    }
+
+    /// Map the remaining slots of the current round to their scheduled witnesses
+    pub fn current_round(&self) -> Result<Round, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let schedule = self.get_witness_schedule()?;
+        let witnesses = &schedule.current_shuffled_witnesses;
+        let scheduled = (schedule.num_scheduled_witnesses as usize).min(witnesses.len());
+        if scheduled == 0 {
+            return Err("Witness schedule is empty".to_string());
+        }
+        let remaining = schedule
+            .next_shuffle_block_num
+            .saturating_sub(props.head_block_number);
+        let slots = (1..=remaining)
+            .map(|slot| {
+                let aslot = props.current_aslot + u64::from(slot);
+                ScheduledSlot {
+                    slot,
+                    aslot,
+                    witness: witnesses[(aslot % scheduled as u64) as usize].clone(),
+                }
+            })
+            .collect();
+        Ok(Round {
+            head_block_number: props.head_block_number,
+            next_shuffle_block_num: schedule.next_shuffle_block_num,
+            slots,
+        })
+    }
+
+    /// Fetch the top `limit` witnesses by approval votes
+    pub fn list_witnesses_by_vote(&self, limit: u32) -> Result<Vec<Witness>, String> {
+        let params = ListWitnessesParams {
+            start: (i64::MAX, ""),
+            limit: limit.clamp(1, LIST_WITNESSES_LIMIT),
+            order: "by_vote_name",
+        };
+        let resp: WitnessesResponse = self
+            .call("database_api.list_witnesses", params)
+            .map_err(|e| format!("Error listing witnesses: {e}"))?;
+        Ok(resp.witnesses)
+    }
+
+    /// Snapshot the top `top` witnesses with votes, missed blocks, version and feed age
+    pub fn witness_ranking(&self, top: u32) -> Result<WitnessRanking, String> {
+        let props = self.get_dynamic_global_properties()?;
+        let now = parse_timestamp(&props.time)?;
+        let witnesses = self
+            .list_witnesses_by_vote(top)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, witness)| RankedWitness {
+                rank: index + 1,
+                votes_hp: props.vests_to_hp(Asset::new(witness.votes, AssetSymbol::Vests)),
+                feed_age_secs: parse_timestamp(&witness.last_hbd_exchange_update)
+                    .map_or(i64::MAX, |updated| now - updated),
+                witness,
+            })
+            .collect();
+        Ok(WitnessRanking {
+            head_block_number: props.head_block_number,
+            time: props.time,
+            witnesses,
+        })
+    }
+}
+
+impl WriteClient {
+    /// Publish a price feed for `witness`, returning the transaction id
+    pub fn publish_feed(
+        &self,
+        witness: &str,
+        price: f64,
+        signer: &dyn TransactionSigner,
+    ) -> Result<String, String> {
+        let op = feed_publish_operation(witness, price)?;
+        let mut tx = self.prepare_transaction(vec![op])?;
+        let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+}
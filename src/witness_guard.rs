@@ -0,0 +1,241 @@
+//! Automatic witness failover on missed blocks
+//!
+//! [`WitnessGuard`] follows the chain and watches a witness's missed block
+//! count. Once the witness misses `threshold` blocks it broadcasts a
+//! `witness_update` switching to the next backup signing key, and disables
+//! the witness once the backups are used up. Dry-run mode reports what it
+//! would broadcast without signing anything.
+
+use crate::block::BLOCK_INTERVAL;
+use crate::transaction::TransactionSigner;
+use crate::witness::{witness_update_operation, Witness, DISABLED_SIGNING_KEY};
+use crate::WriteClient;
+use std::fmt;
+use std::thread;
+
+/// What a [`WitnessGuard`] observed or did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardEvent {
+    /// First check; missed blocks are counted from here
+    Started {
+        total_missed: u32,
+        signing_key: String,
+    },
+    /// The witness missed blocks since the last check
+    Missed {
+        total_missed: u32,
+        /// Missed since starting or since the last failover
+        since_failover: u32,
+    },
+    /// The signing key was switched, or would have been in dry-run mode
+    FailedOver {
+        from: String,
+        to: String,
+        /// `None` in dry-run mode
+        trx_id: Option<String>,
+    },
+    /// Broadcasting the switch failed; it is retried on the next check
+    FailoverFailed { to: String, error: String },
+    /// The witness is still missing blocks with no backup key left
+    Exhausted,
+    /// Checking the witness failed; the guard keeps running
+    Error(String),
+}
+
+impl GuardEvent {
+    /// Whether an operator should be alerted
+    pub fn is_alert(&self) -> bool {
+        !matches!(self, Self::Started { .. })
+    }
+}
+
+/// Switches a witness to backup signing keys when it misses blocks
+pub struct WitnessGuard<'a> {
+    witness: String,
+    threshold: u32,
+    backup_keys: Vec<String>,
+    disable_when_exhausted: bool,
+    dry_run: bool,
+    signer: &'a dyn TransactionSigner,
+    /// Missed count at start or at the last failover
+    baseline: Option<u32>,
+    last_missed: u32,
+    exhausted: bool,
+    /// The key a dry run pretends to have switched to
+    dry_run_key: Option<String>,
+}
+
+impl fmt::Debug for WitnessGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WitnessGuard")
+            .field("witness", &self.witness)
+            .field("threshold", &self.threshold)
+            .field("backup_keys", &self.backup_keys)
+            .field("dry_run", &self.dry_run)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> WitnessGuard<'a> {
+    /// Guard `witness`, failing over after `threshold` missed blocks
+    ///
+    /// `signer` must hold the witness's active key. Without backup keys the
+    /// witness is disabled on the first failover.
+    pub fn new(witness: &str, threshold: u32, signer: &'a dyn TransactionSigner) -> Self {
+        Self {
+            witness: witness.to_string(),
+            threshold: threshold.max(1),
+            backup_keys: Vec::new(),
+            disable_when_exhausted: true,
+            dry_run: false,
+            signer,
+            baseline: None,
+            last_missed: 0,
+            exhausted: false,
+            dry_run_key: None,
+        }
+    }
+
+    /// Add a backup signing key, tried in the order added
+    pub fn backup_key(mut self, key: &str) -> Self {
+        self.backup_keys.push(key.to_string());
+        self
+    }
+
+    /// Keep the last key instead of disabling the witness once backups run out
+    pub fn keep_last_key(mut self) -> Self {
+        self.disable_when_exhausted = false;
+        self
+    }
+
+    /// Report failovers without signing or broadcasting
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// The key to switch to from `current`, skipping keys already used
+    fn next_key(&self, current: &str) -> Option<String> {
+        if current == DISABLED_SIGNING_KEY {
+            return None;
+        }
+        let position = self.backup_keys.iter().position(|k| k == current);
+        let remaining = match position {
+            Some(index) => &self.backup_keys[index + 1..],
+            None => &self.backup_keys[..],
+        };
+        remaining
+            .iter()
+            .find(|k| k.as_str() != current)
+            .cloned()
+            .or_else(|| {
+                self.disable_when_exhausted
+                    .then(|| DISABLED_SIGNING_KEY.to_string())
+            })
+    }
+
+    /// Compare the witness against the last check, failing over if needed
+    ///
+    /// A missed count below the last one seen comes from a lagging node and
+    /// is ignored.
+    pub fn process(&mut self, client: &WriteClient, witness: &Witness) -> Vec<GuardEvent> {
+        let total = witness.total_missed;
+        let Some(baseline) = self.baseline else {
+            self.baseline = Some(total);
+            self.last_missed = total;
+            return vec![GuardEvent::Started {
+                total_missed: total,
+                signing_key: witness.signing_key.clone(),
+            }];
+        };
+        let mut events = Vec::new();
+        if total < self.last_missed {
+            return events;
+        }
+        let since_failover = total.saturating_sub(baseline);
+        if total > self.last_missed {
+            events.push(GuardEvent::Missed {
+                total_missed: total,
+                since_failover,
+            });
+        }
+        self.last_missed = total;
+        if since_failover < self.threshold {
+            return events;
+        }
+        let current = match &self.dry_run_key {
+            Some(key) if self.dry_run => key.clone(),
+            _ => witness.signing_key.clone(),
+        };
+        let Some(to) = self.next_key(&current) else {
+            if !self.exhausted {
+                self.exhausted = true;
+                events.push(GuardEvent::Exhausted);
+            }
+            return events;
+        };
+        match self.switch_key(client, witness, &to) {
+            Ok(trx_id) => {
+                self.baseline = Some(total);
+                if self.dry_run {
+                    self.dry_run_key = Some(to.clone());
+                }
+                let from = current;
+                events.push(GuardEvent::FailedOver { from, to, trx_id });
+            }
+            Err(error) => events.push(GuardEvent::FailoverFailed { to, error }),
+        }
+        events
+    }
+
+    fn switch_key(
+        &self,
+        client: &WriteClient,
+        witness: &Witness,
+        key: &str,
+    ) -> Result<Option<String>, String> {
+        let op = witness_update_operation(witness, key)?;
+        if self.dry_run {
+            return Ok(None);
+        }
+        let mut tx = client.prepare_transaction(vec![op])?;
+        let trx_id = client.sign_transaction(&mut tx, &[self.signer])?;
+        client.broadcast_transaction(&tx)?;
+        Ok(Some(trx_id))
+    }
+
+    /// Fetch the witness and process it once
+    pub fn check(&mut self, client: &WriteClient) -> Vec<GuardEvent> {
+        match client.find_witness(&self.witness) {
+            Ok(Some(witness)) => self.process(client, &witness),
+            Ok(None) => vec![GuardEvent::Error(format!(
+                "{} is not a witness",
+                self.witness
+            ))],
+            Err(e) => vec![GuardEvent::Error(e)],
+        }
+    }
+
+    /// Check after every new block until `on_event` returns `false`
+    ///
+    /// Node errors are reported as [`GuardEvent::Error`] and do not stop the guard.
+    pub fn watch<F>(&mut self, client: &WriteClient, mut on_event: F) -> Result<(), String>
+    where
+        F: FnMut(GuardEvent) -> bool,
+    {
+        let head = client.get_dynamic_global_properties()?.head_block_number;
+        let mut blocks = client.stream_blocks(head + 1).header_only();
+        loop {
+            let mut events = self.check(client);
+            if let Some(Err(e)) = blocks.next() {
+                events.push(GuardEvent::Error(e));
+                thread::sleep(BLOCK_INTERVAL);
+            }
+            for event in events {
+                if !on_event(event) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
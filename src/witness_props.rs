@@ -0,0 +1,215 @@
+//! `witness_set_properties` with its hex-encoded property values
+//!
+//! Each property is sent as a `[name, hex]` pair whose hex is the binary
+//! serialization of the value, sorted by name. The operation must include
+//! the witness's current signing key as the `key` property and is signed
+//! with that signing key rather than an account key. [`WitnessProperties`]
+//! takes readable values and does the encoding.
+
+use crate::asset::{Asset, AssetSymbol};
+use crate::chain::ChainConfig;
+use crate::crypto::to_hex;
+use crate::operation::Operation;
+use crate::serializer::Serializer;
+use crate::transaction::TransactionSigner;
+use crate::WriteClient;
+use std::collections::BTreeMap;
+
+/// Smallest block size witnesses may vote for
+pub const MIN_BLOCK_SIZE: u32 = 65_536;
+
+/// Properties to change with a `witness_set_properties` operation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WitnessProperties {
+    owner: String,
+    signing_key: String,
+    account_creation_fee: Option<Asset>,
+    maximum_block_size: Option<u32>,
+    hbd_interest_rate: Option<u16>,
+    account_subsidy_budget: Option<i32>,
+    account_subsidy_decay: Option<u32>,
+    new_signing_key: Option<String>,
+    hbd_exchange_rate: Option<f64>,
+    url: Option<String>,
+}
+
+impl WitnessProperties {
+    /// Change properties of `owner`, whose current signing key is `signing_key`
+    pub fn new(owner: &str, signing_key: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            signing_key: signing_key.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Fee in HIVE to create an account
+    pub fn account_creation_fee(mut self, fee: Asset) -> Self {
+        self.account_creation_fee = Some(fee);
+        self
+    }
+
+    /// Maximum block size in bytes, at least [`MIN_BLOCK_SIZE`]
+    pub fn maximum_block_size(mut self, bytes: u32) -> Self {
+        self.maximum_block_size = Some(bytes);
+        self
+    }
+
+    /// Interest on HBD savings in basis points
+    pub fn hbd_interest_rate(mut self, basis_points: u16) -> Self {
+        self.hbd_interest_rate = Some(basis_points);
+        self
+    }
+
+    /// Interest on HBD savings as a yearly percentage, e.g. `15.0`
+    pub fn hbd_apr(self, percent: f64) -> Self {
+        self.hbd_interest_rate((percent * 100.0).round().clamp(0.0, 10_000.0) as u16)
+    }
+
+    pub fn account_subsidy_budget(mut self, budget: i32) -> Self {
+        self.account_subsidy_budget = Some(budget);
+        self
+    }
+
+    pub fn account_subsidy_decay(mut self, decay: u32) -> Self {
+        self.account_subsidy_decay = Some(decay);
+        self
+    }
+
+    /// Switch block signing to `key`; the operation is still signed with the current key
+    pub fn new_signing_key(mut self, key: &str) -> Self {
+        self.new_signing_key = Some(key.to_string());
+        self
+    }
+
+    /// Price feed quoting one HIVE at `price` HBD
+    pub fn hbd_exchange_rate(mut self, price: f64) -> Self {
+        self.hbd_exchange_rate = Some(price);
+        self
+    }
+
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// Each property name with its serialized value, in the order hived expects
+    pub fn encoded(&self) -> Result<BTreeMap<&'static str, Vec<u8>>, String> {
+        self.encoded_for(&ChainConfig::mainnet())
+    }
+
+    /// [`encoded`](Self::encoded) with `chain`'s key prefix and asset symbols
+    pub fn encoded_for(
+        &self,
+        chain: &ChainConfig,
+    ) -> Result<BTreeMap<&'static str, Vec<u8>>, String> {
+        let mut props = BTreeMap::new();
+        let mut encode = |name: &'static str, f: &dyn Fn(&mut Serializer) -> Result<(), String>| {
+            let mut s = Serializer::for_chain(chain);
+            f(&mut s)?;
+            props.insert(name, s.into_bytes());
+            Ok::<_, String>(())
+        };
+        encode("key", &|s| s.public_key(&self.signing_key))?;
+        if let Some(fee) = self.account_creation_fee {
+            if fee.symbol != AssetSymbol::Hive {
+                return Err(format!("Account creation fee must be in HIVE, not {fee}"));
+            }
+            encode("account_creation_fee", &|s| {
+                s.asset(&fee);
+                Ok(())
+            })?;
+        }
+        if let Some(size) = self.maximum_block_size {
+            if size < MIN_BLOCK_SIZE {
+                return Err(format!(
+                    "Maximum block size {size} is below the minimum of {MIN_BLOCK_SIZE}"
+                ));
+            }
+            encode("maximum_block_size", &|s| {
+                s.u32(size);
+                Ok(())
+            })?;
+        }
+        if let Some(rate) = self.hbd_interest_rate {
+            if rate > 10_000 {
+                return Err(format!("HBD interest rate {rate} exceeds 100%"));
+            }
+            encode("hbd_interest_rate", &|s| {
+                s.u16(rate);
+                Ok(())
+            })?;
+        }
+        if let Some(budget) = self.account_subsidy_budget {
+            encode("account_subsidy_budget", &|s| {
+                s.bytes(&budget.to_le_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(decay) = self.account_subsidy_decay {
+            encode("account_subsidy_decay", &|s| {
+                s.u32(decay);
+                Ok(())
+            })?;
+        }
+        if let Some(key) = &self.new_signing_key {
+            encode("new_signing_key", &|s| s.public_key(key))?;
+        }
+        if let Some(price) = self.hbd_exchange_rate {
+            if !price.is_finite() || price <= 0.0 {
+                return Err(format!("Invalid feed price: {price}"));
+            }
+            let base = Asset::new((price * 1_000.0).round() as i64, AssetSymbol::Hbd);
+            encode("hbd_exchange_rate", &|s| {
+                s.asset(&base);
+                s.asset(&Asset::new(1_000, AssetSymbol::Hive));
+                Ok(())
+            })?;
+        }
+        if let Some(url) = &self.url {
+            encode("url", &|s| {
+                s.string(url);
+                Ok(())
+            })?;
+        }
+        Ok(props)
+    }
+
+    /// The `witness_set_properties` operation with hex-encoded values
+    pub fn operation(&self) -> Result<Operation, String> {
+        self.operation_for(&ChainConfig::mainnet())
+    }
+
+    /// [`operation`](Self::operation) for `chain`
+    pub fn operation_for(&self, chain: &ChainConfig) -> Result<Operation, String> {
+        let props: Vec<(&str, String)> = self
+            .encoded_for(chain)?
+            .into_iter()
+            .map(|(name, bytes)| (name, to_hex(&bytes)))
+            .collect();
+        Ok(Operation::new(
+            "witness_set_properties_operation",
+            serde_json::json!({
+                "owner": self.owner,
+                "props": props,
+                "extensions": [],
+            }),
+        ))
+    }
+}
+
+impl WriteClient {
+    /// Broadcast `props`, returning the transaction id
+    ///
+    /// `signer` must hold the witness's current block signing key.
+    pub fn set_witness_properties(
+        &self,
+        props: &WitnessProperties,
+        signer: &dyn TransactionSigner,
+    ) -> Result<String, String> {
+        let mut tx = self.prepare_transaction(vec![props.operation_for(self.chain())?])?;
+        let trx_id = self.sign_transaction(&mut tx, &[signer])?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+}
@@ -0,0 +1,319 @@
+//! The broadcasting half of the client
+//!
+//! [`Client`] only reads from the chain: it has no signing or broadcast
+//! methods, so using one to change chain state fails to compile, and its
+//! [`Client::call`] refuses broadcast APIs named at runtime. A service handed
+//! a `Client` provably cannot change chain state. Wrapping it with [`WriteClient::new`]
+//! is the only way to broadcast; there is no conversion that upgrades a
+//! `Client` implicitly. Reads remain available through `Deref`.
+
+use crate::attempts::CallError;
+use crate::audit::AuditLog;
+use crate::block::BLOCK_INTERVAL;
+use crate::error::NectarError;
+use crate::limits::{LimitError, TransferGuard};
+use crate::transaction::{Transaction, TransactionSigner, TransactionStatus};
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+
+/// API prefixes that change chain state
+const BROADCAST_METHODS: &[&str] = &["network_broadcast_api.", "condenser_api.broadcast_"];
+
+/// The legacy method taking `[api, method, args]` as its params
+const LEGACY_CALL: &str = "call";
+
+/// Whether `method` broadcasts, and so needs a [`WriteClient`]
+pub fn is_broadcast_method(method: &str) -> bool {
+    BROADCAST_METHODS
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// Whether calling `method` with `params` broadcasts, including through the
+/// legacy `call` method, e.g. `call(["condenser_api", "broadcast_transaction", [tx]])`
+///
+/// A legacy call naming its API by number cannot be checked and counts as a
+/// broadcast.
+pub fn is_broadcast_call(method: &str, params: &Value) -> bool {
+    if method != LEGACY_CALL {
+        return is_broadcast_method(method);
+    }
+    match (params.get(0), params.get(1).and_then(Value::as_str)) {
+        (Some(Value::String(api)), Some(inner)) => is_broadcast_method(&format!("{api}.{inner}")),
+        (Some(Value::String(_)), None) => false,
+        _ => true,
+    }
+}
+
+/// Refuse a broadcast made through a read-only [`Client`]
+///
+/// The one check behind [`Client::call`], batches and the async client.
+pub(crate) fn refuse_broadcast<P: Serialize>(method: &str, params: &P) -> Result<(), CallError> {
+    let broadcasts = if method == LEGACY_CALL {
+        serde_json::to_value(params).map_or(true, |params| is_broadcast_call(method, &params))
+    } else {
+        is_broadcast_method(method)
+    };
+    if broadcasts {
+        return Err(CallError::RequiresWriteClient(method.to_string()));
+    }
+    Ok(())
+}
+
+/// Why a [`WriteClient`] did not sign or broadcast a transaction
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WriteError {
+    /// Refused by the [`TransferLimits`](crate::TransferLimits)
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    /// A broadcast whose transaction could not be decoded, and so not checked
+    #[error("Cannot check the broadcast transaction: {0}")]
+    Decode(String),
+    /// The node validated the transaction and refused it, e.g. for a missing
+    /// authority or an insufficient balance; sending it again cannot succeed
+    #[error("Transaction rejected: {0}")]
+    Rejected(String),
+    #[error("{0}")]
+    Failed(String),
+    /// The broadcast could not be recorded in the audit log; `broadcast` is
+    /// what the broadcast itself returned, so an accepted transaction can be
+    /// told apart from one that was never sent
+    #[error("Broadcast not recorded in the audit log: {error}")]
+    Audit {
+        error: String,
+        broadcast: Result<(), String>,
+    },
+}
+
+/// hived's code for a failed assertion while validating a transaction
+const ASSERT_EXCEPTION: i32 = -32003;
+
+/// The node's reason for refusing a broadcast, if it refused rather than failed
+///
+/// A duplicate is not a refusal: the transaction was already accepted.
+fn rejection(error: &CallError) -> Option<String> {
+    error
+        .attempts()
+        .iter()
+        .find_map(|attempt| match &attempt.error {
+            NectarError::Rpc { code, message, .. }
+                if *code == ASSERT_EXCEPTION && !message.to_lowercase().contains("duplicate") =>
+            {
+                Some(message.clone())
+            }
+            _ => None,
+        })
+}
+
+impl From<CallError> for WriteError {
+    fn from(e: CallError) -> Self {
+        match rejection(&e) {
+            Some(reason) => WriteError::Rejected(reason),
+            None => WriteError::Failed(e.to_string()),
+        }
+    }
+}
+
+impl From<String> for WriteError {
+    fn from(e: String) -> Self {
+        WriteError::Failed(e)
+    }
+}
+
+impl From<WriteError> for String {
+    fn from(e: WriteError) -> Self {
+        e.to_string()
+    }
+}
+
+/// The transaction in the params of a broadcast call
+///
+/// Both the appbase and the legacy `condenser_api` transaction formats are
+/// decoded; anything else is an error, since it could not be checked.
+fn broadcast_trx(method: &str, params: &Value) -> Result<Transaction, WriteError> {
+    let params = match method {
+        LEGACY_CALL => params.get(2),
+        _ => Some(params),
+    };
+    let trx = params
+        .and_then(|params| params.get("trx").or_else(|| params.get(0)))
+        .ok_or_else(|| WriteError::Decode(format!("no transaction in the params of {method}")))?;
+    serde_json::from_value(trx.clone()).map_err(|e| WriteError::Decode(e.to_string()))
+}
+
+/// A [`Client`] that can sign and broadcast transactions
+///
+/// A plain [`Client`] has neither:
+///
+/// ```compile_fail
+/// # use nectarflower_rs::{Client, SecretWif, Transaction};
+/// # fn sign(client: &Client, tx: &mut Transaction, key: &SecretWif) {
+/// client.sign_transaction(tx, &[key]);
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct WriteClient {
+    client: Client,
+    pub(crate) audit: Option<AuditLog>,
+    pub(crate) limits: Option<TransferGuard>,
+}
+
+impl WriteClient {
+    /// Grant `client` write access
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            audit: None,
+            limits: None,
+        }
+    }
+
+    /// Give up write access, keeping the node configuration
+    pub fn into_read_only(self) -> Client {
+        self.client
+    }
+
+    /// Make a JSON-RPC call, including to broadcast APIs
+    ///
+    /// Transactions broadcast this way are checked against the transfer limits;
+    /// a broadcast whose transaction cannot be decoded is refused with
+    /// [`WriteError::Decode`].
+    pub fn call<P: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, WriteError> {
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        if is_broadcast_call(method, &params) {
+            self.admit(&broadcast_trx(method, &params)?)?;
+        }
+        Ok(self.client.call_any(method, params)?)
+    }
+
+    /// Check a transaction about to be broadcast against the transfer limits
+    fn admit(&self, tx: &Transaction) -> Result<(), LimitError> {
+        let Some(limits) = &self.limits else {
+            return Ok(());
+        };
+        // Unsupported operations leave the transaction without a local id
+        let trx_id = tx
+            .id_for(self.chain())
+            .unwrap_or_else(|_| tx.signatures.concat());
+        limits.admit(&trx_id, &tx.operations)
+    }
+
+    /// Sign a transaction for the client's chain with every signer, returning
+    /// the transaction id
+    ///
+    /// The transfer limits are checked first, and the signing keys noted for
+    /// the audit log. The digest is computed from the local serialization;
+    /// see [`Client::verify_transaction_bytes`] to compare it with a node's.
+    pub fn sign_transaction(
+        &self,
+        tx: &mut Transaction,
+        signers: &[&dyn TransactionSigner],
+    ) -> Result<String, WriteError> {
+        self.check_transfers(&tx.operations)?;
+        let digest = tx.digest(self.chain())?;
+        let before = tx.signatures.len();
+        for signer in signers {
+            tx.signatures.push(signer.sign_digest(&digest)?);
+        }
+        let trx_id = tx.id_for(self.chain())?;
+        if let Some(audit) = &self.audit {
+            audit.note_signers(tx, &trx_id, &tx.signatures[before..], signers);
+        }
+        Ok(trx_id)
+    }
+
+    /// Broadcast a signed transaction without waiting for inclusion
+    ///
+    /// The transaction is checked against the transfer limits first, however
+    /// it was signed.
+    pub fn broadcast_transaction(&self, tx: &Transaction) -> Result<(), WriteError> {
+        self.admit(tx)?;
+        let (result, node) = self.client.call_attempts::<_, Value>(
+            "network_broadcast_api.broadcast_transaction",
+            serde_json::json!({ "trx": tx, "max_block_age": -1 }),
+        );
+        let result = result.map(drop).map_err(|e| match rejection(&e) {
+            Some(reason) => WriteError::Rejected(reason),
+            None => WriteError::Failed(format!("Error broadcasting transaction: {e}")),
+        });
+        if let Some(audit) = &self.audit {
+            let broadcast = result.clone().map_err(String::from);
+            if let Err(error) = audit.record(tx, self.chain(), node, &broadcast) {
+                return Err(WriteError::Audit { error, broadcast });
+            }
+        }
+        result
+    }
+
+    /// Relay a signed transaction serialized as hex, e.g. by cli_wallet
+    ///
+    /// Returns the transaction id once the transaction is broadcast.
+    pub fn broadcast_hex(&self, hex: &str) -> Result<String, WriteError> {
+        let tx = Transaction::from_hex(hex)?;
+        if tx.signatures.is_empty() {
+            return Err(WriteError::Failed("Transaction is not signed".to_string()));
+        }
+        let trx_id = tx.id_for(self.chain())?;
+        self.broadcast_transaction(&tx)?;
+        Ok(trx_id)
+    }
+
+    /// Broadcast a signed transaction and wait until it is included in a block or expires
+    ///
+    /// Re-broadcasting the same signed transaction is safe: the chain rejects
+    /// duplicates, so a failed attempt is retried until the status settles.
+    /// A transaction refused by the transfer limits, or by the node before any
+    /// attempt could have gone through, is not retried.
+    pub fn broadcast_reliably(
+        &self,
+        tx: &Transaction,
+        trx_id: &str,
+        max_attempts: u32,
+    ) -> Result<TransactionStatus, WriteError> {
+        let mut last_err = None;
+        let mut maybe_sent = false;
+        for _ in 0..max_attempts.max(1) {
+            match self.broadcast_transaction(tx) {
+                Ok(()) => maybe_sent = true,
+                Err(WriteError::Failed(e)) => {
+                    maybe_sent = true;
+                    last_err = Some(e);
+                }
+                // Once an earlier attempt may have gone through, a refusal can
+                // come from the chain seeing that attempt's effects
+                Err(WriteError::Rejected(e)) if maybe_sent => last_err = Some(e),
+                Err(e) => return Err(e),
+            }
+            thread::sleep(BLOCK_INTERVAL);
+            match self.find_transaction(trx_id, Some(&tx.expiration)) {
+                Ok(status) if status.is_included() || status.is_expired() => return Ok(status),
+                Ok(_) => {}
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| format!("Transaction {trx_id} not included"))
+            .into())
+    }
+}
+
+impl Deref for WriteClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl DerefMut for WriteClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        &mut self.client
+    }
+}
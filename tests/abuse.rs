@@ -0,0 +1,82 @@
+//! Abuse signals computed from votes and an author's history
+
+use nectarflower_rs::history::HistoryEntry;
+use nectarflower_rs::votes::Vote;
+use nectarflower_rs::AbuseAnalyzer;
+use serde_json::{json, Value};
+
+fn vote(voter: &str, rshares: i64) -> Vote {
+    serde_json::from_value(json!({
+        "voter": voter,
+        "author": "alice",
+        "permlink": "post",
+        "weight": 0,
+        "rshares": rshares,
+        "vote_percent": 10000,
+        "last_update": "2024-01-01T00:00:00",
+    }))
+    .unwrap()
+}
+
+fn entry(op_type: &str, value: Value) -> HistoryEntry {
+    serde_json::from_value(json!({
+        "trx_id": "",
+        "block": 1,
+        "trx_in_block": 0,
+        "op_in_trx": 0,
+        "virtual_op": false,
+        "timestamp": "2024-01-01T00:00:00",
+        "op": { "type": op_type, "value": value },
+    }))
+    .unwrap()
+}
+
+fn upvote(author: &str) -> HistoryEntry {
+    entry(
+        "vote_operation",
+        json!({ "voter": "alice", "author": author, "permlink": "p", "weight": 10000 }),
+    )
+}
+
+fn options(beneficiary: &str) -> HistoryEntry {
+    entry(
+        "comment_options_operation",
+        json!({
+            "author": "alice",
+            "permlink": "p",
+            "extensions": [{
+                "type": "comment_payout_beneficiaries",
+                "value": { "beneficiaries": [{ "account": beneficiary, "weight": 1000 }] },
+            }],
+        }),
+    )
+}
+
+#[test]
+fn signals_cover_self_votes_circles_beneficiaries_and_blacklists() {
+    let votes = [vote("alice", 500), vote("bob", 300), vote("carol", 200)];
+    let history = [
+        upvote("alice"),
+        upvote("bob"),
+        upvote("bob"),
+        upvote("alice"),
+        options("farm"),
+        options("farm"),
+        options("dev"),
+    ];
+    let analyzer = AbuseAnalyzer::new().blacklist("spaminator", ["alice"]);
+    let signals = analyzer.analyze("alice", "post", &votes, &history);
+
+    assert_eq!(signals.self_vote_share, 0.5);
+    assert_eq!(signals.account_self_vote_share, 0.5);
+    assert_eq!(signals.vote_circle_share, 0.3);
+    assert_eq!(signals.circle_voters, ["bob"]);
+    assert_eq!(signals.recurring_beneficiaries.len(), 1);
+    assert_eq!(signals.recurring_beneficiaries[0].account, "farm");
+    assert_eq!(signals.recurring_beneficiaries[0].posts, 2);
+    assert_eq!(signals.blacklisted_on, ["spaminator"]);
+    assert!(signals.score() > 50.0 && signals.score() <= 100.0);
+
+    let clean = AbuseAnalyzer::new().analyze("carol", "post", &[vote("bob", 100)], &[]);
+    assert_eq!(clean.score(), 0.0);
+}
@@ -0,0 +1,146 @@
+//! Account changes between two block heights, from mocked history
+
+use nectarflower_rs::account_diff::{AuthorityChange, DelegationChange};
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn entry(index: usize, block: u32, op: &str, value: Value) -> Value {
+    json!([index, {
+        "trx_id": format!("{block:040x}"),
+        "block": block,
+        "trx_in_block": 0,
+        "op_in_trx": 0,
+        "virtual_op": false,
+        "timestamp": "2024-01-01T00:00:00",
+        "op": { "type": format!("{op}_operation"), "value": value },
+    }])
+}
+
+fn transfer(from: &str, to: &str, amount: &str) -> Value {
+    json!({ "from": from, "to": to, "amount": amount, "memo": "" })
+}
+
+fn delegation(vests: &str) -> Value {
+    json!({ "delegator": "alice", "delegatee": "carol", "vesting_shares": vests })
+}
+
+fn client() -> Client {
+    let ops = [
+        (100, "transfer", transfer("bob", "alice", "50.000 HIVE")),
+        (102, "transfer", transfer("bob", "alice", "7.000 HIVE")),
+        (103, "transfer", transfer("bob", "alice", "10.000 HIVE")),
+        (
+            104,
+            "delegate_vesting_shares",
+            delegation("1000.000000 VESTS"),
+        ),
+        (
+            105,
+            "account_update2",
+            json!({
+                "account": "alice",
+                "posting": { "weight_threshold": 1, "account_auths": [], "key_auths": [] },
+                "memo_key": "",
+                "json_metadata": "",
+                "posting_json_metadata": "",
+                "extensions": [],
+            }),
+        ),
+        (
+            105,
+            "change_recovery_account",
+            json!({ "account_to_recover": "bob", "new_recovery_account": "alice", "extensions": [] }),
+        ),
+        (
+            106,
+            "delegate_vesting_shares",
+            delegation("500.000000 VESTS"),
+        ),
+        (107, "transfer", transfer("alice", "dave", "2.000 HIVE")),
+        // Past the head block the balances were read at
+        (1001, "transfer", transfer("bob", "alice", "1.000 HIVE")),
+    ];
+    let history: Vec<Value> = ops
+        .into_iter()
+        .enumerate()
+        .map(|(index, (block, op, value))| entry(index, block, op, value))
+        .collect();
+    let mock = MockTransport::new()
+        .respond(
+            "account_history_api.get_account_history",
+            json!({ "history": history }),
+        )
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 1000,
+                "head_block_id": "000003e8aabbccdd000000000000000000000000",
+                "time": "2024-01-03T12:00:00",
+                "current_witness": "bob",
+                "current_aslot": 1000,
+                "last_irreversible_block_num": 980,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{
+                "name": "alice",
+                "json_metadata": "",
+                "balance": "100.000 HIVE",
+                "hbd_balance": "0.000 HBD",
+                "vesting_shares": "4000.000000 VESTS",
+            }] }),
+        );
+    Client::builder().transport(mock).build()
+}
+
+fn hive(amount: i64) -> Asset {
+    Asset::new(amount, AssetSymbol::Hive)
+}
+
+#[test]
+fn balances_are_reconstructed_at_both_heights() {
+    let diff = client().account_diff("alice", 102, 106).unwrap();
+    assert_eq!((diff.from_block, diff.to_block), (102, 106));
+    // Later history is undone from the current balance
+    assert_eq!(diff.after.hive, hive(102_000));
+    assert_eq!(diff.before.hive, hive(92_000));
+    assert_eq!(diff.balance_change().hive, hive(10_000));
+    assert_eq!(diff.hp_after.to_string(), "2.000 HIVE");
+    assert_eq!(diff.operations, 5);
+}
+
+#[test]
+fn delegations_and_authority_changes_keep_their_end_state() {
+    let diff = client().account_diff("alice", 102, 106).unwrap();
+    assert_eq!(
+        diff.delegations,
+        [DelegationChange {
+            delegator: "alice".to_string(),
+            delegatee: "carol".to_string(),
+            vesting_shares: Asset::new(500_000_000, AssetSymbol::Vests),
+            block: 106,
+        }]
+    );
+    // Only alice's own update counts, without the unchanged memo key
+    assert_eq!(
+        diff.authority_changes,
+        [AuthorityChange {
+            block: 105,
+            timestamp: "2024-01-01T00:00:00".to_string(),
+            trx_id: format!("{:040x}", 105),
+            operation: "account_update2".to_string(),
+            fields: vec!["posting"],
+        }]
+    );
+}
+
+#[test]
+fn ranges_must_be_ordered() {
+    let err = client().account_diff("alice", 106, 106).unwrap_err();
+    assert_eq!(err, "from_block 106 must be below to_block 106");
+}
@@ -0,0 +1,120 @@
+//! Scanning every account in name order, resumably
+
+use nectarflower_rs::cursor::Cursor;
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::transport::Transport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const NAMES: [&str; 7] = ["alice", "bob", "carol", "dave", "erin", "frank", "grace"];
+
+/// Lists accounts from `NAMES` like a node, starting at `start` inclusive
+#[derive(Clone, Default)]
+struct Node {
+    starts: Arc<Mutex<Vec<(String, u64)>>>,
+}
+
+impl Transport for Node {
+    fn send(&self, _url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        let params = &request["params"];
+        assert_eq!(params["order"], "by_name");
+        let start = params["start"].as_str().unwrap();
+        let limit = params["limit"].as_u64().unwrap();
+        self.starts.lock().unwrap().push((start.to_string(), limit));
+        if start == "boom" {
+            let error = json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": "down" } });
+            return Ok(error.to_string().into_bytes());
+        }
+        let accounts: Vec<Value> = NAMES
+            .iter()
+            .filter(|name| **name >= start)
+            .take(limit as usize)
+            .map(|name| json!({ "name": name, "json_metadata": "" }))
+            .collect();
+        let response =
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "accounts": accounts } });
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+fn names(scan: impl Iterator<Item = Result<nectarflower_rs::Account, String>>) -> Vec<String> {
+    scan.map(|account| account.unwrap().name).collect()
+}
+
+#[test]
+fn pages_overlap_on_the_last_account_only() {
+    let node = Node::default();
+    let client = Client::builder().transport(node.clone()).build();
+
+    assert_eq!(names(client.scan_accounts().page_size(3)), NAMES);
+    let starts = node.starts.lock().unwrap().clone();
+    assert_eq!(
+        starts,
+        [
+            (String::new(), 3),
+            ("carol".to_string(), 3),
+            ("erin".to_string(), 3),
+            ("grace".to_string(), 3),
+        ]
+    );
+}
+
+#[test]
+fn scans_resume_after_a_checkpoint() {
+    let client = Client::builder().transport(Node::default()).build();
+    let mut scan = client.scan_accounts().page_size(2);
+    assert_eq!(scan.checkpoint(), None);
+    scan.next().unwrap().unwrap();
+    scan.next().unwrap().unwrap();
+    scan.next().unwrap().unwrap();
+    assert_eq!(scan.checkpoint(), Some("carol"));
+    let cursor = scan.cursor();
+    assert_eq!(
+        cursor,
+        Cursor::Accounts {
+            after: Some("carol".to_string())
+        }
+    );
+
+    let rest = names(client.scan_accounts().page_size(2).resume_after("carol"));
+    assert_eq!(rest, NAMES[3..]);
+    let rest = names(client.scan_accounts().resume(&cursor).unwrap());
+    assert_eq!(rest, NAMES[3..]);
+    let all = names(
+        client
+            .scan_accounts()
+            .resume(&Cursor::Accounts { after: None })
+            .unwrap(),
+    );
+    assert_eq!(all, NAMES);
+    assert!(client
+        .scan_accounts()
+        .resume(&Cursor::Blocks { next: 1 })
+        .is_err());
+}
+
+#[test]
+fn errors_end_the_scan_and_requests_are_spaced() {
+    let client = Client::builder().transport(Node::default()).build();
+    let mut scan = client.scan_accounts().resume_after("boom");
+    let err = scan.next().unwrap().unwrap_err();
+    assert!(
+        err.starts_with("Error listing accounts from 'boom':"),
+        "{err}"
+    );
+    assert!(scan.next().is_none());
+
+    let started = Instant::now();
+    let scanned = names(
+        client
+            .scan_accounts()
+            .page_size(3)
+            .rate_limit(Duration::from_millis(50)),
+    );
+    assert_eq!(scanned.len(), NAMES.len());
+    // Four requests, three waits
+    assert!(started.elapsed() >= Duration::from_millis(150));
+}
@@ -0,0 +1,60 @@
+use nectarflower_rs::annotations::{operation_key, transaction_key};
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::{AnnotationStore, HistoryEntry};
+use serde_json::json;
+
+fn transfer(trx_id: &str, op_in_trx: u32) -> HistoryEntry {
+    HistoryEntry {
+        index: 0,
+        trx_id: trx_id.to_string(),
+        block: 100,
+        trx_in_block: 3,
+        op_in_trx,
+        virtual_op: false,
+        timestamp: "2024-01-01T00:00:00".to_string(),
+        op: Operation::new("transfer_operation", json!({"from": "a", "to": "b"})),
+    }
+}
+
+#[test]
+fn entries_combine_transaction_and_operation_annotations() {
+    let mut store = AnnotationStore::in_memory();
+    let first = transfer("abc", 0);
+    let second = transfer("abc", 1);
+    store.label(&transaction_key("abc"), "payroll");
+    store.label(&operation_key(&second), "refund");
+    store.set_note(&operation_key(&second), Some("returned overpayment"));
+
+    let annotated: Vec<_> = store
+        .annotate(vec![Ok(first), Ok(second)])
+        .collect::<Result<_, String>>()
+        .unwrap();
+    assert_eq!(annotated[0].annotation.labels.len(), 1);
+    assert_eq!(annotated[1].annotation.labels.len(), 2);
+    let line = serde_json::to_value(&annotated[1]).unwrap();
+    assert_eq!(line["labels"], json!(["payroll", "refund"]));
+    assert_eq!(line["note"], "returned overpayment");
+    assert_eq!(line["trx_id"], "abc");
+}
+
+#[test]
+fn saves_and_reopens_from_disk() {
+    let path = std::env::temp_dir().join(format!("nf-annotations-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut store = AnnotationStore::open(&path).unwrap();
+    assert!(store.is_empty());
+    store.label("abc", "payroll");
+    store.label("def", "payroll");
+    store.label("def", "fees");
+    assert!(store.unlabel("def", "fees"));
+    store.save().unwrap();
+
+    let reopened = AnnotationStore::open(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(
+        reopened.with_label("payroll").collect::<Vec<_>>(),
+        ["abc", "def"]
+    );
+    assert!(reopened.with_label("fees").next().is_none());
+}
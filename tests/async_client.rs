@@ -0,0 +1,175 @@
+#![cfg(feature = "async")]
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{AsyncClient, CallError, Client, NectarError};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[tokio::test]
+async fn async_calls_share_the_blocking_call_semantics() {
+    let mut client = AsyncClient::from(Client::new());
+    let refused = client
+        .call_traced::<_, Value>("condenser_api.broadcast_transaction", ())
+        .await;
+    assert!(matches!(refused, Err(CallError::RequiresWriteClient(_))));
+
+    client
+        .set_nodes(vec!["http://127.0.0.1:9".to_string()], HashMap::new())
+        .unwrap();
+    let call = client.call_traced::<_, Value>("condenser_api.get_config", ());
+    assert_send(&call);
+    match call.await {
+        Err(CallError::AllNodesFailed { attempts, .. }) => {
+            assert_eq!(attempts.len(), 1);
+            assert_eq!(attempts[0].node, "http://127.0.0.1:9");
+        }
+        other => panic!("expected every node to fail, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn async_calls_go_through_the_client_transport() {
+    let mock = MockTransport::new().respond(
+        "database_api.get_config",
+        json!({ "HIVE_BLOCK_INTERVAL": 3 }),
+    );
+    let client = AsyncClient::from(
+        Client::builder()
+            .nodes(["https://up.example"])
+            .transport(mock.clone())
+            .build(),
+    );
+    let config: Value = client
+        .call("database_api.get_config", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(config["HIVE_BLOCK_INTERVAL"], 3);
+    assert_eq!(mock.requests()[0].0, "https://up.example");
+}
+
+#[tokio::test]
+async fn async_calls_fail_over_like_blocking_ones() {
+    let mock = MockTransport::new()
+        .fail_node("https://down.example")
+        .respond(
+            "database_api.get_config",
+            json!({ "HIVE_BLOCK_INTERVAL": 3 }),
+        );
+    let blocking = Client::builder()
+        .nodes(["https://down.example", "https://up.example"])
+        .transport(mock.clone())
+        .build();
+    let expected: Value = blocking.call("database_api.get_config", json!({})).unwrap();
+    let client = AsyncClient::from(blocking);
+    let config: Value = client
+        .call("database_api.get_config", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(config, expected);
+    let nodes: Vec<String> = mock.requests().into_iter().map(|(node, _)| node).collect();
+    assert_eq!(
+        nodes,
+        [
+            "https://down.example",
+            "https://up.example",
+            "https://down.example",
+            "https://up.example"
+        ]
+    );
+
+    let err = client
+        .call_traced::<_, Value>("database_api.get_version", json!({}))
+        .await
+        .unwrap_err();
+    let failed: Vec<&str> = err.attempts().iter().map(|a| a.node.as_str()).collect();
+    assert_eq!(failed, ["https://down.example", "https://up.example"]);
+    assert!(err.attempts()[1].error.is_rpc());
+}
+
+/// Serve one JSON-RPC response of `size` bytes, chunked or with a length
+fn serve_large(size: usize, chunked: bool) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line.trim().is_empty() {
+                break;
+            }
+        }
+        reader.read_exact(&mut vec![0; length]).unwrap();
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":"{}"}}"#,
+            "x".repeat(size)
+        );
+        let _ = if chunked {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+            )
+            .and_then(|()| {
+                for chunk in body.as_bytes().chunks(1024) {
+                    write!(stream, "{:x}\r\n", chunk.len())?;
+                    stream.write_all(chunk)?;
+                    write!(stream, "\r\n")?;
+                }
+                write!(stream, "0\r\n\r\n")
+            })
+        } else {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        };
+    });
+    url
+}
+
+#[tokio::test]
+async fn async_responses_respect_the_size_limit() {
+    for chunked in [false, true] {
+        let url = serve_large(10_000, chunked);
+        let client = AsyncClient::from(
+            Client::builder()
+                .nodes([url.as_str()])
+                .build()
+                .max_response_size(4_096),
+        );
+        let err = client
+            .call_traced::<_, Value>("condenser_api.get_config", ())
+            .await
+            .unwrap_err();
+        let length = if chunked { None } else { Some(10_036) };
+        assert_eq!(
+            err.attempts()[0].error,
+            NectarError::ResponseTooLarge {
+                length,
+                limit: 4_096
+            },
+            "chunked: {chunked}"
+        );
+
+        let url = serve_large(1_000, chunked);
+        let client = AsyncClient::from(
+            Client::builder()
+                .nodes([url.as_str()])
+                .build()
+                .max_response_size(4_096),
+        );
+        let result: String = client.call("condenser_api.get_config", ()).await.unwrap();
+        assert_eq!(result.len(), 1_000);
+    }
+}
@@ -0,0 +1,28 @@
+use nectarflower_rs::{CallError, Client};
+use serde_json::Value;
+
+#[test]
+fn failed_calls_report_every_node_attempt() {
+    // Nothing listens on these ports, so both attempts fail fast
+    let mut client = Client::new();
+    client.nodes = vec![
+        "http://127.0.0.1:1".to_string(),
+        "http://127.0.0.1:2".to_string(),
+    ];
+    let err = client
+        .call_traced::<_, Value>("database_api.get_version", ())
+        .unwrap_err();
+    let nodes: Vec<_> = err.attempts().iter().map(|a| a.node.as_str()).collect();
+    assert_eq!(nodes, ["http://127.0.0.1:1", "http://127.0.0.1:2"]);
+    assert!(err.is_systemic());
+
+    let message: String = err.into();
+    assert!(message.starts_with("database_api.get_version failed on all 2 nodes"));
+    assert!(message.contains("http://127.0.0.1:2: Request error"));
+
+    client.nodes.clear();
+    let err = client
+        .call_traced::<_, Value>("network_broadcast_api.broadcast_transaction", ())
+        .unwrap_err();
+    assert!(matches!(err, CallError::RequiresWriteClient(_)));
+}
@@ -0,0 +1,114 @@
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::transaction::Transaction;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{AuditLog, AuditRecord, Client, WriteClient, WriteError};
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn failed_broadcasts_are_logged_without_the_memo() {
+    let buffer = SharedBuffer::default();
+    let mut client = Client::new();
+    client.nodes.clear();
+    let writer = WriteClient::new(client).audit_log(AuditLog::from_writer(buffer.clone()));
+    let tx = Transaction {
+        ref_block_num: 1,
+        ref_block_prefix: 2,
+        expiration: "2024-01-01T00:01:00".to_string(),
+        operations: vec![Operation::new(
+            "transfer_operation",
+            json!({"from": "alice", "to": "bob", "amount": "1.000 HIVE", "memo": "invoice 42"}),
+        )],
+        extensions: Vec::new(),
+        signatures: vec!["20".repeat(65)],
+    };
+
+    assert!(writer.broadcast_transaction(&tx).is_err());
+
+    let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(!log.contains("invoice 42"));
+    let record: AuditRecord = serde_json::from_str(log.trim()).unwrap();
+    assert!(!record.ok);
+    assert_eq!(record.trx_id, Some(tx.id().unwrap()));
+    assert_eq!(record.operations, ["@alice transferred 1.000 HIVE to @bob"]);
+    assert_eq!(record.signers, ["unknown"]);
+    assert_eq!(record.node, None);
+    assert!(record.error.unwrap().contains("No nodes available"));
+}
+
+struct BrokenWriter;
+
+impl Write for BrokenWriter {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn audit_failures_are_returned_with_the_broadcast_result() {
+    let mock = MockTransport::new()
+        .respond("network_broadcast_api.broadcast_transaction", json!({}))
+        .respond(
+            "transaction_status_api.find_transaction",
+            json!({ "status": "within_irreversible_block" }),
+        );
+    let writer = WriteClient::new(Client::builder().transport(mock.clone()).build())
+        .audit_log(AuditLog::from_writer(BrokenWriter));
+    let tx = Transaction {
+        ref_block_num: 1,
+        ref_block_prefix: 2,
+        expiration: "2024-01-01T00:01:00".to_string(),
+        operations: vec![Operation::new(
+            "vote_operation",
+            json!({"voter": "alice", "author": "bob", "permlink": "post", "weight": 10000}),
+        )],
+        extensions: Vec::new(),
+        signatures: vec!["20".repeat(65)],
+    };
+
+    let err = writer.broadcast_transaction(&tx).unwrap_err();
+    assert_eq!(
+        err,
+        WriteError::Audit {
+            error: "Error writing audit log: disk full".to_string(),
+            broadcast: Ok(()),
+        }
+    );
+    assert_eq!(mock.requests().len(), 1);
+
+    // An accepted but unrecorded transaction is not sent again
+    let err = writer
+        .broadcast_reliably(&tx, &tx.id().unwrap(), 3)
+        .unwrap_err();
+    assert!(matches!(err, WriteError::Audit { .. }), "{err}");
+    assert_eq!(mock.requests().len(), 2);
+
+    // A failed broadcast is reported along with the audit failure
+    let mut client = Client::new();
+    client.nodes.clear();
+    let writer = WriteClient::new(client).audit_log(AuditLog::from_writer(BrokenWriter));
+    match writer.broadcast_transaction(&tx).unwrap_err() {
+        WriteError::Audit {
+            broadcast: Err(e), ..
+        } => assert!(e.contains("No nodes available"), "{e}"),
+        other => panic!("{other:?}"),
+    }
+}
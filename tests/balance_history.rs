@@ -0,0 +1,162 @@
+//! Replaying mocked account history into daily balances
+
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Balances, Client};
+use serde_json::{json, Value};
+
+fn entry(index: u64, timestamp: &str, op: &str, value: Value) -> Value {
+    json!([index, {
+        "trx_id": "00",
+        "block": 100 + index,
+        "trx_in_block": 0,
+        "op_in_trx": 0,
+        "virtual_op": false,
+        "timestamp": timestamp,
+        "op": { "type": format!("{op}_operation"), "value": value },
+    }])
+}
+
+fn client(history: Vec<Value>, hive: &str, hbd: &str) -> Client {
+    let mock = MockTransport::new()
+        .respond(
+            "account_history_api.get_account_history",
+            json!({ "history": history }),
+        )
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 1000,
+                "head_block_id": "000003e8aabbccdd000000000000000000000000",
+                "time": "2024-01-03T12:00:00",
+                "current_witness": "bob",
+                "current_aslot": 1000,
+                "last_irreversible_block_num": 980,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{
+                "name": "alice",
+                "json_metadata": "",
+                "balance": hive,
+                "hbd_balance": hbd,
+                "vesting_shares": "0.000000 VESTS",
+            }] }),
+        );
+    Client::builder().transport(mock).build()
+}
+
+fn balances(hive: i64, hbd: i64) -> Balances {
+    Balances {
+        hive: Asset::new(hive, AssetSymbol::Hive),
+        hbd: Asset::new(hbd, AssetSymbol::Hbd),
+        vests: Asset::zero_vests(),
+    }
+}
+
+fn collateralized_conversion() -> Vec<Value> {
+    vec![
+        entry(
+            0,
+            "2024-01-01T08:00:00",
+            "transfer",
+            json!({ "from": "bob", "to": "alice", "amount": "100.000 HIVE", "memo": "" }),
+        ),
+        entry(
+            1,
+            "2024-01-01T09:00:00",
+            "collateralized_convert",
+            json!({ "owner": "alice", "requestid": 1, "amount": "10.000 HIVE" }),
+        ),
+        entry(
+            2,
+            "2024-01-01T09:00:00",
+            "collateralized_convert_immediate_conversion",
+            json!({ "owner": "alice", "requestid": 1, "hbd_out": "4.000 HBD" }),
+        ),
+        entry(
+            3,
+            "2024-01-02T09:00:00",
+            "fill_collateralized_convert_request",
+            json!({
+                "owner": "alice",
+                "requestid": 1,
+                "amount_in": "9.000 HIVE",
+                "amount_out": "4.000 HBD",
+                "excess_collateral": "1.000 HIVE",
+            }),
+        ),
+    ]
+}
+
+#[test]
+fn collateralized_conversions_pay_hbd_once() {
+    let client = client(collateralized_conversion(), "91.000 HIVE", "4.000 HBD");
+    let history = client.balance_history("alice").unwrap();
+
+    let days: Vec<_> = history
+        .days
+        .iter()
+        .map(|day| (day.date.as_str(), day.balances))
+        .collect();
+    assert_eq!(
+        days,
+        [
+            ("2024-01-01", balances(90_000, 4_000)),
+            ("2024-01-02", balances(91_000, 4_000)),
+            ("2024-01-03", balances(91_000, 4_000)),
+        ]
+    );
+    assert!(history.is_consistent(), "{:?}", history.discrepancy());
+}
+
+#[test]
+fn hbd_conversions_and_transfers_replay() {
+    let history = vec![
+        entry(
+            0,
+            "2024-01-01T08:00:00",
+            "transfer",
+            json!({ "from": "bob", "to": "alice", "amount": "20.000 HBD", "memo": "" }),
+        ),
+        entry(
+            1,
+            "2024-01-01T09:00:00",
+            "convert",
+            json!({ "owner": "alice", "requestid": 2, "amount": "5.000 HBD" }),
+        ),
+        entry(
+            2,
+            "2024-01-03T09:00:00",
+            "fill_convert_request",
+            json!({
+                "owner": "alice",
+                "requestid": 2,
+                "amount_in": "5.000 HBD",
+                "amount_out": "12.500 HIVE",
+            }),
+        ),
+        entry(
+            3,
+            "2024-01-03T10:00:00",
+            "transfer",
+            json!({ "from": "alice", "to": "carol", "amount": "2.500 HIVE", "memo": "" }),
+        ),
+    ];
+    let client = client(history, "10.000 HIVE", "15.000 HBD");
+    let history = client.balance_history("alice").unwrap();
+    assert_eq!(history.days[1].balances, balances(0, 15_000));
+    assert_eq!(history.reconstructed, balances(10_000, 15_000));
+    assert!(history.is_consistent());
+}
+
+#[test]
+fn unexplained_changes_show_as_a_discrepancy() {
+    let client = client(collateralized_conversion(), "91.000 HIVE", "7.000 HBD");
+    let history = client.balance_history("alice").unwrap();
+    assert!(!history.is_consistent());
+    assert_eq!(history.discrepancy(), balances(0, 3_000));
+}
@@ -0,0 +1,61 @@
+use nectarflower_rs::batch::MISSING_RESPONSE_CODE;
+use nectarflower_rs::{Client, NectarError};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// A node answering one batch out of order, with an error and a missing response
+fn batch_node() -> (String, thread::JoinHandle<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let node = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut request = vec![0; length];
+        reader.read_exact(&mut request).unwrap();
+        let body = r#"[{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params"},"id":1},{"jsonrpc":"2.0","result":{"n":1},"id":0}]"#;
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        serde_json::from_slice(&request).unwrap()
+    });
+    (node, handle)
+}
+
+#[test]
+fn batch_results_are_matched_to_calls_by_id() {
+    let (node, server) = batch_node();
+    let client = Client::builder().nodes([node]).build();
+    let results = client
+        .call_batch([
+            ("database_api.get_config", json!({})),
+            ("database_api.find_accounts", json!({ "accounts": 1 })),
+            ("database_api.get_version", json!({})),
+        ])
+        .unwrap();
+    let request = server.join().unwrap();
+    assert_eq!(request.as_array().map(Vec::len), Some(3));
+    assert_eq!(request[2]["method"], "database_api.get_version");
+    assert_eq!(results[0], Ok(json!({ "n": 1 })));
+    assert_eq!(results[1].as_ref().unwrap_err().code, -32602);
+    assert_eq!(results[2].as_ref().unwrap_err().code, MISSING_RESPONSE_CODE);
+
+    let refused = client
+        .call_batch_as::<Value, _, _, _>([("condenser_api.broadcast_transaction", json!([]))]);
+    assert!(matches!(refused, Err(NectarError::RequiresWriteClient(_))));
+}
@@ -0,0 +1,126 @@
+//! Block sources: live streams and replayed dump files
+
+use nectarflower_rs::block::StreamedBlock;
+use nectarflower_rs::block_source::{
+    BlockFileFormat, BlockFileWriter, BlockSource, FileBlockSource,
+};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+use std::path::PathBuf;
+
+fn header() -> serde_json::Value {
+    json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": "bob",
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+    })
+}
+
+fn streamed(block_num: u32) -> StreamedBlock {
+    serde_json::from_value(json!({ "block_num": block_num, "header": header(), "block": null }))
+        .unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("nf-block-source-{name}-{}", std::process::id()))
+}
+
+fn replay(source: &mut FileBlockSource) -> Vec<u32> {
+    let mut nums = Vec::new();
+    while let Some(block) = source.next_block() {
+        let block = block.unwrap();
+        nums.push(block.block_num);
+        // Blocks count as irreversible once read
+        assert_eq!(
+            source.last_irreversible_block_num().unwrap(),
+            block.block_num
+        );
+    }
+    nums
+}
+
+#[test]
+fn dumped_blocks_replay_in_both_formats() {
+    for (name, format) in [
+        ("jsonl", BlockFileFormat::JsonLines),
+        ("msgpack", BlockFileFormat::Msgpack),
+    ] {
+        let path = temp_path(name);
+        let mut writer = BlockFileWriter::create(&path, format).unwrap();
+        for block_num in [5, 6, 7] {
+            writer.write(&streamed(block_num)).unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        // The format is detected from the file
+        let mut source = FileBlockSource::open(&path).unwrap();
+        assert!(source.streams_irreversible());
+        assert_eq!(source.last_irreversible_block_num().unwrap(), 0);
+        assert_eq!(replay(&mut source), [5, 6, 7], "{name}");
+        assert!(source.irreversible_block(5).unwrap().is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn json_lines_skip_blank_lines_and_report_bad_ones() {
+    let path = temp_path("lines");
+    let line = |n| serde_json::to_string(&streamed(n)).unwrap();
+    std::fs::write(&path, format!("{}\n\n{{oops\n{}\n", line(1), line(2))).unwrap();
+
+    let mut source = FileBlockSource::open(&path).unwrap();
+    assert_eq!(source.next_block().unwrap().unwrap().block_num, 1);
+    let err = source.next_block().unwrap().unwrap_err();
+    assert!(err.starts_with("Error decoding block:"), "{err}");
+    assert_eq!(source.last_irreversible_block_num().unwrap(), 1);
+    assert_eq!(source.next_block().unwrap().unwrap().block_num, 2);
+    assert!(source.next_block().is_none());
+    std::fs::remove_file(path).unwrap();
+
+    let err = FileBlockSource::open(temp_path("missing")).unwrap_err();
+    assert!(err.starts_with("Error opening"), "{err}");
+}
+
+#[test]
+fn live_streams_ask_the_node() {
+    let mut block = header();
+    block.as_object_mut().unwrap().extend([
+        ("witness_signature".to_string(), json!("1f00")),
+        ("transactions".to_string(), json!([])),
+        (
+            "block_id".to_string(),
+            json!("0000000a00000000000000000000000000000000"),
+        ),
+        ("signing_key".to_string(), json!("")),
+        ("transaction_ids".to_string(), json!([])),
+    ]);
+    let mock = MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-01T00:00:30",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 8,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond("block_api.get_block", json!({ "block": block }));
+    let client = Client::builder().transport(mock).build();
+
+    let mut source = client.stream_blocks(10);
+    assert!(!source.streams_irreversible());
+    assert_eq!(source.next_block().unwrap().unwrap().block_num, 10);
+    assert_eq!(source.last_irreversible_block_num().unwrap(), 8);
+    let confirmed = source.irreversible_block(8).unwrap().unwrap();
+    assert_eq!(
+        confirmed.block_id,
+        "0000000a00000000000000000000000000000000"
+    );
+}
@@ -0,0 +1,250 @@
+//! Block and header fetching, and block streams
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn props(head: u32) -> serde_json::Value {
+    json!({
+        "head_block_number": head,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": head,
+        "last_irreversible_block_num": head,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+fn header() -> serde_json::Value {
+    json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": "bob",
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+    })
+}
+
+fn block() -> serde_json::Value {
+    let mut block = header();
+    block.as_object_mut().unwrap().extend([
+        ("witness_signature".to_string(), json!("1f00")),
+        (
+            "transactions".to_string(),
+            json!([{ "operations": [{
+                "type": "vote_operation",
+                "value": { "voter": "alice", "author": "bob", "permlink": "p", "weight": 100 },
+            }] }]),
+        ),
+        (
+            "block_id".to_string(),
+            json!("0000000a00000000000000000000000000000000"),
+        ),
+        (
+            "signing_key".to_string(),
+            json!("STM1111111111111111111111111111111114T1Anm"),
+        ),
+        ("transaction_ids".to_string(), json!(["ab"])),
+    ]);
+    block
+}
+
+fn methods(mock: &MockTransport) -> Vec<String> {
+    mock.requests()
+        .iter()
+        .map(|(_, request)| request["method"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn headers_are_fetched_without_the_block() {
+    let mock =
+        MockTransport::new().respond("block_api.get_block_header", json!({ "header": header() }));
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let header = client.get_block_header(10).unwrap().unwrap();
+    assert_eq!(header.witness, "bob");
+    assert_eq!(header.timestamp, "2024-01-01T00:00:30");
+    assert!(header.extensions.is_empty());
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "block_num": 10 }));
+
+    // Blocks not produced yet have no header
+    let mock = MockTransport::new().respond("block_api.get_block_header", json!({}));
+    let client = Client::builder().transport(mock).build();
+    assert!(client.get_block_header(11).unwrap().is_none());
+
+    let mock = MockTransport::new().respond_error("block_api.get_block_header", -32000, "down");
+    let client = Client::builder().transport(mock).build();
+    let err = client.get_block_header(12).unwrap_err();
+    assert!(err.starts_with("Error fetching block header 12:"), "{err}");
+}
+
+#[test]
+fn header_only_streams_never_fetch_blocks() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(12))
+        .respond("block_api.get_block_header", json!({ "header": header() }));
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let blocks: Vec<_> = client
+        .stream_blocks(10)
+        .header_only()
+        .take(3)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let nums: Vec<u32> = blocks.iter().map(|b| b.block_num).collect();
+    assert_eq!(nums, [10, 11, 12]);
+    assert!(blocks.iter().all(|b| b.block.is_none()));
+    assert_eq!(blocks[0].header.witness, "bob");
+    assert!(!methods(&mock).contains(&"block_api.get_block".to_string()));
+}
+
+#[test]
+fn full_streams_carry_the_block_and_its_header() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(10))
+        .respond("block_api.get_block", json!({ "block": block() }));
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let streamed = client.stream_blocks(10).next().unwrap().unwrap();
+    assert_eq!(streamed.block_num, 10);
+    assert_eq!(streamed.header.witness, "bob");
+    let block = streamed.block.unwrap();
+    assert_eq!(block.block_id, "0000000a00000000000000000000000000000000");
+    let ops = block.operations();
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].0, "ab");
+    assert!(!methods(&mock).contains(&"block_api.get_block_header".to_string()));
+}
+
+#[test]
+fn stream_errors_are_yielded() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(10))
+        .respond_error("block_api.get_block_header", -32000, "down");
+    let client = Client::builder().transport(mock).build();
+    let err = client
+        .stream_blocks(10)
+        .header_only()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.starts_with("Error fetching block header 10:"), "{err}");
+}
+
+/// A chain whose head can move, recording the block ranges asked for
+#[derive(Clone, Default)]
+struct Chain {
+    head: Arc<AtomicU32>,
+    ranges: Arc<Mutex<Vec<(u32, u32)>>>,
+    single: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Chain {
+    fn at(head: u32) -> Self {
+        let chain = Self::default();
+        chain.head.store(head, Ordering::SeqCst);
+        chain
+    }
+
+    fn counts(&self) -> Vec<u32> {
+        self.ranges.lock().unwrap().iter().map(|r| r.1).collect()
+    }
+}
+
+impl Transport for Chain {
+    fn send(&self, _url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        let params = &request["params"];
+        let head = self.head.load(Ordering::SeqCst);
+        let result = match request["method"].as_str().unwrap() {
+            "database_api.get_dynamic_global_properties" => props(head),
+            "block_api.get_block_range" => {
+                let start = params["starting_block_num"].as_u64().unwrap() as u32;
+                let count = params["count"].as_u64().unwrap() as u32;
+                self.ranges.lock().unwrap().push((start, count));
+                let available = (head + 1).saturating_sub(start).min(count);
+                json!({ "blocks": vec![block(); available as usize] })
+            }
+            "block_api.get_block" => {
+                let num = params["block_num"].as_u64().unwrap() as u32;
+                self.single.lock().unwrap().push(num);
+                match num <= head {
+                    true => json!({ "block": block() }),
+                    false => json!({}),
+                }
+            }
+            method => panic!("unexpected {method}"),
+        };
+        Ok(
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                .to_string()
+                .into_bytes(),
+        )
+    }
+}
+
+#[test]
+fn lagging_streams_double_their_batches_up_to_the_maximum() {
+    let chain = Chain::at(1_000);
+    let client = Client::builder().transport(chain.clone()).build();
+    let mut stream = client.stream_blocks(1).header_only().max_batch_size(16);
+
+    let blocks: Vec<_> = stream.by_ref().take(60).map(Result::unwrap).collect();
+    let nums: Vec<u32> = blocks.iter().map(|block| block.block_num).collect();
+    assert_eq!(nums, (1..=60).collect::<Vec<_>>());
+    assert!(blocks.iter().all(|block| block.block.is_none()));
+    assert_eq!(chain.counts(), [2, 4, 8, 16, 16, 16]);
+    let starts: Vec<u32> = chain.ranges.lock().unwrap().iter().map(|r| r.0).collect();
+    assert_eq!(starts, [1, 3, 7, 15, 31, 47]);
+    assert!(chain.single.lock().unwrap().is_empty());
+
+    let lag = stream.lag();
+    assert!(lag.catching_up());
+    assert_eq!(lag.batch_size, 16);
+    assert_eq!(lag.next_block, 61);
+    assert_eq!(lag.buffered, 2);
+    assert_eq!(lag.behind, 940);
+    assert_eq!(lag.behind_time(), Duration::from_secs(3 * 940));
+}
+
+#[test]
+fn caught_up_streams_return_to_single_blocks() {
+    let chain = Chain::at(28);
+    let client = Client::builder().transport(chain.clone()).build();
+    let mut stream = client
+        .stream_blocks(1)
+        .catch_up_threshold(5)
+        .max_batch_size(8);
+
+    let blocks: Vec<_> = stream.by_ref().take(28).map(Result::unwrap).collect();
+    assert!(blocks.iter().all(|block| block.block.is_some()));
+    assert_eq!(blocks.last().unwrap().block_num, 28);
+    // The last batch asks for no more blocks than were behind
+    assert_eq!(chain.counts(), [2, 4, 8, 8, 6]);
+    assert!(stream.lag().catching_up());
+
+    // Within the threshold of the new head, blocks are fetched one by one
+    chain.head.store(30, Ordering::SeqCst);
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 29);
+    assert!(!stream.lag().catching_up());
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 30);
+    assert_eq!(*chain.single.lock().unwrap(), [29, 30]);
+    assert_eq!(chain.counts().len(), 5);
+}
+
+#[test]
+fn batching_can_be_disabled() {
+    let chain = Chain::at(100);
+    let client = Client::builder().transport(chain.clone()).build();
+    let mut stream = client.stream_blocks(1).max_batch_size(1);
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 1);
+    assert!(chain.ranges.lock().unwrap().is_empty());
+    assert_eq!(stream.lag().behind, 99);
+}
@@ -0,0 +1,24 @@
+use nectarflower_rs::{Client, NectarError};
+use serde_json::Value;
+use std::time::Duration;
+
+#[test]
+fn max_retries_limits_the_nodes_a_call_tries() {
+    // Nothing listens on the discard port, so every attempt fails fast
+    let client = Client::builder()
+        .nodes([
+            "http://127.0.0.1:9",
+            "http://127.0.0.1:9/a",
+            "http://127.0.0.1:9/b",
+        ])
+        .connect_timeout(Duration::from_secs(1))
+        .user_agent("builder-test")
+        .max_retries(1)
+        .build();
+    let err = client
+        .call::<_, Value>("condenser_api.get_config", ())
+        .unwrap_err();
+    assert!(matches!(err, NectarError::NoNodesAvailable { .. }));
+    assert_eq!(err.attempts().len(), 2);
+    assert_eq!(Client::new().nodes, ["https://api.hive.blog"]);
+}
@@ -0,0 +1,45 @@
+use nectarflower_rs::witness::Witness;
+use nectarflower_rs::Account;
+use serde_json::json;
+
+#[test]
+fn canonical_json_is_independent_of_asset_format_and_key_order() {
+    let legacy: Account = serde_json::from_value(json!({
+        "name": "alice",
+        "json_metadata": "",
+        "balance": "1.500 HIVE",
+        "hbd_balance": "2.000 HBD",
+    }))
+    .unwrap();
+    let nai: Account = serde_json::from_value(json!({
+        "hbd_balance": {"amount": "2000", "precision": 3, "nai": "@@000000013"},
+        "balance": {"amount": "1500", "precision": 3, "nai": "@@000000021"},
+        "json_metadata": "",
+        "name": "alice",
+    }))
+    .unwrap();
+
+    let canonical = legacy.to_canonical_json();
+    assert_eq!(canonical, nai.to_canonical_json());
+    assert!(canonical.contains("\"balance\": \"1.500 HIVE\""));
+    let active = canonical.find("\"active\"").unwrap();
+    let name = canonical.find("\"name\"").unwrap();
+    assert!(active < name);
+}
+
+#[test]
+fn witness_snapshot_sorts_nested_keys() {
+    let witness: Witness = serde_json::from_value(json!({
+        "owner": "bob",
+        "votes": "12345",
+        "total_missed": 2,
+        "signing_key": "STM1111111111111111111111111111111114T1Anm",
+        "running_version": "1.27.5",
+        "hbd_exchange_rate": {"quote": "1.000 HIVE", "base": "0.250 HBD"},
+        "last_hbd_exchange_update": "2024-01-01T00:00:00",
+    }))
+    .unwrap();
+    let canonical = witness.to_canonical_json();
+    assert!(canonical.find("\"base\"").unwrap() < canonical.find("\"quote\"").unwrap());
+    assert!(canonical.contains("\"votes\": 12345"));
+}
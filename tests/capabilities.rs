@@ -0,0 +1,43 @@
+use nectarflower_rs::capabilities::{NodeCapabilities, Support};
+use nectarflower_rs::{Capabilities, Capability};
+
+#[test]
+fn support_is_summarized_across_reachable_nodes() {
+    let capabilities = Capabilities {
+        nodes: vec![
+            NodeCapabilities::from_methods(
+                "https://a.example",
+                &["account_history_api.get_account_history", "bridge.get_post"],
+            ),
+            NodeCapabilities::from_methods(
+                "https://b.example",
+                &[
+                    "account_history_api.get_ops_in_block",
+                    "database_api.get_config",
+                ],
+            ),
+            NodeCapabilities {
+                node: "https://c.example".to_string(),
+                apis: Err("Request timed out".to_string()),
+            },
+        ],
+    };
+    assert_eq!(capabilities.support(Capability::History), Support::Full);
+    assert_eq!(capabilities.support(Capability::Bridge), Support::Partial);
+    assert_eq!(
+        capabilities.support(Capability::Broadcast),
+        Support::Unavailable
+    );
+    assert!(!capabilities.support(Capability::Broadcast).is_available());
+    assert_eq!(
+        capabilities.nodes_with(Capability::Bridge),
+        ["https://a.example"]
+    );
+    assert_eq!(
+        capabilities.unreachable(),
+        [("https://c.example", "Request timed out")]
+    );
+    assert!(capabilities
+        .to_string()
+        .starts_with("history: yes, broadcast: no, bridge: partial"));
+}
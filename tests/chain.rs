@@ -0,0 +1,151 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::chain::TESTNET_CHAIN_ID;
+use nectarflower_rs::crypto::{decode_public_key, encode_public_key};
+use nectarflower_rs::limits::{LimitError, TransferLimits};
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+use nectarflower_rs::witness_props::WitnessProperties;
+use nectarflower_rs::{ChainConfig, ChainRegistry, Client, Operation, Transaction, WriteClient};
+use serde_json::json;
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn amounts_of_different_chains_do_not_mix() {
+    let hive = ChainConfig::mainnet();
+    let testnet = ChainConfig::testnet(TESTNET_CHAIN_ID);
+
+    let a = hive.parse_asset("1.000 HIVE").unwrap();
+    let b = testnet.parse_asset("2.000 TESTS").unwrap();
+    assert!(hive.parse_asset("1.000 TESTS").is_err());
+    assert!(testnet.parse_asset("1.000 HIVE").is_err());
+    assert!(a.checked_add(&b).is_err());
+
+    let sum = b.checked_add(&b).unwrap();
+    assert_eq!(sum.asset(), Asset::new(4_000, AssetSymbol::Hive));
+    assert_eq!(testnet.format_asset(sum.asset()), "4.000 TESTS");
+    assert_eq!(sum.to_string(), "4.000 TESTS");
+    assert_eq!(a.to_string(), "1.000 HIVE");
+
+    assert!(hive.decode_public_key(DISABLED_SIGNING_KEY).is_ok());
+    assert!(testnet.decode_public_key(DISABLED_SIGNING_KEY).is_err());
+}
+
+#[test]
+fn registry_builds_clients_per_chain() {
+    let mut registry = ChainRegistry::new();
+    registry
+        .register(ChainConfig::testnet(TESTNET_CHAIN_ID))
+        .unwrap();
+    assert!(registry.register(ChainConfig::mainnet()).is_err());
+
+    let client = registry
+        .client("testnet", &["http://127.0.0.1:8090"])
+        .unwrap();
+    assert_eq!(client.chain().chain_id, TESTNET_CHAIN_ID);
+    assert_eq!(client.nodes, ["http://127.0.0.1:8090"]);
+    assert_eq!(
+        registry
+            .by_chain_id(TESTNET_CHAIN_ID)
+            .unwrap()
+            .address_prefix,
+        "TST"
+    );
+    assert!(registry.client("steem", &[]).is_err());
+    // No usable node is an error, not a fall back to a main network node
+    assert!(registry.client("testnet", &[]).is_err());
+    assert!(registry.client("testnet", &["not a url"]).is_err());
+}
+
+#[test]
+fn transactions_serialize_with_the_chain_symbols() {
+    let testnet = ChainConfig::testnet(TESTNET_CHAIN_ID);
+    let tx = Transaction {
+        ref_block_num: 1,
+        ref_block_prefix: 2,
+        expiration: "2026-01-01T00:00:00".to_string(),
+        operations: vec![Operation::new(
+            "transfer_operation",
+            json!({"from": "alice", "to": "bob", "amount": "1.000 TESTS", "memo": ""}),
+        )],
+        extensions: vec![],
+        signatures: vec![],
+    };
+
+    let testnet_bytes = tx.to_bytes_for(&testnet).unwrap();
+    assert!(contains(&testnet_bytes, b"TESTS\0\0"));
+    // A testnet amount is never signed as HIVE on the main network
+    let err = tx.to_bytes().unwrap_err();
+    assert!(err.contains("TESTS"), "{err}");
+
+    let mut mainnet_tx = tx.clone();
+    mainnet_tx.operations[0].value["amount"] = json!("1.000 HIVE");
+    let mainnet_bytes = mainnet_tx.to_bytes().unwrap();
+    assert!(contains(&mainnet_bytes, b"STEEM\0\0"));
+    assert!(mainnet_tx.to_bytes_for(&testnet).is_err());
+    // NAI amounts carry no name and serialize on either chain
+    let mut nai_tx = tx.clone();
+    nai_tx.operations[0].value["amount"] = Asset::new(1_000, AssetSymbol::Hive).to_nai();
+    assert!(contains(&nai_tx.to_bytes().unwrap(), b"STEEM\0\0"));
+    assert_eq!(nai_tx.to_bytes_for(&testnet).unwrap(), testnet_bytes);
+    assert_ne!(nai_tx.id().unwrap(), nai_tx.id_for(&testnet).unwrap());
+    assert_ne!(
+        nai_tx.digest(&ChainConfig::mainnet()).unwrap(),
+        nai_tx.digest(&testnet).unwrap()
+    );
+}
+
+#[test]
+fn asset_names_are_looked_up_per_chain() {
+    let hive = ChainConfig::mainnet();
+    let testnet = ChainConfig::testnet(TESTNET_CHAIN_ID);
+
+    assert!("1.000 TESTS".parse::<Asset>().is_err());
+    assert!("1.000 TBD".parse::<Asset>().is_err());
+    assert_eq!(
+        "1.000 STEEM".parse::<Asset>().unwrap(),
+        Asset::new(1_000, AssetSymbol::Hive)
+    );
+    assert_eq!(hive.symbol_from_name("SBD"), Some(AssetSymbol::Hbd));
+    assert_eq!(hive.symbol_from_name("TBD"), None);
+    assert_eq!(testnet.symbol_from_name("TBD"), Some(AssetSymbol::Hbd));
+    assert_eq!(testnet.symbol_from_name("HBD"), None);
+    assert_eq!(
+        testnet.asset_from_value(&json!("2.500 TBD")).unwrap(),
+        Asset::new(2_500, AssetSymbol::Hbd)
+    );
+    assert!(testnet.asset_from_value(&json!("2.500 HBD")).is_err());
+}
+
+#[test]
+fn testnet_transfers_are_limited() {
+    let testnet = ChainConfig::testnet(TESTNET_CHAIN_ID);
+    let limits = TransferLimits::default().per_transfer(Asset::new(1_000, AssetSymbol::Hive));
+    let writer = WriteClient::new(Client::new().with_chain(testnet)).transfer_limits(limits);
+    let transfer = |amount: &str| {
+        Operation::new(
+            "transfer_operation",
+            json!({"from": "alice", "to": "bob", "amount": amount, "memo": ""}),
+        )
+    };
+    assert!(writer.check_transfers(&[transfer("1.000 TESTS")]).is_ok());
+    assert!(matches!(
+        writer.check_transfers(&[transfer("1.001 TESTS")]),
+        Err(LimitError::PerTransfer { .. })
+    ));
+}
+
+#[test]
+fn witness_properties_use_the_chain_key_prefix() {
+    let testnet = ChainConfig::testnet(TESTNET_CHAIN_ID);
+    let key = decode_public_key(DISABLED_SIGNING_KEY).unwrap();
+    let testnet_key = encode_public_key(&key, "TST");
+    let props = WitnessProperties::new("alice", &testnet_key).hbd_exchange_rate(0.25);
+
+    assert!(props.encoded().is_err());
+    let encoded = props.encoded_for(&testnet).unwrap();
+    assert_eq!(encoded["key"], key);
+    assert!(contains(&encoded["hbd_exchange_rate"], b"TBD\0\0\0\0"));
+    assert!(contains(&encoded["hbd_exchange_rate"], b"TESTS\0\0"));
+}
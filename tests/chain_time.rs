@@ -0,0 +1,113 @@
+//! Chain clock offsets and expirations set in chain time
+
+use nectarflower_rs::timestamp::{format_timestamp, parse_timestamp};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{ChainClock, Client, Transaction};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 2024-01-01T00:00:00
+const HEAD: i64 = 1_704_067_200;
+
+fn local_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn node(head_time: &str) -> (Client, MockTransport) {
+    let mock = MockTransport::new().respond(
+        "database_api.get_dynamic_global_properties",
+        json!({
+            "head_block_number": 10,
+            "head_block_id": "0000000aaabbccdd000000000000000000000000",
+            "time": head_time,
+            "current_witness": "bob",
+            "current_aslot": 10,
+            "last_irreversible_block_num": 10,
+            "total_vesting_fund_hive": "1000.000 HIVE",
+            "total_vesting_shares": "2000000.000000 VESTS",
+        }),
+    );
+    (Client::builder().transport(mock.clone()).build(), mock)
+}
+
+#[test]
+fn offsets_account_for_block_age_and_round_trip() {
+    let sent = UNIX_EPOCH + Duration::from_secs(HEAD as u64 - 10);
+    let clock = ChainClock::from_head_time("2024-01-01T00:00:00", sent, Duration::from_millis(200))
+        .unwrap();
+    // Half a block interval after the head, against halfway through the round trip
+    assert_eq!(clock.offset_ms(), 10_000 + 1_500 - 100);
+    assert_eq!(clock.rtt(), Duration::from_millis(200));
+    assert!(clock.age() < Duration::from_secs(5));
+    assert!(ChainClock::from_head_time("yesterday", sent, Duration::ZERO).is_err());
+}
+
+#[test]
+fn expirations_are_clamped_below_the_chain_limit() {
+    let clock = ChainClock::from_head_time(
+        &format_timestamp(local_now() + 3_600),
+        SystemTime::now(),
+        Duration::ZERO,
+    )
+    .unwrap();
+    let now = clock.now();
+    assert!((now - local_now() - 3_600).abs() <= 2, "{now}");
+
+    let expiration = |secs| parse_timestamp(&clock.expiration(secs)).unwrap() - clock.now();
+    assert!((59..=60).contains(&expiration(60)));
+    assert!((0..=1).contains(&expiration(0)));
+    // One block short of the hour, for the estimate running ahead of the head
+    assert!((3_596..=3_597).contains(&expiration(10_000)));
+}
+
+#[test]
+fn clients_record_the_offset_from_global_properties() {
+    // The node's chain is two hours ahead of the local clock
+    let (client, mock) = node(&format_timestamp(local_now() + 7_200));
+    assert!(client.chain_clock().is_none());
+
+    let clock = client.sync_chain_time().unwrap();
+    assert!((clock.offset_ms() - 7_200_000).abs() < 3_000, "{clock:?}");
+    assert_eq!(client.chain_clock(), Some(clock));
+
+    // A fresh offset is reused rather than measured again
+    let now = client.chain_now().unwrap();
+    assert!((now - local_now() - 7_200).abs() <= 3, "{now}");
+    assert_eq!(mock.requests().len(), 1);
+
+    let mut tx = Transaction {
+        ref_block_num: 10,
+        ref_block_prefix: 0,
+        expiration: "2000-01-01T00:00:00".to_string(),
+        operations: vec![],
+        extensions: vec![],
+        signatures: vec![],
+    };
+    client.refresh_expiration(&mut tx, 120).unwrap();
+    let expires = parse_timestamp(&tx.expiration).unwrap();
+    assert!((expires - now - 120).abs() <= 1, "{}", tx.expiration);
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn sync_errors_are_reported() {
+    let mock = MockTransport::new().respond_error(
+        "database_api.get_dynamic_global_properties",
+        -32000,
+        "down",
+    );
+    let client = Client::builder().transport(mock).build();
+    assert!(client.sync_chain_time().is_err());
+    assert!(client.chain_now().is_err());
+    assert!(client.chain_clock().is_none());
+
+    // An unreadable head time leaves no offset behind
+    let (client, _) = node("not a time");
+    assert_eq!(
+        client.sync_chain_time().unwrap_err(),
+        "Chain time was not recorded"
+    );
+}
@@ -0,0 +1,49 @@
+//! Overflow policies of the bounded stream channel
+
+use nectarflower_rs::channel::{bounded, SendError};
+use nectarflower_rs::OverflowPolicy;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn drop_oldest_keeps_the_newest_items() {
+    let (tx, rx) = bounded(2, OverflowPolicy::DropOldest);
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    assert_eq!(rx.dropped(), 3);
+    assert_eq!(rx.collect::<Vec<_>>(), [3, 4]);
+}
+
+#[test]
+fn error_policy_closes_the_channel() {
+    let (tx, rx) = bounded(1, OverflowPolicy::Error);
+    tx.send(1).unwrap();
+    assert_eq!(tx.send(2), Err(SendError::Overflow));
+    assert_eq!(tx.send(3), Err(SendError::Overflow));
+    assert!(rx.overflowed());
+    assert_eq!(rx.try_recv(), Some(1));
+}
+
+#[test]
+fn block_policy_waits_for_the_consumer() {
+    let (tx, rx) = bounded(1, OverflowPolicy::Block);
+    let producer = thread::spawn(move || {
+        for i in 0..3 {
+            tx.send(i).unwrap();
+        }
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx.collect::<Vec<_>>(), [0, 1, 2]);
+    producer.join().unwrap();
+}
+
+#[test]
+fn dropping_the_receiver_disconnects_senders() {
+    let (tx, rx) = bounded(1, OverflowPolicy::Block);
+    tx.send(1).unwrap();
+    drop(rx);
+    assert_eq!(tx.send(2), Err(SendError::Disconnected));
+}
@@ -0,0 +1,51 @@
+//! Decoding consensus comment objects and paging keys
+
+use nectarflower_rs::{ChainComment, CommentOrder, CommentStart};
+use serde_json::json;
+
+#[test]
+fn chain_comment_decodes_and_continues_listings() {
+    let comment: ChainComment = serde_json::from_value(json!({
+        "id": 123456789,
+        "author": "bob",
+        "permlink": "re-alice-hello",
+        "parent_author": "alice",
+        "parent_permlink": "hello",
+        "root_author": "alice",
+        "root_permlink": "hello",
+        "cashout_time": "2024-01-08T12:00:00",
+        "depth": 1,
+        "net_rshares": "-1500000000",
+        "max_accepted_payout": { "amount": "1000000000", "precision": 3, "nai": "@@000000013" },
+        "percent_hbd": 10000,
+        "beneficiaries": [{ "account": "dev", "weight": 500 }],
+    }))
+    .unwrap();
+    assert!(!comment.is_post());
+    assert_eq!(comment.net_rshares, -1_500_000_000);
+    assert!(comment.allow_votes);
+
+    let next = comment.start_key(CommentOrder::ByCashoutTime);
+    assert_eq!(next.order(), CommentOrder::ByCashoutTime);
+    assert_eq!(
+        next,
+        CommentStart::CashoutTime {
+            time: "2024-01-08T12:00:00".into(),
+            author: "bob".into(),
+            permlink: "re-alice-hello".into(),
+        }
+    );
+    assert_eq!(
+        comment.start_key(CommentOrder::ByParent).order().as_str(),
+        "by_parent"
+    );
+    assert_eq!(
+        CommentStart::replies_to("alice", "hello"),
+        CommentStart::Parent {
+            parent_author: "alice".into(),
+            parent_permlink: "hello".into(),
+            author: String::new(),
+            permlink: String::new(),
+        }
+    );
+}
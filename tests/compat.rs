@@ -0,0 +1,108 @@
+//! beem and hive-js style names over the native API
+#![cfg(feature = "compat")]
+
+use nectarflower_rs::compat::{Amount, Hive};
+use nectarflower_rs::transaction::Transaction;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, SecretWif, WriteClient};
+use serde_json::{json, Value};
+
+fn mock() -> MockTransport {
+    MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 1234,
+                "head_block_id": "000004d2f776e542000000000000000000000000",
+                "time": "2017-07-15T16:50:19",
+                "current_witness": "bob",
+                "current_aslot": 1234,
+                "last_irreversible_block_num": 1200,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond("network_broadcast_api.broadcast_transaction", json!({}))
+}
+
+fn broadcast_op(mock: &MockTransport) -> (Transaction, Value) {
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(
+        request["method"],
+        "network_broadcast_api.broadcast_transaction"
+    );
+    let tx: Transaction = serde_json::from_value(request["params"]["trx"].clone()).unwrap();
+    let op = serde_json::to_value(&tx.operations[0]).unwrap();
+    (tx, op)
+}
+
+#[test]
+fn account_lookups_use_find_accounts() {
+    let mock = MockTransport::new().respond(
+        "database_api.find_accounts",
+        json!({ "accounts": [{ "name": "alice", "json_metadata": "" }] }),
+    );
+    let hive: Hive = Client::builder().transport(mock.clone()).build();
+    assert_eq!(hive.get_account("alice").unwrap().unwrap().name, "alice");
+    assert_eq!(hive.get_accounts(&["alice"]).unwrap().len(), 1);
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "accounts": ["alice"] }));
+
+    let mock =
+        MockTransport::new().respond("database_api.find_accounts", json!({ "accounts": [] }));
+    let hive = Client::builder().transport(mock).build();
+    assert!(hive.get_account("nobody").unwrap().is_none());
+}
+
+#[test]
+fn broadcasts_sign_and_send_one_operation() {
+    let mock = mock();
+    let client = WriteClient::new(Client::builder().transport(mock.clone()).build());
+    let key = SecretWif::parse("5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL").unwrap();
+    let broadcast = client.broadcast(&key);
+
+    let amount: Amount = "1.000 HIVE".parse().unwrap();
+    let trx_id = broadcast.transfer("alice", "bob", amount, "hi").unwrap();
+    let (tx, op) = broadcast_op(&mock);
+    assert_eq!(trx_id, tx.id().unwrap());
+    assert_eq!(tx.signatures.len(), 1);
+    assert_eq!(tx.operations.len(), 1);
+    assert_eq!(op["type"], "transfer_operation");
+    assert_eq!(op["value"]["to"], "bob");
+    assert_eq!(op["value"]["amount"], amount.to_nai());
+
+    broadcast.vote("alice", "bob", "post", -10_000).unwrap();
+    let (_, op) = broadcast_op(&mock);
+    assert_eq!(op["type"], "vote_operation");
+    assert_eq!(op["value"]["weight"], -10_000);
+
+    broadcast
+        .account_witness_vote("alice", "bob", false)
+        .unwrap();
+    let (_, op) = broadcast_op(&mock);
+    assert_eq!(op["type"], "account_witness_vote_operation");
+    assert_eq!(op["value"]["approve"], false);
+
+    broadcast
+        .custom_json("alice", "follow", json!(["follow", {}]))
+        .unwrap();
+    let (_, op) = broadcast_op(&mock);
+    assert_eq!(op["type"], "custom_json_operation");
+    assert_eq!(op["value"]["required_posting_auths"], json!(["alice"]));
+}
+
+#[test]
+fn broadcast_errors_are_returned() {
+    let mock = mock().respond_error(
+        "network_broadcast_api.broadcast_transaction",
+        -32000,
+        "missing required posting authority",
+    );
+    let client = WriteClient::new(Client::builder().transport(mock).build());
+    let key = SecretWif::parse("5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL").unwrap();
+    let err = client
+        .broadcast(&key)
+        .vote("alice", "bob", "post", 100)
+        .unwrap_err();
+    assert!(err.contains("missing required posting authority"), "{err}");
+}
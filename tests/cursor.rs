@@ -0,0 +1,41 @@
+//! Persisting and resuming pagination cursors, without contacting a node
+
+use nectarflower_rs::history::HistoryOrder;
+use nectarflower_rs::{Client, Cursor, HistoryCursor};
+
+#[test]
+fn cursors_round_trip_through_tokens_and_resume() {
+    let client = Client::new();
+    let history = Cursor::AccountHistory(HistoryCursor {
+        account: "alice".into(),
+        order: HistoryOrder::OldestFirst,
+        after: Some(41),
+    });
+    let token = history.to_token();
+    assert_eq!(Cursor::from_token(&token).unwrap(), history);
+
+    let resumed = client.account_history("alice").resume(&history).unwrap();
+    assert_eq!(resumed.cursor(), history);
+    let err = client.account_history("bob").resume(&history).unwrap_err();
+    assert!(err.contains("alice"), "{err}");
+
+    let blocks = Cursor::Blocks { next: 5 };
+    let stream = client.stream_blocks(100).resume(&blocks).unwrap();
+    assert_eq!(stream.cursor(), blocks);
+    assert_eq!(stream.next_block_num(), 5);
+
+    let accounts = Cursor::Accounts {
+        after: Some("carol".into()),
+    };
+    let scan = client.scan_accounts().resume(&accounts).unwrap();
+    assert_eq!(scan.cursor(), accounts);
+    assert!(client.scan_accounts().resume(&blocks).is_err());
+
+    let merged = client.merged_history(&["alice", "bob"]).cursor();
+    let Cursor::MergedHistory { accounts } = &merged else {
+        panic!("{merged:?}");
+    };
+    assert_eq!(accounts.len(), 2);
+    assert!(client.merged_history(&["alice"]).resume(&history).is_err());
+    assert!(Cursor::from_token("{\"kind\":\"nope\"}").is_err());
+}
@@ -0,0 +1,170 @@
+use nectarflower_rs::custom_json::{
+    CustomJson, CustomJsonBuilder, CustomOp, CustomOpRegistry, IdempotencyFilter,
+};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+fn custom(sequence: u64) -> CustomJson {
+    let op = CustomJsonBuilder::new("game", "alice", json!({"move": "e4"}))
+        .idempotent(sequence)
+        .operation()
+        .unwrap();
+    serde_json::from_value(op.value).unwrap()
+}
+
+#[test]
+fn retries_collapse_but_repeated_operations_do_not() {
+    let mut filter = IdempotencyFilter::new(10);
+    assert!(filter.first_seen(&custom(1)));
+    // A retry of the same logical operation
+    assert!(!filter.first_seen(&custom(1)));
+    // The same move made again on purpose
+    assert!(filter.first_seen(&custom(2)));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Follow {
+    follower: String,
+    following: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum App {
+    Follow(Follow),
+    Move(String),
+}
+
+impl From<Follow> for App {
+    fn from(follow: Follow) -> Self {
+        App::Follow(follow)
+    }
+}
+
+fn registry() -> CustomOpRegistry<App> {
+    CustomOpRegistry::new()
+        .register::<Follow>("follow")
+        // Only moves signed by the player count
+        .register_with("game", |custom, payload| match custom.signer() {
+            Some("alice") => Ok(App::Move(payload["move"].as_str().unwrap_or("").into())),
+            signer => Err(format!("Not a player: {signer:?}")),
+        })
+}
+
+fn custom_op(id: &str, account: &str, payload: &str) -> Value {
+    json!({
+        "type": "custom_json_operation",
+        "value": {
+            "required_auths": [],
+            "required_posting_auths": [account],
+            "id": id,
+            "json": payload,
+        },
+    })
+}
+
+#[test]
+fn registered_ids_decode_into_the_app_type() {
+    let registry = registry();
+    assert!(registry.is_registered("follow"));
+    assert!(!registry.is_registered("other"));
+    let decode = |op: Value| registry.decode(&serde_json::from_value(op).unwrap());
+
+    assert_eq!(
+        decode(custom_op(
+            "follow",
+            "bob",
+            r#"{"follower":"bob","following":"alice"}"#
+        )),
+        Some(CustomOp::Typed(App::Follow(Follow {
+            follower: "bob".into(),
+            following: "alice".into(),
+        })))
+    );
+    assert_eq!(
+        decode(custom_op("game", "alice", r#"{"move":"e4"}"#)),
+        Some(CustomOp::Typed(App::Move("e4".into())))
+    );
+    let Some(CustomOp::Invalid(custom, err)) = decode(custom_op("game", "bob", "{}")) else {
+        panic!("expected an invalid operation");
+    };
+    assert_eq!(
+        (custom.id.as_str(), err.as_str()),
+        ("game", "Not a player: Some(\"bob\")")
+    );
+    let Some(CustomOp::Invalid(_, err)) = decode(custom_op("follow", "bob", "not json")) else {
+        panic!("expected an invalid payload");
+    };
+    assert!(
+        err.starts_with("Invalid custom_json payload for 'follow':"),
+        "{err}"
+    );
+    let Some(CustomOp::Invalid(_, err)) = decode(custom_op("follow", "bob", r#"{"x":1}"#)) else {
+        panic!("expected a payload of the wrong shape");
+    };
+    assert!(err.contains("follower"), "{err}");
+    assert!(matches!(
+        decode(custom_op("other", "bob", "{}")),
+        Some(CustomOp::Unregistered(custom)) if custom.id == "other"
+    ));
+    // Other operation types are not custom_json
+    assert_eq!(
+        decode(json!({ "type": "vote_operation", "value": { "voter": "bob" } })),
+        None
+    );
+}
+
+#[test]
+fn streamed_blocks_decode_their_custom_json() {
+    let block = json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": "bob",
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+        "witness_signature": "1f00",
+        "transactions": [
+            { "operations": [
+                custom_op("game", "alice", r#"{"move":"e4"}"#),
+                { "type": "vote_operation", "value": { "voter": "bob" } },
+            ] },
+            { "operations": [custom_op("other", "carol", "{}")] },
+        ],
+        "block_id": "0000000a00000000000000000000000000000000",
+        "signing_key": "STM1111111111111111111111111111111114T1Anm",
+        "transaction_ids": ["aa", "bb"],
+    });
+    let mock = MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-01T00:00:30",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond("block_api.get_block", json!({ "block": block }))
+        .respond("block_api.get_block_header", json!({ "header": block }));
+    let client = Client::builder().transport(mock).build();
+    let registry = registry();
+
+    let streamed = client.stream_blocks(10).next().unwrap().unwrap();
+    let ops = streamed.custom_ops(&registry);
+    assert_eq!(ops.len(), 2);
+    assert_eq!(ops[0], ("aa", CustomOp::Typed(App::Move("e4".into()))));
+    assert_eq!(ops[1].0, "bb");
+
+    // Header-only streams carry no operations
+    let header = client
+        .stream_blocks(10)
+        .header_only()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(header.custom_ops(&registry).is_empty());
+}
@@ -0,0 +1,104 @@
+//! Vesting delegations and HIVE Power returning from removed ones
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+
+fn props() -> serde_json::Value {
+    json!({
+        "head_block_number": 10,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": 10,
+        "last_irreversible_block_num": 10,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+#[test]
+fn delegations_are_fetched_by_delegator() {
+    let mock = MockTransport::new().respond(
+        "database_api.find_vesting_delegations",
+        json!({ "delegations": [{
+            "delegator": "alice",
+            "delegatee": "bob",
+            "vesting_shares": "1000.000000 VESTS",
+            "min_delegation_time": "2024-01-01T00:00:00",
+        }] }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let delegations = client.find_vesting_delegations("alice").unwrap();
+    assert_eq!(delegations[0].delegatee, "bob");
+    assert_eq!(delegations[0].vesting_shares.amount, 1_000_000_000);
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "account": "alice" }));
+}
+
+#[test]
+fn returning_power_is_sorted_and_converted() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props())
+        .respond(
+            "database_api.find_vesting_delegation_expirations",
+            json!({ "delegations": [
+                {
+                    "delegator": "alice",
+                    "vesting_shares": "4000.000000 VESTS",
+                    "expiration": "2024-01-05T12:00:00",
+                },
+                {
+                    "delegator": "alice",
+                    "vesting_shares": "2000.000000 VESTS",
+                    "expiration": "2024-01-03T00:00:00",
+                },
+            ] }),
+        );
+    let client = Client::builder().transport(mock).build();
+
+    let returning = client.returning_hp("alice").unwrap();
+    let summary: Vec<_> = returning
+        .iter()
+        .map(|r| (r.expiration.as_str(), r.vests.to_string(), r.hp.to_string()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "2024-01-03T00:00:00",
+                "2000.000000 VESTS".to_string(),
+                "1.000 HIVE".to_string()
+            ),
+            (
+                "2024-01-05T12:00:00",
+                "4000.000000 VESTS".to_string(),
+                "2.000 HIVE".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn delegation_errors_name_the_account() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props())
+        .respond_error("database_api.find_vesting_delegations", -32000, "down")
+        .respond_error(
+            "database_api.find_vesting_delegation_expirations",
+            -32000,
+            "down",
+        );
+    let client = Client::builder().transport(mock).build();
+    let err = client.find_vesting_delegations("alice").unwrap_err();
+    assert!(
+        err.starts_with("Error fetching delegations for alice:"),
+        "{err}"
+    );
+    let err = client.returning_hp("alice").unwrap_err();
+    assert!(
+        err.starts_with("Error fetching delegation expirations for alice:"),
+        "{err}"
+    );
+}
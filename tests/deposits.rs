@@ -0,0 +1,164 @@
+//! Deposits are credited only if they survive into the irreversible block
+
+use nectarflower_rs::block::{Block, StreamedBlock};
+use nectarflower_rs::block_source::BlockSource;
+use nectarflower_rs::{DepositEvent, DepositWatcher};
+use serde_json::json;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+fn block(transfers: &[(&str, &str, &str)]) -> Block {
+    let (transactions, ids): (Vec<_>, Vec<_>) = transfers
+        .iter()
+        .map(|(trx_id, amount, memo)| {
+            let op = json!({
+                "type": "transfer_operation",
+                "value": { "from": "alice", "to": "exchange", "amount": amount, "memo": memo },
+            });
+            (json!({ "operations": [op] }), trx_id.to_string())
+        })
+        .unzip();
+    serde_json::from_value(json!({
+        "previous": "00",
+        "timestamp": "2024-01-01T00:00:00",
+        "witness": "bob",
+        "transaction_merkle_root": "00",
+        "witness_signature": "00",
+        "transactions": transactions,
+        "block_id": "00",
+        "signing_key": "STM1111111111111111111111111111111114T1Anm",
+        "transaction_ids": ids,
+    }))
+    .unwrap()
+}
+
+/// Streams reversible blocks, then serves different irreversible ones
+///
+/// The last irreversible block trails the streamed one by `lag` blocks, and
+/// each block in `unavailable` cannot be fetched the first time it is asked for.
+struct ForkingSource {
+    streamed: VecDeque<(u32, Block)>,
+    irreversible: HashMap<u32, Block>,
+    lag: u32,
+    lib: u32,
+    unavailable: HashSet<u32>,
+}
+
+impl ForkingSource {
+    fn new(streamed: Vec<(u32, Block)>, irreversible: Vec<(u32, Block)>, lag: u32) -> Self {
+        Self {
+            streamed: streamed.into(),
+            irreversible: irreversible.into_iter().collect(),
+            lag,
+            lib: 0,
+            unavailable: HashSet::new(),
+        }
+    }
+}
+
+impl BlockSource for ForkingSource {
+    fn next_block(&mut self) -> Option<Result<StreamedBlock, String>> {
+        let (block_num, block) = self.streamed.pop_front()?;
+        self.lib = block_num - self.lag;
+        Some(Ok(StreamedBlock {
+            block_num,
+            header: block.header.clone(),
+            block: Some(block),
+        }))
+    }
+
+    fn last_irreversible_block_num(&mut self) -> Result<u32, String> {
+        Ok(self.lib)
+    }
+
+    fn irreversible_block(&mut self, block_num: u32) -> Result<Option<Block>, String> {
+        if self.unavailable.remove(&block_num) {
+            return Ok(None);
+        }
+        Ok(self.irreversible.get(&block_num).cloned())
+    }
+}
+
+#[test]
+fn forked_out_deposits_are_reverted_instead_of_credited() {
+    let mut watcher = DepositWatcher::new("exchange", 1);
+    watcher.expect("user-1", "1");
+    watcher.expect("user-2", "2");
+    let mut source = ForkingSource::new(
+        vec![(
+            10,
+            block(&[
+                ("aa", "5.000 HIVE", "user-1"),
+                ("bb", "7.000 HIVE", "user-2"),
+            ]),
+        )],
+        vec![(10, block(&[("aa", "5.000 HIVE", "user-1")]))],
+        0,
+    );
+
+    let events = watch(&mut watcher, &mut source);
+    assert!(matches!(&events[2], DepositEvent::Credited(d) if d.trx_id == "aa"));
+    assert!(matches!(&events[3], DepositEvent::Reverted(d) if d.trx_id == "bb"));
+    assert!(watcher.pending().is_empty());
+}
+
+fn watch(watcher: &mut DepositWatcher, source: &mut ForkingSource) -> Vec<DepositEvent> {
+    let mut events = Vec::new();
+    watcher
+        .watch_source(source, |event| {
+            events.push(event);
+            true
+        })
+        .unwrap();
+    events
+}
+
+#[test]
+fn deposits_moved_into_passed_blocks_are_credited() {
+    let mut watcher = DepositWatcher::new("exchange", 1);
+    watcher.expect("user-2", "2");
+    // A fork moved the transfer seen in block 10 into block 9, already streamed
+    let mut source = ForkingSource::new(
+        vec![
+            (9, block(&[])),
+            (10, block(&[("bb", "7.000 HIVE", "user-2")])),
+            (11, block(&[])),
+        ],
+        vec![
+            (9, block(&[("bb", "7.000 HIVE", "user-2")])),
+            (10, block(&[])),
+            (11, block(&[])),
+        ],
+        1,
+    );
+
+    let events = watch(&mut watcher, &mut source);
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert!(matches!(&events[0], DepositEvent::Seen(d) if d.block_num == 10));
+    assert!(matches!(&events[1], DepositEvent::Credited(d) if d.block_num == 9));
+    assert!(watcher.pending().is_empty());
+}
+
+#[test]
+fn unavailable_irreversible_blocks_are_retried() {
+    let mut watcher = DepositWatcher::new("exchange", 1);
+    watcher.expect("user-1", "1");
+    let mut source = ForkingSource::new(
+        vec![
+            (10, block(&[("aa", "5.000 HIVE", "user-1")])),
+            (11, block(&[])),
+        ],
+        vec![
+            (10, block(&[("aa", "5.000 HIVE", "user-1")])),
+            (11, block(&[])),
+        ],
+        0,
+    );
+    source.unavailable.insert(10);
+
+    let events = watch(&mut watcher, &mut source);
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert!(matches!(&events[0], DepositEvent::Seen(d) if d.trx_id == "aa"));
+    assert!(matches!(&events[1], DepositEvent::Credited(d) if d.trx_id == "aa"));
+    assert!(source.unavailable.is_empty());
+    assert!(watcher.pending().is_empty());
+}
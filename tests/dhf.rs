@@ -0,0 +1,145 @@
+//! DHF proposal funding against the treasury budget
+
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::dhf::{allocate_funding, Proposal, TREASURY_ACCOUNT};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn proposal(id: u32, receiver: &str, votes: &str, daily_pay: &str) -> Value {
+    json!({
+        "id": id,
+        "proposal_id": id,
+        "creator": "alice",
+        "receiver": receiver,
+        "start_date": "2024-01-01T00:00:00",
+        "end_date": "2025-01-01T00:00:00",
+        "daily_pay": daily_pay,
+        "subject": format!("Proposal {id}"),
+        "permlink": format!("proposal-{id}"),
+        "total_votes": votes,
+        "status": "active",
+    })
+}
+
+fn proposals() -> Vec<Value> {
+    vec![
+        proposal(1, "dev", "4000000000000", "5000.000 HBD"),
+        proposal(0, TREASURY_ACCOUNT, "2000000000000", "240000000.000 HBD"),
+        proposal(2, "ops", "5000000000000", "6000.000 HBD"),
+        proposal(3, "art", "1000000000000", "100.000 HBD"),
+    ]
+}
+
+fn client(treasury_hbd: &str) -> (Client, MockTransport) {
+    let mock = MockTransport::new()
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{ "name": TREASURY_ACCOUNT, "json_metadata": "", "hbd_balance": treasury_hbd }] }),
+        )
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-01T00:00:00",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond("database_api.list_proposals", json!({ "proposals": proposals() }));
+    (Client::builder().transport(mock.clone()).build(), mock)
+}
+
+fn hbd(amount: i64) -> Asset {
+    Asset::new(amount, AssetSymbol::Hbd)
+}
+
+#[test]
+fn proposals_above_the_return_proposal_share_the_budget() {
+    let (client, mock) = client("1000000.000 HBD");
+    let status = client.dhf_status().unwrap();
+    assert_eq!(status.treasury_balance, hbd(1_000_000_000));
+    assert_eq!(status.daily_budget, hbd(10_000_000));
+    assert_eq!(status.threshold_votes, 2_000_000_000_000);
+    assert_eq!(status.threshold_hp.to_string(), "1000.000 HIVE");
+
+    let funding: Vec<_> = status
+        .proposals
+        .iter()
+        .map(|p| (p.proposal.id, p.funded_pay.amount, p.is_fully_funded()))
+        .collect();
+    // The budget runs out part way through the second proposal
+    assert_eq!(
+        funding,
+        [
+            (2, 6_000_000, true),
+            (1, 4_000_000, false),
+            (0, 0, false),
+            (3, 0, false)
+        ]
+    );
+    let funded: Vec<_> = status.funded().map(|p| p.proposal.id).collect();
+    assert_eq!(funded, [2, 1]);
+    let unfunded: Vec<_> = status.unfunded().map(|p| p.proposal.id).collect();
+    assert_eq!(unfunded, [3]);
+    assert_eq!(status.daily_spend, hbd(10_000_000));
+    assert_eq!(status.runway_days, Some(100.0));
+
+    let list = mock
+        .requests()
+        .into_iter()
+        .find(|(_, r)| r["method"] == "database_api.list_proposals")
+        .unwrap()
+        .1;
+    assert_eq!(
+        list["params"],
+        json!({
+            "start": [],
+            "limit": 1000,
+            "order": "by_total_votes",
+            "order_direction": "descending",
+            "status": "active",
+        })
+    );
+}
+
+#[test]
+fn an_empty_treasury_funds_nothing() {
+    let (client, _) = client("0.000 HBD");
+    let status = client.dhf_status().unwrap();
+    assert_eq!(status.funded().count(), 0);
+    assert_eq!(status.runway_days, None);
+}
+
+#[test]
+fn without_a_return_proposal_any_votes_qualify() {
+    let proposals: Vec<Proposal> = [
+        proposal(4, "a", "1", "1.000 HBD"),
+        proposal(5, "b", "0", "1.000 HBD"),
+    ]
+    .into_iter()
+    .map(|p| serde_json::from_value(p).unwrap())
+    .collect();
+    let (funded, threshold) = allocate_funding(proposals, hbd(5_000));
+    assert_eq!(threshold, 0);
+    assert!(funded[0].is_fully_funded());
+    assert!(!funded[1].is_funded());
+}
+
+#[test]
+fn proposals_are_found_by_id() {
+    let mock = MockTransport::new().respond(
+        "database_api.find_proposals",
+        json!({ "proposals": [proposal(7, "dev", "12", "1.000 HBD")] }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    let found = client.find_proposals(&[7, 8]).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].total_votes, 12);
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "proposal_ids": [7, 8] }));
+}
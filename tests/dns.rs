@@ -0,0 +1,116 @@
+//! DNS overrides, custom resolvers and address family preference
+
+use nectarflower_rs::{Client, DnsResolver, IpPreference};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const V4: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+const V4_B: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+const V6: IpAddr = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// Answer one JSON-RPC request with `result`
+fn serve_once(result: Value) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+    port
+}
+
+/// Resolves every host to fixed addresses, recording the hosts asked for
+#[derive(Clone)]
+struct Fixed {
+    addrs: Vec<IpAddr>,
+    asked: Arc<Mutex<Vec<String>>>,
+}
+
+impl Fixed {
+    fn new(addrs: &[IpAddr]) -> Self {
+        Self {
+            addrs: addrs.to_vec(),
+            asked: Arc::default(),
+        }
+    }
+}
+
+impl DnsResolver for Fixed {
+    fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        self.asked.lock().unwrap().push(host.to_string());
+        Ok(self.addrs.clone())
+    }
+}
+
+#[test]
+fn preferences_order_and_filter_address_families() {
+    let addrs = vec![V6, V4, V4_B];
+    assert_eq!(IpPreference::Any.apply(addrs.clone()), [V6, V4, V4_B]);
+    assert_eq!(IpPreference::PreferV4.apply(addrs.clone()), [V4, V4_B, V6]);
+    assert_eq!(
+        IpPreference::PreferV6.apply(vec![V4, V6, V4_B]),
+        [V6, V4, V4_B]
+    );
+    assert_eq!(IpPreference::V4Only.apply(addrs.clone()), [V4, V4_B]);
+    assert_eq!(IpPreference::V6Only.apply(addrs), [V6]);
+    assert_eq!(IpPreference::default(), IpPreference::Any);
+}
+
+#[test]
+fn custom_resolvers_pick_the_node_address() {
+    let port = serve_once(json!({ "HIVE_BLOCK_INTERVAL": 3 }));
+    let resolver = Fixed::new(&[LOCALHOST]);
+    let client = Client::builder()
+        .nodes([format!("http://node.internal:{port}")])
+        .build()
+        .dns_resolver(resolver.clone());
+    let config: Value = client.call("database_api.get_config", json!({})).unwrap();
+    assert_eq!(config["HIVE_BLOCK_INTERVAL"], 3);
+    assert_eq!(*resolver.asked.lock().unwrap(), ["node.internal"]);
+}
+
+#[test]
+fn restricted_families_without_an_address_fail() {
+    let resolver = Fixed::new(&[LOCALHOST]);
+    let client = Client::builder()
+        .nodes(["http://node.internal:1"])
+        .max_retries(0)
+        .build()
+        .dns_resolver(resolver.clone())
+        .ip_preference(IpPreference::V6Only);
+    assert!(client
+        .call::<_, Value>("database_api.get_config", json!({}))
+        .is_err());
+    assert_eq!(*resolver.asked.lock().unwrap(), ["node.internal"]);
+}
+
+#[test]
+fn overrides_replace_earlier_ones_for_the_host() {
+    let port = serve_once(json!({ "HIVE_BLOCK_INTERVAL": 3 }));
+    // Overrides win over the resolver, and a port of 0 keeps the URL's port
+    let resolver = Fixed::new(&[V4]);
+    let client = Client::builder()
+        .nodes([format!("http://node.internal:{port}")])
+        .build()
+        .dns_resolver(resolver.clone())
+        .dns_override("node.internal", &[(V4, 0).into()])
+        .dns_override("node.internal", &[(LOCALHOST, 0).into()]);
+    let config: Value = client.call("database_api.get_config", json!({})).unwrap();
+    assert_eq!(config["HIVE_BLOCK_INTERVAL"], 3);
+    assert!(resolver.asked.lock().unwrap().is_empty());
+}
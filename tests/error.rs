@@ -0,0 +1,44 @@
+use nectarflower_rs::{Client, NectarError};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+#[test]
+fn rpc_errors_keep_their_code_and_data() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let node = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 4096];
+        let _ = stream.read(&mut request).unwrap();
+        let body = r#"{"jsonrpc":"2.0","error":{"code":-32003,"message":"Assert Exception","data":{"name":"assert_exception"}},"id":1}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+    let mut client = Client::new();
+    client.set_nodes(vec![node], HashMap::new()).unwrap();
+
+    let err = client
+        .call::<_, Value>("database_api.get_config", json!({}))
+        .unwrap_err();
+    assert!(matches!(err, NectarError::NoNodesAvailable { .. }));
+    let [attempt] = err.attempts() else {
+        panic!("expected one attempt: {err}");
+    };
+    assert!(attempt.error.is_rpc() && !attempt.error.is_transport());
+    assert_eq!(
+        attempt.error,
+        NectarError::Rpc {
+            code: -32003,
+            message: "Assert Exception".to_string(),
+            data: Some(json!({ "name": "assert_exception" })),
+        }
+    );
+    assert!(String::from(err).contains("RPC error: Assert Exception (code: -32003)"));
+}
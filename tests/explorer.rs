@@ -0,0 +1,171 @@
+//! Explorer queries and what they resolve to
+
+use nectarflower_rs::explorer::{validate_account_name, Entity, Query};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+const TRX_ID: &str = "6fde0190a97835ea6d9e651293e90c89911f933c";
+
+fn content(author: &str) -> Value {
+    json!({
+        "author": author,
+        "permlink": if author.is_empty() { "" } else { "my-post" },
+        "parent_author": "",
+        "parent_permlink": "hive",
+        "title": "Title",
+        "body": "Body",
+        "json_metadata": "{}",
+    })
+}
+
+fn mock() -> MockTransport {
+    MockTransport::new()
+        .respond(
+            "block_api.get_block",
+            json!({ "block": {
+                "previous": "0000000900000000000000000000000000000000",
+                "timestamp": "2024-01-01T00:00:30",
+                "witness": "bob",
+                "transaction_merkle_root": "0000000000000000000000000000000000000000",
+                "witness_signature": "1f00",
+                "transactions": [],
+                "block_id": "0012d68700000000000000000000000000000000",
+                "signing_key": "",
+                "transaction_ids": [],
+            } }),
+        )
+        .respond(
+            "account_history_api.get_transaction",
+            json!({
+                "ref_block_num": 1,
+                "ref_block_prefix": 2,
+                "expiration": "2024-01-01T00:01:00",
+                "operations": [],
+                "extensions": [],
+                "signatures": [],
+                "transaction_id": TRX_ID,
+                "block_num": 1234567,
+                "transaction_num": 3,
+            }),
+        )
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{ "name": "alice", "json_metadata": "" }] }),
+        )
+        .respond("condenser_api.get_content", content("alice"))
+}
+
+#[test]
+fn queries_are_recognized_by_shape() {
+    let parse = |input: &str| Query::parse(input).unwrap();
+    assert_eq!(parse("1234567"), Query::BlockNum(1_234_567));
+    assert_eq!(parse(" #1,234,567 "), Query::BlockNum(1_234_567));
+    assert_eq!(parse("1_234_567"), Query::BlockNum(1_234_567));
+    assert_eq!(
+        parse(&TRX_ID.to_ascii_uppercase()),
+        Query::TransactionId(TRX_ID.to_string())
+    );
+    assert_eq!(parse("Alice"), Query::Account("alice".to_string()));
+    assert_eq!(parse("@bob.hive"), Query::Account("bob.hive".to_string()));
+    assert_eq!(
+        parse("https://peakd.com/@alice/wallet"),
+        Query::Account("alice".to_string())
+    );
+    assert_eq!(
+        parse("https://hive.blog/hive-174578/@alice/my-post"),
+        Query::Post {
+            author: "alice".to_string(),
+            permlink: "my-post".to_string()
+        }
+    );
+
+    assert_eq!(
+        Query::parse("99999999999").unwrap_err(),
+        "Block number out of range: 99999999999"
+    );
+    for input in ["", "#", "ab", "alice!", "-alice", "a.bob"] {
+        assert_eq!(
+            Query::parse(input).unwrap_err(),
+            format!("Unrecognized explorer query: '{input}'"),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn account_names_follow_the_chain_rules() {
+    for name in [
+        "abc",
+        "alice-1",
+        "bob.hive",
+        "a1b.c-d.efg",
+        "sixteen-chars-ok",
+    ] {
+        assert_eq!(validate_account_name(name), Ok(()), "{name}");
+    }
+    assert_eq!(
+        validate_account_name("ab").unwrap_err(),
+        "Account name must be 3 to 16 characters: 'ab'"
+    );
+    assert!(validate_account_name("seventeen-chars-x").is_err());
+    for name in ["1abc", "abc-", "ab.cde", "abc..de", "Alice", "ali_ce"] {
+        assert_eq!(
+            validate_account_name(name).unwrap_err(),
+            format!("Invalid account name: '{name}'"),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn lookups_resolve_each_kind_of_query() {
+    let mock = mock();
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let Entity::Block { block_num, block } = client.lookup("#1,234,567").unwrap() else {
+        panic!("expected a block");
+    };
+    assert_eq!(block_num, 1_234_567);
+    assert_eq!(block.header.witness, "bob");
+
+    let Entity::Transaction(trx) = client.lookup(TRX_ID).unwrap() else {
+        panic!("expected a transaction");
+    };
+    assert_eq!((trx.block_num, trx.transaction_num), (1_234_567, 3));
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(
+        request["params"],
+        json!({ "id": TRX_ID, "include_reversible": true })
+    );
+
+    let Entity::Account(account) = client.lookup("@alice").unwrap() else {
+        panic!("expected an account");
+    };
+    assert_eq!(account.name, "alice");
+
+    let Entity::Post(post) = client.lookup("@alice/my-post").unwrap() else {
+        panic!("expected a post");
+    };
+    assert_eq!(post.title, "Title");
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!(["alice", "my-post"]));
+}
+
+#[test]
+fn missing_entities_are_errors() {
+    let mock = MockTransport::new()
+        .respond("block_api.get_block", json!({}))
+        .respond("database_api.find_accounts", json!({ "accounts": [] }))
+        .respond("condenser_api.get_content", content(""));
+    let client = Client::builder().transport(mock).build();
+    assert_eq!(client.lookup("5").unwrap_err(), "Block 5 not found");
+    assert_eq!(
+        client.lookup("alice").unwrap_err(),
+        "Account 'alice' not found"
+    );
+    assert_eq!(
+        client.lookup("@alice/gone").unwrap_err(),
+        "Post @alice/gone not found"
+    );
+}
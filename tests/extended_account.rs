@@ -0,0 +1,97 @@
+use nectarflower_rs::extended_account::{CondenserAccount, DatabaseAccount, ExtendedAccount};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+fn condenser_alice() -> Value {
+    json!({
+        "id": 1370484,
+        "name": "alice",
+        "json_metadata": "",
+        "post_count": 1844,
+        "can_vote": true,
+        "voting_manabar": {"current_mana": 112783442381092i64, "last_update_time": 1704110400},
+        "balance": "1520.331 HIVE",
+        "hbd_balance": "48.210 HBD",
+        "vesting_shares": "112783442.381092 VESTS",
+        "reward_hive_balance": "0.000 HIVE",
+        "reputation": "123456789012",
+        "witness_votes": ["gtg", "blocktrades"],
+        "voting_power": 9800,
+    })
+}
+
+#[test]
+fn both_account_formats_convert_to_the_same_extended_account() {
+    let database: DatabaseAccount =
+        serde_json::from_str(include_str!("../fixtures/account.json")).unwrap();
+    let condenser: CondenserAccount = serde_json::from_value(condenser_alice()).unwrap();
+    let from_database = ExtendedAccount::from(database);
+    let from_condenser = ExtendedAccount::from(condenser);
+
+    for account in [&from_database, &from_condenser] {
+        assert_eq!(account.account.name, "alice");
+        assert_eq!(account.account.balance.to_string(), "1520.331 HIVE");
+        assert_eq!(account.stats.id, 1370484);
+        assert_eq!(account.stats.post_count, 1844);
+        assert_eq!(account.stats.voting_manabar.current_mana, 112783442381092);
+    }
+    assert_eq!(from_database.reputation, None);
+    assert_eq!(from_condenser.reputation, Some(123456789012));
+    assert_eq!(from_condenser.witness_votes.as_ref().map(Vec::len), Some(2));
+}
+
+#[test]
+fn nodes_without_database_api_are_asked_through_condenser_api() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let node = format!("http://{}", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let mut methods = Vec::new();
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut request = vec![0; length];
+            reader.read_exact(&mut request).unwrap();
+            let request: Value = serde_json::from_slice(&request).unwrap();
+            let method = request["method"].as_str().unwrap().to_string();
+            let body = if method == "database_api.find_accounts" {
+                json!({"jsonrpc": "2.0", "id": 1, "error": {
+                    "code": -32002, "message": "Assert Exception: Could not find API database_api"
+                }})
+            } else {
+                json!({"jsonrpc": "2.0", "id": 1, "result": [condenser_alice()]})
+            }
+            .to_string();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            methods.push(method);
+        }
+        methods
+    });
+
+    let client = Client::builder().nodes([node]).build();
+    let accounts = client.get_extended_accounts(&["alice"]).unwrap();
+    assert_eq!(accounts[0].account.name, "alice");
+    assert_eq!(accounts[0].reputation, Some(123456789012));
+    assert_eq!(
+        server.join().unwrap(),
+        ["database_api.find_accounts", "condenser_api.get_accounts"]
+    );
+}
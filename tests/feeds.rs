@@ -0,0 +1,140 @@
+//! Price feed staleness of the top witnesses
+
+use nectarflower_rs::feeds::{FeedEvent, FeedMonitor};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn witness(owner: &str, updated: &str, key: &str) -> Value {
+    json!({
+        "owner": owner,
+        "votes": "1000",
+        "total_missed": 0,
+        "signing_key": key,
+        "running_version": "1.27.5",
+        "hbd_exchange_rate": { "base": "0.250 HBD", "quote": "1.000 HIVE" },
+        "last_hbd_exchange_update": updated,
+    })
+}
+
+fn respond(mock: &MockTransport, updates: [&str; 3]) {
+    let key = "STM87F7tN56tAUL2C6J9Gzi9HzgNpZdi6M2cLQo7TjDU5v178QsYA";
+    mock.clone()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-03T00:00:00",
+                "current_witness": "alice",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(
+            "database_api.list_witnesses",
+            json!({ "witnesses": [
+                witness("alice", updates[0], key),
+                witness("bob", updates[1], key),
+                witness("carol", updates[2], key),
+                // Disabled witnesses publish no feed and are not counted
+                witness("dave", "2023-01-01T00:00:00", DISABLED_SIGNING_KEY),
+            ] }),
+        );
+}
+
+const FRESH: &str = "2024-01-02T12:00:00";
+const STALE: &str = "2024-01-01T00:00:00";
+
+fn stale(owner: &str) -> FeedEvent {
+    FeedEvent::Stale {
+        owner: owner.to_string(),
+        age_secs: 2 * 86_400,
+    }
+}
+
+#[test]
+fn feeds_older_than_the_limit_are_stale() {
+    let mock = MockTransport::new();
+    respond(&mock, [FRESH, FRESH, STALE]);
+    let client = Client::builder().transport(mock).build();
+
+    let health = client.feed_health(20, 86_400).unwrap();
+    assert_eq!(health.feeds.len(), 3);
+    assert!(health.get("dave").is_none());
+    let bob = health.get("bob").unwrap();
+    assert_eq!((bob.rank, bob.age_secs, bob.stale), (2, 43_200, false));
+    assert_eq!(bob.price, 0.25);
+    let stale: Vec<_> = health.stale().map(|f| f.owner.as_str()).collect();
+    assert_eq!(stale, ["carol"]);
+    assert!(health.median_is_healthy());
+
+    // A longer limit accepts the same feed
+    assert_eq!(client.feed_health(20, 3 * 86_400).unwrap().fresh_count(), 3);
+}
+
+#[test]
+fn monitors_report_what_changed_between_checks() {
+    let mock = MockTransport::new();
+    respond(&mock, [FRESH, FRESH, STALE]);
+    let client = Client::builder().transport(mock.clone()).build();
+    let mut monitor = FeedMonitor::new().top(4).max_age_secs(86_400);
+    assert!(monitor.last().is_none());
+
+    assert_eq!(monitor.check(&client).unwrap(), [stale("carol")]);
+    // Nothing changed
+    assert!(monitor.check(&client).unwrap().is_empty());
+
+    respond(&mock, [FRESH, STALE, STALE]);
+    assert_eq!(
+        monitor.check(&client).unwrap(),
+        [
+            stale("bob"),
+            FeedEvent::MedianDegraded { fresh: 1, total: 3 }
+        ]
+    );
+
+    respond(&mock, [FRESH, FRESH, FRESH]);
+    assert_eq!(
+        monitor.check(&client).unwrap(),
+        [
+            FeedEvent::Refreshed {
+                owner: "bob".to_string()
+            },
+            FeedEvent::Refreshed {
+                owner: "carol".to_string()
+            },
+            FeedEvent::MedianRecovered { fresh: 3, total: 3 },
+        ]
+    );
+    assert_eq!(monitor.last().unwrap().fresh_count(), 3);
+}
+
+#[test]
+fn first_checks_report_a_degraded_median() {
+    let mock = MockTransport::new();
+    respond(&mock, [STALE, STALE, FRESH]);
+    let client = Client::builder().transport(mock).build();
+
+    let mut events = Vec::new();
+    FeedMonitor::new()
+        .max_age_secs(86_400)
+        .interval(Duration::from_secs(600))
+        .watch(&client, |event| {
+            events.push(event);
+            events.len() < 3
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        [
+            stale("alice"),
+            stale("bob"),
+            FeedEvent::MedianDegraded { fresh: 1, total: 3 }
+        ]
+    );
+}
@@ -0,0 +1,230 @@
+#![cfg(feature = "ffi")]
+
+use nectarflower_rs::ffi::*;
+use serde_json::{json, Value};
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::ptr;
+
+const WIF: &str = "5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL";
+const ZERO_CHAIN_ID: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+/// Take ownership of a returned string, panicking with the last error on NULL
+fn take(s: *mut c_char) -> String {
+    assert!(!s.is_null(), "{}", last_error());
+    let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+    unsafe { nf_string_free(s) };
+    owned
+}
+
+fn last_error() -> String {
+    let error = nf_last_error();
+    assert!(!error.is_null());
+    unsafe { CStr::from_ptr(error) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn vote() -> CString {
+    c(&json!({
+        "ref_block_num": 1234,
+        "ref_block_prefix": 1122334455,
+        "expiration": "2017-07-15T16:51:19",
+        "operations": [{
+            "type": "vote_operation",
+            "value": {"voter": "foo", "author": "bar", "permlink": "baz", "weight": 10000},
+        }],
+        "extensions": [],
+        "signatures": [],
+    })
+    .to_string())
+}
+
+#[test]
+fn header_declares_every_export() {
+    let header = include_str!("../include/nectarflower.h");
+    let source = include_str!("../src/ffi.rs");
+    let exports: Vec<&str> = source
+        .split("extern \"C\" fn ")
+        .skip(1)
+        .map(|rest| rest.split('(').next().unwrap())
+        .collect();
+    assert!(exports.len() > 10);
+    for name in exports {
+        assert!(header.contains(&format!("{name}(")), "{name} is missing");
+    }
+    assert!(header.contains(&format!("#define NF_API_VERSION {NF_API_VERSION}")));
+    assert!(header.contains(&format!(
+        "#define NF_SIGNATURE_HEX_LEN {NF_SIGNATURE_HEX_LEN}"
+    )));
+}
+
+#[test]
+fn transactions_are_hashed_locally() {
+    let tx = vote();
+    let id = take(unsafe { nf_transaction_id(tx.as_ptr()) });
+    assert_eq!(id.len(), 40);
+    let digest = take(unsafe { nf_transaction_digest(tx.as_ptr(), c(ZERO_CHAIN_ID).as_ptr()) });
+    assert_eq!(digest.len(), 64);
+
+    assert!(unsafe { nf_transaction_id(c("{").as_ptr()) }.is_null());
+    assert!(last_error().contains("Invalid transaction JSON"));
+    assert!(unsafe { nf_transaction_id(ptr::null()) }.is_null());
+    assert_eq!(last_error(), "Argument tx_json is NULL");
+}
+
+#[test]
+fn keys_sign_offline() {
+    let wif = c(WIF);
+    let public = take(unsafe { nf_public_key(wif.as_ptr(), c("STM").as_ptr()) });
+    assert_eq!(
+        public,
+        "STM87F7tN56tAUL2C6J9Gzi9HzgNpZdi6M2cLQo7TjDU5v178QsYA"
+    );
+
+    let tx = vote();
+    let signed = take(unsafe {
+        nf_sign_transaction_with_key(
+            ptr::null(),
+            tx.as_ptr(),
+            c(ZERO_CHAIN_ID).as_ptr(),
+            wif.as_ptr(),
+        )
+    });
+    let signed: Value = serde_json::from_str(&signed).unwrap();
+    assert_eq!(signed["signatures"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        signed["signatures"][0].as_str().unwrap().len(),
+        NF_SIGNATURE_HEX_LEN
+    );
+
+    let bad = unsafe {
+        nf_sign_transaction_with_key(
+            ptr::null(),
+            tx.as_ptr(),
+            c(ZERO_CHAIN_ID).as_ptr(),
+            c("5Kbad").as_ptr(),
+        )
+    };
+    assert!(bad.is_null());
+    assert!(!last_error().contains("5Kbad"));
+
+    let no_chain = unsafe {
+        nf_sign_transaction_with_key(ptr::null(), tx.as_ptr(), ptr::null(), wif.as_ptr())
+    };
+    assert!(no_chain.is_null());
+    assert_eq!(last_error(), "Argument chain_id is NULL");
+}
+
+extern "C" fn fixed_signature(
+    digest: *const u8,
+    out: *mut c_char,
+    out_len: usize,
+    user_data: *mut c_void,
+) -> i32 {
+    assert_eq!(out_len, NF_SIGNATURE_HEX_LEN + 1);
+    let seen = unsafe { &mut *(user_data as *mut Vec<u8>) };
+    seen.extend_from_slice(unsafe { std::slice::from_raw_parts(digest, 32) });
+    let signature = c(&"1f".repeat(65));
+    unsafe { ptr::copy_nonoverlapping(signature.as_ptr(), out, NF_SIGNATURE_HEX_LEN + 1) };
+    0
+}
+
+extern "C" fn failing_signature(_: *const u8, _: *mut c_char, _: usize, _: *mut c_void) -> i32 {
+    7
+}
+
+#[test]
+fn callbacks_sign_the_local_digest() {
+    let tx = vote();
+    let chain_id = c(ZERO_CHAIN_ID);
+    let mut seen: Vec<u8> = Vec::new();
+    let signed = take(unsafe {
+        nf_sign_transaction(
+            ptr::null(),
+            tx.as_ptr(),
+            chain_id.as_ptr(),
+            fixed_signature,
+            &mut seen as *mut Vec<u8> as *mut c_void,
+        )
+    });
+    let signed: Value = serde_json::from_str(&signed).unwrap();
+    assert_eq!(signed["signatures"][0], "1f".repeat(65));
+    let digest = take(unsafe { nf_transaction_digest(tx.as_ptr(), chain_id.as_ptr()) });
+    assert_eq!(nectarflower_rs::crypto::to_hex(&seen), digest);
+
+    let failed = unsafe {
+        nf_sign_transaction(
+            ptr::null(),
+            tx.as_ptr(),
+            chain_id.as_ptr(),
+            failing_signature,
+            ptr::null_mut(),
+        )
+    };
+    assert!(failed.is_null());
+    assert_eq!(last_error(), "Signing callback failed with code 7");
+}
+
+#[test]
+fn unsupported_operations_are_never_signed_by_the_node() {
+    let client = nf_client_new();
+    let nodes = c(r#"["http://127.0.0.1:1"]"#);
+    assert_eq!(unsafe { nf_client_set_nodes(client, nodes.as_ptr()) }, 0);
+    let tx = c(&vote()
+        .to_str()
+        .unwrap()
+        .replace("vote_operation", "pow_operation"));
+    let signed =
+        unsafe { nf_sign_transaction_with_key(client, tx.as_ptr(), ptr::null(), c(WIF).as_ptr()) };
+    assert!(signed.is_null());
+    assert!(last_error().contains("pow"), "{}", last_error());
+    unsafe { nf_client_free(client) };
+}
+
+#[test]
+fn client_errors_name_the_bad_argument() {
+    let client = nf_client_new();
+    assert!(!client.is_null());
+
+    assert_eq!(unsafe { nf_client_set_nodes(client, ptr::null()) }, -1);
+    assert_eq!(last_error(), "Argument nodes_json is NULL");
+    assert_eq!(
+        unsafe { nf_client_set_nodes(client, c("[1]").as_ptr()) },
+        -1
+    );
+    assert!(last_error().starts_with("Invalid nodes JSON"));
+    assert_eq!(
+        unsafe { nf_client_set_nodes(ptr::null_mut(), c("[]").as_ptr()) },
+        -1
+    );
+    assert_eq!(last_error(), "Argument client is NULL");
+
+    let nodes = c(r#"["http://127.0.0.1:1"]"#);
+    assert_eq!(unsafe { nf_client_set_nodes(client, nodes.as_ptr()) }, 0);
+    let result = unsafe {
+        nf_call(
+            client,
+            c("condenser_api.get_config").as_ptr(),
+            c("[]").as_ptr(),
+        )
+    };
+    assert!(result.is_null());
+    assert!(!last_error().is_empty());
+
+    let refused = unsafe {
+        nf_call(
+            client,
+            c("condenser_api.broadcast_transaction").as_ptr(),
+            c("[]").as_ptr(),
+        )
+    };
+    assert!(refused.is_null());
+    assert!(last_error().contains("broadcast"));
+
+    unsafe { nf_client_free(client) };
+    assert_eq!(nf_api_version(), NF_API_VERSION);
+}
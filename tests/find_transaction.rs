@@ -0,0 +1,130 @@
+//! Locating the block and position of a transaction
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, TransactionLocation};
+use serde_json::{json, Value};
+
+const TRX_ID: &str = "a1b2c3d4e5f60718293a4b5c6d7e8f9011223344";
+
+fn block(transaction_ids: &[&str]) -> Value {
+    json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": "bob",
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+        "witness_signature": "1f00",
+        "transactions": [],
+        "block_id": "0000000a00000000000000000000000000000000",
+        "signing_key": "STM1111111111111111111111111111111114T1Anm",
+        "transaction_ids": transaction_ids,
+    })
+}
+
+fn included() -> Value {
+    json!({
+        "ref_block_num": 1,
+        "ref_block_prefix": 2,
+        "expiration": "2024-01-01T00:01:00",
+        "operations": [],
+        "extensions": [],
+        "signatures": [],
+        "transaction_id": TRX_ID,
+        "block_num": 7,
+        "transaction_num": 4,
+    })
+}
+
+fn status(status: &str) -> MockTransport {
+    MockTransport::new().respond(
+        "transaction_status_api.find_transaction",
+        json!({ "status": status, "block_num": 10 }),
+    )
+}
+
+fn methods(mock: &MockTransport) -> Vec<String> {
+    mock.requests()
+        .iter()
+        .map(|(_, request)| request["method"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn location(block_num: u32, trx_in_block: u32) -> TransactionLocation {
+    TransactionLocation {
+        trx_id: TRX_ID.to_string(),
+        block_num,
+        trx_in_block,
+    }
+}
+
+#[test]
+fn recent_transactions_are_found_in_their_block() {
+    let mock = status("within_reversible_block").respond(
+        "block_api.get_block",
+        json!({ "block": block(&["00", TRX_ID]) }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    assert_eq!(client.find_transaction_block(TRX_ID), Ok(location(10, 1)));
+    assert_eq!(
+        methods(&mock),
+        [
+            "transaction_status_api.find_transaction",
+            "block_api.get_block"
+        ]
+    );
+    let (_, request) = &mock.requests()[1];
+    assert_eq!(request["params"], json!({ "block_num": 10 }));
+}
+
+#[test]
+fn other_cases_fall_back_to_account_history() {
+    // Not in the reported block's ids
+    let mock = status("within_irreversible_block")
+        .respond("block_api.get_block", json!({ "block": block(&["00"]) }))
+        .respond("account_history_api.get_transaction", included());
+    let client = Client::builder().transport(mock.clone()).build();
+    assert_eq!(client.find_transaction_block(TRX_ID), Ok(location(7, 4)));
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(
+        request["params"],
+        json!({ "id": TRX_ID, "include_reversible": true })
+    );
+
+    // Too old for the status API, or the API is not enabled
+    for mock in [status("too_old"), MockTransport::new()] {
+        let mock = mock.respond("account_history_api.get_transaction", included());
+        let client = Client::builder().transport(mock.clone()).build();
+        assert_eq!(client.find_transaction_block(TRX_ID), Ok(location(7, 4)));
+        assert!(!methods(&mock).contains(&"block_api.get_block".to_string()));
+    }
+}
+
+#[test]
+fn pending_and_expired_transactions_have_no_block() {
+    let client = Client::builder()
+        .transport(status("within_mempool"))
+        .build();
+    assert_eq!(
+        client.find_transaction_block(TRX_ID).unwrap_err(),
+        format!("Transaction {TRX_ID} is not in a block yet")
+    );
+
+    for expired in ["expired_reversible", "expired_irreversible"] {
+        let client = Client::builder().transport(status(expired)).build();
+        assert_eq!(
+            client.find_transaction_block(TRX_ID).unwrap_err(),
+            format!("Transaction {TRX_ID} expired without being included")
+        );
+    }
+
+    let mock = status("too_old").respond_error(
+        "account_history_api.get_transaction",
+        -32000,
+        "Unknown Transaction",
+    );
+    let client = Client::builder().transport(mock).build();
+    let err = client.find_transaction_block(TRX_ID).unwrap_err();
+    assert!(
+        err.starts_with(&format!("Error fetching transaction {TRX_ID}:")),
+        "{err}"
+    );
+}
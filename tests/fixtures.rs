@@ -0,0 +1,104 @@
+#![cfg(feature = "fixtures")]
+//! Fixture samples decode, and keep decoding under schema variations
+
+use nectarflower_rs::block::{Block, DynamicGlobalProperties};
+use nectarflower_rs::fixtures::{self, failing_variants, variants, Variation};
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::Account;
+use serde_json::{json, Value};
+
+fn parse(json: &str) -> Value {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn every_fixture_is_in_the_corpus() {
+    let names: Vec<_> = fixtures::corpus()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "block",
+            "account",
+            "dynamic_global_properties",
+            "operations",
+            "node_metadata"
+        ]
+    );
+    serde_json::from_str::<Block>(fixtures::BLOCK).unwrap();
+    serde_json::from_str::<Account>(fixtures::ACCOUNT).unwrap();
+    serde_json::from_str::<Vec<Operation>>(fixtures::OPERATIONS).unwrap();
+}
+
+#[test]
+fn variants_change_one_value_each() {
+    let sample = json!({ "a/b": 1, "n": "42", "list": [{ "x": "" }] });
+    let found: Vec<_> = variants(&sample)
+        .into_iter()
+        .map(|v| (v.variation, v.value))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                Variation::MissingField("/a~1b".to_string()),
+                json!({ "n": "42", "list": [{ "x": "" }] })
+            ),
+            (
+                Variation::NumberAsString("/a~1b".to_string()),
+                json!({ "a/b": "1", "n": "42", "list": [{ "x": "" }] })
+            ),
+            (
+                Variation::MissingField("/list".to_string()),
+                json!({ "a/b": 1, "n": "42" })
+            ),
+            (
+                Variation::MissingField("/list/0/x".to_string()),
+                json!({ "a/b": 1, "n": "42", "list": [{}] })
+            ),
+            (
+                Variation::MissingField("/n".to_string()),
+                json!({ "a/b": 1, "list": [{ "x": "" }] })
+            ),
+            (
+                Variation::StringAsNumber("/n".to_string()),
+                json!({ "a/b": 1, "n": 42, "list": [{ "x": "" }] })
+            ),
+        ]
+    );
+}
+
+#[test]
+fn integers_beyond_u64_stay_strings() {
+    let sample = json!({ "n": u128::MAX.to_string() });
+    let found: Vec<_> = variants(&sample).into_iter().map(|v| v.variation).collect();
+    if cfg!(feature = "arbitrary_precision") {
+        assert_eq!(found.len(), 2);
+    } else {
+        assert_eq!(found, [Variation::MissingField("/n".to_string())]);
+    }
+}
+
+#[test]
+fn global_properties_survive_schema_variations() {
+    let required = [
+        "/head_block_number",
+        "/head_block_id",
+        "/time",
+        "/current_witness",
+        "/current_aslot",
+        "/last_irreversible_block_num",
+        "/total_vesting_fund_hive",
+        "/total_vesting_shares",
+    ];
+    let sample = parse(fixtures::DYNAMIC_GLOBAL_PROPERTIES);
+    let failing = failing_variants::<DynamicGlobalProperties>(&sample, &required);
+    assert!(failing.is_empty(), "{failing:?}");
+
+    // Required fields do fail when missing
+    let mut missing = sample.clone();
+    missing.as_object_mut().unwrap().remove("head_block_number");
+    assert!(serde_json::from_value::<DynamicGlobalProperties>(missing).is_err());
+}
@@ -0,0 +1,84 @@
+//! Reports of governance votes about to expire
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn account(name: &str, expires: &str) -> Value {
+    json!({ "name": name, "json_metadata": "", "governance_vote_expiration_ts": expires })
+}
+
+fn client(method: &str) -> (Client, MockTransport) {
+    let accounts = json!({ "accounts": [
+        account("alice", "2024-01-11T00:00:00"),
+        account("bob", "2024-01-03T12:00:00"),
+        // Already expired
+        account("carol", "2023-12-31T23:59:59"),
+        // Accounts that never voted
+        account("dave", "2106-02-07T06:28:15"),
+        { "name": "erin", "json_metadata": "" },
+        account("frank", "2024-01-31T00:00:00"),
+    ] });
+    let mock = MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-01T00:00:00",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(method, accounts);
+    (Client::builder().transport(mock.clone()).build(), mock)
+}
+
+#[test]
+fn votes_expiring_within_the_window_are_reported_soonest_first() {
+    let (client, _) = client("database_api.list_accounts");
+    let report = client.governance_expiry_report(None, 10).unwrap();
+    assert_eq!(report.generated_at, "2024-01-01T00:00:00");
+    let expiring: Vec<_> = report
+        .expiring
+        .iter()
+        .map(|e| (e.account.as_str(), e.days_left))
+        .collect();
+    assert_eq!(expiring, [("bob", 2), ("alice", 10)]);
+
+    let report = client.governance_expiry_report(None, 1).unwrap();
+    assert!(report.is_empty());
+}
+
+#[test]
+fn named_accounts_are_looked_up_directly() {
+    let (client, mock) = client("database_api.find_accounts");
+    let report = client
+        .governance_expiry_report(Some(&["alice", "bob"]), 30)
+        .unwrap();
+    assert_eq!(report.expiring.len(), 3);
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "accounts": ["alice", "bob"] }));
+}
+
+#[test]
+fn reports_become_post_drafts() {
+    let (client, _) = client("database_api.list_accounts");
+    let report = client.governance_expiry_report(None, 10).unwrap();
+    assert_eq!(
+        report.to_markdown(),
+        "The witness and proposal votes of these accounts expire within 10 days. \
+         Vote for a witness or proposal to renew them.\n\n\
+         | Account | Expires (UTC) | Days left |\n|---|---|---|\n\
+         | @bob | 2024-01-03 12:00:00 | 2 |\n\
+         | @alice | 2024-01-11 00:00:00 | 10 |\n"
+    );
+    let draft = report.to_draft("reminders", "Expiring votes", &["hive", "governance"]);
+    assert_eq!(draft.author, "reminders");
+    assert_eq!(draft.title, "Expiring votes");
+    assert_eq!(draft.body, report.to_markdown());
+    assert_eq!(draft.tags, ["hive", "governance"]);
+}
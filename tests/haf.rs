@@ -0,0 +1,163 @@
+//! Blocks and account history read from a HAF database
+#![cfg(feature = "haf")]
+
+use nectarflower_rs::block_source::BlockSource;
+use nectarflower_rs::haf::{HafConnection, HafSource};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn block(witness: &str) -> Value {
+    json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": witness,
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+        "extensions": [],
+        "witness_signature": "1f00",
+        "block_id": "0000000a00000000000000000000000000000000",
+        "signing_key": "STM1111111111111111111111111111111114T1Anm",
+        "transaction_ids": [],
+        "transactions": [],
+    })
+}
+
+/// An in-memory HAF database answering this module's queries
+#[derive(Default)]
+struct Haf {
+    irreversible: u32,
+    blocks: HashMap<u32, Value>,
+    history: Vec<Value>,
+    queries: Vec<Vec<Value>>,
+}
+
+impl HafConnection for Haf {
+    fn query_json(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Value>, String> {
+        self.queries.push(params.to_vec());
+        if sql.contains("hive.irreversible_data_view") {
+            Ok(vec![self.irreversible.into()])
+        } else if sql.contains("FROM hive.blocks_view") {
+            let num = params[0].as_u64().unwrap() as u32;
+            Ok(self.blocks.get(&num).cloned().into_iter().collect())
+        } else if sql.contains("hive.account_operations_view") {
+            Ok(self.history.clone())
+        } else {
+            Err(format!("unexpected query: {sql}"))
+        }
+    }
+}
+
+fn haf(irreversible: u32) -> Haf {
+    Haf {
+        irreversible,
+        blocks: (10..=irreversible).map(|num| (num, block("haf"))).collect(),
+        ..Haf::default()
+    }
+}
+
+#[test]
+fn blocks_replay_up_to_the_irreversible_head() {
+    let mut source = HafSource::new(haf(12), 10);
+    assert_eq!(source.haf_irreversible_block_num(), Ok(12));
+    let mut nums = Vec::new();
+    while let Some(block) = source.next_block() {
+        let block = block.unwrap();
+        assert_eq!(block.header.witness, "haf");
+        assert!(block.block.is_some());
+        nums.push(block.block_num);
+    }
+    assert_eq!(nums, [10, 11, 12]);
+    assert_eq!(source.next_block_num(), 13);
+    assert_eq!(source.last_irreversible_block_num(), Ok(12));
+}
+
+#[test]
+fn streams_continue_over_rpc_after_haf() {
+    let props = json!({
+        "head_block_number": 20,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": 20,
+        "last_irreversible_block_num": 15,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    });
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props)
+        .respond("block_api.get_block", json!({ "block": block("rpc") }));
+    let client = Client::builder().transport(mock.clone()).build();
+    let mut source = HafSource::new(haf(11), 10).with_live(&client);
+
+    let witnesses: Vec<_> = (0..4)
+        .map(|_| {
+            let block = source.next_block().unwrap().unwrap();
+            (block.block_num, block.header.witness)
+        })
+        .collect();
+    assert_eq!(
+        witnesses,
+        [
+            (10, "haf".to_string()),
+            (11, "haf".to_string()),
+            (12, "rpc".to_string()),
+            (13, "rpc".to_string()),
+        ]
+    );
+    assert_eq!(source.next_block_num(), 14);
+    // Once live, the node is asked for the irreversible block instead
+    assert_eq!(source.last_irreversible_block_num(), Ok(15));
+}
+
+#[test]
+fn missing_and_malformed_blocks() {
+    let mut conn = haf(12);
+    conn.blocks.remove(&11);
+    conn.blocks.insert(12, json!({ "witness": "haf" }));
+    let mut source = HafSource::new(conn, 10);
+    assert!(source.get_block(11).unwrap().is_none());
+    let err = source.get_block(12).unwrap_err();
+    assert!(err.starts_with("Error decoding HAF block 12:"), "{err}");
+
+    // A block HAF lacks ends the replay without a live client
+    assert_eq!(source.next_block().unwrap().unwrap().block_num, 10);
+    assert!(source.next_block().is_none());
+
+    let mut source = HafSource::new(Haf::default(), 1);
+    source.connection().irreversible = 0;
+    assert!(source.next_block().is_none());
+}
+
+#[test]
+fn account_history_is_read_by_sequence_number() {
+    let mut conn = haf(10);
+    conn.history = vec![json!({
+        "index": 7,
+        "trx_id": "0000000000000000000000000000000000000000",
+        "block": 10,
+        "trx_in_block": 0,
+        "op_in_trx": 1,
+        "virtual_op": true,
+        "timestamp": "2024-01-01T00:00:30",
+        "op": { "type": "producer_reward_operation", "value": { "producer": "alice" } },
+    })];
+    let mut source = HafSource::new(conn, 10);
+
+    let entries = source.account_history("alice", 7, 100).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].index, 7);
+    assert!(entries[0].virtual_op);
+    assert_eq!(entries[0].op.op_type, "producer_reward_operation");
+    assert_eq!(
+        source.connection().queries.pop().unwrap(),
+        [json!("alice"), json!(7), json!(100)]
+    );
+
+    source.connection().history = vec![json!({ "index": 8 })];
+    let err = source.account_history("alice", 8, 1).unwrap_err();
+    assert!(
+        err.starts_with("Error decoding HAF history of alice:"),
+        "{err}"
+    );
+}
@@ -0,0 +1,107 @@
+//! Streams at the head wait on a notifier and fall back to polling
+
+use nectarflower_rs::head_notify::HeadNotifier;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn props(head: u32) -> serde_json::Value {
+    json!({
+        "head_block_number": head,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": head,
+        "last_irreversible_block_num": head,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+fn mock() -> MockTransport {
+    MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(10))
+        .respond(
+            "block_api.get_block_header",
+            json!({ "header": {
+                "previous": "00",
+                "timestamp": "2024-01-01T00:00:00",
+                "witness": "bob",
+                "transaction_merkle_root": "00",
+            } }),
+        )
+}
+
+/// Announces block 11 at once, as a push source would when it is produced
+struct Pushed;
+
+impl HeadNotifier for Pushed {
+    fn wait_for_block(
+        &self,
+        _client: &Client,
+        block_num: u32,
+        _timeout: Duration,
+    ) -> Result<Option<u32>, String> {
+        assert_eq!(block_num, 11);
+        Ok(Some(11))
+    }
+}
+
+/// Fails, while the chain moves on to block 11
+struct Broken {
+    mock: MockTransport,
+}
+
+impl HeadNotifier for Broken {
+    fn wait_for_block(&self, _: &Client, _: u32, _: Duration) -> Result<Option<u32>, String> {
+        self.mock
+            .clone()
+            .respond("database_api.get_dynamic_global_properties", props(11));
+        Err("connection lost".to_string())
+    }
+}
+
+#[test]
+fn streams_at_the_head_wait_on_the_notifier() {
+    let client = Client::builder()
+        .transport(mock())
+        .build()
+        .subscribe_head(Pushed);
+    assert!(client.head_subscribed());
+
+    // Without the notifier the stream would sleep for a minute
+    let started = Instant::now();
+    let mut stream = client
+        .stream_blocks(11)
+        .header_only()
+        .poll_interval(Duration::from_secs(60));
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 11);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(client.head_subscribed());
+}
+
+#[test]
+fn failing_notifiers_fall_back_to_polling() {
+    let mock = mock();
+    let client = Client::builder()
+        .transport(mock.clone())
+        .build()
+        .subscribe_head(Broken { mock });
+
+    let mut stream = client
+        .stream_blocks(11)
+        .header_only()
+        .poll_interval(Duration::from_millis(10));
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 11);
+    // Polling continues until the retry period is over
+    assert!(!client.head_subscribed());
+}
+
+#[test]
+fn clients_without_a_notifier_poll() {
+    let client = Client::builder().transport(mock()).build();
+    assert!(!client.head_subscribed());
+    let mut stream = client.stream_blocks(10).header_only();
+    assert_eq!(stream.next().unwrap().unwrap().block_num, 10);
+}
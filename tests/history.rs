@@ -0,0 +1,99 @@
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+
+fn entry(index: u64) -> serde_json::Value {
+    json!([index, {
+        "trx_id": "00",
+        "block": 100 + index,
+        "trx_in_block": 0,
+        "op_in_trx": 0,
+        "virtual_op": false,
+        "timestamp": "2024-01-01T00:00:00",
+        "op": { "type": "vote_operation", "value": {} },
+    }])
+}
+
+#[test]
+fn last_newest_first_page_asks_for_no_more_than_remain() {
+    let mock = MockTransport::new().respond(
+        "account_history_api.get_account_history",
+        json!({ "history": [entry(1), entry(2)] }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let entries: Vec<_> = client
+        .account_history("alice")
+        .page_size(3)
+        .take(3)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(entries[0].index, 2);
+
+    let limits: Vec<_> = mock
+        .requests()
+        .iter()
+        .map(|(_, request)| {
+            (
+                request["params"]["start"].clone(),
+                request["params"]["limit"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(limits, [(json!(-1), json!(3)), (json!(0), json!(1))]);
+}
+
+#[test]
+fn dumps_write_one_json_line_per_entry_oldest_first() {
+    let mock = MockTransport::new().respond(
+        "account_history_api.get_account_history",
+        json!({ "history": [entry(2), entry(0), entry(1)] }),
+    );
+    let client = Client::builder().transport(mock).build();
+
+    let mut out = Vec::new();
+    assert_eq!(client.dump_account_history("alice", &mut out).unwrap(), 3);
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let blocks: Vec<_> = lines.iter().map(|line| line["block"].clone()).collect();
+    assert_eq!(blocks, [json!(100), json!(101), json!(102)]);
+    assert_eq!(lines[1]["index"], 1);
+    assert_eq!(lines[1]["op"]["type"], "vote_operation");
+}
+
+/// Refuses every write, as a full disk would
+struct Full;
+
+impl std::io::Write for Full {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn dump_errors_are_reported() {
+    let mock = MockTransport::new().respond(
+        "account_history_api.get_account_history",
+        json!({ "history": [entry(0)] }),
+    );
+    let client = Client::builder().transport(mock).build();
+    let err = client.dump_account_history("alice", Full).unwrap_err();
+    assert_eq!(err, "Error writing history: disk full");
+
+    let mock = MockTransport::new().respond_error(
+        "account_history_api.get_account_history",
+        -32000,
+        "down",
+    );
+    let client = Client::builder().transport(mock).build();
+    let mut out = Vec::new();
+    assert!(client.dump_account_history("alice", &mut out).is_err());
+    assert!(out.is_empty());
+}
@@ -0,0 +1,57 @@
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::{HistoryEntry, HistoryMerger, HistoryOrder};
+use serde_json::json;
+
+fn entry(index: u64, block: u32, trx_in_block: u32, from: &str, to: &str) -> HistoryEntry {
+    HistoryEntry {
+        index,
+        trx_id: format!("{block:08x}{trx_in_block:032x}"),
+        block,
+        trx_in_block,
+        op_in_trx: 0,
+        virtual_op: false,
+        timestamp: "2024-01-01T00:00:00".to_string(),
+        op: Operation::new(
+            "transfer_operation",
+            json!({"from": from, "to": to, "amount": "1.000 HIVE", "memo": ""}),
+        ),
+    }
+}
+
+#[test]
+fn merges_chronologically_and_dedupes_shared_operations() {
+    let alice = vec![
+        entry(0, 10, 0, "alice", "carol"),
+        entry(1, 20, 1, "alice", "bob"),
+        entry(2, 40, 0, "dave", "alice"),
+    ];
+    let bob = vec![
+        entry(0, 15, 2, "erin", "bob"),
+        entry(1, 20, 1, "alice", "bob"),
+        entry(2, 30, 0, "bob", "frank"),
+    ];
+    let merged: Vec<_> = HistoryMerger::new(HistoryOrder::OldestFirst)
+        .add("alice", alice.into_iter().map(Ok))
+        .add("bob", bob.into_iter().map(Ok))
+        .collect::<Result<_, String>>()
+        .unwrap();
+
+    let blocks: Vec<u32> = merged.iter().map(|m| m.entry.block).collect();
+    assert_eq!(blocks, [10, 15, 20, 30, 40]);
+    assert_eq!(merged[2].accounts, ["alice", "bob"]);
+    assert_eq!(merged[3].accounts, ["bob"]);
+}
+
+#[test]
+fn newest_first_and_errors_name_the_account() {
+    let alice = vec![Ok(entry(1, 20, 0, "alice", "bob")), Err("boom".to_string())];
+    let bob = vec![Ok(entry(0, 25, 0, "bob", "carol"))];
+    let mut merged = HistoryMerger::new(HistoryOrder::NewestFirst)
+        .add("alice", alice.into_iter())
+        .add("bob", bob.into_iter());
+
+    assert_eq!(merged.next().unwrap().unwrap().entry.block, 25);
+    assert_eq!(merged.next().unwrap().unwrap().entry.block, 20);
+    assert_eq!(merged.next().unwrap().unwrap_err(), "alice: boom");
+    assert!(merged.next().is_none());
+}
@@ -0,0 +1,11 @@
+use nectarflower_rs::Client;
+
+#[test]
+fn http2_prior_knowledge_matches_nodes_by_origin() {
+    let client = Client::new().http2_prior_knowledge(&["https://api.deathwing.me/", "not a url"]);
+    assert!(client.uses_http2("https://api.deathwing.me"));
+    assert!(client.uses_http2("https://api.deathwing.me:443/rpc"));
+    assert!(!client.uses_http2("http://api.deathwing.me"));
+    assert!(!client.uses_http2("https://api.hive.blog"));
+    assert!(!Client::new().uses_http2("https://api.deathwing.me"));
+}
@@ -0,0 +1,55 @@
+use nectarflower_rs::i18n::{English, Message, Templates, Translator};
+use nectarflower_rs::{NectarError, Operation};
+use serde_json::json;
+
+struct Shouting;
+
+impl Translator for Shouting {
+    fn translate(&self, message: &Message) -> Option<String> {
+        English.translate(message).map(|text| text.to_uppercase())
+    }
+}
+
+#[test]
+fn summaries_render_through_translator_with_english_fallback() {
+    let german = Templates::new().template(
+        "op.vote.up",
+        "{voter} hat {post} positiv bewertet ({weight})",
+    );
+    let vote = Operation::new(
+        "vote_operation",
+        json!({"voter": "alice", "author": "bob", "permlink": "hello", "weight": 10000}),
+    );
+    assert_eq!(
+        vote.summary_in(&german),
+        "@alice hat @bob/hello positiv bewertet (100.00%)"
+    );
+
+    let transfer = Operation::new(
+        "transfer_operation",
+        json!({"from": "alice", "to": "bob", "amount": "1.000 HBD", "memo": "#secret"}),
+    );
+    assert_eq!(
+        transfer.summary_in(&german),
+        "@alice transferred 1.000 HBD to @bob — memo: (encrypted)"
+    );
+    assert_eq!(
+        transfer.summary_messages(true)[0].get("amount"),
+        Some("1.000 HBD")
+    );
+    assert_eq!(
+        transfer.summary_in(&Shouting),
+        transfer.summary().to_uppercase()
+    );
+}
+
+#[test]
+fn errors_render_through_translator() {
+    let error = NectarError::AccountNotFound("alice".to_string());
+    let german = Templates::new().template(
+        "error.account_not_found",
+        "Konto '{account}' nicht gefunden",
+    );
+    assert_eq!(error.localized(&german), "Konto 'alice' nicht gefunden");
+    assert_eq!(error.localized(&English), error.to_string());
+}
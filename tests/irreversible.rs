@@ -0,0 +1,128 @@
+//! Reads confirmed once the last irreversible block passes them
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+fn props(head: u32, lib: u32) -> Value {
+    json!({
+        "head_block_number": head,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": head,
+        "last_irreversible_block_num": lib,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+/// Answers global properties and `condenser_api.get_balance` in turn from queues
+#[derive(Clone, Default)]
+struct Node {
+    props: Arc<Mutex<VecDeque<Value>>>,
+    balances: Arc<Mutex<VecDeque<Value>>>,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Node {
+    fn new(props: Vec<Value>, balances: Vec<Value>) -> Self {
+        Self {
+            props: Arc::new(Mutex::new(props.into())),
+            balances: Arc::new(Mutex::new(balances.into())),
+            calls: Arc::default(),
+        }
+    }
+
+    /// The next answer, repeating the last one once the queue runs out
+    fn next(queue: &Mutex<VecDeque<Value>>) -> Value {
+        let mut queue = queue.lock().unwrap();
+        match queue.len() {
+            1 => queue[0].clone(),
+            _ => queue.pop_front().unwrap(),
+        }
+    }
+}
+
+impl Transport for Node {
+    fn send(&self, _url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        let method = request["method"].as_str().unwrap();
+        self.calls.lock().unwrap().push(method.to_string());
+        let result = match method {
+            "database_api.get_dynamic_global_properties" => Self::next(&self.props),
+            _ => Self::next(&self.balances),
+        };
+        Ok(
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+                .to_string()
+                .into_bytes(),
+        )
+    }
+}
+
+#[test]
+fn results_are_returned_once_seen_past_the_irreversible_block() {
+    // Read at head 10, confirmed once the irreversible block reaches it
+    let node = Node::new(
+        vec![props(10, 8), props(11, 9), props(13, 10)],
+        vec![json!("5.000 HIVE")],
+    );
+    let client = Client::builder().transport(node.clone()).build();
+    let balance: String = client
+        .call_at_irreversible("condenser_api.get_balance", json!(["alice"]))
+        .unwrap();
+    assert_eq!(balance, "5.000 HIVE");
+    let calls = node.calls.lock().unwrap();
+    assert_eq!(calls.first().unwrap(), "condenser_api.get_balance");
+    assert_eq!(calls.last().unwrap(), "condenser_api.get_balance");
+    // One read for the head, two while waiting for the irreversible block
+    let props = calls.iter().filter(|m| m.contains("global")).count();
+    assert_eq!(props, 3);
+}
+
+#[test]
+fn changed_results_are_confirmed_again() {
+    let node = Node::new(
+        vec![props(10, 10)],
+        vec![json!("5.000 HIVE"), json!("4.000 HIVE")],
+    );
+    let client = Client::builder().transport(node.clone()).build();
+    let balance: String = client
+        .call_at_irreversible("condenser_api.get_balance", json!(["alice"]))
+        .unwrap();
+    assert_eq!(balance, "4.000 HIVE");
+    let reads = node
+        .calls
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|m| m.as_str() == "condenser_api.get_balance")
+        .count();
+    assert_eq!(reads, 3);
+
+    let err = client
+        .call_at_irreversible::<_, u32>("condenser_api.get_balance", json!(["alice"]))
+        .unwrap_err();
+    assert!(err.starts_with("Result decode error:"), "{err}");
+}
+
+#[test]
+fn irreversibility_is_read_from_global_properties() {
+    let mock =
+        MockTransport::new().respond("database_api.get_dynamic_global_properties", props(20, 15));
+    let client = Client::builder().transport(mock).build();
+    assert!(client.is_irreversible(15).unwrap());
+    assert!(!client.is_irreversible(16).unwrap());
+
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(20, 15))
+        .respond_error("condenser_api.get_balance", -32000, "down");
+    let client = Client::builder().transport(mock).build();
+    assert!(client
+        .call_at_irreversible::<_, Value>("condenser_api.get_balance", json!(["alice"]))
+        .is_err());
+}
@@ -0,0 +1,19 @@
+use nectarflower_rs::Client;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn joined_calls_run_concurrently_and_keep_their_order() {
+    let client = Client::new();
+    let slow = |value: u32| {
+        move |_: &Client| {
+            thread::sleep(Duration::from_millis(200));
+            value
+        }
+    };
+    let started = Instant::now();
+    let (a, b, c) = client.join((slow(1), |_: &Client| "two", slow(3)));
+    assert_eq!((a, b, c), (1, "two", 3));
+    assert_eq!(client.join_all(vec![slow(4), slow(5)]), [4, 5]);
+    assert!(started.elapsed() < Duration::from_millis(700));
+}
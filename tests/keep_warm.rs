@@ -0,0 +1,31 @@
+//! Keep-warm pinging against a node that refuses connections
+
+use nectarflower_rs::Client;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn failing_preferred_node_is_marked_cold() {
+    let mut client = Client::new();
+    client.nodes = vec!["http://127.0.0.1:1".to_string()];
+    let client = Arc::new(client);
+    let warm = client.keep_warm(Duration::from_secs(60));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let ping = loop {
+        if let Some(ping) = warm.last_ping() {
+            break ping;
+        }
+        assert!(Instant::now() < deadline, "no ping completed");
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(ping.node, "http://127.0.0.1:1");
+    assert!(!ping.is_ok());
+    assert_eq!(client.cold_nodes(), ["http://127.0.0.1:1"]);
+
+    // Dropping the handle wakes and stops the thread without waiting out the interval
+    let started = Instant::now();
+    drop(warm);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
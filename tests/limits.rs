@@ -0,0 +1,247 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::transaction::Transaction;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, LimitError, TransferLimits, WriteClient, WriteError};
+use serde_json::json;
+
+fn transfer(to: &str, amount: &str) -> Operation {
+    Operation::new(
+        "transfer_operation",
+        json!({"from": "hot", "to": to, "amount": amount, "memo": ""}),
+    )
+}
+
+fn hive(amount: i64) -> Asset {
+    Asset::new(amount, AssetSymbol::Hive)
+}
+
+#[test]
+fn transfers_are_checked_against_each_limit() {
+    let limits = TransferLimits::default()
+        .per_transfer(hive(100_000))
+        .per_hour(hive(150_000))
+        .deny(&["thief"]);
+    let mut client = Client::new();
+    client.nodes.clear();
+    let writer = WriteClient::new(client).transfer_limits(limits);
+
+    assert_eq!(
+        writer.check_transfers(&[transfer("bob", "100.001 HIVE")]),
+        Err(LimitError::PerTransfer {
+            to: "bob".to_string(),
+            amount: hive(100_001),
+            max: hive(100_000),
+        })
+    );
+    assert_eq!(
+        writer.check_transfers(&[transfer("thief", "1.000 HIVE")]),
+        Err(LimitError::Denied("thief".to_string()))
+    );
+    assert!(writer
+        .check_transfers(&[transfer("bob", "5.000 HBD")])
+        .is_ok());
+
+    // The first transaction passes the check and counts towards the hour
+    // even though broadcasting fails without a node
+    let tx = signed(vec![transfer("bob", "100.000 HIVE")]);
+    let err = writer.broadcast_transaction(&tx).unwrap_err();
+    assert!(matches!(err, WriteError::Failed(_)), "{err}");
+    assert_eq!(
+        writer.check_transfers(&[transfer("carol", "50.001 HIVE")]),
+        Err(LimitError::PerHour {
+            total: hive(150_001),
+            max: hive(150_000),
+        })
+    );
+}
+
+fn signed(operations: Vec<Operation>) -> Transaction {
+    Transaction {
+        ref_block_num: 0,
+        ref_block_prefix: 0,
+        expiration: "2024-01-01T00:00:00".to_string(),
+        operations,
+        extensions: Vec::new(),
+        signatures: vec!["1f".repeat(65)],
+    }
+}
+
+#[test]
+fn every_broadcast_path_is_checked() {
+    let mock = MockTransport::new()
+        .respond("network_broadcast_api.broadcast_transaction", json!({}))
+        .respond("condenser_api.broadcast_transaction", json!({}));
+    let limits = TransferLimits::default().per_hour(hive(150_000));
+    let writer =
+        WriteClient::new(Client::builder().transport(mock.clone()).build()).transfer_limits(limits);
+
+    // Signed elsewhere, e.g. by another process holding the key
+    let tx = signed(vec![transfer("bob", "100.000 HIVE")]);
+    writer.broadcast_transaction(&tx).unwrap();
+    // Re-broadcasting the same transaction does not count twice
+    writer.broadcast_transaction(&tx).unwrap();
+
+    let tx = signed(vec![transfer("carol", "60.000 HIVE")]);
+    let err = writer.broadcast_hex(&tx.to_hex().unwrap()).unwrap_err();
+    assert!(
+        matches!(err, WriteError::Limit(LimitError::PerHour { .. })),
+        "{err}"
+    );
+    let err = writer
+        .call::<_, serde_json::Value>("condenser_api.broadcast_transaction", json!([tx]))
+        .unwrap_err();
+    assert!(matches!(err, WriteError::Limit(_)), "{err}");
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[test]
+fn delegations_count_as_transfers() {
+    let limits = TransferLimits::default().deny(&["thief"]);
+    let writer = WriteClient::new(Client::new()).transfer_limits(limits);
+    let delegation = Operation::new(
+        "delegate_vesting_shares_operation",
+        json!({"delegator": "hot", "delegatee": "thief", "vesting_shares": "1000.000000 VESTS"}),
+    );
+    assert_eq!(
+        writer.check_transfers(&[delegation]),
+        Err(LimitError::Denied("thief".to_string()))
+    );
+}
+
+#[test]
+fn allow_list_restricts_destinations() {
+    let limits = TransferLimits::default().allow(&["exchange"]);
+    let writer = WriteClient::new(Client::new()).transfer_limits(limits);
+    let vesting = Operation::new(
+        "transfer_to_vesting_operation",
+        json!({"from": "exchange", "to": "", "amount": "1.000 HIVE"}),
+    );
+    assert!(writer.check_transfers(&[vesting]).is_ok());
+    assert_eq!(
+        writer.check_transfers(&[transfer("bob", "1.000 HIVE")]),
+        Err(LimitError::NotAllowed("bob".to_string()))
+    );
+}
+
+#[test]
+fn legacy_format_broadcasts_are_checked() {
+    let mock = MockTransport::new()
+        .respond("condenser_api.broadcast_transaction", json!({}))
+        .respond("call", json!({}));
+    let limits = TransferLimits::default().per_transfer(hive(100_000));
+    let writer =
+        WriteClient::new(Client::builder().transport(mock.clone()).build()).transfer_limits(limits);
+
+    let legacy = json!({
+        "ref_block_num": 0,
+        "ref_block_prefix": 0,
+        "expiration": "2024-01-01T00:00:00",
+        "operations": [["transfer", {"from": "hot", "to": "bob", "amount": "100.001 HIVE", "memo": ""}]],
+        "extensions": [],
+        "signatures": ["1f".repeat(65)],
+    });
+    let err = writer
+        .call::<_, serde_json::Value>("condenser_api.broadcast_transaction", json!([legacy]))
+        .unwrap_err();
+    assert!(
+        matches!(err, WriteError::Limit(LimitError::PerTransfer { .. })),
+        "{err}"
+    );
+    let err = writer
+        .call::<_, serde_json::Value>(
+            "call",
+            json!(["condenser_api", "broadcast_transaction", [legacy]]),
+        )
+        .unwrap_err();
+    assert!(matches!(err, WriteError::Limit(_)), "{err}");
+
+    // A transaction the limits cannot be checked against is not broadcast
+    let err = writer
+        .call::<_, serde_json::Value>(
+            "condenser_api.broadcast_transaction",
+            json!([{"operations": [{"transfer": {"to": "bob"}}]}]),
+        )
+        .unwrap_err();
+    assert!(matches!(err, WriteError::Decode(_)), "{err}");
+    assert!(mock.requests().is_empty());
+}
+
+#[test]
+fn escrow_agents_and_vesting_routes_are_destinations() {
+    let limits = TransferLimits::default()
+        .per_transfer(hive(10_000))
+        .deny(&["thief"]);
+    let writer = WriteClient::new(Client::new()).transfer_limits(limits);
+    let escrow = |to: &str, agent: &str, fee: &str| {
+        Operation::new(
+            "escrow_transfer_operation",
+            json!({
+                "from": "hot", "to": to, "agent": agent, "escrow_id": 1,
+                "hbd_amount": "0.000 HBD", "hive_amount": "5.000 HIVE", "fee": fee,
+                "ratification_deadline": "2024-01-01T00:00:00",
+                "escrow_expiration": "2024-01-02T00:00:00", "json_meta": "",
+            }),
+        )
+    };
+    assert!(writer
+        .check_transfers(&[escrow("bob", "agent", "0.001 HIVE")])
+        .is_ok());
+    assert_eq!(
+        writer.check_transfers(&[escrow("bob", "thief", "0.001 HIVE")]),
+        Err(LimitError::Denied("thief".to_string()))
+    );
+    assert_eq!(
+        writer.check_transfers(&[escrow("bob", "agent", "10.001 HIVE")]),
+        Err(LimitError::PerTransfer {
+            to: "agent".to_string(),
+            amount: hive(10_001),
+            max: hive(10_000),
+        })
+    );
+
+    let route = |percent: u16| {
+        Operation::new(
+            "set_withdraw_vesting_route_operation",
+            json!({
+                "from_account": "hot", "to_account": "thief",
+                "percent": percent, "auto_vest": false,
+            }),
+        )
+    };
+    assert_eq!(
+        writer.check_transfers(&[route(10_000)]),
+        Err(LimitError::Denied("thief".to_string()))
+    );
+    // Removing a route moves nothing
+    assert!(writer.check_transfers(&[route(0)]).is_ok());
+}
+
+#[test]
+fn allow_lists_refuse_unrecognized_operations() {
+    let limits = TransferLimits::default().allow(&["exchange"]);
+    let writer = WriteClient::new(Client::new()).transfer_limits(limits);
+    let proposal = Operation::new(
+        "create_proposal_operation",
+        json!({
+            "creator": "hot", "receiver": "bob",
+            "start_date": "2024-01-01T00:00:00", "end_date": "2024-02-01T00:00:00",
+            "daily_pay": "100.000 HBD", "subject": "x", "permlink": "x", "extensions": [],
+        }),
+    );
+    assert_eq!(
+        writer.check_transfers(std::slice::from_ref(&proposal)),
+        Err(LimitError::Unrecognized("create_proposal".to_string()))
+    );
+    let vote = Operation::new(
+        "vote_operation",
+        json!({"voter": "hot", "author": "bob", "permlink": "x", "weight": 10_000}),
+    );
+    assert!(writer
+        .check_transfers(&[vote, transfer("exchange", "1.000 HIVE")])
+        .is_ok());
+
+    // Without an allow list only the known transfers are checked
+    let writer = WriteClient::new(Client::new()).transfer_limits(TransferLimits::default());
+    assert!(writer.check_transfers(&[proposal]).is_ok());
+}
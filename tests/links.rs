@@ -0,0 +1,150 @@
+use nectarflower_rs::links::{
+    account_url, parse_account_url, parse_post_url, post_path, post_url, Frontend,
+};
+
+fn post(input: &str) -> Result<(String, String), String> {
+    parse_post_url(input)
+}
+
+fn ok(author: &str, permlink: &str) -> Result<(String, String), String> {
+    Ok((author.to_string(), permlink.to_string()))
+}
+
+#[test]
+fn post_links_from_every_frontend_parse() {
+    for input in [
+        "https://hive.blog/hive-174578/@alice/my-post",
+        "https://peakd.com/@alice/my-post",
+        "https://ecency.com/hive-174578/@alice/my-post",
+        "https://www.peakd.com/@alice/my-post/",
+        "http://hive.blog/@alice/my-post?ref=bob#comments",
+        "https://unknown.example/@alice/my-post",
+        "hive.blog/@alice/my-post",
+        "/@alice/my-post",
+        "@alice/my-post",
+        "  @Alice/my-post\n",
+        "/photography/@alice/my-post",
+    ] {
+        assert_eq!(post(input), ok("alice", "my-post"), "{input}");
+    }
+    assert_eq!(
+        post("@alice.bee-1/re-x-20240101t120000123z"),
+        ok("alice.bee-1", "re-x-20240101t120000123z")
+    );
+}
+
+#[test]
+fn links_inside_query_strings_are_not_urls() {
+    assert_eq!(
+        post("@alice/my-post?ref=https://evil.example/@bob/other"),
+        ok("alice", "my-post")
+    );
+    assert_eq!(
+        parse_account_url("@alice?next=https://x.example/@bob"),
+        Ok("alice".to_string())
+    );
+}
+
+#[test]
+fn non_post_links_are_rejected() {
+    for input in [
+        "",
+        "   ",
+        "@alice",
+        "https://hive.blog",
+        "https://hive.blog/",
+        "https://peakd.com/@alice",
+        "https://peakd.com/@alice/wallet",
+        "@alice/blog",
+        "alice/my-post",
+        "/trending/hive/@alice/my-post",
+        "@al/my-post",
+        "@alice/My-Post",
+        "@alice/my post",
+        "@alice/привет",
+        "@алиса/my-post",
+        "@alice/my%20post",
+    ] {
+        assert_eq!(
+            post(input),
+            Err(format!("Not a Hive post link: '{input}'")),
+            "{input}"
+        );
+    }
+    let long = format!("@alice/{}", "a".repeat(256));
+    assert!(post(&long).is_err());
+    let longest = format!("@alice/{}", "a".repeat(255));
+    assert_eq!(post(&longest), ok("alice", &"a".repeat(255)));
+}
+
+#[test]
+fn account_links_parse() {
+    for input in [
+        "https://peakd.com/@alice",
+        "https://hive.blog/@alice/wallet",
+        "https://ecency.com/@Alice?tab=posts",
+        "@alice",
+        "/@alice/",
+        "@alice#top",
+    ] {
+        assert_eq!(parse_account_url(input), Ok("alice".to_string()), "{input}");
+    }
+    for input in [
+        "",
+        "alice",
+        "@",
+        "@al",
+        "@alice!",
+        "https://peakd.com/",
+        "/trending/@alice",
+    ] {
+        assert_eq!(
+            parse_account_url(input),
+            Err(format!("Not a Hive account link: '{input}'")),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn frontends_are_recognized_by_host() {
+    assert_eq!(Frontend::from_host("peakd.com"), Some(Frontend::PeakD));
+    assert_eq!(
+        Frontend::from_host("www.ecency.com"),
+        Some(Frontend::Ecency)
+    );
+    assert_eq!(Frontend::from_host("Hive.Blog"), Some(Frontend::HiveBlog));
+    assert_eq!(Frontend::from_host("www.www.hive.blog"), None);
+    assert_eq!(Frontend::from_host("evilhive.blog"), None);
+    assert_eq!(Frontend::from_host(""), None);
+    for frontend in [Frontend::HiveBlog, Frontend::PeakD, Frontend::Ecency] {
+        assert_eq!(Frontend::from_host(frontend.host()), Some(frontend));
+    }
+}
+
+#[test]
+fn formatted_links_parse_back() {
+    assert_eq!(post_path(None, "alice", "my-post"), "/@alice/my-post");
+    assert_eq!(post_path(Some(""), "alice", "my-post"), "/@alice/my-post");
+    assert_eq!(
+        post_path(Some("hive-174578"), "alice", "my-post"),
+        "/hive-174578/@alice/my-post"
+    );
+    assert_eq!(
+        account_url(Frontend::PeakD, "alice"),
+        "https://peakd.com/@alice"
+    );
+
+    for frontend in [Frontend::HiveBlog, Frontend::PeakD, Frontend::Ecency] {
+        let url = post_url(frontend, "alice", "my-post");
+        assert_eq!(post(&url), ok("alice", "my-post"), "{url}");
+        assert_eq!(
+            parse_account_url(&account_url(frontend, "alice")),
+            Ok("alice".to_string())
+        );
+    }
+    assert_eq!(
+        post_url(Frontend::default(), "a", "b"),
+        "https://hive.blog/@a/b"
+    );
+}
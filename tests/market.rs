@@ -0,0 +1,160 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::market::{OrderBook, OrderSide};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn hive(amount: i64) -> Asset {
+    Asset::new(amount, AssetSymbol::Hive)
+}
+
+fn hbd(amount: i64) -> Asset {
+    Asset::new(amount, AssetSymbol::Hbd)
+}
+
+fn order(price: &str, hive: i64, hbd: i64) -> Value {
+    json!({ "real_price": price, "hive": hive, "hbd": hbd, "created": "2026-01-01T00:00:00" })
+}
+
+fn book_json() -> Value {
+    json!({
+        "asks": [order("0.250", 4_000, 1_000), order("0.260", 10_000, 2_600)],
+        "bids": [
+            order("0.240", 5_000, 1_200),
+            order("0.235", 0, 0),
+            order("0.230", 10_000, 2_300),
+        ],
+    })
+}
+
+fn book() -> OrderBook {
+    serde_json::from_value(book_json()).unwrap()
+}
+
+#[test]
+fn order_filled_by_the_best_order_has_no_slippage() {
+    let sim = book()
+        .simulate_market_order(OrderSide::Buy, hbd(500))
+        .unwrap();
+    assert_eq!(sim.fills.len(), 1);
+    assert_eq!(sim.spent, hbd(500));
+    assert_eq!(sim.received, hive(2_000));
+    assert_eq!(sim.unfilled, hbd(0));
+    assert_eq!(sim.best_price, 0.25);
+    assert_eq!(sim.average_price, 0.25);
+    assert_eq!(sim.slippage, 0.0);
+}
+
+#[test]
+fn buying_walks_the_asks() {
+    let sim = book()
+        .simulate_market_order(OrderSide::Buy, hbd(2_000))
+        .unwrap();
+    let prices: Vec<f64> = sim.fills.iter().map(|f| f.price).collect();
+    assert_eq!(prices, [0.25, 0.26]);
+    assert_eq!(sim.fills[0].hive, hive(4_000));
+    assert_eq!(sim.fills[0].hbd, hbd(1_000));
+    // 1.000 HBD buys 3.846153... HIVE at the second level, rounded down
+    assert_eq!(sim.fills[1].hive, hive(3_846));
+    assert_eq!(sim.received, hive(7_846));
+    assert_eq!(sim.spent, hbd(2_000));
+    assert!((sim.average_price - 2_000.0 / 7_846.0).abs() < 1e-12);
+    assert!((sim.slippage - (sim.average_price - 0.25) / 0.25).abs() < 1e-12);
+    assert!(sim.slippage > 0.0);
+}
+
+#[test]
+fn selling_walks_the_bids_and_skips_empty_orders() {
+    let sim = book()
+        .simulate_market_order(OrderSide::Sell, hive(6_000))
+        .unwrap();
+    let prices: Vec<f64> = sim.fills.iter().map(|f| f.price).collect();
+    assert_eq!(prices, [0.24, 0.23]);
+    assert_eq!(sim.received, hbd(1_430));
+    assert_eq!(sim.spent, hive(6_000));
+    assert!((sim.average_price - 1_430.0 / 6_000.0).abs() < 1e-12);
+    // Selling below the best bid is still slippage
+    assert!((sim.slippage - (0.24 - sim.average_price) / 0.24).abs() < 1e-12);
+}
+
+#[test]
+fn thin_books_leave_the_rest_unfilled() {
+    let sim = book()
+        .simulate_market_order(OrderSide::Buy, hbd(5_000))
+        .unwrap();
+    assert_eq!(sim.spent, hbd(3_600));
+    assert_eq!(sim.unfilled, hbd(1_400));
+    assert_eq!(sim.received, hive(14_000));
+    assert_eq!(sim.spent.amount + sim.unfilled.amount, 5_000);
+}
+
+#[test]
+fn tiny_and_empty_orders_receive_nothing() {
+    let book = book();
+    let sim = book.simulate_market_order(OrderSide::Buy, hbd(0)).unwrap();
+    assert!(sim.fills.is_empty());
+    assert_eq!(sim.received, hive(0));
+    assert_eq!(sim.average_price, sim.best_price);
+    assert_eq!(sim.slippage, 0.0);
+
+    // The last 0.001 HIVE is worth less than 0.001 HBD at the next bid
+    let sim = book
+        .simulate_market_order(OrderSide::Sell, hive(5_001))
+        .unwrap();
+    assert_eq!(sim.fills.len(), 2);
+    assert_eq!(sim.fills[1].hbd, hbd(0));
+    assert_eq!(sim.received, hbd(1_200));
+}
+
+#[test]
+fn orders_in_the_wrong_asset_or_on_an_empty_book_fail() {
+    let book = book();
+    let err = book
+        .simulate_market_order(OrderSide::Buy, hive(1_000))
+        .unwrap_err();
+    assert_eq!(err, "Buy orders must be given in HBD, got 1.000 HIVE");
+    let err = book
+        .simulate_market_order(OrderSide::Sell, Asset::new(1, AssetSymbol::Vests))
+        .unwrap_err();
+    assert!(
+        err.starts_with("Sell orders must be given in HIVE"),
+        "{err}"
+    );
+
+    let empty = OrderBook {
+        bids: Vec::new(),
+        asks: Vec::new(),
+    };
+    assert_eq!(
+        empty
+            .simulate_market_order(OrderSide::Buy, hbd(1_000))
+            .unwrap_err(),
+        "Order book side is empty"
+    );
+}
+
+#[test]
+fn invalid_prices_fail_to_decode() {
+    let mut book = book_json();
+    book["asks"][0]["real_price"] = json!("cheap");
+    assert!(serde_json::from_value::<OrderBook>(book).is_err());
+}
+
+#[test]
+fn client_simulates_against_the_fetched_book() {
+    let mock = MockTransport::new().respond("market_history_api.get_order_book", book_json());
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let sim = client
+        .simulate_market_order(OrderSide::Buy, hbd(2_000))
+        .unwrap();
+    assert_eq!(sim.received, hive(7_846));
+    // Books are requested at the API's maximum depth
+    assert_eq!(client.get_order_book(10_000).unwrap().asks.len(), 2);
+    let limits: Vec<Value> = mock
+        .requests()
+        .into_iter()
+        .map(|(_, body)| body["params"]["limit"].clone())
+        .collect();
+    assert_eq!(limits, [json!(500), json!(500)]);
+}
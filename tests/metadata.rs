@@ -0,0 +1,100 @@
+use nectarflower_rs::chain::ChainConfig;
+use nectarflower_rs::crypto::{encode_public_key, recover_public_key, to_hex};
+use nectarflower_rs::metadata::{merge_metadata, metadata_operation};
+use nectarflower_rs::transaction::Transaction;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, MetadataField, SecretWif, WriteClient};
+use serde_json::{json, Value};
+
+#[test]
+fn metadata_updates_keep_other_keys() {
+    let raw = r#"{"profile":{"name":"Alice"},"beneficiaries":[{"name":"dev"}]}"#;
+    let merged = merge_metadata(raw, |meta| {
+        meta["profile"]["about"] = json!("Builds things");
+    })
+    .unwrap()
+    .unwrap();
+    let merged: Value = serde_json::from_str(&merged).unwrap();
+    assert_eq!(merged["profile"]["name"], "Alice");
+    assert_eq!(merged["profile"]["about"], "Builds things");
+    assert_eq!(merged["beneficiaries"][0]["name"], "dev");
+
+    assert_eq!(
+        merge_metadata(raw, |meta| {
+            meta.insert("profile".into(), json!({"name": "Alice"}));
+        }),
+        Ok(None)
+    );
+    assert_eq!(
+        merge_metadata("", |meta| {
+            meta.insert("a".into(), json!(1));
+        }),
+        Ok(Some(r#"{"a":1}"#.to_string()))
+    );
+    assert!(merge_metadata("[1]", |_| {}).is_err());
+
+    let op = metadata_operation("alice", MetadataField::Posting, "{}");
+    assert_eq!(op.value["json_metadata"], "");
+    assert_eq!(op.value["posting_json_metadata"], "{}");
+}
+
+#[test]
+fn metadata_updates_are_signed_and_broadcast() {
+    let mock = MockTransport::new()
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{ "name": "alice", "json_metadata": "" }] }),
+        )
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 1234,
+                "head_block_id": "000004d2f776e542000000000000000000000000",
+                "time": "2017-07-15T16:50:19",
+                "current_witness": "bob",
+                "current_aslot": 1234,
+                "last_irreversible_block_num": 1200,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond("network_broadcast_api.broadcast_transaction", json!({}))
+        .respond(
+            "transaction_status_api.find_transaction",
+            json!({ "status": "within_irreversible_block" }),
+        );
+    let client = WriteClient::new(Client::builder().transport(mock.clone()).build());
+    let key = SecretWif::parse("5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL").unwrap();
+
+    let trx_id = client
+        .update_json_metadata("alice", MetadataField::Json, &key, |meta| {
+            meta.insert("a".into(), json!(1));
+        })
+        .unwrap()
+        .unwrap();
+
+    let requests = mock.requests();
+    let (_, request) = requests
+        .iter()
+        .find(|(_, r)| r["method"] == "network_broadcast_api.broadcast_transaction")
+        .unwrap();
+    let tx: Transaction = serde_json::from_value(request["params"]["trx"].clone()).unwrap();
+    // account_update2 (op 43) with no authorities, no memo key and no extensions
+    let chain = ChainConfig::mainnet();
+    assert_eq!(
+        to_hex(&tx.to_bytes_for(&chain).unwrap()),
+        "d204f776e54207486a59012b05616c69636500000000077b2261223a317d000000"
+    );
+    let digest = tx.digest(&chain).unwrap();
+    assert_eq!(
+        to_hex(&digest),
+        "c7a909e001af696a6f07145d57097a3e5af795a2c6059a072e28edce117eb229"
+    );
+    assert_eq!(trx_id, tx.id_for(&chain).unwrap());
+    assert_eq!(tx.signatures.len(), 1);
+    let signer = recover_public_key(&digest, &tx.signatures[0]).unwrap();
+    assert_eq!(
+        encode_public_key(&signer, "STM"),
+        key.public_key("STM").unwrap()
+    );
+}
@@ -0,0 +1,24 @@
+use nectarflower_rs::methods::{condenser_api, BlockApi, DatabaseApi, Method};
+use std::collections::HashSet;
+
+#[test]
+fn method_names_round_trip_and_are_unique() {
+    let mut seen = HashSet::new();
+    for method in Method::known() {
+        assert!(seen.insert(method.to_string()), "duplicate {method}");
+        assert_eq!(method.as_str().parse::<Method>().unwrap(), method);
+    }
+    assert_eq!(
+        Method::from("block_api.get_block"),
+        Method::BlockApi(BlockApi::GetBlock)
+    );
+    assert_eq!(
+        Method::from(DatabaseApi::FindAccounts).api(),
+        "database_api"
+    );
+    assert_eq!(condenser_api::GET_ACCOUNTS, "condenser_api.get_accounts");
+
+    let typo = Method::from("database_api.get_dynamic_global_propertis");
+    assert!(!typo.is_known());
+    assert_eq!(typo.as_str(), "database_api.get_dynamic_global_propertis");
+}
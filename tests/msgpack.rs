@@ -0,0 +1,239 @@
+use nectarflower_rs::msgpack::{MAX_DEPTH, STREAM_MAGIC, STREAM_VERSION};
+use nectarflower_rs::{from_msgpack, to_msgpack, MsgpackReader, MsgpackWriter};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    block_num: u32,
+    ops: Vec<Value>,
+}
+
+fn round_trip(value: &Value) -> Value {
+    from_msgpack(&to_msgpack(value).unwrap()).unwrap()
+}
+
+#[test]
+fn empty_values_round_trip() {
+    for value in [
+        json!(null),
+        json!(""),
+        json!([]),
+        json!({}),
+        json!([[], {}, ""]),
+    ] {
+        assert_eq!(round_trip(&value), value);
+    }
+    assert_eq!(to_msgpack(&json!(null)).unwrap(), [0xc0]);
+    assert_eq!(to_msgpack(&json!([])).unwrap(), [0x90]);
+    assert_eq!(to_msgpack(&json!({})).unwrap(), [0x80]);
+    assert_eq!(to_msgpack(&json!("")).unwrap(), [0xa0]);
+}
+
+#[test]
+fn integers_use_the_smallest_encoding() {
+    let cases: [(Value, &[u8]); 12] = [
+        (json!(0), &[0x00]),
+        (json!(127), &[0x7f]),
+        (json!(128), &[0xcc, 0x80]),
+        (json!(256), &[0xcd, 0x01, 0x00]),
+        (json!(65_536), &[0xce, 0x00, 0x01, 0x00, 0x00]),
+        (json!(1u64 << 32), &[0xcf, 0, 0, 0, 1, 0, 0, 0, 0]),
+        (json!(-1), &[0xff]),
+        (json!(-32), &[0xe0]),
+        (json!(-33), &[0xd0, 0xdf]),
+        (json!(-129), &[0xd1, 0xff, 0x7f]),
+        (json!(-32_769), &[0xd2, 0xff, 0xff, 0x7f, 0xff]),
+        (
+            json!(i64::from(i32::MIN) - 1),
+            &[0xd3, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff],
+        ),
+    ];
+    for (value, bytes) in cases {
+        assert_eq!(to_msgpack(&value).unwrap(), bytes, "{value}");
+        assert_eq!(round_trip(&value), value);
+    }
+    for value in [
+        json!(u64::MAX),
+        json!(i64::MIN),
+        json!(1.5),
+        json!(-0.25),
+        json!(1e300),
+    ] {
+        assert_eq!(round_trip(&value), value);
+    }
+}
+
+#[test]
+fn string_and_container_length_boundaries() {
+    for (len, header) in [
+        (31, vec![0xbf]),
+        (32, vec![0xd9, 32]),
+        (255, vec![0xd9, 255]),
+        (256, vec![0xda, 1, 0]),
+        (65_536, vec![0xdb, 0, 1, 0, 0]),
+    ] {
+        let text = "x".repeat(len);
+        let bytes = to_msgpack(&text).unwrap();
+        assert_eq!(bytes[..header.len()], header[..], "{len}");
+        assert_eq!(from_msgpack::<String>(&bytes).unwrap(), text);
+    }
+    for (len, header) in [
+        (15, vec![0x9f]),
+        (16, vec![0xdc, 0, 16]),
+        (65_536, vec![0xdd, 0, 1, 0, 0]),
+    ] {
+        let items = vec![0u8; len];
+        let bytes = to_msgpack(&items).unwrap();
+        assert_eq!(bytes[..header.len()], header[..], "{len}");
+        assert_eq!(from_msgpack::<Vec<u8>>(&bytes).unwrap(), items);
+    }
+}
+
+#[test]
+fn unicode_strings_and_keys_round_trip() {
+    let value = json!({
+        "memo": "Grüße 🐝 日本語",
+        "ключ": ["", "\u{0}", "\u{10FFFF}"],
+    });
+    assert_eq!(round_trip(&value), value);
+    // Lengths are in bytes, so eight four-byte emoji need the str8 form
+    let bytes = to_msgpack(&"🐝".repeat(8)).unwrap();
+    assert_eq!(bytes[..2], [0xd9, 32]);
+}
+
+#[test]
+fn maps_are_encoded_in_key_order() {
+    let a = to_msgpack(&json!({ "b": 1, "a": 2, "ä": 3 })).unwrap();
+    let b = to_msgpack(&json!({ "ä": 3, "a": 2, "b": 1 })).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a[..3], [0x83, 0xa1, b'a']);
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    let err = |bytes: &[u8]| from_msgpack::<Value>(bytes).unwrap_err();
+    assert!(err(&[]).starts_with("Decode error:"));
+    assert_eq!(
+        err(&[0xc1]),
+        "Decode error: Unsupported MessagePack type 0xc1"
+    );
+    assert_eq!(err(&[0xc0, 0xc0]), "Decode error: 1 trailing bytes");
+    assert_eq!(
+        err(&[0x81, 0x01, 0x02]),
+        "Decode error: Map key is not a string"
+    );
+    assert!(err(&[0xa2, 0xff, 0xfe]).contains("Invalid UTF-8"));
+    assert!(err(&[0xca, 0x7f, 0x80, 0x00, 0x00]).contains("Non-finite float"));
+    // Truncated values
+    assert!(err(&[0xa3, b'a']).starts_with("Decode error:"));
+    assert!(err(&[0xcd, 0x01]).starts_with("Decode error:"));
+    assert!(err(&[0x92, 0x01]).starts_with("Decode error:"));
+    // A length far beyond the input fails instead of allocating it
+    assert!(err(&[0xdb, 0xff, 0xff, 0xff, 0xff, b'a']).starts_with("Decode error:"));
+    assert!(err(&[0xdd, 0xff, 0xff, 0xff, 0xff]).starts_with("Decode error:"));
+    // A value of the wrong shape for the target type
+    assert!(from_msgpack::<Record>(&[0x01]).is_err());
+}
+
+#[test]
+fn nesting_is_limited() {
+    let nested = |depth: usize| {
+        let mut bytes = vec![0x91; depth];
+        bytes.push(0xc0);
+        bytes
+    };
+    assert!(from_msgpack::<Value>(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(
+        from_msgpack::<Value>(&nested(MAX_DEPTH + 1)).unwrap_err(),
+        format!("Decode error: Nesting deeper than {MAX_DEPTH} levels")
+    );
+    // Far deeper input fails the same way rather than overflowing the stack
+    assert!(from_msgpack::<Value>(&nested(1_000_000)).is_err());
+}
+
+#[test]
+fn streams_replay_their_records() {
+    let records = [
+        Record {
+            block_num: 1,
+            ops: vec![],
+        },
+        Record {
+            block_num: 2,
+            ops: vec![json!(["vote", { "voter": "alice", "weight": -10_000 }])],
+        },
+    ];
+    let mut buf = Vec::new();
+    let mut writer = MsgpackWriter::new(&mut buf).unwrap();
+    for record in &records {
+        writer.write(record).unwrap();
+    }
+    writer.flush().unwrap();
+    drop(writer);
+
+    assert_eq!(buf[..4], STREAM_MAGIC[..]);
+    assert_eq!(buf[4], STREAM_VERSION);
+    let replayed: Vec<Record> = MsgpackReader::new(buf.as_slice())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(replayed, records);
+}
+
+#[test]
+fn empty_and_foreign_streams() {
+    let mut buf = Vec::new();
+    MsgpackWriter::new(&mut buf).unwrap().flush().unwrap();
+    let mut reader = MsgpackReader::<_, Record>::new(buf.as_slice()).unwrap();
+    assert!(reader.next().is_none());
+
+    let err = MsgpackReader::<_, Value>::new(&b"NFM"[..]).unwrap_err();
+    assert!(err.starts_with("Read error:"), "{err}");
+    let err = MsgpackReader::<_, Value>::new(&b"JSON\x01"[..]).unwrap_err();
+    assert_eq!(err, "Not a nectarflower MessagePack stream");
+    let err = MsgpackReader::<_, Value>::new(&b"NFMP\x02"[..]).unwrap_err();
+    assert_eq!(err, "Unsupported stream version 2 (expected 1)");
+}
+
+#[test]
+fn stream_errors_end_or_skip_records() {
+    let mut buf = Vec::new();
+    let mut writer = MsgpackWriter::new(&mut buf).unwrap();
+    writer.write(&json!("not a record")).unwrap();
+    writer
+        .write(&Record {
+            block_num: 7,
+            ops: vec![],
+        })
+        .unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    // A record of the wrong shape is skipped past
+    let mut reader = MsgpackReader::<_, Record>::new(buf.as_slice()).unwrap();
+    assert!(reader
+        .next()
+        .unwrap()
+        .unwrap_err()
+        .starts_with("Decode error:"));
+    assert_eq!(reader.next().unwrap().unwrap().block_num, 7);
+    assert!(reader.next().is_none());
+
+    // Corrupt bytes end the stream instead of decoding garbage
+    buf.extend([0xc1, 0x01, 0x02]);
+    let results: Vec<_> = MsgpackReader::<_, Value>::new(buf.as_slice())
+        .unwrap()
+        .collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[2].as_ref().unwrap_err(),
+        "Read error: Unsupported MessagePack type 0xc1"
+    );
+
+    // So does a truncated final record
+    let truncated = &buf[..buf.len() - 5];
+    let results: Vec<_> = MsgpackReader::<_, Value>::new(truncated).unwrap().collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[1].as_ref().unwrap_err().starts_with("Read error:"));
+}
@@ -0,0 +1,305 @@
+//! Round-trips of the node list metadata published by the `nectarflower` account
+
+use nectarflower_rs::crypto::{encode_public_key, recover_public_key};
+use nectarflower_rs::node_scan::NODE_LIST_ACCOUNT;
+use nectarflower_rs::seeds::seed_nodes;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{
+    AutoBenchmark, ChainConfig, Client, EmptyNodeList, EmptyNodePolicy, FailReason, NodeFailure,
+    NodeMetadata, SecretWif, Transaction, Url, WriteClient,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const METADATA: &str = include_str!("../fixtures/node_metadata.json");
+
+fn assert_round_trip(json: &str) {
+    let metadata = NodeMetadata::from_json(json).unwrap();
+    let original: Value = serde_json::from_str(json).unwrap();
+    let reserialized: Value = serde_json::from_str(&metadata.to_json()).unwrap();
+    assert_eq!(reserialized, original);
+    assert_eq!(
+        NodeMetadata::from_json(&metadata.to_json()).unwrap(),
+        metadata
+    );
+}
+
+#[test]
+fn published_metadata_round_trips() {
+    assert_round_trip(METADATA);
+}
+
+#[test]
+fn unknown_fields_are_kept() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    assert!(metadata.extra.contains_key("profile"));
+    let parameter = metadata.parameter.unwrap();
+    assert_eq!(parameter.extra["threading"], Value::Bool(false));
+}
+
+#[test]
+fn node_data_feeds_the_client() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    let node_data = metadata.node_data();
+    let mut client = Client::new();
+    client
+        .set_nodes(node_data.nodes, node_data.failing_nodes)
+        .unwrap();
+    assert_eq!(client.nodes, metadata.nodes);
+    let failure = &client.failing_nodes[&Url::parse("https://rpc.mahdiyari.info").unwrap()];
+    assert_eq!(failure.reason, FailReason::Timeout);
+    assert_eq!(
+        failure.message,
+        "history: Request error: operation timed out"
+    );
+}
+
+#[test]
+fn reports_reflect_failures() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    assert!(metadata.report_for("https://api.hive.blog").unwrap().ok());
+    let failing = metadata.report_for("https://rpc.mahdiyari.info").unwrap();
+    assert!(!failing.ok());
+    assert_eq!(failing.history.as_ref().unwrap().rank, Some(-1));
+    assert!(metadata.report_for("https://example.com").is_none());
+}
+
+#[test]
+fn publish_operation_carries_the_metadata() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    let op = metadata.publish_operation(NODE_LIST_ACCOUNT);
+    assert_eq!(op.op_type, "account_update2_operation");
+    let json = op.value["json_metadata"].as_str().unwrap();
+    assert_eq!(NodeMetadata::from_json(json).unwrap(), metadata);
+}
+
+#[test]
+fn publish_operation_can_be_signed() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    let mut tx = Transaction {
+        ref_block_num: 1234,
+        ref_block_prefix: 1122334455,
+        expiration: "2017-07-15T16:51:19".to_string(),
+        operations: vec![metadata.publish_operation(NODE_LIST_ACCOUNT)],
+        extensions: vec![],
+        signatures: vec![],
+    };
+    let client = WriteClient::new(Client::builder().transport(MockTransport::new()).build());
+    let key = SecretWif::parse("5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL").unwrap();
+    let trx_id = client.sign_transaction(&mut tx, &[&key]).unwrap();
+
+    // account_update2 is operation 43, with no authorities or memo key
+    let bytes = tx.to_bytes().unwrap();
+    let account = NODE_LIST_ACCOUNT.as_bytes();
+    assert_eq!(bytes[10..12], [1, 43]);
+    assert_eq!(bytes[12] as usize, account.len());
+    assert_eq!(&bytes[13..13 + account.len()], account);
+    assert_eq!(bytes[13 + account.len()..][..4], [0; 4]);
+    assert_eq!(trx_id, tx.id().unwrap());
+
+    let digest = tx.digest(&ChainConfig::mainnet()).unwrap();
+    let signer = recover_public_key(&digest, &tx.signatures[0]).unwrap();
+    assert_eq!(
+        encode_public_key(&signer, "STM"),
+        key.public_key("STM").unwrap()
+    );
+    let decoded = Transaction::from_hex(&tx.to_hex().unwrap()).unwrap();
+    let json = decoded.operations[0].value["json_metadata"]
+        .as_str()
+        .unwrap();
+    assert_eq!(NodeMetadata::from_json(json).unwrap(), metadata);
+}
+
+#[test]
+#[ignore = "requires network access"]
+fn live_metadata_round_trips() {
+    let client = Client::new();
+    let account = client.find_accounts(&[NODE_LIST_ACCOUNT]).unwrap();
+    assert_round_trip(&account[0].json_metadata);
+}
+
+fn scan_with_times(times: &[(&str, f64)]) -> NodeMetadata {
+    let json = serde_json::json!({
+        "nodes": times.iter().map(|(node, _)| node).collect::<Vec<_>>(),
+        "failing_nodes": {},
+        "report": times.iter().map(|(node, time)| serde_json::json!({
+            "node": node,
+            "apicall": { "ok": true, "time": time },
+        })).collect::<Vec<_>>(),
+    });
+    NodeMetadata::from_json(&json.to_string()).unwrap()
+}
+
+#[test]
+fn rebenchmark_keeps_order_within_hysteresis() {
+    let mut client = Client::new();
+    client
+        .set_nodes(
+            vec!["https://a".into(), "https://b".into()],
+            Default::default(),
+        )
+        .unwrap();
+    let bench = AutoBenchmark::for_client(&client).hysteresis(0.2);
+
+    let jitter = scan_with_times(&[("https://b", 0.9), ("https://a", 1.0)]);
+    assert!(!bench.apply(&mut client, &jitter));
+    assert_eq!(client.nodes, ["https://a", "https://b"]);
+
+    let faster = scan_with_times(&[("https://b", 0.5), ("https://a", 1.0)]);
+    assert!(bench.apply(&mut client, &faster));
+    assert_eq!(client.nodes, ["https://b", "https://a"]);
+}
+
+#[test]
+fn rebenchmark_drops_failing_and_appends_new_nodes() {
+    let mut client = Client::new();
+    client
+        .set_nodes(
+            vec!["https://a".into(), "https://b".into()],
+            Default::default(),
+        )
+        .unwrap();
+    let bench = AutoBenchmark::for_client(&client);
+
+    let mut scan = scan_with_times(&[("https://c", 0.4), ("https://b", 1.0)]);
+    scan.failing_nodes.insert(
+        "https://a".into(),
+        NodeFailure::from_message("config: timed out"),
+    );
+    assert!(bench.apply(&mut client, &scan));
+    assert_eq!(client.nodes, ["https://c", "https://b"]);
+    assert!(client
+        .failing_nodes
+        .contains_key(&Url::parse("https://a").unwrap()));
+
+    let all_failing = NodeMetadata::default();
+    assert!(!bench.apply(&mut client, &all_failing));
+    assert_eq!(client.nodes, ["https://c", "https://b"]);
+}
+
+#[test]
+fn failure_messages_are_classified() {
+    let metadata = NodeMetadata::from_json(METADATA).unwrap();
+    assert_eq!(
+        metadata.failing_nodes["https://hive-api.arcange.eu"].reason,
+        FailReason::Stale
+    );
+    let cases = [
+        (
+            "Unexpected status code: 429 Too Many Requests",
+            FailReason::RateLimited,
+        ),
+        (
+            "Unexpected status code: 502 Bad Gateway",
+            FailReason::HttpError(502),
+        ),
+        (
+            "Request error: invalid peer certificate: Expired",
+            FailReason::BadTls,
+        ),
+        (
+            "RPC error: Could not find API bridge (code: -32601)",
+            FailReason::MissingApi,
+        ),
+        (
+            "Request timed out: error sending request",
+            FailReason::Timeout,
+        ),
+        ("Decode error: expected value", FailReason::Other),
+    ];
+    for (message, reason) in cases {
+        assert_eq!(FailReason::classify(message), reason, "{message}");
+    }
+}
+
+#[test]
+fn node_list_changes_are_reported_to_listeners() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let mut client =
+        Client::new().on_node_list_change(move |change| sink.lock().unwrap().push(change.clone()));
+    client
+        .set_nodes(
+            vec!["https://a".into(), "https://b".into()],
+            Default::default(),
+        )
+        .unwrap();
+
+    let failure = NodeFailure::from_message("config: timed out");
+    let failing: HashMap<_, _> = [(Url::parse("https://a").unwrap(), failure.clone())].into();
+    let change = client
+        .set_nodes(
+            vec!["https://a".into(), "https://b".into(), "https://c".into()],
+            failing,
+        )
+        .unwrap();
+    assert_eq!(change.added, ["https://c"]);
+    assert_eq!(change.removed, ["https://a"]);
+    assert_eq!(
+        change.newly_failing,
+        [(Url::parse("https://a").unwrap(), failure)]
+    );
+    assert!(!change.reordered);
+
+    assert!(client
+        .set_nodes(client.nodes.clone(), client.failing_nodes.clone())
+        .unwrap()
+        .is_empty());
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[1], change);
+}
+
+#[test]
+fn empty_node_lists_follow_the_policy() {
+    let failure = NodeFailure::from_message("config: timed out");
+    let all_failing =
+        || -> HashMap<_, _> { [(Url::parse("https://a").unwrap(), failure.clone())].into() };
+    let offered = || vec!["https://a".to_string(), "not a url".to_string()];
+
+    let mut client = Client::new();
+    client.set_nodes(offered(), all_failing()).unwrap();
+    assert_eq!(client.nodes, ["https://api.hive.blog"]);
+    assert!(client
+        .failing_nodes
+        .contains_key(&Url::parse("https://a").unwrap()));
+
+    let mut client =
+        Client::new().on_empty_node_list(EmptyNodePolicy::Fallback(vec!["https://b".into()]));
+    client.set_nodes(offered(), all_failing()).unwrap();
+    assert_eq!(client.nodes, ["https://b"]);
+
+    let mut client = Client::new().on_empty_node_list(EmptyNodePolicy::Error);
+    let err = client.set_nodes(offered(), all_failing()).unwrap_err();
+    assert_eq!(
+        err,
+        EmptyNodeList {
+            offered: 2,
+            invalid: 1,
+            failing: 1
+        }
+    );
+    assert_eq!(client.nodes, ["https://api.hive.blog"]);
+    assert!(client.failing_nodes.is_empty());
+}
+
+#[test]
+fn seeded_clients_fall_back_to_the_seeds() {
+    let mut client = Client::new().with_default_seeds();
+    assert_eq!(client.nodes, seed_nodes());
+    client
+        .set_nodes(vec!["https://only.example".into()], Default::default())
+        .unwrap();
+    assert_eq!(client.nodes, ["https://only.example"]);
+
+    let failing: HashMap<_, _> = [(
+        Url::parse("https://only.example").unwrap(),
+        NodeFailure::from_message("config: timed out"),
+    )]
+    .into();
+    client
+        .set_nodes(vec!["https://only.example".into()], failing)
+        .unwrap();
+    assert_eq!(client.nodes, seed_nodes());
+}
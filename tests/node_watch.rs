@@ -0,0 +1,55 @@
+use nectarflower_rs::block::Block;
+use nectarflower_rs::{Client, NodeListWatcher, NodeWatchEvent};
+use serde_json::{json, Value};
+
+fn block_with(ops: Value) -> Block {
+    let mut block: Value = serde_json::from_str(include_str!("../fixtures/block.json")).unwrap();
+    block["transactions"] = json!([{
+        "ref_block_num": 1,
+        "ref_block_prefix": 2,
+        "expiration": "2024-01-01T12:01:00",
+        "operations": ops,
+        "extensions": [],
+        "signatures": [],
+    }]);
+    block["transaction_ids"] = json!(["ab".repeat(20)]);
+    serde_json::from_value(block).unwrap()
+}
+
+#[test]
+fn published_node_lists_are_applied_from_blocks() {
+    let metadata = json!({"nodes": ["https://a", "https://b"], "failing_nodes": {}});
+    let block = block_with(json!([
+        {"type": "account_update2_operation", "value": {
+            "account": "someone-else", "json_metadata": metadata.to_string(),
+            "posting_json_metadata": "", "extensions": []}},
+        {"type": "account_update2_operation", "value": {
+            "account": "nectarflower", "json_metadata": "",
+            "posting_json_metadata": "{}", "extensions": []}},
+        {"type": "account_update2_operation", "value": {
+            "account": "nectarflower", "json_metadata": metadata.to_string(),
+            "posting_json_metadata": "", "extensions": []}},
+    ]));
+
+    let mut client = Client::new();
+    let events = NodeListWatcher::new().apply_block(&mut client, 7, &block);
+    assert_eq!(events.len(), 1);
+    let NodeWatchEvent::Updated {
+        block_num, change, ..
+    } = &events[0]
+    else {
+        panic!("expected an update, got {events:?}");
+    };
+    assert_eq!(*block_num, 7);
+    assert_eq!(change.added, ["https://a", "https://b"]);
+    assert_eq!(client.nodes, ["https://a", "https://b"]);
+
+    let bad = block_with(json!([
+        {"type": "account_update_operation", "value": {
+            "account": "nectarflower", "json_metadata": "not json",
+            "memo_key": "STM1111111111111111111111111111111114T1Anm"}},
+    ]));
+    let events = NodeListWatcher::new().apply_block(&mut client, 8, &bad);
+    assert!(matches!(events[..], [NodeWatchEvent::Rejected { .. }]));
+    assert_eq!(client.nodes, ["https://a", "https://b"]);
+}
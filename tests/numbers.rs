@@ -0,0 +1,124 @@
+use nectarflower_rs::numbers::{i128_string, u128_string, ExactNumber};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Totals {
+    #[serde(with = "u128_string")]
+    vests: u128,
+    #[serde(with = "i128_string")]
+    delta: i128,
+}
+
+fn totals(vests: serde_json::Value, delta: serde_json::Value) -> Result<Totals, String> {
+    serde_json::from_value(json!({ "vests": vests, "delta": delta })).map_err(|e| e.to_string())
+}
+
+#[test]
+fn integers_decode_from_numbers_and_strings() {
+    let t = totals(json!(42), json!(-7)).unwrap();
+    assert_eq!((t.vests, t.delta), (42, -7));
+    let t = totals(json!("0"), json!("-0")).unwrap();
+    assert_eq!((t.vests, t.delta), (0, 0));
+    let t = totals(json!(u64::MAX), json!(i64::MIN)).unwrap();
+    assert_eq!((t.vests, t.delta), (u64::MAX as u128, i64::MIN as i128));
+    let t = totals(json!(u128::MAX.to_string()), json!(i128::MIN.to_string())).unwrap();
+    assert_eq!((t.vests, t.delta), (u128::MAX, i128::MIN));
+}
+
+#[test]
+fn integers_serialize_as_strings() {
+    let t = Totals {
+        vests: u128::MAX,
+        delta: -1,
+    };
+    assert_eq!(
+        serde_json::to_value(&t).unwrap(),
+        json!({ "vests": u128::MAX.to_string(), "delta": "-1" })
+    );
+    let back: Totals = serde_json::from_value(serde_json::to_value(&t).unwrap()).unwrap();
+    assert_eq!((back.vests, back.delta), (u128::MAX, -1));
+}
+
+#[test]
+fn invalid_and_out_of_range_integers_are_errors() {
+    let err = totals(json!("12a"), json!(0)).unwrap_err();
+    assert!(err.starts_with("Invalid integer '12a'"), "{err}");
+    let err = totals(json!(""), json!(0)).unwrap_err();
+    assert!(err.starts_with("Invalid integer ''"), "{err}");
+    let err = totals(json!(-1), json!(0)).unwrap_err();
+    assert!(err.starts_with("Invalid integer '-1'"), "{err}");
+    let too_big = format!("{}0", u128::MAX);
+    let err = totals(json!(too_big), json!(0)).unwrap_err();
+    assert!(
+        err.starts_with(&format!("Invalid integer '{too_big}'")),
+        "{err}"
+    );
+    assert_eq!(
+        totals(json!(null), json!(0)).unwrap_err(),
+        "Invalid integer: null"
+    );
+    assert_eq!(
+        totals(json!(true), json!(0)).unwrap_err(),
+        "Invalid integer: true"
+    );
+}
+
+#[test]
+fn fractions_are_not_rounded_to_integers() {
+    assert_eq!(
+        totals(json!(1.5), json!(0)).unwrap_err(),
+        "Invalid integer: 1.5"
+    );
+    assert!(totals(json!(0), json!("-0.5")).is_err());
+}
+
+#[test]
+fn integers_above_u64_keep_every_digit_or_fail() {
+    let raw = r#"{"vests": 123456789012345678901234567890, "delta": 1}"#;
+    let decoded = serde_json::from_str::<serde_json::Value>(raw)
+        .map_err(|e| e.to_string())
+        .and_then(|v| serde_json::from_value::<Totals>(v).map_err(|e| e.to_string()));
+    if cfg!(feature = "arbitrary_precision") {
+        assert_eq!(decoded.unwrap().vests, 123456789012345678901234567890);
+    } else {
+        let err = decoded.unwrap_err();
+        assert!(err.contains("may have lost precision"), "{err}");
+    }
+}
+
+#[test]
+fn exact_numbers_are_canonical() {
+    let n: ExactNumber = "000123".parse().unwrap();
+    assert_eq!(n.as_str(), "123");
+    assert_eq!(n, ExactNumber::from(123u128));
+    assert_eq!("-0".parse::<ExactNumber>().unwrap().as_str(), "0");
+    assert_eq!(
+        "-00".parse::<ExactNumber>().unwrap(),
+        ExactNumber::default()
+    );
+    assert_eq!(ExactNumber::default().as_str(), "0");
+    assert_eq!("-0042".parse::<ExactNumber>().unwrap().to_i128(), Some(-42));
+    assert_eq!("-0042".parse::<ExactNumber>().unwrap().to_u128(), None);
+
+    let huge = format!("{}{}", u128::MAX, u128::MAX);
+    let n: ExactNumber = huge.parse().unwrap();
+    assert_eq!(n.to_string(), huge);
+    assert_eq!(n.to_u128(), None);
+    assert_eq!(serde_json::to_value(&n).unwrap(), json!(huge));
+    assert_eq!(
+        serde_json::from_value::<ExactNumber>(json!(huge)).unwrap(),
+        n
+    );
+    assert_eq!(
+        serde_json::from_value::<ExactNumber>(json!(7)).unwrap(),
+        ExactNumber::from(7i128)
+    );
+
+    for bad in ["", "-", "+1", " 1", "1 ", "1.0", "1e3", "١٢٣"] {
+        assert_eq!(
+            bad.parse::<ExactNumber>().unwrap_err(),
+            format!("Invalid integer: '{bad}'")
+        );
+    }
+}
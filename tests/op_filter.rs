@@ -0,0 +1,25 @@
+//! Operation filter masks for account history
+
+use nectarflower_rs::op_filter::op_type_id;
+use nectarflower_rs::{OpTypeSet, Operation};
+use serde_json::json;
+
+#[test]
+fn op_type_set_compiles_to_low_and_high_masks() {
+    let set = OpTypeSet::from_names(&["transfer", "claim_reward_balance_operation"]).unwrap();
+    assert_eq!(set.low(), (1 << 2) | (1 << 39));
+    assert_eq!(set.high(), 0);
+
+    // Virtual operations from id 64 on land in the high mask
+    assert_eq!(op_type_id("producer_reward"), Some(64));
+    let rewards = OpTypeSet::rewards();
+    assert_eq!(rewards.high(), 1 << 2 | 1);
+    assert!(rewards.contains("proposal_pay"));
+
+    let both = set.union(rewards);
+    assert!(both.matches(&Operation::new("transfer_operation", json!({}))));
+    assert!(!both.matches(&Operation::new("vote_operation", json!({}))));
+    assert_eq!(both.names().next(), Some("transfer"));
+
+    assert!(OpTypeSet::new().with("not_an_op").is_err());
+}
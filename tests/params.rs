@@ -0,0 +1,24 @@
+use nectarflower_rs::params;
+use serde_json::json;
+
+#[test]
+fn params_macro_builds_named_and_positional_params() {
+    let account = "alice";
+    assert_eq!(
+        params!(get_account_history {
+            account: account,
+            start: -1,
+            limit: 100u32,
+        }),
+        json!({"account": "alice", "start": -1, "limit": 100})
+    );
+    assert_eq!(params!(get_version {}), json!({}));
+    assert_eq!(
+        params! { tag: "hive", observer: Option::<&str>::None },
+        json!({"tag": "hive", "observer": null})
+    );
+    assert_eq!(
+        params!([account, vec!["a", "b"], 3]),
+        json!(["alice", ["a", "b"], 3])
+    );
+}
@@ -0,0 +1,98 @@
+use nectarflower_rs::comment::edit_body;
+use nectarflower_rs::patch::patch_text;
+
+#[test]
+fn identical_texts_make_no_patch() {
+    assert_eq!(patch_text("", "").unwrap(), "");
+    assert_eq!(patch_text("same", "same").unwrap(), "");
+}
+
+#[test]
+fn insertion() {
+    assert_eq!(
+        patch_text("The quick fox", "The quick brown fox").unwrap(),
+        "@@ -3,11 +3,17 @@\n e quick \n+brown \n fox\n"
+    );
+}
+
+#[test]
+fn deletion() {
+    assert_eq!(
+        patch_text("The quick brown fox", "The quick fox").unwrap(),
+        "@@ -7,13 +7,7 @@\n ick \n-brown \n fox\n"
+    );
+}
+
+#[test]
+fn matches_diff_match_patch_suite() {
+    // From diff-match-patch's own testPatchMake
+    let text1 = "The quick brown fox jumps over the lazy dog.";
+    let text2 = "That quick brown fox jumped over a lazy dog.";
+    assert_eq!(
+        patch_text(text2, text1).unwrap(),
+        "@@ -1,8 +1,7 @@\n Th\n-at\n+e\n  qui\n@@ -21,17 +21,18 @@\n jump\n-ed\n+s\n  over \n-a\n+the\n  laz\n"
+    );
+    assert_eq!(
+        patch_text(text1, text2).unwrap(),
+        "@@ -1,11 +1,12 @@\n Th\n-e\n+at\n  quick b\n@@ -22,18 +22,17 @@\n jump\n-s\n+ed\n  over \n-the\n+a\n  laz\n"
+    );
+    assert_eq!(
+        patch_text(r"`1234567890-=[]\;',./", "~!@#$%^&*()_+{}|:\"<>?").unwrap(),
+        "@@ -1,21 +1,21 @@\n-%601234567890-=%5B%5D%5C;',./\n+~!@#$%25%5E&*()_+%7B%7D%7C:%22%3C%3E?\n"
+    );
+}
+
+#[test]
+fn unicode_is_percent_encoded_and_counted_by_character() {
+    assert_eq!(
+        patch_text("héllo wörld", "héllo wörld 🌍").unwrap(),
+        "@@ -4,8 +4,10 @@\n lo w%C3%B6rld\n+ %F0%9F%8C%8D\n"
+    );
+}
+
+#[test]
+fn context_grows_until_unique() {
+    // diff-match-patch's long string with repeats
+    let text1 = "abcdef".repeat(100);
+    let text2 = format!("{text1}123");
+    assert_eq!(
+        patch_text(&text1, &text2).unwrap(),
+        "@@ -573,28 +573,31 @@\n cdefabcdefabcdefabcdefabcdef\n+123\n"
+    );
+}
+
+#[test]
+fn equality_of_twice_the_margin_splits_patches() {
+    assert_eq!(
+        patch_text("abcdefgh12345678ijklmnop", "abcdefghX12345678Yijklmnop").unwrap(),
+        "@@ -5,16 +5,17 @@\n efgh\n+X\n 12345678\n ijkl\n\
+         @@ -10,16 +10,17 @@\n 12345678\n+Y\n ijklmnop\n"
+    );
+}
+
+#[test]
+fn large_rewrites_finish_quickly() {
+    let old: String = (0..10_000)
+        .map(|i| char::from(b'a' + (i * 7 % 26) as u8))
+        .collect();
+    let new: String = (0..10_000)
+        .map(|i| char::from(b'a' + (i * 11 % 26) as u8))
+        .collect();
+    let started = std::time::Instant::now();
+    let body = edit_body(&old, &new);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(body, new);
+}
+
+#[test]
+fn small_edits_of_long_bodies_are_sent_as_patches() {
+    let old: String = (0..2_000)
+        .map(|i| format!("Line {i} of the post\n"))
+        .collect();
+    let new = old.replace("Line 1000 of", "Line one thousand of");
+    let body = edit_body(&old, &new);
+    assert_eq!(
+        body,
+        "@@ -20892,12 +20892,20 @@\n ine \n-1000\n+one thousand\n  of \n"
+    );
+}
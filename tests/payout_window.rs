@@ -0,0 +1,40 @@
+//! Reporting posts as they enter the final hours before payout
+
+use nectarflower_rs::timestamp::parse_timestamp;
+use nectarflower_rs::{ChainComment, PayoutWindowScanner};
+use serde_json::json;
+
+fn comment(author: &str, parent_author: &str, cashout_time: &str) -> ChainComment {
+    serde_json::from_value(json!({
+        "author": author,
+        "permlink": "post",
+        "parent_author": parent_author,
+        "cashout_time": cashout_time,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn posts_are_reported_once_when_entering_the_window() {
+    let now = parse_timestamp("2024-01-08T00:00:00").unwrap();
+    let mut scanner = PayoutWindowScanner::new(6 * 3_600);
+    let comments = || {
+        vec![
+            comment("alice", "", "2024-01-08T05:00:00"),
+            comment("bob", "alice", "2024-01-08T05:30:00"),
+            comment("carol", "", "2024-01-08T07:00:00"),
+        ]
+    };
+
+    let entered = scanner.process(now, comments());
+    let authors: Vec<_> = entered.iter().map(|c| c.author.as_str()).collect();
+    assert_eq!(authors, ["alice"]);
+
+    // An hour later carol is within six hours too; alice was already reported
+    let entered = scanner.process(now + 3_600, comments());
+    let authors: Vec<_> = entered.iter().map(|c| c.author.as_str()).collect();
+    assert_eq!(authors, ["carol"]);
+
+    let mut with_replies = PayoutWindowScanner::new(6 * 3_600).include_comments();
+    assert_eq!(with_replies.process(now, comments()).len(), 2);
+}
@@ -0,0 +1,175 @@
+//! Pending payouts of an account's posts and comments
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::payouts::PendingPayout;
+use nectarflower_rs::posts::{Post, BRIDGE_POSTS_LIMIT};
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+fn post(permlink: &str, created: &str, pending: &str, beneficiaries: Value) -> Value {
+    json!({
+        "post_id": 1,
+        "author": "alice",
+        "permlink": permlink,
+        "category": "hive",
+        "title": permlink,
+        "body": "",
+        "created": created,
+        "depth": 0,
+        "children": 0,
+        "is_paidout": false,
+        "payout_at": "2024-01-10T00:00:00",
+        "payout": 0.0,
+        "pending_payout_value": pending,
+        "author_payout_value": "0.000 HBD",
+        "curator_payout_value": "0.000 HBD",
+        "beneficiaries": beneficiaries,
+    })
+}
+
+/// Serves `bridge.get_account_posts` per listing, paged like hivemind
+#[derive(Clone)]
+struct Bridge {
+    posts: Vec<Value>,
+    comments: Vec<Value>,
+    mock: MockTransport,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl Transport for Bridge {
+    fn send(&self, url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        if request["method"] != "bridge.get_account_posts" {
+            return self.mock.send(url, body);
+        }
+        let params = request["params"].clone();
+        self.requests.lock().unwrap().push(params.clone());
+        let listing = match params["sort"].as_str().unwrap() {
+            "posts" => &self.posts,
+            "comments" => &self.comments,
+            sort => panic!("unexpected sort {sort}"),
+        };
+        // The start post is included, as hivemind does
+        let skip = listing
+            .iter()
+            .position(|p| p["permlink"] == params["start_permlink"])
+            .unwrap_or(0);
+        let limit = params["limit"].as_u64().unwrap() as usize;
+        let page: Vec<&Value> = listing.iter().skip(skip).take(limit).collect();
+        let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": page });
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+fn client(posts: Vec<Value>, comments: Vec<Value>) -> (Client, Bridge) {
+    let bridge = Bridge {
+        posts,
+        comments,
+        mock: MockTransport::new().respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-08T00:00:00",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        ),
+        requests: Arc::default(),
+    };
+    (Client::builder().transport(bridge.clone()).build(), bridge)
+}
+
+#[test]
+fn curators_are_paid_before_beneficiaries() {
+    let split = |pending: &str, beneficiaries: Value| {
+        let post: Post =
+            serde_json::from_value(post("p", "2024-01-07T00:00:00", pending, beneficiaries))
+                .unwrap();
+        let payout = PendingPayout::from_post(&post);
+        (
+            payout.curator_share.to_string(),
+            payout.beneficiary_share.to_string(),
+            payout.author_share.to_string(),
+        )
+    };
+    let owned = |s: [&str; 3]| (s[0].to_string(), s[1].to_string(), s[2].to_string());
+
+    // Beneficiaries take their weight from the author's half
+    assert_eq!(
+        split(
+            "10.000 HBD",
+            json!([{ "account": "bob", "weight": 500 }, { "account": "carol", "weight": 500 }])
+        ),
+        owned(["5.000 HBD", "0.500 HBD", "4.500 HBD"])
+    );
+    // Weights above 100% are capped, leaving the author nothing
+    assert_eq!(
+        split(
+            "1.001 HBD",
+            json!([{ "account": "bob", "weight": 10_000 }, { "account": "carol", "weight": 1_000 }])
+        ),
+        owned(["0.500 HBD", "0.501 HBD", "0.000 HBD"])
+    );
+    // Odd amounts round the curator share down
+    assert_eq!(
+        split("0.003 HBD", json!([])),
+        owned(["0.001 HBD", "0.000 HBD", "0.002 HBD"])
+    );
+}
+
+#[test]
+fn posts_and_comments_in_the_window_are_summed() {
+    // 25 posts four hours apart, all in the window, over two pages
+    let posts: Vec<Value> = (0..25)
+        .map(|i| {
+            let created = format!("2024-01-0{}T{:02}:00:00", 7 - i * 4 / 24, 20 - i * 4 % 24);
+            post(&format!("p{i:02}"), &created, "1.000 HBD", json!([]))
+        })
+        .collect();
+    let mut paid = post("paid", "2024-01-06T00:00:00", "0.000 HBD", json!([]));
+    paid["is_paidout"] = json!(true);
+    let comments = vec![
+        post(
+            "c1",
+            "2024-01-07T00:00:00",
+            "2.000 HBD",
+            json!([{ "account": "bob", "weight": 5_000 }]),
+        ),
+        paid,
+        // Older than the window, ending the listing
+        post("old", "2023-12-31T23:59:59", "9.000 HBD", json!([])),
+        post("older", "2023-12-30T00:00:00", "9.000 HBD", json!([])),
+    ];
+    let (client, bridge) = client(posts, comments);
+
+    let payouts = client.pending_payouts("alice").unwrap();
+    assert_eq!(payouts.account, "alice");
+    let permlinks: Vec<_> = payouts.items.iter().map(|i| i.permlink.as_str()).collect();
+    assert_eq!(permlinks.len(), 26);
+    assert_eq!(
+        permlinks[19..],
+        ["p19", "p20", "p21", "p22", "p23", "p24", "c1"]
+    );
+    assert_eq!(payouts.total_pending.to_string(), "27.000 HBD");
+    assert_eq!(payouts.total_author.to_string(), "13.000 HBD");
+
+    let requests = bridge.requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0]["limit"], BRIDGE_POSTS_LIMIT);
+    assert_eq!(requests[1]["start_permlink"], "p19");
+    assert_eq!(requests[2]["sort"], "comments");
+}
+
+#[test]
+fn accounts_without_posts_have_nothing_pending() {
+    let (client, _) = client(vec![], vec![]);
+    let payouts = client.pending_payouts("alice").unwrap();
+    assert!(payouts.items.is_empty());
+    assert_eq!(payouts.total_pending.to_string(), "0.000 HBD");
+}
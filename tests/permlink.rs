@@ -0,0 +1,110 @@
+use nectarflower_rs::permlink::{
+    generate_permlink, generate_reply_permlink, permlink_suffix, slugify, validate_permlink,
+    MAX_PERMLINK_LENGTH,
+};
+
+#[test]
+fn titles_are_slugified_like_condenser() {
+    assert_eq!(slugify("Hello, World!"), "hello-world");
+    assert_eq!(
+        slugify("  --Spaces   and -- dashes--  "),
+        "spaces-and-dashes"
+    );
+    assert_eq!(slugify("Crème brûlée & Straße"), "creme-brulee-and-strasse");
+    assert_eq!(slugify("Привет мир"), "privet-mir");
+    assert_eq!(slugify("Ёжик щука"), "yozhik-schuka");
+    assert_eq!(slugify("ÆØÅ Œuvre"), "aeoa-oeuvre");
+    assert_eq!(slugify("Top 10 tips"), "top-10-tips");
+}
+
+#[test]
+fn titles_without_latin_text_have_empty_slugs() {
+    assert_eq!(slugify(""), "");
+    assert_eq!(slugify("!!! ???"), "");
+    assert_eq!(slugify("你好世界"), "");
+    assert_eq!(slugify("🐝🍯"), "");
+    // Hard signs transliterate to nothing without leaving a hyphen
+    assert_eq!(slugify("ъ"), "");
+}
+
+#[test]
+fn suffixes_are_valid_timestamps() {
+    let suffix = permlink_suffix();
+    assert_eq!(suffix.len(), "20240101t120000123z".len(), "{suffix}");
+    assert_eq!(&suffix[8..9], "t");
+    assert!(suffix.ends_with('z'));
+    assert!(validate_permlink(&suffix).is_ok());
+}
+
+#[test]
+fn generated_permlinks_end_with_a_suffix() {
+    let permlink = generate_permlink("My First Post");
+    let suffix_len = permlink_suffix().len();
+    assert!(permlink.starts_with("my-first-post-"), "{permlink}");
+    assert_eq!(permlink.len(), "my-first-post-".len() + suffix_len);
+    assert!(validate_permlink(&permlink).is_ok());
+
+    // An empty slug leaves only the suffix
+    for title in ["", "你好", "🐝"] {
+        let permlink = generate_permlink(title);
+        assert_eq!(permlink.len(), suffix_len, "{permlink}");
+        assert!(validate_permlink(&permlink).is_ok());
+    }
+}
+
+#[test]
+fn over_long_titles_are_truncated_to_the_limit() {
+    let permlink = generate_permlink(&"word ".repeat(200));
+    assert!(permlink.len() <= MAX_PERMLINK_LENGTH, "{}", permlink.len());
+    assert!(validate_permlink(&permlink).is_ok());
+    // Truncation never leaves a double hyphen before the suffix
+    assert!(!permlink.contains("--"), "{permlink}");
+
+    let permlink = generate_permlink(&"ж".repeat(1_000));
+    assert_eq!(permlink.len(), 255);
+    assert!(permlink.starts_with("zhzh"));
+    assert!(validate_permlink(&permlink).is_ok());
+}
+
+#[test]
+fn reply_permlinks_use_the_re_prefix() {
+    let permlink = generate_reply_permlink("alice.bob", "my-first-post");
+    assert!(
+        permlink.starts_with("re-alice-bob-my-first-post-"),
+        "{permlink}"
+    );
+    assert!(validate_permlink(&permlink).is_ok());
+
+    let long = "a".repeat(MAX_PERMLINK_LENGTH);
+    let permlink = generate_reply_permlink("alice", &long);
+    assert!(permlink.starts_with("re-alice-aaa"));
+    assert!(permlink.len() <= MAX_PERMLINK_LENGTH);
+    assert!(validate_permlink(&permlink).is_ok());
+}
+
+#[test]
+fn invalid_permlinks_are_rejected() {
+    assert_eq!(validate_permlink("").unwrap_err(), "Permlink is empty");
+    assert!(validate_permlink("ok-permlink-123").is_ok());
+    assert!(validate_permlink(&"a".repeat(MAX_PERMLINK_LENGTH)).is_ok());
+    assert_eq!(
+        validate_permlink(&"a".repeat(MAX_PERMLINK_LENGTH + 1)).unwrap_err(),
+        "Permlink is 256 characters, above the limit of 255"
+    );
+    assert_eq!(
+        validate_permlink("Upper").unwrap_err(),
+        "Permlink 'Upper' contains invalid character 'U'"
+    );
+    assert_eq!(
+        validate_permlink("with space").unwrap_err(),
+        "Permlink 'with space' contains invalid character ' '"
+    );
+    assert_eq!(
+        validate_permlink("snake_case").unwrap_err(),
+        "Permlink 'snake_case' contains invalid character '_'"
+    );
+    assert_eq!(
+        validate_permlink("café").unwrap_err(),
+        "Permlink 'café' contains invalid character 'é'"
+    );
+}
@@ -0,0 +1,83 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::{HistoryEntry, PostRewards};
+use serde_json::{json, Value};
+
+fn entry(op_type: &str, value: Value) -> HistoryEntry {
+    HistoryEntry {
+        index: 0,
+        trx_id: "0".repeat(40),
+        block: 80_000_000,
+        trx_in_block: 4_294_967_295,
+        op_in_trx: 0,
+        virtual_op: true,
+        timestamp: "2024-01-08T00:00:00".to_string(),
+        op: Operation::new(op_type, value),
+    }
+}
+
+#[test]
+fn payout_ops_add_up_to_the_final_split() {
+    let entries = vec![
+        entry(
+            "curation_reward_operation",
+            json!({"curator": "c1", "reward": "100.000000 VESTS",
+                   "comment_author": "alice", "comment_permlink": "post"}),
+        ),
+        entry(
+            "curation_reward_operation",
+            json!({"curator": "c2", "reward": "50.000000 VESTS",
+                   "comment_author": "alice", "comment_permlink": "post"}),
+        ),
+        entry(
+            "curation_reward_operation",
+            json!({"curator": "c3", "reward": "9.000000 VESTS",
+                   "comment_author": "bob", "comment_permlink": "post"}),
+        ),
+        entry(
+            "comment_benefactor_reward_operation",
+            json!({"benefactor": "dev", "author": "alice", "permlink": "post",
+                   "hbd_payout": "0.500 HBD", "hive_payout": "0.000 HIVE",
+                   "vesting_payout": "20.000000 VESTS"}),
+        ),
+        entry(
+            "author_reward_operation",
+            json!({"author": "alice", "permlink": "post",
+                   "hbd_payout": "1.250 HBD", "hive_payout": "0.000 HIVE",
+                   "vesting_payout": "75.000000 VESTS"}),
+        ),
+        entry(
+            "comment_reward_operation",
+            json!({"author": "alice", "permlink": "post",
+                   "total_payout_value": "3.000 HBD"}),
+        ),
+    ];
+    let rewards = PostRewards::from_entries("alice", "post", &entries)
+        .unwrap()
+        .unwrap();
+    assert_eq!(rewards.block, 80_000_000);
+    assert_eq!(
+        rewards.author_reward.hbd,
+        Asset::new(1_250, AssetSymbol::Hbd)
+    );
+    assert_eq!(
+        rewards.author_reward.vests,
+        Asset::new(75_000_000, AssetSymbol::Vests)
+    );
+    assert_eq!(rewards.beneficiaries.len(), 1);
+    assert_eq!(rewards.beneficiaries[0].0, "dev");
+    assert_eq!(rewards.curators, 2);
+    assert_eq!(
+        rewards.curators_vests,
+        Asset::new(150_000_000, AssetSymbol::Vests)
+    );
+    assert_eq!(rewards.liquid_hbd(), Asset::new(1_750, AssetSymbol::Hbd));
+    assert_eq!(
+        rewards.total_payout_value,
+        Some(Asset::new(3_000, AssetSymbol::Hbd))
+    );
+
+    assert!(PostRewards::from_entries("carol", "post", &entries)
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,125 @@
+//! Post discovery queries over hivemind's bridge API
+
+use nectarflower_rs::posts::{AccountPostSort, PostQuery, PostSort, BRIDGE_POSTS_LIMIT};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn post(permlink: &str, metadata: Value) -> Value {
+    json!({
+        "post_id": 1,
+        "author": "alice",
+        "permlink": permlink,
+        "category": "hive-174578",
+        "title": "Title",
+        "body": "Body",
+        "json_metadata": metadata,
+        "created": "2024-01-01T00:00:00",
+        "depth": 0,
+        "children": 2,
+        "is_paidout": false,
+        "payout_at": "2024-01-08T00:00:00",
+        "payout": 1.5,
+        "pending_payout_value": "1.500 HBD",
+        "author_payout_value": "0.000 HBD",
+        "curator_payout_value": "0.000 HBD",
+        "beneficiaries": [{ "account": "bob", "weight": 500 }],
+    })
+}
+
+fn sent(mock: &MockTransport) -> (Value, Value) {
+    let (_, request) = mock.requests().pop().unwrap();
+    (request["method"].clone(), request["params"].clone())
+}
+
+#[test]
+fn ranked_queries_use_the_tag_and_sort() {
+    let mock =
+        MockTransport::new().respond("bridge.get_ranked_posts", json!([post("a", json!({}))]));
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let posts = client.query_posts(&PostQuery::new()).unwrap();
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts[0].beneficiaries[0].account, "bob");
+    assert_eq!(
+        sent(&mock),
+        (
+            json!("bridge.get_ranked_posts"),
+            json!({ "sort": "trending", "tag": "", "limit": BRIDGE_POSTS_LIMIT })
+        )
+    );
+
+    let query = PostQuery::new()
+        .sort(PostSort::PayoutComments)
+        .tag("photography")
+        .observer("carol")
+        .limit(100)
+        .start("alice", "a");
+    client.query_posts(&query).unwrap();
+    assert_eq!(
+        sent(&mock).1,
+        json!({
+            "sort": "payout_comments",
+            "tag": "photography",
+            "limit": BRIDGE_POSTS_LIMIT,
+            "observer": "carol",
+            "start_author": "alice",
+            "start_permlink": "a",
+        })
+    );
+    assert_eq!(query.start_post(), Some(("alice", "a")));
+}
+
+#[test]
+fn author_queries_filter_by_tag_locally() {
+    let mock = MockTransport::new().respond(
+        "bridge.get_account_posts",
+        json!([
+            post("tagged", json!({ "tags": ["travel", "photography"] })),
+            post("untagged", json!({ "tags": ["food"] })),
+            // Without tags the category counts as the only tag
+            post("category", json!({})),
+        ]),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let query = PostQuery::new()
+        .author("alice")
+        .account_sort(AccountPostSort::Blog)
+        .tag("photography")
+        .limit(5);
+    let posts = client.query_posts(&query).unwrap();
+    let permlinks: Vec<_> = posts.iter().map(|p| p.permlink.as_str()).collect();
+    assert_eq!(permlinks, ["tagged"]);
+    assert_eq!(
+        sent(&mock),
+        (
+            json!("bridge.get_account_posts"),
+            json!({ "sort": "blog", "account": "alice", "limit": 5 })
+        )
+    );
+
+    let posts = client
+        .query_posts(&PostQuery::new().author("alice").tag("hive-174578"))
+        .unwrap();
+    assert_eq!(posts[0].permlink, "category");
+    assert_eq!(posts[0].tags(), ["hive-174578"]);
+
+    let all = client
+        .query_posts(&PostQuery::new().author("alice"))
+        .unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(sent(&mock).1["sort"], "posts");
+}
+
+#[test]
+fn single_posts_and_errors() {
+    let mock = MockTransport::new()
+        .respond("bridge.get_post", post("a", json!({ "tags": ["travel"] })))
+        .respond_error("bridge.get_ranked_posts", -32602, "Invalid parameters");
+    let client = Client::builder().transport(mock).build();
+    let post = client.get_post("alice", "a").unwrap().unwrap();
+    assert_eq!(post.tags(), ["travel"]);
+    let err = client.query_posts(&PostQuery::new()).unwrap_err();
+    assert!(err.starts_with("Error querying posts:"), "{err}");
+}
@@ -0,0 +1,193 @@
+use nectarflower_rs::price::{
+    median, trimmed_mean, Aggregation, InternalMarket, JsonUrlSource, PriceOracle, PriceSource,
+};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+
+/// A source always answering with the same price or error
+struct Fixed(&'static str, Result<f64, &'static str>);
+
+impl PriceSource for Fixed {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn fetch_price(&self, _client: &Client) -> Result<f64, String> {
+        self.1.map_err(str::to_string)
+    }
+}
+
+fn close(a: Option<f64>, b: f64) -> bool {
+    a.is_some_and(|a| (a - b).abs() < 1e-12)
+}
+
+/// An oracle asking the internal market, then `sources`
+fn oracle(sources: Vec<Fixed>) -> PriceOracle {
+    sources
+        .into_iter()
+        .fold(PriceOracle::new(), |oracle, source| oracle.source(source))
+}
+
+/// A client whose internal market is down
+fn offline_client() -> Client {
+    Client::builder()
+        .transport(MockTransport::new().respond_error(
+            "market_history_api.get_ticker",
+            -32000,
+            "market down",
+        ))
+        .build()
+}
+
+#[test]
+fn median_handles_edge_cases() {
+    assert_eq!(median(&[]), None);
+    assert_eq!(median(&[0.3]), Some(0.3));
+    assert_eq!(median(&[0.5, 0.1, 0.3]), Some(0.3));
+    assert_eq!(median(&[0.4, 0.1, 0.3, 0.2]), Some(0.25));
+    assert_eq!(median(&[0.2, 0.2, 0.2, 9.0]), Some(0.2));
+}
+
+#[test]
+fn trimmed_mean_drops_outliers() {
+    assert_eq!(trimmed_mean(&[], 0.2), None);
+    assert_eq!(trimmed_mean(&[0.3], 0.4), Some(0.3));
+    assert!(close(trimmed_mean(&[0.1, 0.2, 0.3], 0.0), 0.2));
+    // One price in five is dropped from each end
+    let prices = [100.0, 0.2, 0.3, 0.25, 0.0001];
+    assert!(close(trimmed_mean(&prices, 0.2), 0.25));
+    // Too few prices to trim any keeps them all
+    assert!(close(trimmed_mean(&[0.1, 0.2, 0.6], 0.2), 0.3));
+    // Fractions of a half or more still keep the middle price
+    assert_eq!(trimmed_mean(&[0.1, 0.2, 0.9], 0.9), Some(0.2));
+    // Negative fractions trim nothing
+    assert!(close(trimmed_mean(&[0.2, 0.1], -1.0), 0.15));
+}
+
+#[test]
+fn oracle_aggregates_successful_quotes() {
+    let client = offline_client();
+    let report = oracle(vec![
+        Fixed("a", Ok(0.30)),
+        Fixed("b", Ok(0.20)),
+        Fixed("c", Ok(0.25)),
+        Fixed("d", Err("timeout")),
+    ])
+    .fetch(&client)
+    .unwrap();
+
+    assert_eq!(report.price, 0.25);
+    let names: Vec<&str> = report.quotes.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+    let failed: Vec<&str> = report.errors.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(failed, ["internal_market", "d"]);
+    assert!(
+        report.errors[0].1.contains("market down"),
+        "{:?}",
+        report.errors
+    );
+}
+
+#[test]
+fn unusable_prices_count_as_errors() {
+    let client = offline_client();
+    let report = oracle(vec![
+        Fixed("zero", Ok(0.0)),
+        Fixed("negative", Ok(-0.2)),
+        Fixed("nan", Ok(f64::NAN)),
+        Fixed("infinite", Ok(f64::INFINITY)),
+        Fixed("good", Ok(0.21)),
+    ])
+    .fetch(&client)
+    .unwrap();
+
+    assert_eq!(report.price, 0.21);
+    assert_eq!(report.quotes.len(), 1);
+    assert_eq!(report.errors.len(), 5);
+    assert!(report
+        .errors
+        .iter()
+        .filter(|(name, _)| name != "internal_market")
+        .all(|(_, e)| e.starts_with("Bad price")));
+}
+
+#[test]
+fn too_few_sources_is_an_error() {
+    let client = offline_client();
+    let err = oracle(vec![Fixed("a", Ok(0.3)), Fixed("b", Err("down"))])
+        .min_sources(2)
+        .fetch(&client)
+        .unwrap_err();
+    assert_eq!(err, "Only 1 of 2 required price sources responded");
+
+    // No source at all still needs one quote
+    let err = oracle(vec![]).min_sources(0).fetch(&client).unwrap_err();
+    assert_eq!(err, "Only 0 of 1 required price sources responded");
+}
+
+#[test]
+fn trimmed_mean_aggregation_ignores_a_bad_source() {
+    let client = offline_client();
+    let report = oracle(vec![
+        Fixed("a", Ok(0.20)),
+        Fixed("b", Ok(0.21)),
+        Fixed("c", Ok(0.22)),
+        Fixed("d", Ok(0.23)),
+        Fixed("manipulated", Ok(5.0)),
+    ])
+    .aggregation(Aggregation::TrimmedMean(0.2))
+    .fetch(&client)
+    .unwrap();
+    assert!(close(Some(report.price), 0.22), "{}", report.price);
+}
+
+#[test]
+fn built_in_sources_parse_their_responses() {
+    let mock = MockTransport::new()
+        .respond(
+            "market_history_api.get_ticker",
+            json!({ "latest": "0.245" }),
+        )
+        .respond_url(
+            "https://feed.example/usd",
+            json!({ "hive": { "usd": 0.5 } }),
+        )
+        .respond_url(
+            "https://feed.example/str",
+            json!({ "hive": { "usd": "0.25" } }),
+        )
+        .respond_url(
+            "https://feed.example/bad",
+            json!({ "hive": { "usd": true } }),
+        );
+    let client = Client::builder().transport(mock).build();
+
+    assert_eq!(InternalMarket.fetch_price(&client), Ok(0.245));
+    let usd = JsonUrlSource::new("usd", "https://feed.example/usd", "/hive/usd");
+    assert_eq!(usd.fetch_price(&client), Ok(0.5));
+    let inverted = JsonUrlSource::new("inv", "https://feed.example/usd", "/hive/usd").scale(2.0);
+    assert_eq!(inverted.fetch_price(&client), Ok(1.0));
+    let string = JsonUrlSource::new("str", "https://feed.example/str", "/hive/usd");
+    assert_eq!(string.fetch_price(&client), Ok(0.25));
+
+    let missing = JsonUrlSource::new("usd", "https://feed.example/usd", "/hive/eur");
+    let err = missing.fetch_price(&client).unwrap_err();
+    assert_eq!(err, "No value at '/hive/eur' in usd response");
+    let bad = JsonUrlSource::new("bad", "https://feed.example/bad", "/hive/usd");
+    assert_eq!(
+        bad.fetch_price(&client).unwrap_err(),
+        "Invalid price from bad: true"
+    );
+    let unknown = JsonUrlSource::new("gone", "https://feed.example/gone", "/hive/usd");
+    assert!(unknown.fetch_price(&client).is_err());
+}
+
+#[test]
+fn unparseable_ticker_is_an_error() {
+    let mock =
+        MockTransport::new().respond("market_history_api.get_ticker", json!({ "latest": "" }));
+    let client = Client::builder().transport(mock).build();
+    let err = InternalMarket.fetch_price(&client).unwrap_err();
+    assert!(err.starts_with("Invalid ticker price ''"), "{err}");
+}
@@ -0,0 +1,31 @@
+//! RIPEMD-160 and public key decoding
+
+use nectarflower_rs::crypto::{decode_public_key, ripemd160, to_hex};
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+
+#[test]
+fn ripemd160_matches_reference_vectors() {
+    assert_eq!(
+        to_hex(&ripemd160(b"")),
+        "9c1185a5c5e9fc54612808977ee8f548b2258d31"
+    );
+    assert_eq!(
+        to_hex(&ripemd160(b"abc")),
+        "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+    );
+    assert_eq!(
+        to_hex(&ripemd160(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        "12a053384a9c0c88e405a06c27dcf49ada62eb2b"
+    );
+}
+
+#[test]
+fn public_keys_decode_with_checksum() {
+    assert_eq!(decode_public_key(DISABLED_SIGNING_KEY).unwrap(), [0; 33]);
+    let mut corrupted = DISABLED_SIGNING_KEY.to_string();
+    corrupted.replace_range(corrupted.len() - 1.., "n");
+    assert!(decode_public_key(&corrupted).is_err());
+    assert!(decode_public_key("XYZ111").is_err());
+}
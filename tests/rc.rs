@@ -0,0 +1,158 @@
+//! Resource credit manabars, transaction sizes and resource estimates
+
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::rc::{RcAccount, ResourceUsage, TransactionBudget, RC_REGENERATION_SECS};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, Transaction};
+use serde_json::json;
+
+fn transfer() -> Operation {
+    Operation::new(
+        "transfer_operation",
+        json!({ "from": "foo", "to": "bar", "amount": "1.000 HIVE", "memo": "wedding present" }),
+    )
+}
+
+fn tx(operations: Vec<Operation>) -> Transaction {
+    Transaction {
+        ref_block_num: 1234,
+        ref_block_prefix: 1122334455,
+        expiration: "2017-07-15T16:51:19".to_string(),
+        operations,
+        extensions: vec![],
+        signatures: vec![],
+    }
+}
+
+fn resource_params() -> serde_json::Value {
+    json!({
+        "resource_names": ["resource_history_bytes", "resource_state_bytes", "resource_execution_time"],
+        "size_info": {
+            "resource_state_bytes": {
+                "transaction_object_base_size": 35_000,
+                "transaction_object_byte_size": "174",
+            },
+            "resource_execution_time": {
+                "transaction_time": 15_000,
+                "transfer_operation_exec_time": 9_000,
+                "vote_time": 4_000,
+            },
+        },
+    })
+}
+
+#[test]
+fn manabars_regenerate_up_to_the_maximum() {
+    let account: RcAccount = serde_json::from_value(json!({
+        "account": "alice",
+        "rc_manabar": { "current_mana": "1000", "last_update_time": 1_000 },
+        "max_rc": 11_000,
+    }))
+    .unwrap();
+    assert_eq!(account.current_mana(1_000), 1_000);
+    // A clock behind the last update regenerates nothing
+    assert_eq!(account.current_mana(0), 1_000);
+    let half = 1_000 + RC_REGENERATION_SECS / 2;
+    assert_eq!(account.current_mana(half), 6_500);
+    assert_eq!(
+        account.current_mana(1_000 + RC_REGENERATION_SECS * 2),
+        11_000
+    );
+    assert_eq!(account.percent(half), 6_500.0 * 100.0 / 11_000.0);
+
+    let empty = RcAccount {
+        max_rc: 0,
+        ..account
+    };
+    assert_eq!(empty.percent(half), 0.0);
+}
+
+#[test]
+fn transactions_measure_their_size_locally() {
+    // 53 bytes unsigned, as in dhive's transfer vector
+    let mut tx = tx(vec![transfer()]);
+    assert_eq!(tx.to_bytes().unwrap().len(), 53);
+    assert_eq!(tx.serialized_size(0).unwrap(), 53 + 1);
+    assert_eq!(tx.serialized_size(2).unwrap(), 53 + 1 + 2 * 65);
+    // Existing signatures count when there are more of them
+    tx.signatures = vec!["1f".repeat(65); 3];
+    assert_eq!(tx.serialized_size(1).unwrap(), 53 + 1 + 3 * 65);
+    assert_eq!(
+        tx.serialized_size(1).unwrap(),
+        tx.to_signed_bytes().unwrap().len()
+    );
+
+    let unsupported = self::tx(vec![Operation::new("pow_operation", json!({}))]);
+    let err = unsupported.serialized_size(1).unwrap_err();
+    assert!(err.contains("pow"), "{err}");
+}
+
+#[test]
+fn clients_measure_unsupported_operations_with_the_node() {
+    // The node's bytes for the unsigned transaction end with an empty signature list
+    let mock = MockTransport::new().respond(
+        "database_api.get_transaction_hex",
+        json!({ "hex": "00".repeat(40) }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    let unsupported = tx(vec![Operation::new("pow_operation", json!({}))]);
+    assert_eq!(
+        client.serialized_size(&unsupported, 1).unwrap(),
+        39 + 1 + 65
+    );
+
+    let supported = tx(vec![transfer()]);
+    assert_eq!(client.serialized_size(&supported, 1).unwrap(), 53 + 1 + 65);
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn estimates_use_the_chain_constants() {
+    let mock = MockTransport::new().respond("rc_api.get_resource_params", resource_params());
+    let client = Client::builder().transport(mock).build();
+    let vote = Operation::new(
+        "vote_operation",
+        json!({ "voter": "foo", "author": "bar", "permlink": "baz", "weight": 10_000 }),
+    );
+    let comment = Operation::new(
+        "comment_operation",
+        json!({
+            "parent_author": "", "parent_permlink": "hive", "author": "foo",
+            "permlink": "baz", "title": "", "body": "", "json_metadata": "",
+        }),
+    );
+    let tx = tx(vec![transfer(), vote, comment]);
+    let size = tx.serialized_size(1).unwrap();
+
+    let usage = client.estimate_resources(&tx, 1).unwrap();
+    assert_eq!(
+        usage,
+        ResourceUsage {
+            size: size as i64,
+            history_bytes: size as i64,
+            state_bytes: 35_000 + 174 * size as i64,
+            // Operations without a constant, like comments, cost nothing extra
+            execution_time: 15_000 + 9_000 + 4_000,
+        }
+    );
+
+    let budget = TransactionBudget::default()
+        .max_size(100)
+        .max_execution_time(usage.execution_time);
+    let (_, warnings) = client.check_budget(&tx, 1, &budget).unwrap();
+    assert_eq!(
+        warnings,
+        [format!("Transaction size {size} exceeds the budget of 100")]
+    );
+    assert!(TransactionBudget::default().check(&usage).is_empty());
+}
+
+#[test]
+fn resource_param_errors_are_reported() {
+    let mock = MockTransport::new().respond_error("rc_api.get_resource_params", -32000, "down");
+    let client = Client::builder().transport(mock).build();
+    let err = client
+        .estimate_resources(&tx(vec![transfer()]), 1)
+        .unwrap_err();
+    assert!(err.starts_with("Error fetching resource params:"), "{err}");
+}
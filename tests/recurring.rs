@@ -0,0 +1,123 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::market::BookOrder;
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::{
+    HistoryEntry, OrderBook, OrderSide, OrderStatus, OrderTracker, Recurrence, RecurringAction,
+};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn recurrence_skips_missed_runs_and_stops_after_count() {
+    let mut daily = Recurrence::every(Duration::from_secs(86_400))
+        .starting_at(1_000)
+        .times(2);
+    assert!(!daily.is_due(999));
+    assert!(daily.is_due(1_000));
+    // Three days late: one run, then back on the original schedule
+    daily.record_run(1_000 + 3 * 86_400 + 5);
+    assert_eq!(daily.next_run(), Some(1_000 + 4 * 86_400));
+    assert!(!daily.is_due(1_000 + 4 * 86_400 - 1));
+    daily.record_run(1_000 + 4 * 86_400);
+    assert!(daily.is_finished());
+    assert!(!daily.is_due(i64::MAX));
+}
+
+#[test]
+fn market_order_accepts_at_most_the_given_slippage() {
+    let order = |price: f64, hive: i64| BookOrder {
+        real_price: price,
+        hive,
+        hbd: (hive as f64 * price) as i64,
+        created: String::new(),
+    };
+    let book = OrderBook {
+        bids: vec![order(0.25, 100_000)],
+        asks: vec![order(0.26, 100_000)],
+    };
+    let buy = RecurringAction::MarketOrder {
+        side: OrderSide::Buy,
+        amount: Asset::new(10_400, AssetSymbol::Hbd),
+        max_slippage: 0.0,
+    };
+    let op = buy
+        .operation("alice", 7, Some(&book), "2024-01-02T00:00:00")
+        .unwrap();
+    assert_eq!(op.op_type, "limit_order_create_operation");
+    assert_eq!(op.value["orderid"], 7);
+    assert_eq!(
+        Asset::from_value(&op.value["min_to_receive"]).unwrap(),
+        Asset::new(40_000, AssetSymbol::Hive)
+    );
+    let convert = RecurringAction::Convert {
+        amount: Asset::new(1_000, AssetSymbol::Hive),
+    };
+    assert!(convert.operation("alice", 1, None, "").is_err());
+}
+
+fn virtual_op(index: u64, name: &str, value: serde_json::Value) -> HistoryEntry {
+    HistoryEntry {
+        index,
+        trx_id: "0".repeat(40),
+        block: 100 + index as u32,
+        trx_in_block: 0,
+        op_in_trx: 0,
+        virtual_op: true,
+        timestamp: "2024-01-01T00:00:00".to_string(),
+        op: Operation::new(name, value),
+    }
+}
+
+#[test]
+fn tracker_follows_partial_fills_and_cancellation() {
+    let mut tracker = OrderTracker::new("alice");
+    tracker.place(
+        1,
+        Asset::new(10_000, AssetSymbol::Hbd),
+        Asset::new(38_000, AssetSymbol::Hive),
+    );
+    tracker.place(
+        2,
+        Asset::new(5_000, AssetSymbol::Hbd),
+        Asset::new(19_000, AssetSymbol::Hive),
+    );
+    let fill = |index, open_pays: &str, current_pays: &str, orderid| {
+        virtual_op(
+            index,
+            "fill_order_operation",
+            json!({
+                "current_owner": "bob", "current_orderid": 9, "current_pays": current_pays,
+                "open_owner": "alice", "open_orderid": orderid, "open_pays": open_pays,
+            }),
+        )
+    };
+
+    let event = tracker
+        .apply(&fill(1, "4.000 HBD", "16.000 HIVE", 1))
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.status, OrderStatus::Open);
+    tracker
+        .apply(&fill(2, "6.000 HBD", "23.000 HIVE", 1))
+        .unwrap();
+    tracker
+        .apply(&fill(3, "1.000 HBD", "4.000 HIVE", 2))
+        .unwrap();
+    let cancelled = virtual_op(
+        4,
+        "limit_order_cancelled_operation",
+        json!({"seller": "alice", "orderid": 2, "amount_back": "4.000 HBD"}),
+    );
+    tracker.apply(&cancelled).unwrap();
+
+    let first = tracker.get(1).unwrap();
+    assert_eq!(first.status, OrderStatus::Filled);
+    assert_eq!(first.received, Asset::new(39_000, AssetSymbol::Hive));
+    assert_eq!(tracker.get(2).unwrap().status, OrderStatus::Cancelled);
+    assert_eq!(
+        tracker.get(2).unwrap().sold,
+        Asset::new(1_000, AssetSymbol::Hbd)
+    );
+    tracker.prune();
+    assert_eq!(tracker.orders().count(), 0);
+}
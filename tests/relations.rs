@@ -0,0 +1,49 @@
+//! Relation graphs built from fetched accounts and delegations
+
+use nectarflower_rs::delegations::VestingDelegation;
+use nectarflower_rs::{Account, Asset, RelationGraph, RelationKind};
+
+#[test]
+fn graph_links_auths_recovery_proxy_and_delegations() {
+    let mut account: Account =
+        serde_json::from_str(include_str!("../fixtures/account.json")).unwrap();
+    account.proxy = "bob".to_string();
+    let mut graph = RelationGraph::default();
+    graph.add_account(&account);
+    graph.add_delegation(&VestingDelegation {
+        delegator: "alice".into(),
+        delegatee: "carol".into(),
+        vesting_shares: Asset::from_value(&serde_json::json!("1000.000000 VESTS")).unwrap(),
+        min_delegation_time: "2024-01-01T00:00:00".into(),
+    });
+
+    let kinds: Vec<_> = graph
+        .relations_from("alice")
+        .map(|r| (r.to.as_str(), r.kind.label()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("peakd.app", "posting auth".to_string()),
+            ("steem", "recovery".to_string()),
+            ("bob", "proxy".to_string()),
+            ("carol", "delegation".to_string()),
+        ]
+    );
+    assert_eq!(
+        graph.relations_to("peakd.app").next().unwrap().kind,
+        RelationKind::AccountAuth {
+            role: "posting".into(),
+            weight: 1
+        }
+    );
+
+    let dot = graph.to_dot();
+    assert!(dot.contains("\"alice\" [shape=box];"));
+    assert!(dot.contains("\"steem\" [shape=ellipse];"));
+    assert!(dot.contains("\"alice\" -> \"carol\" [label=\"delegation 1000.000000 VESTS\"];"));
+
+    let json: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+    assert_eq!(json["relations"][0]["kind"], "account_auth");
+    assert_eq!(json["relations"][0]["role"], "posting");
+}
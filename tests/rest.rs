@@ -0,0 +1,101 @@
+//! Third-party REST APIs: failover, errors and rate limiting
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::{Client, RestApi};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[test]
+fn ecency_points_are_posted_for_the_user() {
+    let mock = MockTransport::new().respond_url(
+        "https://ecency.com/private-api/points",
+        json!({ "username": "alice", "points": "12.500", "unclaimed_points": "0.750" }),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    let api = RestApi::ecency();
+    assert_eq!(api.name(), "ecency");
+
+    let points = client.ecency_points(&api, "alice").unwrap();
+    assert_eq!(points.points, "12.500");
+    assert_eq!(points.unclaimed_points, "0.750");
+    assert_eq!(
+        mock.requests(),
+        [(
+            "https://ecency.com/private-api/points".to_string(),
+            json!({ "username": "alice" })
+        )]
+    );
+}
+
+#[test]
+fn failures_name_the_api_and_the_last_error() {
+    let mock = MockTransport::new()
+        .fail_node("https://a.example/points")
+        .respond_url("https://b.example/points", json!("not an object"));
+    let client = Client::builder().transport(mock.clone()).build();
+
+    // Trailing and leading slashes are joined once
+    let api = RestApi::new("test", &["https://a.example/", "https://b.example"]);
+    let err = api.get::<Vec<Value>>(&client, "/points", &[]).unwrap_err();
+    assert!(err.starts_with("Decode error:"), "{err}");
+    let urls: Vec<_> = mock.requests().into_iter().map(|(url, _)| url).collect();
+    assert_eq!(
+        urls,
+        ["https://a.example/points", "https://b.example/points"]
+    );
+
+    let api = RestApi::new("test", &["https://a.example"]);
+    let err = api.get::<Value>(&client, "points", &[]).unwrap_err();
+    assert!(err.starts_with("Error calling test points:"), "{err}");
+    assert!(err.contains("connection refused"), "{err}");
+
+    let api = RestApi::new("empty", &[]);
+    assert_eq!(
+        api.get::<Value>(&client, "points", &[]).unwrap_err(),
+        "Error calling empty points: No base URLs configured"
+    );
+}
+
+/// Refuses every body as too large, recording the URLs asked
+#[derive(Clone, Default)]
+struct Huge(Arc<Mutex<Vec<String>>>);
+
+impl Transport for Huge {
+    fn send(&self, url: &str, _body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        self.get(url)
+    }
+
+    fn get(&self, url: &str) -> Result<Vec<u8>, NectarError> {
+        self.0.lock().unwrap().push(url.to_string());
+        Err(NectarError::ResponseTooLarge {
+            length: Some(100),
+            limit: 10,
+        })
+    }
+}
+
+#[test]
+fn oversized_responses_are_not_retried_on_mirrors() {
+    let huge = Huge::default();
+    let client = Client::builder().transport(huge.clone()).build();
+    let api = RestApi::new("test", &["https://a.example", "https://b.example"]);
+    let err = api.get::<Value>(&client, "search", &[]).unwrap_err();
+    assert_eq!(err, "Response of 100 bytes exceeds the limit of 10 bytes");
+    assert_eq!(*huge.0.lock().unwrap(), ["https://a.example/search"]);
+}
+
+#[test]
+fn requests_are_spaced_by_the_minimum_interval() {
+    let mock = MockTransport::new().respond_url("https://a.example/feed", json!({}));
+    let client = Client::builder().transport(mock).build();
+    let api = RestApi::new("test", &["https://a.example"]).min_interval(Duration::from_millis(50));
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        api.get::<Value>(&client, "feed", &[]).unwrap();
+    }
+    // The first request goes out at once, the other two wait
+    assert!(started.elapsed() >= Duration::from_millis(100));
+}
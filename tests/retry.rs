@@ -0,0 +1,64 @@
+use nectarflower_rs::retry::RetryPolicy;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+/// A node answering 502 to its first request and a result to the second
+fn flaky_node() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let node = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let body = r#"{"jsonrpc":"2.0","result":7,"id":1}"#;
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let bad_gateway =
+            "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        for response in [bad_gateway, &ok] {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    node
+}
+
+#[test]
+fn transient_failures_are_retried_on_the_same_node() {
+    let client = Client::builder()
+        .nodes([flaky_node()])
+        .retry_policy(
+            RetryPolicy::new()
+                .attempts_per_node(2)
+                .base_delay(Duration::from_millis(10)),
+        )
+        .build();
+    let result: Value = client.call("database_api.get_config", json!({})).unwrap();
+    assert_eq!(result, 7);
+
+    let once = Client::builder().nodes([flaky_node()]).build();
+    let err = once
+        .call::<_, Value>("database_api.get_config", json!({}))
+        .unwrap_err();
+    assert_eq!(err.attempts().len(), 1);
+}
+
+#[test]
+fn delays_grow_up_to_the_cap_with_jitter() {
+    let policy = RetryPolicy::new()
+        .base_delay(Duration::from_millis(100))
+        .max_delay(Duration::from_millis(300))
+        .jitter(0.5);
+    for _ in 0..20 {
+        let first = policy.delay(0);
+        assert!(first > Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let capped = policy.delay(10);
+        assert!(capped > Duration::from_millis(150) && capped <= Duration::from_millis(300));
+    }
+    assert_eq!(policy.jitter(0.0).delay(1), Duration::from_millis(200));
+}
@@ -0,0 +1,166 @@
+//! Rich list reports over scanned accounts
+
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::block::DynamicGlobalProperties;
+use nectarflower_rs::rich_list::{RankBy, RichList};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Account, Client};
+use serde_json::{json, Value};
+
+fn props() -> Value {
+    json!({
+        "head_block_number": 10,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "bob",
+        "current_aslot": 10,
+        "last_irreversible_block_num": 10,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+fn account(name: &str, hive: &str, savings: &str, hbd: &str, vests: &str) -> Value {
+    json!({
+        "name": name,
+        "json_metadata": "",
+        "balance": hive,
+        "savings_balance": savings,
+        "hbd_balance": hbd,
+        "savings_hbd_balance": "1.000 HBD",
+        "vesting_shares": vests,
+    })
+}
+
+fn accounts() -> Vec<Value> {
+    vec![
+        account(
+            "alice",
+            "5.000 HIVE",
+            "5.000 HIVE",
+            "0.000 HBD",
+            "20000.000000 VESTS",
+        ),
+        account(
+            "bob",
+            "8.000 HIVE",
+            "2.000 HIVE",
+            "9.000 HBD",
+            "0.000000 VESTS",
+        ),
+        account(
+            "null",
+            "0.000 HIVE",
+            "0.000 HIVE",
+            "0.000 HBD",
+            "0.000000 VESTS",
+        ),
+        account(
+            "exchange",
+            "900.000 HIVE",
+            "0.000 HIVE",
+            "0.000 HBD",
+            "0.000000 VESTS",
+        ),
+        account(
+            "carol",
+            "1.000 HIVE",
+            "0.000 HIVE",
+            "2.000 HBD",
+            "2000.000000 VESTS",
+        ),
+    ]
+}
+
+fn client() -> Client {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props())
+        .respond(
+            "database_api.list_accounts",
+            json!({ "accounts": accounts() }),
+        );
+    Client::builder().transport(mock).build()
+}
+
+fn ranking(settings: &RichList) -> Vec<(usize, String)> {
+    let report = client().rich_list(settings).unwrap();
+    report
+        .holdings
+        .into_iter()
+        .map(|h| (h.rank, h.account))
+        .collect()
+}
+
+fn ranked(names: &[&str]) -> Vec<(usize, String)> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (i + 1, name.to_string()))
+        .collect()
+}
+
+#[test]
+fn accounts_are_ranked_by_the_chosen_holding() {
+    let settings = RichList::new(RankBy::Hive).exclude(&["exchange"]);
+    // Savings count, and ties are broken by name
+    assert_eq!(ranking(&settings), ranked(&["alice", "bob", "carol"]));
+    let report = client().rich_list(&settings).unwrap();
+    assert_eq!(report.scanned, 5);
+    assert_eq!(report.holdings[0].hp.to_string(), "10.000 HIVE");
+
+    // Accounts without any of the ranked asset are left out
+    assert_eq!(
+        ranking(&RichList::new(RankBy::Hp)),
+        ranked(&["alice", "carol"])
+    );
+    assert_eq!(
+        ranking(&RichList::new(RankBy::Hbd).top(2)),
+        ranked(&["bob", "carol"])
+    );
+    let settings = RichList::new(RankBy::Hive).min_balance(Asset::new(10_000, AssetSymbol::Hive));
+    assert_eq!(ranking(&settings), ranked(&["exchange", "alice", "bob"]));
+}
+
+#[test]
+fn reports_export_as_csv_and_json() {
+    let report = client()
+        .rich_list(&RichList::new(RankBy::Hbd).exclude(&["alice", "null", "exchange"]))
+        .unwrap();
+    assert_eq!(
+        report.to_csv(),
+        "rank,account,hive,hbd,hp\n1,bob,10.000,10.000,0.000\n2,carol,1.000,3.000,1.000\n"
+    );
+    let json: Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["rank_by"], "hbd");
+    assert_eq!(json["holdings"][1]["account"], "carol");
+}
+
+#[test]
+fn large_scans_keep_only_the_top_holders() {
+    let props: DynamicGlobalProperties = serde_json::from_value(props()).unwrap();
+    let accounts = (1..=3000).map(|i| {
+        let hive = format!("{i}.000 HIVE");
+        serde_json::from_value::<Account>(account(
+            &format!("user{i:04}"),
+            &hive,
+            "0.000 HIVE",
+            "0.000 HBD",
+            "0.000000 VESTS",
+        ))
+        .unwrap()
+    });
+    let report = RichList::new(RankBy::Hive).top(3).build(accounts, &props);
+    assert_eq!(report.scanned, 3000);
+    let top: Vec<_> = report.holdings.iter().map(|h| h.account.as_str()).collect();
+    assert_eq!(top, ["user3000", "user2999", "user2998"]);
+}
+
+#[test]
+fn scan_errors_fail_the_report() {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props())
+        .respond_error("database_api.list_accounts", -32000, "down");
+    let client = Client::builder().transport(mock).build();
+    let err = client.rich_list(&RichList::new(RankBy::Hive)).unwrap_err();
+    assert!(err.starts_with("Error listing accounts from '':"), "{err}");
+}
@@ -0,0 +1,255 @@
+use nectarflower_rs::sanitize::{sanitize_body, MAX_BODY_BYTES};
+
+/// Sanitize `body`, asserting nothing that could run script survives
+fn clean(body: &str) -> String {
+    let report = sanitize_body(body);
+    let lower = report.body.to_ascii_lowercase();
+    for needle in [
+        "<script",
+        "<iframe",
+        "<svg",
+        "<body",
+        "onerror",
+        "onload",
+        "javascript",
+        "vbscript",
+        "data:",
+    ] {
+        assert!(
+            !lower.contains(needle),
+            "{needle} survived in {:?} from {body:?}",
+            report.body
+        );
+    }
+    report.body
+}
+
+#[test]
+fn script_tags_are_dropped_with_their_content() {
+    assert_eq!(clean("a<script>alert(1)</script>b"), "ab");
+    assert_eq!(clean("a<SCRIPT SRC=//evil.js></SCRIPT>b"), "ab");
+    assert_eq!(clean("a<script >alert(1)</script >b"), "ab");
+    // An unclosed script swallows the rest, as a browser would
+    assert_eq!(clean("a<script>alert(1)"), "a");
+    assert_eq!(clean("<style>body{}</style><form><input></form>ok"), "ok");
+    assert_eq!(
+        clean("<iframe src=\"https://evil.example\"></iframe>x"),
+        "x"
+    );
+
+    let report = sanitize_body("<script>alert(1)</script>");
+    assert_eq!(report.warnings, ["Removed <script> and its content"]);
+}
+
+#[test]
+fn event_handlers_are_removed() {
+    assert_eq!(clean("<img src=x onerror=alert(1)>"), "<img src=\"x\">");
+    assert_eq!(
+        clean("<img src=\"x\" ONERROR=\"alert(1)\">"),
+        "<img src=\"x\">"
+    );
+    assert_eq!(clean("<img/src=x/onerror=alert(1)>"), "<img>");
+    assert_eq!(clean("<svg onload=alert(1)>x</svg>"), "x");
+    assert_eq!(clean("<body onload=alert(1)>"), "");
+    assert_eq!(
+        clean("<div\nonclick=alert(1)\nclass=\"pull-right\">"),
+        "<div class=\"pull-right\">"
+    );
+}
+
+#[test]
+fn script_urls_are_removed_however_they_are_written() {
+    for href in [
+        "javascript:alert(1)",
+        "JaVaScRiPt:alert(1)",
+        "  javascript:alert(1)",
+        "java\tscript:alert(1)",
+        "java\nscript:alert(1)",
+        "\u{1}javascript:alert(1)",
+        "java&#x09;script:alert(1)",
+        "&#106;avascript:alert(1)",
+        "&#0000106avascript:alert(1)",
+        "&#x6A&#x61&#x76&#x61script:alert(1)",
+        "javascript&colon;alert(1)",
+        "javascript&COLON;alert(1)",
+        "vbscript:msgbox(1)",
+        "data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==",
+    ] {
+        for quote in ["\"", "'"] {
+            assert_eq!(
+                clean(&format!("<a href={quote}{href}{quote}>x</a>")),
+                "<a>x</a>",
+                "{href}"
+            );
+        }
+    }
+    assert_eq!(clean("<a href=javascript:alert(1)>x</a>"), "<a>x</a>");
+    assert_eq!(clean("<img src=\"data:image/svg+xml,<svg>\">"), "<img>");
+
+    let report = sanitize_body("<a href=\"javascript:alert(1)\">x</a>");
+    assert_eq!(report.warnings, ["Removed javascript: URL from <a>"]);
+}
+
+#[test]
+fn tags_hidden_behind_malformed_markup_are_still_checked() {
+    assert_eq!(
+        clean("1 < 2 <img src=x onerror=alert(1)>"),
+        "1 < 2 <img src=\"x\">"
+    );
+    assert_eq!(clean("<scr<script>ipt>alert(1)</script>"), "ipt>alert(1)");
+    assert_eq!(clean("<<script>alert(1)</script>"), "<");
+    assert_eq!(
+        clean("<!-- x --!><img src=x onerror=alert(1)>"),
+        "<!-- x --!><img src=\"x\">"
+    );
+    assert_eq!(clean("<!--><img src=x onerror=1>"), "<!--><img src=\"x\">");
+    assert_eq!(
+        clean("<!-- <img src=x onerror=alert(1)>"),
+        "<!-- <img src=\"x\">"
+    );
+    assert_eq!(clean("<!-- note -->"), "<!-- note -->");
+    // A quoted '>' does not end the tag early
+    assert_eq!(
+        clean("<a title=\"a>b\" onclick=alert(1)>x</a>"),
+        "<a title=\"a>b\">x</a>"
+    );
+    // A tag cut off by the end of the body is dropped, not passed through
+    let report = sanitize_body("ok <img src=x onerror=alert(1)");
+    assert_eq!(report.body, "ok ");
+    assert_eq!(
+        report.warnings,
+        ["Removed unterminated tag at the end of the body"]
+    );
+    assert_eq!(clean("a <b"), "a ");
+    assert_eq!(clean("2 < 3"), "2 < 3");
+}
+
+#[test]
+fn supported_markup_is_kept() {
+    let body = "<center><a href=\"https://hive.blog/@alice\" title='Alice'>hi</a></center>";
+    let report = sanitize_body(body);
+    assert_eq!(
+        report.body,
+        "<center><a href=\"https://hive.blog/@alice\" title=\"Alice\">hi</a></center>"
+    );
+    assert!(report.warnings.is_empty());
+
+    assert_eq!(clean("<br/>"), "<br />");
+    assert_eq!(
+        clean("<img alt='say \"hi\"' src=\"/relative.png\">"),
+        "<img alt=\"say &quot;hi&quot;\" src=\"/relative.png\">"
+    );
+    // Text that merely mentions a scheme is not a URL
+    assert_eq!(
+        sanitize_body("Never paste javascript: links").body,
+        "Never paste javascript: links"
+    );
+}
+
+#[test]
+fn empty_and_unicode_bodies() {
+    let report = sanitize_body("");
+    assert_eq!(report.body, "");
+    assert!(report.warnings.is_empty());
+
+    let body = "Grüße aus Köln 🐝\n\n日本語のテキスト <b>太字</b>";
+    let report = sanitize_body(body);
+    assert_eq!(report.body, body);
+    assert!(report.warnings.is_empty());
+
+    let report = sanitize_body("#🐝 Bees\n<blink>ÄÖÜ</blink>");
+    assert_eq!(report.body, "# 🐝 Bees\nÄÖÜ");
+    assert_eq!(report.warnings.len(), 2);
+}
+
+#[test]
+fn markdown_is_normalized() {
+    let report = sanitize_body("#Title\r\n##Sub\r\n####### seven\r\n# fine\r\n#");
+    assert_eq!(report.body, "# Title\n## Sub\n####### seven\n# fine\n#");
+    assert_eq!(
+        report.warnings,
+        [
+            "Line 1: heading needs a space after '#'; one was added",
+            "Line 2: heading needs a space after '#'; one was added",
+        ]
+    );
+}
+
+#[test]
+fn code_blocks_are_left_alone() {
+    let body = "```html\n<script>alert(1)</script>\n#notaheading\n```\n<script>x</script>after";
+    let report = sanitize_body(body);
+    assert_eq!(
+        report.body,
+        "```html\n<script>alert(1)</script>\n#notaheading\n```\nafter"
+    );
+
+    let report = sanitize_body("```\n<b>unclosed");
+    assert_eq!(report.body, "```\n<b>unclosed\n```");
+    assert_eq!(
+        report.warnings,
+        ["Unclosed code fence; a closing ``` was added"]
+    );
+}
+
+#[test]
+fn markdown_links_and_images_lose_blocked_urls() {
+    assert_eq!(clean("[x](javascript:alert(1))"), "[x]()");
+    assert_eq!(clean("![](data:image/svg+xml;base64,PHN2Zz4=)"), "![]()");
+    assert_eq!(clean("[x]( JaVaScRiPt:alert(1) \"t\")"), "[x]( \"t\")");
+    assert_eq!(clean("[x](java&#x09;script:alert(1))"), "[x]()");
+    assert_eq!(clean("[x](javascript\\:alert(1))"), "[x]()");
+    assert_eq!(clean("[x](\nvbscript:msgbox(1))"), "[x]()");
+    assert_eq!(
+        clean("[a][1] and [b][2]\n\n[1]: javascript:alert(1)\n  [2]: <data:text/html,x>"),
+        // The HTML pass already strips the bracketed form as an unknown tag
+        "[a][1] and [b][2]\n\n\n  [2]: "
+    );
+
+    let report = sanitize_body("![a](data:x) [b](javascript:y) [c]: vbscript:z");
+    assert_eq!(
+        report.warnings,
+        [
+            "Removed data: URL from a markdown image",
+            "Removed javascript: URL from a markdown link",
+        ]
+    );
+    let report = sanitize_body("[c]: vbscript:z");
+    assert_eq!(
+        report.warnings,
+        ["Removed vbscript: URL from a markdown reference"]
+    );
+}
+
+#[test]
+fn safe_markdown_links_and_fenced_code_are_kept() {
+    for body in [
+        "[post](https://hive.blog/@alice/my-post)",
+        "![img](https://images.hive.blog/a.png \"title\")",
+        "[wiki](https://en.wikipedia.org/wiki/Bee_(disambiguation))",
+        "[rel](/@alice) and [mail](mailto:alice@example.com)",
+        "[ref]: https://peakd.com/@alice",
+        "Write [text](url) for links",
+    ] {
+        let report = sanitize_body(body);
+        assert_eq!(report.body, body);
+        assert!(report.warnings.is_empty(), "{body}");
+    }
+    let fenced = "```md\n[x](javascript:alert(1))\n```";
+    assert_eq!(sanitize_body(fenced).body, fenced);
+}
+
+#[test]
+fn oversized_bodies_are_reported() {
+    let report = sanitize_body(&"a".repeat(MAX_BODY_BYTES));
+    assert!(report.warnings.is_empty());
+    // The limit is in bytes, not characters
+    let report = sanitize_body(&"é".repeat(MAX_BODY_BYTES / 2 + 1));
+    assert_eq!(
+        report.warnings,
+        [format!(
+            "Body is {} bytes, above the {MAX_BODY_BYTES} byte limit",
+            MAX_BODY_BYTES + 1
+        )]
+    );
+}
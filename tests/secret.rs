@@ -0,0 +1,59 @@
+use nectarflower_rs::crypto::sha256;
+use nectarflower_rs::transaction::TransactionSigner;
+use nectarflower_rs::{SecretWif, WifSigner};
+
+const WIF: &str = "5HpjKrb7dH5kKQQzmbjB87Mxova7mek5bXUTWfndcX6tBoqUwzm";
+
+#[test]
+fn wif_decodes_and_never_prints() {
+    let key = SecretWif::parse(WIF).unwrap();
+    let expected: Vec<u8> = (1..=32).collect();
+    assert_eq!(key.key_bytes().as_slice(), expected.as_slice());
+    assert_eq!(format!("{key:?}"), "SecretWif(<redacted>)");
+
+    let signer = WifSigner::new(key, |key, digest| Ok(format!("{}{}", key[0], digest[0])));
+    assert_eq!(signer.sign_digest(&[9; 32]).unwrap(), "19");
+    assert!(!format!("{signer:?}").contains(WIF));
+}
+
+#[test]
+fn invalid_keys_are_not_echoed_in_errors() {
+    let mut corrupted = WIF.to_string();
+    corrupted.replace_range(10..11, "z");
+    for bad in [
+        corrupted.as_str(),
+        "5HpjKrb7dH5kKQQzmbjB87Mxova7mek5b0",
+        "abc",
+    ] {
+        let err = SecretWif::parse(bad).unwrap_err();
+        assert!(!err.contains(bad), "{err}");
+    }
+    let err = serde_json::from_str::<SecretWif>(&format!("\"{corrupted}\"")).unwrap_err();
+    assert!(!err.to_string().contains(&corrupted));
+}
+
+// Key and signatures from dhive's test suite
+const DHIVE_WIF: &str = "5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL";
+
+#[test]
+fn keys_derive_their_public_key() {
+    let key = SecretWif::parse(DHIVE_WIF).unwrap();
+    assert_eq!(
+        key.public_key("STM").unwrap(),
+        "STM87F7tN56tAUL2C6J9Gzi9HzgNpZdi6M2cLQo7TjDU5v178QsYA"
+    );
+    // dhive's PrivateKey.fromLogin("foo", "barman", "active")
+    assert_eq!(key.key_bytes().as_slice(), sha256(b"fooactivebarman"));
+}
+
+#[test]
+fn keys_sign_like_dhive() {
+    let key = SecretWif::parse(DHIVE_WIF).unwrap();
+    let digest: [u8; 32] = std::array::from_fn(|i| i as u8);
+    assert_eq!(
+        key.sign_digest(&digest).unwrap(),
+        "20173e52773241c69a8870c796634a537cb543e088c8aa13b89d46e33c0227c6\
+         2e4afda5266272bd53c4e3e7f417af4d811b3fae5bd069c94447f1fdc48a525b8d"
+    );
+    assert_eq!(key.key_fingerprint(), Some(key.fingerprint()));
+}
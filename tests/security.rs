@@ -0,0 +1,212 @@
+//! Account security audits
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::security::{audit_keys, FindingKind, Severity};
+use nectarflower_rs::transport::Transport;
+use nectarflower_rs::{Account, Client};
+use serde_json::{json, Value};
+
+const OWNER: &str = "STM87F7tN56tAUL2C6J9Gzi9HzgNpZdi6M2cLQo7TjDU5v178QsYA";
+const ACTIVE: &str = "STM6FATHLohxTN8RWWkU9ZZwVywXo6MEDjHHui1jEBYkG2tTdvMYo";
+const POSTING: &str = "STM8GC13uCZbP44HzMLV6zPZGwVQ8Nt4Kji8PapsPiNq1BK153XTX";
+const MEMO: &str = "STM5CQ3qS8GoXZuFu6tgAa3zyS2a7tJd14DCaxDmdtqPxj3vPtH3Q";
+
+fn authority(key: &str) -> Value {
+    json!({ "weight_threshold": 1, "account_auths": [], "key_auths": [[key, 1]] })
+}
+
+fn account(name: &str, recovery: &str, keys: [&str; 4]) -> Value {
+    json!({
+        "name": name,
+        "json_metadata": "",
+        "owner": authority(keys[0]),
+        "active": authority(keys[1]),
+        "posting": authority(keys[2]),
+        "memo_key": keys[3],
+        "recovery_account": recovery,
+        "created": "2020-01-01T00:00:00",
+        "last_owner_update": "1970-01-01T00:00:00",
+        "last_account_update": "2024-01-20T00:00:00",
+        "last_post": "2023-12-01T00:00:00",
+        "last_vote_time": "1970-01-01T00:00:00",
+    })
+}
+
+/// Answers each call with `answer(method, params)`
+struct Node<F>(F);
+
+impl<F: Fn(&str, &Value) -> Value + Send + Sync> Transport for Node<F> {
+    fn send(&self, _url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        let result = (self.0)(request["method"].as_str().unwrap(), &request["params"]);
+        let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+fn client(accounts: Vec<Value>, recovery_requests: Value, changes: Value) -> Client {
+    let node = Node(move |method: &str, params: &Value| match method {
+        "database_api.get_dynamic_global_properties" => json!({
+            "head_block_number": 10,
+            "head_block_id": "0000000aaabbccdd000000000000000000000000",
+            "time": "2024-02-01T00:00:00",
+            "current_witness": "bob",
+            "current_aslot": 10,
+            "last_irreversible_block_num": 10,
+            "total_vesting_fund_hive": "1000.000 HIVE",
+            "total_vesting_shares": "2000000.000000 VESTS",
+        }),
+        "database_api.find_accounts" => {
+            let found: Vec<&Value> = accounts
+                .iter()
+                .filter(|a| params["accounts"][0] == a["name"])
+                .collect();
+            json!({ "accounts": found })
+        }
+        "database_api.find_account_recovery_requests" => json!({ "requests": recovery_requests }),
+        "database_api.find_change_recovery_account_requests" => json!({ "requests": changes }),
+        other => panic!("unexpected call {other}"),
+    });
+    Client::builder().transport(node).build()
+}
+
+fn kinds(client: &Client, name: &str) -> Vec<FindingKind> {
+    let audit = client.audit_account_security(name).unwrap();
+    audit.findings.into_iter().map(|f| f.kind).collect()
+}
+
+#[test]
+fn shared_keys_are_reported_by_role() {
+    let alice: Account =
+        serde_json::from_value(account("alice", "bob", [OWNER, ACTIVE, OWNER, ACTIVE])).unwrap();
+    let findings = audit_keys(&alice);
+    let kinds: Vec<_> = findings
+        .iter()
+        .map(|f| (f.severity, f.kind.clone()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (
+                Severity::Warning,
+                FindingKind::KeyReused {
+                    key: ACTIVE.to_string(),
+                    roles: vec!["active".to_string(), "memo".to_string()],
+                }
+            ),
+            (
+                Severity::Critical,
+                FindingKind::OwnerKeyShared {
+                    role: "posting".to_string(),
+                    key: OWNER.to_string(),
+                }
+            ),
+            (
+                Severity::Warning,
+                FindingKind::KeyReused {
+                    key: OWNER.to_string(),
+                    roles: vec!["owner".to_string(), "posting".to_string()],
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        findings[1].message,
+        format!("Owner key {OWNER} is also used as the posting key")
+    );
+
+    let distinct: Account =
+        serde_json::from_value(account("alice", "bob", [OWNER, ACTIVE, MEMO, ""])).unwrap();
+    assert!(audit_keys(&distinct).is_empty());
+}
+
+#[test]
+fn recovery_setups_are_checked() {
+    let keys = [OWNER, ACTIVE, POSTING, MEMO];
+    let mut dormant = account("dormant", "", keys);
+    dormant["last_account_update"] = json!("2022-01-01T00:00:00");
+    dormant["last_post"] = json!("2022-06-01T00:00:00");
+    let accounts = vec![
+        account("alice", "dormant", keys),
+        account("self", "self", keys),
+        account("gone", "nobody", keys),
+        account("unset", "", keys),
+        dormant,
+    ];
+    let client = client(accounts, json!([]), json!([]));
+
+    let recently = FindingKind::AccountRecentlyUpdated {
+        at: "2024-01-20T00:00:00".to_string(),
+    };
+    assert_eq!(
+        kinds(&client, "alice"),
+        [
+            FindingKind::RecoveryAccountInactive {
+                recovery_account: "dormant".to_string(),
+                last_activity: "2022-06-01T00:00:00".to_string(),
+            },
+            recently.clone(),
+        ]
+    );
+    assert_eq!(
+        kinds(&client, "self"),
+        [FindingKind::RecoveryAccountSelf, recently.clone()]
+    );
+    assert_eq!(
+        kinds(&client, "gone"),
+        [
+            FindingKind::RecoveryAccountMissing {
+                recovery_account: "nobody".to_string(),
+            },
+            recently.clone(),
+        ]
+    );
+    let audit = client.audit_account_security("unset").unwrap();
+    assert_eq!(audit.worst(), Some(Severity::Critical));
+    assert_eq!(audit.findings[0].message, "No recovery account is set");
+
+    let err = client.audit_account_security("nobody").unwrap_err();
+    assert_eq!(err, "Account 'nobody' not found");
+}
+
+#[test]
+fn pending_recovery_and_recent_changes_are_reported() {
+    let mut alice = account("alice", "alice", [OWNER, ACTIVE, MEMO, ""]);
+    alice["last_owner_update"] = json!("2024-01-25T00:00:00");
+    alice["last_account_update"] = json!("2023-01-01T00:00:00");
+    let client = client(
+        vec![alice],
+        json!([{ "account_to_recover": "alice", "expires": "2024-02-02T00:00:00" }]),
+        json!([{
+            "account_to_recover": "alice",
+            "recovery_account": "carol",
+            "effective_on": "2024-03-01T00:00:00",
+        }]),
+    );
+
+    let audit = client.audit_account_security("alice").unwrap();
+    let findings: Vec<_> = audit
+        .findings
+        .iter()
+        .map(|f| (f.severity, f.message.as_str()))
+        .collect();
+    // Most severe first
+    assert_eq!(
+        findings,
+        [
+            (
+                Severity::Critical,
+                "Account recovery request pending until 2024-02-02T00:00:00"
+            ),
+            (Severity::Warning, "The account is its own recovery account"),
+            (
+                Severity::Warning,
+                "Recovery account changes to @carol on 2024-03-01T00:00:00"
+            ),
+            (
+                Severity::Warning,
+                "Owner authority changed on 2024-01-25T00:00:00"
+            ),
+        ]
+    );
+}
@@ -0,0 +1,29 @@
+use nectarflower_rs::{SignerSet, TransactionSigner};
+
+struct Fixed(&'static str);
+
+impl TransactionSigner for Fixed {
+    fn sign_digest(&self, _digest: &[u8; 32]) -> Result<String, String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[test]
+fn signer_set_selects_signers_for_required_keys() {
+    let (active, posting, replacement) = (Fixed("active"), Fixed("posting"), Fixed("new"));
+    let signers = SignerSet::new()
+        .with("STM_ACTIVE", &active)
+        .with("STM_POSTING", &posting)
+        .with("STM_POSTING", &replacement);
+    assert_eq!(
+        signers.public_keys().collect::<Vec<_>>(),
+        ["STM_ACTIVE", "STM_POSTING"]
+    );
+
+    let selected = signers.select(&["STM_POSTING".to_string()]).unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].sign_digest(&[0; 32]).unwrap(), "new");
+
+    let err = signers.select(&["STM_OWNER".to_string()]).err().unwrap();
+    assert!(err.contains("STM_OWNER"));
+}
@@ -0,0 +1,157 @@
+//! Golden vectors for local transaction serialization, digests and ids
+//!
+//! The first transactions and their hex come from dhive's serializer tests, so
+//! they check this crate against an independent implementation. The rest cover
+//! flat_set ordering and account_update2, with hex assembled by hand from
+//! hived's operation layouts. Digests and ids are the SHA-256 of that hex, and
+//! the signatures were made with dhive's test key by a separate secp256k1
+//! implementation, all outside the crate. Mainnet transactions captured with
+//! `examples/capture_signing_vectors.rs` can be appended in the same format.
+
+use nectarflower_rs::crypto::{encode_public_key, from_hex, recover_public_key, to_hex};
+use nectarflower_rs::transaction::TransactionSigner;
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{ChainConfig, Client, SecretWif, Transaction, WriteClient};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize)]
+struct Vector {
+    name: String,
+    transaction: Transaction,
+    chain_id: String,
+    hex: String,
+    digest: String,
+    id: String,
+    /// Keys the transaction's signatures recover to, in order
+    #[serde(default)]
+    signing_keys: Vec<String>,
+}
+
+fn vectors() -> Vec<Vector> {
+    serde_json::from_str(include_str!("../fixtures/signing_vectors.json")).unwrap()
+}
+
+#[test]
+fn serialization_matches_golden_hex() {
+    for v in vectors() {
+        let bytes = v.transaction.to_bytes().unwrap();
+        assert_eq!(to_hex(&bytes), v.hex, "{}", v.name);
+    }
+}
+
+#[test]
+fn digest_matches_golden_digest() {
+    for v in vectors() {
+        let chain = ChainConfig::new("vector", &v.chain_id);
+        let digest = v.transaction.digest(&chain).unwrap();
+        assert_eq!(to_hex(&digest), v.digest, "{}", v.name);
+    }
+}
+
+#[test]
+fn id_matches_golden_id() {
+    for v in vectors() {
+        assert_eq!(v.transaction.id().unwrap(), v.id, "{}", v.name);
+    }
+}
+
+#[test]
+fn signatures_recover_to_signing_keys() {
+    let vectors = vectors();
+    assert!(
+        vectors.iter().any(|v| !v.transaction.signatures.is_empty()),
+        "no vector carries signatures"
+    );
+    for v in vectors {
+        let digest: [u8; 32] = from_hex(&v.digest).unwrap().try_into().unwrap();
+        let recovered: Vec<String> = v
+            .transaction
+            .signatures
+            .iter()
+            .map(|sig| encode_public_key(&recover_public_key(&digest, sig).unwrap(), "STM"))
+            .collect();
+        assert_eq!(recovered, v.signing_keys, "{}", v.name);
+    }
+}
+
+#[test]
+fn recovers_the_signing_key() {
+    let key = key();
+    let digest = [7u8; 32];
+    let signature = key.sign_digest(&digest).unwrap();
+    assert_eq!(
+        encode_public_key(&recover_public_key(&digest, &signature).unwrap(), "STM"),
+        key.public_key("STM").unwrap()
+    );
+    assert!(recover_public_key(&digest, "00").is_err());
+}
+
+#[test]
+fn signatures_do_not_change_id() {
+    for mut v in vectors() {
+        v.transaction.signatures = vec!["1f".repeat(65)];
+        assert_eq!(v.transaction.id().unwrap(), v.id, "{}", v.name);
+    }
+}
+
+#[test]
+fn unsupported_operation_is_rejected() {
+    let mut tx = vectors().remove(0).transaction;
+    tx.operations[0].op_type = "pow_operation".to_string();
+    let err = tx.to_bytes().unwrap_err();
+    assert!(err.contains("pow"), "{err}");
+}
+
+#[test]
+fn signatures_match_dhive() {
+    // dhive signs its vote vector, with a `long-pants` extension, on a zero chain id
+    let key = key();
+    let digest: [u8; 32] =
+        from_hex("77342bdde45a4901a0a65a98e0806a292ccfeb8b9b048d1ca93af69434c866de")
+            .unwrap()
+            .try_into()
+            .unwrap();
+    assert_eq!(
+        key.sign_digest(&digest).unwrap(),
+        "1f037a09c1110a8bd8757ad3081a11456d241feedd4366723bb9f9046cc6a1b2\
+         1b26bf4b8372546bc2446c7498ff5742dce0143ff1fe13591eb8dd88b9a7fef2f2"
+    );
+}
+
+fn key() -> SecretWif {
+    SecretWif::parse("5KG4sr3rMH1QuduYj79p36h7PrEeZakHEPjB9NkLWqgw19DDieL").unwrap()
+}
+
+#[test]
+fn signing_never_uses_node_serialization() {
+    let v = vectors().remove(1);
+    // A node answering with another transaction's bytes is never asked
+    let mock = MockTransport::new().respond(
+        "database_api.get_transaction_hex",
+        json!({ "hex": format!("{}00", vectors()[0].hex) }),
+    );
+    let client = WriteClient::new(Client::builder().transport(mock.clone()).build());
+    let mut tx = v.transaction.clone();
+    tx.signatures.clear();
+    let key = key();
+    let trx_id = client.sign_transaction(&mut tx, &[&key]).unwrap();
+    assert_eq!(trx_id, v.id);
+    let digest: [u8; 32] = from_hex(&v.digest).unwrap().try_into().unwrap();
+    assert_eq!(tx.signatures, vec![key.sign_digest(&digest).unwrap()]);
+    assert!(mock.requests().is_empty());
+
+    let err = client.verify_transaction_bytes(&v.transaction).unwrap_err();
+    assert!(err.contains(&v.hex), "{err}");
+}
+
+#[test]
+fn matching_node_serialization_verifies() {
+    let v = vectors().remove(1);
+    let mock = MockTransport::new().respond(
+        "database_api.get_transaction_hex",
+        json!({ "hex": format!("{}00", v.hex) }),
+    );
+    let client = Client::builder().transport(mock).build();
+    client.verify_transaction_bytes(&v.transaction).unwrap();
+}
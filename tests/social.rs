@@ -0,0 +1,93 @@
+//! The client's profile cache
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+use std::time::Duration;
+
+fn mock() -> MockTransport {
+    MockTransport::new().respond(
+        "bridge.get_profile",
+        json!({
+            "id": 1,
+            "name": "alice",
+            "created": "2020-01-01T00:00:00",
+            "post_count": 42,
+            "reputation": 65.3,
+            "stats": { "followers": 120, "following": 30, "rank": 0 },
+            "metadata": { "profile": { "name": "Alice", "about": "Photos" } },
+        }),
+    )
+}
+
+fn profile_requests(mock: &MockTransport) -> usize {
+    mock.requests()
+        .iter()
+        .filter(|(_, request)| request["method"] == "bridge.get_profile")
+        .count()
+}
+
+#[test]
+fn profiles_are_fetched_once_while_fresh() {
+    let mock = mock();
+    let client = Client::builder().transport(mock.clone()).build();
+    let cache = client.social_cache();
+    assert!(cache.is_empty());
+    assert!(cache.cached("alice").is_none());
+
+    let profile = cache.profile("alice").unwrap();
+    assert_eq!(profile.post_count, 42);
+    assert_eq!(profile.profile.name, "Alice");
+    assert_eq!(profile.profile.about, "Photos");
+    assert_eq!(cache.reputation("alice").unwrap(), 65.3);
+    assert_eq!(cache.follow_counts("alice").unwrap(), (120, 30));
+    // Every handle shares the client's cache
+    assert!(client.social_cache().cached("alice").is_some());
+    assert_eq!(profile_requests(&mock), 1);
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!({ "account": "alice" }));
+
+    cache.invalidate("alice");
+    assert!(cache.is_empty());
+    cache.profile("alice").unwrap();
+    assert_eq!(profile_requests(&mock), 2);
+}
+
+#[test]
+fn expired_profiles_are_refetched_and_pruned() {
+    let mock = mock();
+    let client = Client::builder().transport(mock.clone()).build();
+    let cache = client.social_cache();
+    assert_eq!(cache.ttl(), Duration::from_secs(300));
+
+    assert!(cache.warm(&["alice"]).is_empty());
+    assert_eq!(cache.len(), 1);
+    // Shortening the lifetime expires entries already cached
+    cache.set_ttl(Duration::ZERO);
+    assert!(cache.cached("alice").is_none());
+    cache.reputation("alice").unwrap();
+    assert_eq!(profile_requests(&mock), 2);
+
+    cache.prune();
+    assert!(cache.is_empty());
+    cache.set_ttl(Duration::from_secs(60));
+    cache.warm(&["alice"]);
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn failed_fetches_are_reported_and_not_cached() {
+    let mock = MockTransport::new().respond_error("bridge.get_profile", -32602, "Invalid account");
+    let client = Client::builder().transport(mock).build();
+    let cache = client.social_cache();
+    let errors = cache.warm(&["ghost"]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "ghost");
+    assert!(
+        errors[0].1.starts_with("Error fetching profile of ghost:"),
+        "{}",
+        errors[0].1
+    );
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,46 @@
+use nectarflower_rs::{Operation, Transaction};
+use serde_json::json;
+
+#[test]
+fn operations_read_as_sentences() {
+    let vote = Operation::new(
+        "vote_operation",
+        json!({"voter": "alice", "author": "bob", "permlink": "hello", "weight": -2550}),
+    );
+    assert_eq!(vote.summary(), "@alice downvoted @bob/hello (25.50%)");
+
+    let post = Operation::new(
+        "comment_operation",
+        json!({"parent_author": "", "parent_permlink": "hive", "author": "bob",
+               "permlink": "hello", "title": "Hello Hive", "body": "..."}),
+    );
+    assert_eq!(post.summary(), "@bob posted @bob/hello: \"Hello Hive\"");
+    assert_eq!(post.summary_redacted(), "@bob posted @bob/hello");
+
+    let secret = Operation::new(
+        "transfer_operation",
+        json!({"from": "alice", "to": "bob", "amount": {"amount": "5000", "precision": 3, "nai": "@@000000021"}, "memo": "#encrypted"}),
+    );
+    assert_eq!(
+        secret.summary(),
+        "@alice transferred 5.000 HIVE to @bob — memo: (encrypted)"
+    );
+
+    let unknown = Operation::new("account_update2_operation", json!({"account": "alice"}));
+    assert_eq!(unknown.summary(), "account_update2 account=alice");
+
+    let tx = Transaction {
+        ref_block_num: 1,
+        ref_block_prefix: 2,
+        expiration: "2024-01-01T00:01:00".to_string(),
+        operations: vec![vote, unknown],
+        extensions: Vec::new(),
+        signatures: Vec::new(),
+    };
+    assert_eq!(
+        tx.summary(),
+        "Transaction with 2 operations, expiring 2024-01-01T00:01:00\n  \
+         1. @alice downvoted @bob/hello (25.50%)\n  \
+         2. account_update2 account=alice"
+    );
+}
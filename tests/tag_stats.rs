@@ -0,0 +1,181 @@
+//! Trending tags and per-tag activity from blocks and posts
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::posts::Post;
+use nectarflower_rs::tag_stats::{TagOrder, TagStats};
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+fn comment(author: &str, parent_author: &str, category: &str, tags: Value) -> Value {
+    json!({
+        "type": "comment_operation",
+        "value": {
+            "parent_author": parent_author,
+            "parent_permlink": category,
+            "author": author,
+            "permlink": "post",
+            "title": "",
+            "body": "",
+            "json_metadata": json!({ "tags": tags }).to_string(),
+        },
+    })
+}
+
+fn block(operations: Vec<Value>) -> Value {
+    json!({
+        "previous": "0000000900000000000000000000000000000000",
+        "timestamp": "2024-01-01T00:00:30",
+        "witness": "bob",
+        "transaction_merkle_root": "0000000000000000000000000000000000000000",
+        "witness_signature": "1f00",
+        "transactions": [{ "operations": operations }],
+        "block_id": "0000000a00000000000000000000000000000000",
+        "signing_key": "",
+        "transaction_ids": ["aa"],
+    })
+}
+
+/// Serves blocks 1 to 3 and a head block of 3
+struct Chain;
+
+impl Transport for Chain {
+    fn send(&self, _url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        let result = match request["method"].as_str().unwrap() {
+            "database_api.get_dynamic_global_properties" => json!({
+                "head_block_number": 3,
+                "head_block_id": "0000000300000000000000000000000000000000",
+                "time": "2024-01-01T00:00:09",
+                "current_witness": "bob",
+                "current_aslot": 3,
+                "last_irreversible_block_num": 3,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+            "block_api.get_block" => {
+                let ops = match request["params"]["block_num"].as_u64().unwrap() {
+                    1 => vec![
+                        comment(
+                            "alice",
+                            "",
+                            "hive-174578",
+                            json!(["Photography", "#travel"]),
+                        ),
+                        comment("bob", "alice", "post", json!(["photography"])),
+                    ],
+                    // An edit of alice's post is not counted again
+                    2 => vec![comment("alice", "", "hive-174578", json!(["photography"]))],
+                    3 => vec![
+                        comment("carol", "", "travel", json!(["travel", "food"])),
+                        json!({ "type": "vote_operation", "value": { "voter": "dave" } }),
+                    ],
+                    n => panic!("block {n} was not asked for"),
+                };
+                json!({ "block": block(ops) })
+            }
+            other => panic!("unexpected call {other}"),
+        };
+        let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+fn stats(tag: &str, posts: u32, comments: u32, payout: f64) -> TagStats {
+    TagStats {
+        tag: tag.to_string(),
+        posts,
+        comments,
+        payout,
+    }
+}
+
+#[test]
+fn blocks_are_counted_per_tag() {
+    let client = Client::builder().transport(Chain).build();
+    let collector = client.tag_stats(1, 3).unwrap();
+
+    assert_eq!(
+        collector.get("hive-174578"),
+        Some(&stats("hive-174578", 1, 0, 0.0))
+    );
+    // Replies count under their own tags, without the parent permlink
+    assert_eq!(
+        collector.get("photography"),
+        Some(&stats("photography", 1, 1, 0.0))
+    );
+    assert_eq!(collector.get("travel"), Some(&stats("travel", 2, 0, 0.0)));
+    assert!(collector.get("post").is_none());
+
+    let top: Vec<_> = collector
+        .top(2, TagOrder::Posts)
+        .into_iter()
+        .map(|s| s.tag)
+        .collect();
+    assert_eq!(top, ["travel", "food"]);
+    assert_eq!(collector.top(1, TagOrder::Comments)[0].tag, "photography");
+    assert_eq!(collector.into_stats().len(), 4);
+
+    // An empty range reads nothing
+    assert!(client
+        .tag_stats(3, 2)
+        .unwrap()
+        .top(10, TagOrder::Posts)
+        .is_empty());
+}
+
+#[test]
+fn post_payouts_are_added_once_per_tag() {
+    let post = |permlink: &str, depth: u32, payout: f64| -> Post {
+        serde_json::from_value(json!({
+            "post_id": 1,
+            "author": "alice",
+            "permlink": permlink,
+            "category": "hive",
+            "title": "",
+            "body": "",
+            "json_metadata": { "tags": ["hive", "art"] },
+            "created": "2024-01-01T00:00:00",
+            "depth": depth,
+            "children": 0,
+            "is_paidout": false,
+            "payout_at": "2024-01-08T00:00:00",
+            "payout": payout,
+            "pending_payout_value": "0.000 HBD",
+            "author_payout_value": "0.000 HBD",
+            "curator_payout_value": "0.000 HBD",
+        }))
+        .unwrap()
+    };
+    let client = Client::builder().transport(Chain).build();
+    let mut collector = client.tag_stats(1, 1).unwrap();
+    collector.record_post(&post("a", 0, 2.5));
+    collector.record_post(&post("a", 0, 2.5));
+    collector.record_post(&post("b", 1, 1.0));
+
+    assert_eq!(collector.get("hive").unwrap().payout, 3.5);
+    assert_eq!(collector.get("art").unwrap().payout, 3.5);
+    let top = collector.top(1, TagOrder::Payout);
+    assert_eq!(top[0].tag, "art");
+}
+
+#[test]
+fn trending_tags_are_fetched_from_a_start_tag() {
+    let mock = MockTransport::new().respond(
+        "condenser_api.get_trending_tags",
+        json!([{
+            "name": "photography",
+            "total_payouts": "1234.567 HBD",
+            "net_votes": 10,
+            "top_posts": 5,
+            "comments": 20,
+            "trending": "0",
+        }]),
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    let tags = client.get_trending_tags("art", 500).unwrap();
+    assert_eq!(tags[0].name, "photography");
+    assert_eq!(tags[0].total_payouts.to_string(), "1234.567 HBD");
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"], json!(["art", 100]));
+}
@@ -0,0 +1,107 @@
+use nectarflower_rs::tags::{normalize_tag, validate_tag, validate_tags, MAX_TAGS, MAX_TAG_LENGTH};
+
+fn tags(list: &[&str]) -> Vec<String> {
+    list.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn tags_are_normalized() {
+    assert_eq!(normalize_tag("  Hive "), "hive");
+    assert_eq!(normalize_tag("#Photography"), "photography");
+    assert_eq!(normalize_tag("##hive"), "hive");
+    assert_eq!(normalize_tag("ÜBER"), "über");
+    assert_eq!(normalize_tag(""), "");
+    assert_eq!(normalize_tag(" # "), "");
+    assert_eq!(normalize_tag("# Hive"), "hive");
+}
+
+#[test]
+fn single_tags_follow_condenser_rules() {
+    for ok in [
+        "hive",
+        "hive-174578",
+        "a",
+        "web3",
+        &"a".repeat(MAX_TAG_LENGTH),
+    ] {
+        assert!(validate_tag(ok).is_ok(), "{ok}");
+    }
+    let err = |tag: &str| validate_tag(tag).unwrap_err();
+    assert_eq!(err(""), "Tag is empty");
+    assert_eq!(
+        err(&"a".repeat(MAX_TAG_LENGTH + 1)),
+        "Tag 'aaaaaaaaaaaaaaaaaaaaaaaaa' is 25 characters, above the limit of 24"
+    );
+    assert_eq!(
+        err("ünïcödé"),
+        "Tag 'ünïcödé' contains invalid character 'ü'"
+    );
+    // Lengths count characters, not bytes
+    assert_eq!(
+        err(&"ж".repeat(MAX_TAG_LENGTH + 1))
+            .split(" is ")
+            .nth(1)
+            .unwrap(),
+        "25 characters, above the limit of 24"
+    );
+    assert_eq!(err("Hive"), "Tag 'Hive' contains invalid character 'H'");
+    assert_eq!(
+        err("hive_blog"),
+        "Tag 'hive_blog' contains invalid character '_'"
+    );
+    assert_eq!(
+        err("two words"),
+        "Tag 'two words' contains invalid character ' '"
+    );
+    assert_eq!(err("🐝"), "Tag '🐝' contains invalid character '🐝'");
+    assert_eq!(err("1hive"), "Tag '1hive' must start with a letter");
+    assert_eq!(err("-hive"), "Tag '-hive' must start with a letter");
+    assert_eq!(err("hive-"), "Tag 'hive-' must end with a letter or digit");
+    assert_eq!(
+        err("hive--blog"),
+        "Tag 'hive--blog' contains consecutive dashes"
+    );
+}
+
+#[test]
+fn category_comes_first_and_duplicates_are_dropped() {
+    assert_eq!(
+        validate_tags(Some("Hive"), &tags(&["#photo", "hive", "PHOTO", "art"])).unwrap(),
+        ["hive", "photo", "art"]
+    );
+    assert_eq!(
+        validate_tags(None, &tags(&["art", "hive", "art"])).unwrap(),
+        ["art", "hive"]
+    );
+    assert!(validate_tags(None, &[]).unwrap().is_empty());
+    assert_eq!(validate_tags(Some("hive"), &[]).unwrap(), ["hive"]);
+}
+
+#[test]
+fn every_invalid_tag_is_reported() {
+    let err = validate_tags(Some("Hive"), &tags(&["ok", "bad_tag", "", "x--y"])).unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid tags: Tag 'bad_tag' contains invalid character '_'; Tag is empty; \
+         Tag 'x--y' contains consecutive dashes"
+    );
+    // Duplicate invalid tags are reported once
+    let err = validate_tags(None, &tags(&["a_b", "A_B"])).unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid tags: Tag 'a_b' contains invalid character '_'"
+    );
+}
+
+#[test]
+fn too_many_tags_is_an_error() {
+    let many: Vec<String> = (0..MAX_TAGS).map(|i| format!("tag{i}")).collect();
+    assert_eq!(validate_tags(None, &many).unwrap().len(), MAX_TAGS);
+    // The category counts toward the limit
+    let err = validate_tags(Some("hive"), &many).unwrap_err();
+    assert_eq!(err, "Invalid tags: 11 tags given, above the limit of 10");
+    // Duplicates do not
+    let mut repeated = many.clone();
+    repeated.push("tag0".to_string());
+    assert!(validate_tags(None, &repeated).is_ok());
+}
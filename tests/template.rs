@@ -0,0 +1,124 @@
+use nectarflower_rs::template::PostTemplate;
+use std::collections::HashMap;
+use std::fs;
+
+fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn placeholders_are_substituted() {
+    let template = PostTemplate::new("", "").default_var("week", "1");
+    let render = |text: &str, v: &[(&str, &str)]| template.render_text(text, &vars(v));
+
+    assert_eq!(render("", &[]).unwrap(), "");
+    assert_eq!(render("no placeholders", &[]).unwrap(), "no placeholders");
+    assert_eq!(render("Week {{week}}", &[]).unwrap(), "Week 1");
+    // Supplied values win over defaults, and names may be padded
+    assert_eq!(
+        render("Week {{ week }}", &[("week", "2")]).unwrap(),
+        "Week 2"
+    );
+    assert_eq!(
+        render("{{a}}{{b}}{{a}}", &[("a", "x"), ("b", "y")]).unwrap(),
+        "xyx"
+    );
+    // Substituted values are not expanded again
+    assert_eq!(render("{{a}}", &[("a", "{{week}}")]).unwrap(), "{{week}}");
+    // A lone closing brace pair is plain text
+    assert_eq!(render("}} {{week}} }}", &[]).unwrap(), "}} 1 }}");
+}
+
+#[test]
+fn unicode_text_and_values_are_kept_intact() {
+    let template = PostTemplate::new("", "");
+    let text = "Привет {{name}} — 🐝 {{emoji}}!";
+    let rendered = template
+        .render_text(text, &vars(&[("name", "Zoë"), ("emoji", "🍯")]))
+        .unwrap();
+    assert_eq!(rendered, "Привет Zoë — 🐝 🍯!");
+    assert_eq!(
+        template
+            .render_text("{{ключ}}", &vars(&[("ключ", "значение")]))
+            .unwrap(),
+        "значение"
+    );
+}
+
+#[test]
+fn malformed_templates_are_errors() {
+    let template = PostTemplate::new("", "");
+    let render = |text: &str| template.render_text(text, &HashMap::new());
+
+    assert_eq!(
+        render("Hello {{name}}").unwrap_err(),
+        "Missing template variable 'name'"
+    );
+    assert_eq!(
+        render("Hello {{name").unwrap_err(),
+        "Unterminated '{{' in template"
+    );
+    assert_eq!(render("{{}}").unwrap_err(), "Missing template variable ''");
+    assert_eq!(render("{{").unwrap_err(), "Unterminated '{{' in template");
+}
+
+#[test]
+fn render_builds_a_draft() {
+    let template = PostTemplate::new("Report {{date}}", "Body for {{date}}")
+        .tags(&["hive", "report"])
+        .beneficiary("dev", 500);
+
+    let draft = template
+        .render("bot", None, &vars(&[("date", "2026-10-16")]))
+        .unwrap();
+    assert_eq!(draft.author, "bot");
+    assert_eq!(draft.permlink, "");
+    assert_eq!(draft.title, "Report 2026-10-16");
+    assert_eq!(draft.body, "Body for 2026-10-16");
+    assert_eq!(draft.tags, ["hive", "report"]);
+    assert_eq!(draft.beneficiaries.len(), 1);
+    assert_eq!(draft.beneficiaries[0].weight, 500);
+
+    let draft = template
+        .render("bot", Some("fixed"), &vars(&[("date", "x")]))
+        .unwrap();
+    assert_eq!(draft.permlink, "fixed");
+    // A missing variable in the body fails the whole render
+    assert!(template.render("bot", None, &HashMap::new()).is_err());
+}
+
+#[test]
+fn templates_round_trip_through_disk() {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("nf-template-{}.json", std::process::id()));
+    let template = PostTemplate::new("Ünïcode {{x}}", "Body")
+        .tags(&["hive"])
+        .beneficiary("dev", 1_000)
+        .default_var("x", "✓");
+    template.save(&path).unwrap();
+
+    let loaded = PostTemplate::load(&path).unwrap();
+    assert_eq!(loaded.title, template.title);
+    assert_eq!(loaded.tags, template.tags);
+    assert_eq!(loaded.beneficiaries[0].account, "dev");
+    assert_eq!(
+        loaded.render_text(&loaded.title, &HashMap::new()).unwrap(),
+        "Ünïcode ✓"
+    );
+
+    // Optional fields may be left out of hand-written files
+    fs::write(&path, r#"{"title": "T", "body": "B"}"#).unwrap();
+    let minimal = PostTemplate::load(&path).unwrap();
+    assert!(minimal.tags.is_empty() && minimal.defaults.is_empty());
+
+    fs::write(&path, "{not json").unwrap();
+    let err = PostTemplate::load(&path).unwrap_err();
+    assert!(err.starts_with("Error parsing template"), "{err}");
+    fs::remove_file(&path).unwrap();
+
+    let err = PostTemplate::load(&path).unwrap_err();
+    assert!(err.starts_with("Error reading template"), "{err}");
+}
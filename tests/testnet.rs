@@ -0,0 +1,15 @@
+//! End-to-end checks against the testnet named by `NECTARFLOWER_TESTNET_URL`
+#![cfg(feature = "testnet-harness")]
+
+use nectarflower_rs::testnet::Testnet;
+use nectarflower_rs::transaction::HIVE_CHAIN_ID;
+
+#[test]
+#[ignore = "needs a running hived testnet"]
+fn testnet_produces_blocks() {
+    let testnet = Testnet::from_env()
+        .unwrap()
+        .expect("NECTARFLOWER_TESTNET_URL is not set");
+    assert_ne!(testnet.chain_id(), HIVE_CHAIN_ID);
+    testnet.wait_blocks(2).unwrap();
+}
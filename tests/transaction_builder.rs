@@ -0,0 +1,151 @@
+//! Transactions built within hived's operation, expiration and size limits
+
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::transaction::{MAX_EXPIRATION_SECS, MAX_TRANSACTION_SIZE};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{Client, Transaction, TransactionBuilder, TransactionError};
+use serde_json::json;
+
+fn client() -> Client {
+    let mock = MockTransport::new().respond(
+        "database_api.get_dynamic_global_properties",
+        json!({
+            "head_block_number": 0x1_0005,
+            "head_block_id": "00010005aabbccdd000000000000000000000000",
+            "time": "2024-01-01T00:00:00",
+            "current_witness": "bob",
+            "current_aslot": 10,
+            "last_irreversible_block_num": 10,
+            "total_vesting_fund_hive": "1000.000 HIVE",
+            "total_vesting_shares": "2000000.000000 VESTS",
+        }),
+    );
+    Client::builder().transport(mock).build()
+}
+
+fn transfer(memo: &str) -> Operation {
+    Operation::new(
+        "transfer_operation",
+        json!({ "from": "alice", "to": "bob", "amount": "1.000 HIVE", "memo": memo }),
+    )
+}
+
+fn transfers(memos: &[&str]) -> TransactionBuilder {
+    TransactionBuilder::new().operations(memos.iter().map(|memo| transfer(memo)))
+}
+
+fn memos(tx: &Transaction) -> Vec<&str> {
+    tx.operations
+        .iter()
+        .map(|op| op.value["memo"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn limits_are_checked_before_building() {
+    assert_eq!(
+        TransactionBuilder::new().validate(),
+        Err(TransactionError::Empty)
+    );
+    let three = transfers(&["a", "b", "c"]);
+    assert_eq!(three.len(), 3);
+    assert_eq!(
+        three.clone().max_operations(2).validate(),
+        Err(TransactionError::TooManyOperations { count: 3, max: 2 })
+    );
+    for secs in [0, MAX_EXPIRATION_SECS + 1] {
+        assert_eq!(
+            three.clone().expiration_secs(secs).validate(),
+            Err(TransactionError::InvalidExpiration {
+                secs,
+                max: MAX_EXPIRATION_SECS
+            })
+        );
+    }
+    assert!(three
+        .expiration_secs(MAX_EXPIRATION_SECS)
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn built_transactions_reference_the_head_block() {
+    let client = client();
+    let tx = transfers(&["a"])
+        .expiration_secs(120)
+        .build(&client)
+        .unwrap();
+    assert_eq!(tx.ref_block_num, 5);
+    assert_eq!(tx.ref_block_prefix, 0xddccbbaa);
+    assert_eq!(tx.expiration, "2024-01-01T00:02:00");
+    assert!(tx.signatures.is_empty());
+
+    // The size cap counts the expected signatures
+    let size = tx.serialized_size(2).unwrap();
+    let builder = transfers(&["a"]).expiration_secs(120).signatures(2);
+    assert!(builder.clone().max_size(size).build(&client).is_ok());
+    assert_eq!(
+        builder.max_size(size - 1).build(&client),
+        Err(TransactionError::TooLarge {
+            size,
+            max: size - 1
+        })
+    );
+
+    // Caps are only ever lowered
+    let tx = transfers(&["a"])
+        .max_size(usize::MAX)
+        .build(&client)
+        .unwrap();
+    assert!(tx.serialized_size(1).unwrap() <= MAX_TRANSACTION_SIZE);
+}
+
+#[test]
+fn operations_are_split_by_count_and_size_in_order() {
+    let client = client();
+    let builder = transfers(&["a", "b", "c", "d", "e"]).max_operations(2);
+    let split: Vec<_> = builder
+        .split_into_transactions()
+        .iter()
+        .map(TransactionBuilder::len)
+        .collect();
+    assert_eq!(split, [2, 2, 1]);
+
+    // Batches over the size cap are halved until each fits
+    let two = transfers(&["a", "b"])
+        .build(&client)
+        .unwrap()
+        .serialized_size(1)
+        .unwrap();
+    let built = transfers(&["a", "b", "c", "d", "e"])
+        .max_size(two)
+        .build_all(&client)
+        .unwrap();
+    let batches: Vec<_> = built.iter().map(memos).collect();
+    assert_eq!(batches, [vec!["a", "b"], vec!["c"], vec!["d", "e"]]);
+
+    // A single operation over the cap cannot be split further
+    let err = transfers(&["a", "b"]).max_size(10).build_all(&client);
+    assert!(matches!(
+        err,
+        Err(TransactionError::TooLarge { max: 10, .. })
+    ));
+    assert_eq!(
+        TransactionBuilder::new().build_all(&client),
+        Err(TransactionError::Empty)
+    );
+}
+
+#[test]
+fn node_errors_are_passed_through() {
+    let mock = MockTransport::new().respond_error(
+        "database_api.get_dynamic_global_properties",
+        -32000,
+        "down",
+    );
+    let client = Client::builder().transport(mock).build();
+    let err = transfers(&["a"]).build(&client).unwrap_err();
+    assert!(matches!(err, TransactionError::Rpc(_)), "{err}");
+    let message: String = err.into();
+    assert!(message.contains("down"), "{message}");
+}
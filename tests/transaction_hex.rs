@@ -0,0 +1,176 @@
+use nectarflower_rs::crypto::{decode_public_key, encode_public_key, to_hex};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+use nectarflower_rs::{Client, Operation, SignedTransaction, Transaction, WriteClient};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize)]
+struct Vector {
+    name: String,
+    hex: String,
+    id: String,
+}
+
+fn signed_transaction() -> Transaction {
+    let mut key = [7u8; 33];
+    key[0] = 0x02;
+    let signing_key = encode_public_key(&key, "STM");
+    assert_eq!(decode_public_key(&signing_key).unwrap(), key);
+    Transaction {
+        ref_block_num: 1234,
+        ref_block_prefix: 987654321,
+        expiration: "2024-01-01T12:00:00".to_string(),
+        operations: vec![
+            Operation::new(
+                "witness_update_operation",
+                json!({
+                    "owner": "alice",
+                    "url": "https://alice.example",
+                    "block_signing_key": signing_key,
+                    "props": {
+                        "account_creation_fee": "3.000 HIVE",
+                        "maximum_block_size": 65536,
+                        "hbd_interest_rate": 1500,
+                    },
+                    "fee": "0.000 HIVE",
+                }),
+            ),
+            Operation::new(
+                "comment_options_operation",
+                json!({
+                    "author": "alice",
+                    "permlink": "hello",
+                    "max_accepted_payout": "1000000.000 HBD",
+                    "percent_hbd": 10000,
+                    "allow_votes": true,
+                    "allow_curation_rewards": true,
+                    "extensions": [{
+                        "type": "comment_payout_beneficiaries",
+                        "value": { "beneficiaries": [{ "account": "bob", "weight": 500 }] },
+                    }],
+                }),
+            ),
+        ],
+        extensions: vec![],
+        signatures: vec![to_hex(&[0x1f; 65])],
+    }
+}
+
+#[test]
+fn golden_transactions_decode_from_cli_wallet_hex() {
+    let vectors: Vec<Vector> =
+        serde_json::from_str(include_str!("../fixtures/signing_vectors.json")).unwrap();
+    for v in vectors {
+        // cli_wallet appends the signature list, empty for an unsigned transaction
+        let tx = Transaction::from_hex(&format!("{}00", v.hex)).unwrap();
+        assert_eq!(to_hex(&tx.to_bytes().unwrap()), v.hex, "{}", v.name);
+        assert_eq!(tx.id().unwrap(), v.id, "{}", v.name);
+    }
+}
+
+#[test]
+fn signed_transactions_round_trip_through_hex() {
+    let tx = signed_transaction();
+    let hex = tx.to_hex().unwrap();
+    let decoded = SignedTransaction::from_hex(&hex).unwrap();
+    assert_eq!(decoded.to_hex().unwrap(), hex);
+    assert_eq!(decoded.signatures, tx.signatures);
+    assert_eq!(
+        decoded.operations[0].value["block_signing_key"],
+        tx.operations[0].value["block_signing_key"]
+    );
+    assert_eq!(
+        decoded.operations[1].value["extensions"][0]["value"]["beneficiaries"][0]["weight"],
+        500
+    );
+    assert!(Transaction::from_hex(&format!("{hex}00")).is_err());
+
+    let mut disabled = tx.clone();
+    disabled.operations[0].value["block_signing_key"] = json!(DISABLED_SIGNING_KEY);
+    let decoded = Transaction::from_hex(&disabled.to_hex().unwrap()).unwrap();
+    assert_eq!(
+        decoded.operations[0].value["block_signing_key"],
+        DISABLED_SIGNING_KEY
+    );
+}
+
+#[test]
+fn externally_signed_hex_is_relayed() {
+    let mock =
+        MockTransport::new().respond("network_broadcast_api.broadcast_transaction", json!({}));
+    let client = WriteClient::new(Client::builder().transport(mock.clone()).build());
+    let tx = signed_transaction();
+
+    let trx_id = client.broadcast_hex(&tx.to_hex().unwrap()).unwrap();
+    assert_eq!(trx_id, tx.id().unwrap());
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(request["params"]["trx"]["signatures"][0], tx.signatures[0]);
+
+    let mut unsigned = tx;
+    unsigned.signatures.clear();
+    assert!(client.broadcast_hex(&unsigned.to_hex().unwrap()).is_err());
+}
+
+#[test]
+fn account_updates_serialize_authorities_in_key_order() {
+    let key = |first: u8, fill: u8| {
+        let mut bytes = [fill; 33];
+        bytes[0] = first;
+        encode_public_key(&bytes, "STM")
+    };
+    let (low, high) = (key(0x02, 9), key(0x03, 1));
+    let posting = |account_auths: serde_json::Value| {
+        json!({
+            "weight_threshold": 1,
+            "account_auths": account_auths,
+            "key_auths": [[high, 1], [low, 1]],
+        })
+    };
+    let tx = |account_auths| Transaction {
+        ref_block_num: 1,
+        ref_block_prefix: 2,
+        expiration: "2024-01-01T12:00:00".to_string(),
+        operations: vec![
+            Operation::new(
+                "account_update2_operation",
+                json!({
+                    "account": "alice",
+                    "posting": posting(account_auths),
+                    "json_metadata": "",
+                    "posting_json_metadata": "{}",
+                    "extensions": [],
+                }),
+            ),
+            Operation::new(
+                "account_update_operation",
+                json!({
+                    "account": "alice",
+                    "memo_key": low,
+                    "json_metadata": "{}",
+                }),
+            ),
+        ],
+        extensions: vec![],
+        signatures: vec![],
+    };
+
+    let decoded =
+        SignedTransaction::from_hex(&tx(json!([["dapp", 1], ["app", 1]])).to_hex().unwrap())
+            .unwrap();
+    let update2 = &decoded.operations[0].value;
+    assert_eq!(
+        update2["posting"]["account_auths"],
+        json!([["app", 1], ["dapp", 1]])
+    );
+    assert_eq!(
+        update2["posting"]["key_auths"],
+        json!([[low, 1], [high, 1]])
+    );
+    assert!(update2.get("owner").is_none());
+    assert!(update2.get("memo_key").is_none());
+    assert_eq!(decoded.operations[1].value["memo_key"], low);
+
+    let err = tx(json!([["app", 1], ["app", 2]])).to_hex().unwrap_err();
+    assert_eq!(err, "Duplicate account app in posting of account_update2");
+}
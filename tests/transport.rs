@@ -0,0 +1,220 @@
+use nectarflower_rs::price::JsonUrlSource;
+use nectarflower_rs::transport::{HttpTransport, MockTransport, Transport, METHOD_NOT_FOUND_CODE};
+use nectarflower_rs::{Client, NectarError, PriceSource, RestApi};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+fn client(mock: &MockTransport) -> Client {
+    Client::builder()
+        .nodes(["https://down.example", "https://up.example"])
+        .transport(mock.clone())
+        .build()
+}
+
+#[test]
+fn mock_answers_fail_over_and_are_recorded() {
+    let mock = MockTransport::new()
+        .fail_node("https://down.example")
+        .respond(
+            "database_api.get_config",
+            json!({ "HIVE_BLOCK_INTERVAL": 3 }),
+        )
+        .respond_error("database_api.get_version", -32000, "Assert Exception");
+    let client = client(&mock);
+
+    let config: Value = client.call("database_api.get_config", json!({})).unwrap();
+    assert_eq!(config["HIVE_BLOCK_INTERVAL"], 3);
+    let requests = mock.requests();
+    assert_eq!(requests[0].0, "https://down.example");
+    assert_eq!(requests[1].0, "https://up.example");
+    assert_eq!(requests[1].1["method"], "database_api.get_config");
+
+    let version = client.call::<_, Value>("database_api.get_version", json!({}));
+    assert!(version
+        .unwrap_err()
+        .attempts()
+        .last()
+        .unwrap()
+        .error
+        .is_rpc());
+
+    let results = client
+        .call_batch([
+            ("database_api.get_config", json!({})),
+            ("database_api.get_witness_schedule", json!({})),
+        ])
+        .unwrap();
+    assert_eq!(results[0], Ok(json!({ "HIVE_BLOCK_INTERVAL": 3 })));
+    assert_eq!(results[1].as_ref().unwrap_err().code, METHOD_NOT_FOUND_CODE);
+}
+
+#[test]
+fn account_lookups_fall_back_to_condenser_over_the_mock() {
+    let mock = MockTransport::new()
+        .respond_error(
+            "database_api.find_accounts",
+            -32002,
+            "Could not find API database_api",
+        )
+        .respond(
+            "condenser_api.get_accounts",
+            json!([{ "name": "alice", "json_metadata": "", "reputation": 25 }]),
+        );
+    let accounts = client(&mock).get_extended_accounts(&["alice"]).unwrap();
+    assert_eq!(accounts[0].reputation, Some(25));
+
+    let missing = Client::builder()
+        .nodes(["https://up.example"])
+        .transport(MockTransport::new())
+        .build()
+        .call::<_, Value>("bridge.get_community", json!({}));
+    assert!(matches!(missing, Err(NectarError::NoNodesAvailable { .. })));
+}
+
+#[test]
+fn rest_and_price_requests_go_through_the_transport() {
+    let mock = MockTransport::new()
+        .fail_node("https://down.example/points?username=alice")
+        .fail_node("https://down.example/feed")
+        .respond_url(
+            "https://up.example/points?username=alice",
+            json!({ "points": "12.500" }),
+        )
+        .respond_url("https://up.example/feed", json!({ "usd": 1.25 }))
+        .respond_url(
+            "https://prices.example/hive",
+            json!({ "hive": { "usd": "0.30" } }),
+        );
+    let client = client(&mock);
+    let api = RestApi::new("test", &["https://down.example", "https://up.example"]);
+
+    let points: Value = api
+        .get(&client, "points", &[("username", "alice")])
+        .unwrap();
+    assert_eq!(points["points"], "12.500");
+    let feed: Value = api
+        .post(&client, "/feed", &json!({ "asset": "HIVE" }))
+        .unwrap();
+    assert_eq!(feed["usd"], 1.25);
+    let price = JsonUrlSource::new("test", "https://prices.example/hive", "/hive/usd")
+        .fetch_price(&client)
+        .unwrap();
+    assert_eq!(price, 0.30);
+
+    let requests = mock.requests();
+    assert_eq!(
+        requests[0],
+        (
+            "https://down.example/points?username=alice".to_string(),
+            Value::Null
+        )
+    );
+    assert_eq!(requests[2].1, json!({ "asset": "HIVE" }));
+    assert_eq!(requests.len(), 5);
+}
+
+/// Answer one request with `body`, returning the request head
+fn serve_once(body: &'static str) -> (u16, thread::JoinHandle<String>) {
+    serve_raw(format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    ))
+}
+
+/// Answer one request with a raw HTTP `response`, returning the request head
+fn serve_raw(response: String) -> (u16, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut head = String::new();
+        let mut reader = BufReader::new(&stream);
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        head
+    });
+    (port, handle)
+}
+
+#[test]
+fn default_transport_uses_the_client_http_settings() {
+    let (port, server) = serve_once(r#"{"points": "1.000"}"#);
+    let client = Client::builder()
+        .user_agent("settings-test/1.0")
+        .build()
+        .dns_override("rest.invalid", &[([127, 0, 0, 1], port).into()]);
+    let api = RestApi::new("test", &[&format!("http://rest.invalid:{port}")]);
+    let points: Value = api.get(&client, "points", &[]).unwrap();
+    assert_eq!(points["points"], "1.000");
+    let head = server.join().unwrap().to_ascii_lowercase();
+    assert!(head.contains("user-agent: settings-test/1.0"), "{head}");
+
+    let (port, _server) = serve_once(r#"{"points": "1.000"}"#);
+    let client = Client::new().max_response_size(8);
+    let api = RestApi::new("test", &[&format!("http://127.0.0.1:{port}")]);
+    let err = api.get::<Value>(&client, "points", &[]).unwrap_err();
+    assert!(err.contains("8"), "{err}");
+}
+
+#[test]
+fn responses_over_the_size_limit_are_refused() {
+    let body = r#"{"jsonrpc":"2.0","id":1,"result":{"HIVE_BLOCK_INTERVAL":3}}"#;
+    let request = br#"{"jsonrpc":"2.0","id":1,"method":"database_api.get_config","params":{}}"#;
+
+    // A declared length over the limit is refused before reading the body
+    let (port, _server) = serve_once(body);
+    let err = HttpTransport::new()
+        .max_response_size(16)
+        .send(&format!("http://127.0.0.1:{port}"), request)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        NectarError::ResponseTooLarge {
+            length: Some(len),
+            limit: 16,
+        } if len == body.len() as u64
+    ));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Response of {} bytes exceeds the limit of 16 bytes",
+            body.len()
+        )
+    );
+
+    // Without a length, reading stops once the limit is passed
+    let (port, _server) = serve_raw(format!(
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n{:x}\r\n{body}\r\n0\r\n\r\n",
+        body.len()
+    ));
+    let err = HttpTransport::new()
+        .max_response_size(16)
+        .send(&format!("http://127.0.0.1:{port}"), request)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        NectarError::ResponseTooLarge {
+            length: None,
+            limit: 16
+        }
+    ));
+    assert_eq!(err.to_string(), "Response exceeds the limit of 16 bytes");
+
+    // Bodies at the limit pass, through the client as well
+    let (port, _server) = serve_once(body);
+    let client = Client::builder()
+        .nodes([format!("http://127.0.0.1:{port}")])
+        .build()
+        .max_response_size(body.len() as u64);
+    let config: Value = client.call("database_api.get_config", json!({})).unwrap();
+    assert_eq!(config["HIVE_BLOCK_INTERVAL"], 3);
+}
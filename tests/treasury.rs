@@ -0,0 +1,69 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::operation::Operation;
+use nectarflower_rs::{HistoryEntry, ProposalPayments, TreasuryOp};
+use serde_json::json;
+
+fn entry(timestamp: &str, op: Operation) -> HistoryEntry {
+    HistoryEntry {
+        index: 0,
+        trx_id: "0".repeat(40),
+        block: 1,
+        trx_in_block: 0,
+        op_in_trx: 0,
+        virtual_op: true,
+        timestamp: timestamp.to_string(),
+        op,
+    }
+}
+
+fn pay(proposal_id: u32, amount: &str) -> Operation {
+    Operation::new(
+        "proposal_pay_operation",
+        json!({
+            "proposal_id": proposal_id,
+            "receiver": "builder",
+            "payer": "hive.fund",
+            "payment": {"amount": amount, "precision": 3, "nai": "@@000000013"},
+        }),
+    )
+}
+
+#[test]
+fn proposal_payments_add_up_per_proposal() {
+    let mut payments = ProposalPayments::new();
+    payments
+        .apply(&entry("2024-01-02T00:00:00", pay(7, "1500")))
+        .unwrap();
+    payments
+        .apply(&entry("2024-01-01T00:00:00", pay(7, "1000")))
+        .unwrap();
+    payments
+        .apply(&entry("2024-01-01T00:00:00", pay(9, "250")))
+        .unwrap();
+
+    let paid = payments.get(7).unwrap();
+    assert_eq!(paid.total, Asset::new(2_500, AssetSymbol::Hbd));
+    assert_eq!(paid.payments, 2);
+    assert_eq!(paid.first_paid, "2024-01-01T00:00:00");
+    assert_eq!(paid.last_paid, "2024-01-02T00:00:00");
+    assert_eq!(payments.total(8), Asset::zero(AssetSymbol::Hbd));
+    assert_eq!(payments.grand_total(), Asset::new(2_750, AssetSymbol::Hbd));
+}
+
+#[test]
+fn legacy_sps_names_decode_as_dhf_ops() {
+    let op = Operation::new(
+        "sps_fund_operation",
+        json!({"fund_account": "hive.fund", "additional_funds": "10.000 HBD"}),
+    );
+    let Some(TreasuryOp::DhfFunding(funding)) = TreasuryOp::from_operation(&op).unwrap() else {
+        panic!("expected dhf_funding");
+    };
+    assert_eq!(funding.treasury, "hive.fund");
+    assert_eq!(
+        TreasuryOp::DhfFunding(funding).treasury_delta(),
+        vec![Asset::new(10_000, AssetSymbol::Hbd)]
+    );
+    let transfer = Operation::new("transfer_operation", json!({}));
+    assert_eq!(TreasuryOp::from_operation(&transfer).unwrap(), None);
+}
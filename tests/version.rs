@@ -0,0 +1,44 @@
+use nectarflower_rs::fail_reason::FailReason;
+use nectarflower_rs::{Client, Url};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// A node that answers one `get_version` call for another chain
+fn testnet_node() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let node = format!("http://{}/", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 4096];
+        let _ = stream.read(&mut request).unwrap();
+        let body = r#"{"jsonrpc":"2.0","result":{"blockchain_version":"1.27.5","hive_revision":"abc","fc_revision":"abc","chain_id":"18dcf0a285365fc58b71f18b3d3fec954aa0c141c44e4e5cb4cf777b9eab274e","node_type":"testnet"},"id":1}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+    });
+    node
+}
+
+#[test]
+fn nodes_of_another_chain_are_dropped_when_added() {
+    let testnet = testnet_node();
+    let unreachable = "http://127.0.0.1:9/".to_string();
+    let mut client = Client::new().verify_chain_on_add();
+    let change = client
+        .set_nodes(vec![testnet.clone(), unreachable.clone()], HashMap::new())
+        .unwrap();
+    assert_eq!(client.nodes, std::slice::from_ref(&unreachable));
+    assert_eq!(change.added, [unreachable]);
+    let failure = &client.failing_nodes[&Url::parse(&testnet).unwrap()];
+    assert_eq!(failure.reason, FailReason::WrongChain);
+    assert!(failure.message.contains("expected beeab0de"), "{failure}");
+    assert_eq!(
+        FailReason::classify(&failure.message),
+        FailReason::WrongChain
+    );
+}
@@ -0,0 +1,169 @@
+//! Paging through a post's votes and annotating them with voter HIVE Power
+
+use nectarflower_rs::error::NectarError;
+use nectarflower_rs::transport::{MockTransport, Transport};
+use nectarflower_rs::votes::LIST_VOTES_LIMIT;
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// Answers `database_api.list_votes` from `votes` like a node, in
+/// (author, permlink, voter) order, and everything else from `mock`
+#[derive(Clone)]
+struct Node {
+    votes: Vec<(String, String, String)>,
+    mock: MockTransport,
+    starts: Arc<Mutex<Vec<Value>>>,
+}
+
+impl Node {
+    fn new(mut votes: Vec<(String, String, String)>) -> Self {
+        votes.sort();
+        Node {
+            votes,
+            mock: MockTransport::new(),
+            starts: Arc::default(),
+        }
+    }
+}
+
+impl Transport for Node {
+    fn send(&self, url: &str, body: &[u8]) -> Result<Vec<u8>, NectarError> {
+        let request: Value = serde_json::from_slice(body).unwrap();
+        if request["method"] != "database_api.list_votes" {
+            return self.mock.send(url, body);
+        }
+        let params = &request["params"];
+        assert_eq!(params["order"], "by_comment_voter");
+        self.starts.lock().unwrap().push(params["start"].clone());
+        let start: (String, String, String) =
+            serde_json::from_value(params["start"].clone()).unwrap();
+        let limit = params["limit"].as_u64().unwrap() as usize;
+        let page: Vec<Value> = self
+            .votes
+            .iter()
+            .filter(|vote| **vote >= start)
+            .take(limit)
+            .map(|(author, permlink, voter)| {
+                json!({
+                    "voter": voter,
+                    "author": author,
+                    "permlink": permlink,
+                    "weight": "18446744073709551615",
+                    "rshares": "-123456789012",
+                    "vote_percent": -10000,
+                    "last_update": "2024-01-01T00:00:00",
+                    "num_changes": 0,
+                })
+            })
+            .collect();
+        let response =
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "votes": page } });
+        Ok(response.to_string().into_bytes())
+    }
+}
+
+fn votes(author: &str, permlink: &str, count: usize) -> Vec<(String, String, String)> {
+    (0..count)
+        .map(|i| {
+            (
+                author.to_string(),
+                permlink.to_string(),
+                format!("voter{i:05}"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn votes_are_paged_without_duplicates() {
+    let limit = LIST_VOTES_LIMIT as usize;
+    let mut all = votes("alice", "post", 2 * limit + 500);
+    all.extend(votes("alice", "earlier", 10));
+    all.extend(votes("bob", "post", 10));
+    let node = Node::new(all);
+    let client = Client::builder().transport(node.clone()).build();
+
+    let fetched = client.get_active_votes("alice", "post").unwrap();
+    assert_eq!(fetched.len(), 2 * limit + 500);
+    assert!(fetched.windows(2).all(|w| w[0].voter < w[1].voter));
+    assert!(fetched
+        .iter()
+        .all(|v| v.author == "alice" && v.permlink == "post"));
+    // Votes are read from strings, and weights beyond i64 survive
+    assert_eq!(fetched[0].weight, u64::MAX);
+    assert_eq!(fetched[0].rshares, -123_456_789_012);
+
+    // Each page starts at the last voter of the one before
+    let starts = node.starts.lock().unwrap().clone();
+    assert_eq!(
+        starts,
+        [
+            json!(["alice", "post", ""]),
+            json!(["alice", "post", format!("voter{:05}", limit - 1)]),
+            json!(["alice", "post", format!("voter{:05}", 2 * limit - 2)]),
+        ]
+    );
+}
+
+#[test]
+fn a_full_page_reaching_the_next_post_is_the_last() {
+    let limit = LIST_VOTES_LIMIT as usize;
+    let mut all = votes("alice", "post", limit - 5);
+    all.extend(votes("bob", "post", 10));
+    let node = Node::new(all);
+    let client = Client::builder().transport(node.clone()).build();
+
+    assert_eq!(
+        client.get_active_votes("alice", "post").unwrap().len(),
+        limit - 5
+    );
+    assert_eq!(node.starts.lock().unwrap().len(), 1);
+
+    // A post without votes
+    assert!(client.get_active_votes("carol", "none").unwrap().is_empty());
+}
+
+#[test]
+fn votes_are_annotated_with_voter_power_and_age() {
+    let mut node = Node::new(votes("alice", "post", 2));
+    node.mock = MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 10,
+                "head_block_id": "0000000aaabbccdd000000000000000000000000",
+                "time": "2024-01-01T01:00:00",
+                "current_witness": "bob",
+                "current_aslot": 10,
+                "last_irreversible_block_num": 10,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(
+            "database_api.find_accounts",
+            json!({ "accounts": [{
+                "name": "voter00000",
+                "json_metadata": "",
+                "balance": "0.000 HIVE",
+                "hbd_balance": "0.000 HBD",
+                "vesting_shares": "3000.000000 VESTS",
+                "delegated_vesting_shares": "1000.000000 VESTS",
+                "received_vesting_shares": "500.000000 VESTS",
+            }] }),
+        );
+    let client = Client::builder().transport(node).build();
+
+    let annotated = client.get_annotated_votes("alice", "post").unwrap();
+    assert_eq!(annotated.len(), 2);
+    assert_eq!(
+        annotated[0].effective_vests.to_string(),
+        "2500.000000 VESTS"
+    );
+    assert_eq!(annotated[0].effective_hp.to_string(), "1.250 HIVE");
+    assert_eq!(annotated[0].age_secs, 3600);
+    // Voters the node did not return count as having no power
+    assert_eq!(annotated[1].vote.voter, "voter00001");
+    assert_eq!(annotated[1].effective_vests.amount, 0);
+}
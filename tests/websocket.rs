@@ -0,0 +1,139 @@
+#![cfg(feature = "websocket")]
+
+use nectarflower_rs::{Client, NectarError};
+use serde_json::{json, Value};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use tungstenite::Message;
+
+/// Answers `requests` calls on connection `connection` with its number, pinging before each answer
+fn serve(stream: TcpStream, connection: usize, requests: usize) {
+    let mut socket = tungstenite::accept(stream).unwrap();
+    let mut answered = 0;
+    while answered < requests {
+        let Ok(message) = socket.read() else {
+            return;
+        };
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let request: Value = serde_json::from_str(&text).unwrap();
+        socket.send(Message::Ping(b"hi".to_vec().into())).unwrap();
+        let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": connection});
+        socket.send(Message::text(response.to_string())).unwrap();
+        answered += 1;
+    }
+}
+
+/// Accepts connections on a new port, reading one request on each and dropping the socket
+fn dropping_node() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut socket) = tungstenite::accept(stream.unwrap()) else {
+                continue;
+            };
+            let _ = socket.read();
+        }
+    });
+    format!("ws://127.0.0.1:{port}")
+}
+
+#[test]
+fn calls_reuse_the_socket_and_reconnect_after_it_closes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        // The first connection answers two calls and closes; the second stays up
+        for (connection, requests) in [(1, 2), (2, usize::MAX)] {
+            let (stream, _) = listener.accept().unwrap();
+            serve(stream, connection, requests);
+        }
+    });
+
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let dead = format!("ws://127.0.0.1:{}", closed.local_addr().unwrap().port());
+    drop(closed);
+    let client = Client::builder()
+        .nodes([dead, format!("ws://127.0.0.1:{port}")])
+        .build();
+
+    let answers: Vec<u32> = (0..4)
+        .map(|_| client.call("condenser_api.get_config", json!([])).unwrap())
+        .collect();
+    assert_eq!(answers, [1, 1, 2, 2]);
+
+    client.close_websockets();
+    server.join().unwrap();
+}
+
+#[test]
+fn nodes_dropping_the_socket_mid_call_are_failed_over() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(stream, 7, usize::MAX);
+    });
+
+    let client = Client::builder()
+        .nodes([dropping_node(), format!("ws://127.0.0.1:{port}")])
+        .build();
+    let answer: u32 = client.call("condenser_api.get_config", json!([])).unwrap();
+    assert_eq!(answer, 7);
+}
+
+#[test]
+fn oversized_messages_are_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let mut socket = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+        while let Ok(message) = socket.read() {
+            if message.is_text() {
+                let response = json!({"jsonrpc": "2.0", "id": 1, "result": "x".repeat(4096)});
+                let _ = socket.send(Message::text(response.to_string()));
+            }
+        }
+    });
+
+    let client = Client::builder()
+        .nodes([format!("ws://127.0.0.1:{port}")])
+        .build()
+        .max_response_size(1024);
+    let err = client
+        .call_traced::<_, Value>("condenser_api.get_config", json!([]))
+        .unwrap_err();
+    assert!(!err.attempts().is_empty());
+    assert!(err
+        .attempts()
+        .iter()
+        .all(|a| matches!(a.error, NectarError::ResponseTooLarge { limit: 1024, .. })));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_client_calls_websocket_nodes() {
+    use nectarflower_rs::AsyncClient;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(stream, 3, usize::MAX);
+    });
+
+    let client = AsyncClient::from(
+        Client::builder()
+            .nodes([dropping_node(), format!("ws://127.0.0.1:{port}")])
+            .build(),
+    );
+    for _ in 0..2 {
+        let answer: u32 = client
+            .call("condenser_api.get_config", json!([]))
+            .await
+            .unwrap();
+        assert_eq!(answer, 3);
+    }
+}
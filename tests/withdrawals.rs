@@ -0,0 +1,196 @@
+//! Withdrawals are only re-queued once their expiration is irreversible
+
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::{
+    Client, TransactionSigner, WithdrawalLimits, WithdrawalQueue, WithdrawalStatus, WriteClient,
+};
+use serde_json::json;
+
+struct FakeSigner;
+
+impl TransactionSigner for FakeSigner {
+    fn sign_digest(&self, _digest: &[u8; 32]) -> Result<String, String> {
+        Ok("1f".repeat(65))
+    }
+}
+
+fn client(mock: &MockTransport) -> WriteClient {
+    WriteClient::new(Client::builder().transport(mock.clone()).build())
+}
+
+fn mock(status: &str) -> MockTransport {
+    MockTransport::new()
+        .respond(
+            "database_api.get_dynamic_global_properties",
+            json!({
+                "head_block_number": 1000,
+                "head_block_id": "000003e8aabbccdd000000000000000000000000",
+                "time": "2024-01-01T00:00:00",
+                "current_witness": "bob",
+                "current_aslot": 1000,
+                "last_irreversible_block_num": 980,
+                "total_vesting_fund_hive": "1000.000 HIVE",
+                "total_vesting_shares": "2000000.000000 VESTS",
+            }),
+        )
+        .respond(
+            "rc_api.find_rc_accounts",
+            json!({ "rc_accounts": [{
+                "account": "exchange",
+                "rc_manabar": { "current_mana": "100000000000000", "last_update_time": 1704067200 },
+                "max_rc": "100000000000000",
+            }] }),
+        )
+        .respond(
+            "database_api.get_transaction_hex",
+            json!({ "hex": "e803ccddaabb00" }),
+        )
+        .respond("network_broadcast_api.broadcast_transaction", json!({}))
+        .respond(
+            "transaction_status_api.find_transaction",
+            json!({ "status": status }),
+        )
+}
+
+#[test]
+fn reversibly_expired_withdrawals_stay_pending() {
+    let mock = mock("expired_reversible");
+    let client = client(&mock);
+    let mut queue = WithdrawalQueue::new("exchange", WithdrawalLimits::default());
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    assert_eq!(queue.process(&client, &FakeSigner).unwrap(), 1);
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Pending { .. })
+    ));
+    queue.refresh(&client).unwrap();
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Pending { .. })
+    ));
+    assert!(!queue.requeue(id));
+}
+
+#[test]
+fn irreversibly_expired_withdrawals_can_be_requeued() {
+    let mock = mock("expired_irreversible");
+    let client = client(&mock);
+    let mut queue = WithdrawalQueue::new("exchange", WithdrawalLimits::default());
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    assert_eq!(queue.process(&client, &FakeSigner).unwrap(), 0);
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Failed(_))
+    ));
+    assert!(queue.requeue(id));
+}
+
+struct BrokenSigner;
+
+impl TransactionSigner for BrokenSigner {
+    fn sign_digest(&self, _digest: &[u8; 32]) -> Result<String, String> {
+        Err("Signer unavailable".to_string())
+    }
+}
+
+#[test]
+fn rejected_withdrawals_fail_without_retrying() {
+    let mock = mock("unknown").respond_error(
+        "network_broadcast_api.broadcast_transaction",
+        -32003,
+        "Assert Exception: missing required active authority",
+    );
+    let client = client(&mock);
+    let mut queue = WithdrawalQueue::new("exchange", WithdrawalLimits::default());
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    assert_eq!(queue.process(&client, &FakeSigner).unwrap(), 0);
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Failed(e)) if e.contains("missing required active authority")
+    ));
+    let broadcasts = mock
+        .requests()
+        .iter()
+        .filter(|(_, req)| req["method"] == "network_broadcast_api.broadcast_transaction")
+        .count();
+    assert_eq!(broadcasts, 1);
+    assert!(queue.requeue(id));
+}
+
+#[test]
+fn signing_failures_fail_before_broadcasting() {
+    let mock = mock("unknown");
+    let client = client(&mock);
+    let mut queue = WithdrawalQueue::new("exchange", WithdrawalLimits::default());
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    assert_eq!(queue.process(&client, &BrokenSigner).unwrap(), 0);
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Failed(_))
+    ));
+    assert!(mock
+        .requests()
+        .iter()
+        .all(|(_, req)| req["method"] != "network_broadcast_api.broadcast_transaction"));
+}
+
+#[test]
+fn queue_is_persisted_as_pending_before_broadcasting() {
+    let mock = mock("within_irreversible_block");
+    let client = client(&mock);
+    let limits = WithdrawalLimits::default().per_day(Asset::new(10_000, AssetSymbol::Hive));
+    let mut queue = WithdrawalQueue::new("exchange", limits);
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    let mut saved = None;
+    let sent = queue
+        .process_with(&client, &FakeSigner, |queue| {
+            assert!(mock
+                .requests()
+                .iter()
+                .all(|(_, req)| req["method"] != "network_broadcast_api.broadcast_transaction"));
+            saved = Some(serde_json::to_string(queue).map_err(|e| e.to_string())?);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(sent, 1);
+    assert!(matches!(
+        queue.status(id),
+        Some(WithdrawalStatus::Sent { .. })
+    ));
+
+    // A restart from the saved state resolves the batch instead of resending it
+    let mut restored: WithdrawalQueue = serde_json::from_str(&saved.unwrap()).unwrap();
+    assert!(matches!(
+        restored.status(id),
+        Some(WithdrawalStatus::Pending { .. })
+    ));
+    restored.refresh(&client).unwrap();
+    assert_eq!(restored.status(id), queue.status(id));
+    // The daily total survived the restart
+    restored.enqueue("bob", Asset::new(6_000, AssetSymbol::Hive), "");
+    assert_eq!(restored.process(&client, &FakeSigner).unwrap(), 0);
+}
+
+#[test]
+fn failed_persistence_broadcasts_nothing() {
+    let mock = mock("unknown");
+    let client = client(&mock);
+    let mut queue = WithdrawalQueue::new("exchange", WithdrawalLimits::default());
+    let id = queue.enqueue("alice", Asset::new(5_000, AssetSymbol::Hive), "");
+
+    let err = queue
+        .process_with(&client, &FakeSigner, |_| Err("Disk full".to_string()))
+        .unwrap_err();
+    assert_eq!(err, "Disk full");
+    assert_eq!(queue.status(id), Some(&WithdrawalStatus::Queued));
+    assert!(mock
+        .requests()
+        .iter()
+        .all(|(_, req)| req["method"] != "network_broadcast_api.broadcast_transaction"));
+}
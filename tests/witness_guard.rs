@@ -0,0 +1,111 @@
+//! Witness failover decisions, in dry-run mode so nothing is broadcast
+
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+use nectarflower_rs::{Client, GuardEvent, TransactionSigner, Witness, WitnessGuard, WriteClient};
+use serde_json::json;
+
+struct NoSigner;
+
+impl TransactionSigner for NoSigner {
+    fn sign_digest(&self, _digest: &[u8; 32]) -> Result<String, String> {
+        Err("dry run must not sign".to_string())
+    }
+}
+
+fn witness(total_missed: u32, signing_key: &str) -> Witness {
+    serde_json::from_value(json!({
+        "owner": "alice",
+        "url": "https://example.com",
+        "votes": "1000",
+        "total_missed": total_missed,
+        "signing_key": signing_key,
+        "running_version": "1.27.5",
+        "hbd_exchange_rate": {
+            "base": { "amount": "250", "precision": 3, "nai": "@@000000013" },
+            "quote": { "amount": "1000", "precision": 3, "nai": "@@000000021" },
+        },
+        "last_hbd_exchange_update": "2024-01-01T00:00:00",
+        "props": {
+            "account_creation_fee": { "amount": "3000", "precision": 3, "nai": "@@000000021" },
+            "maximum_block_size": 65536,
+            "hbd_interest_rate": 2000,
+        },
+    }))
+    .unwrap()
+}
+
+#[test]
+fn guard_fails_over_to_backups_then_disables() {
+    let client = WriteClient::new(Client::new());
+    let mut guard = WitnessGuard::new("alice", 2, &NoSigner)
+        .backup_key("STMbackup")
+        .dry_run();
+
+    assert!(matches!(
+        guard.process(&client, &witness(10, "STMprimary"))[..],
+        [GuardEvent::Started {
+            total_missed: 10,
+            ..
+        }]
+    ));
+    assert_eq!(
+        guard.process(&client, &witness(11, "STMprimary")),
+        [GuardEvent::Missed {
+            total_missed: 11,
+            since_failover: 1
+        }]
+    );
+    let events = guard.process(&client, &witness(12, "STMprimary"));
+    assert_eq!(
+        events[1],
+        GuardEvent::FailedOver {
+            from: "STMprimary".into(),
+            to: "STMbackup".into(),
+            trx_id: None,
+        }
+    );
+
+    // The backup misses too, so the witness is disabled
+    let events = guard.process(&client, &witness(14, "STMbackup"));
+    assert!(matches!(&events[1], GuardEvent::FailedOver { to, .. } if to == DISABLED_SIGNING_KEY));
+
+    guard.process(&client, &witness(14, DISABLED_SIGNING_KEY));
+    let events = guard.process(&client, &witness(16, DISABLED_SIGNING_KEY));
+    assert_eq!(events.last(), Some(&GuardEvent::Exhausted));
+}
+
+#[test]
+fn lagging_node_reporting_fewer_misses_is_ignored() {
+    let client = WriteClient::new(Client::new());
+    let mut guard = WitnessGuard::new("alice", 2, &NoSigner)
+        .backup_key("STMbackup")
+        .dry_run();
+
+    guard.process(&client, &witness(10, "STMprimary"));
+    assert!(guard.process(&client, &witness(7, "STMprimary")).is_empty());
+    assert_eq!(
+        guard.process(&client, &witness(11, "STMprimary")),
+        [GuardEvent::Missed {
+            total_missed: 11,
+            since_failover: 1
+        }]
+    );
+}
+
+#[test]
+fn dry_run_walks_the_backup_keys_once() {
+    let client = WriteClient::new(Client::new());
+    let mut guard = WitnessGuard::new("alice", 1, &NoSigner)
+        .backup_key("STMbackup")
+        .keep_last_key()
+        .dry_run();
+
+    guard.process(&client, &witness(10, "STMprimary"));
+    let events = guard.process(&client, &witness(11, "STMprimary"));
+    assert!(matches!(&events[1], GuardEvent::FailedOver { to, .. } if to == "STMbackup"));
+    // Nothing was broadcast, so the node still reports the primary key
+    let events = guard.process(&client, &witness(12, "STMprimary"));
+    assert_eq!(events.last(), Some(&GuardEvent::Exhausted));
+    let events = guard.process(&client, &witness(13, "STMprimary"));
+    assert_eq!(events.len(), 1);
+}
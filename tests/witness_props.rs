@@ -0,0 +1,43 @@
+use nectarflower_rs::asset::{Asset, AssetSymbol};
+use nectarflower_rs::serializer::Serializer;
+use nectarflower_rs::witness::DISABLED_SIGNING_KEY;
+use nectarflower_rs::WitnessProperties;
+
+#[test]
+fn witness_properties_are_hex_encoded_and_sorted() {
+    let props = WitnessProperties::new("alice", DISABLED_SIGNING_KEY)
+        .maximum_block_size(65_536)
+        .hbd_apr(15.0)
+        .account_creation_fee(Asset::new(3_000, AssetSymbol::Hive));
+    let op = props.operation().unwrap();
+    let value = serde_json::to_value(&op).unwrap();
+    let pairs = &value["value"]["props"];
+    assert_eq!(pairs[0][0], "account_creation_fee");
+    assert_eq!(pairs[0][1], "b80b00000000000003535445454d0000");
+    assert_eq!(pairs[1][0], "hbd_interest_rate");
+    assert_eq!(pairs[1][1], "dc05");
+    assert_eq!(pairs[2][0], "key");
+    assert_eq!(pairs[2][1], "00".repeat(33));
+    assert_eq!(pairs[3][0], "maximum_block_size");
+    assert_eq!(pairs[3][1], "00000100");
+
+    let mut serializer = Serializer::new();
+    serializer.operation(&op).unwrap();
+    let bytes = serializer.into_bytes();
+    assert_eq!(bytes[0], 42);
+    assert_eq!(*bytes.last().unwrap(), 0);
+}
+
+#[test]
+fn witness_properties_reject_invalid_values() {
+    let base = WitnessProperties::new("alice", DISABLED_SIGNING_KEY);
+    assert!(base.clone().maximum_block_size(1024).operation().is_err());
+    assert!(base
+        .clone()
+        .account_creation_fee(Asset::new(3_000, AssetSymbol::Hbd))
+        .operation()
+        .is_err());
+    assert!(WitnessProperties::new("alice", "STM1bad")
+        .operation()
+        .is_err());
+}
@@ -0,0 +1,137 @@
+//! Witness ranking snapshots and the changes between them
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::witness::{WitnessChange, DISABLED_SIGNING_KEY};
+use nectarflower_rs::Client;
+use serde_json::{json, Value};
+
+const KEY: &str = "STM87F7tN56tAUL2C6J9Gzi9HzgNpZdi6M2cLQo7TjDU5v178QsYA";
+
+fn witness(owner: &str, votes: &str, missed: u32, version: &str, key: &str) -> Value {
+    json!({
+        "owner": owner,
+        "votes": votes,
+        "total_missed": missed,
+        "signing_key": key,
+        "running_version": version,
+        "hbd_exchange_rate": { "base": "0.250 HBD", "quote": "1.000 HIVE" },
+        "last_hbd_exchange_update": "2024-01-01T11:00:00",
+    })
+}
+
+fn props(head: u32) -> Value {
+    json!({
+        "head_block_number": head,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T12:00:00",
+        "current_witness": "bob",
+        "current_aslot": head,
+        "last_irreversible_block_num": head,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+fn respond(mock: &MockTransport, head: u32, witnesses: Vec<Value>) {
+    mock.clone()
+        .respond("database_api.get_dynamic_global_properties", props(head))
+        .respond(
+            "database_api.list_witnesses",
+            json!({ "witnesses": witnesses }),
+        );
+}
+
+#[test]
+fn snapshots_rank_by_votes_and_value_them_in_hp() {
+    let mock = MockTransport::new();
+    respond(
+        &mock,
+        10,
+        vec![
+            witness("alice", "4000000000000", 1, "1.27.5", KEY),
+            witness("bob", "2000000000000", 0, "1.27.5", KEY),
+        ],
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+
+    let ranking = client.witness_ranking(2).unwrap();
+    assert_eq!(ranking.head_block_number, 10);
+    let alice = ranking.get("alice").unwrap();
+    assert_eq!(alice.rank, 1);
+    assert_eq!(alice.votes_hp.to_string(), "2000.000 HIVE");
+    assert_eq!(alice.feed_age_secs, 3600);
+    assert_eq!(ranking.get("bob").unwrap().rank, 2);
+    assert!(ranking.get("carol").is_none());
+
+    let (_, request) = mock.requests().pop().unwrap();
+    assert_eq!(
+        request["params"],
+        json!({ "start": [i64::MAX, ""], "limit": 2, "order": "by_vote_name" })
+    );
+}
+
+#[test]
+fn changes_between_snapshots_are_reported() {
+    let mock = MockTransport::new();
+    respond(
+        &mock,
+        10,
+        vec![
+            witness("alice", "4000", 1, "1.27.5", KEY),
+            witness("bob", "3000", 0, "1.27.4", KEY),
+            witness("carol", "2000", 5, "1.27.5", KEY),
+        ],
+    );
+    let client = Client::builder().transport(mock.clone()).build();
+    let before = client.witness_ranking(3).unwrap();
+
+    respond(
+        &mock,
+        20,
+        vec![
+            witness("bob", "5000", 0, "1.27.5", KEY),
+            witness("alice", "4000", 3, "1.27.5", DISABLED_SIGNING_KEY),
+            witness("dave", "2500", 0, "1.27.5", KEY),
+        ],
+    );
+    let after = client.witness_ranking(3).unwrap();
+
+    let owner = |name: &str| name.to_string();
+    assert_eq!(
+        after.changes_since(&before),
+        [
+            WitnessChange::Moved {
+                owner: owner("bob"),
+                from: 2,
+                to: 1
+            },
+            WitnessChange::VersionChanged {
+                owner: owner("bob"),
+                from: owner("1.27.4"),
+                to: owner("1.27.5"),
+            },
+            WitnessChange::Moved {
+                owner: owner("alice"),
+                from: 1,
+                to: 2
+            },
+            WitnessChange::MissedBlocks {
+                owner: owner("alice"),
+                missed: 2
+            },
+            WitnessChange::SigningKeyChanged {
+                owner: owner("alice"),
+                disabled: true
+            },
+            WitnessChange::Entered {
+                owner: owner("dave"),
+                rank: 3
+            },
+            WitnessChange::Left {
+                owner: owner("carol"),
+                rank: 3
+            },
+        ]
+    );
+    assert!(after.changes_since(&after).is_empty());
+}
@@ -0,0 +1,99 @@
+//! Mapping the current round's slots to scheduled witnesses
+
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::Client;
+use serde_json::json;
+use std::time::Duration;
+
+fn props(head: u32, aslot: u64) -> serde_json::Value {
+    json!({
+        "head_block_number": head,
+        "head_block_id": "0000000aaabbccdd000000000000000000000000",
+        "time": "2024-01-01T00:00:00",
+        "current_witness": "w0",
+        "current_aslot": aslot,
+        "last_irreversible_block_num": head,
+        "total_vesting_fund_hive": "1000.000 HIVE",
+        "total_vesting_shares": "2000000.000000 VESTS",
+    })
+}
+
+fn schedule(witnesses: &[&str], scheduled: u32, next_shuffle: u32) -> serde_json::Value {
+    json!({
+        "id": 0,
+        "current_virtual_time": "0",
+        "next_shuffle_block_num": next_shuffle,
+        "current_shuffled_witnesses": witnesses,
+        "num_scheduled_witnesses": scheduled,
+    })
+}
+
+fn client(props: serde_json::Value, schedule: serde_json::Value) -> Client {
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props)
+        .respond("database_api.get_witness_schedule", schedule);
+    Client::builder().transport(mock).build()
+}
+
+#[test]
+fn slots_map_to_witnesses_by_absolute_slot() {
+    let witnesses = ["w0", "w1", "w2", "w3", "w4"];
+    // Missed blocks put the absolute slot ahead of the head block
+    let round = client(props(100, 107), schedule(&witnesses, 5, 104))
+        .current_round()
+        .unwrap();
+    assert_eq!(round.head_block_number, 100);
+    assert_eq!(round.next_shuffle_block_num, 104);
+
+    let mapped: Vec<_> = round
+        .slots
+        .iter()
+        .map(|s| (s.slot, s.aslot, s.witness.as_str()))
+        .collect();
+    assert_eq!(
+        mapped,
+        [
+            (1, 108, "w3"),
+            (2, 109, "w4"),
+            (3, 110, "w0"),
+            (4, 111, "w1")
+        ]
+    );
+    assert_eq!(round.slots[2].eta(), Duration::from_secs(9));
+    assert_eq!(round.next_slot_for("w0").unwrap().slot, 3);
+    assert!(round.next_slot_for("w2").is_none());
+}
+
+#[test]
+fn only_scheduled_witnesses_take_slots() {
+    // Entries past num_scheduled_witnesses are not in the rotation
+    let round = client(props(10, 10), schedule(&["a", "b", "c", "d"], 2, 13))
+        .current_round()
+        .unwrap();
+    let order: Vec<_> = round.slots.iter().map(|s| s.witness.as_str()).collect();
+    assert_eq!(order, ["b", "a", "b"]);
+}
+
+#[test]
+fn finished_and_empty_schedules() {
+    // The head block is the last of the round
+    let round = client(props(104, 104), schedule(&["a"], 1, 104))
+        .current_round()
+        .unwrap();
+    assert!(round.slots.is_empty());
+
+    let err = client(props(10, 10), schedule(&[], 21, 20))
+        .current_round()
+        .unwrap_err();
+    assert_eq!(err, "Witness schedule is empty");
+
+    let mock = MockTransport::new()
+        .respond("database_api.get_dynamic_global_properties", props(10, 10))
+        .respond_error("database_api.get_witness_schedule", -32000, "down");
+    let err = Client::builder()
+        .transport(mock)
+        .build()
+        .current_round()
+        .unwrap_err();
+    assert!(err.starts_with("Error fetching witness schedule:"), "{err}");
+}
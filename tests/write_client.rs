@@ -0,0 +1,79 @@
+use nectarflower_rs::transport::MockTransport;
+use nectarflower_rs::write::{is_broadcast_call, is_broadcast_method};
+use nectarflower_rs::{Client, NectarError, WriteClient, WriteError};
+use serde_json::{json, Value};
+
+#[test]
+fn read_only_client_refuses_broadcast_apis() {
+    // Fails before any request is made, so no node is needed
+    let client = Client::new();
+    let err = client
+        .call::<_, Value>(
+            "network_broadcast_api.broadcast_transaction",
+            json!({ "trx": {} }),
+        )
+        .unwrap_err();
+    assert!(matches!(err, NectarError::RequiresWriteClient(_)), "{err}");
+    assert!(err.to_string().contains("WriteClient"), "{err}");
+    assert!(is_broadcast_method(
+        "condenser_api.broadcast_transaction_synchronous"
+    ));
+    assert!(!is_broadcast_method("condenser_api.get_accounts"));
+}
+
+#[test]
+fn legacy_call_form_cannot_smuggle_broadcasts() {
+    let client = Client::new();
+    let legacy = json!(["condenser_api", "broadcast_transaction", [{}]]);
+    let err = client.call::<_, Value>("call", legacy.clone()).unwrap_err();
+    assert!(matches!(err, NectarError::RequiresWriteClient(_)), "{err}");
+    let err = client.call_batch([("call", legacy)]).unwrap_err();
+    assert!(matches!(err, NectarError::RequiresWriteClient(_)), "{err}");
+
+    assert!(is_broadcast_call(
+        "call",
+        &json!(["network_broadcast_api", "broadcast_block", [{}]])
+    ));
+    assert!(is_broadcast_call(
+        "call",
+        &json!([3, "broadcast_transaction", []])
+    ));
+    assert!(!is_broadcast_call(
+        "call",
+        &json!(["condenser_api", "get_accounts", [["alice"]]])
+    ));
+}
+
+#[test]
+fn failed_calls_keep_their_typed_errors() {
+    let mock = MockTransport::new()
+        .respond_error("database_api.find_accounts", -32000, "node busy")
+        .respond_error(
+            "condenser_api.broadcast_transaction",
+            -32003,
+            "missing required active authority",
+        );
+    let client = Client::builder().transport(mock).build();
+
+    let err = client.find_accounts(&["alice"]).unwrap_err();
+    assert!(matches!(err, NectarError::NoNodesAvailable { .. }), "{err}");
+    assert!(err.attempts().iter().all(|a| a.error.is_rpc()));
+
+    // A node refusing a broadcast made through `call` is a rejection, not a failure
+    let writer = WriteClient::new(client);
+    let trx = json!({
+        "ref_block_num": 1,
+        "ref_block_prefix": 2,
+        "expiration": "2026-01-01T00:00:00",
+        "operations": [],
+        "extensions": [],
+        "signatures": [],
+    });
+    let err = writer
+        .call::<_, Value>("condenser_api.broadcast_transaction", json!([trx]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        WriteError::Rejected("missing required active authority".to_string())
+    );
+}